@@ -0,0 +1,145 @@
+//! Synchronous read/write path vs the io_uring backend on a large file
+//!
+//! `cargo bench --features io-uring --bench io_uring [-- SIZE_MIB]`
+//!
+//! Times the two loops the backend replaces, each on the synchronous path
+//! (`with_io_uring_disabled`) and on the ring: comparing a file with its
+//! copy (`verify_ranges_equal`), and a whole one-byte replacement (backup,
+//! draft copy, verification, rename). The `cold` rows run with
+//! `PageCacheMode::DropBehind` and drop both files from the page cache
+//! before each run, so reads come from the device.
+
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use basic_file_byte_operations::{
+    operation_config::{OperationConfig, PageCacheMode, with_operation_config},
+    range_verification::verify_ranges_equal,
+    replace_single_byte_in_file,
+    uring_backend::with_io_uring_disabled,
+};
+
+/// File size when no size is given on the command line
+const DEFAULT_SIZE_MIB: u64 = 256;
+
+/// Runs per case; the fastest counts
+const RUNS: usize = 3;
+
+fn main() -> io::Result<()> {
+    // `cargo bench` passes `--bench`; the first number is the size
+    let size_mib = std::env::args()
+        .skip(1)
+        .find_map(|argument| argument.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SIZE_MIB);
+    let byte_count = size_mib * 1024 * 1024;
+
+    let original_path = std::env::temp_dir().join("bfbo_bench_io_uring.bin");
+    let copy_path = std::env::temp_dir().join("bfbo_bench_io_uring.copy.bin");
+    let content: Vec<u8> = (0..byte_count).map(|value| (value % 251) as u8).collect();
+    fs::write(&original_path, &content)?;
+    fs::write(&copy_path, &content)?;
+    drop(content);
+
+    println!("{} MiB, best of {} runs", size_mib, RUNS);
+    println!("{:<32} {:>12} {:>12}", "case", "sync MiB/s", "uring MiB/s");
+    for cold in [false, true] {
+        let page_cache_mode = if cold {
+            PageCacheMode::DropBehind
+        } else {
+            PageCacheMode::Normal
+        };
+        let config = OperationConfig::builder()
+            .page_cache_mode(page_cache_mode)
+            .build()?;
+        let cache_label = if cold { "cold" } else { "warm" };
+
+        let verify = || {
+            let mismatches = verify_ranges_equal(
+                &original_path,
+                &[(0, byte_count)],
+                &copy_path,
+                &[(0, byte_count)],
+            )?;
+            assert!(mismatches.is_empty());
+            Ok(())
+        };
+        let replace = || {
+            with_operation_config(&config, || {
+                // The byte's own value, so the file keeps matching its copy
+                replace_single_byte_in_file(
+                    original_path.clone(),
+                    byte_count / 2,
+                    (byte_count / 2 % 251) as u8,
+                )
+            })
+        };
+
+        let paths = [original_path.as_path(), copy_path.as_path()];
+        let prepare = || evict(&paths, cold);
+        report(
+            &format!("verify file against copy ({})", cache_label),
+            size_mib,
+            time_best(prepare, || with_io_uring_disabled(verify))?,
+            time_best(prepare, verify)?,
+        );
+        report(
+            &format!("replace one byte ({})", cache_label),
+            size_mib,
+            time_best(prepare, || with_io_uring_disabled(replace))?,
+            time_best(prepare, replace)?,
+        );
+    }
+
+    let _ = fs::remove_file(&original_path);
+    let _ = fs::remove_file(&copy_path);
+    Ok(())
+}
+
+/// Drops the files from the page cache: a drop-behind replacement of a
+/// byte with itself leaves nothing of them cached.
+fn evict(paths: &[&Path], cold: bool) -> io::Result<()> {
+    if !cold {
+        return Ok(());
+    }
+    let config = OperationConfig::builder()
+        .page_cache_mode(PageCacheMode::DropBehind)
+        .build()?;
+    with_operation_config(&config, || {
+        for path in paths {
+            let first_byte = fs::File::open(path).and_then(|mut file| {
+                let mut byte = [0u8; 1];
+                io::Read::read_exact(&mut file, &mut byte).map(|_| byte[0])
+            })?;
+            replace_single_byte_in_file(path.to_path_buf(), 0, first_byte)?;
+        }
+        Ok(())
+    })
+}
+
+/// Fastest of `RUNS` runs of `run`, each after an untimed `prepare`.
+fn time_best(
+    prepare: impl Fn() -> io::Result<()>,
+    run: impl Fn() -> io::Result<()>,
+) -> io::Result<Duration> {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        prepare()?;
+        let started = Instant::now();
+        run()?;
+        best = best.min(started.elapsed());
+    }
+    Ok(best)
+}
+
+fn report(case: &str, size_mib: u64, synchronous: Duration, uring: Duration) {
+    let rate = |duration: Duration| size_mib as f64 / duration.as_secs_f64();
+    println!(
+        "{:<32} {:>12.0} {:>12.0}",
+        case,
+        rate(synchronous),
+        rate(uring)
+    );
+}
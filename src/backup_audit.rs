@@ -0,0 +1,242 @@
+//! Backup store auditing
+//!
+//! Backups kept for rollback are only useful if they are still intact when
+//! they are needed. A backup store is a directory of retained backup copies
+//! plus a manifest, `MANIFEST.sha256`, in the format written by
+//! `sha256sum` (so existing stores can be adopted with
+//! `sha256sum * > MANIFEST.sha256`):
+//! ```text
+//! <64 hex digits>  <path relative to the store>
+//! <64 hex digits> *<path>            (binary-mode marker, also accepted)
+//! ```
+//! Blank lines and lines starting with `#` are ignored.
+//!
+//! `audit_backup_store` re-hashes every listed backup and reports each one
+//! as intact, corrupted (bit-rot: digest differs), or missing. It only
+//! reads; run it on a schedule (cron, systemd timer) via
+//! `bfbo audit-backups <STORE>`.
+
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::hash::sha256::{digest_to_hex, sha256_of_file};
+use crate::reporter::report_detail;
+
+/// Manifest file name inside a backup store
+pub const BACKUP_MANIFEST_FILE_NAME: &str = "MANIFEST.sha256";
+
+/// Outcome for one manifest entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupAuditStatus {
+    /// Digest matches the recorded digest
+    Intact,
+    /// Digest differs (hex of the digest actually found)
+    Corrupted { actual_sha256_hex: String },
+    /// The listed file does not exist
+    Missing,
+}
+
+impl BackupAuditStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackupAuditStatus::Intact => "OK",
+            BackupAuditStatus::Corrupted { .. } => "CORRUPTED",
+            BackupAuditStatus::Missing => "MISSING",
+        }
+    }
+}
+
+/// One audited manifest entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupAuditEntry {
+    pub relative_path: PathBuf,
+    pub recorded_sha256_hex: String,
+    pub status: BackupAuditStatus,
+}
+
+/// Result of auditing a whole store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupAuditReport {
+    pub entries: Vec<BackupAuditEntry>,
+}
+
+impl BackupAuditReport {
+    /// True if every listed backup is present and intact.
+    pub fn is_clean(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|entry| entry.status == BackupAuditStatus::Intact)
+    }
+
+    /// Count of entries with the given status name (`OK`, `CORRUPTED`, `MISSING`).
+    pub fn count_with_status(&self, status_name: &str) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status.name() == status_name)
+            .count()
+    }
+}
+
+/// Re-hashes every backup listed in the store's manifest.
+///
+/// # Parameters
+/// - `store_directory`: Directory containing `MANIFEST.sha256`
+///
+/// # Returns
+/// - `Ok(BackupAuditReport)`: one entry per manifest line, in manifest order
+///   (corruption and missing files are reported, not returned as errors)
+/// - `Err(io::Error)` if the manifest is missing or malformed, lists a path
+///   outside the store, or a present backup cannot be read
+pub fn audit_backup_store(store_directory: &Path) -> io::Result<BackupAuditReport> {
+    let manifest_path = store_directory.join(BACKUP_MANIFEST_FILE_NAME);
+    let manifest_text = std::fs::read_to_string(&manifest_path)?;
+    let manifest_entries = parse_backup_manifest(&manifest_text)?;
+
+    let mut entries = Vec::with_capacity(manifest_entries.len());
+    for (recorded_sha256_hex, relative_path) in manifest_entries {
+        let backup_path = store_directory.join(&relative_path);
+        let status = if !backup_path.is_file() {
+            BackupAuditStatus::Missing
+        } else {
+            let actual_sha256_hex = digest_to_hex(&sha256_of_file(&backup_path)?);
+            if actual_sha256_hex == recorded_sha256_hex {
+                BackupAuditStatus::Intact
+            } else {
+                BackupAuditStatus::Corrupted { actual_sha256_hex }
+            }
+        };
+
+        report_detail!("   {} {}", status.name(), relative_path.display());
+
+        entries.push(BackupAuditEntry {
+            relative_path,
+            recorded_sha256_hex,
+            status,
+        });
+    }
+
+    Ok(BackupAuditReport { entries })
+}
+
+/// Digest recorded for `backup_path` in the manifest of the directory it
+/// is in, if that directory is a backup store listing it.
+pub(crate) fn recorded_backup_sha256_hex(backup_path: &Path) -> io::Result<Option<String>> {
+    let store_directory = backup_path.parent().unwrap_or(Path::new(""));
+    let manifest_path = store_directory.join(BACKUP_MANIFEST_FILE_NAME);
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+    let manifest_text = std::fs::read_to_string(&manifest_path)?;
+    Ok(parse_backup_manifest(&manifest_text)?
+        .into_iter()
+        .find(|(_, relative_path)| store_directory.join(relative_path) == backup_path)
+        .map(|(recorded_sha256_hex, _)| recorded_sha256_hex))
+}
+
+/// Parses `sha256sum`-format lines into (lowercase hex digest, relative path).
+fn parse_backup_manifest(manifest_text: &str) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut manifest_entries = Vec::new();
+
+    for (line_index, line) in manifest_text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let malformed = |reason: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} line {}: {}",
+                    BACKUP_MANIFEST_FILE_NAME, line_number, reason
+                ),
+            )
+        };
+
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((digest_text, path_text)) = line.split_once(' ') else {
+            return Err(malformed("expected `<digest>  <path>`"));
+        };
+        if digest_text.len() != 64 || !digest_text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(malformed("digest must be 64 hex digits"));
+        }
+        // Second separator character: ' ' (text mode) or '*' (binary mode)
+        let path_text = path_text
+            .strip_prefix(' ')
+            .or_else(|| path_text.strip_prefix('*'))
+            .ok_or_else(|| malformed("expected two-character separator"))?;
+
+        let relative_path = PathBuf::from(path_text);
+        let stays_inside_store = !path_text.is_empty()
+            && relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !stays_inside_store {
+            return Err(malformed("path must be relative and stay inside the store"));
+        }
+
+        manifest_entries.push((digest_text.to_ascii_lowercase(), relative_path));
+    }
+
+    Ok(manifest_entries)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256::sha256_of_bytes;
+
+    #[test]
+    fn test_audit_backup_store_reports_rot_and_missing() {
+        let store_directory = std::env::temp_dir().join("test_audit_backup_store");
+        let _ = std::fs::remove_dir_all(&store_directory);
+        std::fs::create_dir_all(store_directory.join("sub")).expect("create store");
+
+        std::fs::write(store_directory.join("a.bin.backup"), b"alpha").expect("write a");
+        std::fs::write(store_directory.join("sub/b.bin.backup"), b"bravo").expect("write b");
+        let manifest_text = format!(
+            "# retained backups\n{}  a.bin.backup\n{} *sub/b.bin.backup\n{}  gone.backup\n",
+            digest_to_hex(&sha256_of_bytes(b"alpha")),
+            digest_to_hex(&sha256_of_bytes(b"bravo")).to_uppercase(),
+            digest_to_hex(&sha256_of_bytes(b"gone")),
+        );
+        std::fs::write(
+            store_directory.join(BACKUP_MANIFEST_FILE_NAME),
+            manifest_text,
+        )
+        .expect("write manifest");
+
+        let report = audit_backup_store(&store_directory).expect("audit store");
+        assert!(!report.is_clean());
+        assert_eq!(report.entries[0].status, BackupAuditStatus::Intact);
+        assert_eq!(report.entries[1].status, BackupAuditStatus::Intact);
+        assert_eq!(report.entries[2].status, BackupAuditStatus::Missing);
+
+        // Bit-rot: flip one bit of a stored backup
+        std::fs::write(store_directory.join("a.bin.backup"), b"alphb").expect("rot a");
+        let report = audit_backup_store(&store_directory).expect("audit store");
+        assert_eq!(report.count_with_status("CORRUPTED"), 1);
+        assert_eq!(report.count_with_status("MISSING"), 1);
+
+        let _ = std::fs::remove_dir_all(&store_directory);
+    }
+
+    #[test]
+    fn test_parse_backup_manifest_rejects_escaping_paths() {
+        let digest = "0".repeat(64);
+        assert!(parse_backup_manifest(&format!("{}  ../etc/passwd", digest)).is_err());
+        assert!(parse_backup_manifest(&format!("{}  /etc/passwd", digest)).is_err());
+        assert!(parse_backup_manifest("abc  file").is_err());
+        assert_eq!(
+            parse_backup_manifest(&format!("{}  ./ok.backup", digest))
+                .expect("valid line")
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Per-backup manifests
+//!
+//! A backup that outlives its operation (`keep_backup`, rotated backups)
+//! gets a small manifest next to it, `<backup>.manifest`, saying what it is
+//! a copy of:
+//!
+//! ```text
+//! bfbo-backup-manifest 1
+//! original: /data/f.bin
+//! size: 4096
+//! sha256: 9f86d0...
+//! operation: Byte Removal
+//! created: 2024-01-31T12:00:00Z
+//! verification: xor-sum
+//! ```
+//!
+//! The size and SHA-256 are those of the backup as written, i.e. of the
+//! original before the operation. If the operation verified with BLAKE3
+//! (`blake3` feature), the digest is BLAKE3 instead, on a `blake3:` line;
+//! either way it is collision-resistant. `restore_from_backup` and the orphan
+//! scanner check a backup against its manifest before they use it, so a
+//! backup that rotted or was swapped for another file's is refused instead
+//! of restored. Backups without a manifest (removed on success, or made by
+//! an older version) are used as before.
+//!
+//! `verification` names the checksum the operation's verification kept
+//! (`verification_hash`). Manifests written before it was recorded lack
+//! the line.
+//!
+//! This is separate from a backup store's `MANIFEST.sha256` (see
+//! `backup_audit.rs`), which lists many backups in `sha256sum` format.
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::custody_report::{current_unix_seconds, format_rfc3339_utc};
+use crate::error::ByteOpError;
+use crate::hash::{HashAlgorithm, HashValue, hash_file};
+use crate::operation_config::OperationConfig;
+use crate::reporter::report_detail;
+
+/// Suffix appended to a backup's path to name its manifest
+pub const BACKUP_MANIFEST_SUFFIX: &str = ".manifest";
+
+/// First line of every manifest; the number is the format version
+const BACKUP_MANIFEST_HEADER: &str = "bfbo-backup-manifest 1";
+
+/// What a backup is a copy of, as recorded when it was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupManifest {
+    /// Absolute path of the file the backup was taken from
+    pub original_file_path: PathBuf,
+    pub size: u64,
+    /// Digest of the backup: SHA-256, or the verification hash if that is
+    /// collision-resistant too
+    pub content_hash: HashValue,
+    /// Name of the operation that made the backup (e.g. "Byte Removal")
+    pub operation_name: String,
+    /// RFC 3339 UTC time the backup was made
+    pub created: String,
+    /// Checksum the operation's verification used, `None` in manifests
+    /// that predate it
+    pub verification_hash: Option<HashAlgorithm>,
+}
+
+/// Path of the manifest that belongs to `backup_file_path`.
+pub fn backup_manifest_path(backup_file_path: &Path) -> PathBuf {
+    let mut manifest_path = backup_file_path.to_path_buf().into_os_string();
+    manifest_path.push(BACKUP_MANIFEST_SUFFIX);
+    PathBuf::from(manifest_path)
+}
+
+/// Writes the manifest for a backup just copied from `original_file_path`,
+/// if the config keeps backups; otherwise removes a stale manifest a
+/// previous backup at that path may have left.
+///
+/// Hashes the backup (one read pass) only when a manifest is written.
+pub(crate) fn record_backup_manifest(
+    backup_file_path: &Path,
+    original_file_path: &Path,
+    operation_name: &str,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    let manifest_path = backup_manifest_path(backup_file_path);
+    if !config.retains_backup() {
+        return remove_backup_manifest(backup_file_path);
+    }
+
+    let manifest = BackupManifest {
+        original_file_path: std::path::absolute(original_file_path)?,
+        size: fs::metadata(backup_file_path)?.len(),
+        content_hash: hash_file(
+            backup_file_path,
+            manifest_hash_for(config.verification_hash()),
+        )?,
+        operation_name: operation_name.to_string(),
+        created: format_rfc3339_utc(current_unix_seconds()),
+        verification_hash: Some(config.verification_hash()),
+    };
+    let original_path_text = manifest.original_file_path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Cannot record a non-UTF-8 path in a backup manifest: {}",
+                original_file_path.display()
+            ),
+        )
+    })?;
+    if original_path_text.contains(['\n', '\r']) || operation_name.contains(['\n', '\r']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot record a line break in a backup manifest",
+        ));
+    }
+    let manifest_text = format!(
+        "{}\noriginal: {}\nsize: {}\n{}: {}\noperation: {}\ncreated: {}\nverification: {}\n",
+        BACKUP_MANIFEST_HEADER,
+        original_path_text,
+        manifest.size,
+        manifest.content_hash.algorithm().name(),
+        manifest.content_hash.to_hex(),
+        manifest.operation_name,
+        manifest.created,
+        config.verification_hash().name()
+    );
+
+    let mut manifest_file = File::create(&manifest_path)?;
+    manifest_file.write_all(manifest_text.as_bytes())?;
+    manifest_file.sync_all()?;
+    report_detail!("Backup manifest: {}", manifest_path.display());
+    Ok(())
+}
+
+/// Algorithm for a manifest's digest: the verification hash if it is
+/// collision-resistant, SHA-256 otherwise.
+fn manifest_hash_for(verification_hash: HashAlgorithm) -> HashAlgorithm {
+    if verification_hash.is_collision_resistant() {
+        verification_hash
+    } else {
+        HashAlgorithm::Sha256
+    }
+}
+
+/// Removes the manifest of `backup_file_path`, if there is one.
+pub(crate) fn remove_backup_manifest(backup_file_path: &Path) -> io::Result<()> {
+    match fs::remove_file(backup_manifest_path(backup_file_path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the manifest of `backup_file_path`.
+///
+/// # Returns
+/// - `Ok(None)` if the backup has no manifest
+/// - `Ok(Some(BackupManifest))`
+/// - `Err(io::Error)` with `ErrorKind::InvalidData` if the manifest is not
+///   one this version wrote
+pub fn read_backup_manifest(backup_file_path: &Path) -> io::Result<Option<BackupManifest>> {
+    let manifest_path = backup_manifest_path(backup_file_path);
+    let manifest_text = match fs::read_to_string(&manifest_path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    parse_backup_manifest_text(&manifest_text)
+        .map(Some)
+        .map_err(|reason| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", manifest_path.display(), reason),
+            )
+        })
+}
+
+/// Checks `backup_file_path` against its manifest before it is restored
+/// over `target_path`.
+///
+/// # Returns
+/// - `Ok(None)` if the backup has no manifest (nothing to check)
+/// - `Ok(Some(BackupManifest))` if the backup matches it
+/// - `Err(io::Error)` if the manifest names another original
+///   (`ErrorKind::InvalidData`), or the backup's size or SHA-256 differs
+///   from the recorded one (`ByteOpError::BackupChecksumMismatch`)
+pub fn verify_backup_manifest(
+    backup_file_path: &Path,
+    target_path: &Path,
+) -> io::Result<Option<BackupManifest>> {
+    let Some(manifest) = read_backup_manifest(backup_file_path)? else {
+        return Ok(None);
+    };
+    if manifest.original_file_path != std::path::absolute(target_path)? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Backup {} is a copy of {}, not {}",
+                backup_file_path.display(),
+                manifest.original_file_path.display(),
+                target_path.display()
+            ),
+        ));
+    }
+
+    let actual_hash = hash_file(backup_file_path, manifest.content_hash.algorithm())?;
+    if actual_hash != manifest.content_hash
+        || fs::metadata(backup_file_path)?.len() != manifest.size
+    {
+        return Err(ByteOpError::BackupChecksumMismatch {
+            path: backup_file_path.to_path_buf(),
+            recorded_hash: manifest.content_hash.to_string(),
+            actual_hash: actual_hash.to_string(),
+        }
+        .into());
+    }
+    Ok(Some(manifest))
+}
+
+fn parse_backup_manifest_text(manifest_text: &str) -> Result<BackupManifest, String> {
+    let mut lines = manifest_text.lines();
+    if lines.next() != Some(BACKUP_MANIFEST_HEADER) {
+        return Err("not a bfbo backup manifest (or an unsupported version)".to_string());
+    }
+
+    let mut original_file_path = None;
+    let mut size = None;
+    let mut content_hash = None;
+    let mut operation_name = None;
+    let mut created = None;
+    let mut verification_hash = None;
+    for line in lines {
+        let (key, value) = line
+            .split_once(": ")
+            .ok_or_else(|| format!("malformed line: {}", line))?;
+        match key {
+            "original" => original_file_path = Some(PathBuf::from(value)),
+            "size" => {
+                size = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("bad size: {}", value))?,
+                )
+            }
+            "sha256" | "blake3" => {
+                let algorithm = HashAlgorithm::from_name(key)
+                    .ok_or_else(|| format!("{} digest needs the {} feature", key, key))?;
+                content_hash = Some(
+                    HashValue::from_hex(algorithm, value)
+                        .ok_or_else(|| format!("bad {} digest: {}", key, value))?,
+                )
+            }
+            "operation" => operation_name = Some(value.to_string()),
+            "created" => created = Some(value.to_string()),
+            "verification" => {
+                verification_hash = Some(
+                    HashAlgorithm::from_name(value)
+                        .ok_or_else(|| format!("unknown checksum: {}", value))?,
+                )
+            }
+            _ => return Err(format!("unknown field: {}", key)),
+        }
+    }
+
+    let missing = |field: &str| format!("missing field: {}", field);
+    let content_hash = content_hash.ok_or_else(|| missing("sha256"))?;
+    Ok(BackupManifest {
+        original_file_path: original_file_path.ok_or_else(|| missing("original"))?,
+        size: size.ok_or_else(|| missing("size"))?,
+        content_hash,
+        operation_name: operation_name.ok_or_else(|| missing("operation"))?,
+        created: created.ok_or_else(|| missing("created"))?,
+        verification_hash,
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256::sha256_of_bytes;
+
+    #[test]
+    fn test_backup_manifest_round_trip_and_checks() {
+        let directory = std::env::temp_dir().join("test_backup_manifest");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        let backup_path = directory.join("data.bin.backup");
+        fs::write(&backup_path, b"before").expect("write backup");
+        let keep_config = OperationConfig::builder()
+            .keep_backup(true)
+            .build()
+            .expect("valid config");
+
+        record_backup_manifest(&backup_path, &target_path, "Byte Removal", &keep_config)
+            .expect("record manifest");
+        let manifest = verify_backup_manifest(&backup_path, &target_path)
+            .expect("verify")
+            .expect("manifest present");
+        assert_eq!(manifest.size, 6);
+        assert_eq!(manifest.content_hash.as_bytes(), sha256_of_bytes(b"before"));
+        assert_eq!(manifest.operation_name, "Byte Removal");
+        assert_eq!(
+            manifest.original_file_path,
+            std::path::absolute(&target_path).expect("absolute")
+        );
+
+        // A copy of another file is refused
+        let error = verify_backup_manifest(&backup_path, &directory.join("other.bin"))
+            .expect_err("another original");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A backup that is not kept gets no manifest, and loses a stale one
+        record_backup_manifest(
+            &backup_path,
+            &target_path,
+            "Byte Removal",
+            &OperationConfig::default(),
+        )
+        .expect("remove stale manifest");
+        assert!(!backup_manifest_path(&backup_path).exists());
+        assert_eq!(
+            verify_backup_manifest(&backup_path, &target_path).expect("verify"),
+            None
+        );
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_backup_manifest_records_blake3_when_verifying_with_it() {
+        let directory = std::env::temp_dir().join("test_backup_manifest_blake3");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        let backup_path = directory.join("data.bin.backup");
+        fs::write(&backup_path, b"before").expect("write backup");
+        let blake3_config = OperationConfig::builder()
+            .keep_backup(true)
+            .verification_hash(HashAlgorithm::Blake3)
+            .build()
+            .expect("valid config");
+
+        record_backup_manifest(&backup_path, &target_path, "Byte Removal", &blake3_config)
+            .expect("record manifest");
+        let manifest_text =
+            fs::read_to_string(backup_manifest_path(&backup_path)).expect("read manifest");
+        assert!(manifest_text.contains("\nblake3: "), "{}", manifest_text);
+        let manifest = verify_backup_manifest(&backup_path, &target_path)
+            .expect("verify")
+            .expect("manifest present");
+        assert_eq!(
+            manifest.content_hash.as_bytes(),
+            blake3::hash(b"before").as_bytes()
+        );
+        assert_eq!(manifest.verification_hash, Some(HashAlgorithm::Blake3));
+
+        // A changed backup no longer matches its BLAKE3 digest
+        fs::write(&backup_path, b"BEFORE").expect("rewrite backup");
+        let error = verify_backup_manifest(&backup_path, &target_path).expect_err("changed");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::BackupChecksumMismatch { .. })
+        ));
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
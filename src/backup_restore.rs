@@ -0,0 +1,357 @@
+//! Restoring a file from its backup
+//!
+//! A kept or leftover backup is the file as it was before an operation.
+//! `restore_from_backup` puts it back without hand-copying:
+//! 1. Locate the backup the active `OperationConfig` names (suffix and
+//!    directory, as for a new operation), or the newest numbered backup
+//!    (`<backup>.1`) when backups are rotated, or the newest timestamped
+//!    backup with `BackupNaming::Timestamped`
+//! 2. Check it against its own manifest (`<backup>.manifest`, see
+//!    `backup_manifest.rs`: same original, size, and SHA-256), and hash it
+//!    and compare with the digest recorded for it in a backup store
+//!    manifest (`MANIFEST.sha256` next to it, see `backup_audit.rs`), if
+//!    there are any
+//! 3. Copy it to the target's `.draft` and check the copy has the same
+//!    SHA-256
+//! 4. Atomically rename the draft over the target
+//!
+//! The backup itself is left in place, so it remains a recovery point. A
+//! target with a pending journal is refused: the interrupted operation has
+//! to be recovered (`journal::recover`) first.
+
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::backup_audit::recorded_backup_sha256_hex;
+use crate::backup_manifest::verify_backup_manifest;
+use crate::draft_workflow::{
+    DraftArtifactPaths, build_artifact_paths, check_artifact_overwrite,
+    latest_timestamped_backup_path, replace_with_draft, rotated_backup_path,
+};
+use crate::error::ByteOpError;
+use crate::hash::HashAlgorithm;
+use crate::hash::sha256::{digest_to_hex, sha256_of_file};
+use crate::operation_config::{
+    BackupNaming, OperationConfig, active_operation_config, with_operation_config,
+};
+use crate::reflink::clone_or_copy;
+use crate::reporter::{ReportEvent, report, report_detail, report_problem};
+
+/// What `restore_from_backup` restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupRestore {
+    pub backup_file_path: PathBuf,
+    /// SHA-256 of the backup, and now of the target
+    pub sha256: [u8; 32],
+    /// Whether a manifest (the backup's own or its store's) recorded a
+    /// digest for the backup (it matched)
+    pub checked_against_manifest: bool,
+}
+
+/// Atomically replaces `original_file_path` with a verified copy of its
+/// backup. The target may be missing (e.g. after a crash mid-replace).
+///
+/// # Returns
+/// - `Ok(BackupRestore)`
+/// - `Err(io::Error)`, with the target unchanged, if:
+///   - there is no backup (`ErrorKind::NotFound`)
+///   - a manifest records a different size or digest for the backup
+///     (`ByteOpError::BackupChecksumMismatch`), or the backup's manifest
+///     names another original (`ErrorKind::InvalidData`)
+///   - an interrupted operation left a journal (`ByteOpError::PendingJournal`)
+///     or the overwrite policy forbids replacing a leftover draft
+///   - the copy does not match the backup, or any step fails
+pub fn restore_from_backup(original_file_path: PathBuf) -> io::Result<BackupRestore> {
+    report(&ReportEvent::OperationStarted {
+        operation_name: "Backup Restore",
+        target_path: &original_file_path,
+    });
+    if original_file_path.exists() && !original_file_path.is_file() {
+        let error = ByteOpError::not_a_file(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    let config = active_operation_config();
+    let artifact_paths = build_artifact_paths(&original_file_path)?;
+    check_artifact_overwrite(&original_file_path, &artifact_paths, false)?;
+    let draft_file_path = artifact_paths.draft_file_path.clone();
+    let Some(backup_file_path) = backup_to_restore(&original_file_path, artifact_paths)? else {
+        let message = format!("No backup to restore: {}", original_file_path.display());
+        report_problem!("ERROR: {}", message);
+        return Err(io::Error::new(io::ErrorKind::NotFound, message));
+    };
+    report_detail!("Backup path: {}", backup_file_path.display());
+
+    // =========================================
+    // Backup Verification Phase
+    // =========================================
+    let backup_manifest = verify_backup_manifest(&backup_file_path, &original_file_path)
+        .inspect_err(|e| report_problem!("ERROR: {}", e))?;
+    if backup_manifest.is_some() {
+        report_detail!("Backup matches its backup manifest");
+    }
+    let backup_sha256 = sha256_of_file(&backup_file_path)?;
+    let recorded_sha256_hex = recorded_backup_sha256_hex(&backup_file_path)?;
+    if let Some(recorded_sha256_hex) = &recorded_sha256_hex {
+        let actual_sha256_hex = digest_to_hex(&backup_sha256);
+        if *recorded_sha256_hex != actual_sha256_hex {
+            let error = ByteOpError::BackupChecksumMismatch {
+                path: backup_file_path,
+                recorded_hash: format!("{}:{}", HashAlgorithm::Sha256.name(), recorded_sha256_hex),
+                actual_hash: format!("{}:{}", HashAlgorithm::Sha256.name(), actual_sha256_hex),
+            };
+            report_problem!("ERROR: {}", error);
+            return Err(error.into());
+        }
+        report_detail!("Backup matches its manifest entry");
+    }
+
+    // =========================================
+    // Draft Copy Phase
+    // =========================================
+    let copy_result = copy_to_draft(&backup_file_path, &draft_file_path, &config)
+        .and_then(|()| sha256_of_file(&draft_file_path));
+    match copy_result {
+        Ok(draft_sha256) if draft_sha256 == backup_sha256 => {}
+        Ok(_) => {
+            let _ = fs::remove_file(&draft_file_path);
+            let message = "Copy of the backup does not match the backup".to_string();
+            report_problem!("ERROR: {}", message);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        Err(e) => {
+            report_problem!("ERROR: Copying backup failed: {}", e);
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(e);
+        }
+    }
+
+    // =========================================
+    // Atomic Replacement Phase
+    // =========================================
+    if let Err(source) = replace_with_draft(&draft_file_path, &original_file_path) {
+        let error = ByteOpError::AtomicReplaceFailed { source };
+        report_problem!("{}", error);
+        return Err(error.into());
+    }
+    config
+        .durability_mode()
+        .finish_replace(&original_file_path)?;
+
+    report_detail!(
+        "Restored {} bytes",
+        fs::metadata(&original_file_path)?.len()
+    );
+    report(&ReportEvent::OperationFinished {
+        operation_name: "Backup Restore",
+        target_path: &original_file_path,
+    });
+    Ok(BackupRestore {
+        backup_file_path,
+        sha256: backup_sha256,
+        checked_against_manifest: backup_manifest.is_some() || recorded_sha256_hex.is_some(),
+    })
+}
+
+/// Like `restore_from_backup`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn restore_from_backup_with_config(
+    original_file_path: PathBuf,
+    config: &OperationConfig,
+) -> io::Result<BackupRestore> {
+    with_operation_config(config, || restore_from_backup(original_file_path))
+}
+
+/// The backup `restore_from_backup` would restore under the active config,
+/// if it exists.
+pub(crate) fn backup_to_restore(
+    original_file_path: &Path,
+    artifact_paths: DraftArtifactPaths,
+) -> io::Result<Option<PathBuf>> {
+    let config = active_operation_config();
+    if config.backup_naming() == BackupNaming::Timestamped {
+        return latest_timestamped_backup_path(original_file_path);
+    }
+    let mut backup_file_path = artifact_paths.backup_file_path;
+    if !backup_file_path.is_file() && config.rotated_backup_count().is_some() {
+        backup_file_path = rotated_backup_path(&backup_file_path, 1);
+    }
+    Ok(Some(backup_file_path).filter(|path| path.is_file()))
+}
+
+/// Copies the backup to the draft path, synced if the config asks for it.
+fn copy_to_draft(
+    backup_file_path: &Path,
+    draft_file_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    clone_or_copy(backup_file_path, draft_file_path)?;
+    if config.durability_mode().syncs_draft() {
+        File::open(draft_file_path)?.sync_all()?;
+    }
+    Ok(())
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup_audit::BACKUP_MANIFEST_FILE_NAME;
+    use crate::hash::sha256::sha256_of_bytes;
+    use crate::operation_config::with_operation_config;
+    use crate::replace_single_byte_in_file_with_config;
+
+    #[test]
+    fn test_restore_from_kept_backup() {
+        let test_file = std::env::temp_dir().join("test_restore_backup.bin");
+        std::fs::write(&test_file, b"ABCD").expect("create test file");
+        let config = OperationConfig::builder()
+            .keep_backup(true)
+            .build()
+            .expect("valid config");
+        replace_single_byte_in_file_with_config(test_file.clone(), 0, b'X', &config)
+            .expect("replace");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"XBCD");
+
+        let restored = restore_from_backup(test_file.clone()).expect("restore");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ABCD");
+        assert_eq!(restored.sha256, sha256_of_bytes(b"ABCD"));
+        // A kept backup has its own manifest
+        assert!(restored.checked_against_manifest);
+        // The backup stays a recovery point
+        assert!(restored.backup_file_path.exists());
+
+        // Target missing entirely
+        std::fs::remove_file(&test_file).expect("remove target");
+        restore_from_backup(test_file.clone()).expect("restore missing target");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ABCD");
+
+        // A backup that no longer matches its manifest is refused
+        std::fs::write(&restored.backup_file_path, b"ABCE").expect("corrupt backup");
+        let error = restore_from_backup(test_file.clone()).expect_err("corrupt backup");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::BackupChecksumMismatch { .. })
+        ));
+
+        let _ = std::fs::remove_file(&restored.backup_file_path);
+        let _ = std::fs::remove_file(crate::backup_manifest::backup_manifest_path(
+            &restored.backup_file_path,
+        ));
+        let error = restore_from_backup(test_file.clone()).expect_err("no backup");
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_restore_checks_manifest_digest() {
+        let store_directory = std::env::temp_dir().join("test_restore_backup_store");
+        let _ = std::fs::remove_dir_all(&store_directory);
+        std::fs::create_dir_all(&store_directory).expect("create store");
+        let test_file = std::env::temp_dir().join("test_restore_manifest.bin");
+        std::fs::write(&test_file, b"edited").expect("create test file");
+        let backup_path = store_directory.join("test_restore_manifest.bin.backup");
+        std::fs::write(&backup_path, b"original").expect("write backup");
+        let config = OperationConfig::builder()
+            .backup_directory(&store_directory)
+            .build()
+            .expect("valid config");
+
+        // Recorded digest does not match: bit-rot, refuse
+        std::fs::write(
+            store_directory.join(BACKUP_MANIFEST_FILE_NAME),
+            format!(
+                "{}  test_restore_manifest.bin.backup\n",
+                digest_to_hex(&sha256_of_bytes(b"0riginal"))
+            ),
+        )
+        .expect("write manifest");
+        let error = with_operation_config(&config, || restore_from_backup(test_file.clone()))
+            .expect_err("digest mismatch");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::BackupChecksumMismatch { .. })
+        ));
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"edited");
+
+        std::fs::write(
+            store_directory.join(BACKUP_MANIFEST_FILE_NAME),
+            format!(
+                "{}  ./test_restore_manifest.bin.backup\n",
+                digest_to_hex(&sha256_of_bytes(b"original"))
+            ),
+        )
+        .expect("write manifest");
+        let restored = with_operation_config(&config, || restore_from_backup(test_file.clone()))
+            .expect("restore");
+        assert!(restored.checked_against_manifest);
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"original");
+
+        let _ = std::fs::remove_dir_all(&store_directory);
+        let _ = std::fs::remove_file(&test_file);
+    }
+    #[test]
+    fn test_timestamped_backups_kept_per_operation() {
+        let directory = std::env::temp_dir().join("test_timestamped_backups");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("create directory");
+        let test_file = directory.join("data.bin");
+        std::fs::write(&test_file, b"ABCD").expect("create test file");
+        let config = OperationConfig::builder()
+            .backup_naming(BackupNaming::Timestamped)
+            .keep_backup(true)
+            .build()
+            .expect("valid config");
+
+        replace_single_byte_in_file_with_config(test_file.clone(), 0, b'X', &config)
+            .expect("first replace");
+        replace_single_byte_in_file_with_config(test_file.clone(), 1, b'Y', &config)
+            .expect("second replace");
+        let mut backup_names: Vec<String> = std::fs::read_dir(&directory)
+            .expect("list directory")
+            .map(|entry| {
+                entry
+                    .expect("entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .filter(|name| name != "data.bin" && !name.ends_with(".manifest"))
+            .collect();
+        backup_names.sort();
+        assert_eq!(backup_names.len(), 2);
+        for backup_name in &backup_names {
+            let stamp = backup_name
+                .strip_prefix("data.bin.")
+                .and_then(|rest| rest.strip_suffix(".backup"))
+                .expect("timestamped name");
+            assert_eq!(stamp.as_bytes()[8], b'T');
+        }
+
+        // The newest backup is the state before the second edit
+        let restored = with_operation_config(&config, || restore_from_backup(test_file.clone()))
+            .expect("restore");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"XBCD");
+        assert_eq!(
+            std::fs::read(&restored.backup_file_path).expect("read backup"),
+            b"XBCD"
+        );
+
+        assert!(
+            OperationConfig::builder()
+                .backup_naming(BackupNaming::Timestamped)
+                .rotate_backups(2)
+                .build()
+                .is_err()
+        );
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,296 @@
+//! Batch mode: one patch plan applied to many files, with command hooks
+//!
+//! Each file is processed independently. Around each file's operation the
+//! caller may configure shell commands:
+//! - a pre-hook (e.g. stop a service, take an LVM snapshot); if it fails,
+//!   that file's operation is aborted and the file is not touched
+//! - a post-hook (e.g. restart the service, run a validation suite); it runs
+//!   whenever the pre-hook succeeded, whether or not the operation did, so a
+//!   stopped service is always restarted
+//!
+//! Hooks run through the platform shell (`sh -c` / `cmd /C`) and receive the
+//! target in `BFBO_TARGET_FILE`; the post-hook also gets
+//! `BFBO_OPERATION_STATUS` (`applied` or `failed`). Every hook's result is
+//! recorded in the per-file report.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::operation_config::{ActiveConfigScope, OperationConfig};
+use crate::patch_plan::{PlanEntry, PlanEntryOutcome, apply_patch_plan};
+use crate::reporter::report_problem;
+
+/// Environment variable holding the target file path for hooks
+pub const HOOK_TARGET_FILE_VARIABLE: &str = "BFBO_TARGET_FILE";
+
+/// Environment variable holding the operation status for post-hooks
+pub const HOOK_OPERATION_STATUS_VARIABLE: &str = "BFBO_OPERATION_STATUS";
+
+/// Commands run before and after each file's operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationHooks {
+    pub pre_command: Option<String>,
+    pub post_command: Option<String>,
+}
+
+/// Which side of the operation a hook ran on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPhase {
+    Pre,
+    Post,
+}
+
+/// Recorded result of one hook invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookResult {
+    pub phase: HookPhase,
+    pub command: String,
+    /// Process exit code (`None` if killed by a signal or not started)
+    pub exit_code: Option<i32>,
+    pub succeeded: bool,
+    /// Set if the hook could not be started at all
+    pub spawn_error: Option<String>,
+}
+
+/// Per-file result of a batch run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileBatchReport {
+    pub file_path: PathBuf,
+    pub pre_hook: Option<HookResult>,
+    /// Plan outcomes, or the error message if the plan failed or was aborted
+    pub operation_result: Result<Vec<PlanEntryOutcome>, String>,
+    pub post_hook: Option<HookResult>,
+}
+
+impl FileBatchReport {
+    /// True if the pre-hook (if any), the plan, and the post-hook (if any)
+    /// all succeeded.
+    pub fn fully_succeeded(&self) -> bool {
+        let pre_ok = self.pre_hook.as_ref().is_none_or(|hook| hook.succeeded);
+        let post_ok = self.post_hook.as_ref().is_none_or(|hook| hook.succeeded);
+        pre_ok && self.operation_result.is_ok() && post_ok
+    }
+}
+
+/// Applies one patch plan to each file in turn, running hooks around each.
+///
+/// # Behavior
+/// - Files are processed in the given order; a failure on one file does not
+///   stop the batch
+/// - A failing pre-hook aborts that file's operation (recorded as an error)
+///   and its post-hook is not run
+///
+/// # Returns
+/// One `FileBatchReport` per input file, in order.
+pub fn apply_patch_plan_to_files(
+    file_paths: &[PathBuf],
+    plan_entries: &[PlanEntry],
+    hooks: &OperationHooks,
+) -> Vec<FileBatchReport> {
+    let mut reports = Vec::with_capacity(file_paths.len());
+
+    for file_path in file_paths {
+        // =========================================
+        // Pre-Hook Phase
+        // =========================================
+        let pre_hook = hooks
+            .pre_command
+            .as_ref()
+            .map(|command| run_hook(HookPhase::Pre, command, file_path, None));
+
+        if let Some(pre_hook_result) = &pre_hook
+            && !pre_hook_result.succeeded
+        {
+            report_problem!(
+                "Pre-hook failed for {}; operation aborted",
+                file_path.display()
+            );
+            reports.push(FileBatchReport {
+                file_path: file_path.clone(),
+                pre_hook,
+                operation_result: Err("Aborted: pre-hook failed".to_string()),
+                post_hook: None,
+            });
+            continue;
+        }
+
+        // =========================================
+        // Operation Phase
+        // =========================================
+        let operation_result =
+            apply_patch_plan(file_path, plan_entries).map_err(|error| error.to_string());
+
+        // =========================================
+        // Post-Hook Phase
+        // =========================================
+        let operation_status = if operation_result.is_ok() {
+            "applied"
+        } else {
+            "failed"
+        };
+        let post_hook = hooks
+            .post_command
+            .as_ref()
+            .map(|command| run_hook(HookPhase::Post, command, file_path, Some(operation_status)));
+
+        reports.push(FileBatchReport {
+            file_path: file_path.clone(),
+            pre_hook,
+            operation_result,
+            post_hook,
+        });
+    }
+
+    reports
+}
+
+/// Like `apply_patch_plan_to_files`, using `config` instead of the active
+/// (or default) `OperationConfig` for every file.
+pub fn apply_patch_plan_to_files_with_config(
+    file_paths: &[PathBuf],
+    plan_entries: &[PlanEntry],
+    hooks: &OperationHooks,
+    config: &OperationConfig,
+) -> Vec<FileBatchReport> {
+    let _active_config_scope = ActiveConfigScope::enter(config);
+    apply_patch_plan_to_files(file_paths, plan_entries, hooks)
+}
+
+/// Runs one hook command through the platform shell and records the result.
+fn run_hook(
+    phase: HookPhase,
+    command: &str,
+    file_path: &Path,
+    operation_status: Option<&str>,
+) -> HookResult {
+    let mut shell_command = build_shell_command(command);
+    shell_command.env(HOOK_TARGET_FILE_VARIABLE, file_path);
+    if let Some(status) = operation_status {
+        shell_command.env(HOOK_OPERATION_STATUS_VARIABLE, status);
+    }
+
+    match shell_command.status() {
+        Ok(exit_status) => HookResult {
+            phase,
+            command: command.to_string(),
+            exit_code: exit_status.code(),
+            succeeded: exit_status.success(),
+            spawn_error: None,
+        },
+        Err(error) => HookResult {
+            phase,
+            command: command.to_string(),
+            exit_code: None,
+            succeeded: false,
+            spawn_error: Some(error.to_string()),
+        },
+    }
+}
+
+/// Wraps a command line in the platform shell.
+fn build_shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell_command = Command::new("cmd");
+        shell_command.arg("/C").arg(command);
+        shell_command
+    } else {
+        let mut shell_command = Command::new("sh");
+        shell_command.arg("-c").arg(command);
+        shell_command
+    }
+}
+
+/// Summarizes a batch as an `io::Result`: error if any file did not fully succeed.
+pub fn batch_error_if_any_failed(reports: &[FileBatchReport]) -> io::Result<()> {
+    let failed_count = reports
+        .iter()
+        .filter(|report| !report.fully_succeeded())
+        .count();
+    if failed_count == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} of {} files failed",
+            failed_count,
+            reports.len()
+        )))
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::patch_plan::ByteOperation;
+
+    #[test]
+    fn test_batch_pre_hook_failure_aborts_file() {
+        let test_file = std::env::temp_dir().join("test_batch_pre_hook_abort.bin");
+        std::fs::write(&test_file, vec![0x00, 0x01]).expect("create test file");
+
+        let plan = vec![PlanEntry::unconditional(ByteOperation::Replace {
+            position: 0,
+            new_byte_value: 0xFF,
+        })];
+        let hooks = OperationHooks {
+            pre_command: Some("exit 7".to_string()),
+            post_command: Some("true".to_string()),
+        };
+
+        let reports = apply_patch_plan_to_files(std::slice::from_ref(&test_file), &plan, &hooks);
+
+        assert_eq!(reports.len(), 1);
+        let pre_hook = reports[0].pre_hook.as_ref().expect("pre-hook recorded");
+        assert_eq!(pre_hook.exit_code, Some(7));
+        assert!(reports[0].operation_result.is_err());
+        assert!(reports[0].post_hook.is_none());
+        assert!(batch_error_if_any_failed(&reports).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x00, 0x01]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_batch_hooks_run_around_operation() {
+        let test_file = std::env::temp_dir().join("test_batch_hooks_around.bin");
+        let marker_file = std::env::temp_dir().join("test_batch_hooks_around.marker");
+        std::fs::write(&test_file, vec![0x00, 0x01]).expect("create test file");
+        let _ = std::fs::remove_file(&marker_file);
+
+        let plan = vec![PlanEntry::unconditional(ByteOperation::Replace {
+            position: 1,
+            new_byte_value: 0xAB,
+        })];
+        let hooks = OperationHooks {
+            pre_command: Some("test -f \"$BFBO_TARGET_FILE\"".to_string()),
+            post_command: Some(format!(
+                "echo \"$BFBO_OPERATION_STATUS\" > \"{}\"",
+                marker_file.display()
+            )),
+        };
+
+        let reports = apply_patch_plan_to_files(std::slice::from_ref(&test_file), &plan, &hooks);
+
+        assert!(reports[0].fully_succeeded());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x00, 0xAB]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&marker_file).expect("post-hook wrote marker"),
+            "applied\n"
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+        let _ = std::fs::remove_file(&marker_file);
+    }
+}
@@ -0,0 +1,218 @@
+//! Bit-level edits
+//!
+//! Flags in firmware and configuration blobs are often single bits.
+//! `modify_bit_in_file` changes one bit through the standard replace
+//! workflow (one-byte splice, no frame-shift) and additionally proves that
+//! the original and draft bytes differ in nothing but the targeted bit.
+
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{open_at_offset, validate_target_file};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::range_operations::splice_byte_range_with_draft_check;
+use crate::reporter::report_check_passed;
+
+/// What to do with the targeted bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOp {
+    /// Invert the bit
+    Flip,
+    /// Force the bit to 1
+    Set,
+    /// Force the bit to 0
+    Clear,
+}
+
+impl BitOp {
+    /// Applies the operation to `bit_index` (0 = least significant) of a byte.
+    pub fn apply(&self, byte_value: u8, bit_index: u8) -> u8 {
+        let bit_mask = 1u8 << bit_index;
+        match self {
+            BitOp::Flip => byte_value ^ bit_mask,
+            BitOp::Set => byte_value | bit_mask,
+            BitOp::Clear => byte_value & !bit_mask,
+        }
+    }
+}
+
+/// Flips, sets, or clears one bit of one byte.
+///
+/// # Verification
+/// In addition to the splice verification (length, all other bytes
+/// identical), the draft byte is checked against the original byte read
+/// back from disk:
+/// - Bits other than `bit_index` are identical
+/// - The targeted bit has the value `bit_op` implies (for `Flip`, the
+///   inverse of the original bit)
+///
+/// Like `replace_single_byte_in_file`, setting an already-set bit (or
+/// clearing a clear one) still completes the workflow and is idempotent.
+///
+/// # Parameters
+/// - `byte_position`: Zero-indexed byte position
+/// - `bit_index`: 0 (least significant) through 7
+///
+/// # Returns
+/// - `Ok(u8)`: the new byte value
+/// - `Err(io::Error)` if the bit index or position is out of range, or any
+///   phase fails
+pub fn modify_bit_in_file(
+    original_file_path: PathBuf,
+    byte_position: u64,
+    bit_index: u8,
+    bit_op: BitOp,
+) -> io::Result<u8> {
+    if bit_index > 7 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Bit index {} out of range 0..=7", bit_index),
+        ));
+    }
+
+    let original_file_size = validate_target_file(&original_file_path)?;
+    if byte_position >= original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte position {} exceeds file size {}",
+                byte_position, original_file_size
+            ),
+        ));
+    }
+
+    let original_byte = read_byte_at(&original_file_path, byte_position)?;
+    let new_byte = bit_op.apply(original_byte, bit_index);
+    let target_path_for_check = original_file_path.clone();
+
+    splice_byte_range_with_draft_check(
+        original_file_path,
+        byte_position,
+        1,
+        &[new_byte],
+        "Bit Modification",
+        |draft_path| {
+            let original_byte_on_disk = read_byte_at(&target_path_for_check, byte_position)?;
+            let draft_byte = read_byte_at(draft_path, byte_position)?;
+            verify_single_bit_change(
+                byte_position,
+                original_byte_on_disk,
+                draft_byte,
+                bit_index,
+                bit_op,
+            )
+        },
+    )?;
+
+    Ok(new_byte)
+}
+
+/// Like `modify_bit_in_file`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn modify_bit_in_file_with_config(
+    original_file_path: PathBuf,
+    byte_position: u64,
+    bit_index: u8,
+    bit_op: BitOp,
+    config: &OperationConfig,
+) -> io::Result<u8> {
+    with_operation_config(config, || {
+        modify_bit_in_file(original_file_path, byte_position, bit_index, bit_op)
+    })
+}
+
+/// Checks that `draft_byte` is `original_byte` with only the targeted bit
+/// (possibly) changed, to the value `bit_op` implies.
+fn verify_single_bit_change(
+    byte_position: u64,
+    original_byte: u8,
+    draft_byte: u8,
+    bit_index: u8,
+    bit_op: BitOp,
+) -> io::Result<()> {
+    let bit_mask = 1u8 << bit_index;
+    let other_bits_changed = (original_byte ^ draft_byte) & !bit_mask != 0;
+    let expected_bit_set = match bit_op {
+        BitOp::Flip => original_byte & bit_mask == 0,
+        BitOp::Set => true,
+        BitOp::Clear => false,
+    };
+    let draft_bit_set = draft_byte & bit_mask != 0;
+
+    if other_bits_changed || draft_bit_set != expected_bit_set {
+        let expected_byte = if expected_bit_set {
+            original_byte | bit_mask
+        } else {
+            original_byte & !bit_mask
+        };
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::AtPosition,
+            byte_position,
+            u64::from(expected_byte),
+            u64::from(draft_byte),
+        )
+        .into());
+    }
+
+    report_check_passed!(
+        "   ✓ Only bit {} differs: 0b{:08b} -> 0b{:08b}",
+        bit_index,
+        original_byte,
+        draft_byte
+    );
+    Ok(())
+}
+
+fn read_byte_at(file_path: &Path, byte_position: u64) -> io::Result<u8> {
+    let mut byte_buffer = [0u8; 1];
+    open_at_offset(file_path, byte_position)?.read_exact(&mut byte_buffer)?;
+    Ok(byte_buffer[0])
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modify_bit_flip_set_clear() {
+        let test_file = std::env::temp_dir().join("test_modify_bit.bin");
+        std::fs::write(&test_file, [0x00, 0b1010_0000, 0xFF]).expect("create test file");
+
+        assert_eq!(
+            modify_bit_in_file(test_file.clone(), 1, 0, BitOp::Flip).expect("flip"),
+            0b1010_0001
+        );
+        assert_eq!(
+            modify_bit_in_file(test_file.clone(), 1, 7, BitOp::Clear).expect("clear"),
+            0b0010_0001
+        );
+        assert_eq!(
+            modify_bit_in_file(test_file.clone(), 1, 5, BitOp::Set).expect("idempotent set"),
+            0b0010_0001
+        );
+        assert_eq!(
+            std::fs::read(&test_file).expect("read modified file"),
+            vec![0x00, 0b0010_0001, 0xFF]
+        );
+
+        assert!(modify_bit_in_file(test_file.clone(), 1, 8, BitOp::Flip).is_err());
+        assert!(modify_bit_in_file(test_file.clone(), 3, 0, BitOp::Flip).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_verify_single_bit_change_rejects_other_bits() {
+        assert!(verify_single_bit_change(0, 0b0000_0000, 0b0000_0100, 2, BitOp::Flip).is_ok());
+        assert!(verify_single_bit_change(0, 0b0000_0000, 0b0000_0110, 2, BitOp::Flip).is_err());
+        assert!(verify_single_bit_change(0, 0b0000_0100, 0b0000_0100, 2, BitOp::Flip).is_err());
+        assert!(verify_single_bit_change(0, 0b0000_0100, 0b0000_0100, 2, BitOp::Set).is_ok());
+    }
+}
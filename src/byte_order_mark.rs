@@ -0,0 +1,317 @@
+//! Byte-order mark (BOM) detection and replacement
+//!
+//! Editors and toolchains disagree about BOMs: some require one, some choke
+//! on one. `set_bom` makes the leading mark whatever is wanted, in one
+//! verified splice at position 0. Only the mark changes; the text itself is
+//! not re-encoded (switching UTF-8 ↔ UTF-16 is a different operation).
+//! Files with a UTF-32 mark are recognized and refused.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::range_operations::splice_byte_range;
+use crate::reporter::report_detail;
+
+/// A byte-order mark, or its absence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrderMark {
+    /// No recognized BOM at position 0
+    None,
+    /// `EF BB BF`
+    Utf8Bom,
+    /// `FF FE`
+    Utf16Le,
+    /// `FE FF`
+    Utf16Be,
+}
+
+impl ByteOrderMark {
+    /// The mark's bytes (empty for `None`).
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            ByteOrderMark::None => &[],
+            ByteOrderMark::Utf8Bom => &[0xEF, 0xBB, 0xBF],
+            ByteOrderMark::Utf16Le => &[0xFF, 0xFE],
+            ByteOrderMark::Utf16Be => &[0xFE, 0xFF],
+        }
+    }
+}
+
+/// What `set_bom` found and did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BomChangeReport {
+    pub previous_bom: ByteOrderMark,
+    pub new_bom: ByteOrderMark,
+    /// False if the file already had the requested mark (file untouched)
+    pub changed: bool,
+}
+
+/// Detects the byte-order mark at the start of a file.
+///
+/// `FF FE 00 00` (UTF-32LE) is reported as `None` rather than misread as
+/// UTF-16LE, so it is never partially stripped; `set_bom` refuses files
+/// with a UTF-32 mark.
+pub fn detect_bom(file_path: &Path) -> io::Result<ByteOrderMark> {
+    let leading_bytes = read_leading_bytes(file_path)?;
+    let leading_bytes = leading_bytes.as_slice();
+
+    Ok(if leading_bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        ByteOrderMark::Utf8Bom
+    } else if leading_bytes.starts_with(&UTF32_LE_BOM) {
+        ByteOrderMark::None
+    } else if leading_bytes.starts_with(&[0xFF, 0xFE]) {
+        ByteOrderMark::Utf16Le
+    } else if leading_bytes.starts_with(&[0xFE, 0xFF]) {
+        ByteOrderMark::Utf16Be
+    } else {
+        ByteOrderMark::None
+    })
+}
+
+/// `FF FE 00 00`
+const UTF32_LE_BOM: [u8; 4] = [0xFF, 0xFE, 0x00, 0x00];
+/// `00 00 FE FF`
+const UTF32_BE_BOM: [u8; 4] = [0x00, 0x00, 0xFE, 0xFF];
+
+/// Up to the first 4 bytes of the file (fewer if it is shorter).
+fn read_leading_bytes(file_path: &Path) -> io::Result<Vec<u8>> {
+    let mut source_file = File::open(file_path)?;
+    let mut leading_bytes = [0u8; 4];
+    let mut bytes_available: usize = 0;
+    for _attempt in 0..leading_bytes.len() {
+        let bytes_read = source_file.read(&mut leading_bytes[bytes_available..])?;
+        if bytes_read == 0 {
+            break;
+        }
+        bytes_available += bytes_read;
+        if bytes_available == leading_bytes.len() {
+            break;
+        }
+    }
+    Ok(leading_bytes[..bytes_available].to_vec())
+}
+
+/// Refuses a file that starts with a UTF-32 BOM: `ByteOrderMark` cannot
+/// express one, so any change would corrupt it (UTF-32LE would lose half
+/// its mark, any other mark would be put in front of it).
+fn refuse_utf32_bom(file_path: &Path) -> io::Result<()> {
+    let leading_bytes = read_leading_bytes(file_path)?;
+    let encoding_name = if leading_bytes == UTF32_LE_BOM {
+        "UTF-32LE"
+    } else if leading_bytes == UTF32_BE_BOM {
+        "UTF-32BE"
+    } else {
+        return Ok(());
+    };
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} starts with a {} BOM; its mark cannot be changed",
+            file_path.display(),
+            encoding_name
+        ),
+    ))
+}
+
+/// Makes the file start with `wanted_bom` (or with no BOM for `None`).
+///
+/// # Overview
+/// Detects the current mark, then replaces it with one splice at position 0
+/// (remove the old mark's bytes, insert the new mark's bytes), verified with
+/// the splice's frame-shift checks.
+///
+/// # Returns
+/// - `Ok(BomChangeReport)` describing the previous and new mark
+/// - `Err(io::Error)` (`InvalidData`) if the file starts with a UTF-32 BOM;
+///   any workflow error otherwise. The original is untouched
+pub fn set_bom(
+    original_file_path: PathBuf,
+    wanted_bom: ByteOrderMark,
+) -> io::Result<BomChangeReport> {
+    refuse_utf32_bom(&original_file_path)?;
+    let previous_bom = detect_bom(&original_file_path)?;
+
+    if previous_bom == wanted_bom {
+        return Ok(BomChangeReport {
+            previous_bom,
+            new_bom: wanted_bom,
+            changed: false,
+        });
+    }
+
+    splice_byte_range(
+        original_file_path,
+        0,
+        previous_bom.bytes().len() as u64,
+        wanted_bom.bytes(),
+    )?;
+
+    report_detail!("BOM changed: {:?} -> {:?}", previous_bom, wanted_bom);
+
+    Ok(BomChangeReport {
+        previous_bom,
+        new_bom: wanted_bom,
+        changed: true,
+    })
+}
+
+/// Like `set_bom`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn set_bom_with_config(
+    original_file_path: PathBuf,
+    wanted_bom: ByteOrderMark,
+    config: &OperationConfig,
+) -> io::Result<BomChangeReport> {
+    with_operation_config(config, || set_bom(original_file_path, wanted_bom))
+}
+
+/// Adding or removing the UTF-8 BOM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BomAction {
+    /// Insert `EF BB BF` at position 0 unless already present
+    Add,
+    /// Remove a leading `EF BB BF` if present
+    Remove,
+}
+
+/// Adds or removes the UTF-8 BOM; idempotent.
+///
+/// A convenience over `set_bom` for UTF-8 files: `Add` inserts the 3-byte
+/// mark (frame-shift +3), `Remove` removes it (frame-shift -3), and a file
+/// already in the requested state is left untouched (`changed == false`).
+///
+/// # Returns
+/// - `Ok(BomChangeReport)` describing the previous and new mark
+/// - `Err(io::Error)` (`InvalidData`) if the file starts with a UTF-16 BOM
+///   (it is not a UTF-8 file; use `set_bom` to change that mark
+///   deliberately) or a UTF-32 BOM; any workflow error otherwise
+pub fn set_utf8_bom(original_file_path: PathBuf, action: BomAction) -> io::Result<BomChangeReport> {
+    let previous_bom = detect_bom(&original_file_path)?;
+    if matches!(
+        previous_bom,
+        ByteOrderMark::Utf16Le | ByteOrderMark::Utf16Be
+    ) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("File starts with a {:?} BOM, not UTF-8", previous_bom),
+        ));
+    }
+
+    let wanted_bom = match action {
+        BomAction::Add => ByteOrderMark::Utf8Bom,
+        BomAction::Remove => ByteOrderMark::None,
+    };
+    set_bom(original_file_path, wanted_bom)
+}
+
+/// Like `set_utf8_bom`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn set_utf8_bom_with_config(
+    original_file_path: PathBuf,
+    action: BomAction,
+    config: &OperationConfig,
+) -> io::Result<BomChangeReport> {
+    with_operation_config(config, || set_utf8_bom(original_file_path, action))
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_bom_add_swap_remove() {
+        let test_file = std::env::temp_dir().join("test_set_bom.txt");
+        std::fs::write(&test_file, b"text").expect("create test file");
+
+        let report = set_bom(test_file.clone(), ByteOrderMark::Utf8Bom).expect("add BOM");
+        assert_eq!(report.previous_bom, ByteOrderMark::None);
+        assert!(report.changed);
+        assert_eq!(
+            std::fs::read(&test_file).expect("read"),
+            b"\xEF\xBB\xBFtext".to_vec()
+        );
+
+        let report = set_bom(test_file.clone(), ByteOrderMark::Utf8Bom).expect("no-op");
+        assert!(!report.changed);
+
+        let report = set_bom(test_file.clone(), ByteOrderMark::Utf16Be).expect("swap BOM");
+        assert_eq!(report.previous_bom, ByteOrderMark::Utf8Bom);
+        assert_eq!(
+            std::fs::read(&test_file).expect("read"),
+            b"\xFE\xFFtext".to_vec()
+        );
+
+        set_bom(test_file.clone(), ByteOrderMark::None).expect("remove BOM");
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"text".to_vec());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_set_utf8_bom_add_remove_idempotent() {
+        let test_file = std::env::temp_dir().join("test_set_utf8_bom.txt");
+        std::fs::write(&test_file, b"text").expect("create test file");
+
+        assert!(
+            set_utf8_bom(test_file.clone(), BomAction::Add)
+                .expect("add")
+                .changed
+        );
+        assert!(
+            !set_utf8_bom(test_file.clone(), BomAction::Add)
+                .expect("again")
+                .changed
+        );
+        assert_eq!(
+            std::fs::read(&test_file).expect("read"),
+            b"\xEF\xBB\xBFtext".to_vec()
+        );
+        assert!(
+            set_utf8_bom(test_file.clone(), BomAction::Remove)
+                .expect("remove")
+                .changed
+        );
+        assert!(
+            !set_utf8_bom(test_file.clone(), BomAction::Remove)
+                .expect("again")
+                .changed
+        );
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"text".to_vec());
+
+        std::fs::write(&test_file, b"\xFF\xFEt\x00").expect("write UTF-16LE");
+        assert!(set_utf8_bom(test_file.clone(), BomAction::Remove).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_detect_bom_does_not_misread_utf32() {
+        let test_file = std::env::temp_dir().join("test_detect_bom_utf32.txt");
+        std::fs::write(&test_file, [0xFF, 0xFE, 0x00, 0x00, 0x41, 0x00, 0x00, 0x00])
+            .expect("create test file");
+        assert_eq!(detect_bom(&test_file).expect("detect"), ByteOrderMark::None);
+
+        // Neither UTF-32 mark is stripped, replaced, or prefixed
+        for utf32_bytes in [
+            [0xFF, 0xFE, 0x00, 0x00, 0x41, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0xFE, 0xFF, 0x00, 0x00, 0x00, 0x41],
+        ] {
+            std::fs::write(&test_file, utf32_bytes).expect("write UTF-32");
+            for wanted_bom in [ByteOrderMark::None, ByteOrderMark::Utf8Bom] {
+                let error = set_bom(test_file.clone(), wanted_bom).expect_err("UTF-32 BOM");
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            }
+            assert!(set_utf8_bom(test_file.clone(), BomAction::Add).is_err());
+            assert_eq!(std::fs::read(&test_file).expect("read"), utf32_bytes);
+        }
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,151 @@
+//! Streaming byte-pattern search
+//!
+//! Finds occurrences of a byte pattern without loading the whole source into
+//! memory. Bytes are read through a bucket-brigade buffer allocated once,
+//! and the last `pattern.len() - 1` bytes of each chunk are carried forward so
+//! that matches straddling a chunk boundary are still found.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::memory_budget::{MemoryConsumer, admit_consumers};
+use crate::operation_config::active_buffer_size;
+
+/// Scans a reader for a byte pattern and returns the offsets of matches.
+///
+/// # Parameters
+/// - `reader`: Any byte source (file, stdin, ...), read sequentially
+/// - `pattern`: The byte sequence to find (must be non-empty)
+/// - `max_matches`: Stop after this many matches (`None` = find all)
+///
+/// # Returns
+/// - `Ok(Vec<u64>)` with zero-indexed offsets of every match, ascending.
+///   Overlapping matches are reported (pattern `AA` in `AAA` matches at 0 and 1).
+/// - `Err(io::Error)` if the pattern is empty, the search does not fit the
+///   active config's memory budget (without `max_matches` it has no bound),
+///   or reading fails
+pub fn find_pattern_offsets_in_reader<R: Read>(
+    mut reader: R,
+    pattern: &[u8],
+    max_matches: Option<usize>,
+) -> io::Result<Vec<u64>> {
+    if pattern.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Search pattern must not be empty",
+        ));
+    }
+
+    admit_consumers(&[MemoryConsumer::PatternSearch {
+        pattern_length: pattern.len(),
+        max_matches,
+    }])?;
+
+    let mut match_offsets: Vec<u64> = Vec::new();
+    if max_matches == Some(0) {
+        return Ok(match_offsets);
+    }
+
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+
+    // Window = carried tail of previous chunks + current chunk
+    let carry_length = pattern.len() - 1;
+    let mut search_window: Vec<u8> = Vec::with_capacity(carry_length + bucket_brigade_buffer.len());
+
+    // Source offset of search_window[0]
+    let mut window_start_offset: u64 = 0;
+
+    // No chunk cap here: the reader may be a pipe of unknown length. Every
+    // iteration consumes at least one byte or ends at EOF, and the scanned
+    // byte count is checked for overflow.
+    let mut bytes_scanned: u64 = 0;
+
+    loop {
+        let bytes_read = reader.read(&mut bucket_brigade_buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        bytes_scanned = bytes_scanned
+            .checked_add(bytes_read as u64)
+            .ok_or_else(|| io::Error::other("Input larger than the offset range"))?;
+
+        search_window.extend_from_slice(&bucket_brigade_buffer[..bytes_read]);
+
+        if search_window.len() >= pattern.len() {
+            for index in 0..=(search_window.len() - pattern.len()) {
+                if &search_window[index..index + pattern.len()] == pattern {
+                    match_offsets.push(window_start_offset + index as u64);
+                    if Some(match_offsets.len()) == max_matches {
+                        return Ok(match_offsets);
+                    }
+                }
+            }
+
+            // Keep only the bytes that could still begin a match
+            let bytes_to_drop = search_window.len() - carry_length;
+            search_window.drain(..bytes_to_drop);
+            window_start_offset += bytes_to_drop as u64;
+        }
+    }
+
+    Ok(match_offsets)
+}
+
+/// Scans a file for a byte pattern and returns the offsets of matches.
+///
+/// See `find_pattern_offsets_in_reader` for semantics.
+pub fn find_pattern_offsets_in_file(
+    file_path: &Path,
+    pattern: &[u8],
+    max_matches: Option<usize>,
+) -> io::Result<Vec<u64>> {
+    let source_file = File::open(file_path)?;
+    find_pattern_offsets_in_reader(source_file, pattern, max_matches)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation_config::{OperationConfig, with_operation_config};
+
+    #[test]
+    fn test_find_pattern_basic_and_overlapping() {
+        let data: &[u8] = &[0xAA, 0xAA, 0xAA, 0x00, 0xAA];
+        let offsets = find_pattern_offsets_in_reader(data, &[0xAA, 0xAA], None)
+            .expect("search should succeed");
+        assert_eq!(offsets, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_find_pattern_across_chunk_boundary() {
+        let mut data = [0u8; 200];
+        data[62..66].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        data[190..194].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("valid config");
+        let offsets = with_operation_config(&tiny_config, || {
+            find_pattern_offsets_in_reader(&data[..], &[0xDE, 0xAD, 0xBE, 0xEF], None)
+        })
+        .expect("search should succeed");
+        assert_eq!(offsets, vec![62, 190]);
+    }
+
+    #[test]
+    fn test_find_pattern_max_matches_and_empty_pattern() {
+        let data: &[u8] = &[0x01, 0x01, 0x01];
+        let offsets =
+            find_pattern_offsets_in_reader(data, &[0x01], Some(2)).expect("search should succeed");
+        assert_eq!(offsets, vec![0, 1]);
+
+        assert!(find_pattern_offsets_in_reader(data, &[], None).is_err());
+    }
+}
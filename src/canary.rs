@@ -0,0 +1,419 @@
+//! Self-check canary trailer
+//!
+//! A file managed by this tool can carry a small canary region at its end:
+//!
+//! ```text
+//! [ content ... ][ "BFBOCNRY" (8 bytes) ][ HMAC-SHA256(key, SHA-256(content)) (32 bytes) ]
+//! ```
+//!
+//! Every sanctioned edit refreshes the MAC, so `check_canary` later detects
+//! any change made outside the tool since the last sanctioned edit. Without
+//! the key (`BFBO_CANARY_KEY`), a tamperer cannot produce a matching
+//! trailer. The canary is installed and refreshed through the standard
+//! splice pipeline (backup, draft, verify, atomic rename), and the draft
+//! must itself pass `check_canary` before it replaces the original.
+//!
+//! An edit can also re-sign its own draft: with a `CanarySeal` on the
+//! active `OperationConfig`, every draft gets a fresh trailer after it is
+//! verified and before the rename, so the edit and the refresh land in one
+//! atomic replacement. A seal that only refreshes requires the original to
+//! carry a trailer already; `CanarySeal::install` also adds one to a file
+//! without.
+//!
+//! The trailer is part of the file: formats that reject trailing bytes
+//! should not carry a canary, and edits must stay within the content part.
+
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{open_at_offset, validate_target_file};
+use crate::hash::sha256::{hmac_sha256, sha256_of_file_range};
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::range_operations::splice_byte_range_with_draft_check;
+use crate::reporter::{report_detail, report_problem};
+
+/// Environment variable holding the canary key
+pub const CANARY_KEY_ENVIRONMENT_VARIABLE: &str = "BFBO_CANARY_KEY";
+
+/// Marks the start of the trailer
+pub const CANARY_MAGIC: &[u8; 8] = b"BFBOCNRY";
+
+/// Total trailer length: magic + MAC
+pub const CANARY_REGION_LENGTH: usize = 8 + 32;
+
+/// Result of checking a file's canary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanaryStatus {
+    /// No trailer present
+    Absent,
+    /// Trailer matches the content under the key
+    Valid,
+    /// Trailer present but does not match: modified outside the tool
+    /// (or checked with the wrong key)
+    Tampered,
+}
+
+impl CanaryStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CanaryStatus::Absent => "ABSENT",
+            CanaryStatus::Valid => "VALID",
+            CanaryStatus::Tampered => "TAMPERED",
+        }
+    }
+}
+
+/// Re-signs the canary trailer of every draft built under a config (see
+/// `OperationConfigBuilder::canary_seal`).
+#[derive(Clone, PartialEq, Eq)]
+pub struct CanarySeal {
+    canary_key: Vec<u8>,
+    install: bool,
+}
+
+impl CanarySeal {
+    /// Refreshes an existing trailer; an original without one is refused.
+    pub fn refresh(canary_key: impl Into<Vec<u8>>) -> Self {
+        CanarySeal {
+            canary_key: canary_key.into(),
+            install: false,
+        }
+    }
+
+    /// Refreshes an existing trailer, or appends one to a file without.
+    pub fn install(canary_key: impl Into<Vec<u8>>) -> Self {
+        CanarySeal {
+            canary_key: canary_key.into(),
+            install: true,
+        }
+    }
+
+    /// Whether an original without a trailer gets one
+    pub fn installs(&self) -> bool {
+        self.install
+    }
+}
+
+// The key stays out of logs and debug output
+impl fmt::Debug for CanarySeal {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("CanarySeal")
+            .field("install", &self.install)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Checks the canary trailer of a file.
+///
+/// # Returns
+/// - `Ok(CanaryStatus)`
+/// - `Err(io::Error)` if the file cannot be read
+pub fn check_canary(file_path: &Path, canary_key: &[u8]) -> io::Result<CanaryStatus> {
+    let file_size = std::fs::metadata(file_path)?.len();
+    let Some(content_length) = trailer_content_length(file_path, file_size)? else {
+        return Ok(CanaryStatus::Absent);
+    };
+
+    let recorded_mac = read_bytes_at(file_path, content_length + CANARY_MAGIC.len() as u64, 32)?;
+    let expected_mac = canary_mac(file_path, content_length, canary_key)?;
+    if recorded_mac[..] == expected_mac[..] {
+        Ok(CanaryStatus::Valid)
+    } else {
+        Ok(CanaryStatus::Tampered)
+    }
+}
+
+/// Installs a canary, or refreshes an existing one, as one verified edit.
+///
+/// Refreshing is how a sanctioned edit is "signed off": run it after the
+/// edit so the trailer covers the new content, or give the edit's config a
+/// `CanarySeal` to sign its draft before the one rename.
+///
+/// # Returns
+/// - `Ok(())` once the file carries a valid canary
+/// - `Err(io::Error)` if any workflow phase fails; the file is untouched
+pub fn refresh_canary(original_file_path: PathBuf, canary_key: &[u8]) -> io::Result<()> {
+    let file_size = validate_target_file(&original_file_path)?;
+    let (content_length, removed_length) =
+        match trailer_content_length(&original_file_path, file_size)? {
+            Some(content_length) => (content_length, CANARY_REGION_LENGTH as u64),
+            None => (file_size, 0),
+        };
+
+    let mut new_trailer = Vec::with_capacity(CANARY_REGION_LENGTH);
+    new_trailer.extend_from_slice(CANARY_MAGIC);
+    new_trailer.extend_from_slice(&canary_mac(
+        &original_file_path,
+        content_length,
+        canary_key,
+    )?);
+
+    splice_byte_range_with_draft_check(
+        original_file_path,
+        content_length,
+        removed_length,
+        &new_trailer,
+        "Canary Refresh",
+        |draft_path| match check_canary(draft_path, canary_key)? {
+            CanaryStatus::Valid => Ok(()),
+            other_status => Err(io::Error::other(format!(
+                "Draft canary check failed: {}",
+                other_status.name()
+            ))),
+        },
+    )
+}
+
+/// Like `refresh_canary`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn refresh_canary_with_config(
+    original_file_path: PathBuf,
+    canary_key: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || refresh_canary(original_file_path, canary_key))
+}
+
+/// Reads the canary key from `BFBO_CANARY_KEY`.
+pub fn canary_key_from_environment() -> io::Result<Vec<u8>> {
+    match std::env::var(CANARY_KEY_ENVIRONMENT_VARIABLE) {
+        Ok(key) if !key.is_empty() => Ok(key.into_bytes()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} must be set", CANARY_KEY_ENVIRONMENT_VARIABLE),
+        )),
+    }
+}
+
+/// If the active config carries a `CanarySeal`, writes a fresh trailer
+/// into the verified draft.
+///
+/// The draft must end with the original's trailer unchanged (the edit
+/// stayed within the content); its MAC is then replaced to cover the new
+/// content. An original without a trailer is refused unless the seal
+/// installs one. On failure the draft is removed. Called just before the
+/// rename, after the operation's own verification has passed.
+pub(crate) fn seal_draft_with_canary(
+    original_file_path: &Path,
+    draft_file_path: &Path,
+) -> io::Result<()> {
+    let config = active_operation_config();
+    let Some(seal) = config.canary_seal() else {
+        return Ok(());
+    };
+
+    let seal_result = (|| -> io::Result<()> {
+        let region_length = CANARY_REGION_LENGTH as u64;
+        let original_size = fs::metadata(original_file_path)?.len();
+        let draft_size = fs::metadata(draft_file_path)?.len();
+        let content_length = match trailer_content_length(original_file_path, original_size)? {
+            Some(original_content_length) => {
+                let original_trailer = read_bytes_at(
+                    original_file_path,
+                    original_content_length,
+                    CANARY_REGION_LENGTH,
+                )?;
+                if draft_size < region_length
+                    || read_bytes_at(
+                        draft_file_path,
+                        draft_size - region_length,
+                        CANARY_REGION_LENGTH,
+                    )? != original_trailer
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Edit reached the canary trailer; edits must stay within the content",
+                    ));
+                }
+                draft_size - region_length
+            }
+            None if seal.install => draft_size,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} has no canary trailer to refresh; install one first",
+                        original_file_path.display()
+                    ),
+                ));
+            }
+        };
+
+        let new_mac = canary_mac(draft_file_path, content_length, &seal.canary_key)?;
+        let mut draft_file = OpenOptions::new().write(true).open(draft_file_path)?;
+        draft_file.seek(SeekFrom::Start(content_length))?;
+        draft_file.write_all(CANARY_MAGIC)?;
+        draft_file.write_all(&new_mac)?;
+        if config.durability_mode().syncs_draft() {
+            draft_file.sync_all()?;
+        }
+        drop(draft_file);
+
+        match check_canary(draft_file_path, &seal.canary_key)? {
+            CanaryStatus::Valid => Ok(()),
+            other_status => Err(io::Error::other(format!(
+                "Draft canary check failed: {}",
+                other_status.name()
+            ))),
+        }
+    })();
+
+    if let Err(e) = &seal_result {
+        report_problem!("ERROR: Canary seal failed: {}", e);
+        let _ = fs::remove_file(draft_file_path);
+    } else {
+        report_detail!("Canary: draft trailer re-signed");
+    }
+    seal_result
+}
+
+/// Length of the content before the trailer, if a trailer is present.
+fn trailer_content_length(file_path: &Path, file_size: u64) -> io::Result<Option<u64>> {
+    let region_length = CANARY_REGION_LENGTH as u64;
+    if file_size < region_length {
+        return Ok(None);
+    }
+    let content_length = file_size - region_length;
+    let magic_bytes = read_bytes_at(file_path, content_length, CANARY_MAGIC.len())?;
+    if magic_bytes[..] == CANARY_MAGIC[..] {
+        Ok(Some(content_length))
+    } else {
+        Ok(None)
+    }
+}
+
+fn canary_mac(file_path: &Path, content_length: u64, canary_key: &[u8]) -> io::Result<[u8; 32]> {
+    let content_hash = sha256_of_file_range(file_path, 0, content_length)?;
+    Ok(hmac_sha256(canary_key, &content_hash))
+}
+
+fn read_bytes_at(file_path: &Path, byte_offset: u64, byte_count: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; byte_count];
+    open_at_offset(file_path, byte_offset)?.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canary_install_refresh_and_tamper_detection() {
+        let test_file = std::env::temp_dir().join("test_canary_trailer.bin");
+        std::fs::write(&test_file, b"firmware payload").expect("create test file");
+        let canary_key = b"site-key";
+
+        assert_eq!(
+            check_canary(&test_file, canary_key).expect("check"),
+            CanaryStatus::Absent
+        );
+
+        refresh_canary(test_file.clone(), canary_key).expect("install should succeed");
+        assert_eq!(
+            std::fs::metadata(&test_file).expect("metadata").len(),
+            (16 + CANARY_REGION_LENGTH) as u64
+        );
+        assert_eq!(
+            check_canary(&test_file, canary_key).expect("check"),
+            CanaryStatus::Valid
+        );
+        assert_eq!(
+            check_canary(&test_file, b"other-key").expect("check"),
+            CanaryStatus::Tampered
+        );
+
+        // Out-of-band edit is detected
+        let mut tampered = std::fs::read(&test_file).expect("read");
+        tampered[0] = b'F';
+        std::fs::write(&test_file, &tampered).expect("tamper");
+        assert_eq!(
+            check_canary(&test_file, canary_key).expect("check"),
+            CanaryStatus::Tampered
+        );
+
+        // Sanctioned refresh replaces (not appends) the trailer
+        refresh_canary(test_file.clone(), canary_key).expect("refresh should succeed");
+        assert_eq!(
+            std::fs::metadata(&test_file).expect("metadata").len(),
+            (16 + CANARY_REGION_LENGTH) as u64
+        );
+        assert_eq!(
+            check_canary(&test_file, canary_key).expect("check"),
+            CanaryStatus::Valid
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_canary_seal_signs_the_edit_draft() {
+        use crate::range_operations::splice_byte_range;
+
+        let test_file = std::env::temp_dir().join("test_canary_seal.bin");
+        std::fs::write(&test_file, b"firmware payload").expect("create test file");
+        let canary_key = b"site-key";
+        let refresh_config = OperationConfig::builder()
+            .canary_seal(CanarySeal::refresh(canary_key.to_vec()))
+            .build()
+            .expect("valid config");
+        let install_config = OperationConfig::builder()
+            .canary_seal(CanarySeal::install(canary_key.to_vec()))
+            .build()
+            .expect("valid config");
+
+        // A refresh-only seal needs an existing trailer
+        let edit = |config: &OperationConfig, start: u64, bytes: &[u8]| {
+            with_operation_config(config, || {
+                splice_byte_range(test_file.clone(), start, bytes.len() as u64, bytes)
+            })
+        };
+        assert!(edit(&refresh_config, 0, b"F").is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("read unchanged file"),
+            b"firmware payload"
+        );
+
+        // Installing edits and signs in one rename
+        edit(&install_config, 0, b"F").expect("install with the edit");
+        assert_eq!(
+            std::fs::metadata(&test_file).expect("metadata").len(),
+            (16 + CANARY_REGION_LENGTH) as u64
+        );
+        assert_eq!(
+            check_canary(&test_file, canary_key).expect("check"),
+            CanaryStatus::Valid
+        );
+
+        // Later edits keep the one trailer current
+        edit(&refresh_config, 1, b"I").expect("refresh with the edit");
+        assert_eq!(
+            std::fs::read(&test_file).expect("read edited file")[..16],
+            b"FIrmware payload"[..]
+        );
+        assert_eq!(
+            check_canary(&test_file, canary_key).expect("check"),
+            CanaryStatus::Valid
+        );
+
+        // An edit that reaches the trailer is refused
+        let signed_bytes = std::fs::read(&test_file).expect("read signed file");
+        assert!(edit(&refresh_config, 20, b"\x00").is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("read unchanged file"),
+            signed_bytes
+        );
+        assert!(!test_file.with_extension("bin.draft").exists());
+
+        let _ = std::fs::remove_file(&test_file);
+        let _ = std::fs::remove_file(test_file.with_extension("bin.backup"));
+    }
+}
@@ -0,0 +1,2192 @@
+//! Command-line interface (`bfbo`)
+//!
+//! `main()` hands control here when arguments are given. The CLI is a thin,
+//! dependency-free wrapper over the byte operations; each write subcommand
+//! runs the normal backup/draft/verify workflow once per offset.
+//!
+//! Output of `find` is designed to be piped:
+//! ```text
+//! bfbo find firmware.bin DEADBEEF --offsets-only | xargs bfbo replace firmware.bin 00
+//! bfbo find firmware.bin 0D --null-delimited | xargs -0 bfbo remove firmware.bin
+//! cat dump.bin | bfbo find - 7F454C46
+//! ```
+//! Offsets are accepted as decimal or `0x` hexadecimal. An offset list of
+//! `-` is read from standard input, as is a FILE of `-` for `find` and
+//! `hash` (both stream it). Every other file argument is a path.
+//!
+//! Write subcommands accept `--dry-run`, which validates and lists the
+//! operations without writing anything. A host-level write freeze (see
+//! `host_policy.rs`) forces dry-run mode regardless of flags and exits with
+//! `EXIT_WRITES_FROZEN`.
+
+use std::{
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::backup_audit::{BackupAuditStatus, audit_backup_store};
+use crate::backup_restore::{backup_to_restore, restore_from_backup};
+use crate::batch::{OperationHooks, apply_patch_plan_to_files};
+use crate::byte_search::{find_pattern_offsets_in_file, find_pattern_offsets_in_reader};
+use crate::canary::{
+    CanarySeal, CanaryStatus, canary_key_from_environment, check_canary, refresh_canary,
+};
+use crate::cost_estimate::{
+    DEFAULT_PROBE_SAMPLE_BYTES, estimate_plan_cost, probe_storage_throughput,
+};
+use crate::custody_report::{
+    CUSTODY_KEY_ENVIRONMENT_VARIABLE, CustodySession, verify_custody_report,
+};
+use crate::dedup_report::{
+    DEFAULT_DEDUP_CHUNK_SIZE, build_dedup_report, format_dedup_report_as_json,
+};
+use crate::diff_report::{
+    DEFAULT_DIFF_CONTEXT_BYTES, DiffReportFormat, replace_ranges_with_diff_report,
+};
+use crate::direct_write::{plan_direct_write_recovery, recover_direct_write, write_bytes_directly};
+use crate::disk_image::{read_partition_table, resolve_partition_offset};
+use crate::draft_workflow::{DurabilityMode, build_artifact_paths, validate_target_file};
+use crate::error::ByteOpError;
+use crate::hash::sha256::digest_to_hex;
+use crate::hash::{HashAlgorithm, hash_file, hash_reader};
+use crate::host_policy::detect_host_write_freeze;
+use crate::invariants::{build_invariants, format_invariants_as_json, format_invariants_as_lines};
+use crate::journal::{format_journal_as_json, plan_recovery, read_journal, recover};
+use crate::json_schema::{JSON_SCHEMA_NAMES, json_schema_for};
+use crate::memory_budget::{MemoryBudget, MemoryConsumer};
+use crate::multi_edit::{apply_operations, dry_run_operations};
+use crate::operation_config::{
+    BackupNaming, OperationConfig, PageCacheMode, SymlinkPolicy, active_operation_config,
+    with_operation_config,
+};
+use crate::operation_report::format_operation_report_as_json;
+use crate::orphan_scan::{OrphanAction, scan_for_orphans};
+use crate::patch_plan::{
+    ByteOperation, apply_byte_operation, format_plan_as_json, parse_plan_text,
+};
+use crate::recipes::{find_recipe, load_recipes, recipes_directory};
+use crate::reporter::{ReportEvent, Reporter, with_reporter};
+use crate::sector_editing::{
+    SectorStraddlePolicy, plan_sector_edits, replace_ranges_sector_aligned,
+};
+#[cfg(feature = "soak-test")]
+use crate::soak_test::{SoakConfig, run_soak_test};
+use crate::tar_archive::{edit_tar_member, locate_tar_member};
+use crate::util::offset_to_usize;
+use crate::value_parsing::{parse_hex_byte_text, parse_hex_bytes_text, parse_offset_text};
+use crate::zip_archive::{edit_stored_zip_member, locate_zip_member};
+use crate::{
+    insert_single_byte_into_file_with_report, remove_single_byte_from_file_with_report,
+    replace_single_byte_in_file_with_report,
+};
+
+/// Exit code: success
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code: the requested operation failed (I/O, bounds, verification)
+pub const EXIT_OPERATION_FAILED: i32 = 1;
+/// Exit code: the command line could not be understood
+pub const EXIT_USAGE_ERROR: i32 = 2;
+/// Exit code: a host-level write freeze forced a dry run; nothing was written
+pub const EXIT_WRITES_FROZEN: i32 = 3;
+
+const USAGE_TEXT: &str = "\
+Usage:
+  bfbo find    <FILE|-> <HEX-PATTERN> [--offsets-only] [--null-delimited] [--max N]
+  bfbo replace <FILE> <HEX-BYTE> <OFFSET>... | -   [--dry-run] [--json | --diff-report OUT [--html]]
+  bfbo insert  <FILE> <HEX-BYTE> <OFFSET>... | -   [--dry-run] [--json]
+  bfbo remove  <FILE> <OFFSET>... | -              [--dry-run] [--json]
+  bfbo zip-replace <ARCHIVE> <MEMBER> <OFFSET> <HEX-BYTES> [--dry-run]
+  bfbo tar-replace <ARCHIVE> <MEMBER> <OFFSET> <HEX-BYTES> [--touch] [--dry-run]
+  bfbo sector-replace <IMAGE> <OFFSET> <HEX-BYTES> --sector-size N [--expand] [--dry-run]
+  bfbo direct-write <FILE> <OFFSET> <HEX-BYTES> [--dry-run]
+  bfbo partitions <IMAGE>
+  bfbo hash <FILE|->... [--algorithm NAME]
+  bfbo resolve <IMAGE> p<N>+<OFFSET>
+  bfbo custody-verify <REPORT>
+  bfbo audit-backups <STORE>
+  bfbo check-canary <FILE>
+  bfbo canary-refresh <FILE> [--dry-run]
+  bfbo recover <FILE> [--dry-run]
+  bfbo journal <FILE>
+  bfbo restore-backup <FILE> [--dry-run]
+  bfbo scan-orphans <DIR>
+  bfbo estimate <FILE> <PLAN-FILE> [--probe]
+  bfbo dedup-report <DIR> [--chunk N] [--json]
+  bfbo invariants [--json]
+  bfbo schema <report|plan|journal|dedup-report|invariants>
+  bfbo soak-test [--seconds N] [--iterations N] [--seed N] [--dir DIR]   (feature soak-test)
+  bfbo recipe list
+  bfbo recipe show  <NAME[@VERSION]> [--json]
+  bfbo recipe apply <NAME[@VERSION]> <FILE>...       [--dry-run]
+
+Offsets are decimal or 0x-prefixed hex. An offset list of `-` is read from
+stdin (whitespace- or NUL-separated), e.g. from `bfbo find --offsets-only`.
+Multiple offsets all refer to the original file layout and are applied
+in a single draft pass.
+
+`bfbo hash` prints each FILE's digest in `sha256sum` format (SHA-256 unless
+--algorithm names another), e.g. to record a file before and after an edit.
+A FILE of `-` hashes standard input. Other file arguments (plans, reports)
+are always paths.
+xor-sum is refused: its value depends on the buffer size.
+
+With --json, replace, insert and remove (one offset) print the operation
+report as JSON; `bfbo journal FILE` prints a pending crash-recovery journal and
+`recipe show --json` a recipe's plan. `bfbo schema NAME` prints the schema of
+each JSON output.
+
+Recipes are named, versioned plan files (*.recipe) in $BFBO_RECIPES_DIR
+(default ./.bfbo/recipes). NAME picks the highest version.
+
+Any single-file write subcommand accepts
+  --custody-report FILE --case ID --examiner NAME
+to write a chain-of-custody report (SHA-256 before/after, RFC 3339 times),
+signed with HMAC-SHA256 using $BFBO_CUSTODY_KEY.
+
+With --canary, replace, insert, remove, zip-replace, tar-replace and
+sector-replace refuse files whose canary trailer is missing or shows changes
+made outside the tool, and re-sign the canary in the edit's own draft (key:
+$BFBO_CANARY_KEY). --canary-init also installs a canary on a file without.
+
+With --utf8-guard, a write subcommand fails (file unchanged) if the edit would
+leave the file invalid UTF-8.
+
+--buffer-size BYTES (any subcommand) sets the I/O buffer size (default 64 KiB);
+--tiny-buffer uses 64-byte buffers for memory-constrained hosts.
+
+A write subcommand refuses to start if FILE.backup or FILE.draft is left over
+from an earlier run (see `bfbo scan-orphans`); --force overwrites them.
+
+--journal (any write subcommand) writes a crash-recovery journal next to the
+file while it is edited; after a crash, `bfbo recover FILE` completes or rolls
+back the interrupted edit.
+
+--durable (any write subcommand) syncs the draft before the rename and the
+directory after it, so a power loss right after success keeps the edit.
+
+--preserve-timestamps (any write subcommand) keeps the file's access and
+modification times instead of setting them to the time of the edit.
+
+--refuse-symlinks (any write subcommand) fails instead of editing the file a
+symlinked FILE points to.
+
+--drop-behind (any write subcommand) keeps a rewrite of a large file from
+pushing everything else out of the page cache; --unbuffered also writes the
+new file with O_DIRECT, bypassing the cache.
+
+--verification-hash NAME (any write subcommand) picks the checksum kept of
+both files while the new file is verified: xor-sum (default), crc32, sha256,
+or blake3 (built with the blake3 feature).
+
+--keep-backup (any write subcommand) keeps FILE.backup after a successful
+edit as an undo point (`bfbo restore-backup FILE` puts it back).
+
+--rotate-backups N (any write subcommand) keeps the last N backups of each
+file as FILE.backup.1 (newest) to FILE.backup.N.
+
+--timestamped-backups (any write subcommand) keeps a separate backup per
+edit, named FILE.<UTC time>.backup, e.g. data.bin.20240131T120000.backup.
+
+--memory-budget BYTES (any subcommand) checks the worst-case memory of the
+requested features before running and refuses configurations that cannot
+fit or have no bound (e.g. dedup-report, find without --max, offsets on stdin).
+
+If BFBO_NO_WRITE is set (or /etc/bfbo/no-write exists), write subcommands
+always run as --dry-run and exit with code 3; estimate --probe skips the probe
+and exits with code 3 as well.";
+
+/// How a write subcommand runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteMode {
+    /// Perform the operations
+    Execute,
+    /// Validate and list the operations, write nothing (`--dry-run`)
+    DryRun,
+    /// Dry run forced by a host-level write freeze
+    FrozenDryRun,
+}
+
+/// Errors surfaced by the CLI layer, mapped to exit codes.
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Operation(io::Error),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(formatter, "usage error: {}", message),
+            CliError::Operation(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Operation(error)
+    }
+}
+
+/// Runs the CLI with the given arguments (excluding the program name).
+///
+/// # Returns
+/// The process exit code (see `EXIT_*` constants).
+pub fn run_cli(arguments: &[String]) -> i32 {
+    let subcommand_name = arguments.first().map(String::as_str);
+    let is_write_subcommand = matches!(
+        subcommand_name,
+        Some(
+            "replace"
+                | "insert"
+                | "remove"
+                | "zip-replace"
+                | "tar-replace"
+                | "sector-replace"
+                | "direct-write"
+                | "canary-refresh"
+                | "recover"
+                | "restore-backup"
+        )
+    ) || (subcommand_name == Some("recipe")
+        && arguments.get(1).map(String::as_str) == Some("apply"));
+
+    // Write mode: the host freeze overrides any flag
+    let dry_run_requested = arguments.iter().any(|argument| argument == "--dry-run");
+    let mut subcommand_arguments: Vec<String> = arguments
+        .iter()
+        .skip(1)
+        .filter(|argument| !is_write_subcommand || argument.as_str() != "--dry-run")
+        .cloned()
+        .collect();
+
+    // `estimate --probe` writes a scratch file next to the target
+    let writes_probe_file = subcommand_name == Some("estimate")
+        && arguments.iter().any(|argument| argument == "--probe");
+
+    let write_mode = if !is_write_subcommand && !writes_probe_file {
+        WriteMode::Execute
+    } else if let Some(freeze) = detect_host_write_freeze() {
+        eprintln!(
+            "NOTICE: writes are frozen ({}); running as dry run",
+            freeze.describe()
+        );
+        WriteMode::FrozenDryRun
+    } else if dry_run_requested {
+        WriteMode::DryRun
+    } else {
+        WriteMode::Execute
+    };
+
+    let custody_options = if is_write_subcommand {
+        match extract_custody_options(&mut subcommand_arguments) {
+            Ok(options) => options,
+            Err(error) => return exit_code_for_result(Err(error), write_mode),
+        }
+    } else {
+        None
+    };
+
+    let canary_mode = if is_write_subcommand {
+        match extract_canary_mode(subcommand_name, &mut subcommand_arguments) {
+            Ok(mode) => mode,
+            Err(error) => return exit_code_for_result(Err(error), write_mode),
+        }
+    } else {
+        None
+    };
+    let maintain_canary = canary_mode.is_some();
+
+    let memory_budget_limit =
+        match extract_option_value(&mut subcommand_arguments, "--memory-budget")
+            .and_then(|budget_text| budget_text.as_deref().map(parse_count_argument).transpose())
+        {
+            Ok(limit_bytes) => limit_bytes,
+            Err(error) => return exit_code_for_result(Err(error), write_mode),
+        };
+
+    // The budget rides on the config, so the engine admits what each
+    // operation holds against it as well
+    let operation_config =
+        match extract_operation_config(&mut subcommand_arguments, memory_budget_limit) {
+            Ok(config) => config,
+            Err(error) => return exit_code_for_result(Err(error), write_mode),
+        };
+
+    if let Some(limit_bytes) = memory_budget_limit {
+        let admission = memory_consumers_for_subcommand(
+            subcommand_name,
+            &subcommand_arguments,
+            custody_options.is_some(),
+            maintain_canary,
+        )
+        .and_then(|mut consumers| {
+            if operation_config.journal() {
+                // The journal hashes the original and the draft
+                consumers.push(MemoryConsumer::Sha256Hashing);
+            }
+            // Buffer footprints depend on the configured buffer size
+            Ok(with_operation_config(&operation_config, || {
+                MemoryBudget::new(limit_bytes).admit(&consumers)
+            })?)
+        });
+        if let Err(error) = admission {
+            return exit_code_for_result(Err(error), write_mode);
+        }
+    }
+
+    // Canary checks wrap the edit; a custody report wraps both
+    let run_subcommand = || {
+        let dispatch = || dispatch_subcommand(subcommand_name, &subcommand_arguments, write_mode);
+        match canary_mode {
+            Some(install) => run_with_canary_maintenance(&subcommand_arguments, install, dispatch),
+            None => dispatch(),
+        }
+    };
+    // The config scope itself never fails; the subcommand result passes through
+    let result = with_operation_config(&operation_config, || {
+        Ok(match &custody_options {
+            Some(options) => run_with_custody_report(
+                options,
+                subcommand_name,
+                &subcommand_arguments,
+                write_mode,
+                run_subcommand,
+            ),
+            None => run_subcommand(),
+        })
+    })
+    .unwrap_or_else(|error| Err(CliError::Operation(error)));
+
+    exit_code_for_result(result, write_mode)
+}
+
+/// Routes to the subcommand handler.
+fn dispatch_subcommand(
+    subcommand_name: Option<&str>,
+    subcommand_arguments: &[String],
+    write_mode: WriteMode,
+) -> Result<(), CliError> {
+    match subcommand_name {
+        Some("find") => run_find(subcommand_arguments),
+        Some("replace") => run_replace(subcommand_arguments, write_mode),
+        Some("insert") => run_insert(subcommand_arguments, write_mode),
+        Some("remove") => run_remove(subcommand_arguments, write_mode),
+        Some("zip-replace") => run_zip_replace(subcommand_arguments, write_mode),
+        Some("sector-replace") => run_sector_replace(subcommand_arguments, write_mode),
+        Some("direct-write") => run_direct_write(subcommand_arguments, write_mode),
+        Some("tar-replace") => run_tar_replace(subcommand_arguments, write_mode),
+        Some("partitions") => run_partitions(subcommand_arguments),
+        Some("hash") => run_hash(subcommand_arguments),
+        Some("resolve") => run_resolve(subcommand_arguments),
+        Some("dedup-report") => run_dedup_report(subcommand_arguments),
+        Some("estimate") => run_estimate(subcommand_arguments, write_mode),
+        Some("custody-verify") => run_custody_verify(subcommand_arguments),
+        Some("audit-backups") => run_audit_backups(subcommand_arguments),
+        #[cfg(feature = "soak-test")]
+        Some("soak-test") => run_soak(subcommand_arguments),
+        Some("check-canary") => run_check_canary(subcommand_arguments),
+        Some("canary-refresh") => run_canary_refresh(subcommand_arguments, write_mode),
+        Some("recover") => run_recover(subcommand_arguments, write_mode),
+        Some("journal") => run_journal(subcommand_arguments),
+        Some("restore-backup") => run_restore_backup(subcommand_arguments, write_mode),
+        Some("scan-orphans") => run_scan_orphans(subcommand_arguments),
+        Some("invariants") => run_invariants(subcommand_arguments),
+        Some("schema") => run_schema(subcommand_arguments),
+        Some("recipe") => run_recipe(subcommand_arguments, write_mode),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE_TEXT);
+            Ok(())
+        }
+        Some(other) => Err(CliError::Usage(format!("Unknown subcommand: {}", other))),
+        None => Err(CliError::Usage("Missing subcommand".to_string())),
+    }
+}
+
+/// Lists what a subcommand will hold in memory, for `--memory-budget`.
+///
+/// Sizes come from the arguments alone (and file metadata for plans), so
+/// the check runs before anything is read or written. Malformed arguments
+/// are left for the subcommand itself to report.
+fn memory_consumers_for_subcommand(
+    subcommand_name: Option<&str>,
+    subcommand_arguments: &[String],
+    custody_requested: bool,
+    canary_requested: bool,
+) -> Result<Vec<MemoryConsumer>, CliError> {
+    let positional: Vec<&str> = subcommand_arguments
+        .iter()
+        .map(String::as_str)
+        .filter(|argument| !argument.starts_with("--"))
+        .collect();
+    // Hex payload length without parsing (two digits per byte)
+    let hex_byte_count = |index: usize| {
+        positional.get(index).map_or(0, |token| {
+            token
+                .trim_start_matches("0x")
+                .trim_start_matches("0X")
+                .len()
+                / 2
+        })
+    };
+    let offset_count = |fixed_arguments: usize| {
+        let offsets = positional.get(fixed_arguments..).unwrap_or(&[]);
+        if offsets.contains(&"-") {
+            None
+        } else {
+            Some(offsets.len())
+        }
+    };
+
+    let mut consumers = Vec::new();
+    match subcommand_name {
+        Some("find") => {
+            let max_matches = subcommand_arguments
+                .iter()
+                .position(|argument| argument == "--max")
+                .and_then(|index| subcommand_arguments.get(index + 1))
+                .map(|value| parse_count_argument(value))
+                .transpose()?;
+            consumers.push(MemoryConsumer::PatternSearch {
+                pattern_length: hex_byte_count(1),
+                max_matches,
+            });
+        }
+        Some("replace") | Some("insert") | Some("remove") => {
+            let fixed_arguments = if subcommand_name == Some("remove") {
+                1
+            } else {
+                2
+            };
+            let offset_count = offset_count(fixed_arguments);
+            consumers.push(MemoryConsumer::DraftWorkflow);
+            consumers.push(MemoryConsumer::OffsetList { offset_count });
+            if subcommand_arguments
+                .iter()
+                .any(|argument| argument == "--diff-report")
+            {
+                // The report path is positional-looking; one fewer offset
+                let region_count = offset_count.map_or(0, |count| count.saturating_sub(1));
+                let format = if subcommand_arguments
+                    .iter()
+                    .any(|argument| argument == "--html")
+                {
+                    DiffReportFormat::Html
+                } else {
+                    DiffReportFormat::Text
+                };
+                consumers.push(MemoryConsumer::ReplacementList {
+                    entry_count: region_count,
+                    total_new_bytes: region_count,
+                });
+                consumers.push(MemoryConsumer::DiffReport {
+                    region_count,
+                    total_region_bytes: region_count,
+                    context_bytes: DEFAULT_DIFF_CONTEXT_BYTES,
+                    format,
+                });
+            }
+        }
+        Some("zip-replace") | Some("tar-replace") => {
+            consumers.push(MemoryConsumer::DraftWorkflow);
+            consumers.push(MemoryConsumer::ReplacementList {
+                entry_count: 1,
+                total_new_bytes: hex_byte_count(3),
+            });
+        }
+        Some("sector-replace") => {
+            consumers.push(MemoryConsumer::DraftWorkflow);
+            consumers.push(MemoryConsumer::Sha256Hashing);
+            consumers.push(MemoryConsumer::ReplacementList {
+                entry_count: 1,
+                total_new_bytes: hex_byte_count(2),
+            });
+        }
+        Some("direct-write") => {
+            // The new bytes and the old ones they replace
+            consumers.push(MemoryConsumer::ReplacementList {
+                entry_count: 2,
+                total_new_bytes: hex_byte_count(2).saturating_mul(2),
+            });
+        }
+        Some("canary-refresh") => {
+            consumers.push(MemoryConsumer::DraftWorkflow);
+            consumers.push(MemoryConsumer::Sha256Hashing);
+        }
+        Some("check-canary")
+        | Some("hash")
+        | Some("custody-verify")
+        | Some("audit-backups")
+        | Some("recover")
+        | Some("scan-orphans")
+        | Some("restore-backup") => {
+            consumers.push(MemoryConsumer::Sha256Hashing);
+        }
+        Some("estimate") => {
+            let text_bytes = match positional.get(1) {
+                Some(plan_argument) => std::fs::metadata(plan_argument)?.len() as usize,
+                None => 0,
+            };
+            consumers.push(MemoryConsumer::PlanText { text_bytes });
+        }
+        Some("recipe") => {
+            // Every recipe in the directory is loaded to pick one
+            let mut text_bytes: usize = 0;
+            if let Ok(entries) = std::fs::read_dir(recipes_directory()) {
+                for entry in entries {
+                    let entry = entry?;
+                    if entry.path().extension().is_some_and(|ext| ext == "recipe") {
+                        text_bytes = text_bytes.saturating_add(entry.metadata()?.len() as usize);
+                    }
+                }
+            }
+            consumers.push(MemoryConsumer::DraftWorkflow);
+            consumers.push(MemoryConsumer::PlanText { text_bytes });
+        }
+        Some("dedup-report") => consumers.push(MemoryConsumer::DedupIndex),
+        _ => {}
+    }
+
+    if custody_requested {
+        consumers.push(MemoryConsumer::Sha256Hashing);
+        consumers.push(MemoryConsumer::CustodyReport);
+    }
+    if canary_requested {
+        consumers.push(MemoryConsumer::Sha256Hashing);
+    }
+    Ok(consumers)
+}
+
+/// Maps a subcommand result to the process exit code, printing errors.
+fn exit_code_for_result(result: Result<(), CliError>, write_mode: WriteMode) -> i32 {
+    match result {
+        Ok(()) if write_mode == WriteMode::FrozenDryRun => EXIT_WRITES_FROZEN,
+        Ok(()) => EXIT_SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("ERROR: {}\n\n{}", message, USAGE_TEXT);
+            EXIT_USAGE_ERROR
+        }
+        Err(CliError::Operation(error)) => {
+            eprintln!("ERROR: {}", error);
+            EXIT_OPERATION_FAILED
+        }
+    }
+}
+
+/// Removes `option VALUE` from `arguments`; returns VALUE if present.
+fn extract_option_value(
+    arguments: &mut Vec<String>,
+    option_name: &str,
+) -> Result<Option<String>, CliError> {
+    let Some(option_index) = arguments
+        .iter()
+        .position(|argument| argument == option_name)
+    else {
+        return Ok(None);
+    };
+    if option_index + 1 >= arguments.len() {
+        return Err(CliError::Usage(format!("{} requires a value", option_name)));
+    }
+    let option_value = arguments.remove(option_index + 1);
+    arguments.remove(option_index);
+    Ok(Some(option_value))
+}
+
+/// Builds the operation config from `--buffer-size BYTES` and `--tiny-buffer`.
+fn extract_operation_config(
+    arguments: &mut Vec<String>,
+    memory_budget_limit: Option<usize>,
+) -> Result<OperationConfig, CliError> {
+    let mut builder = OperationConfig::builder();
+    if let Some(limit_bytes) = memory_budget_limit {
+        builder = builder.memory_budget(limit_bytes);
+    }
+    if let Some(size_text) = extract_option_value(arguments, "--buffer-size")? {
+        builder = builder.buffer_size(parse_count_argument(&size_text)?);
+    }
+    if extract_flag(arguments, "--tiny-buffer") {
+        builder = builder.tiny_buffer();
+    }
+    if extract_flag(arguments, "--force") {
+        builder = builder.force(true);
+    }
+    if extract_flag(arguments, "--journal") {
+        builder = builder.journal(true);
+    }
+    if extract_flag(arguments, "--durable") {
+        builder = builder.durability_mode(DurabilityMode::SyncDraftAndDirectory);
+    }
+    if extract_flag(arguments, "--preserve-timestamps") {
+        builder = builder.preserve_timestamps(true);
+    }
+    if extract_flag(arguments, "--refuse-symlinks") {
+        builder = builder.symlink_policy(SymlinkPolicy::RefuseSymlinks);
+    }
+    if extract_flag(arguments, "--drop-behind") {
+        builder = builder.page_cache_mode(PageCacheMode::DropBehind);
+    }
+    if extract_flag(arguments, "--unbuffered") {
+        builder = builder.page_cache_mode(PageCacheMode::Unbuffered);
+    }
+    if extract_flag(arguments, "--keep-backup") {
+        builder = builder.keep_backup(true);
+    }
+    if extract_flag(arguments, "--timestamped-backups") {
+        builder = builder
+            .backup_naming(BackupNaming::Timestamped)
+            .keep_backup(true);
+    }
+    if let Some(count_text) = extract_option_value(arguments, "--rotate-backups")? {
+        builder = builder.rotate_backups(parse_count_argument(&count_text)?);
+    }
+    if let Some(hash_name) = extract_option_value(arguments, "--verification-hash")? {
+        builder = builder.verification_hash(parse_hash_algorithm(&hash_name)?);
+    }
+    if extract_flag(arguments, "--utf8-guard") {
+        builder = builder.utf8_guard(true);
+    }
+    builder
+        .build()
+        .map_err(|error| match ByteOpError::from_io_error(&error) {
+            // A budget that cannot fit is a refusal, not a usage mistake
+            Some(ByteOpError::MemoryBudgetExceeded { .. }) => CliError::Operation(error),
+            _ => CliError::Usage(error.to_string()),
+        })
+}
+
+/// Removes every occurrence of `flag` from `arguments`; true if any.
+fn extract_flag(arguments: &mut Vec<String>, flag: &str) -> bool {
+    let original_length = arguments.len();
+    arguments.retain(|argument| argument != flag);
+    arguments.len() != original_length
+}
+
+// =========================================
+// Canary Maintenance
+// =========================================
+
+/// Reads `--canary` / `--canary-init` from a write subcommand's arguments.
+///
+/// # Returns
+/// - `Ok(None)` without either flag
+/// - `Ok(Some(install))`, `install` being true for `--canary-init`
+/// - `Err(CliError::Usage)` if the subcommand does not edit through a draft
+fn extract_canary_mode(
+    subcommand_name: Option<&str>,
+    subcommand_arguments: &mut Vec<String>,
+) -> Result<Option<bool>, CliError> {
+    let install = extract_flag(subcommand_arguments, "--canary-init");
+    let refresh = extract_flag(subcommand_arguments, "--canary");
+    if !install && !refresh {
+        return Ok(None);
+    }
+    // The trailer is re-signed inside the edit's draft
+    let edits_through_draft = matches!(
+        subcommand_name,
+        Some("replace" | "insert" | "remove" | "zip-replace" | "tar-replace" | "sector-replace")
+    );
+    if !edits_through_draft {
+        return Err(CliError::Usage(format!(
+            "--canary does not apply to {}; it needs a subcommand that edits through a draft",
+            subcommand_name.unwrap_or_default()
+        )));
+    }
+    Ok(Some(install))
+}
+
+/// Wraps a single-file write subcommand with canary checks (`--canary`).
+///
+/// Refuses to edit a file whose canary shows out-of-band changes, then runs
+/// the edit with a `CanarySeal`, so the draft is re-signed before its one
+/// rename. A file without a canary is refused unless `install` is set
+/// (`--canary-init`). Dry runs only check.
+fn run_with_canary_maintenance<RunSubcommand>(
+    subcommand_arguments: &[String],
+    install: bool,
+    run_subcommand: RunSubcommand,
+) -> Result<(), CliError>
+where
+    RunSubcommand: FnOnce() -> Result<(), CliError>,
+{
+    let target_argument = subcommand_arguments
+        .first()
+        .ok_or_else(|| CliError::Usage("Missing target file".to_string()))?;
+    let target_path = PathBuf::from(target_argument);
+    let canary_key = canary_key_from_environment()?;
+
+    let canary_seal = match check_canary(&target_path, &canary_key)? {
+        CanaryStatus::Valid => CanarySeal::refresh(canary_key),
+        CanaryStatus::Absent if install => CanarySeal::install(canary_key),
+        CanaryStatus::Absent => {
+            return Err(CliError::Operation(io::Error::new(
+                io::ErrorKind::NotFound,
+                "No canary present; pass --canary-init to install one with this edit",
+            )));
+        }
+        CanaryStatus::Tampered => {
+            return Err(CliError::Operation(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Canary mismatch: file changed outside the tool since the last sanctioned edit; \
+                 inspect it, then run canary-refresh to accept its current state",
+            )));
+        }
+    };
+
+    // The subcommand result passes through the config scope
+    with_operation_config(
+        &active_operation_config().with_canary_seal(canary_seal),
+        || Ok(run_subcommand()),
+    )?
+}
+
+/// `bfbo check-canary <FILE>` (key from `BFBO_CANARY_KEY`)
+fn run_check_canary(arguments: &[String]) -> Result<(), CliError> {
+    let [file_argument] = arguments else {
+        return Err(CliError::Usage("check-canary expects <FILE>".to_string()));
+    };
+    let canary_key = canary_key_from_environment()?;
+
+    let canary_status = check_canary(Path::new(file_argument), &canary_key)?;
+    println!("{}\t{}", canary_status.name(), file_argument);
+    match canary_status {
+        CanaryStatus::Valid => Ok(()),
+        CanaryStatus::Absent => Err(CliError::Operation(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No canary present",
+        ))),
+        CanaryStatus::Tampered => Err(CliError::Operation(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Canary mismatch: file changed outside the tool",
+        ))),
+    }
+}
+
+/// `bfbo canary-refresh <FILE>`: install or re-sign the canary
+fn run_canary_refresh(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let [file_argument] = arguments else {
+        return Err(CliError::Usage("canary-refresh expects <FILE>".to_string()));
+    };
+    let canary_key = canary_key_from_environment()?;
+    let file_path = PathBuf::from(file_argument);
+
+    if write_mode != WriteMode::Execute {
+        println!(
+            "DRY-RUN: would refresh canary of {} (currently {})",
+            file_argument,
+            check_canary(&file_path, &canary_key)?.name()
+        );
+        return Ok(());
+    }
+
+    refresh_canary(file_path, &canary_key)?;
+    Ok(())
+}
+
+/// `bfbo recover <FILE>`: complete or roll back an interrupted operation
+fn run_recover(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let [file_argument] = arguments else {
+        return Err(CliError::Usage("recover expects <FILE>".to_string()));
+    };
+    let file_path = PathBuf::from(file_argument);
+
+    if let Some((journal, action)) = plan_direct_write_recovery(&file_path)? {
+        let prefix = if write_mode == WriteMode::Execute {
+            recover_direct_write(file_path)?;
+            ""
+        } else {
+            "DRY-RUN: would "
+        };
+        println!(
+            "{}{}\tJournaled Direct Write\t{} byte(s) at {}\t{}",
+            prefix,
+            action.name(),
+            journal.new_bytes.len(),
+            journal.offset,
+            file_argument
+        );
+        return Ok(());
+    }
+
+    let recovery_plan = if write_mode == WriteMode::Execute {
+        recover(file_path)?
+    } else {
+        plan_recovery(&file_path)?
+    };
+    let prefix = if write_mode == WriteMode::Execute {
+        ""
+    } else {
+        "DRY-RUN: would "
+    };
+    match recovery_plan {
+        None => println!("nothing-to-recover\t{}", file_argument),
+        Some(plan) => println!(
+            "{}{}\t{}\tinterrupted after {}\t{}",
+            prefix,
+            plan.action.name(),
+            plan.journal.operation_name,
+            plan.journal.phase.name(),
+            file_argument
+        ),
+    }
+    Ok(())
+}
+
+/// `bfbo journal <FILE>`: print the pending journal as JSON
+fn run_journal(arguments: &[String]) -> Result<(), CliError> {
+    let [file_argument] = arguments else {
+        return Err(CliError::Usage("journal expects <FILE>".to_string()));
+    };
+    let journal = read_journal(Path::new(file_argument))?.ok_or_else(|| {
+        CliError::Operation(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No pending journal for {}", file_argument),
+        ))
+    })?;
+    println!("{}", format_journal_as_json(&journal));
+    Ok(())
+}
+
+/// `bfbo restore-backup <FILE>`: put the file's backup back over it
+fn run_restore_backup(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let [file_argument] = arguments else {
+        return Err(CliError::Usage("restore-backup expects <FILE>".to_string()));
+    };
+    let file_path = PathBuf::from(file_argument);
+
+    if write_mode != WriteMode::Execute {
+        let artifact_paths = build_artifact_paths(&file_path)?;
+        match backup_to_restore(&file_path, artifact_paths)? {
+            Some(backup_file_path) => println!(
+                "DRY-RUN: would restore {} over {}",
+                backup_file_path.display(),
+                file_argument
+            ),
+            None => println!("DRY-RUN: no backup to restore for {}", file_argument),
+        }
+        return Ok(());
+    }
+
+    let restored = restore_from_backup(file_path)?;
+    println!(
+        "restored\t{}\t{}{}",
+        file_argument,
+        digest_to_hex(&restored.sha256),
+        if restored.checked_against_manifest {
+            "\tmanifest-verified"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// `bfbo scan-orphans <DIR>`: list leftover artifacts and the actions offered
+fn run_scan_orphans(arguments: &[String]) -> Result<(), CliError> {
+    let [directory_argument] = arguments else {
+        return Err(CliError::Usage("scan-orphans expects <DIR>".to_string()));
+    };
+
+    let reports = scan_for_orphans(Path::new(directory_argument))?;
+    for report in &reports {
+        let action_names: Vec<&str> = report.actions.iter().map(OrphanAction::name).collect();
+        let actions_text = if action_names.is_empty() {
+            "inspect-manually".to_string()
+        } else {
+            action_names.join(",")
+        };
+        println!(
+            "{}\t{}\t{}",
+            report.state.name(),
+            actions_text,
+            report.target_path.display()
+        );
+    }
+    println!("orphans={}", reports.len());
+    Ok(())
+}
+
+// =========================================
+// Chain of Custody
+// =========================================
+
+/// `--custody-report FILE --case ID --examiner NAME` on a write subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CustodyOptions {
+    report_path: PathBuf,
+    case_identifier: String,
+    examiner_name: String,
+}
+
+/// Removes the custody options from `arguments`, if present.
+///
+/// All three must be given together.
+fn extract_custody_options(
+    arguments: &mut Vec<String>,
+) -> Result<Option<CustodyOptions>, CliError> {
+    let mut report_path: Option<String> = None;
+    let mut case_identifier: Option<String> = None;
+    let mut examiner_name: Option<String> = None;
+
+    let mut remaining_arguments: Vec<String> = Vec::with_capacity(arguments.len());
+    let mut argument_index = 0;
+    while argument_index < arguments.len() {
+        let option_slot = match arguments[argument_index].as_str() {
+            "--custody-report" => Some(&mut report_path),
+            "--case" => Some(&mut case_identifier),
+            "--examiner" => Some(&mut examiner_name),
+            _ => None,
+        };
+        match option_slot {
+            Some(slot) => {
+                let value = arguments.get(argument_index + 1).ok_or_else(|| {
+                    CliError::Usage(format!("{} requires a value", arguments[argument_index]))
+                })?;
+                *slot = Some(value.clone());
+                argument_index += 2;
+            }
+            None => {
+                remaining_arguments.push(arguments[argument_index].clone());
+                argument_index += 1;
+            }
+        }
+    }
+    *arguments = remaining_arguments;
+
+    match (report_path, case_identifier, examiner_name) {
+        (None, None, None) => Ok(None),
+        (Some(report_path), Some(case_identifier), Some(examiner_name)) => {
+            Ok(Some(CustodyOptions {
+                report_path: PathBuf::from(report_path),
+                case_identifier,
+                examiner_name,
+            }))
+        }
+        _ => Err(CliError::Usage(
+            "--custody-report, --case, and --examiner must be given together".to_string(),
+        )),
+    }
+}
+
+/// Runs one write subcommand inside a custody session and writes the report.
+///
+/// The report is written even if the operation fails, recording the failure.
+fn run_with_custody_report<RunSubcommand>(
+    options: &CustodyOptions,
+    subcommand_name: Option<&str>,
+    subcommand_arguments: &[String],
+    write_mode: WriteMode,
+    run_subcommand: RunSubcommand,
+) -> Result<(), CliError>
+where
+    RunSubcommand: FnOnce() -> Result<(), CliError>,
+{
+    if subcommand_name == Some("recipe") {
+        return Err(CliError::Usage(
+            "custody reports cover single-file subcommands, not recipe apply".to_string(),
+        ));
+    }
+    let target_argument = subcommand_arguments
+        .first()
+        .ok_or_else(|| CliError::Usage("Missing target file".to_string()))?;
+
+    let mut session = CustodySession::begin(&options.case_identifier, &options.examiner_name)?;
+    let mut action_text = format!(
+        "{} {}",
+        subcommand_name.unwrap_or_default(),
+        subcommand_arguments.join(" ")
+    );
+    if write_mode != WriteMode::Execute {
+        action_text.push_str(" (dry run, nothing written)");
+    }
+
+    let operation_result =
+        session.record_operation(Path::new(target_argument), &action_text, run_subcommand);
+
+    let signing_key = std::env::var(CUSTODY_KEY_ENVIRONMENT_VARIABLE).ok();
+    if signing_key.is_none() {
+        eprintln!(
+            "NOTICE: {} is not set; custody report is UNSIGNED",
+            CUSTODY_KEY_ENVIRONMENT_VARIABLE
+        );
+    }
+    let report_text = session.render_report(signing_key.as_deref().map(str::as_bytes));
+    std::fs::write(&options.report_path, report_text)?;
+
+    operation_result
+}
+
+/// `bfbo custody-verify <REPORT>` (key from `BFBO_CUSTODY_KEY`)
+fn run_custody_verify(arguments: &[String]) -> Result<(), CliError> {
+    let [report_argument] = arguments else {
+        return Err(CliError::Usage(
+            "custody-verify expects <REPORT>".to_string(),
+        ));
+    };
+    let signing_key = std::env::var(CUSTODY_KEY_ENVIRONMENT_VARIABLE).map_err(|_| {
+        CliError::Usage(format!(
+            "{} must be set to verify a report",
+            CUSTODY_KEY_ENVIRONMENT_VARIABLE
+        ))
+    })?;
+
+    let report_text = std::fs::read_to_string(report_argument)?;
+    if verify_custody_report(&report_text, signing_key.as_bytes())? {
+        println!("OK: signature matches");
+        Ok(())
+    } else {
+        Err(CliError::Operation(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Signature does not match; report altered or wrong key",
+        )))
+    }
+}
+
+/// `bfbo soak-test`: randomized operations checked against a reference model
+#[cfg(feature = "soak-test")]
+fn run_soak(arguments: &[String]) -> Result<(), CliError> {
+    let mut arguments = arguments.to_vec();
+    let seconds = extract_option_value(&mut arguments, "--seconds")?
+        .map(|value| parse_offset_argument(&value))
+        .transpose()?
+        .unwrap_or(60);
+    let max_iterations = extract_option_value(&mut arguments, "--iterations")?
+        .map(|value| parse_count_argument(&value))
+        .transpose()?;
+    let seed = match extract_option_value(&mut arguments, "--seed")? {
+        Some(value) => parse_offset_argument(&value)?,
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or(0),
+    };
+    let scratch_directory = extract_option_value(&mut arguments, "--dir")?
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("bfbo-soak"));
+    if !arguments.is_empty() {
+        return Err(CliError::Usage(format!(
+            "soak-test: unexpected arguments {:?}",
+            arguments
+        )));
+    }
+
+    println!(
+        "soak-test seed={} dir={}",
+        seed,
+        scratch_directory.display()
+    );
+    let summary = run_soak_test(&SoakConfig {
+        seed,
+        duration: std::time::Duration::from_secs(seconds),
+        max_iterations,
+        scratch_directory,
+        max_file_size: 4096,
+    })?;
+    println!(
+        "OK: seed={} iterations={} elapsed={:.1}s",
+        summary.seed,
+        summary.iterations,
+        summary.elapsed.as_secs_f64()
+    );
+    Ok(())
+}
+
+/// `bfbo audit-backups <STORE>`: re-hash retained backups against the manifest
+fn run_audit_backups(arguments: &[String]) -> Result<(), CliError> {
+    let [store_argument] = arguments else {
+        return Err(CliError::Usage("audit-backups expects <STORE>".to_string()));
+    };
+
+    let report = audit_backup_store(Path::new(store_argument))?;
+    for entry in &report.entries {
+        match &entry.status {
+            BackupAuditStatus::Corrupted { actual_sha256_hex } => println!(
+                "{}\t{}\trecorded={} actual={}",
+                entry.status.name(),
+                entry.relative_path.display(),
+                entry.recorded_sha256_hex,
+                actual_sha256_hex
+            ),
+            status => println!("{}\t{}", status.name(), entry.relative_path.display()),
+        }
+    }
+    println!(
+        "checked={} ok={} corrupted={} missing={}",
+        report.entries.len(),
+        report.count_with_status("OK"),
+        report.count_with_status("CORRUPTED"),
+        report.count_with_status("MISSING")
+    );
+
+    if report.is_clean() {
+        Ok(())
+    } else {
+        Err(CliError::Operation(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Backup store audit found corrupted or missing backups",
+        )))
+    }
+}
+
+// =========================================
+// Subcommands
+// =========================================
+
+/// `bfbo zip-replace <ARCHIVE> <MEMBER> <OFFSET> <HEX-BYTES>`
+///
+/// OFFSET is relative to the start of the member's data.
+fn run_zip_replace(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let [
+        archive_argument,
+        member_name,
+        offset_argument,
+        bytes_argument,
+    ] = arguments
+    else {
+        return Err(CliError::Usage(
+            "zip-replace expects <ARCHIVE> <MEMBER> <OFFSET> <HEX-BYTES>".to_string(),
+        ));
+    };
+    let archive_path = PathBuf::from(archive_argument);
+    let member_offset = parse_offset_argument(offset_argument)?;
+    let new_bytes = parse_hex_bytes(bytes_argument)?;
+
+    if write_mode != WriteMode::Execute {
+        let member = locate_zip_member(&archive_path, member_name)?;
+        println!(
+            "DRY-RUN: would replace {} byte(s) at member offset {} (archive offset {}) of `{}` \
+             (method {}, {} bytes) and fix up its CRC-32",
+            new_bytes.len(),
+            member_offset,
+            member.data_offset + member_offset,
+            member_name,
+            member.compression_method,
+            member.compressed_size
+        );
+        return Ok(());
+    }
+
+    let new_crc32 =
+        edit_stored_zip_member(archive_path, member_name, &[(member_offset, new_bytes)])?;
+    println!("{}\tCRC-32 {:08X}", member_name, new_crc32);
+    Ok(())
+}
+
+/// `bfbo direct-write <FILE> <OFFSET> <HEX-BYTES>`
+///
+/// Overwrites the bytes in place with a journal instead of a draft (see
+/// `direct_write.rs`), for files too large to rewrite.
+fn run_direct_write(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let [file_argument, offset_argument, bytes_argument] = arguments else {
+        return Err(CliError::Usage(
+            "direct-write expects <FILE> <OFFSET> <HEX-BYTES>".to_string(),
+        ));
+    };
+    let file_path = PathBuf::from(file_argument);
+    let offset = parse_offset_argument(offset_argument)?;
+    let new_bytes = parse_hex_bytes(bytes_argument)?;
+
+    if write_mode != WriteMode::Execute {
+        println!(
+            "DRY-RUN: would write {} byte(s) in place at {} of {}",
+            new_bytes.len(),
+            offset,
+            file_argument
+        );
+        return Ok(());
+    }
+    write_bytes_directly(file_path, offset, &new_bytes)?;
+    Ok(())
+}
+
+/// `bfbo sector-replace <IMAGE> <OFFSET> <HEX-BYTES> --sector-size N [--expand]`
+///
+/// Prints one line per touched sector: index, offset, length, and SHA-256
+/// before and after the edit.
+fn run_sector_replace(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut sector_size: Option<usize> = None;
+    let mut straddle_policy = SectorStraddlePolicy::Reject;
+
+    let mut argument_index = 0;
+    while argument_index < arguments.len() {
+        match arguments[argument_index].as_str() {
+            "--expand" => straddle_policy = SectorStraddlePolicy::Expand,
+            "--sector-size" => {
+                argument_index += 1;
+                let value = arguments
+                    .get(argument_index)
+                    .ok_or_else(|| CliError::Usage("--sector-size requires a value".to_string()))?;
+                sector_size = Some(parse_count_argument(value)?);
+            }
+            other => positional.push(other),
+        }
+        argument_index += 1;
+    }
+
+    let (&[image_argument, offset_argument, bytes_argument], Some(sector_size)) =
+        (&positional[..], sector_size)
+    else {
+        return Err(CliError::Usage(
+            "sector-replace expects <IMAGE> <OFFSET> <HEX-BYTES> --sector-size N [--expand]"
+                .to_string(),
+        ));
+    };
+    let image_path = PathBuf::from(image_argument);
+    let edits = vec![(
+        parse_offset_argument(offset_argument)?,
+        parse_hex_bytes(bytes_argument)?,
+    )];
+
+    if write_mode != WriteMode::Execute {
+        let image_size = validate_target_file(&image_path)?;
+        let touched_sectors = plan_sector_edits(image_size, sector_size, &edits, straddle_policy)?;
+        println!(
+            "DRY-RUN: would replace {} byte(s) at {} touching sector(s) {:?} (sector size {})",
+            edits[0].1.len(),
+            edits[0].0,
+            touched_sectors,
+            sector_size
+        );
+        return Ok(());
+    }
+
+    let report = replace_ranges_sector_aligned(image_path, sector_size, &edits, straddle_policy)?;
+    println!("sector\toffset\tlength\tsha256_before\tsha256_after");
+    for record in &report.sector_records {
+        println!("{}", record.format_as_line());
+    }
+    Ok(())
+}
+
+/// `bfbo tar-replace <ARCHIVE> <MEMBER> <OFFSET> <HEX-BYTES> [--touch]`
+///
+/// OFFSET is relative to the start of the member's data. `--touch` sets the
+/// member's modification time to now.
+fn run_tar_replace(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let touch_requested = arguments.iter().any(|argument| argument == "--touch");
+    let positional: Vec<&String> = arguments
+        .iter()
+        .filter(|argument| argument.as_str() != "--touch")
+        .collect();
+    let [
+        archive_argument,
+        member_name,
+        offset_argument,
+        bytes_argument,
+    ] = positional[..]
+    else {
+        return Err(CliError::Usage(
+            "tar-replace expects <ARCHIVE> <MEMBER> <OFFSET> <HEX-BYTES> [--touch]".to_string(),
+        ));
+    };
+    let archive_path = PathBuf::from(archive_argument);
+    let member_offset = parse_offset_argument(offset_argument)?;
+    let new_bytes = parse_hex_bytes(bytes_argument)?;
+
+    if write_mode != WriteMode::Execute {
+        let member = locate_tar_member(&archive_path, member_name)?;
+        println!(
+            "DRY-RUN: would replace {} byte(s) at member offset {} (archive offset {}) of `{}` \
+             ({} bytes) and recompute its header checksum",
+            new_bytes.len(),
+            member_offset,
+            member.data_offset + member_offset,
+            member_name,
+            member.member_size
+        );
+        return Ok(());
+    }
+
+    let new_modification_time = if touch_requested {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(io::Error::other)?;
+        Some(since_epoch.as_secs())
+    } else {
+        None
+    };
+
+    edit_tar_member(
+        archive_path,
+        member_name,
+        &[(member_offset, new_bytes)],
+        new_modification_time,
+    )?;
+    Ok(())
+}
+
+/// `bfbo hash <FILE|->... [--algorithm NAME]`: one `sha256sum`-style line
+/// per file (`-` is stdin)
+fn run_hash(arguments: &[String]) -> Result<(), CliError> {
+    let mut file_arguments: Vec<&str> = Vec::new();
+    let mut algorithm = HashAlgorithm::Sha256;
+
+    let mut index = 0;
+    while index < arguments.len() {
+        match arguments[index].as_str() {
+            "--algorithm" => {
+                index += 1;
+                let value = arguments
+                    .get(index)
+                    .ok_or_else(|| CliError::Usage("--algorithm requires a value".to_string()))?;
+                algorithm = parse_hash_algorithm(value)?;
+                if !algorithm.is_chunking_independent() {
+                    return Err(CliError::Usage(format!(
+                        "{} depends on the buffer size; hash with crc32 or sha256",
+                        algorithm.name()
+                    )));
+                }
+            }
+            other => file_arguments.push(other),
+        }
+        index += 1;
+    }
+    if file_arguments.is_empty() {
+        return Err(CliError::Usage("hash expects <FILE|->...".to_string()));
+    }
+
+    for file_argument in file_arguments {
+        let hash_value = if file_argument == "-" {
+            hash_reader(io::stdin().lock(), algorithm)?
+        } else {
+            hash_file(Path::new(file_argument), algorithm)?
+        };
+        println!("{}  {}", hash_value.to_hex(), file_argument);
+    }
+    Ok(())
+}
+
+/// `bfbo partitions <IMAGE>`
+fn run_partitions(arguments: &[String]) -> Result<(), CliError> {
+    let [image_argument] = arguments else {
+        return Err(CliError::Usage("partitions expects <IMAGE>".to_string()));
+    };
+    let (scheme, partitions) = read_partition_table(Path::new(image_argument))?;
+    println!("Scheme: {:?}", scheme);
+    println!(
+        "{:>4}  {:>12}  {:>12}  {:<36}  Name",
+        "Part", "Start", "Size", "Type"
+    );
+    for partition in &partitions {
+        println!(
+            "{:>4}  {:>12}  {:>12}  {:<36}  {}",
+            partition.partition_number,
+            format!("0x{:X}", partition.start_offset),
+            partition.size_in_bytes,
+            partition.type_label,
+            partition.name
+        );
+    }
+    Ok(())
+}
+
+/// `bfbo resolve <IMAGE> p<N>+<OFFSET>`: prints the absolute image offset,
+/// so it can be fed straight into the write subcommands.
+fn run_resolve(arguments: &[String]) -> Result<(), CliError> {
+    let [image_argument, address_argument] = arguments else {
+        return Err(CliError::Usage(
+            "resolve expects <IMAGE> p<N>+<OFFSET>".to_string(),
+        ));
+    };
+    let (partition_text, offset_text) = address_argument
+        .strip_prefix('p')
+        .and_then(|rest| rest.split_once('+'))
+        .ok_or_else(|| {
+            CliError::Usage(format!(
+                "Address must look like p2+0x1BE, got: {}",
+                address_argument
+            ))
+        })?;
+    let partition_number = parse_count_argument(partition_text)?;
+    let offset_in_partition = parse_offset_argument(offset_text)?;
+
+    let absolute_offset = resolve_partition_offset(
+        Path::new(image_argument),
+        partition_number,
+        offset_in_partition,
+    )?;
+    println!("{}", absolute_offset);
+    Ok(())
+}
+
+/// `bfbo estimate <FILE> <PLAN-FILE> [--probe]`
+///
+/// PLAN-FILE uses the plan text format; a single operation is a one-line
+/// plan. `--probe` measures throughput next to FILE to estimate duration;
+/// it is skipped while writes are frozen.
+fn run_estimate(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let probe_requested = arguments.iter().any(|argument| argument == "--probe");
+    let positional: Vec<&String> = arguments
+        .iter()
+        .filter(|argument| argument.as_str() != "--probe")
+        .collect();
+    let [file_argument, plan_argument] = positional[..] else {
+        return Err(CliError::Usage(
+            "estimate expects <FILE> <PLAN-FILE> [--probe]".to_string(),
+        ));
+    };
+
+    let file_path = Path::new(file_argument);
+    let file_size = validate_target_file(file_path)?;
+    let plan_entries = parse_plan_text(&std::fs::read_to_string(plan_argument)?)?;
+
+    let probe = if probe_requested && write_mode == WriteMode::Execute {
+        let probe_directory = match file_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        Some(probe_storage_throughput(
+            probe_directory,
+            DEFAULT_PROBE_SAMPLE_BYTES,
+        )?)
+    } else {
+        None
+    };
+
+    let estimate = estimate_plan_cost(file_size, &plan_entries, probe);
+    println!("operations={}", estimate.operation_count);
+    println!("bytes_to_read={}", estimate.bytes_to_read);
+    println!("bytes_to_write={}", estimate.bytes_to_write);
+    println!("temp_space_needed={}", estimate.temp_space_needed);
+    println!("final_file_size={}", estimate.final_file_size);
+    if let Some(measured) = probe {
+        println!("probe_bytes_per_second={:.0}", measured.bytes_per_second);
+    }
+    if let Some(duration) = estimate.expected_duration_from_probe {
+        println!("expected_duration_seconds={:.3}", duration.as_secs_f64());
+    }
+    Ok(())
+}
+
+/// `bfbo dedup-report <DIR> [--chunk N] [--json]`
+fn run_dedup_report(arguments: &[String]) -> Result<(), CliError> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut chunk_size = DEFAULT_DEDUP_CHUNK_SIZE;
+    let mut json_output = false;
+
+    let mut argument_index = 0;
+    while argument_index < arguments.len() {
+        match arguments[argument_index].as_str() {
+            "--json" => json_output = true,
+            "--chunk" => {
+                argument_index += 1;
+                let value = arguments
+                    .get(argument_index)
+                    .ok_or_else(|| CliError::Usage("--chunk requires a value".to_string()))?;
+                chunk_size = parse_count_argument(value)?;
+            }
+            other => positional.push(other),
+        }
+        argument_index += 1;
+    }
+
+    let [directory_argument] = positional[..] else {
+        return Err(CliError::Usage(
+            "dedup-report expects <DIR> [--chunk N] [--json]".to_string(),
+        ));
+    };
+
+    let report = build_dedup_report(Path::new(directory_argument), chunk_size)?;
+    if json_output {
+        println!("{}", format_dedup_report_as_json(&report));
+        return Ok(());
+    }
+
+    println!(
+        "Scanned {} files, {} chunks of {} bytes; {} duplicate region(s)",
+        report.files_scanned,
+        report.chunks_hashed,
+        report.chunk_size,
+        report.duplicate_regions.len()
+    );
+    for region in &report.duplicate_regions {
+        println!("\n{} bytes at:", region.byte_length);
+        for occurrence in &region.occurrences {
+            println!(
+                "  {}  0x{:X}",
+                occurrence.file_path.display(),
+                occurrence.byte_offset
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `bfbo invariants [--json]`
+fn run_invariants(arguments: &[String]) -> Result<(), CliError> {
+    let invariants = build_invariants();
+    match arguments {
+        [] => print!("{}", format_invariants_as_lines(&invariants)),
+        [flag] if flag == "--json" => println!("{}", format_invariants_as_json(&invariants)),
+        _ => {
+            return Err(CliError::Usage(
+                "invariants accepts only --json".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `bfbo schema <NAME>`: print the JSON Schema of a JSON output
+fn run_schema(arguments: &[String]) -> Result<(), CliError> {
+    let usage = || {
+        CliError::Usage(format!(
+            "schema expects one of: {}",
+            JSON_SCHEMA_NAMES.join(", ")
+        ))
+    };
+    let [output_name] = arguments else {
+        return Err(usage());
+    };
+    let schema = json_schema_for(output_name).ok_or_else(usage)?;
+    println!("{}", schema);
+    Ok(())
+}
+
+/// `bfbo recipe list | show <NAME[@VERSION]> | apply <NAME[@VERSION]> <FILE>...`
+fn run_recipe(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let recipes_dir = recipes_directory();
+    let recipes = load_recipes(&recipes_dir)?;
+
+    match arguments {
+        [action] if action == "list" => {
+            if recipes.is_empty() {
+                eprintln!("No recipes in {}", recipes_dir.display());
+            }
+            for recipe in &recipes {
+                println!("{}@{}\t{}", recipe.name, recipe.version, recipe.description);
+            }
+            Ok(())
+        }
+        [action, selector] if action == "show" => {
+            let recipe = find_recipe(&recipes, selector)?;
+            println!("# {}", recipe.source_path.display());
+            print!("{}", recipe.source_text);
+            Ok(())
+        }
+        [action, selector, json_flag] if action == "show" && json_flag == "--json" => {
+            let recipe = find_recipe(&recipes, selector)?;
+            println!("{}", format_plan_as_json(&recipe.plan_entries));
+            Ok(())
+        }
+        [action, selector, file_arguments @ ..]
+            if action == "apply" && !file_arguments.is_empty() =>
+        {
+            let recipe = find_recipe(&recipes, selector)?;
+            let file_paths: Vec<PathBuf> = file_arguments.iter().map(PathBuf::from).collect();
+
+            if write_mode != WriteMode::Execute {
+                // Guards depend on the state left by earlier entries, so a
+                // dry run lists the plan rather than predicting outcomes
+                for file_path in &file_paths {
+                    std::fs::metadata(file_path)?;
+                    for plan_entry in &recipe.plan_entries {
+                        println!(
+                            "DRY-RUN: would apply {:?} (guards: {:?}) to {}",
+                            plan_entry.operation,
+                            plan_entry.guards,
+                            file_path.display()
+                        );
+                    }
+                }
+                return Ok(());
+            }
+
+            let reports = apply_patch_plan_to_files(
+                &file_paths,
+                &recipe.plan_entries,
+                &OperationHooks::default(),
+            );
+            let mut failed_count = 0;
+            for report in &reports {
+                match &report.operation_result {
+                    Ok(outcomes) => println!("{}\t{:?}", report.file_path.display(), outcomes),
+                    Err(message) => {
+                        failed_count += 1;
+                        eprintln!("{}\tFAILED: {}", report.file_path.display(), message);
+                    }
+                }
+            }
+            if failed_count > 0 {
+                return Err(CliError::Operation(io::Error::other(format!(
+                    "recipe {}@{} failed on {} of {} files",
+                    recipe.name,
+                    recipe.version,
+                    failed_count,
+                    reports.len()
+                ))));
+            }
+            Ok(())
+        }
+        _ => Err(CliError::Usage(
+            "recipe expects list | show <NAME> | apply <NAME> <FILE>...".to_string(),
+        )),
+    }
+}
+
+/// `bfbo find <FILE|-> <HEX-PATTERN> [--offsets-only] [--null-delimited] [--max N]`
+fn run_find(arguments: &[String]) -> Result<(), CliError> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut offsets_only = false;
+    let mut null_delimited = false;
+    let mut max_matches: Option<usize> = None;
+
+    let mut index = 0;
+    while index < arguments.len() {
+        match arguments[index].as_str() {
+            "--offsets-only" => offsets_only = true,
+            "--null-delimited" => null_delimited = true,
+            "--max" => {
+                index += 1;
+                let value = arguments
+                    .get(index)
+                    .ok_or_else(|| CliError::Usage("--max requires a value".to_string()))?;
+                max_matches = Some(parse_count_argument(value)?);
+            }
+            other => positional.push(other),
+        }
+        index += 1;
+    }
+
+    if positional.len() != 2 {
+        return Err(CliError::Usage(
+            "find expects <FILE|-> <HEX-PATTERN>".to_string(),
+        ));
+    }
+
+    let pattern = parse_hex_bytes(positional[1])?;
+    let match_offsets = if positional[0] == "-" {
+        find_pattern_offsets_in_reader(io::stdin().lock(), &pattern, max_matches)?
+    } else {
+        find_pattern_offsets_in_file(&PathBuf::from(positional[0]), &pattern, max_matches)?
+    };
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    let write_result = if null_delimited {
+        match_offsets
+            .iter()
+            .try_for_each(|offset| write!(output, "{}\0", offset))
+    } else if offsets_only {
+        match_offsets
+            .iter()
+            .try_for_each(|offset| writeln!(output, "{}", offset))
+    } else {
+        write_find_table(&mut output, positional[0], &pattern, &match_offsets)
+    };
+
+    // A closed pipe (e.g. `| head -1`) is not an error for the caller
+    match write_result.and_then(|()| output.flush()) {
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => other.map_err(CliError::from),
+    }
+}
+
+/// `bfbo replace <FILE> <HEX-BYTE> <OFFSET>... | -`
+fn run_replace(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let mut arguments = arguments.to_vec();
+    let html_requested = extract_flag(&mut arguments, "--html");
+    let diff_report_path = extract_option_value(&mut arguments, "--diff-report")?;
+    let json_requested = extract_flag(&mut arguments, "--json");
+
+    let (file_path, new_byte_value, offsets) = parse_file_byte_offsets(&arguments, "replace")?;
+    if json_requested && diff_report_path.is_some() {
+        return Err(CliError::Usage(
+            "--json and --diff-report cannot be combined".to_string(),
+        ));
+    }
+
+    if let Some(diff_report_path) = diff_report_path
+        && write_mode == WriteMode::Execute
+    {
+        // Same result as one operation per offset, but in a single draft,
+        // with the report rendered during verification
+        let mut unique_offsets = offsets;
+        unique_offsets.sort_unstable();
+        unique_offsets.dedup();
+        let replacements: Vec<(u64, Vec<u8>)> = unique_offsets
+            .into_iter()
+            .map(|position| (position, vec![new_byte_value]))
+            .collect();
+        let report_format = if html_requested {
+            DiffReportFormat::Html
+        } else {
+            DiffReportFormat::Text
+        };
+        let report_text = replace_ranges_with_diff_report(
+            file_path,
+            &replacements,
+            report_format,
+            DEFAULT_DIFF_CONTEXT_BYTES,
+        )?;
+        std::fs::write(diff_report_path, report_text)?;
+        return Ok(());
+    }
+
+    // One replacement per byte; repeating an offset changes nothing
+    let mut offsets = offsets;
+    offsets.sort_unstable();
+    offsets.dedup();
+    let operations: Vec<ByteOperation> = offsets
+        .into_iter()
+        .map(|position| ByteOperation::Replace {
+            position,
+            new_byte_value,
+        })
+        .collect();
+    run_write_operations(&file_path, &operations, write_mode, json_requested)
+}
+
+/// `bfbo insert <FILE> <HEX-BYTE> <OFFSET>... | -`
+fn run_insert(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let mut arguments = arguments.to_vec();
+    let json_requested = extract_flag(&mut arguments, "--json");
+    let (file_path, new_byte_value, mut offsets) = parse_file_byte_offsets(&arguments, "insert")?;
+    // Highest first, so the dry run (one at a time) keeps each offset in the original layout
+    offsets.sort_unstable_by(|a, b| b.cmp(a));
+    let operations: Vec<ByteOperation> = offsets
+        .into_iter()
+        .map(|position| ByteOperation::Insert {
+            position,
+            new_byte_value,
+        })
+        .collect();
+    run_write_operations(&file_path, &operations, write_mode, json_requested)
+}
+
+/// `bfbo remove <FILE> <OFFSET>... | -`
+fn run_remove(arguments: &[String], write_mode: WriteMode) -> Result<(), CliError> {
+    let mut arguments = arguments.to_vec();
+    let json_requested = extract_flag(&mut arguments, "--json");
+    if arguments.len() < 2 {
+        return Err(CliError::Usage(
+            "remove expects <FILE> <OFFSET>... | -".to_string(),
+        ));
+    }
+    let file_path = PathBuf::from(&arguments[0]);
+    let mut offsets = collect_offsets(&arguments[1..])?;
+    // Highest first, so the dry run (one at a time) keeps each offset in the original layout
+    offsets.sort_unstable_by(|a, b| b.cmp(a));
+    offsets.dedup();
+    let operations: Vec<ByteOperation> = offsets
+        .into_iter()
+        .map(|position| ByteOperation::Remove { position })
+        .collect();
+    run_write_operations(&file_path, &operations, write_mode, json_requested)
+}
+
+/// Executes the operations (several in one draft pass), or (dry run)
+/// prints the change report: every affected offset with its old and new
+/// byte, and the resulting size. The dry run validates the operations as
+/// the real run would.
+///
+/// With `json_requested`, a single executed operation prints its
+/// `OperationReport` as JSON (`bfbo schema report`).
+fn run_write_operations(
+    file_path: &Path,
+    operations: &[ByteOperation],
+    write_mode: WriteMode,
+    json_requested: bool,
+) -> Result<(), CliError> {
+    if json_requested && operations.len() != 1 {
+        return Err(CliError::Usage(
+            "--json reports on a single offset".to_string(),
+        ));
+    }
+    if json_requested && write_mode == WriteMode::Execute {
+        let file_path = file_path.to_path_buf();
+        // Stdout carries only the JSON document
+        let report = with_reporter(Rc::new(ProblemsToStderrReporter), || match operations[0] {
+            ByteOperation::Replace {
+                position,
+                new_byte_value,
+            } => replace_single_byte_in_file_with_report(file_path, position, new_byte_value),
+            ByteOperation::Insert {
+                position,
+                new_byte_value,
+            } => insert_single_byte_into_file_with_report(file_path, position, new_byte_value),
+            ByteOperation::Remove { position } => {
+                remove_single_byte_from_file_with_report(file_path, position)
+            }
+            ByteOperation::Touch => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--json reports on a byte edit",
+            )),
+        })?;
+        println!("{}", format_operation_report_as_json(&report));
+        return Ok(());
+    }
+    if write_mode == WriteMode::Execute {
+        // Several offsets share one draft pass (all in the original layout)
+        match operations {
+            [operation] => apply_byte_operation(file_path, operation)?,
+            _ => apply_operations(file_path.to_path_buf(), operations)?,
+        }
+        return Ok(());
+    }
+
+    let change_report = dry_run_operations(file_path.to_path_buf(), operations)?;
+    for line in change_report.to_string().lines() {
+        println!("DRY-RUN: {}", line);
+    }
+
+    Ok(())
+}
+
+/// Prints problems to stderr and drops every other event, for subcommands
+/// whose stdout is a JSON document.
+struct ProblemsToStderrReporter;
+
+impl Reporter for ProblemsToStderrReporter {
+    fn report(&self, event: &ReportEvent<'_>) {
+        if let ReportEvent::Problem { message } = event {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+// =========================================
+// Argument Parsing Helpers
+// =========================================
+
+/// Parses `<FILE> <HEX-BYTE> <OFFSET>... | -`
+fn parse_file_byte_offsets(
+    arguments: &[String],
+    subcommand_name: &str,
+) -> Result<(PathBuf, u8, Vec<u64>), CliError> {
+    if arguments.len() < 3 {
+        return Err(CliError::Usage(format!(
+            "{} expects <FILE> <HEX-BYTE> <OFFSET>... | -",
+            subcommand_name
+        )));
+    }
+    let file_path = PathBuf::from(&arguments[0]);
+    let new_byte_value = parse_hex_byte(&arguments[1])?;
+    let offsets = collect_offsets(&arguments[2..])?;
+    Ok((file_path, new_byte_value, offsets))
+}
+
+/// Collects offsets from arguments, or from stdin if the only argument is `-`.
+fn collect_offsets(offset_arguments: &[String]) -> Result<Vec<u64>, CliError> {
+    if offset_arguments.len() == 1 && offset_arguments[0] == "-" {
+        let mut stdin_text = String::new();
+        io::stdin().lock().read_to_string(&mut stdin_text)?;
+        return stdin_text
+            .split(|c: char| c.is_whitespace() || c == '\0')
+            .filter(|token| !token.is_empty())
+            .map(parse_offset_argument)
+            .collect();
+    }
+
+    offset_arguments
+        .iter()
+        .map(|token| parse_offset_argument(token))
+        .collect()
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal offset.
+fn parse_offset_argument(token: &str) -> Result<u64, CliError> {
+    parse_offset_text(token).map_err(usage_error)
+}
+
+/// Parses an in-memory count (buffer size, match limit, ...) like an offset.
+fn parse_count_argument(token: &str) -> Result<usize, CliError> {
+    parse_offset_text(token)
+        .and_then(offset_to_usize)
+        .map_err(usage_error)
+}
+
+/// Parses a hash algorithm name (`sha256`, `crc32`, ...).
+fn parse_hash_algorithm(token: &str) -> Result<HashAlgorithm, CliError> {
+    HashAlgorithm::from_name(token).ok_or_else(|| {
+        let known_names: Vec<&str> = HashAlgorithm::ALL
+            .iter()
+            .map(|algorithm| algorithm.name())
+            .collect();
+        CliError::Usage(format!(
+            "Unknown hash algorithm: {} (expected one of: {})",
+            token,
+            known_names.join(", ")
+        ))
+    })
+}
+
+/// Parses a single byte given as hex (`FF`, `0xFF`).
+fn parse_hex_byte(token: &str) -> Result<u8, CliError> {
+    parse_hex_byte_text(token).map_err(usage_error)
+}
+
+/// Parses a hex byte string (`DEADBEEF`, `0xDEADBEEF`, `de ad be ef` as one argument).
+fn parse_hex_bytes(token: &str) -> Result<Vec<u8>, CliError> {
+    parse_hex_bytes_text(token).map_err(usage_error)
+}
+
+/// Turns a parse error into a usage error (the command line was wrong).
+fn usage_error(error: io::Error) -> CliError {
+    CliError::Usage(error.to_string())
+}
+
+/// Writes the human-readable match table for `find`.
+fn write_find_table(
+    output: &mut impl Write,
+    source_name: &str,
+    pattern: &[u8],
+    match_offsets: &[u64],
+) -> io::Result<()> {
+    let pattern_hex: String = pattern.iter().map(|byte| format!("{:02X}", byte)).collect();
+    writeln!(
+        output,
+        "Pattern {} in {}: {} match(es)",
+        pattern_hex,
+        source_name,
+        match_offsets.len()
+    )?;
+    if match_offsets.is_empty() {
+        return Ok(());
+    }
+    writeln!(output, "{:>20}  {:>18}", "offset (dec)", "offset (hex)")?;
+    for offset in match_offsets {
+        writeln!(output, "{:>20}  {:>#18X}", offset, offset)?;
+    }
+    Ok(())
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_arguments(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_offsets_and_hex() {
+        assert_eq!(parse_offset_argument("42").expect("valid offset"), 42);
+        assert_eq!(parse_offset_argument("0x1F").expect("valid offset"), 31);
+        assert!(parse_offset_argument("-1").is_err());
+        assert_eq!(
+            parse_hex_bytes("DEADbeef").expect("valid hex"),
+            vec![0xDE, 0xAD, 0xBE, 0xEF]
+        );
+        assert_eq!(parse_hex_byte("0xff").expect("valid hex"), 0xFF);
+        assert!(parse_hex_bytes("ABC").is_err());
+    }
+
+    #[test]
+    fn test_cli_remove_multiple_offsets_uses_original_layout() {
+        let test_file = std::env::temp_dir().join("test_cli_remove_multi.bin");
+        std::fs::write(&test_file, vec![0x00, 0x0D, 0x0A, 0x0D, 0x0A]).expect("create test file");
+
+        let path_text = test_file.to_string_lossy().to_string();
+        let exit_code = run_cli(&to_arguments(&["remove", &path_text, "1", "3"]));
+
+        assert_eq!(exit_code, EXIT_SUCCESS);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x00, 0x0A, 0x0A]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_cli_journal_and_recover() {
+        let test_file = std::env::temp_dir().join("test_cli_journal.bin");
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("create test file");
+        let path_text = test_file.to_string_lossy().to_string();
+
+        let exit_code = run_cli(&to_arguments(&["remove", &path_text, "1", "--journal"]));
+        assert_eq!(exit_code, EXIT_SUCCESS);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x00, 0x22]
+        );
+
+        // The journal is gone, so there is nothing to recover
+        let exit_code = run_cli(&to_arguments(&["recover", &path_text, "--dry-run"]));
+        assert_eq!(exit_code, EXIT_SUCCESS);
+        let exit_code = run_cli(&to_arguments(&["recover", &path_text]));
+        assert_eq!(exit_code, EXIT_SUCCESS);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_cli_dry_run_writes_nothing() {
+        let test_file = std::env::temp_dir().join("test_cli_dry_run.bin");
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("create test file");
+
+        let path_text = test_file.to_string_lossy().to_string();
+        let exit_code = run_cli(&to_arguments(&[
+            "replace",
+            &path_text,
+            "FF",
+            "0",
+            "2",
+            "--dry-run",
+        ]));
+        assert_eq!(exit_code, EXIT_SUCCESS);
+
+        // Out-of-bounds is still reported in a dry run
+        let exit_code = run_cli(&to_arguments(&["remove", &path_text, "3", "--dry-run"]));
+        assert_eq!(exit_code, EXIT_OPERATION_FAILED);
+
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x00, 0x11, 0x22]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_cli_memory_budget_checked_before_writing() {
+        let test_file = std::env::temp_dir().join("test_cli_memory_budget.bin");
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("create test file");
+        let path_text = test_file.to_string_lossy().to_string();
+
+        let exit_code = run_cli(&to_arguments(&[
+            "replace",
+            &path_text,
+            "FF",
+            "0",
+            "--memory-budget",
+            "100",
+        ]));
+        assert_eq!(exit_code, EXIT_OPERATION_FAILED);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x00, 0x11, 0x22]
+        );
+
+        let exit_code = run_cli(&to_arguments(&[
+            "replace",
+            &path_text,
+            "FF",
+            "0",
+            "--memory-budget",
+            "0x100000",
+        ]));
+        assert_eq!(exit_code, EXIT_SUCCESS);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0xFF, 0x11, 0x22]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_cli_buffer_size_options() {
+        let test_file = std::env::temp_dir().join("test_cli_buffer_size.bin");
+        std::fs::write(&test_file, (0..200u8).collect::<Vec<u8>>()).expect("create test file");
+        let path_text = test_file.to_string_lossy().to_string();
+
+        // The default 64 KiB buffers do not fit in 64 KiB; tiny buffers do
+        let budget_arguments = [
+            "replace",
+            &path_text,
+            "FF",
+            "150",
+            "--memory-budget",
+            "0x10000",
+        ];
+        assert_eq!(
+            run_cli(&to_arguments(&budget_arguments)),
+            EXIT_OPERATION_FAILED
+        );
+        let mut tiny_arguments = budget_arguments.to_vec();
+        tiny_arguments.push("--tiny-buffer");
+        assert_eq!(run_cli(&to_arguments(&tiny_arguments)), EXIT_SUCCESS);
+        assert_eq!(std::fs::read(&test_file).expect("read file")[150], 0xFF);
+
+        assert_eq!(
+            run_cli(&to_arguments(&[
+                "remove",
+                &path_text,
+                "0",
+                "--buffer-size",
+                "7"
+            ])),
+            EXIT_SUCCESS
+        );
+        assert_eq!(std::fs::read(&test_file).expect("read file").len(), 199);
+        assert_eq!(
+            run_cli(&to_arguments(&[
+                "remove",
+                &path_text,
+                "0",
+                "--buffer-size",
+                "0"
+            ])),
+            EXIT_USAGE_ERROR
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_cli_utf8_guard_rejects_split_character() {
+        let test_file = std::env::temp_dir().join("test_cli_utf8_guard.txt");
+        std::fs::write(&test_file, "café").expect("create test file");
+        let path_text = test_file.to_string_lossy().to_string();
+
+        // 0x65 over the lead byte of 'é' leaves a stray continuation byte
+        let exit_code = run_cli(&to_arguments(&[
+            "replace",
+            &path_text,
+            "65",
+            "3",
+            "--utf8-guard",
+        ]));
+        assert_eq!(exit_code, EXIT_OPERATION_FAILED);
+        assert_eq!(
+            std::fs::read_to_string(&test_file).expect("original untouched"),
+            "café"
+        );
+
+        let exit_code = run_cli(&to_arguments(&[
+            "replace",
+            &path_text,
+            "43",
+            "0",
+            "--utf8-guard",
+        ]));
+        assert_eq!(exit_code, EXIT_SUCCESS);
+        assert_eq!(
+            std::fs::read_to_string(&test_file).expect("still UTF-8"),
+            "Café"
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_cli_usage_errors() {
+        assert_eq!(run_cli(&to_arguments(&[])), EXIT_USAGE_ERROR);
+        assert_eq!(run_cli(&to_arguments(&["bogus"])), EXIT_USAGE_ERROR);
+        assert_eq!(
+            run_cli(&to_arguments(&["find", "only-one-arg"])),
+            EXIT_USAGE_ERROR
+        );
+        assert_eq!(
+            run_cli(&to_arguments(&["hash", "Cargo.toml", "--algorithm", "md5"])),
+            EXIT_USAGE_ERROR
+        );
+        assert_eq!(
+            run_cli(&to_arguments(&[
+                "hash",
+                "Cargo.toml",
+                "--algorithm",
+                "xor-sum"
+            ])),
+            EXIT_USAGE_ERROR
+        );
+    }
+}
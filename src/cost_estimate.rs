@@ -0,0 +1,241 @@
+//! Cost estimates for operations and plans
+//!
+//! Every operation rewrites the whole file (backup copy, draft build,
+//! full verification), so cost grows with file size, not edit size. A 40 GB
+//! image with a 50-entry plan can run for hours. `estimate_plan_cost` tells
+//! schedulers and preflight checks what a run will cost before starting it.
+//!
+//! # Cost Model (one operation on a file of S bytes, draft of S' bytes)
+//! - Backup: read S, write S
+//! - Draft build: read S, write S'
+//! - Verification: read S + S'
+//! - Peak temporary space: S (backup) + S' (draft)
+//!
+//! Plan entries each run the full workflow on the file as left by the
+//! previous entry. Guards are assumed to hold (an upper bound), and a
+//! `contains` guard is counted as a full read.
+//!
+//! Durations need a throughput figure: `probe_storage_throughput` times a
+//! short write-and-read-back in the target's directory.
+
+use std::{
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::draft_workflow::{check_chunk_limit, chunk_limit_for};
+use crate::operation_config::active_buffer_size;
+use crate::patch_plan::{ByteOperation, PlanEntry, PlanGuard};
+use crate::util::chunk_length;
+
+/// Default sample size for the throughput probe
+pub const DEFAULT_PROBE_SAMPLE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Estimated I/O for an operation or plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub operation_count: usize,
+    pub bytes_to_read: u64,
+    pub bytes_to_write: u64,
+    /// Peak backup + draft space needed next to the target
+    pub temp_space_needed: u64,
+    /// Size after the last operation
+    pub final_file_size: u64,
+    /// `None` unless a throughput probe was supplied
+    pub expected_duration_from_probe: Option<Duration>,
+}
+
+/// Measured storage throughput in the target's directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThroughputProbe {
+    /// Combined read+write bytes per second through the bucket-brigade path
+    pub bytes_per_second: f64,
+}
+
+/// Estimates the cost of applying one operation to a file of `file_size`.
+pub fn estimate_operation_cost(
+    file_size: u64,
+    operation: &ByteOperation,
+    probe: Option<ThroughputProbe>,
+) -> CostEstimate {
+    estimate_plan_cost(
+        file_size,
+        &[PlanEntry::unconditional(operation.clone())],
+        probe,
+    )
+}
+
+/// Estimates the cost of a plan on a file of `file_size` (all guards hold).
+pub fn estimate_plan_cost(
+    file_size: u64,
+    plan_entries: &[PlanEntry],
+    probe: Option<ThroughputProbe>,
+) -> CostEstimate {
+    let mut current_size = file_size;
+    let mut bytes_to_read: u64 = 0;
+    let mut bytes_to_write: u64 = 0;
+    let mut temp_space_needed: u64 = 0;
+
+    for entry in plan_entries {
+        for guard in &entry.guards {
+            bytes_to_read += match guard {
+                PlanGuard::ByteEquals { .. } => 1,
+                PlanGuard::FileSizeGreaterThan(_) => 0,
+                PlanGuard::PatternPresent(_) => current_size,
+            };
+        }
+
+        let draft_size = match entry.operation {
+            // Hashing pass only: no backup, draft, or temp space
+            ByteOperation::Touch => {
+                bytes_to_read += current_size;
+                continue;
+            }
+            ByteOperation::Replace { .. } => current_size,
+            ByteOperation::Insert { .. } => current_size + 1,
+            ByteOperation::Remove { .. } => current_size.saturating_sub(1),
+        };
+
+        // Backup, draft build, verification
+        bytes_to_read += current_size + current_size + current_size + draft_size;
+        bytes_to_write += current_size + draft_size;
+        temp_space_needed = temp_space_needed.max(current_size + draft_size);
+        current_size = draft_size;
+    }
+
+    let expected_duration_from_probe = probe.map(|measured| {
+        let total_bytes = (bytes_to_read + bytes_to_write) as f64;
+        Duration::from_secs_f64(total_bytes / measured.bytes_per_second.max(1.0))
+    });
+
+    CostEstimate {
+        operation_count: plan_entries.len(),
+        bytes_to_read,
+        bytes_to_write,
+        temp_space_needed,
+        final_file_size: current_size,
+        expected_duration_from_probe,
+    }
+}
+
+/// Times writing and reading back `sample_bytes` in `directory`.
+///
+/// The sample goes through the same bucket-brigade buffer size as real
+/// operations, is synced to storage before reading back, and is removed
+/// afterwards.
+///
+/// # Returns
+/// - `Ok(ThroughputProbe)`
+/// - `Err(io::Error)` if the directory is not writable or I/O fails
+pub fn probe_storage_throughput(
+    directory: &Path,
+    sample_bytes: u64,
+) -> io::Result<ThroughputProbe> {
+    if sample_bytes == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Probe sample must be non-empty",
+        ));
+    }
+
+    let probe_path = directory.join(format!(".bfbo-throughput-probe-{}", std::process::id()));
+    let probe_result = run_throughput_probe(&probe_path, sample_bytes);
+    let _ = fs::remove_file(&probe_path);
+    probe_result
+}
+
+fn run_throughput_probe(probe_path: &Path, sample_bytes: u64) -> io::Result<ThroughputProbe> {
+    let bucket_brigade_buffer = vec![0xA5u8; active_buffer_size()];
+    let started_at = Instant::now();
+
+    let mut probe_file = File::create(probe_path)?;
+    let mut bytes_written: u64 = 0;
+    let chunk_limit = chunk_limit_for(sample_bytes);
+    let mut chunk_number: usize = 0;
+    while bytes_written < sample_bytes {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_write =
+            chunk_length(bucket_brigade_buffer.len(), sample_bytes - bytes_written);
+        probe_file.write_all(&bucket_brigade_buffer[..bytes_to_write])?;
+        bytes_written += bytes_to_write as u64;
+    }
+    probe_file.sync_all()?;
+    drop(probe_file);
+
+    let mut probe_file = File::open(probe_path)?;
+    let mut read_buffer = vec![0u8; active_buffer_size()];
+    let mut chunk_number: usize = 0;
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        if probe_file.read(&mut read_buffer)? == 0 {
+            break;
+        }
+    }
+
+    let elapsed_seconds = started_at.elapsed().as_secs_f64().max(1e-9);
+    Ok(ThroughputProbe {
+        bytes_per_second: (2 * sample_bytes) as f64 / elapsed_seconds,
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_plan_cost_tracks_size_changes() {
+        let plan = vec![
+            PlanEntry::unconditional(ByteOperation::Insert {
+                position: 0,
+                new_byte_value: 0x00,
+            }),
+            PlanEntry::unconditional(ByteOperation::Remove { position: 0 })
+                .only_if(PlanGuard::PatternPresent(vec![0xEF])),
+        ];
+        let estimate = estimate_plan_cost(1000, &plan, None);
+
+        // Insert: 3*1000 + 1001 read, 1000 + 1001 written
+        // Remove: 1001 (guard) + 3*1001 + 1000 read, 1001 + 1000 written
+        assert_eq!(estimate.operation_count, 2);
+        assert_eq!(estimate.bytes_to_read, 4001 + 1001 + 4003);
+        assert_eq!(estimate.bytes_to_write, 2001 + 2001);
+        assert_eq!(estimate.temp_space_needed, 2001);
+        assert_eq!(estimate.final_file_size, 1000);
+        assert_eq!(estimate.expected_duration_from_probe, None);
+
+        let with_probe = estimate_plan_cost(
+            1000,
+            &plan,
+            Some(ThroughputProbe {
+                bytes_per_second: 13_007.0,
+            }),
+        );
+        assert_eq!(
+            with_probe.expected_duration_from_probe,
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_probe_storage_throughput_cleans_up() {
+        let probe_dir = std::env::temp_dir().join("test_cost_estimate_probe_dir");
+        let _ = fs::remove_dir_all(&probe_dir);
+        fs::create_dir_all(&probe_dir).expect("create probe dir");
+
+        let probe = probe_storage_throughput(&probe_dir, 4096).expect("probe should succeed");
+        assert!(probe.bytes_per_second > 0.0);
+        assert_eq!(fs::read_dir(&probe_dir).expect("list").count(), 0);
+
+        let _ = fs::remove_dir_all(&probe_dir);
+    }
+}
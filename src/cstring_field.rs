@@ -0,0 +1,210 @@
+//! Fixed-width C string fields
+//!
+//! Binaries and on-disk structures commonly store text as a NUL-terminated
+//! string inside a fixed-size field (`char name[32]`). Patching one by hand
+//! means writing the string, a terminator, and padding over whatever the old
+//! value left behind, without ever spilling into the next field.
+//! `write_cstring_at` does exactly that as a same-length replace of the
+//! whole field.
+
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{open_at_offset, validate_target_file};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::range_operations::{checked_range_end, splice_byte_range_with_draft_check};
+use crate::reporter::report_check_passed;
+
+/// Writes `string_bytes`, a NUL terminator, and `pad_byte` padding into the
+/// `max_len`-byte field at `field_offset`.
+///
+/// # Overview
+/// The field is always rewritten in full: `string_bytes`, then `0x00`, then
+/// `pad_byte` up to `max_len`. Stale bytes of a longer previous value are
+/// overwritten, and nothing outside the field changes. File length is
+/// unchanged.
+///
+/// # Verification
+/// In addition to the replace verification (length, all bytes outside the
+/// field identical), the field is read back from the draft and decoded as a
+/// C string: the bytes before the first NUL must equal `string_bytes` and
+/// every byte after the terminator must equal `pad_byte`.
+///
+/// # Parameters
+/// - `field_offset`: Zero-indexed start of the field
+/// - `string_bytes`: String content without terminator (`str::as_bytes()`
+///   for text); must not contain NUL
+/// - `max_len`: Field width in bytes, terminator included
+/// - `pad_byte`: Fill for the rest of the field (commonly `0x00` or `0x20`)
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` (`InvalidInput`) if the string plus terminator exceeds
+///   `max_len`, the string contains NUL, or the field extends past EOF;
+///   any workflow error otherwise
+pub fn write_cstring_at(
+    original_file_path: PathBuf,
+    field_offset: u64,
+    string_bytes: &[u8],
+    max_len: usize,
+    pad_byte: u8,
+) -> io::Result<()> {
+    let field_bytes = build_cstring_field(string_bytes, max_len, pad_byte)?;
+
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let field_end = checked_range_end(field_offset, max_len as u64)?;
+    if field_end > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Field {}..{} extends past file size {}",
+                field_offset, field_end, original_file_size
+            ),
+        ));
+    }
+
+    splice_byte_range_with_draft_check(
+        original_file_path,
+        field_offset,
+        max_len as u64,
+        &field_bytes,
+        "C String Write",
+        |draft_path| {
+            verify_cstring_field(draft_path, field_offset, string_bytes, max_len, pad_byte)
+        },
+    )
+}
+
+/// Like `write_cstring_at`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn write_cstring_at_with_config(
+    original_file_path: PathBuf,
+    field_offset: u64,
+    string_bytes: &[u8],
+    max_len: usize,
+    pad_byte: u8,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        write_cstring_at(
+            original_file_path,
+            field_offset,
+            string_bytes,
+            max_len,
+            pad_byte,
+        )
+    })
+}
+
+/// Lays out the field: string, terminator, padding.
+fn build_cstring_field(string_bytes: &[u8], max_len: usize, pad_byte: u8) -> io::Result<Vec<u8>> {
+    if string_bytes.contains(&0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "String contains a NUL byte; it would be truncated when read back",
+        ));
+    }
+    if string_bytes.len() >= max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "String of {} bytes plus terminator exceeds field width {}",
+                string_bytes.len(),
+                max_len
+            ),
+        ));
+    }
+
+    let mut field_bytes = Vec::with_capacity(max_len);
+    field_bytes.extend_from_slice(string_bytes);
+    field_bytes.push(0);
+    field_bytes.resize(max_len, pad_byte);
+    Ok(field_bytes)
+}
+
+/// Decodes the field from the draft and checks string and padding.
+fn verify_cstring_field(
+    draft_path: &Path,
+    field_offset: u64,
+    string_bytes: &[u8],
+    max_len: usize,
+    pad_byte: u8,
+) -> io::Result<()> {
+    let mut draft_field = vec![0u8; max_len];
+    open_at_offset(draft_path, field_offset)?.read_exact(&mut draft_field)?;
+
+    let terminator_index = draft_field.iter().position(|&byte| byte == 0);
+    let string_matches = terminator_index == Some(string_bytes.len())
+        && &draft_field[..string_bytes.len()] == string_bytes;
+    let padding_matches = draft_field[string_bytes.len() + 1..]
+        .iter()
+        .all(|&byte| byte == pad_byte);
+
+    if !string_matches || !padding_matches {
+        // Report the first byte that is not string, terminator, or padding
+        let expected_field_byte = |index: usize| match index.cmp(&string_bytes.len()) {
+            std::cmp::Ordering::Less => string_bytes[index],
+            std::cmp::Ordering::Equal => 0,
+            std::cmp::Ordering::Greater => pad_byte,
+        };
+        let mismatch_index = (0..max_len)
+            .find(|&index| draft_field[index] != expected_field_byte(index))
+            .unwrap_or(0);
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::WrittenBytes,
+            field_offset + mismatch_index as u64,
+            u64::from(expected_field_byte(mismatch_index)),
+            u64::from(draft_field[mismatch_index]),
+        )
+        .into());
+    }
+
+    report_check_passed!(
+        "   ✓ C string field decodes to {} bytes, padded with 0x{:02X}",
+        string_bytes.len(),
+        pad_byte
+    );
+    Ok(())
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_cstring_at_overwrites_whole_field() {
+        let test_file = std::env::temp_dir().join("test_write_cstring_at.bin");
+        std::fs::write(&test_file, b"HDRlongname\0\0\0\0\0TAIL").expect("create test file");
+
+        write_cstring_at(test_file.clone(), 3, b"abc", 13, b' ').expect("write field");
+        assert_eq!(
+            std::fs::read(&test_file).expect("read modified file"),
+            b"HDRabc\0         TAIL".to_vec()
+        );
+
+        // Exactly fills the field with the terminator
+        write_cstring_at(test_file.clone(), 3, b"twelve-chars", 13, 0).expect("full field");
+        assert_eq!(
+            std::fs::read(&test_file).expect("read modified file"),
+            b"HDRtwelve-chars\0TAIL".to_vec()
+        );
+
+        // No room for the terminator, interior NUL, past EOF
+        assert!(write_cstring_at(test_file.clone(), 3, b"thirteen-char", 13, 0).is_err());
+        assert!(write_cstring_at(test_file.clone(), 3, b"a\0b", 13, 0).is_err());
+        assert!(write_cstring_at(test_file.clone(), 10, b"abc", 13, 0).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("read unchanged file"),
+            b"HDRtwelve-chars\0TAIL".to_vec()
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
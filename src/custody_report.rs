@@ -0,0 +1,317 @@
+//! Chain-of-custody reports for forensic users
+//!
+//! When an evidence copy is edited for analysis (e.g. neutralizing a boot
+//! sector before mounting), the change has to be documented in a form that
+//! can be presented later: who, which case, which tool build, what was done,
+//! when, and the file's SHA-256 before and after each operation.
+//!
+//! A `CustodySession` collects operation records; `render_report` produces a
+//! plain-text report that ends in a signature line. The signature is an
+//! HMAC-SHA256 over every preceding line, keyed with the examiner's secret
+//! from `BFBO_CUSTODY_KEY`. This is a shared-secret MAC, not a public-key
+//! signature: anyone holding the key can verify the report (and could forge
+//! one). Without a key the report is marked UNSIGNED.
+//!
+//! Timestamps are RFC 3339 in UTC, second precision.
+
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::hash::sha256::{digest_to_hex, hmac_sha256, sha256_of_file};
+
+/// Environment variable holding the report signing key
+pub const CUSTODY_KEY_ENVIRONMENT_VARIABLE: &str = "BFBO_CUSTODY_KEY";
+
+/// Tool name and version recorded in every report
+pub const CUSTODY_TOOL_VERSION: &str =
+    concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+
+/// Prefix of the final report line
+const SIGNATURE_LINE_PREFIX: &str = "Signature (HMAC-SHA256): ";
+
+/// Signature line value when no key is available
+const UNSIGNED_MARKER: &str = "UNSIGNED";
+
+/// One operation performed during a custody session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustodyOperationRecord {
+    pub timestamp_unix_seconds: u64,
+    /// Free-form description, e.g. the command line
+    pub action: String,
+    pub target_path: PathBuf,
+    /// `None` if the file could not be hashed (e.g. missing)
+    pub pre_operation_sha256: Option<[u8; 32]>,
+    pub post_operation_sha256: Option<[u8; 32]>,
+    /// "success" or "failed: <reason>"
+    pub outcome: String,
+}
+
+/// Collects operation records for one examiner, one case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustodySession {
+    pub case_identifier: String,
+    pub examiner_name: String,
+    pub started_unix_seconds: u64,
+    pub operations: Vec<CustodyOperationRecord>,
+}
+
+impl CustodySession {
+    /// Starts a session now.
+    ///
+    /// # Returns
+    /// - `Err(io::Error)` if the case ID or examiner name is empty or
+    ///   contains control characters (which could forge report lines)
+    pub fn begin(case_identifier: &str, examiner_name: &str) -> io::Result<Self> {
+        for (field_name, field_value) in [("Case ID", case_identifier), ("Examiner", examiner_name)]
+        {
+            if field_value.trim().is_empty() || field_value.chars().any(char::is_control) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} must be non-empty, single-line text", field_name),
+                ));
+            }
+        }
+
+        Ok(CustodySession {
+            case_identifier: case_identifier.to_string(),
+            examiner_name: examiner_name.to_string(),
+            started_unix_seconds: current_unix_seconds(),
+            operations: Vec::new(),
+        })
+    }
+
+    /// Runs `operation` against `target_path` and records it.
+    ///
+    /// The target is hashed immediately before and after. A failed
+    /// operation is recorded too (with its error) and its error returned.
+    pub fn record_operation<Operation, OperationError>(
+        &mut self,
+        target_path: &Path,
+        action: &str,
+        operation: Operation,
+    ) -> Result<(), OperationError>
+    where
+        Operation: FnOnce() -> Result<(), OperationError>,
+        OperationError: Display,
+    {
+        let timestamp_unix_seconds = current_unix_seconds();
+        let pre_operation_sha256 = sha256_of_file(target_path).ok();
+        let operation_result = operation();
+        let post_operation_sha256 = sha256_of_file(target_path).ok();
+
+        let outcome = match &operation_result {
+            Ok(()) => "success".to_string(),
+            Err(error) => format!("failed: {}", error),
+        };
+
+        self.operations.push(CustodyOperationRecord {
+            timestamp_unix_seconds,
+            action: action.to_string(),
+            target_path: target_path.to_path_buf(),
+            pre_operation_sha256,
+            post_operation_sha256,
+            outcome,
+        });
+
+        operation_result
+    }
+
+    /// Renders the report, ending the session now.
+    ///
+    /// # Parameters
+    /// - `signing_key`: HMAC key; `None` produces an UNSIGNED report
+    pub fn render_report(&self, signing_key: Option<&[u8]>) -> String {
+        let mut report_body = String::new();
+        report_body.push_str("CHAIN OF CUSTODY REPORT\n");
+        report_body.push_str(&format!("Case ID: {}\n", self.case_identifier));
+        report_body.push_str(&format!("Examiner: {}\n", self.examiner_name));
+        report_body.push_str(&format!("Tool: {}\n", CUSTODY_TOOL_VERSION));
+        report_body.push_str(&format!(
+            "Session started: {}\n",
+            format_rfc3339_utc(self.started_unix_seconds)
+        ));
+        report_body.push_str(&format!(
+            "Session ended: {}\n",
+            format_rfc3339_utc(current_unix_seconds())
+        ));
+
+        for (operation_index, record) in self.operations.iter().enumerate() {
+            report_body.push_str(&format!("\nOperation {}\n", operation_index + 1));
+            report_body.push_str(&format!(
+                "  Time: {}\n",
+                format_rfc3339_utc(record.timestamp_unix_seconds)
+            ));
+            report_body.push_str(&format!(
+                "  Action: {}\n",
+                escape_control_characters(&record.action)
+            ));
+            report_body.push_str(&format!(
+                "  Target: {}\n",
+                escape_control_characters(&record.target_path.to_string_lossy())
+            ));
+            report_body.push_str(&format!(
+                "  SHA-256 before: {}\n",
+                format_optional_digest(&record.pre_operation_sha256)
+            ));
+            report_body.push_str(&format!(
+                "  SHA-256 after: {}\n",
+                format_optional_digest(&record.post_operation_sha256)
+            ));
+            report_body.push_str(&format!(
+                "  Result: {}\n",
+                escape_control_characters(&record.outcome)
+            ));
+        }
+        report_body.push('\n');
+
+        let signature_text = match signing_key {
+            Some(key) => digest_to_hex(&hmac_sha256(key, report_body.as_bytes())),
+            None => UNSIGNED_MARKER.to_string(),
+        };
+        report_body.push_str(SIGNATURE_LINE_PREFIX);
+        report_body.push_str(&signature_text);
+        report_body.push('\n');
+        report_body
+    }
+}
+
+/// Checks a rendered report's signature against `signing_key`.
+///
+/// # Returns
+/// - `Ok(true)` if the signature matches, `Ok(false)` if it does not
+/// - `Err(io::Error)` if the report has no signature line or is UNSIGNED
+pub fn verify_custody_report(report_text: &str, signing_key: &[u8]) -> io::Result<bool> {
+    let signature_start = report_text.rfind(SIGNATURE_LINE_PREFIX).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Report has no signature line")
+    })?;
+    let (report_body, signature_line) = report_text.split_at(signature_start);
+    let recorded_signature = signature_line[SIGNATURE_LINE_PREFIX.len()..].trim();
+
+    if recorded_signature == UNSIGNED_MARKER {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Report is unsigned",
+        ));
+    }
+
+    let expected_signature = digest_to_hex(&hmac_sha256(signing_key, report_body.as_bytes()));
+    Ok(recorded_signature == expected_signature)
+}
+
+/// Formats Unix seconds as RFC 3339 UTC, e.g. `2024-02-29T23:59:59Z`.
+pub fn format_rfc3339_utc(unix_seconds: u64) -> String {
+    let days_since_epoch = (unix_seconds / 86_400) as i64;
+    let seconds_of_day = unix_seconds % 86_400;
+
+    // Civil-from-days (proleptic Gregorian), eras of 400 years
+    let shifted_days = days_since_epoch + 719_468;
+    let era = shifted_days.div_euclid(146_097);
+    let day_of_era = shifted_days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day_of_month,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60,
+        seconds_of_day % 60
+    )
+}
+
+pub(crate) fn current_unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+fn format_optional_digest(digest: &Option<[u8; 32]>) -> String {
+    match digest {
+        Some(digest_bytes) => digest_to_hex(digest_bytes),
+        None => "(unavailable)".to_string(),
+    }
+}
+
+/// Keeps a field on one line, so it cannot forge report lines.
+fn escape_control_characters(text: &str) -> String {
+    text.chars()
+        .map(|character| {
+            if character.is_control() {
+                character.escape_default().to_string()
+            } else {
+                character.to_string()
+            }
+        })
+        .collect()
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rfc3339_utc() {
+        assert_eq!(format_rfc3339_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339_utc(951_868_799), "2000-02-29T23:59:59Z");
+        assert_eq!(format_rfc3339_utc(1_709_251_199), "2024-02-29T23:59:59Z");
+        assert_eq!(format_rfc3339_utc(4_102_444_800), "2100-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_custody_session_report_signed_and_verified() {
+        let test_file = std::env::temp_dir().join("test_custody_report_target.bin");
+        std::fs::write(&test_file, [0x00, 0x01, 0x02]).expect("create test file");
+
+        let mut session =
+            CustodySession::begin("CASE-2024-017", "J. Doe").expect("session should start");
+        session
+            .record_operation(&test_file, "replace 0xFF at 1", || {
+                std::fs::write(&test_file, [0x00, 0xFF, 0x02])
+            })
+            .expect("operation should succeed");
+        let failed: io::Result<()> = session.record_operation(&test_file, "remove at 9", || {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "out of bounds"))
+        });
+        assert!(failed.is_err());
+
+        let report = session.render_report(Some(b"examiner-secret"));
+        assert!(report.contains("Case ID: CASE-2024-017\n"));
+        assert!(report.contains(&format!(
+            "  SHA-256 after: {}\n",
+            digest_to_hex(&crate::hash::sha256::sha256_of_bytes(&[0x00, 0xFF, 0x02]))
+        )));
+        assert!(report.contains("  Result: failed: out of bounds\n"));
+
+        assert!(verify_custody_report(&report, b"examiner-secret").expect("signed"));
+        assert!(!verify_custody_report(&report, b"wrong-key").expect("signed"));
+        let tampered = report.replace("J. Doe", "R. Roe");
+        assert!(!verify_custody_report(&tampered, b"examiner-secret").expect("signed"));
+
+        let unsigned = session.render_report(None);
+        assert!(verify_custody_report(&unsigned, b"examiner-secret").is_err());
+
+        assert!(CustodySession::begin("CASE\nForged: yes", "J. Doe").is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,337 @@
+//! Duplicate-chunk report across a directory
+//!
+//! Before planning a patch that has to land in several files, it helps to
+//! know which blobs those files share. `build_dedup_report` splits every
+//! regular file under a directory into fixed-size, aligned chunks, groups
+//! chunks by content fingerprint (CRC-32 + FNV-1a 64, streamed), confirms
+//! candidate duplicates byte-for-byte, and merges runs of consecutive
+//! duplicate chunks into regions.
+//!
+//! Only whole chunks are considered; a file's trailing partial chunk is
+//! ignored. Symlinks and this tool's `.backup`/`.draft` artifacts are skipped.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    BACKUP_FILE_SUFFIX, DRAFT_FILE_SUFFIX, check_chunk_limit, chunk_limit_for_chunk_size,
+    open_at_offset,
+};
+use crate::hash::crc32::Crc32;
+use crate::invariants::escape_json_string;
+
+/// Chunk size used when none is given
+pub const DEFAULT_DEDUP_CHUNK_SIZE: usize = 4096;
+
+/// Upper bound on directory entries visited, to prevent runaway walks
+const MAX_DIRECTORY_ENTRIES: usize = 16_777_216;
+
+/// One place a duplicated region occurs.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChunkLocation {
+    pub file_path: PathBuf,
+    pub byte_offset: u64,
+}
+
+/// A run of identical bytes found at two or more locations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRegion {
+    pub byte_length: u64,
+    /// Sorted by file, then offset
+    pub occurrences: Vec<ChunkLocation>,
+}
+
+/// Result of a dedup scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupReport {
+    pub chunk_size: usize,
+    pub files_scanned: usize,
+    pub chunks_hashed: usize,
+    pub duplicate_regions: Vec<DuplicateRegion>,
+}
+
+/// 64-bit FNV-1a, streamed.
+#[derive(Debug, Clone, Copy)]
+struct Fnv1a64 {
+    state: u64,
+}
+
+impl Fnv1a64 {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    fn new() -> Self {
+        Fnv1a64 {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte_value in bytes {
+            self.state ^= byte_value as u64;
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+/// Scans a directory tree and reports duplicated chunk regions.
+///
+/// # Parameters
+/// - `root_directory`: Directory to scan (walked iteratively, no recursion)
+/// - `chunk_size`: Chunk size in bytes (must be > 0)
+///
+/// # Returns
+/// - `Ok(DedupReport)`, regions ordered by their first occurrence
+/// - `Err(io::Error)` if the directory or a file cannot be read
+pub fn build_dedup_report(root_directory: &Path, chunk_size: usize) -> io::Result<DedupReport> {
+    if chunk_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Chunk size must be greater than 0",
+        ));
+    }
+
+    let file_paths = collect_regular_files(root_directory)?;
+
+    // =========================================
+    // Fingerprint Phase
+    // =========================================
+    let mut chunks_by_fingerprint: HashMap<(u32, u64), Vec<ChunkLocation>> = HashMap::new();
+    let mut chunks_hashed: usize = 0;
+    let mut chunk_buffer = vec![0u8; chunk_size];
+
+    for file_path in &file_paths {
+        let mut source_file = File::open(file_path)?;
+        let mut byte_offset: u64 = 0;
+        let chunk_limit = chunk_limit_for_chunk_size(source_file.metadata()?.len(), chunk_size);
+        let mut chunk_number: usize = 0;
+        loop {
+            check_chunk_limit(chunk_number, chunk_limit)?;
+            chunk_number += 1;
+
+            if !read_full_chunk(&mut source_file, &mut chunk_buffer)? {
+                break;
+            }
+            let mut crc_state = Crc32::new();
+            crc_state.update(&chunk_buffer);
+            let mut fnv_state = Fnv1a64::new();
+            fnv_state.update(&chunk_buffer);
+
+            chunks_by_fingerprint
+                .entry((crc_state.finalize(), fnv_state.state))
+                .or_default()
+                .push(ChunkLocation {
+                    file_path: file_path.clone(),
+                    byte_offset,
+                });
+            chunks_hashed += 1;
+            byte_offset += chunk_size as u64;
+        }
+    }
+
+    // =========================================
+    // Confirmation Phase (byte-for-byte)
+    // =========================================
+    let mut duplicate_groups: Vec<Vec<ChunkLocation>> = Vec::new();
+    for (_fingerprint, mut locations) in chunks_by_fingerprint {
+        if locations.len() < 2 {
+            continue;
+        }
+        locations.sort();
+        for confirmed_group in split_by_exact_content(locations, chunk_size)? {
+            if confirmed_group.len() >= 2 {
+                duplicate_groups.push(confirmed_group);
+            }
+        }
+    }
+    duplicate_groups.sort();
+
+    // =========================================
+    // Region Merge Phase
+    // =========================================
+    let mut duplicate_regions: Vec<DuplicateRegion> = Vec::new();
+    let mut region_index_by_next_start: HashMap<Vec<ChunkLocation>, usize> = HashMap::new();
+
+    for group in duplicate_groups {
+        if let Some(region_index) = region_index_by_next_start.remove(&group) {
+            // This group continues an existing region at every occurrence
+            duplicate_regions[region_index].byte_length += chunk_size as u64;
+            region_index_by_next_start.insert(shift_locations(&group, chunk_size), region_index);
+            continue;
+        }
+        region_index_by_next_start
+            .insert(shift_locations(&group, chunk_size), duplicate_regions.len());
+        duplicate_regions.push(DuplicateRegion {
+            byte_length: chunk_size as u64,
+            occurrences: group,
+        });
+    }
+
+    Ok(DedupReport {
+        chunk_size,
+        files_scanned: file_paths.len(),
+        chunks_hashed,
+        duplicate_regions,
+    })
+}
+
+/// Renders a report as one JSON object.
+pub fn format_dedup_report_as_json(report: &DedupReport) -> String {
+    let mut output = format!(
+        "{{\"chunk_size\":{},\"files_scanned\":{},\"chunks_hashed\":{},\"duplicate_regions\":[",
+        report.chunk_size, report.files_scanned, report.chunks_hashed
+    );
+    for (region_index, region) in report.duplicate_regions.iter().enumerate() {
+        if region_index > 0 {
+            output.push(',');
+        }
+        output.push_str(&format!(
+            "{{\"length\":{},\"occurrences\":[",
+            region.byte_length
+        ));
+        for (occurrence_index, occurrence) in region.occurrences.iter().enumerate() {
+            if occurrence_index > 0 {
+                output.push(',');
+            }
+            output.push_str(&format!(
+                "{{\"file\":\"{}\",\"offset\":{}}}",
+                escape_json_string(&occurrence.file_path.to_string_lossy()),
+                occurrence.byte_offset
+            ));
+        }
+        output.push_str("]}");
+    }
+    output.push_str("]}");
+    output
+}
+
+/// Lists regular files under `root_directory` without recursion.
+fn collect_regular_files(root_directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut pending_directories = vec![root_directory.to_path_buf()];
+    let mut file_paths = Vec::new();
+    let mut entries_visited: usize = 0;
+
+    while let Some(directory) = pending_directories.pop() {
+        for directory_entry in fs::read_dir(&directory)? {
+            entries_visited += 1;
+            if entries_visited > MAX_DIRECTORY_ENTRIES {
+                return Err(io::Error::other("Too many directory entries"));
+            }
+            let directory_entry = directory_entry?;
+            let file_type = directory_entry.file_type()?;
+            let entry_path = directory_entry.path();
+            if file_type.is_dir() {
+                pending_directories.push(entry_path);
+            } else if file_type.is_file() && !is_tool_artifact(&entry_path) {
+                file_paths.push(entry_path);
+            }
+        }
+    }
+
+    file_paths.sort();
+    Ok(file_paths)
+}
+
+fn is_tool_artifact(file_path: &Path) -> bool {
+    let file_name = file_path.to_string_lossy();
+    file_name.ends_with(BACKUP_FILE_SUFFIX) || file_name.ends_with(DRAFT_FILE_SUFFIX)
+}
+
+/// Fills `chunk_buffer` completely; `Ok(false)` if EOF comes first.
+fn read_full_chunk(source_file: &mut File, chunk_buffer: &mut [u8]) -> io::Result<bool> {
+    let mut bytes_filled: usize = 0;
+    while bytes_filled < chunk_buffer.len() {
+        let bytes_read = source_file.read(&mut chunk_buffer[bytes_filled..])?;
+        if bytes_read == 0 {
+            return Ok(false);
+        }
+        bytes_filled += bytes_read;
+    }
+    Ok(true)
+}
+
+/// Splits same-fingerprint locations into groups of identical content.
+fn split_by_exact_content(
+    locations: Vec<ChunkLocation>,
+    chunk_size: usize,
+) -> io::Result<Vec<Vec<ChunkLocation>>> {
+    let mut groups: Vec<(Vec<u8>, Vec<ChunkLocation>)> = Vec::new();
+    for location in locations {
+        let mut chunk_bytes = vec![0u8; chunk_size];
+        open_at_offset(&location.file_path, location.byte_offset)?.read_exact(&mut chunk_bytes)?;
+        match groups
+            .iter_mut()
+            .find(|(content, _)| *content == chunk_bytes)
+        {
+            Some((_, members)) => members.push(location),
+            None => groups.push((chunk_bytes, vec![location])),
+        }
+    }
+    Ok(groups.into_iter().map(|(_, members)| members).collect())
+}
+
+fn shift_locations(locations: &[ChunkLocation], chunk_size: usize) -> Vec<ChunkLocation> {
+    locations
+        .iter()
+        .map(|location| ChunkLocation {
+            file_path: location.file_path.clone(),
+            byte_offset: location.byte_offset + chunk_size as u64,
+        })
+        .collect()
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_report_merges_consecutive_chunks() {
+        let test_dir = std::env::temp_dir().join("test_dedup_report_dir");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(test_dir.join("nested")).expect("create test dir");
+
+        let shared_blob: Vec<u8> = (0..=255u8).cycle().take(32).collect();
+        let mut first_file = vec![0xAAu8; 16];
+        first_file.extend_from_slice(&shared_blob);
+        let mut second_file = shared_blob.clone();
+        second_file.extend_from_slice(&[0xBB; 20]); // 16 + partial tail
+        fs::write(test_dir.join("a.bin"), &first_file).expect("write a");
+        fs::write(test_dir.join("nested/b.bin"), &second_file).expect("write b");
+        fs::write(test_dir.join("a.bin.backup"), &first_file).expect("write artifact");
+
+        let report = build_dedup_report(&test_dir, 16).expect("scan should succeed");
+
+        assert_eq!(report.files_scanned, 2);
+        assert_eq!(report.chunks_hashed, 3 + 3);
+        assert_eq!(report.duplicate_regions.len(), 1);
+        let region = &report.duplicate_regions[0];
+        assert_eq!(region.byte_length, 32);
+        assert_eq!(
+            region.occurrences,
+            vec![
+                ChunkLocation {
+                    file_path: test_dir.join("a.bin"),
+                    byte_offset: 16
+                },
+                ChunkLocation {
+                    file_path: test_dir.join("nested/b.bin"),
+                    byte_offset: 0
+                },
+            ]
+        );
+
+        let json = format_dedup_report_as_json(&report);
+        assert!(json.starts_with("{\"chunk_size\":16,\"files_scanned\":2"));
+        assert!(json.contains("\"length\":32"));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+}
@@ -0,0 +1,415 @@
+//! Human-readable diff reports, produced during verification
+//!
+//! Change tickets often need review material: "which bytes changed, and
+//! what was around them". Rather than running a second full-file comparison
+//! after the edit, `replace_ranges_with_diff_report` renders the report
+//! inside the verification phase, from the original and the verified draft,
+//! reading only a small hexdump window around each replaced region.
+//!
+//! Reports are plain text (unified-diff style hexdump) or a self-contained
+//! HTML page with changed bytes highlighted.
+
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    check_chunk_limit, chunk_limit_for_chunk_size, open_at_offset, run_draft_workflow,
+    validate_target_file,
+};
+use crate::memory_budget::{MemoryConsumer, admit_draft_operation};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::range_operations::{
+    build_multi_replacement_draft, replacement_list_consumer, validate_range_replacements,
+    verify_multi_replacement,
+};
+use crate::util::chunk_length;
+
+/// Bytes per hexdump line
+const HEXDUMP_LINE_WIDTH: usize = 16;
+
+/// Default context shown before and after each changed region
+pub const DEFAULT_DIFF_CONTEXT_BYTES: usize = 16;
+
+/// Output format of a diff report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffReportFormat {
+    Text,
+    Html,
+}
+
+/// Applies same-length replacements (one draft) and returns a diff report.
+///
+/// The edit itself is exactly `replace_multiple_byte_ranges_in_file`; the
+/// report is rendered after verification succeeds and before the rename,
+/// so it describes precisely the bytes that were committed.
+///
+/// # Parameters
+/// - `replacements`: `(position, new_bytes)` pairs, non-overlapping
+/// - `report_format`: Text or HTML
+/// - `context_bytes`: Unchanged bytes to show around each region
+///
+/// # Returns
+/// - `Ok(String)`: the rendered report, after the edit is committed
+/// - `Err(io::Error)` if any phase fails; the original is untouched
+pub fn replace_ranges_with_diff_report(
+    original_file_path: PathBuf,
+    replacements: &[(u64, Vec<u8>)],
+    report_format: DiffReportFormat,
+    context_bytes: usize,
+) -> io::Result<String> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let sorted_replacements = validate_range_replacements(replacements, original_file_size)?;
+    let changed_regions: Vec<(u64, u64)> = sorted_replacements
+        .iter()
+        .map(|(position, new_bytes)| (*position, new_bytes.len() as u64))
+        .collect();
+    admit_draft_operation(vec![
+        replacement_list_consumer(replacements),
+        MemoryConsumer::DiffReport {
+            region_count: changed_regions.len(),
+            total_region_bytes: replacements
+                .iter()
+                .map(|(_, new_bytes)| new_bytes.len())
+                .sum(),
+            context_bytes,
+            format: report_format,
+        },
+    ])?;
+
+    let mut rendered_report = String::new();
+    run_draft_workflow(
+        &original_file_path,
+        "Multi-Range Replacement (with diff report)",
+        original_file_size,
+        |source_file, draft_file| {
+            build_multi_replacement_draft(source_file, draft_file, &sorted_replacements)
+        },
+        |original_path, draft_path| {
+            verify_multi_replacement(
+                original_path,
+                draft_path,
+                &sorted_replacements,
+                original_file_size,
+            )?;
+            rendered_report = render_diff_report(
+                &original_file_path,
+                original_path,
+                draft_path,
+                &changed_regions,
+                original_file_size,
+                report_format,
+                context_bytes,
+            )?;
+            Ok(())
+        },
+    )?;
+
+    Ok(rendered_report)
+}
+
+/// Like `replace_ranges_with_diff_report`, using `config` instead of the active
+/// (or default) `OperationConfig`.
+pub fn replace_ranges_with_diff_report_with_config(
+    original_file_path: PathBuf,
+    replacements: &[(u64, Vec<u8>)],
+    report_format: DiffReportFormat,
+    context_bytes: usize,
+    config: &OperationConfig,
+) -> io::Result<String> {
+    with_operation_config(config, || {
+        replace_ranges_with_diff_report(
+            original_file_path,
+            replacements,
+            report_format,
+            context_bytes,
+        )
+    })
+}
+
+/// Renders hexdump windows around `changed_regions` of two same-size files.
+///
+/// `display_path` is only used for the report title.
+pub(crate) fn render_diff_report(
+    display_path: &Path,
+    original_path: &Path,
+    draft_path: &Path,
+    changed_regions: &[(u64, u64)],
+    file_size: u64,
+    report_format: DiffReportFormat,
+    context_bytes: usize,
+) -> io::Result<String> {
+    let title = format!("Diff report: {}", display_path.display());
+    let mut report = match report_format {
+        DiffReportFormat::Text => format!("{}\n", title),
+        DiffReportFormat::Html => format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title>\n\
+             <style>pre{{font-family:monospace}} mark{{background:#fd8}} \
+             .del{{color:#a00}} .ins{{color:#070}}</style></head><body>\n<h1>{0}</h1>\n",
+            escape_html(&title)
+        ),
+    };
+
+    // Group regions whose context windows overlap, so no line is shown twice
+    let mut display_windows: Vec<DisplayWindow> = Vec::new();
+    let context_length = context_bytes as u64;
+    let line_width = HEXDUMP_LINE_WIDTH as u64;
+    for (region_index, &(region_start, region_length)) in changed_regions.iter().enumerate() {
+        let window_start = region_start.saturating_sub(context_length) / line_width * line_width;
+        let window_end = std::cmp::min(
+            file_size,
+            region_start
+                .saturating_add(region_length)
+                .saturating_add(context_length),
+        );
+        match display_windows.last_mut() {
+            Some(previous_window) if window_start <= previous_window.window_end => {
+                previous_window.window_end = previous_window.window_end.max(window_end);
+                previous_window.last_region_number = region_index + 1;
+                previous_window.span_end = region_start + region_length;
+                previous_window.replaced_byte_count += region_length;
+            }
+            _ => display_windows.push(DisplayWindow {
+                window_start,
+                window_end,
+                first_region_number: region_index + 1,
+                last_region_number: region_index + 1,
+                span_start: region_start,
+                span_end: region_start + region_length,
+                replaced_byte_count: region_length,
+            }),
+        }
+    }
+
+    for display_window in &display_windows {
+        let window_start = display_window.window_start;
+        let window_end = display_window.window_end;
+
+        let mut original_reader = open_at_offset(original_path, window_start)?;
+        let mut draft_reader = open_at_offset(draft_path, window_start)?;
+
+        let mut region_lines = String::new();
+        let mut changed_byte_count: usize = 0;
+        let mut line_offset = window_start;
+        let chunk_limit = chunk_limit_for_chunk_size(window_end - window_start, HEXDUMP_LINE_WIDTH);
+        let mut chunk_number: usize = 0;
+        while line_offset < window_end {
+            check_chunk_limit(chunk_number, chunk_limit)?;
+            chunk_number += 1;
+
+            let line_length = chunk_length(HEXDUMP_LINE_WIDTH, window_end - line_offset);
+            let original_line = read_line_bytes(&mut original_reader, line_length)?;
+            let draft_line = read_line_bytes(&mut draft_reader, line_length)?;
+            let differs: Vec<bool> = original_line
+                .iter()
+                .zip(&draft_line)
+                .map(|(original_byte, draft_byte)| original_byte != draft_byte)
+                .collect();
+            changed_byte_count += differs.iter().filter(|&&differ| differ).count();
+
+            if differs.contains(&true) {
+                region_lines.push_str(&render_hexdump_line(
+                    '-',
+                    line_offset,
+                    &original_line,
+                    &differs,
+                    report_format,
+                ));
+                region_lines.push_str(&render_hexdump_line(
+                    '+',
+                    line_offset,
+                    &draft_line,
+                    &differs,
+                    report_format,
+                ));
+            } else {
+                region_lines.push_str(&render_hexdump_line(
+                    ' ',
+                    line_offset,
+                    &original_line,
+                    &differs,
+                    report_format,
+                ));
+            }
+            line_offset += line_length as u64;
+        }
+
+        let region_label =
+            if display_window.first_region_number == display_window.last_region_number {
+                format!("Region {}", display_window.first_region_number)
+            } else {
+                format!(
+                    "Regions {}-{}",
+                    display_window.first_region_number, display_window.last_region_number
+                )
+            };
+        let heading = format!(
+            "{}: 0x{:08X}..0x{:08X} ({} bytes replaced, {} differ)",
+            region_label,
+            display_window.span_start,
+            display_window.span_end,
+            display_window.replaced_byte_count,
+            changed_byte_count
+        );
+        match report_format {
+            DiffReportFormat::Text => {
+                report.push_str(&format!("\n{}\n{}", heading, region_lines));
+            }
+            DiffReportFormat::Html => {
+                report.push_str(&format!(
+                    "<h2>{}</h2>\n<pre>{}</pre>\n",
+                    heading, region_lines
+                ));
+            }
+        }
+    }
+
+    if report_format == DiffReportFormat::Html {
+        report.push_str("</body></html>\n");
+    }
+    Ok(report)
+}
+
+/// Hexdump window covering one or more nearby replaced regions.
+struct DisplayWindow {
+    window_start: u64,
+    window_end: u64,
+    first_region_number: usize,
+    last_region_number: usize,
+    span_start: u64,
+    span_end: u64,
+    replaced_byte_count: u64,
+}
+
+fn read_line_bytes<R: Read>(reader: &mut R, line_length: usize) -> io::Result<Vec<u8>> {
+    let mut line_bytes = vec![0u8; line_length];
+    reader.read_exact(&mut line_bytes)?;
+    Ok(line_bytes)
+}
+
+/// `- 00000010  41 42 ..  |AB..|`, with differing bytes marked in HTML.
+fn render_hexdump_line(
+    marker: char,
+    line_offset: u64,
+    line_bytes: &[u8],
+    differs: &[bool],
+    report_format: DiffReportFormat,
+) -> String {
+    let highlight = report_format == DiffReportFormat::Html && marker != ' ';
+    let mut hex_column = String::new();
+    let mut ascii_column = String::new();
+
+    for column_index in 0..HEXDUMP_LINE_WIDTH {
+        let Some(&byte_value) = line_bytes.get(column_index) else {
+            hex_column.push_str("   ");
+            continue;
+        };
+        let hex_text = format!("{:02X}", byte_value);
+        let ascii_char = if byte_value.is_ascii_graphic() || byte_value == b' ' {
+            byte_value as char
+        } else {
+            '.'
+        };
+        let ascii_text = match report_format {
+            DiffReportFormat::Text => ascii_char.to_string(),
+            DiffReportFormat::Html => escape_html(&ascii_char.to_string()),
+        };
+
+        if highlight && differs[column_index] {
+            hex_column.push_str(&format!("<mark>{}</mark> ", hex_text));
+            ascii_column.push_str(&format!("<mark>{}</mark>", ascii_text));
+        } else {
+            hex_column.push_str(&format!("{} ", hex_text));
+            ascii_column.push_str(&ascii_text);
+        }
+    }
+
+    let line_text = format!(
+        "{} {:08X}  {} |{}|",
+        marker, line_offset, hex_column, ascii_column
+    );
+    match (report_format, marker) {
+        (DiffReportFormat::Html, '-') => format!("<span class=\"del\">{}</span>\n", line_text),
+        (DiffReportFormat::Html, '+') => format!("<span class=\"ins\">{}</span>\n", line_text),
+        _ => format!("{}\n", line_text),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_with_text_diff_report() {
+        let test_file = std::env::temp_dir().join("test_diff_report_text.bin");
+        let test_data: Vec<u8> = (0u8..64).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        let report = replace_ranges_with_diff_report(
+            test_file.clone(),
+            &[(0x22, vec![0xAA, 0x23])],
+            DiffReportFormat::Text,
+            4,
+        )
+        .expect("edit should succeed");
+
+        let mut expected_data = test_data.clone();
+        expected_data[0x22] = 0xAA;
+        assert_eq!(std::fs::read(&test_file).expect("read file"), expected_data);
+
+        assert!(report.contains("Region 1: 0x00000022..0x00000024 (2 bytes replaced, 1 differ)"));
+        assert!(report.contains("- 00000020  20 21 22 23 24 25 26 27 "));
+        assert!(report.contains("+ 00000020  20 21 AA 23 24 25 26 27 "));
+        // Window ends 4 bytes after the region, no unchanged lines beyond it
+        assert!(!report.contains("00000030"));
+
+        // Nearby regions share one window
+        let report = replace_ranges_with_diff_report(
+            test_file.clone(),
+            &[(0x03, vec![0xFF]), (0x09, vec![0xFF]), (0x3F, vec![0xFF])],
+            DiffReportFormat::Text,
+            4,
+        )
+        .expect("edit should succeed");
+        assert!(
+            report.contains("Regions 1-2: 0x00000003..0x0000000A (2 bytes replaced, 2 differ)")
+        );
+        assert!(report.contains("Region 3: 0x0000003F..0x00000040"));
+        assert_eq!(report.matches("- 00000000").count(), 1);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_with_html_diff_report_marks_changes() {
+        let test_file = std::env::temp_dir().join("test_diff_report_html.bin");
+        std::fs::write(&test_file, b"<tag> & text, more text here....").expect("create test file");
+
+        let report = replace_ranges_with_diff_report(
+            test_file.clone(),
+            &[(1, b"TAG".to_vec())],
+            DiffReportFormat::Html,
+            DEFAULT_DIFF_CONTEXT_BYTES,
+        )
+        .expect("edit should succeed");
+
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<mark>54</mark>"));
+        assert!(report.contains("&lt;"));
+        assert!(report.ends_with("</body></html>\n"));
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,569 @@
+//! Journaled direct writes for files too large to rewrite
+//!
+//! The draft workflow copies the whole file, so changing one byte of a
+//! 2 TB image costs hours of I/O and 2 TB of free space.
+//! `write_bytes_directly` overwrites the bytes where they are instead, and a
+//! small journal makes that recoverable:
+//! 1. Read the old bytes; write `<name>.journal` (aside, synced, renamed into
+//!    place, directory synced) with the offset, the old and new bytes, and
+//!    their SHA-256s
+//! 2. Write the new bytes at the offset and sync the file
+//! 3. Read the range back and compare it with the new bytes
+//! 4. Remove the journal
+//!
+//! There is no backup and no draft: the journal's copy of the old bytes is
+//! the undo point. If a step fails, the old bytes are written back before
+//! the error is returned. If the process dies between 1 and 4,
+//! `recover_direct_write` (CLI: `bfbo recover`) keeps a range that already
+//! holds the new bytes and writes the old bytes back over anything else (the
+//! untouched original or a torn write). While the journal is present, every
+//! other operation on the file is refused with `ByteOpError::PendingJournal`.
+//!
+//! Only same-length overwrites can be done in place, up to
+//! `MAX_DIRECT_WRITE_LENGTH` bytes (the journal holds them as hex). The syncs
+//! in steps 1 and 2 are always made, whatever the `DurabilityMode`: the
+//! journal must be on disk before the file changes, and the change before
+//! the journal goes.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    build_artifact_paths, check_original_preconditions, resolve_symlinked_target,
+    sync_parent_directory, validate_target_file,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::hash::sha256::{digest_to_hex, hex_to_digest, sha256_of_bytes};
+use crate::lock_file::OperationLock;
+use crate::offset::Offset;
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::reporter::{
+    OperationPhase, ReportEvent, report, report_detail, report_phase, report_problem,
+};
+use crate::value_parsing::parse_hex_bytes_text;
+use crate::writability::check_target_writable;
+
+/// Most bytes one direct write may change (1 MiB)
+pub const MAX_DIRECT_WRITE_LENGTH: usize = 1024 * 1024;
+
+/// First line of a direct-write journal; the number is the format version.
+/// Shares the `.journal` path with draft-workflow journals (`journal.rs`).
+pub(crate) const DIRECT_WRITE_JOURNAL_HEADER: &str = "bfbo-direct-write-journal 1";
+
+const OPERATION_NAME: &str = "Journaled Direct Write";
+
+/// Suffix of the temporary file the journal is written to
+const JOURNAL_TEMPORARY_SUFFIX: &str = ".tmp";
+
+/// A direct-write journal as read back from disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectWriteJournal {
+    pub target_path: PathBuf,
+    /// Size of the target when the write started; a direct write never
+    /// changes it
+    pub file_size: u64,
+    pub offset: u64,
+    pub old_bytes: Vec<u8>,
+    pub new_bytes: Vec<u8>,
+}
+
+/// What `recover_direct_write` does for a leftover journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectWriteRecovery {
+    /// The range already holds the new bytes; only the journal is removed
+    KeepNewBytes,
+    /// The old bytes are written back (the write never happened or was
+    /// torn), then the journal is removed
+    RestoreOldBytes,
+}
+
+impl DirectWriteRecovery {
+    /// Stable name used in CLI output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DirectWriteRecovery::KeepNewBytes => "keep-new-bytes",
+            DirectWriteRecovery::RestoreOldBytes => "restore-old-bytes",
+        }
+    }
+}
+
+/// Overwrites `new_bytes.len()` bytes of `target_path` at `byte_offset`
+/// in place, with a journal instead of a backup and draft.
+///
+/// # Returns
+/// - `Ok(())` once the new bytes are synced, read back, and the journal is
+///   removed
+/// - `Err(io::Error)` if the range is empty, longer than
+///   `MAX_DIRECT_WRITE_LENGTH`, or not inside the file (nothing written);
+///   or if the write or its read-back fails, after the old bytes were
+///   written back. If even that fails, the journal is kept for
+///   `recover_direct_write`
+pub fn write_bytes_directly(
+    target_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_bytes: &[u8],
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+    report(&ReportEvent::OperationStarted {
+        operation_name: OPERATION_NAME,
+        target_path: &target_path,
+    });
+    report_detail!("Write position: {}", byte_offset);
+    report_detail!("Bytes to write: {}", new_bytes.len());
+
+    let config = active_operation_config();
+    let target_path = resolve_symlinked_target(&target_path, &config)?;
+    let file_size = validate_target_file(&target_path)?;
+    if new_bytes.is_empty() || new_bytes.len() > MAX_DIRECT_WRITE_LENGTH {
+        let error = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "A direct write takes 1 to {} bytes, not {}",
+                MAX_DIRECT_WRITE_LENGTH,
+                new_bytes.len()
+            ),
+        );
+        report_problem!("ERROR: {}", error);
+        return Err(error);
+    }
+    let offset = byte_offset.resolve(file_size)?;
+    let last_position = offset.saturating_add(new_bytes.len() as u64 - 1);
+    if last_position >= file_size {
+        let error = ByteOpError::PositionOutOfBounds {
+            position: last_position,
+            file_size,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    check_original_preconditions(&target_path, &config)?;
+    let journal_file_path = build_artifact_paths(&target_path)?.journal_file_path;
+    check_target_writable(&target_path)?;
+    let _operation_lock = OperationLock::acquire(&target_path)?;
+    if journal_file_path.exists() {
+        let error = ByteOpError::PendingJournal {
+            path: journal_file_path,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    let mut target_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&target_path)?;
+    let old_bytes = read_range(&mut target_file, offset, new_bytes.len())?;
+    if old_bytes == new_bytes {
+        report_detail!("The range already holds these bytes; nothing written");
+        report(&ReportEvent::OperationFinished {
+            operation_name: OPERATION_NAME,
+            target_path: &target_path,
+        });
+        return Ok(());
+    }
+
+    // =========================================
+    // Journal Phase (the old bytes are the backup)
+    // =========================================
+    report_phase(OperationPhase::Backup);
+    let journal = DirectWriteJournal {
+        target_path: std::path::absolute(&target_path)?,
+        file_size,
+        offset,
+        old_bytes,
+        new_bytes: new_bytes.to_vec(),
+    };
+    write_journal(&journal_file_path, &journal)?;
+    report_detail!("Journal path: {}", journal_file_path.display());
+
+    // =========================================
+    // Write and Verify Phases
+    // =========================================
+    report_phase(OperationPhase::Replace);
+    let write_result = write_range(&mut target_file, offset, new_bytes).and_then(|()| {
+        report_phase(OperationPhase::Verify);
+        let written_bytes = read_range(&mut target_file, offset, new_bytes.len())?;
+        match first_difference(&written_bytes, new_bytes) {
+            None => Ok(()),
+            Some(index) => Err(ByteOpError::verification_failed(
+                VerificationPhase::AtPosition,
+                offset + index as u64,
+                u64::from(new_bytes[index]),
+                u64::from(written_bytes[index]),
+            )
+            .into()),
+        }
+    });
+
+    if let Err(e) = write_result {
+        report_problem!("ERROR: Direct write failed: {}", e);
+        match write_range(&mut target_file, offset, &journal.old_bytes) {
+            Ok(()) => {
+                report_detail!("Old bytes written back");
+                let _ = fs::remove_file(&journal_file_path);
+            }
+            Err(restore_error) => report_problem!(
+                "ERROR: Could not write the old bytes back ({}); journal kept at {}, \
+                 run recovery",
+                restore_error,
+                journal_file_path.display()
+            ),
+        }
+        return Err(e);
+    }
+    report(&ReportEvent::VerificationPassed {
+        detail: format_args!("{} byte(s) at {} read back", new_bytes.len(), offset),
+    });
+
+    // =========================================
+    // Cleanup Phase
+    // =========================================
+    report_phase(OperationPhase::Cleanup);
+    fs::remove_file(&journal_file_path)?;
+    report(&ReportEvent::OperationFinished {
+        operation_name: OPERATION_NAME,
+        target_path: &target_path,
+    });
+    Ok(())
+}
+
+/// Like `write_bytes_directly`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn write_bytes_directly_with_config(
+    target_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_bytes: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        write_bytes_directly(target_path, byte_offset, new_bytes)
+    })
+}
+
+/// Reads the direct-write journal an interrupted write left for
+/// `target_path`.
+///
+/// # Returns
+/// - `Ok(None)` if there is no journal, or it is a draft-workflow journal
+///   (see `journal::read_journal`)
+/// - `Ok(Some(DirectWriteJournal))`
+/// - `Err(io::Error)` with `ErrorKind::InvalidData` if the journal is
+///   damaged (its bytes do not match their SHA-256s) or belongs to another
+///   target
+pub fn read_direct_write_journal(target_path: &Path) -> io::Result<Option<DirectWriteJournal>> {
+    let journal_file_path = build_artifact_paths(target_path)?.journal_file_path;
+    let journal_text = match fs::read_to_string(&journal_file_path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if journal_text.lines().next() != Some(DIRECT_WRITE_JOURNAL_HEADER) {
+        return Ok(None);
+    }
+    let invalid = |reason: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", journal_file_path.display(), reason),
+        )
+    };
+    let journal = parse_journal_text(&journal_text).map_err(invalid)?;
+    if journal.target_path != std::path::absolute(target_path)? {
+        return Err(invalid(format!(
+            "belongs to {}",
+            journal.target_path.display()
+        )));
+    }
+    Ok(Some(journal))
+}
+
+/// Reads the leftover direct-write journal for `target_path` and picks the
+/// recovery action, without changing anything.
+///
+/// # Returns
+/// - `Ok(None)` if there is no direct-write journal
+/// - `Ok(Some((DirectWriteJournal, DirectWriteRecovery)))`
+/// - `Err(io::Error)` with `ErrorKind::InvalidData` if the journal is
+///   damaged or the target's size changed since the write started
+pub fn plan_direct_write_recovery(
+    target_path: &Path,
+) -> io::Result<Option<(DirectWriteJournal, DirectWriteRecovery)>> {
+    let Some(journal) = read_direct_write_journal(target_path)? else {
+        return Ok(None);
+    };
+    let actual_size = fs::metadata(&journal.target_path)?.len();
+    if actual_size != journal.file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Cannot recover {}: it is {} bytes, but was {} when the direct write \
+                 started; nothing changed",
+                journal.target_path.display(),
+                actual_size,
+                journal.file_size
+            ),
+        ));
+    }
+
+    let mut target_file = File::open(&journal.target_path)?;
+    let current_bytes = read_range(&mut target_file, journal.offset, journal.new_bytes.len())?;
+    let action = if current_bytes == journal.new_bytes {
+        DirectWriteRecovery::KeepNewBytes
+    } else {
+        DirectWriteRecovery::RestoreOldBytes
+    };
+    Ok(Some((journal, action)))
+}
+
+/// Completes the recovery of a direct write interrupted on `target_path`
+/// (see `DirectWriteRecovery`), then removes its journal.
+///
+/// # Returns
+/// - `Ok(None)` if there was no direct-write journal
+/// - `Ok(Some(DirectWriteRecovery))`: the action that was carried out
+/// - `Err(io::Error)` if the target is locked by another operation, the
+///   journal is damaged, the target's size changed, or a step fails; the
+///   journal is kept
+pub fn recover_direct_write(target_path: PathBuf) -> io::Result<Option<DirectWriteRecovery>> {
+    // Held from reading the journal to removing it, so no other operation
+    // can start on the target in between
+    let _operation_lock = OperationLock::acquire(&target_path)?;
+    let Some((journal, action)) = plan_direct_write_recovery(&target_path)? else {
+        return Ok(None);
+    };
+    report_detail!(
+        "Recovering direct write to {} ({} byte(s) at {}): {}",
+        journal.target_path.display(),
+        journal.new_bytes.len(),
+        journal.offset,
+        action.name()
+    );
+
+    if action == DirectWriteRecovery::RestoreOldBytes {
+        let mut target_file = OpenOptions::new().write(true).open(&journal.target_path)?;
+        write_range(&mut target_file, journal.offset, &journal.old_bytes)?;
+    }
+    fs::remove_file(build_artifact_paths(&target_path)?.journal_file_path)?;
+    Ok(Some(action))
+}
+
+/// Like `recover_direct_write`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn recover_direct_write_with_config(
+    target_path: PathBuf,
+    config: &OperationConfig,
+) -> io::Result<Option<DirectWriteRecovery>> {
+    with_operation_config(config, || recover_direct_write(target_path))
+}
+
+fn read_range(file: &mut File, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; length];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes `bytes` at `offset` and syncs them to disk.
+fn write_range(file: &mut File, offset: u64, bytes: &[u8]) -> io::Result<()> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)?;
+    file.sync_data()
+}
+
+fn first_difference(actual: &[u8], expected: &[u8]) -> Option<usize> {
+    actual
+        .iter()
+        .zip(expected)
+        .position(|(actual_byte, expected_byte)| actual_byte != expected_byte)
+}
+
+/// Writes the journal aside, syncs it, renames it into place, and syncs
+/// the directory, so it is on disk before the target changes.
+fn write_journal(journal_file_path: &Path, journal: &DirectWriteJournal) -> io::Result<()> {
+    let target_path_text = journal.target_path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Cannot journal a non-UTF-8 path: {}",
+                journal.target_path.display()
+            ),
+        )
+    })?;
+    if target_path_text.contains(['\n', '\r']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot journal a path containing a line break",
+        ));
+    }
+    let journal_text = format!(
+        "{}\ntarget: {}\nfile_size: {}\noffset: {}\nold_bytes: {}\nold_sha256: {}\n\
+         new_bytes: {}\nnew_sha256: {}\n",
+        DIRECT_WRITE_JOURNAL_HEADER,
+        target_path_text,
+        journal.file_size,
+        journal.offset,
+        digest_to_hex(&journal.old_bytes),
+        digest_to_hex(&sha256_of_bytes(&journal.old_bytes)),
+        digest_to_hex(&journal.new_bytes),
+        digest_to_hex(&sha256_of_bytes(&journal.new_bytes))
+    );
+
+    let mut temporary_path = journal_file_path.to_path_buf().into_os_string();
+    temporary_path.push(JOURNAL_TEMPORARY_SUFFIX);
+    let temporary_path = PathBuf::from(temporary_path);
+    {
+        let mut temporary_file = File::create(&temporary_path)?;
+        temporary_file.write_all(journal_text.as_bytes())?;
+        temporary_file.sync_all()?;
+    }
+    fs::rename(&temporary_path, journal_file_path)?;
+    sync_parent_directory(journal_file_path)
+}
+
+fn parse_journal_text(journal_text: &str) -> Result<DirectWriteJournal, String> {
+    let mut target_path = None;
+    let mut file_size = None;
+    let mut offset = None;
+    let mut old_bytes = None;
+    let mut old_sha256 = None;
+    let mut new_bytes = None;
+    let mut new_sha256 = None;
+    let parse_number = |key: &str, value: &str| {
+        value
+            .parse::<u64>()
+            .map_err(|_| format!("bad {}: {}", key, value))
+    };
+    let parse_bytes =
+        |key: &str, value: &str| parse_hex_bytes_text(value).map_err(|_| format!("bad {}", key));
+    let parse_digest =
+        |key: &str, value: &str| hex_to_digest(value).ok_or_else(|| format!("bad {}", key));
+
+    // The journal is renamed into place whole; a short one is damaged
+    for line in journal_text.lines().skip(1) {
+        let (key, value) = line
+            .split_once(": ")
+            .ok_or_else(|| format!("malformed line: {}", line))?;
+        match key {
+            "target" => target_path = Some(PathBuf::from(value)),
+            "file_size" => file_size = Some(parse_number(key, value)?),
+            "offset" => offset = Some(parse_number(key, value)?),
+            "old_bytes" => old_bytes = Some(parse_bytes(key, value)?),
+            "old_sha256" => old_sha256 = Some(parse_digest(key, value)?),
+            "new_bytes" => new_bytes = Some(parse_bytes(key, value)?),
+            "new_sha256" => new_sha256 = Some(parse_digest(key, value)?),
+            _ => return Err(format!("unknown field: {}", key)),
+        }
+    }
+
+    let missing = |field: &str| format!("missing field: {}", field);
+    let old_bytes = old_bytes.ok_or_else(|| missing("old_bytes"))?;
+    let new_bytes = new_bytes.ok_or_else(|| missing("new_bytes"))?;
+    if Some(sha256_of_bytes(&old_bytes)) != old_sha256
+        || Some(sha256_of_bytes(&new_bytes)) != new_sha256
+        || old_bytes.len() != new_bytes.len()
+    {
+        return Err("damaged: the bytes do not match their recorded SHA-256".to_string());
+    }
+    Ok(DirectWriteJournal {
+        target_path: target_path.ok_or_else(|| missing("target"))?,
+        file_size: file_size.ok_or_else(|| missing("file_size"))?,
+        offset: offset.ok_or_else(|| missing("offset"))?,
+        old_bytes,
+        new_bytes,
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direct_write_changes_bytes_in_place() {
+        let test_file = std::env::temp_dir().join("test_direct_write.bin");
+        fs::write(&test_file, b"0123456789").expect("create test file");
+        let journal_file_path = build_artifact_paths(&test_file)
+            .expect("artifact paths")
+            .journal_file_path;
+
+        write_bytes_directly(test_file.clone(), 3, b"AB").expect("direct write");
+        write_bytes_directly(test_file.clone(), Offset::FromEnd(1), b"Z").expect("last byte");
+        assert_eq!(fs::read(&test_file).expect("read file"), b"012AB5678Z");
+        assert!(!journal_file_path.exists());
+
+        let error = write_bytes_directly(test_file.clone(), 9, b"XY").expect_err("past the end");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::PositionOutOfBounds { position: 10, .. })
+        ));
+        assert_eq!(fs::read(&test_file).expect("read file"), b"012AB5678Z");
+
+        let _ = fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_recover_interrupted_direct_write() {
+        let test_file = std::env::temp_dir().join("test_direct_write_recover.bin");
+        let journal_file_path = build_artifact_paths(&test_file)
+            .expect("artifact paths")
+            .journal_file_path;
+        let journal = DirectWriteJournal {
+            target_path: std::path::absolute(&test_file).expect("absolute"),
+            file_size: 6,
+            offset: 2,
+            old_bytes: b"cde".to_vec(),
+            new_bytes: b"XYZ".to_vec(),
+        };
+
+        // Crashed after the journal, before or during the write
+        for (content_on_disk, expected_action, expected_content) in [
+            (
+                &b"abcdef"[..],
+                DirectWriteRecovery::RestoreOldBytes,
+                &b"abcdef"[..],
+            ),
+            (b"abXYef", DirectWriteRecovery::RestoreOldBytes, b"abcdef"),
+            (b"abXYZf", DirectWriteRecovery::KeepNewBytes, b"abXYZf"),
+        ] {
+            fs::write(&test_file, content_on_disk).expect("create test file");
+            write_journal(&journal_file_path, &journal).expect("journal");
+
+            // The pending journal blocks other operations
+            let error = crate::replace_single_byte_in_file(test_file.clone(), 0, b'!')
+                .expect_err("journal pending");
+            assert!(matches!(
+                ByteOpError::from_io_error(&error),
+                Some(ByteOpError::PendingJournal { .. })
+            ));
+
+            // Recovery waits for the lock like any other operation
+            let operation_lock = OperationLock::acquire(&test_file).expect("acquire");
+            let error = recover_direct_write(test_file.clone()).expect_err("target locked");
+            assert!(matches!(
+                ByteOpError::from_io_error(&error),
+                Some(ByteOpError::LockHeldByThisProcess { .. })
+            ));
+            assert!(journal_file_path.exists());
+            drop(operation_lock);
+
+            let action = recover_direct_write(test_file.clone())
+                .expect("recover")
+                .expect("journal present");
+            assert_eq!(action, expected_action);
+            assert_eq!(fs::read(&test_file).expect("read file"), expected_content);
+            assert!(!journal_file_path.exists());
+        }
+        assert_eq!(
+            recover_direct_write(test_file.clone()).expect("recover"),
+            None
+        );
+
+        let _ = fs::remove_file(&test_file);
+    }
+}
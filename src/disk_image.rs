@@ -0,0 +1,476 @@
+//! Partition-relative addressing inside raw disk images
+//!
+//! Embedded developers often need to patch a file at a known offset inside
+//! partition N of an SD-card or eMMC image. Mounting the image (loop devices,
+//! root) just to compute the offset is overkill; this module parses the MBR
+//! or GPT partition table directly and turns "partition 2 + offset 0x1BE"
+//! into an absolute image offset for the normal edit operations.
+//!
+//! # Current Scope
+//! - MBR primary partitions (1-4); extended/logical partitions are not walked
+//! - GPT with 512- or 4096-byte logical sectors (header and partition-entry
+//!   array CRCs are checked)
+//! - Partition numbers are 1-based, as in `/dev/sdX2` / `/dev/mmcblk0p2`
+//! - Entries that overflow or extend past the end of the image are rejected
+//!   as malformed
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::draft_workflow::validate_target_file;
+use crate::hash::crc32::crc32_of_bytes;
+
+/// Logical sector sizes probed for a GPT header, in order
+const PROBED_SECTOR_SIZES: [usize; 2] = [512, 4096];
+
+const MBR_SIZE: usize = 512;
+const MBR_PARTITION_TABLE_OFFSET: usize = 446;
+const MBR_PARTITION_ENTRY_SIZE: usize = 16;
+const MBR_BOOT_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+/// MBR partition type of a GPT protective MBR
+const MBR_TYPE_GPT_PROTECTIVE: u8 = 0xEE;
+
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+/// Sanity bounds on GPT table geometry
+const MAX_GPT_ENTRY_COUNT: usize = 1024;
+const MAX_GPT_ENTRY_SIZE: usize = 4096;
+
+/// Which partitioning scheme an image uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionScheme {
+    Mbr,
+    Gpt,
+}
+
+/// One partition found in an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionEntry {
+    /// 1-based partition number
+    pub partition_number: usize,
+    /// Absolute byte offset of the partition's first byte
+    pub start_offset: u64,
+    pub size_in_bytes: u64,
+    /// MBR type byte as hex, or GPT type GUID
+    pub type_label: String,
+    /// GPT partition name (empty for MBR)
+    pub name: String,
+}
+
+/// Reads the partition table of a raw disk image.
+///
+/// # Returns
+/// - `Ok((scheme, partitions))`, partitions in table order
+/// - `Err(io::Error)` (`InvalidData`) if there is no valid MBR signature or
+///   the GPT header is malformed
+pub fn read_partition_table(
+    image_path: &Path,
+) -> io::Result<(PartitionScheme, Vec<PartitionEntry>)> {
+    let image_size = validate_target_file(image_path)?;
+    let mut image_file = File::open(image_path)?;
+
+    if image_size < MBR_SIZE as u64 {
+        return Err(invalid_image("image is smaller than one sector"));
+    }
+    let master_boot_record = read_exact_at(&mut image_file, 0, MBR_SIZE)?;
+    if master_boot_record[510..512] != MBR_BOOT_SIGNATURE {
+        return Err(invalid_image("no MBR boot signature (0x55AA)"));
+    }
+
+    let mut mbr_partitions = Vec::new();
+    let mut has_protective_entry = false;
+    for entry_index in 0..4 {
+        let entry_offset = MBR_PARTITION_TABLE_OFFSET + entry_index * MBR_PARTITION_ENTRY_SIZE;
+        let entry = &master_boot_record[entry_offset..entry_offset + MBR_PARTITION_ENTRY_SIZE];
+        let partition_type = entry[4];
+        let first_lba = read_u32_le(entry, 8) as u64;
+        let sector_count = read_u32_le(entry, 12) as u64;
+        if partition_type == 0 || sector_count == 0 {
+            continue;
+        }
+        if partition_type == MBR_TYPE_GPT_PROTECTIVE {
+            // Often sized 0xFFFFFFFF sectors regardless of the image; the
+            // GPT itself describes the partitions
+            has_protective_entry = true;
+            continue;
+        }
+        let start_offset = first_lba * MBR_SIZE as u64;
+        let size_in_bytes = sector_count * MBR_SIZE as u64;
+        check_partition_within_image(entry_index + 1, start_offset, size_in_bytes, image_size)?;
+        mbr_partitions.push(PartitionEntry {
+            partition_number: entry_index + 1,
+            start_offset,
+            size_in_bytes,
+            type_label: format!("0x{:02X}", partition_type),
+            name: String::new(),
+        });
+    }
+
+    if !has_protective_entry {
+        return Ok((PartitionScheme::Mbr, mbr_partitions));
+    }
+
+    for sector_size in PROBED_SECTOR_SIZES {
+        if image_size < sector_size as u64 * 2 {
+            break;
+        }
+        let header_block = read_exact_at(&mut image_file, sector_size as u64, sector_size)?;
+        if &header_block[..8] == GPT_SIGNATURE {
+            let gpt_partitions =
+                read_gpt_partitions(&mut image_file, &header_block, sector_size, image_size)?;
+            return Ok((PartitionScheme::Gpt, gpt_partitions));
+        }
+    }
+
+    Err(invalid_image(
+        "protective MBR present but no GPT header found",
+    ))
+}
+
+/// Parses GPT partition entries given the header block at LBA 1.
+fn read_gpt_partitions(
+    image_file: &mut File,
+    header_block: &[u8],
+    sector_size: usize,
+    image_size: u64,
+) -> io::Result<Vec<PartitionEntry>> {
+    // Header CRC covers `header_size` bytes with the CRC field zeroed
+    let header_size = read_u32_le(header_block, 12) as usize;
+    if !(92..=sector_size).contains(&header_size) {
+        return Err(invalid_image("GPT header size out of range"));
+    }
+    let stored_header_crc = read_u32_le(header_block, 16);
+    let mut header_for_crc = header_block[..header_size].to_vec();
+    header_for_crc[16..20].copy_from_slice(&[0, 0, 0, 0]);
+    if crc32_of_bytes(&header_for_crc) != stored_header_crc {
+        return Err(invalid_image("GPT header CRC mismatch"));
+    }
+
+    let entries_lba = read_u64_le(header_block, 72);
+    let entry_count = read_u32_le(header_block, 80) as usize;
+    let entry_size = read_u32_le(header_block, 84) as usize;
+    if entry_count > MAX_GPT_ENTRY_COUNT || !(128..=MAX_GPT_ENTRY_SIZE).contains(&entry_size) {
+        return Err(invalid_image("GPT entry table geometry out of range"));
+    }
+
+    let entry_table = read_exact_at(
+        image_file,
+        lba_to_offset(entries_lba, sector_size)?,
+        entry_count * entry_size,
+    )?;
+    if crc32_of_bytes(&entry_table) != read_u32_le(header_block, 88) {
+        return Err(invalid_image("GPT partition entry array CRC mismatch"));
+    }
+
+    let mut partitions = Vec::new();
+    for entry_index in 0..entry_count {
+        let entry = &entry_table[entry_index * entry_size..(entry_index + 1) * entry_size];
+        let type_guid = &entry[0..16];
+        if type_guid.iter().all(|&byte_value| byte_value == 0) {
+            continue; // Unused slot
+        }
+        let first_lba = read_u64_le(entry, 32);
+        let last_lba = read_u64_le(entry, 40);
+        let block_count = last_lba
+            .checked_sub(first_lba)
+            .ok_or_else(|| invalid_image("GPT entry ends before it starts"))?
+            .checked_add(1)
+            .ok_or_else(|| invalid_image("GPT entry block count out of range"))?;
+        let start_offset = lba_to_offset(first_lba, sector_size)?;
+        let size_in_bytes = lba_to_offset(block_count, sector_size)?;
+        check_partition_within_image(entry_index + 1, start_offset, size_in_bytes, image_size)?;
+
+        let name_units: Vec<u16> = entry[56..128]
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+
+        partitions.push(PartitionEntry {
+            partition_number: entry_index + 1,
+            start_offset,
+            size_in_bytes,
+            type_label: format_guid(type_guid),
+            name: String::from_utf16_lossy(&name_units),
+        });
+    }
+
+    Ok(partitions)
+}
+
+/// Resolves "partition N + offset" to an absolute image offset.
+///
+/// # Returns
+/// - `Ok(absolute_offset)` if partition N exists and the offset lies inside it
+/// - `Err(io::Error)`: `NotFound` for an unknown partition, `InvalidInput`
+///   for an offset at or past the partition's end
+pub fn resolve_partition_offset(
+    image_path: &Path,
+    partition_number: usize,
+    offset_in_partition: u64,
+) -> io::Result<u64> {
+    let (_scheme, partitions) = read_partition_table(image_path)?;
+    let partition = partitions
+        .iter()
+        .find(|entry| entry.partition_number == partition_number)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No partition {} in image", partition_number),
+            )
+        })?;
+
+    if offset_in_partition >= partition.size_in_bytes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Offset {} is outside partition {} ({} bytes)",
+                offset_in_partition, partition_number, partition.size_in_bytes
+            ),
+        ));
+    }
+
+    partition
+        .start_offset
+        .checked_add(offset_in_partition)
+        .ok_or_else(|| invalid_image("partition offset out of range"))
+}
+
+/// Rejects a partition that starts or ends past the end of the image.
+fn check_partition_within_image(
+    partition_number: usize,
+    start_offset: u64,
+    size_in_bytes: u64,
+    image_size: u64,
+) -> io::Result<()> {
+    let end_offset = start_offset
+        .checked_add(size_in_bytes)
+        .ok_or_else(|| invalid_image("partition end out of range"))?;
+    if end_offset > image_size {
+        return Err(invalid_image(&format!(
+            "partition {} (bytes {}..{}) extends past the end of the image ({} bytes)",
+            partition_number, start_offset, end_offset, image_size
+        )));
+    }
+    Ok(())
+}
+
+/// Byte offset of a logical block, rejecting LBAs past the u64 range.
+fn lba_to_offset(logical_block: u64, sector_size: usize) -> io::Result<u64> {
+    logical_block
+        .checked_mul(sector_size as u64)
+        .ok_or_else(|| invalid_image("GPT block address out of range"))
+}
+
+/// Formats a GPT GUID (mixed-endian on disk) in canonical text form.
+fn format_guid(guid_bytes: &[u8]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        read_u32_le(guid_bytes, 0),
+        u16::from_le_bytes([guid_bytes[4], guid_bytes[5]]),
+        u16::from_le_bytes([guid_bytes[6], guid_bytes[7]]),
+        guid_bytes[8],
+        guid_bytes[9],
+        guid_bytes[10],
+        guid_bytes[11],
+        guid_bytes[12],
+        guid_bytes[13],
+        guid_bytes[14],
+        guid_bytes[15]
+    )
+}
+
+fn read_exact_at(file: &mut File, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0u8; length];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn read_u64_le(bytes: &[u8], offset: usize) -> u64 {
+    let mut value_bytes = [0u8; 8];
+    value_bytes.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(value_bytes)
+}
+
+fn invalid_image(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Disk image: {}", reason),
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_mbr_entry(
+        image: &mut [u8],
+        entry_index: usize,
+        partition_type: u8,
+        first_lba: u32,
+        sectors: u32,
+    ) {
+        let entry_offset = MBR_PARTITION_TABLE_OFFSET + entry_index * MBR_PARTITION_ENTRY_SIZE;
+        image[entry_offset + 4] = partition_type;
+        image[entry_offset + 8..entry_offset + 12].copy_from_slice(&first_lba.to_le_bytes());
+        image[entry_offset + 12..entry_offset + 16].copy_from_slice(&sectors.to_le_bytes());
+    }
+
+    #[test]
+    fn test_mbr_partition_resolution() {
+        let test_file = std::env::temp_dir().join("test_disk_image_mbr.img");
+        let mut image = vec![0u8; 512 * 16];
+        image[510..512].copy_from_slice(&MBR_BOOT_SIGNATURE);
+        write_mbr_entry(&mut image, 0, 0x0C, 2, 4);
+        write_mbr_entry(&mut image, 1, 0x83, 8, 8);
+        std::fs::write(&test_file, &image).expect("create test image");
+
+        let (scheme, partitions) = read_partition_table(&test_file).expect("table parses");
+        assert_eq!(scheme, PartitionScheme::Mbr);
+        assert_eq!(partitions.len(), 2);
+        assert_eq!(partitions[1].type_label, "0x83");
+
+        assert_eq!(
+            resolve_partition_offset(&test_file, 2, 0x1BE).expect("resolves"),
+            8 * 512 + 0x1BE
+        );
+        assert!(resolve_partition_offset(&test_file, 2, 8 * 512).is_err());
+        assert!(resolve_partition_offset(&test_file, 3, 0).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_gpt_partition_resolution() {
+        let test_file = std::env::temp_dir().join("test_disk_image_gpt.img");
+        let mut image = vec![0u8; 512 * 64];
+        image[510..512].copy_from_slice(&MBR_BOOT_SIGNATURE);
+        write_mbr_entry(&mut image, 0, MBR_TYPE_GPT_PROTECTIVE, 1, 63);
+
+        // Partition entry table at LBA 2, 4 entries of 128 bytes
+        let entry_table_offset = 2 * 512;
+        let entry = &mut image[entry_table_offset + 128..entry_table_offset + 256];
+        entry[0] = 0xAF; // Non-zero type GUID
+        entry[32..40].copy_from_slice(&34u64.to_le_bytes());
+        entry[40..48].copy_from_slice(&41u64.to_le_bytes());
+        for (unit_index, unit) in "rootfs".encode_utf16().enumerate() {
+            entry[56 + unit_index * 2..58 + unit_index * 2].copy_from_slice(&unit.to_le_bytes());
+        }
+
+        // Header at LBA 1
+        let header = &mut image[512..1024];
+        header[..8].copy_from_slice(GPT_SIGNATURE);
+        header[12..16].copy_from_slice(&92u32.to_le_bytes());
+        header[72..80].copy_from_slice(&2u64.to_le_bytes());
+        header[80..84].copy_from_slice(&4u32.to_le_bytes());
+        header[84..88].copy_from_slice(&128u32.to_le_bytes());
+        seal_gpt_header(&mut image);
+        std::fs::write(&test_file, &image).expect("create test image");
+
+        let (scheme, partitions) = read_partition_table(&test_file).expect("table parses");
+        assert_eq!(scheme, PartitionScheme::Gpt);
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(partitions[0].partition_number, 2);
+        assert_eq!(partitions[0].name, "rootfs");
+        assert_eq!(partitions[0].size_in_bytes, 8 * 512);
+        assert_eq!(
+            resolve_partition_offset(&test_file, 2, 0x10).expect("resolves"),
+            34 * 512 + 0x10
+        );
+
+        // A protective entry larger than the image (as tools write it) is fine
+        write_mbr_entry(&mut image, 0, MBR_TYPE_GPT_PROTECTIVE, 1, u32::MAX);
+        std::fs::write(&test_file, &image).expect("rewrite test image");
+        assert_eq!(
+            read_partition_table(&test_file).expect("table parses").1,
+            partitions
+        );
+
+        // A corrupted entry array is rejected, even with a valid header CRC
+        image[entry_table_offset + 128 + 56] = b'R';
+        std::fs::write(&test_file, &image).expect("rewrite test image");
+        let error = read_partition_table(&test_file).expect_err("entry array CRC");
+        assert!(error.to_string().contains("entry array CRC"), "{}", error);
+
+        // A corrupted header is rejected
+        image[512 + 80] = 5;
+        std::fs::write(&test_file, &image).expect("rewrite test image");
+        assert!(read_partition_table(&test_file).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    /// A GPT image (64 sectors) with one entry spanning `first_lba..=last_lba`.
+    fn build_gpt_image(first_lba: u64, last_lba: u64) -> Vec<u8> {
+        let mut image = vec![0u8; 512 * 64];
+        image[510..512].copy_from_slice(&MBR_BOOT_SIGNATURE);
+        write_mbr_entry(&mut image, 0, MBR_TYPE_GPT_PROTECTIVE, 1, 63);
+        let entry = &mut image[2 * 512..2 * 512 + 128];
+        entry[0] = 0xAF;
+        entry[32..40].copy_from_slice(&first_lba.to_le_bytes());
+        entry[40..48].copy_from_slice(&last_lba.to_le_bytes());
+        let header = &mut image[512..1024];
+        header[..8].copy_from_slice(GPT_SIGNATURE);
+        header[12..16].copy_from_slice(&92u32.to_le_bytes());
+        header[72..80].copy_from_slice(&2u64.to_le_bytes());
+        header[80..84].copy_from_slice(&4u32.to_le_bytes());
+        header[84..88].copy_from_slice(&128u32.to_le_bytes());
+        seal_gpt_header(&mut image);
+        image
+    }
+
+    /// Fills in the entry-array CRC (4 entries of 128 bytes at LBA 2), then
+    /// the header CRC, of the GPT header at LBA 1.
+    fn seal_gpt_header(image: &mut [u8]) {
+        let entries_crc = crc32_of_bytes(&image[2 * 512..2 * 512 + 4 * 128]);
+        let header = &mut image[512..1024];
+        header[88..92].copy_from_slice(&entries_crc.to_le_bytes());
+        header[16..20].copy_from_slice(&[0, 0, 0, 0]);
+        let header_crc = crc32_of_bytes(&header[..92]);
+        header[16..20].copy_from_slice(&header_crc.to_le_bytes());
+    }
+
+    #[test]
+    fn test_malformed_partition_entries_are_rejected() {
+        let test_file = std::env::temp_dir().join("test_disk_image_malformed.img");
+        let malformed_ranges = [
+            (0, u64::MAX),                // Block count overflows
+            (41, 34),                     // Ends before it starts
+            (u64::MAX / 2, u64::MAX / 2), // Byte offset overflows
+            (34, 64),                     // Ends past the image (64 sectors)
+            (100, 101),                   // Starts past the image
+        ];
+        for (first_lba, last_lba) in malformed_ranges {
+            std::fs::write(&test_file, build_gpt_image(first_lba, last_lba))
+                .expect("create test image");
+            let error = read_partition_table(&test_file).expect_err("malformed entry");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", error);
+        }
+
+        // MBR partition past the image end
+        let mut image = vec![0u8; 512 * 16];
+        image[510..512].copy_from_slice(&MBR_BOOT_SIGNATURE);
+        write_mbr_entry(&mut image, 0, 0x83, 8, 9);
+        std::fs::write(&test_file, &image).expect("create test image");
+        let error = read_partition_table(&test_file).expect_err("past the image");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
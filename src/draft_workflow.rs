@@ -0,0 +1,1828 @@
+//! Shared backup/draft/verify workflow for multi-byte operations
+//!
+//! The single-byte operations (`replace`, `remove`, `insert`) each spell out
+//! the full workflow. Range and transform operations share it through
+//! `run_draft_workflow`:
+//!
+//! 1. Validate the target (exists, is a regular file)
+//! 2. Create a backup copy (`<name>.backup`)
+//! 3. Build the draft (`<name>.draft`) by appending bytes from the original
+//!    with bucket-brigade buffers (operation-specific closure)
+//! 4. Check the draft's total length against the expected length
+//! 5. Run operation-specific comparison of original vs. draft
+//! 6. Atomically rename the draft over the original
+//! 7. Remove the backup
+//!
+//! The backup name and location, whether step 7 runs, the verification in
+//! step 5, the durability of the draft and the buffer size all come from the
+//! active `OperationConfig` (see `operation_config.rs`); the defaults are as
+//! described here. A config can also carry preconditions on the original
+//! (its expected size and SHA-256), checked between steps 1 and 2, and
+//! can turn on the crash-recovery journal (see `journal.rs`), written
+//! before step 2 and removed when the operation returns.
+//!
+//! The original file is never modified in place; if anything fails before
+//! step 6 the draft is removed and the original is untouched.
+//!
+//! With `OperationConfig::utf8_guard`, step 5 also checks the whole draft is
+//! valid UTF-8, so a byte-wise edit of a text file cannot commit a split or
+//! stray multi-byte sequence. With `OperationConfig::canary_seal`, step 5
+//! ends by re-signing the draft's canary trailer (see `canary.rs`).
+//!
+//! Inside a `FileSession` transaction (see `file_session.rs`) steps 2 and 7
+//! are skipped: the session already holds a backup of the file, taken once
+//! when the session was opened.
+//!
+//! The streaming helpers here (`copy_exact_bytes`, `verify_next_bytes_match`,
+//! ...) are the building blocks operations use inside their closures.
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::any::Any;
+use std::{
+    cell::RefCell,
+    fs::{self, File, FileTimes, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::backup_manifest::{
+    BACKUP_MANIFEST_SUFFIX, backup_manifest_path, record_backup_manifest, remove_backup_manifest,
+};
+use crate::canary::seal_draft_with_canary;
+use crate::custody_report::{current_unix_seconds, format_rfc3339_utc};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::hash::HashValue;
+use crate::hash::sha256::sha256_of_file;
+use crate::journal::{JournalPhase, OperationJournal};
+use crate::lock_file::OperationLock;
+use crate::memory_budget::admit_draft_workflow;
+use crate::operation_config::{
+    BackupNaming, OperationConfig, OverwritePolicy, SymlinkPolicy, active_buffer_size,
+    active_operation_config, active_verification_hash,
+};
+use crate::page_cache::{DraftFile, advise_reader, release_cached_pages};
+use crate::reflink::clone_or_copy;
+use crate::reporter::{
+    OperationPhase, ReportEvent, report, report_backup_kept, report_check_passed, report_detail,
+    report_phase, report_problem,
+};
+use crate::utf8_operations::validate_utf8_file;
+use crate::util::{chunk_length, first_mismatch};
+use crate::writability::check_target_writable;
+
+/// Default size of the bucket-brigade buffers (64 KiB, heap-allocated once
+/// per pass); `OperationConfig::buffer_size` overrides it
+pub const DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Buffer size of the tiny-buffer mode (`OperationConfigBuilder::tiny_buffer`),
+/// for hosts where a few 64 KiB buffers are too much memory
+pub const TINY_BUCKET_BRIGADE_BUFFER_SIZE: usize = 64;
+
+/// Capacity of the `BufReader`/`BufWriter` wrapped around the source and
+/// draft handles. Chunks at least this large bypass it; smaller ones
+/// (tiny-buffer mode) are batched into fewer system calls
+pub const STREAM_BUFFER_CAPACITY: usize = 8 * 1024;
+
+/// Loop iterations allowed per expected full chunk (see `chunk_limit_for`).
+/// A short read can split a chunk in two, e.g. at a `BufReader` refill
+pub(crate) const CHUNK_LIMIT_ITERATIONS_PER_CHUNK: usize = 2;
+
+/// Extra loop iterations every pass is allowed (EOF probe, empty passes)
+pub(crate) const CHUNK_LIMIT_SLACK: usize = 64;
+
+/// Suffix appended to the file name for the backup copy
+pub const BACKUP_FILE_SUFFIX: &str = ".backup";
+
+/// Suffix appended to the file name for the draft under construction
+pub const DRAFT_FILE_SUFFIX: &str = ".draft";
+
+/// Suffix appended to the file name for the crash-recovery journal
+/// (see `journal.rs`)
+pub const JOURNAL_FILE_SUFFIX: &str = ".journal";
+
+/// How far the draft is pushed toward stable storage before the rename.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurabilityMode {
+    /// `flush()` only: data is handed to the OS, not forced to disk
+    FlushOnly,
+    /// `sync_all()` on the draft before the rename
+    SyncDraft,
+    /// `sync_all()` on the draft before the rename and on its directory
+    /// after it, so a power loss right after success leaves the new file,
+    /// not an empty or missing one (the directory sync is Unix-only)
+    SyncDraftAndDirectory,
+}
+
+impl DurabilityMode {
+    /// Stable name used in reports and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DurabilityMode::FlushOnly => "flush-only",
+            DurabilityMode::SyncDraft => "sync-draft",
+            DurabilityMode::SyncDraftAndDirectory => "sync-draft-and-directory",
+        }
+    }
+
+    /// Whether the draft is `sync_all()`ed before the rename.
+    pub(crate) fn syncs_draft(&self) -> bool {
+        *self != DurabilityMode::FlushOnly
+    }
+
+    /// Flushes a finished, buffered draft and applies this mode to it.
+    ///
+    /// This is the only flush of the draft: the build loops write through
+    /// the `BufWriter` and never flush per chunk.
+    pub(crate) fn finish_draft(&self, draft_writer: BufWriter<DraftFile>) -> io::Result<()> {
+        let mut draft_file = draft_writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .finish()?;
+        draft_file.flush()?;
+        if self.syncs_draft() {
+            draft_file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Applies this mode after the draft was renamed onto
+    /// `replaced_file_path`: `SyncDraftAndDirectory` syncs the directory
+    /// that holds the new entry.
+    ///
+    /// # Returns
+    /// - `Err(io::Error)` carrying `ByteOpError::DirectorySyncFailed` if the
+    ///   sync fails; the rename has already happened
+    pub(crate) fn finish_replace(&self, replaced_file_path: &Path) -> io::Result<()> {
+        if *self != DurabilityMode::SyncDraftAndDirectory {
+            return Ok(());
+        }
+        sync_parent_directory(replaced_file_path).map_err(|source| {
+            let error = ByteOpError::DirectorySyncFailed { source };
+            report_problem!("ERROR: {}", error);
+            io::Error::from(error)
+        })
+    }
+}
+
+/// Syncs the directory containing `file_path` (its entries, such as a
+/// rename into it).
+///
+/// Only Unix can open a directory to sync it; elsewhere this does nothing
+/// and the rename is as durable as the filesystem makes it.
+pub(crate) fn sync_parent_directory(file_path: &Path) -> io::Result<()> {
+    if !cfg!(unix) {
+        return Ok(());
+    }
+    let parent_directory = file_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    File::open(parent_directory)?.sync_all()
+}
+
+/// Durability mode of the default `OperationConfig`
+pub const DEFAULT_DURABILITY_MODE: DurabilityMode = DurabilityMode::FlushOnly;
+
+/// How thoroughly a draft is checked before it replaces the original.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationLevel {
+    /// Total length plus a byte-for-byte comparison of every region
+    /// (pre-position, at-position, frame-shifted post-position)
+    FullComparison,
+    /// Total length only; the draft's bytes are not compared. For scratch
+    /// copies where the extra read pass is not worth it
+    LengthOnly,
+}
+
+impl VerificationLevel {
+    /// Stable name used in reports and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            VerificationLevel::FullComparison => "full-comparison",
+            VerificationLevel::LengthOnly => "length-only",
+        }
+    }
+}
+
+/// Verification level of the default `OperationConfig`
+pub const DEFAULT_VERIFICATION_LEVEL: VerificationLevel = VerificationLevel::FullComparison;
+
+thread_local! {
+    /// Targets whose backup is held by an open `FileSession` on this thread
+    static SESSION_BACKED_TARGETS: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `operations` with `target_path` marked as session-backed, so
+/// `run_draft_workflow` skips its per-operation backup copy for it.
+///
+/// The mark is removed when `operations` returns, whether or not it failed.
+pub(crate) fn with_session_backup<T>(
+    target_path: &Path,
+    operations: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    SESSION_BACKED_TARGETS.with(|targets| targets.borrow_mut().push(target_path.to_path_buf()));
+    let result = operations();
+    SESSION_BACKED_TARGETS.with(|targets| {
+        let mut targets = targets.borrow_mut();
+        if let Some(index) = targets.iter().rposition(|path| path == target_path) {
+            targets.remove(index);
+        }
+    });
+    result
+}
+
+/// If the active config has the UTF-8 guard on, checks the finished draft is
+/// valid UTF-8.
+///
+/// On failure the draft is removed and a `ByteOpError::Utf8GuardRejected`
+/// names the operation, the target, and the first bad byte. Called just
+/// before the rename, after the operation's own verification has passed.
+pub(crate) fn check_draft_against_utf8_guard(
+    original_file_path: &Path,
+    draft_file_path: &Path,
+    operation_name: &str,
+) -> io::Result<()> {
+    if !active_operation_config().utf8_guard() {
+        return Ok(());
+    }
+    if let Err(e) = validate_utf8_file(draft_file_path) {
+        report_problem!("ERROR: UTF-8 guard rejected draft: {}", e);
+        let _ = fs::remove_file(draft_file_path);
+        return Err(ByteOpError::Utf8GuardRejected {
+            operation_name: operation_name.to_string(),
+            path: original_file_path.to_path_buf(),
+            detail: e.to_string(),
+        }
+        .into());
+    }
+    report_detail!("UTF-8 guard: draft is valid UTF-8");
+    Ok(())
+}
+
+fn is_session_backed(target_path: &Path) -> bool {
+    SESSION_BACKED_TARGETS.with(|targets| targets.borrow().iter().any(|path| path == target_path))
+}
+
+/// Backup, draft, and journal paths for one operation on one file.
+#[derive(Debug, Clone)]
+pub(crate) struct DraftArtifactPaths {
+    pub backup_file_path: PathBuf,
+    pub draft_file_path: PathBuf,
+    pub journal_file_path: PathBuf,
+}
+
+/// Validates that the target exists and is a regular file; returns its size.
+pub(crate) fn validate_target_file(original_file_path: &Path) -> io::Result<u64> {
+    // Verify file exists before any operations
+    if !original_file_path.exists() {
+        let error = ByteOpError::target_not_found(original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Verify file is actually a file, not a directory
+    if !original_file_path.is_file() {
+        let error = ByteOpError::not_a_file(original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    let original_metadata = fs::metadata(original_file_path)?;
+    Ok(original_metadata.len())
+}
+
+/// Applies the config's `SymlinkPolicy` to the target path.
+///
+/// A rename onto a symlink would replace the link instead of the file it
+/// points to, so a symlinked target is resolved to that file
+/// (`FollowAndEditTarget`) or refused (`RefuseSymlinks`). Only the last
+/// path component matters; any other path is returned unchanged, as is a
+/// missing one (validation reports it).
+pub(crate) fn resolve_symlinked_target(
+    original_file_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<PathBuf> {
+    let is_symlink = fs::symlink_metadata(original_file_path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_symlink {
+        return Ok(original_file_path.to_path_buf());
+    }
+
+    match config.symlink_policy() {
+        SymlinkPolicy::RefuseSymlinks => {
+            let error = ByteOpError::SymlinkRefused {
+                path: original_file_path.to_path_buf(),
+            };
+            report_problem!("ERROR: {}", error);
+            Err(error.into())
+        }
+        SymlinkPolicy::FollowAndEditTarget => match fs::canonicalize(original_file_path) {
+            Ok(link_target_path) => {
+                report_detail!(
+                    "{} is a symlink; editing {}",
+                    original_file_path.display(),
+                    link_target_path.display()
+                );
+                Ok(link_target_path)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let error = ByteOpError::target_not_found(original_file_path);
+                report_problem!("ERROR: {}", error);
+                Err(error.into())
+            }
+            Err(e) => Err(e),
+        },
+    }
+}
+
+/// Checks the active config's preconditions (memory budget, then the
+/// original's expected size and SHA-256) before any backup or draft is
+/// created.
+pub(crate) fn check_original_preconditions(
+    original_file_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    if let Err(e) = admit_draft_workflow(config) {
+        report_problem!("ERROR: {}", e);
+        return Err(e);
+    }
+    if let Some(expected_size) = config.expected_original_size() {
+        let actual_size = fs::metadata(original_file_path)?.len();
+        if actual_size != expected_size {
+            let error = ByteOpError::UnexpectedFileSize {
+                expected: expected_size,
+                actual: actual_size,
+            };
+            report_problem!("ERROR: {}", error);
+            return Err(error.into());
+        }
+        report_detail!("Precondition passed: original is {} bytes", actual_size);
+    }
+    if let Some(expected_sha256) = config.expected_original_sha256() {
+        let actual_sha256 = sha256_of_file(original_file_path)?;
+        if actual_sha256 != expected_sha256 {
+            let error = ByteOpError::UnexpectedFileHash {
+                expected: expected_sha256,
+                actual: actual_sha256,
+            };
+            report_problem!("ERROR: {}", error);
+            return Err(error.into());
+        }
+        report_detail!("Precondition passed: original SHA-256 matches");
+    }
+    Ok(())
+}
+
+/// Builds the backup, `.draft`, and `.journal` paths for a target file.
+///
+/// The draft and journal are always siblings of the target; the backup
+/// name and directory come from the active `OperationConfig`.
+pub(crate) fn build_artifact_paths(original_file_path: &Path) -> io::Result<DraftArtifactPaths> {
+    let config = active_operation_config();
+    let file_name = original_file_path
+        .file_name()
+        .ok_or_else(|| ByteOpError::invalid_file_name(original_file_path))?
+        .to_string_lossy()
+        .to_string();
+
+    let backup_path_for = |backup_file_name: String| match config.backup_directory() {
+        Some(backup_directory) => backup_directory.join(backup_file_name),
+        None => original_file_path.with_file_name(backup_file_name),
+    };
+    let backup_file_path = match config.backup_naming() {
+        BackupNaming::Suffixed => {
+            backup_path_for(format!("{}{}", file_name, config.backup_suffix()))
+        }
+        BackupNaming::Timestamped => {
+            // ISO 8601 basic format; a second operation within the same
+            // second gets `-2`, `-3`, ... rather than overwriting
+            let timestamp = format_rfc3339_utc(current_unix_seconds()).replace(['-', ':', 'Z'], "");
+            let mut backup_file_path = backup_path_for(format!(
+                "{}.{}{}",
+                file_name,
+                timestamp,
+                config.backup_suffix()
+            ));
+            let mut collision_number = 1;
+            while backup_file_path.exists() {
+                collision_number += 1;
+                backup_file_path = backup_path_for(format!(
+                    "{}.{}-{}{}",
+                    file_name,
+                    timestamp,
+                    collision_number,
+                    config.backup_suffix()
+                ));
+            }
+            backup_file_path
+        }
+    };
+
+    let mut draft_file_path = original_file_path.to_path_buf();
+    draft_file_path.set_file_name(format!("{}{}", file_name, DRAFT_FILE_SUFFIX));
+    let journal_file_path =
+        original_file_path.with_file_name(format!("{}{}", file_name, JOURNAL_FILE_SUFFIX));
+
+    Ok(DraftArtifactPaths {
+        backup_file_path,
+        draft_file_path,
+        journal_file_path,
+    })
+}
+
+/// Applies the active overwrite policy to leftover backup/draft files.
+///
+/// `check_backup` is false when no backup will be written (session-backed
+/// targets). Returns `ByteOpError::ArtifactExists` if the policy forbids
+/// replacing a file that is already there, and `ByteOpError::PendingJournal`
+/// under any policy if an interrupted operation left a journal.
+///
+/// A leftover backup with exactly the original's content (e.g. retained by
+/// a failed operation) is redundant and may always be replaced.
+pub(crate) fn check_artifact_overwrite(
+    original_file_path: &Path,
+    artifact_paths: &DraftArtifactPaths,
+    check_backup: bool,
+) -> io::Result<()> {
+    if artifact_paths.journal_file_path.exists() {
+        let error = ByteOpError::PendingJournal {
+            path: artifact_paths.journal_file_path.clone(),
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+    if active_operation_config().overwrite_policy() == OverwritePolicy::ReplaceStaleArtifacts {
+        return Ok(());
+    }
+
+    let backup_path = Some(&artifact_paths.backup_file_path).filter(|_| check_backup);
+    for artifact_path in backup_path
+        .into_iter()
+        .chain([&artifact_paths.draft_file_path])
+    {
+        if artifact_path.exists() {
+            if *artifact_path == artifact_paths.backup_file_path
+                && original_file_path.is_file()
+                && files_have_same_content(artifact_path, original_file_path)?
+            {
+                report_detail!(
+                    "Leftover backup matches the original; replacing it: {}",
+                    artifact_path.display()
+                );
+                continue;
+            }
+            let error = ByteOpError::ArtifactExists {
+                path: artifact_path.clone(),
+            };
+            report_problem!("ERROR: {}", error);
+            return Err(error.into());
+        }
+    }
+    Ok(())
+}
+
+/// Newest timestamped backup (`<name>.<UTC time>[-n]<suffix>`) of a target
+/// in the active config's backup location, if there is one.
+pub(crate) fn latest_timestamped_backup_path(
+    original_file_path: &Path,
+) -> io::Result<Option<PathBuf>> {
+    let config = active_operation_config();
+    let file_name = original_file_path
+        .file_name()
+        .ok_or_else(|| ByteOpError::invalid_file_name(original_file_path))?
+        .to_string_lossy()
+        .to_string();
+    let backup_directory = match config.backup_directory() {
+        Some(backup_directory) => backup_directory.to_path_buf(),
+        None => original_file_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf(),
+    };
+
+    let mut latest: Option<((String, u32), PathBuf)> = None;
+    for entry in fs::read_dir(&backup_directory)? {
+        let entry = entry?;
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        let Some(stamp) = entry_name
+            .strip_prefix(&file_name)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .and_then(|rest| rest.strip_suffix(config.backup_suffix()))
+        else {
+            continue;
+        };
+        let (timestamp, collision_number) = match stamp.split_once('-') {
+            Some((timestamp, number)) => match number.parse::<u32>() {
+                Ok(number) => (timestamp, number),
+                Err(_) => continue,
+            },
+            None => (stamp, 1),
+        };
+        let is_timestamp = timestamp.len() == 15
+            && timestamp.char_indices().all(|(index, character)| {
+                if index == 8 {
+                    character == 'T'
+                } else {
+                    character.is_ascii_digit()
+                }
+            });
+        if !is_timestamp {
+            continue;
+        }
+        let sort_key = (timestamp.to_string(), collision_number);
+        if latest
+            .as_ref()
+            .is_none_or(|(latest_key, _)| sort_key > *latest_key)
+        {
+            latest = Some((sort_key, entry.path()));
+        }
+    }
+    Ok(latest.map(|(_, path)| path))
+}
+
+/// Moves a finished draft (or other replacement) onto `original_file_path`.
+///
+/// On Unix this is `rename(2)`, which is atomic. On Windows an existing
+/// original is replaced with `ReplaceFileW` (see `windows_replace.rs`),
+/// which keeps its attributes and alternate data streams, and transient
+/// sharing violations are retried.
+///
+/// A draft is always next to its target, but a replacement from a separate
+/// backup directory (journal recovery, orphan roll-back) may be on another
+/// filesystem, where a rename fails with `EXDEV`. That case falls back to
+/// `replace_across_devices`.
+pub(crate) fn replace_with_draft(
+    draft_file_path: &Path,
+    original_file_path: &Path,
+) -> io::Result<()> {
+    match rename_over(draft_file_path, original_file_path) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            report_detail!(
+                "{} is on another filesystem; copying it next to {} first",
+                draft_file_path.display(),
+                original_file_path.display()
+            );
+            replace_across_devices(draft_file_path, original_file_path)
+        }
+        rename_result => rename_result,
+    }
+}
+
+fn rename_over(replacement_file_path: &Path, original_file_path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    if original_file_path.exists() {
+        return crate::windows_replace::replace_file(original_file_path, replacement_file_path);
+    }
+    fs::rename(replacement_file_path, original_file_path)
+}
+
+/// Replaces `original_file_path` with a file on another filesystem: copies
+/// it to the target's draft path, checks the copy's SHA-256, syncs it,
+/// renames it within the target's directory, and only then removes the
+/// source. The result is the same as a rename; if any step fails the copy
+/// is removed and the original is unchanged.
+fn replace_across_devices(source_file_path: &Path, original_file_path: &Path) -> io::Result<()> {
+    let staging_file_path = build_artifact_paths(original_file_path)?.draft_file_path;
+    let source_sha256 = sha256_of_file(source_file_path)?;
+    let staging_result = fs::copy(source_file_path, &staging_file_path)
+        .and_then(|_| File::open(&staging_file_path)?.sync_all())
+        .and_then(|()| sha256_of_file(&staging_file_path));
+    let staging_error = match staging_result {
+        Ok(staging_sha256) if staging_sha256 == source_sha256 => None,
+        Ok(_) => Some(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Copy of {} does not match it", source_file_path.display()),
+        )),
+        Err(e) => Some(e),
+    };
+    if let Some(error) = staging_error {
+        let _ = fs::remove_file(&staging_file_path);
+        return Err(error);
+    }
+
+    if let Err(e) = rename_over(&staging_file_path, original_file_path) {
+        let _ = fs::remove_file(&staging_file_path);
+        return Err(e);
+    }
+    fs::remove_file(source_file_path)
+}
+
+/// Gives the draft the original's permission bits, so the rename does not
+/// change the file's mode (an executable, a 0600 secret, a read-only file),
+/// and, with `preserve_timestamps`, its access and modification times.
+/// On failure the draft is removed and the original is unchanged.
+pub(crate) fn copy_original_metadata(
+    original_file_path: &Path,
+    draft_file_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    let copy_result = fs::metadata(original_file_path).and_then(|metadata| {
+        // Times first: the permissions may make the draft read-only
+        if config.preserve_timestamps() {
+            let original_times = FileTimes::new()
+                .set_accessed(metadata.accessed()?)
+                .set_modified(metadata.modified()?);
+            OpenOptions::new()
+                .write(true)
+                .open(draft_file_path)?
+                .set_times(original_times)?;
+        }
+        fs::set_permissions(draft_file_path, metadata.permissions())
+    });
+    if let Err(e) = copy_result {
+        report_problem!("ERROR: Could not copy file metadata to the draft: {}", e);
+        let _ = fs::remove_file(draft_file_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Path of numbered backup `number` (`<backup>.<number>`, 1 is the newest).
+pub(crate) fn rotated_backup_path(backup_file_path: &Path, number: usize) -> PathBuf {
+    let mut rotated_path = backup_file_path.to_path_buf().into_os_string();
+    rotated_path.push(format!(".{}", number));
+    PathBuf::from(rotated_path)
+}
+
+/// Moves a finished operation's backup to `<backup>.1`, after shifting the
+/// existing numbered backups up by one and pruning any numbered beyond
+/// `rotated_backup_count`.
+///
+/// Non-fatal like backup removal: the operation has already succeeded, so
+/// failures are reported as warnings and the files are left where they are.
+pub(crate) fn rotate_backups(backup_file_path: &Path, rotated_backup_count: usize) {
+    let rotation_result = (|| -> io::Result<()> {
+        // Prune everything that would be shifted past the limit, including
+        // leftovers of an earlier, larger limit
+        let backup_directory = backup_file_path.parent().unwrap_or(Path::new(""));
+        let backup_name = backup_file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let listing_directory = if backup_directory.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            backup_directory
+        };
+        for entry in fs::read_dir(listing_directory)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let numbered_name = file_name
+                .strip_prefix(&backup_name)
+                .and_then(|rest| rest.strip_prefix('.'));
+            let number = numbered_name
+                .map(|rest| rest.strip_suffix(BACKUP_MANIFEST_SUFFIX).unwrap_or(rest))
+                .and_then(|digits| digits.parse::<usize>().ok());
+            if let Some(number) = number
+                && number >= rotated_backup_count
+            {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        // Each backup's manifest moves with it
+        let move_with_manifest = |from_path: &Path, to_path: &Path| -> io::Result<()> {
+            fs::rename(from_path, to_path)?;
+            let from_manifest_path = backup_manifest_path(from_path);
+            if from_manifest_path.exists() {
+                fs::rename(from_manifest_path, backup_manifest_path(to_path))?;
+            } else {
+                remove_backup_manifest(to_path)?;
+            }
+            Ok(())
+        };
+        for number in (1..rotated_backup_count).rev() {
+            let older_path = rotated_backup_path(backup_file_path, number);
+            if older_path.exists() {
+                move_with_manifest(
+                    &older_path,
+                    &rotated_backup_path(backup_file_path, number + 1),
+                )?;
+            }
+        }
+        move_with_manifest(backup_file_path, &rotated_backup_path(backup_file_path, 1))
+    })();
+
+    match rotation_result {
+        Ok(()) => {
+            report_detail!("Backups rotated (keeping {})", rotated_backup_count);
+            report_backup_kept(&rotated_backup_path(backup_file_path, 1));
+        }
+        Err(e) => {
+            report_problem!(
+                "WARNING: Could not rotate backups of: {} ({})",
+                backup_file_path.display(),
+                e
+            );
+            if backup_file_path.exists() {
+                report_backup_kept(backup_file_path);
+            }
+        }
+    }
+}
+
+/// True if both files have the same size and SHA-256.
+pub(crate) fn files_have_same_content(first_path: &Path, second_path: &Path) -> io::Result<bool> {
+    if fs::metadata(first_path)?.len() != fs::metadata(second_path)?.len() {
+        return Ok(false);
+    }
+    Ok(sha256_of_file(first_path)? == sha256_of_file(second_path)?)
+}
+
+/// Runs the full backup → draft → verify → replace → cleanup workflow.
+///
+/// # Parameters
+/// - `original_file_path`: The file to (effectively) modify
+/// - `operation_name`: Short label for debug output, e.g. "Byte Range Removal"
+/// - `expected_draft_size`: Exact length the finished draft must have
+/// - `build_draft`: Appends the draft's bytes; receives the original (opened
+///   for reading at position 0) and the empty draft (opened for writing)
+/// - `verify_draft`: Compares original vs. finished draft; receives both paths
+///
+/// # Returns
+/// - `Ok(())` once the original has been replaced and the backup removed
+///   (or kept, if the active config says so)
+/// - `Err(io::Error)` if any phase fails; the draft is removed and the
+///   original is left untouched (the backup copy is retained)
+pub(crate) fn run_draft_workflow<BuildDraft, VerifyDraft>(
+    original_file_path: &Path,
+    operation_name: &str,
+    expected_draft_size: u64,
+    build_draft: BuildDraft,
+    verify_draft: VerifyDraft,
+) -> io::Result<()>
+where
+    BuildDraft: FnOnce(&mut BufReader<File>, &mut BufWriter<DraftFile>) -> io::Result<()>,
+    VerifyDraft: FnOnce(&Path, &Path) -> io::Result<()>,
+{
+    // =========================================
+    // Path Construction Phase
+    // =========================================
+    let config = active_operation_config();
+    report(&ReportEvent::OperationStarted {
+        operation_name,
+        target_path: original_file_path,
+    });
+    let original_file_path = &resolve_symlinked_target(original_file_path, &config)?;
+    let artifact_paths = build_artifact_paths(original_file_path)?;
+    check_target_writable(original_file_path)?;
+    let _operation_lock = OperationLock::acquire(original_file_path)?;
+
+    report_detail!("Backup path: {}", artifact_paths.backup_file_path.display());
+    report_detail!("Draft path: {}", artifact_paths.draft_file_path.display());
+    check_original_preconditions(original_file_path, &config)?;
+
+    // =========================================
+    // Backup Creation Phase
+    // =========================================
+    report_phase(OperationPhase::Backup);
+    let session_backed = is_session_backed(original_file_path);
+    check_artifact_overwrite(original_file_path, &artifact_paths, !session_backed)?;
+    let mut journal = OperationJournal::begin(
+        original_file_path,
+        operation_name,
+        &artifact_paths,
+        !session_backed,
+        &config,
+    )?;
+    if session_backed {
+        report_detail!("Session backup in place; skipping per-operation backup");
+    } else {
+        clone_or_copy(original_file_path, &artifact_paths.backup_file_path).map_err(|source| {
+            let error = ByteOpError::BackupFailed { source };
+            report_problem!("ERROR: {}", error);
+            io::Error::from(error)
+        })?;
+        record_backup_manifest(
+            &artifact_paths.backup_file_path,
+            original_file_path,
+            operation_name,
+            &config,
+        )?;
+    }
+    journal.mark(JournalPhase::BackedUp)?;
+
+    // =========================================
+    // Draft File Construction Phase
+    // =========================================
+    report_phase(OperationPhase::Draft);
+
+    let build_result = (|| -> io::Result<()> {
+        let mut source_file = open_buffered_reader(original_file_path)?;
+        let mut draft_file = create_buffered_draft(&artifact_paths.draft_file_path)?;
+        build_draft(&mut source_file, &mut draft_file)?;
+        config.durability_mode().finish_draft(draft_file)?;
+        Ok(())
+        // Both handles closed here, before verification and rename
+    })();
+
+    if let Err(e) = build_result {
+        report_problem!("ERROR: Draft construction failed: {}", e);
+        let _ = fs::remove_file(&artifact_paths.draft_file_path);
+        return Err(e);
+    }
+
+    // =========================================
+    // Basic Verification Phase
+    // =========================================
+    let draft_size = fs::metadata(&artifact_paths.draft_file_path)?.len();
+
+    if draft_size != expected_draft_size {
+        report_problem!(
+            "ERROR: Draft size mismatch - draft: {} bytes, expected: {} bytes",
+            draft_size,
+            expected_draft_size
+        );
+        let _ = fs::remove_file(&artifact_paths.draft_file_path);
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            expected_draft_size,
+            draft_size,
+        )
+        .into());
+    }
+
+    // =========================================
+    // Comprehensive Verification Phase
+    // =========================================
+    report_phase(OperationPhase::Verify);
+
+    if config.verification_level() == VerificationLevel::LengthOnly {
+        report_detail!("Verification level length-only: byte comparison skipped");
+    } else if let Err(e) = verify_draft(original_file_path, &artifact_paths.draft_file_path) {
+        report_problem!("ERROR: Verification failed: {}", e);
+        let _ = fs::remove_file(&artifact_paths.draft_file_path);
+        return Err(e);
+    }
+
+    check_draft_against_utf8_guard(
+        original_file_path,
+        &artifact_paths.draft_file_path,
+        operation_name,
+    )?;
+    seal_draft_with_canary(original_file_path, &artifact_paths.draft_file_path)?;
+    // A newly installed canary trailer adds to the verified size
+    let draft_size = fs::metadata(&artifact_paths.draft_file_path)?.len();
+    journal.mark_draft_verified(&artifact_paths.draft_file_path)?;
+
+    report_detail!("All verification checks PASSED\n");
+
+    // =========================================
+    // Atomic Replacement Phase
+    // =========================================
+    report_phase(OperationPhase::Replace);
+    release_cached_pages(&[
+        original_file_path,
+        &artifact_paths.backup_file_path,
+        &artifact_paths.draft_file_path,
+    ]);
+    copy_original_metadata(original_file_path, &artifact_paths.draft_file_path, &config)?;
+
+    if let Err(source) = replace_with_draft(&artifact_paths.draft_file_path, original_file_path) {
+        // DO NOT try to copy over the original!
+        // Leave all files as-is for safety
+        let error = ByteOpError::AtomicReplaceFailed { source };
+        report_problem!("{}", error);
+        return Err(error.into());
+    }
+    config
+        .durability_mode()
+        .finish_replace(original_file_path)?;
+    journal.mark_replaced();
+
+    // =========================================
+    // Cleanup Phase
+    // =========================================
+    report_phase(OperationPhase::Cleanup);
+    if session_backed {
+        // Nothing to clean up; the session owns its backup
+    } else if let Some(rotated_backup_count) = config.rotated_backup_count() {
+        rotate_backups(&artifact_paths.backup_file_path, rotated_backup_count);
+    } else if config.keep_backup() {
+        report_backup_kept(&artifact_paths.backup_file_path);
+    } else if let Err(e) = fs::remove_file(&artifact_paths.backup_file_path) {
+        // Non-fatal: backup removal failure is not critical
+        report_problem!(
+            "WARNING: Could not remove backup file: {} ({})",
+            artifact_paths.backup_file_path.display(),
+            e
+        );
+        report_backup_kept(&artifact_paths.backup_file_path);
+    }
+
+    report_detail!("New size: {} bytes", draft_size);
+    report(&ReportEvent::OperationFinished {
+        operation_name,
+        target_path: original_file_path,
+    });
+
+    Ok(())
+}
+
+// =========================================
+// Draft Construction Helpers
+// =========================================
+
+/// Opens a file for buffered, sequential reading, with the page-cache
+/// hints of the active config.
+pub(crate) fn open_buffered_reader(file_path: &Path) -> io::Result<BufReader<File>> {
+    let file_handle = File::open(file_path)?;
+    advise_reader(&file_handle);
+    Ok(BufReader::with_capacity(
+        STREAM_BUFFER_CAPACITY,
+        file_handle,
+    ))
+}
+
+/// Creates (or truncates) a draft file behind a `BufWriter`.
+///
+/// Finish it with `DurabilityMode::finish_draft`, which flushes once.
+pub(crate) fn create_buffered_draft(draft_file_path: &Path) -> io::Result<BufWriter<DraftFile>> {
+    Ok(BufWriter::with_capacity(
+        STREAM_BUFFER_CAPACITY,
+        DraftFile::create(draft_file_path)?,
+    ))
+}
+
+/// Opens a second, buffered read handle on a file, positioned at
+/// `byte_offset`.
+///
+/// Used when an operation needs bytes from elsewhere in the original (e.g.
+/// copying a region forward or backward) while the main source handle keeps
+/// streaming sequentially.
+pub(crate) fn open_at_offset(file_path: &Path, byte_offset: u64) -> io::Result<BufReader<File>> {
+    let mut file_handle = File::open(file_path)?;
+    file_handle.seek(SeekFrom::Start(byte_offset))?;
+    Ok(BufReader::with_capacity(
+        STREAM_BUFFER_CAPACITY,
+        file_handle,
+    ))
+}
+
+/// Appends exactly `byte_count` bytes from `source` to `draft`.
+///
+/// Errors if the source reaches EOF early or a write is incomplete.
+pub(crate) fn copy_exact_bytes<R: Read + 'static, W: Write + 'static>(
+    source: &mut R,
+    draft: &mut W,
+    byte_count: u64,
+) -> io::Result<()> {
+    if let Some(copied) = kernel_copy(source, draft, Some(byte_count)) {
+        let bytes_copied = copied?;
+        if bytes_copied < byte_count {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Source ended early: {} of {} bytes still expected",
+                    byte_count - bytes_copied,
+                    byte_count
+                ),
+            ));
+        }
+        return Ok(());
+    }
+
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+    let mut bytes_remaining = byte_count;
+    let chunk_limit = chunk_limit_for(byte_count);
+    let mut chunk_number: usize = 0;
+
+    while bytes_remaining > 0 {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_read = chunk_length(bucket_brigade_buffer.len(), bytes_remaining);
+        let bytes_read = source.read(&mut bucket_brigade_buffer[..bytes_to_read])?;
+        if bytes_read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Source ended early: {} of {} bytes still expected",
+                    bytes_remaining, byte_count
+                ),
+            ));
+        }
+
+        write_all_checked(draft, &bucket_brigade_buffer[..bytes_read])?;
+        bytes_remaining -= bytes_read as u64;
+    }
+
+    Ok(())
+}
+
+/// Appends everything remaining in `source` to `draft`; returns the byte count.
+///
+/// The loop bound comes from the distance between the read position and
+/// the end of `source` when the copy starts.
+pub(crate) fn copy_remaining_bytes<R: Read + Seek + 'static, W: Write + 'static>(
+    source: &mut R,
+    draft: &mut W,
+) -> io::Result<u64> {
+    if let Some(copied) = kernel_copy(source, draft, None) {
+        return copied;
+    }
+
+    let chunk_limit = chunk_limit_for(remaining_byte_count(source)?);
+
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+    let mut total_bytes_copied: u64 = 0;
+    let mut chunk_number: usize = 0;
+
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_read = source.read(&mut bucket_brigade_buffer)?;
+        if bytes_read == 0 {
+            return Ok(total_bytes_copied);
+        }
+
+        write_all_checked(draft, &bucket_brigade_buffer[..bytes_read])?;
+        total_bytes_copied += bytes_read as u64;
+    }
+}
+
+/// Kernel-side copy for the unmodified stretches of a draft build.
+///
+/// When `source` and `draft` are the buffered file handles of a draft
+/// build, the write buffer is flushed and `io::copy` drains the read
+/// buffer, then moves the bytes with `copy_file_range` (falling back to
+/// `sendfile` or `splice`, then to a userspace loop, on filesystems that
+/// refuse them), so they never pass through the bucket brigade. The copy
+/// is bounded by `byte_limit`, or by EOF when `None`. It runs in
+/// `DROP_BEHIND_INTERVAL` pieces so a drop-behind draft keeps dropping
+/// its pages; an `O_DIRECT` draft uses the bucket brigade.
+///
+/// # Returns
+/// - `Some(Ok(bytes))`: bytes copied; fewer than `byte_limit` means EOF
+/// - `None`: other handle types (or platforms), which use the bucket brigade
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn kernel_copy<R: Read + 'static, W: Write + 'static>(
+    source: &mut R,
+    draft: &mut W,
+    byte_limit: Option<u64>,
+) -> Option<io::Result<u64>> {
+    use crate::page_cache::DROP_BEHIND_INTERVAL;
+
+    let source = (source as &mut dyn Any).downcast_mut::<BufReader<File>>()?;
+    let draft = (draft as &mut dyn Any).downcast_mut::<BufWriter<DraftFile>>()?;
+    if !draft.get_ref().accepts_kernel_copy() {
+        return None;
+    }
+    Some((|| {
+        draft.flush()?;
+        let draft_file = draft.get_mut();
+        let mut total_bytes_copied: u64 = 0;
+        loop {
+            let piece_length = match byte_limit {
+                Some(byte_limit) => (byte_limit - total_bytes_copied).min(DROP_BEHIND_INTERVAL),
+                None => DROP_BEHIND_INTERVAL,
+            };
+            if piece_length == 0 {
+                return Ok(total_bytes_copied);
+            }
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            let uring_copied = crate::uring_backend::copy_to_draft(
+                source,
+                draft_file.file_mut(),
+                piece_length,
+                active_buffer_size(),
+            );
+            #[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+            let uring_copied: Option<io::Result<u64>> = None;
+            let bytes_copied = match uring_copied {
+                Some(copied) => copied?,
+                None => io::copy(
+                    &mut (&mut *source).take(piece_length),
+                    draft_file.file_mut(),
+                )?,
+            };
+            draft_file.note_written(bytes_copied)?;
+            total_bytes_copied += bytes_copied;
+            if bytes_copied < piece_length {
+                return Ok(total_bytes_copied);
+            }
+        }
+    })())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn kernel_copy<R: Read, W: Write>(
+    _source: &mut R,
+    _draft: &mut W,
+    _byte_limit: Option<u64>,
+) -> Option<io::Result<u64>> {
+    None
+}
+
+/// Advances `source` by exactly `byte_count` bytes without writing them.
+///
+/// This is the frame-shift step of removal: the skipped bytes are never
+/// appended to the draft.
+pub(crate) fn skip_exact_bytes<R: Read>(source: &mut R, byte_count: u64) -> io::Result<()> {
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+    let mut bytes_remaining = byte_count;
+    let chunk_limit = chunk_limit_for(byte_count);
+    let mut chunk_number: usize = 0;
+
+    while bytes_remaining > 0 {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_read = chunk_length(bucket_brigade_buffer.len(), bytes_remaining);
+        let bytes_read = source.read(&mut bucket_brigade_buffer[..bytes_to_read])?;
+        if bytes_read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Source ended while skipping bytes",
+            ));
+        }
+        bytes_remaining -= bytes_read as u64;
+    }
+
+    Ok(())
+}
+
+/// Writes a slice, treating a short write as an error.
+pub(crate) fn write_all_checked<W: Write>(draft: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let mut bytes_written_total: usize = 0;
+    let write_attempt_limit = chunk_limit_for(bytes.len() as u64);
+    let mut write_attempts: usize = 0;
+
+    while bytes_written_total < bytes.len() {
+        check_chunk_limit(write_attempts, write_attempt_limit)?;
+        write_attempts += 1;
+
+        let bytes_written = draft.write(&bytes[bytes_written_total..])?;
+        if bytes_written == 0 {
+            return Err(ByteOpError::IncompleteWrite {
+                requested: bytes.len(),
+                written: bytes_written_total,
+            }
+            .into());
+        }
+        bytes_written_total += bytes_written;
+    }
+
+    Ok(())
+}
+
+/// Appends `byte_count` bytes of `pattern` repeated (tiled) to `draft`.
+///
+/// Tiling restarts at pattern index 0 at the first written byte; the last
+/// repetition is cut short if `byte_count` is not a multiple of the length.
+pub(crate) fn write_repeating_pattern<W: Write>(
+    draft: &mut W,
+    pattern: &[u8],
+    byte_count: u64,
+) -> io::Result<()> {
+    if pattern.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Fill pattern must not be empty",
+        ));
+    }
+
+    let mut tile_buffer = vec![0u8; active_buffer_size()];
+    let pattern_length = pattern.len() as u64;
+    let mut bytes_written: u64 = 0;
+    let chunk_limit = chunk_limit_for(byte_count);
+    let mut chunk_number: usize = 0;
+
+    while bytes_written < byte_count {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_write = chunk_length(tile_buffer.len(), byte_count - bytes_written);
+        // The pattern index is below `pattern.len()`, so it fits in usize
+        let mut pattern_index = (bytes_written % pattern_length) as usize;
+        for tile_byte in &mut tile_buffer[..bytes_to_write] {
+            *tile_byte = pattern[pattern_index];
+            pattern_index = (pattern_index + 1) % pattern.len();
+        }
+        write_all_checked(draft, &tile_buffer[..bytes_to_write])?;
+        bytes_written += bytes_to_write as u64;
+    }
+
+    Ok(())
+}
+
+// =========================================
+// Verification Helpers
+// =========================================
+
+/// Reports which region failed verification and returns the structured
+/// error for the mismatch.
+fn region_mismatch(
+    region_label: &str,
+    phase: VerificationPhase,
+    offset: u64,
+    expected: u64,
+    actual: u64,
+) -> io::Error {
+    report_problem!("ERROR: {} failed verification", region_label);
+    ByteOpError::verification_failed(phase, offset, expected, actual).into()
+}
+
+/// Compares the next `byte_count` bytes of `original` and `draft`.
+///
+/// Both readers advance by `byte_count`. A mismatch is returned as
+/// `VerificationPhase::CopiedRange` at the draft offset `draft_start + i`;
+/// the reported problem also names the original offset `original_start + i`
+/// so frame-shifted regions are described in each file's own coordinates.
+///
+/// # Returns
+/// - `Ok(HashValue)`: the region's checksum, with the configured
+///   `verification_hash`
+/// - `Err(io::Error)` on early EOF, or carrying
+///   `ByteOpError::VerificationFailed` on the first mismatch or a checksum
+///   mismatch
+pub(crate) fn verify_next_bytes_match<A: Read + 'static, B: Read + 'static>(
+    original: &mut A,
+    draft: &mut B,
+    byte_count: u64,
+    original_start: u64,
+    draft_start: u64,
+    region_label: &str,
+) -> io::Result<HashValue> {
+    let buffer_size = active_buffer_size();
+    let mut original_digest = active_verification_hash().digest();
+    let mut draft_digest = active_verification_hash().digest();
+    let mut bytes_verified: u64 = 0;
+    let chunk_limit = chunk_limit_for(byte_count);
+    let mut chunk_number: usize = 0;
+
+    let mut compare_chunk = |original_chunk: &[u8], draft_chunk: &[u8]| -> io::Result<()> {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        original_digest.update(original_chunk);
+        draft_digest.update(draft_chunk);
+
+        if let Some(i) = first_mismatch(original_chunk, draft_chunk) {
+            let draft_offset = draft_start + bytes_verified + i as u64;
+            report_problem!(
+                "ERROR: {} bytes differ at original[{}], draft[{}]",
+                region_label,
+                original_start + bytes_verified + i as u64,
+                draft_offset
+            );
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::CopiedRange,
+                draft_offset,
+                u64::from(original_chunk[i]),
+                u64::from(draft_chunk[i]),
+            )
+            .into());
+        }
+
+        bytes_verified += original_chunk.len() as u64;
+        Ok(())
+    };
+
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    let uring_compared = crate::uring_backend::compare_readers(
+        original,
+        draft,
+        byte_count,
+        buffer_size,
+        &mut compare_chunk,
+    );
+    #[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+    let uring_compared: Option<io::Result<()>> = None;
+
+    match uring_compared {
+        Some(compared) => compared?,
+        None => {
+            let mut original_buffer = vec![0u8; buffer_size];
+            let mut draft_buffer = vec![0u8; buffer_size];
+            let mut bytes_read: u64 = 0;
+            while bytes_read < byte_count {
+                let bytes_to_read = chunk_length(buffer_size, byte_count - bytes_read);
+                original.read_exact(&mut original_buffer[..bytes_to_read])?;
+                draft.read_exact(&mut draft_buffer[..bytes_to_read])?;
+                compare_chunk(
+                    &original_buffer[..bytes_to_read],
+                    &draft_buffer[..bytes_to_read],
+                )?;
+                bytes_read += bytes_to_read as u64;
+            }
+        }
+    }
+
+    let original_checksum = original_digest.value();
+    let draft_checksum = draft_digest.value();
+    if original_checksum != draft_checksum {
+        return Err(region_mismatch(
+            region_label,
+            VerificationPhase::CopiedRangeChecksum,
+            draft_start,
+            original_checksum.leading_u64(),
+            draft_checksum.leading_u64(),
+        ));
+    }
+
+    report_check_passed!(
+        "   ✓ {} bytes match ({} bytes, {})",
+        region_label,
+        byte_count,
+        original_checksum
+    );
+
+    Ok(original_checksum)
+}
+
+/// Verifies the next `byte_count` bytes of `draft` are `pattern` tiled
+/// from pattern index 0 (see `write_repeating_pattern`).
+pub(crate) fn verify_next_bytes_follow_pattern<B: Read>(
+    draft: &mut B,
+    pattern: &[u8],
+    byte_count: u64,
+    draft_start: u64,
+    region_label: &str,
+) -> io::Result<()> {
+    let mut draft_buffer = vec![0u8; active_buffer_size()];
+    let pattern_length = pattern.len() as u64;
+    let mut bytes_verified: u64 = 0;
+    let chunk_limit = chunk_limit_for(byte_count);
+    let mut chunk_number: usize = 0;
+
+    while bytes_verified < byte_count {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_read = chunk_length(draft_buffer.len(), byte_count - bytes_verified);
+        draft.read_exact(&mut draft_buffer[..bytes_to_read])?;
+
+        for i in 0..bytes_to_read {
+            // The pattern index is below `pattern.len()`, so it fits in usize
+            let expected_byte = pattern[((bytes_verified + i as u64) % pattern_length) as usize];
+            if draft_buffer[i] != expected_byte {
+                return Err(region_mismatch(
+                    region_label,
+                    VerificationPhase::WrittenBytes,
+                    draft_start + bytes_verified + i as u64,
+                    u64::from(expected_byte),
+                    u64::from(draft_buffer[i]),
+                ));
+            }
+        }
+
+        bytes_verified += bytes_to_read as u64;
+    }
+
+    report_check_passed!(
+        "   ✓ {} follows the fill pattern ({} bytes)",
+        region_label,
+        byte_count
+    );
+
+    Ok(())
+}
+
+/// Verifies the next `expected_bytes.len()` bytes of `draft` equal `expected_bytes`.
+pub(crate) fn verify_next_bytes_equal_slice<B: Read>(
+    draft: &mut B,
+    expected_bytes: &[u8],
+    draft_start: u64,
+    region_label: &str,
+) -> io::Result<()> {
+    let mut draft_buffer = vec![0u8; active_buffer_size()];
+    let mut bytes_verified: usize = 0;
+    let chunk_limit = chunk_limit_for(expected_bytes.len() as u64);
+    let mut chunk_number: usize = 0;
+
+    while bytes_verified < expected_bytes.len() {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_read =
+            std::cmp::min(draft_buffer.len(), expected_bytes.len() - bytes_verified);
+        draft.read_exact(&mut draft_buffer[..bytes_to_read])?;
+
+        let expected_chunk = &expected_bytes[bytes_verified..bytes_verified + bytes_to_read];
+        if let Some(i) = first_mismatch(&draft_buffer[..bytes_to_read], expected_chunk) {
+            return Err(region_mismatch(
+                region_label,
+                VerificationPhase::WrittenBytes,
+                draft_start + (bytes_verified + i) as u64,
+                u64::from(expected_chunk[i]),
+                u64::from(draft_buffer[i]),
+            ));
+        }
+
+        bytes_verified += bytes_to_read;
+    }
+
+    report_check_passed!(
+        "   ✓ {} bytes correct ({} bytes)",
+        region_label,
+        expected_bytes.len()
+    );
+
+    Ok(())
+}
+
+/// Verifies that both readers are at EOF (no trailing bytes in either).
+///
+/// Trailing original bytes are reported as
+/// `VerificationPhase::OriginalChanged`, trailing draft bytes as
+/// `VerificationPhase::TrailingBytes`; `actual` is the number of bytes left.
+pub(crate) fn verify_both_at_eof<A: Read, B: Read>(
+    original: &mut A,
+    draft: &mut B,
+) -> io::Result<()> {
+    let original_trailing_count = io::copy(original, &mut io::sink())?;
+    if original_trailing_count != 0 {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::OriginalChanged,
+            0,
+            0,
+            original_trailing_count,
+        )
+        .into());
+    }
+    let draft_trailing_count = io::copy(draft, &mut io::sink())?;
+    if draft_trailing_count != 0 {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TrailingBytes,
+            0,
+            0,
+            draft_trailing_count,
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Bytes between the read position of `source` and its end; the read
+/// position is left unchanged.
+pub(crate) fn remaining_byte_count<S: Seek>(source: &mut S) -> io::Result<u64> {
+    let start_position = source.stream_position()?;
+    let end_position = source.seek(SeekFrom::End(0))?;
+    source.seek(SeekFrom::Start(start_position))?;
+    Ok(end_position.saturating_sub(start_position))
+}
+
+/// Loop bound for a pass over `byte_count` bytes with the active buffer.
+///
+/// Scales with the data instead of capping file size: a pass is allowed
+/// `CHUNK_LIMIT_ITERATIONS_PER_CHUNK` iterations per buffer-sized chunk,
+/// plus `CHUNK_LIMIT_SLACK`. Exceeding it means the loop stopped making
+/// progress (or the file grew during the pass), not that the file is big.
+pub(crate) fn chunk_limit_for(byte_count: u64) -> usize {
+    chunk_limit_for_chunk_size(byte_count, active_buffer_size())
+}
+
+/// `chunk_limit_for` for loops that step in their own unit (dedup chunks,
+/// hexdump lines) rather than the bucket-brigade buffer.
+///
+/// Saturates at `usize::MAX` where the count does not fit (32-bit targets).
+pub(crate) fn chunk_limit_for_chunk_size(byte_count: u64, chunk_size: usize) -> usize {
+    let chunk_count = byte_count.div_ceil(chunk_size.max(1) as u64);
+    usize::try_from(chunk_count)
+        .unwrap_or(usize::MAX)
+        .saturating_mul(CHUNK_LIMIT_ITERATIONS_PER_CHUNK)
+        .saturating_add(CHUNK_LIMIT_SLACK)
+}
+
+/// Debug-Assert, Test-Assert, Production-Catch-Handle for loop bounds.
+///
+/// `chunk_limit` is normally `chunk_limit_for(bytes the loop expects)`.
+pub(crate) fn check_chunk_limit(chunk_number: usize, chunk_limit: usize) -> io::Result<()> {
+    debug_assert!(chunk_number < chunk_limit, "Exceeded maximum chunk limit");
+
+    #[cfg(test)]
+    {
+        assert!(chunk_number < chunk_limit, "Exceeded maximum chunk limit");
+    }
+
+    if chunk_number >= chunk_limit {
+        return Err(ByteOpError::ChunkLimitExceeded { chunk_limit }.into());
+    }
+
+    Ok(())
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation_config::{OperationConfig, with_operation_config};
+
+    #[test]
+    fn test_chunk_limit_scales_with_pass_size() {
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("tiny config");
+        // 4 GiB at 64-byte chunks: far past the old fixed 16,777,216 cap
+        let pass_bytes: u64 = 4 << 30;
+        let tiny_limit = with_operation_config(&tiny_config, || Ok(chunk_limit_for(pass_bytes)))
+            .expect("tiny limit");
+        assert_eq!(
+            tiny_limit,
+            (pass_bytes / 64) as usize * 2 + CHUNK_LIMIT_SLACK
+        );
+        assert!(tiny_limit > 16_777_216);
+
+        assert_eq!(chunk_limit_for(0), CHUNK_LIMIT_SLACK);
+        assert!(check_chunk_limit(CHUNK_LIMIT_SLACK - 1, chunk_limit_for(0)).is_ok());
+        assert_eq!(
+            chunk_limit_for_chunk_size(100, 0),
+            100 * 2 + CHUNK_LIMIT_SLACK
+        );
+    }
+
+    #[test]
+    fn test_copy_remaining_bytes_bounded_by_remaining_length() {
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("tiny config");
+        let source_bytes: Vec<u8> = (0..10_000u32).map(|value| value as u8).collect();
+        let mut source = io::Cursor::new(source_bytes.clone());
+        source.set_position(1_000);
+        let mut draft: Vec<u8> = Vec::new();
+
+        let copied = with_operation_config(&tiny_config, || {
+            copy_remaining_bytes(&mut source, &mut draft)
+        })
+        .expect("copy");
+        assert_eq!(copied, 9_000);
+        assert_eq!(draft, source_bytes[1_000..]);
+        assert_eq!(remaining_byte_count(&mut source).expect("at end"), 0);
+    }
+
+    #[test]
+    fn test_verification_helpers_return_structured_mismatches() {
+        let original_bytes: Vec<u8> = (0..200u8).collect();
+        let mut draft_bytes = original_bytes.clone();
+        draft_bytes[150] = 0xEE;
+
+        // Frame-shifted region: draft offsets start 10 bytes later
+        let error = verify_next_bytes_match(
+            &mut io::Cursor::new(original_bytes.clone()),
+            &mut io::Cursor::new(draft_bytes.clone()),
+            200,
+            0,
+            10,
+            "Shifted region",
+        )
+        .expect_err("byte 150 differs");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::VerificationFailed {
+                phase: VerificationPhase::CopiedRange,
+                offset: 160,
+                expected: 150,
+                actual: 0xEE,
+            })
+        ));
+
+        let error = verify_next_bytes_equal_slice(
+            &mut io::Cursor::new(draft_bytes.clone()),
+            &original_bytes,
+            0,
+            "Written bytes",
+        )
+        .expect_err("byte 150 differs");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::VerificationFailed {
+                phase: VerificationPhase::WrittenBytes,
+                offset: 150,
+                expected: 150,
+                actual: 0xEE,
+            })
+        ));
+
+        let error = verify_both_at_eof(
+            &mut io::Cursor::new(Vec::new()),
+            &mut io::Cursor::new(vec![0u8; 3]),
+        )
+        .expect_err("draft has trailing bytes");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::VerificationFailed {
+                phase: VerificationPhase::TrailingBytes,
+                actual: 3,
+                ..
+            })
+        ));
+    }
+    #[test]
+    fn test_sync_draft_and_directory_durability() {
+        let test_file = std::env::temp_dir().join("test_durability_directory_sync.bin");
+        std::fs::write(&test_file, b"ABCD").expect("create test file");
+        let config = OperationConfig::builder()
+            .durability_mode(DurabilityMode::SyncDraftAndDirectory)
+            .build()
+            .expect("valid config");
+        assert!(config.durability_mode().syncs_draft());
+
+        crate::replace_single_byte_in_file_with_config(test_file.clone(), 1, b'X', &config)
+            .expect("replace");
+        with_operation_config(&config, || {
+            crate::resize_operations::append_bytes_to_file(test_file.clone(), b"E")
+        })
+        .expect("workflow");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"AXCDE");
+
+        // A bare file name lives in the current directory
+        sync_parent_directory(Path::new("no_such_file.bin")).expect("sync current directory");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replacement_keeps_original_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let test_file = std::env::temp_dir().join("test_keeps_permissions.bin");
+        let _ = std::fs::remove_file(&test_file);
+        std::fs::write(&test_file, b"#!/bin/sh\n").expect("create test file");
+        let file_mode = |path: &Path| {
+            std::fs::metadata(path)
+                .expect("metadata")
+                .permissions()
+                .mode()
+                & 0o7777
+        };
+
+        // An executable stays executable, a secret stays private
+        for mode in [0o755, 0o600] {
+            std::fs::set_permissions(&test_file, std::fs::Permissions::from_mode(mode))
+                .expect("set mode");
+            crate::replace_single_byte_in_file(test_file.clone(), 9, b' ').expect("replace");
+            assert_eq!(file_mode(&test_file), mode);
+            crate::insert_single_byte_into_file(test_file.clone(), 9, b'\n').expect("insert");
+            assert_eq!(file_mode(&test_file), mode);
+            crate::remove_single_byte_from_file(test_file.clone(), 9).expect("remove");
+            assert_eq!(file_mode(&test_file), mode);
+            crate::resize_operations::append_bytes_to_file(test_file.clone(), b"\n")
+                .expect("append");
+            assert_eq!(file_mode(&test_file), mode);
+        }
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy_follows_or_refuses() {
+        let directory = std::env::temp_dir().join("test_symlink_policy");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        let link_path = directory.join("link.bin");
+        std::fs::write(&target_path, b"ABCD").expect("write target");
+        std::os::unix::fs::symlink(&target_path, &link_path).expect("create symlink");
+
+        // The file behind the link is edited and the link stays a link
+        crate::replace_single_byte_in_file(link_path.clone(), 0, b'X').expect("replace");
+        crate::resize_operations::append_bytes_to_file(link_path.clone(), b"E").expect("append");
+        assert_eq!(std::fs::read(&target_path).expect("read target"), b"XBCDE");
+        let link_metadata = std::fs::symlink_metadata(&link_path).expect("link metadata");
+        assert!(link_metadata.file_type().is_symlink());
+
+        let refuse_config = OperationConfig::builder()
+            .symlink_policy(SymlinkPolicy::RefuseSymlinks)
+            .build()
+            .expect("valid config");
+        let error =
+            crate::remove_single_byte_from_file_with_config(link_path.clone(), 0, &refuse_config)
+                .expect_err("symlink refused");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::SymlinkRefused { .. })
+        ));
+        assert_eq!(std::fs::read(&target_path).expect("read target"), b"XBCDE");
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_replace_across_devices_moves_verified_copy() {
+        let directory = std::env::temp_dir().join("test_replace_across_devices");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("store")).expect("create directories");
+        let target_path = directory.join("data.bin");
+        let source_path = directory.join("store").join("data.bin.backup");
+        std::fs::write(&target_path, b"edited").expect("write target");
+        std::fs::write(&source_path, b"original").expect("write source");
+
+        replace_across_devices(&source_path, &target_path).expect("replace");
+        assert_eq!(
+            std::fs::read(&target_path).expect("read target"),
+            b"original"
+        );
+        // Like a rename: the source is gone and nothing is staged
+        assert!(!source_path.exists());
+        assert!(!directory.join("data.bin.draft").exists());
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_rotated_backups_keep_last_edits() {
+        let test_file = std::env::temp_dir().join("test_rotated_backups.bin");
+        std::fs::write(&test_file, b"AAAA").expect("create test file");
+        let config = OperationConfig::builder()
+            .rotate_backups(2)
+            .build()
+            .expect("valid config");
+        let backup_path = build_artifact_paths(&test_file)
+            .expect("artifact paths")
+            .backup_file_path;
+        // A leftover from an earlier, larger limit is pruned
+        std::fs::write(rotated_backup_path(&backup_path, 5), b"old").expect("write leftover");
+
+        for (position, new_byte) in [(0, b'B'), (1, b'C'), (2, b'D')] {
+            crate::replace_single_byte_in_file_with_config(
+                test_file.clone(),
+                position,
+                new_byte,
+                &config,
+            )
+            .expect("replace");
+        }
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"BCDA");
+        assert!(!backup_path.exists());
+        assert_eq!(
+            std::fs::read(rotated_backup_path(&backup_path, 1)).expect("read .1"),
+            b"BCAA"
+        );
+        assert_eq!(
+            std::fs::read(rotated_backup_path(&backup_path, 2)).expect("read .2"),
+            b"BAAA"
+        );
+        assert!(!rotated_backup_path(&backup_path, 3).exists());
+        assert!(!rotated_backup_path(&backup_path, 5).exists());
+        // Manifests move with their backups
+        let manifest =
+            crate::backup_manifest::read_backup_manifest(&rotated_backup_path(&backup_path, 2))
+                .expect("read manifest")
+                .expect("manifest of .2");
+        assert_eq!(
+            manifest.content_hash.as_bytes(),
+            crate::hash::sha256::sha256_of_bytes(b"BAAA")
+        );
+
+        assert!(
+            OperationConfig::builder()
+                .rotate_backups(0)
+                .build()
+                .is_err()
+        );
+
+        for number in 1..=2 {
+            let rotated_path = rotated_backup_path(&backup_path, number);
+            let _ = std::fs::remove_file(backup_manifest_path(&rotated_path));
+            let _ = std::fs::remove_file(rotated_path);
+        }
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_kernel_copy_of_unmodified_stretches() {
+        let source_path = std::env::temp_dir().join("test_kernel_copy_source.bin");
+        let draft_path = std::env::temp_dir().join("test_kernel_copy_draft.bin");
+        let content: Vec<u8> = (0..300_000u32).map(|value| (value % 251) as u8).collect();
+        std::fs::write(&source_path, &content).expect("write source");
+
+        let mut source = open_at_offset(&source_path, 0).expect("open source");
+        let mut draft = create_buffered_draft(&draft_path).expect("create draft");
+        // A buffered read and write before the copy must stay in order
+        let mut head = [0u8; 10];
+        source.read_exact(&mut head).expect("read head");
+        write_all_checked(&mut draft, &head).expect("write head");
+        copy_exact_bytes(&mut source, &mut draft, 100_000).expect("copy stretch");
+        write_all_checked(&mut draft, b"EDIT").expect("write edit");
+        skip_exact_bytes(&mut source, 4).expect("skip");
+        let tail_length = copy_remaining_bytes(&mut source, &mut draft).expect("copy tail");
+        draft.flush().expect("flush draft");
+        drop(draft);
+
+        let mut expected = content[..100_010].to_vec();
+        expected.extend_from_slice(b"EDIT");
+        expected.extend_from_slice(&content[100_014..]);
+        assert_eq!(tail_length, (content.len() - 100_014) as u64);
+        assert_eq!(std::fs::read(&draft_path).expect("read draft"), expected);
+
+        // A source shorter than the stretch is an error, as with the brigade
+        let mut source = open_at_offset(&source_path, 299_990).expect("open source");
+        let mut draft = create_buffered_draft(&draft_path).expect("create draft");
+        let error = copy_exact_bytes(&mut source, &mut draft, 20).expect_err("short source");
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let _ = std::fs::remove_file(&source_path);
+        let _ = std::fs::remove_file(&draft_path);
+    }
+}
@@ -0,0 +1,285 @@
+//! Edit sessions: queue byte operations, preview them, commit once
+//!
+//! An `EditSession` is the front end over `operation_planner` and
+//! `multi_edit` for callers that build up a change step by step:
+//! - `open` runs the preflight checks and records the file size
+//! - `replace`, `insert`, and `remove` queue operations; each position
+//!   refers to the file as the operations queued before it would leave it,
+//!   and a conflicting or out-of-bounds operation is rejected right away
+//! - `preview` returns the queued edits in the original layout, and
+//!   `read_at` reads the file as it will look after `commit`
+//! - `commit` applies everything in one draft build, verification, and
+//!   atomic rename; `abort` (or dropping the session) discards the queue
+//!
+//! Nothing is written to disk before `commit`. This differs from
+//! `FileSession`, where every transaction is written immediately and the
+//! session only shares one backup between them.
+
+use std::{
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::validate_target_file;
+use crate::multi_edit::apply_operations;
+use crate::operation_planner::{EditOverlay, OperationPlan};
+use crate::patch_plan::ByteOperation;
+use crate::reporter::report_detail;
+
+/// Queued, uncommitted edits to one file.
+#[derive(Debug)]
+pub struct EditSession {
+    target_path: PathBuf,
+    original_file_size: u64,
+    overlay: EditOverlay,
+    queued_operation_count: usize,
+}
+
+impl EditSession {
+    /// Runs the preflight checks and starts an empty queue.
+    ///
+    /// # Returns
+    /// - `Ok(EditSession)`
+    /// - `Err(io::Error)` if the target is not a readable regular file
+    pub fn open(target_path: PathBuf) -> io::Result<Self> {
+        let original_file_size = validate_target_file(&target_path)?;
+        Ok(EditSession {
+            target_path,
+            original_file_size,
+            overlay: EditOverlay::new(original_file_size),
+            queued_operation_count: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.target_path
+    }
+
+    /// File size at `open`.
+    pub fn original_file_size(&self) -> u64 {
+        self.original_file_size
+    }
+
+    /// File size once the queued operations are applied.
+    pub fn file_size(&self) -> u64 {
+        self.overlay.current_file_size()
+    }
+
+    pub fn queued_operation_count(&self) -> usize {
+        self.queued_operation_count
+    }
+
+    /// Queues a hex-edit of the byte at `position`.
+    pub fn replace(&mut self, position: u64, new_byte_value: u8) -> io::Result<()> {
+        self.queue(&ByteOperation::Replace {
+            position,
+            new_byte_value,
+        })
+    }
+
+    /// Queues an insertion before the byte at `position` (`file_size()` appends).
+    pub fn insert(&mut self, position: u64, new_byte_value: u8) -> io::Result<()> {
+        self.queue(&ByteOperation::Insert {
+            position,
+            new_byte_value,
+        })
+    }
+
+    /// Queues the removal of the byte at `position`.
+    pub fn remove(&mut self, position: u64) -> io::Result<()> {
+        self.queue(&ByteOperation::Remove { position })
+    }
+
+    /// Queues any byte operation (`Touch` is accepted and does nothing).
+    ///
+    /// # Returns
+    /// - `Ok(())` if the operation was queued
+    /// - `Err(io::Error)` with `ErrorKind::InvalidInput` if it is out of
+    ///   bounds or conflicts with a queued operation (see
+    ///   `operation_planner::OperationConflict`); the queue is unchanged
+    pub fn queue(&mut self, operation: &ByteOperation) -> io::Result<()> {
+        self.overlay
+            .add(self.queued_operation_count, operation)
+            .map_err(|conflict| {
+                io::Error::new(io::ErrorKind::InvalidInput, conflict.to_string())
+            })?;
+        self.queued_operation_count += 1;
+        Ok(())
+    }
+
+    /// The queued edits in the original file layout, as `commit` will apply them.
+    pub fn preview(&self) -> OperationPlan {
+        self.overlay.to_plan()
+    }
+
+    /// Reads the file as it will look once the queued edits are committed.
+    ///
+    /// Unedited bytes are read from the file on disk; inserted and replaced
+    /// bytes come from the queue, and removed bytes are skipped, so
+    /// `offset` is a position in the edited layout (the one `queue` uses).
+    ///
+    /// # Returns
+    /// - `Ok(count)` of bytes read into `buffer`; short only at the end of
+    ///   the edited file, and 0 at or past it
+    /// - `Err(io::Error)` if the file cannot be read
+    pub fn read_at(&self, offset: u64, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut original_file = File::open(&self.target_path)?;
+        self.overlay.read_at(&mut original_file, offset, buffer)
+    }
+
+    /// Applies the queued edits in one draft pass.
+    ///
+    /// # Returns
+    /// - `Ok(OperationPlan)`: what was applied (nothing is written for an
+    ///   empty queue)
+    /// - `Err(io::Error)` if the file size changed since `open` (the queue
+    ///   was planned against the old layout) or any phase fails; the file
+    ///   is then untouched
+    pub fn commit(self) -> io::Result<OperationPlan> {
+        let current_file_size = validate_target_file(&self.target_path)?;
+        if current_file_size != self.original_file_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "File size changed from {} to {} since the edit session opened: {}",
+                    self.original_file_size,
+                    current_file_size,
+                    self.target_path.display()
+                ),
+            ));
+        }
+
+        let plan = self.overlay.to_plan();
+        report_detail!(
+            "Committing edit session: {} queued operations, {} -> {} bytes",
+            self.queued_operation_count,
+            plan.original_file_size,
+            plan.final_file_size
+        );
+        apply_operations(self.target_path, &plan.operations)?;
+        Ok(plan)
+    }
+
+    /// Discards the queued edits; the file was never modified.
+    pub fn abort(self) {
+        report_detail!(
+            "Edit session aborted: {} queued operations discarded",
+            self.queued_operation_count
+        );
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_session_queue_preview_commit() {
+        let test_file = std::env::temp_dir().join("test_edit_session_commit.bin");
+        std::fs::write(&test_file, b"ABCDEF").expect("create test file");
+
+        let mut session = EditSession::open(test_file.clone()).expect("open session");
+        session.insert(0, b'<').expect("insert");
+        session.remove(3).expect("remove C");
+        session.replace(1, b'a').expect("replace A");
+        session.insert(session.file_size(), b'>').expect("append");
+        assert_eq!(session.file_size(), 7);
+
+        // Rejected operations leave the queue as it was
+        let error = session
+            .replace(0, b'x')
+            .expect_err("edits an inserted byte");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(session.remove(7).is_err());
+        assert_eq!(session.queued_operation_count(), 4);
+
+        let preview = session.preview();
+        assert_eq!(
+            preview.operations,
+            vec![
+                ByteOperation::Insert {
+                    position: 0,
+                    new_byte_value: b'<'
+                },
+                ByteOperation::Replace {
+                    position: 0,
+                    new_byte_value: b'a'
+                },
+                ByteOperation::Remove { position: 2 },
+                ByteOperation::Insert {
+                    position: 6,
+                    new_byte_value: b'>'
+                },
+            ]
+        );
+        // Nothing written before commit
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ABCDEF");
+
+        let applied = session.commit().expect("commit");
+        assert_eq!(applied, preview);
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"<aBDEF>");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_edit_session_abort_and_stale_commit() {
+        let test_file = std::env::temp_dir().join("test_edit_session_abort.bin");
+        std::fs::write(&test_file, b"0123").expect("create test file");
+
+        let mut session = EditSession::open(test_file.clone()).expect("open session");
+        session.remove(0).expect("remove");
+        session.abort();
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"0123");
+
+        let mut session = EditSession::open(test_file.clone()).expect("open session");
+        session.replace(3, b'!').expect("replace");
+        std::fs::write(&test_file, b"012").expect("shrink file underneath");
+        let error = session.commit().expect_err("stale session");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"012");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_edit_session_read_at_matches_committed_file() {
+        let test_file = std::env::temp_dir().join("test_edit_session_read_at.bin");
+        std::fs::write(&test_file, b"0123456789").expect("create test file");
+
+        let mut session = EditSession::open(test_file.clone()).expect("open session");
+        session.insert(0, b'[').expect("insert");
+        session.replace(3, b'x').expect("replace 2");
+        session.remove(5).expect("remove 4");
+        session.insert(5, b'+').expect("insert before 5");
+        session.insert(5, b'-').expect("insert before +");
+        session.remove(session.file_size() - 1).expect("remove 9");
+        session.insert(session.file_size(), b']').expect("append");
+        let expected: &[u8] = b"[01x3-+5678]";
+        assert_eq!(session.file_size(), expected.len() as u64);
+
+        // Every window, including ones running past the end
+        for start in 0..=expected.len() + 1 {
+            for length in 0..=expected.len() + 2 - start {
+                let mut buffer = vec![0u8; length];
+                let count = session
+                    .read_at(start as u64, &mut buffer)
+                    .expect("read through overlay");
+                let expected_window = expected.get(start..).unwrap_or(&[]);
+                let expected_count = length.min(expected_window.len());
+                assert_eq!(count, expected_count, "start {} length {}", start, length);
+                assert_eq!(&buffer[..count], &expected_window[..expected_count]);
+            }
+        }
+
+        session.commit().expect("commit");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), expected);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,681 @@
+//! Structured failure causes for the core byte operations
+//!
+//! Operations keep returning `io::Result`, so they compose with `?` and
+//! with the rest of std. Failures with a known cause are built as a
+//! `ByteOpError` and converted into an `io::Error` that carries it (with a
+//! matching `io::ErrorKind`). Callers that need the cause get it back with
+//! `ByteOpError::from_io_error`:
+//!
+//! ```no_run
+//! use basic_file_byte_operations::error::ByteOpError;
+//!
+//! let result = basic_file_byte_operations::remove_single_byte_from_file("f.bin".into(), 9);
+//! if let Err(error) = result {
+//!     match ByteOpError::from_io_error(&error) {
+//!         Some(ByteOpError::PositionOutOfBounds { position, file_size }) => {
+//!             eprintln!("{} is past the end ({} bytes)", position, file_size)
+//!         }
+//!         _ => eprintln!("{}", error),
+//!     }
+//! }
+//! ```
+//!
+//! Plain I/O failures (permission denied, disk full, ...) are passed through
+//! unchanged and have no `ByteOpError`.
+
+use std::{
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::hash::sha256::digest_to_hex;
+
+/// Which comparison of draft against original failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationPhase {
+    /// Total length of the draft
+    TotalLength,
+    /// A byte before the edit position
+    PrePosition,
+    /// Running checksum of the bytes before the edit position
+    PrePositionChecksum,
+    /// The byte at the edit position
+    AtPosition,
+    /// The first byte after a removal (frame-shift -1)
+    FrameShift,
+    /// A byte after the edit position (with the operation's frame-shift)
+    PostPosition,
+    /// Running checksum of the bytes after the edit position
+    PostPositionChecksum,
+    /// A byte of a range the draft copies from the original; `offset` is
+    /// the draft offset
+    CopiedRange,
+    /// Checksum of a range the draft copies from the original
+    CopiedRangeChecksum,
+    /// A byte the operation wrote itself (replacement, fill, transformed or
+    /// appended bytes); `expected` is the byte it should be
+    WrittenBytes,
+    /// A transformed byte fails the transform's own check; `expected` is
+    /// the original byte
+    TransformedBytes,
+    /// The draft has bytes past the end the operation produced; `actual`
+    /// is how many
+    TrailingBytes,
+    /// A count over the content (e.g. of CR bytes, or of a removed value)
+    /// differs from the count the operation recorded
+    ContentCount,
+    /// A digest of a whole draft or output file differs; `expected` and
+    /// `actual` are the CRC-32 values, or a longer digest's first 8 bytes
+    /// as a big-endian `u64`
+    ContentDigest,
+    /// The original changed while the operation ran (its counts differ
+    /// between passes, or it has bytes past the end read)
+    OriginalChanged,
+}
+
+impl VerificationPhase {
+    /// Stable name used in messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            VerificationPhase::TotalLength => "total-length",
+            VerificationPhase::PrePosition => "pre-position",
+            VerificationPhase::PrePositionChecksum => "pre-position-checksum",
+            VerificationPhase::AtPosition => "at-position",
+            VerificationPhase::FrameShift => "frame-shift",
+            VerificationPhase::PostPosition => "post-position",
+            VerificationPhase::PostPositionChecksum => "post-position-checksum",
+            VerificationPhase::CopiedRange => "copied-range",
+            VerificationPhase::CopiedRangeChecksum => "copied-range-checksum",
+            VerificationPhase::WrittenBytes => "written-bytes",
+            VerificationPhase::TransformedBytes => "transformed-bytes",
+            VerificationPhase::TrailingBytes => "trailing-bytes",
+            VerificationPhase::ContentCount => "content-count",
+            VerificationPhase::ContentDigest => "content-digest",
+            VerificationPhase::OriginalChanged => "original-changed",
+        }
+    }
+}
+
+/// What a target path is when it is not a regular file (see
+/// `ByteOpError::NotAFile`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Directory,
+    Fifo,
+    Socket,
+    CharDevice,
+    BlockDevice,
+    /// Anything else (or a path whose type could not be read)
+    Other,
+}
+
+impl FileKind {
+    /// What `path` is, following symlinks.
+    pub fn of_path(path: &Path) -> FileKind {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return FileKind::Other;
+        };
+        let file_type = metadata.file_type();
+        if file_type.is_dir() {
+            return FileKind::Directory;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                return FileKind::Fifo;
+            } else if file_type.is_socket() {
+                return FileKind::Socket;
+            } else if file_type.is_char_device() {
+                return FileKind::CharDevice;
+            } else if file_type.is_block_device() {
+                return FileKind::BlockDevice;
+            }
+        }
+        FileKind::Other
+    }
+
+    /// Stable name used in messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::Directory => "directory",
+            FileKind::Fifo => "FIFO (named pipe)",
+            FileKind::Socket => "socket",
+            FileKind::CharDevice => "character device",
+            FileKind::BlockDevice => "block device",
+            FileKind::Other => "special file",
+        }
+    }
+
+    /// What to do instead, for messages.
+    pub fn remediation_hint(&self) -> &'static str {
+        match self {
+            FileKind::Directory => "pass the path of a file inside it",
+            FileKind::Fifo | FileKind::Socket => {
+                "its bytes are a stream with no positions to edit; save them to a file first"
+            }
+            FileKind::CharDevice => "a character device has no positions to edit",
+            FileKind::BlockDevice => {
+                "a device cannot be replaced by a draft; image it to a file (e.g. with `dd`), \
+                 edit the image (`bfbo sector-replace` for sector-aligned edits), and write it back"
+            }
+            FileKind::Other => "only regular files can be edited",
+        }
+    }
+}
+
+/// Why a target cannot be replaced (see `ByteOpError::TargetNotWritable`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotWritableReason {
+    /// The file is read-only (or not writable by this user)
+    ReadOnlyFile,
+    /// The file or its directory has the Linux immutable or append-only
+    /// attribute
+    Immutable,
+    /// The directory does not allow creating or renaming files in it
+    DirectoryNotWritable,
+    /// The file is on a read-only mount
+    ReadOnlyFilesystem,
+}
+
+impl NotWritableReason {
+    /// What is wrong, for messages.
+    pub fn description(&self) -> &'static str {
+        match self {
+            NotWritableReason::ReadOnlyFile => "file is read-only",
+            NotWritableReason::Immutable => "immutable or append-only attribute is set",
+            NotWritableReason::DirectoryNotWritable => "directory is not writable",
+            NotWritableReason::ReadOnlyFilesystem => "filesystem is mounted read-only",
+        }
+    }
+
+    /// How to fix it, for messages.
+    pub fn remediation_hint(&self) -> &'static str {
+        match self {
+            NotWritableReason::ReadOnlyFile => {
+                "make it writable (`chmod u+w`, or `attrib -r` on Windows) if the edit is intended"
+            }
+            NotWritableReason::Immutable => "remove the attribute with `chattr -i -a` (needs root)",
+            NotWritableReason::DirectoryNotWritable => {
+                "the backup, draft and lock are created there; grant write access to it"
+            }
+            NotWritableReason::ReadOnlyFilesystem => "remount it read-write",
+        }
+    }
+}
+
+/// Why a byte operation failed.
+#[derive(Debug)]
+pub enum ByteOpError {
+    /// The target path does not exist
+    TargetNotFound { path: PathBuf },
+    /// The target path exists but is not a regular file; `kind` says what
+    /// it is
+    NotAFile { path: PathBuf, kind: FileKind },
+    /// The target (or its directory, see `path`) cannot be replaced; found
+    /// before anything was written
+    TargetNotWritable {
+        path: PathBuf,
+        reason: NotWritableReason,
+    },
+    /// The target path is a symlink and the config's `SymlinkPolicy` refuses
+    /// symlinks; nothing was changed
+    SymlinkRefused { path: PathBuf },
+    /// The target path has no file name to derive backup/draft names from
+    InvalidFileName { path: PathBuf },
+    /// The operation needs at least one byte
+    EmptyFile,
+    /// `position` is not a valid position in a file of `file_size` bytes
+    PositionOutOfBounds { position: u64, file_size: u64 },
+    /// An `Offset::FromEnd` distance reaches back past the first byte
+    OffsetBeforeStart {
+        distance_from_end: u64,
+        file_size: u64,
+    },
+    /// A precondition on the current content failed before anything was
+    /// written: the byte at `position` is `actual`, not `expected`
+    UnexpectedByteValue {
+        position: u64,
+        expected: u8,
+        actual: u8,
+    },
+    /// The original is not the size the operation was prepared for;
+    /// nothing was written
+    UnexpectedFileSize { expected: u64, actual: u64 },
+    /// The original's SHA-256 is not the one the operation was prepared
+    /// for; nothing was written
+    UnexpectedFileHash {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
+    /// The draft differs from what the operation should have produced.
+    /// `offset` is the draft offset compared (0 for length checks);
+    /// `expected`/`actual` are the byte values, sizes or checksums compared
+    VerificationFailed {
+        phase: VerificationPhase,
+        offset: u64,
+        expected: u64,
+        actual: u64,
+    },
+    /// The draft was built but the edit itself never happened
+    /// (e.g. the target position was never reached)
+    EditNotApplied { operation_name: &'static str },
+    /// A write accepted fewer bytes than it was given
+    IncompleteWrite { requested: usize, written: usize },
+    /// A processing loop hit its chunk safety limit
+    ChunkLimitExceeded { chunk_limit: usize },
+    /// The backup copy could not be created; nothing was changed
+    BackupFailed { source: io::Error },
+    /// The draft could not be renamed over the original; the original and
+    /// the backup are left in place
+    AtomicReplaceFailed { source: io::Error },
+    /// The draft replaced the original, but the directory could not be
+    /// synced (`DurabilityMode::SyncDraftAndDirectory`), so the change may
+    /// not survive a power loss; the backup is left in place
+    DirectorySyncFailed { source: io::Error },
+    /// A `.backup` or `.draft` file already exists (e.g. from an interrupted
+    /// run) and the overwrite policy forbids replacing it; nothing was
+    /// changed
+    ArtifactExists { path: PathBuf },
+    /// A backup's digest differs from the one recorded for it; it was not
+    /// restored. Both are `<algorithm>:<hex>` (see `HashValue`)
+    BackupChecksumMismatch {
+        path: PathBuf,
+        recorded_hash: String,
+        actual_hash: String,
+    },
+    /// An interrupted operation left a journal for this target; run
+    /// `journal::recover` first. Nothing was changed
+    PendingJournal { path: PathBuf },
+    /// Another operation holds the lock file `path` (`owner_pid`: the
+    /// process recorded in it, if readable); nothing was changed
+    TargetLocked {
+        path: PathBuf,
+        owner_pid: Option<u32>,
+    },
+    /// This process already holds the lock file `path`: an operation on the
+    /// file was started while another one on it is still running here
+    /// (from a hook, or another thread). Nothing was changed
+    LockHeldByThisProcess { path: PathBuf },
+    /// An `OperationConfig` setting is out of range
+    InvalidConfiguration { reason: &'static str },
+    /// The worst-case memory of what the operation holds exceeds the
+    /// memory budget; nothing was read or written
+    MemoryBudgetExceeded {
+        worst_case_bytes: usize,
+        limit_bytes: usize,
+    },
+    /// The UTF-8 guard rejected the draft (see `OperationConfig::utf8_guard`)
+    Utf8GuardRejected {
+        operation_name: String,
+        path: PathBuf,
+        detail: String,
+    },
+}
+
+impl ByteOpError {
+    /// The `ByteOpError` carried by `error`, if it has one.
+    pub fn from_io_error(error: &io::Error) -> Option<&ByteOpError> {
+        error.get_ref()?.downcast_ref::<ByteOpError>()
+    }
+
+    /// `io::ErrorKind` used when converting into an `io::Error`.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            ByteOpError::TargetNotFound { .. } => io::ErrorKind::NotFound,
+            ByteOpError::NotAFile {
+                kind: FileKind::Directory,
+                ..
+            } => io::ErrorKind::IsADirectory,
+            ByteOpError::NotAFile { .. }
+            | ByteOpError::SymlinkRefused { .. }
+            | ByteOpError::InvalidFileName { .. }
+            | ByteOpError::EmptyFile
+            | ByteOpError::PositionOutOfBounds { .. }
+            | ByteOpError::OffsetBeforeStart { .. }
+            | ByteOpError::InvalidConfiguration { .. }
+            | ByteOpError::MemoryBudgetExceeded { .. } => io::ErrorKind::InvalidInput,
+            ByteOpError::ArtifactExists { .. } | ByteOpError::PendingJournal { .. } => {
+                io::ErrorKind::AlreadyExists
+            }
+            ByteOpError::TargetLocked { .. } | ByteOpError::LockHeldByThisProcess { .. } => {
+                io::ErrorKind::ResourceBusy
+            }
+            ByteOpError::TargetNotWritable { .. } => io::ErrorKind::PermissionDenied,
+            ByteOpError::IncompleteWrite { .. } => io::ErrorKind::WriteZero,
+            ByteOpError::UnexpectedByteValue { .. }
+            | ByteOpError::UnexpectedFileSize { .. }
+            | ByteOpError::UnexpectedFileHash { .. }
+            | ByteOpError::BackupChecksumMismatch { .. }
+            | ByteOpError::Utf8GuardRejected { .. } => io::ErrorKind::InvalidData,
+            ByteOpError::BackupFailed { source }
+            | ByteOpError::AtomicReplaceFailed { source }
+            | ByteOpError::DirectorySyncFailed { source } => source.kind(),
+            ByteOpError::VerificationFailed { .. }
+            | ByteOpError::EditNotApplied { .. }
+            | ByteOpError::ChunkLimitExceeded { .. } => io::ErrorKind::Other,
+        }
+    }
+
+    pub(crate) fn target_not_found(path: &Path) -> Self {
+        ByteOpError::TargetNotFound {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub(crate) fn not_a_file(path: &Path) -> Self {
+        ByteOpError::NotAFile {
+            path: path.to_path_buf(),
+            kind: FileKind::of_path(path),
+        }
+    }
+
+    pub(crate) fn invalid_file_name(path: &Path) -> Self {
+        ByteOpError::InvalidFileName {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub(crate) fn verification_failed(
+        phase: VerificationPhase,
+        offset: u64,
+        expected: u64,
+        actual: u64,
+    ) -> Self {
+        ByteOpError::VerificationFailed {
+            phase,
+            offset,
+            expected,
+            actual,
+        }
+    }
+}
+
+impl fmt::Display for ByteOpError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteOpError::TargetNotFound { path } => {
+                write!(formatter, "Target file does not exist: {}", path.display())
+            }
+            ByteOpError::NotAFile { path, kind } => write!(
+                formatter,
+                "Target path is a {}, not a regular file: {}; {}",
+                kind.name(),
+                path.display(),
+                kind.remediation_hint()
+            ),
+            ByteOpError::TargetNotWritable { path, reason } => write!(
+                formatter,
+                "Cannot replace file: {} ({}); {}; file unchanged",
+                path.display(),
+                reason.description(),
+                reason.remediation_hint()
+            ),
+            ByteOpError::SymlinkRefused { path } => write!(
+                formatter,
+                "Target path is a symlink and the symlink policy refuses it; file unchanged: {}",
+                path.display()
+            ),
+            ByteOpError::InvalidFileName { path } => {
+                write!(formatter, "Invalid file name: {}", path.display())
+            }
+            ByteOpError::EmptyFile => write!(formatter, "File is empty (file size is 0)"),
+            ByteOpError::PositionOutOfBounds {
+                position,
+                file_size,
+            } => write!(
+                formatter,
+                "Byte position {} is out of bounds for file size {}",
+                position, file_size
+            ),
+            ByteOpError::OffsetBeforeStart {
+                distance_from_end,
+                file_size,
+            } => write!(
+                formatter,
+                "Offset {} from the end is before the start of a {}-byte file",
+                distance_from_end, file_size
+            ),
+            ByteOpError::UnexpectedByteValue {
+                position,
+                expected,
+                actual,
+            } => write!(
+                formatter,
+                "Byte at position {} is 0x{:02X}, expected 0x{:02X}; file unchanged",
+                position, actual, expected
+            ),
+            ByteOpError::UnexpectedFileSize { expected, actual } => write!(
+                formatter,
+                "Original is {} bytes, expected {}; file unchanged",
+                actual, expected
+            ),
+            ByteOpError::UnexpectedFileHash { expected, actual } => write!(
+                formatter,
+                "Original SHA-256 is {}, expected {}; file unchanged",
+                digest_to_hex(actual),
+                digest_to_hex(expected)
+            ),
+            ByteOpError::VerificationFailed {
+                phase,
+                offset,
+                expected,
+                actual,
+            } => write!(
+                formatter,
+                "Verification failed ({}) at offset {}: expected=0x{:X}, actual=0x{:X}",
+                phase.name(),
+                offset,
+                expected,
+                actual
+            ),
+            ByteOpError::EditNotApplied { operation_name } => {
+                write!(formatter, "{} did not occur", operation_name)
+            }
+            ByteOpError::IncompleteWrite { requested, written } => write!(
+                formatter,
+                "Incomplete write operation: {} of {} bytes written",
+                written, requested
+            ),
+            ByteOpError::ChunkLimitExceeded { chunk_limit } => write!(
+                formatter,
+                "File too large or infinite loop detected (chunk limit {})",
+                chunk_limit
+            ),
+            ByteOpError::BackupFailed { source } => {
+                write!(formatter, "Failed to create backup: {}", source)
+            }
+            ByteOpError::AtomicReplaceFailed { source } => write!(
+                formatter,
+                "Cannot atomically replace file (original and backup preserved): {}",
+                source
+            ),
+            ByteOpError::DirectorySyncFailed { source } => write!(
+                formatter,
+                "File replaced, but its directory could not be synced (the change may not \
+                 survive a power loss; backup preserved): {}",
+                source
+            ),
+            ByteOpError::ArtifactExists { path } => write!(
+                formatter,
+                "Refusing to overwrite existing file: {} (left by an earlier run? \
+                 check it, remove it, or force); file unchanged",
+                path.display()
+            ),
+            ByteOpError::BackupChecksumMismatch {
+                path,
+                recorded_hash,
+                actual_hash,
+            } => write!(
+                formatter,
+                "Backup {} has digest {}, recorded {}; not restored",
+                path.display(),
+                actual_hash,
+                recorded_hash
+            ),
+            ByteOpError::PendingJournal { path } => write!(
+                formatter,
+                "An interrupted operation left a journal at {}; recover it first; file unchanged",
+                path.display()
+            ),
+            ByteOpError::TargetLocked {
+                path,
+                owner_pid: Some(pid),
+            } => write!(
+                formatter,
+                "File is being edited by process {} (lock {}); file unchanged",
+                pid,
+                path.display()
+            ),
+            ByteOpError::TargetLocked {
+                path,
+                owner_pid: None,
+            } => write!(
+                formatter,
+                "File is locked by another operation ({}); file unchanged",
+                path.display()
+            ),
+            ByteOpError::LockHeldByThisProcess { path } => write!(
+                formatter,
+                "File is already being edited by this process (lock {}); file unchanged",
+                path.display()
+            ),
+            ByteOpError::InvalidConfiguration { reason } => {
+                write!(formatter, "Invalid operation config: {}", reason)
+            }
+            ByteOpError::MemoryBudgetExceeded {
+                worst_case_bytes,
+                limit_bytes,
+            } => write!(
+                formatter,
+                "Memory budget: worst case {} bytes exceeds budget of {} bytes",
+                worst_case_bytes, limit_bytes
+            ),
+            ByteOpError::Utf8GuardRejected {
+                operation_name,
+                path,
+                detail,
+            } => write!(
+                formatter,
+                "UTF-8 guard: {} would leave {} with invalid UTF-8 ({}); original unchanged",
+                operation_name,
+                path.display(),
+                detail
+            ),
+        }
+    }
+}
+
+impl Error for ByteOpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ByteOpError::BackupFailed { source }
+            | ByteOpError::AtomicReplaceFailed { source }
+            | ByteOpError::DirectorySyncFailed { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ByteOpError> for io::Error {
+    fn from(error: ByteOpError) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_op_errors_survive_io_error_conversion() {
+        let test_file = std::env::temp_dir().join("test_byte_op_error.bin");
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("create test file");
+
+        let error = crate::remove_single_byte_from_file(test_file.clone(), 3)
+            .expect_err("position past the end");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::PositionOutOfBounds {
+                position: 3,
+                file_size: 3
+            })
+        ));
+
+        let missing_file = std::env::temp_dir().join("test_byte_op_error_missing.bin");
+        let error =
+            crate::replace_single_byte_in_file(missing_file, 0, 0xFF).expect_err("missing file");
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::TargetNotFound { .. })
+        ));
+
+        // Verification failures name the phase and the compared values
+        let draft_file = std::env::temp_dir().join("test_byte_op_error.draft");
+        std::fs::write(&draft_file, vec![0x00, 0xFF, 0x23]).expect("create draft");
+        let error = crate::verify::verify_byte_replacement_operation(
+            &test_file,
+            &draft_file,
+            1,
+            0x11,
+            0xFF,
+        )
+        .expect_err("post-position byte differs");
+        match ByteOpError::from_io_error(&error) {
+            Some(ByteOpError::VerificationFailed {
+                phase,
+                offset,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(*phase, VerificationPhase::PostPosition);
+                assert_eq!((*offset, *expected, *actual), (2, 0x22, 0x23));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let _ = std::fs::remove_file(&test_file);
+        let _ = std::fs::remove_file(&draft_file);
+    }
+
+    #[test]
+    fn test_not_a_file_names_what_the_path_is() {
+        let directory = std::env::temp_dir().join("test_not_a_file_kinds");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("create directory");
+
+        let error = crate::replace_single_byte_in_file(directory.clone(), 0, 0xFF)
+            .expect_err("directory target");
+        assert_eq!(error.kind(), io::ErrorKind::IsADirectory);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::NotAFile {
+                kind: FileKind::Directory,
+                ..
+            })
+        ));
+
+        #[cfg(unix)]
+        {
+            let socket_path = directory.join("control.sock");
+            let _listener =
+                std::os::unix::net::UnixListener::bind(&socket_path).expect("bind socket");
+            let error = crate::remove_single_byte_from_file(socket_path, 0).expect_err("socket");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            assert!(error.to_string().starts_with("Target path is a socket"));
+            assert_eq!(
+                FileKind::of_path(Path::new("/dev/null")),
+                FileKind::CharDevice
+            );
+        }
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,264 @@
+//! Sessions: many transactions on one file, one backup
+//!
+//! Iterative patch development applies many small edits to the same file in
+//! a row. Run one by one, every operation copies the whole file to
+//! `<name>.backup` and deletes it again, and every caller re-runs the
+//! preflight checks and re-hashes the file to see where it stands.
+//!
+//! A `FileSession` does that work once per session instead:
+//! - `open` runs the preflight checks and copies the file to
+//!   `<name>.session.backup` once
+//! - operations run inside `transact` skip their per-operation backup (the
+//!   draft/verify/rename steps are unchanged, so every edit is still
+//!   verified and atomic)
+//! - the file size and SHA-256 are cached between transactions and only
+//!   recomputed after the file changes
+//! - `close` removes the session backup; `rollback` restores it
+//!
+//! A session that is dropped without `close` or `rollback` keeps its backup
+//! on disk, and `open` refuses to start a new session while it exists.
+//!
+//! Only operations built on the shared draft workflow (range, transform,
+//! resize, ... operations) skip their backup; the single-byte operations in
+//! `replace`, `remove`, and `insert` run their own full workflow and still take one each.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    build_artifact_paths, replace_with_draft, resolve_symlinked_target, validate_target_file,
+    with_session_backup,
+};
+use crate::hash::sha256::sha256_of_file;
+use crate::operation_config::active_operation_config;
+use crate::reflink::clone_or_copy;
+use crate::reporter::report_detail;
+
+/// Suffix appended to the file name for the session backup
+pub const SESSION_BACKUP_FILE_SUFFIX: &str = ".session.backup";
+
+/// An open editing session on one file.
+#[derive(Debug)]
+pub struct FileSession {
+    target_path: PathBuf,
+    session_backup_path: PathBuf,
+    /// From the last preflight (open or after a transaction)
+    file_size: u64,
+    /// SHA-256 of the current content, if computed since the last change
+    cached_sha256: Option<[u8; 32]>,
+    transaction_count: usize,
+}
+
+/// What a closed session did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSessionSummary {
+    pub transaction_count: usize,
+    pub final_size: u64,
+    pub final_sha256: [u8; 32],
+}
+
+impl FileSession {
+    /// Runs the preflight checks and takes the session backup.
+    ///
+    /// # Returns
+    /// - `Ok(FileSession)`
+    /// - `Err(io::Error)` if the target is not a regular file, a session
+    ///   backup already exists (`AlreadyExists`: an earlier session was not
+    ///   closed), or the backup cannot be written
+    pub fn open(target_path: PathBuf) -> io::Result<Self> {
+        // Every transaction edits the file the symlink policy settles on
+        let target_path = resolve_symlinked_target(&target_path, &active_operation_config())?;
+        let file_size = validate_target_file(&target_path)?;
+
+        let file_name = target_path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file name"))?
+            .to_string_lossy()
+            .to_string();
+        let session_backup_path =
+            target_path.with_file_name(format!("{}{}", file_name, SESSION_BACKUP_FILE_SUFFIX));
+        if session_backup_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "Session backup already exists (unclosed session?): {}",
+                    session_backup_path.display()
+                ),
+            ));
+        }
+        clone_or_copy(&target_path, &session_backup_path)?;
+
+        report_detail!(
+            "Session opened: {} ({} bytes), backup at {}",
+            target_path.display(),
+            file_size,
+            session_backup_path.display()
+        );
+
+        Ok(FileSession {
+            target_path,
+            session_backup_path,
+            file_size,
+            cached_sha256: None,
+            transaction_count: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.target_path
+    }
+
+    /// File size as of the last preflight.
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    pub fn transaction_count(&self) -> usize {
+        self.transaction_count
+    }
+
+    /// SHA-256 of the current content, computed at most once per change.
+    pub fn sha256(&mut self) -> io::Result<[u8; 32]> {
+        if let Some(digest) = self.cached_sha256 {
+            return Ok(digest);
+        }
+        let digest = sha256_of_file(&self.target_path)?;
+        self.cached_sha256 = Some(digest);
+        Ok(digest)
+    }
+
+    /// Runs one transaction (one or more operations) on the file.
+    ///
+    /// `edit` receives the target path and calls operations on it as usual,
+    /// e.g. `session.transact(|path| replace_byte_range_in_file(path, 4, b"ok"))`.
+    /// Afterwards the cached size is refreshed and the cached hash dropped,
+    /// whether or not the edit succeeded.
+    pub fn transact<T>(&mut self, edit: impl FnOnce(PathBuf) -> io::Result<T>) -> io::Result<T> {
+        let edit_result = with_session_backup(&self.target_path, || edit(self.target_path.clone()));
+
+        self.cached_sha256 = None;
+        self.file_size = validate_target_file(&self.target_path)?;
+        if edit_result.is_ok() {
+            self.transaction_count += 1;
+        }
+        edit_result
+    }
+
+    /// Ends the session, keeping all changes, and removes the session backup.
+    pub fn close(mut self) -> io::Result<FileSessionSummary> {
+        let summary = FileSessionSummary {
+            transaction_count: self.transaction_count,
+            final_size: self.file_size,
+            final_sha256: self.sha256()?,
+        };
+        fs::remove_file(&self.session_backup_path)?;
+
+        report_detail!(
+            "Session closed: {} transactions, {} bytes",
+            summary.transaction_count,
+            summary.final_size
+        );
+        Ok(summary)
+    }
+
+    /// Ends the session, restoring the file to its state at `open`.
+    ///
+    /// The backup is copied to a draft which is renamed over the target, so
+    /// the target is replaced atomically; the session backup is then removed.
+    pub fn rollback(self) -> io::Result<()> {
+        let draft_file_path = build_artifact_paths(&self.target_path)?.draft_file_path;
+        clone_or_copy(&self.session_backup_path, &draft_file_path)?;
+        if let Err(error) = replace_with_draft(&draft_file_path, &self.target_path) {
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(error);
+        }
+        fs::remove_file(&self.session_backup_path)?;
+
+        report_detail!("Session rolled back: {}", self.target_path.display());
+        Ok(())
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draft_workflow::BACKUP_FILE_SUFFIX;
+    use crate::hash::sha256::sha256_of_bytes;
+    use crate::range_operations::replace_byte_range_in_file;
+    use crate::resize_operations::append_bytes_to_file;
+
+    #[test]
+    fn test_file_session_transactions_and_close() {
+        let test_file = std::env::temp_dir().join("test_file_session_close.bin");
+        std::fs::write(&test_file, b"0123456789").expect("create test file");
+
+        let mut session = FileSession::open(test_file.clone()).expect("open session");
+        assert_eq!(
+            FileSession::open(test_file.clone())
+                .expect_err("second session refused")
+                .kind(),
+            io::ErrorKind::AlreadyExists
+        );
+
+        session
+            .transact(|path| replace_byte_range_in_file(path, 0, b"AB"))
+            .expect("first transaction");
+        // No per-operation backup was left behind or needed
+        assert!(
+            !test_file
+                .with_file_name(format!("test_file_session_close.bin{}", BACKUP_FILE_SUFFIX))
+                .exists()
+        );
+        session
+            .transact(|path| append_bytes_to_file(path, b"!"))
+            .expect("second transaction");
+        assert!(
+            session
+                .transact(|path| replace_byte_range_in_file(path, 50, b"X"))
+                .is_err()
+        );
+
+        assert_eq!(session.file_size(), 11);
+        assert_eq!(
+            session.sha256().expect("hash"),
+            sha256_of_bytes(b"AB23456789!")
+        );
+
+        let summary = session.close().expect("close session");
+        assert_eq!(summary.transaction_count, 2);
+        assert_eq!(summary.final_sha256, sha256_of_bytes(b"AB23456789!"));
+        assert!(
+            !test_file
+                .with_file_name("test_file_session_close.bin.session.backup")
+                .exists()
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_file_session_rollback_restores_original() {
+        let test_file = std::env::temp_dir().join("test_file_session_rollback.bin");
+        std::fs::write(&test_file, b"original").expect("create test file");
+
+        let mut session = FileSession::open(test_file.clone()).expect("open session");
+        session
+            .transact(|path| replace_byte_range_in_file(path, 0, b"O"))
+            .expect("transaction");
+        session.rollback().expect("rollback");
+
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"original");
+        FileSession::open(test_file.clone())
+            .expect("backup removed, new session allowed")
+            .close()
+            .expect("close");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,693 @@
+//! Splitting files into fixed-size chunks, and joining files
+//!
+//! Large images often have to be moved in pieces (size-limited media,
+//! upload limits). `split_file` streams a file into numbered chunk files and
+//! writes a manifest of per-chunk SHA-256 digests, so each piece can be
+//! checked on its own after transfer. `concatenate_files` joins pieces (or
+//! any files) back into one, checking every input's contribution, and
+//! `copy_first_n_bytes` / `copy_last_n_bytes` extract a head or tail.
+//!
+//! Output files are created the same way drafts are: each is written to a
+//! `.draft` sibling, verified, and only then renamed to its final name, so
+//! a chunk or manifest that exists under its final name is complete.
+//!
+//! The manifest is in `sha256sum` format (`<hex>  <chunk file name>`), so
+//! `sha256sum -c` in the output directory checks the chunks.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    DRAFT_FILE_SUFFIX, check_chunk_limit, chunk_limit_for, open_at_offset, validate_target_file,
+    write_all_checked,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::hash::sha256::{
+    Sha256, digest_leading_u64, digest_to_hex, sha256_of_file, sha256_of_file_range,
+};
+use crate::operation_config::{OperationConfig, active_buffer_size, with_operation_config};
+use crate::reporter::{report_check_passed, report_problem};
+use crate::util::{chunk_length, first_mismatch};
+
+// ============================
+// Splitting
+// ============================
+
+/// Suffix appended to the source file name for the manifest
+pub const SPLIT_MANIFEST_SUFFIX: &str = ".manifest.sha256";
+
+/// One chunk written by `split_file`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitChunk {
+    pub chunk_path: PathBuf,
+    /// Position of the chunk's first byte in the source file
+    pub source_offset: u64,
+    pub byte_length: u64,
+    pub sha256: [u8; 32],
+}
+
+/// Result of a split.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitReport {
+    pub chunks: Vec<SplitChunk>,
+    pub manifest_path: PathBuf,
+}
+
+/// Splits a file into `chunk_size`-byte chunk files plus a manifest.
+///
+/// # Overview
+/// Chunks are named `<file name>.part0000`, `.part0001`, ... (wider
+/// numbering if there are more than 10000 chunks); the last chunk holds the
+/// remainder. The source is read once, sequentially, through a
+/// bucket-brigade buffer; each chunk's SHA-256 is computed from the source
+/// bytes as they stream past.
+///
+/// # Verification
+/// Each chunk draft is re-read from disk before its rename: its length must
+/// equal the chunk length and its SHA-256 must equal the digest of the
+/// source bytes. The manifest is written last, so a manifest only exists if
+/// every chunk was verified.
+///
+/// # Parameters
+/// - `source_file_path`: File to split (left untouched)
+/// - `chunk_size`: Bytes per chunk (> 0)
+/// - `output_directory`: Created if missing; must not already contain
+///   chunks or a manifest for this file name
+///
+/// # Returns
+/// - `Ok(SplitReport)`: chunks in order and the manifest path (an empty
+///   source produces no chunks and an empty manifest)
+/// - `Err(io::Error)` if `chunk_size` is zero, an output file already
+///   exists (`AlreadyExists`), or any read, write, or verification fails
+pub fn split_file(
+    source_file_path: &Path,
+    chunk_size: u64,
+    output_directory: &Path,
+) -> io::Result<SplitReport> {
+    if chunk_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Chunk size must be greater than zero",
+        ));
+    }
+    let source_file_size = validate_target_file(source_file_path)?;
+    let source_file_name = source_file_path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file name"))?
+        .to_string_lossy()
+        .to_string();
+
+    let chunk_count = source_file_size.div_ceil(chunk_size);
+    let index_width = std::cmp::max(4, chunk_count.saturating_sub(1).to_string().len());
+    let chunk_paths: Vec<PathBuf> = (0..chunk_count)
+        .map(|chunk_index| {
+            output_directory.join(format!(
+                "{}.part{:0width$}",
+                source_file_name,
+                chunk_index,
+                width = index_width
+            ))
+        })
+        .collect();
+    let manifest_path =
+        output_directory.join(format!("{}{}", source_file_name, SPLIT_MANIFEST_SUFFIX));
+
+    // Never overwrite an earlier split
+    fs::create_dir_all(output_directory)?;
+    if let Some(existing_path) = chunk_paths
+        .iter()
+        .chain(std::iter::once(&manifest_path))
+        .find(|path| path.exists())
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Output file already exists: {}", existing_path.display()),
+        ));
+    }
+
+    let mut source_file = File::open(source_file_path)?;
+    let mut chunks = Vec::with_capacity(chunk_paths.len());
+    for (chunk_index, chunk_path) in chunk_paths.into_iter().enumerate() {
+        let source_offset = chunk_index as u64 * chunk_size;
+        let byte_length = std::cmp::min(chunk_size, source_file_size - source_offset);
+        let sha256 = write_verified_chunk(&mut source_file, byte_length, &chunk_path)?;
+
+        report_check_passed!(
+            "   ✓ Chunk {} ({} bytes at {}) verified",
+            chunk_path.display(),
+            byte_length,
+            source_offset
+        );
+
+        chunks.push(SplitChunk {
+            chunk_path,
+            source_offset,
+            byte_length,
+            sha256,
+        });
+    }
+
+    let mut manifest_text = String::new();
+    for chunk in &chunks {
+        let chunk_file_name = chunk
+            .chunk_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        manifest_text.push_str(&format!(
+            "{}  {}\n",
+            digest_to_hex(&chunk.sha256),
+            chunk_file_name
+        ));
+    }
+    write_file_via_draft(&manifest_path, manifest_text.as_bytes())?;
+
+    Ok(SplitReport {
+        chunks,
+        manifest_path,
+    })
+}
+
+/// Like `split_file`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn split_file_with_config(
+    source_file_path: &Path,
+    chunk_size: u64,
+    output_directory: &Path,
+    config: &OperationConfig,
+) -> io::Result<SplitReport> {
+    with_operation_config(config, || {
+        split_file(source_file_path, chunk_size, output_directory)
+    })
+}
+
+/// Streams the next `byte_length` source bytes into a verified chunk file.
+///
+/// # Returns
+/// The SHA-256 of the chunk, computed from the source bytes.
+fn write_verified_chunk<R: Read>(
+    source_file: &mut R,
+    byte_length: u64,
+    chunk_path: &Path,
+) -> io::Result<[u8; 32]> {
+    let draft_path = draft_path_for(chunk_path);
+    let write_result = (|| -> io::Result<[u8; 32]> {
+        let mut draft_file = OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&draft_path)?;
+        let mut digest = Sha256::new();
+        let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+        let mut bytes_copied: u64 = 0;
+        let chunk_limit = chunk_limit_for(byte_length);
+        let mut chunk_number: usize = 0;
+
+        while bytes_copied < byte_length {
+            check_chunk_limit(chunk_number, chunk_limit)?;
+            chunk_number += 1;
+
+            let bytes_to_read =
+                chunk_length(bucket_brigade_buffer.len(), byte_length - bytes_copied);
+            source_file.read_exact(&mut bucket_brigade_buffer[..bytes_to_read])?;
+            digest.update(&bucket_brigade_buffer[..bytes_to_read]);
+            write_all_checked(&mut draft_file, &bucket_brigade_buffer[..bytes_to_read])?;
+            bytes_copied += bytes_to_read as u64;
+        }
+        drop(draft_file);
+        let source_sha256 = digest.finalize();
+
+        verify_output_file(&draft_path, byte_length, &source_sha256)?;
+        fs::rename(&draft_path, chunk_path)?;
+        Ok(source_sha256)
+    })();
+
+    if write_result.is_err() {
+        let _ = fs::remove_file(&draft_path);
+    }
+    write_result
+}
+
+// ============================
+// Concatenation
+// ============================
+
+/// Where one input ended up in a concatenated output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcatenatedInput {
+    pub input_path: PathBuf,
+    pub output_offset: u64,
+    pub byte_length: u64,
+    pub sha256: [u8; 32],
+}
+
+/// Streams `input_paths`, in order, into a new file at `output_path`.
+///
+/// # Overview
+/// The output is built as `<output>.draft` and renamed into place only
+/// after verification. Input sizes are taken at the start; each input
+/// contributes exactly that many bytes (an input that shrinks meanwhile is
+/// an error). Each input's SHA-256 is computed from the bytes as they are
+/// read.
+///
+/// # Verification
+/// - Total length: draft == sum of input sizes
+/// - Per input: SHA-256 of the draft range at the input's offset equals the
+///   SHA-256 of the bytes read from that input
+///
+/// # Parameters
+/// - `input_paths`: Files to join (at least one; the same file may repeat)
+/// - `output_path`: Must not exist yet
+///
+/// # Returns
+/// - `Ok(Vec<ConcatenatedInput>)`: offset, length, and digest per input
+/// - `Err(io::Error)` if there are no inputs, an input is not a regular
+///   file, the output exists (`AlreadyExists`), or any phase fails (the
+///   draft is removed)
+pub fn concatenate_files(
+    input_paths: &[PathBuf],
+    output_path: &Path,
+) -> io::Result<Vec<ConcatenatedInput>> {
+    if input_paths.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "At least one input file is required",
+        ));
+    }
+    if output_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Output file already exists: {}", output_path.display()),
+        ));
+    }
+
+    let mut concatenated_inputs = Vec::with_capacity(input_paths.len());
+    let mut total_length: u64 = 0;
+    for input_path in input_paths {
+        let byte_length = validate_target_file(input_path)?;
+        concatenated_inputs.push(ConcatenatedInput {
+            input_path: input_path.clone(),
+            output_offset: total_length,
+            byte_length,
+            sha256: [0u8; 32],
+        });
+        total_length = total_length.checked_add(byte_length).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Total length overflows u64")
+        })?;
+    }
+
+    let draft_path = draft_path_for(output_path);
+    let concatenate_result = (|| -> io::Result<()> {
+        // =========================================
+        // Draft Construction Phase
+        // =========================================
+        let mut draft_file = OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&draft_path)?;
+        let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+        for concatenated_input in &mut concatenated_inputs {
+            let mut input_file = File::open(&concatenated_input.input_path)?;
+            let mut digest = Sha256::new();
+            let mut bytes_copied: u64 = 0;
+            let chunk_limit = chunk_limit_for(concatenated_input.byte_length);
+            let mut chunk_number: usize = 0;
+
+            while bytes_copied < concatenated_input.byte_length {
+                check_chunk_limit(chunk_number, chunk_limit)?;
+                chunk_number += 1;
+
+                let bytes_to_read = chunk_length(
+                    bucket_brigade_buffer.len(),
+                    concatenated_input.byte_length - bytes_copied,
+                );
+                input_file.read_exact(&mut bucket_brigade_buffer[..bytes_to_read])?;
+                digest.update(&bucket_brigade_buffer[..bytes_to_read]);
+                write_all_checked(&mut draft_file, &bucket_brigade_buffer[..bytes_to_read])?;
+                bytes_copied += bytes_to_read as u64;
+            }
+            concatenated_input.sha256 = digest.finalize();
+        }
+        drop(draft_file);
+
+        // =========================================
+        // Verification Phase
+        // =========================================
+        let draft_length = fs::metadata(&draft_path)?.len();
+        if draft_length != total_length {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::TotalLength,
+                0,
+                total_length,
+                draft_length,
+            )
+            .into());
+        }
+        for concatenated_input in &concatenated_inputs {
+            let draft_range_sha256 = sha256_of_file_range(
+                &draft_path,
+                concatenated_input.output_offset,
+                concatenated_input.byte_length,
+            )?;
+            if draft_range_sha256 != concatenated_input.sha256 {
+                report_problem!(
+                    "ERROR: {} failed verification at offset {}",
+                    concatenated_input.input_path.display(),
+                    concatenated_input.output_offset
+                );
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::CopiedRangeChecksum,
+                    concatenated_input.output_offset,
+                    digest_leading_u64(&concatenated_input.sha256),
+                    digest_leading_u64(&draft_range_sha256),
+                )
+                .into());
+            }
+            report_check_passed!(
+                "   ✓ {} ({} bytes) verified at offset {}",
+                concatenated_input.input_path.display(),
+                concatenated_input.byte_length,
+                concatenated_input.output_offset
+            );
+        }
+
+        // =========================================
+        // Finalize Phase
+        // =========================================
+        fs::rename(&draft_path, output_path)
+    })();
+
+    if let Err(error) = concatenate_result {
+        let _ = fs::remove_file(&draft_path);
+        return Err(error);
+    }
+    Ok(concatenated_inputs)
+}
+
+/// Like `concatenate_files`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn concatenate_files_with_config(
+    input_paths: &[PathBuf],
+    output_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<Vec<ConcatenatedInput>> {
+    with_operation_config(config, || concatenate_files(input_paths, output_path))
+}
+
+// ============================
+// Head/Tail Extraction
+// ============================
+
+/// Copies the first `byte_count` bytes of a file to a new file.
+///
+/// The output is written as a draft and verified (length, and SHA-256
+/// equal to the digest of the source bytes read) before it is renamed to
+/// `destination_path`. The source is not modified.
+///
+/// # Returns
+/// - `Ok([u8; 32])`: SHA-256 of the extracted bytes
+/// - `Err(io::Error)` if `byte_count` exceeds the file size, the
+///   destination exists (`AlreadyExists`), or any phase fails
+pub fn copy_first_n_bytes(
+    source_file_path: &Path,
+    byte_count: u64,
+    destination_path: &Path,
+) -> io::Result<[u8; 32]> {
+    extract_range_to_new_file(source_file_path, 0, byte_count, destination_path)
+}
+
+/// Like `copy_first_n_bytes`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn copy_first_n_bytes_with_config(
+    source_file_path: &Path,
+    byte_count: u64,
+    destination_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<[u8; 32]> {
+    with_operation_config(config, || {
+        copy_first_n_bytes(source_file_path, byte_count, destination_path)
+    })
+}
+
+/// Copies the last `byte_count` bytes of a file to a new file.
+///
+/// Same verification and errors as `copy_first_n_bytes`.
+pub fn copy_last_n_bytes(
+    source_file_path: &Path,
+    byte_count: u64,
+    destination_path: &Path,
+) -> io::Result<[u8; 32]> {
+    let source_file_size = validate_target_file(source_file_path)?;
+    let source_offset = source_file_size.checked_sub(byte_count).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Requested {} bytes from a file of {} bytes",
+                byte_count, source_file_size
+            ),
+        )
+    })?;
+    extract_range_to_new_file(
+        source_file_path,
+        source_offset,
+        byte_count,
+        destination_path,
+    )
+}
+
+/// Like `copy_last_n_bytes`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn copy_last_n_bytes_with_config(
+    source_file_path: &Path,
+    byte_count: u64,
+    destination_path: &Path,
+    config: &OperationConfig,
+) -> io::Result<[u8; 32]> {
+    with_operation_config(config, || {
+        copy_last_n_bytes(source_file_path, byte_count, destination_path)
+    })
+}
+
+fn extract_range_to_new_file(
+    source_file_path: &Path,
+    source_offset: u64,
+    byte_count: u64,
+    destination_path: &Path,
+) -> io::Result<[u8; 32]> {
+    let source_file_size = validate_target_file(source_file_path)?;
+    if source_offset.saturating_add(byte_count) > source_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Requested {} bytes at {} from a file of {} bytes",
+                byte_count, source_offset, source_file_size
+            ),
+        ));
+    }
+    if destination_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Output file already exists: {}", destination_path.display()),
+        ));
+    }
+
+    let mut source_file = open_at_offset(source_file_path, source_offset)?;
+    write_verified_chunk(&mut source_file, byte_count, destination_path)
+}
+
+// =========================================
+// Shared Helpers
+// =========================================
+
+/// Checks a finished output file's length and SHA-256 on disk.
+pub(crate) fn verify_output_file(
+    file_path: &Path,
+    expected_length: u64,
+    expected_sha256: &[u8; 32],
+) -> io::Result<()> {
+    let actual_length = fs::metadata(file_path)?.len();
+    if actual_length != expected_length {
+        report_problem!("ERROR: {} failed verification", file_path.display());
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            expected_length,
+            actual_length,
+        )
+        .into());
+    }
+    let actual_sha256 = sha256_of_file(file_path)?;
+    if &actual_sha256 != expected_sha256 {
+        report_problem!("ERROR: {} failed verification", file_path.display());
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::ContentDigest,
+            0,
+            digest_leading_u64(expected_sha256),
+            digest_leading_u64(&actual_sha256),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Writes a small in-memory file through a draft, verifying before rename.
+fn write_file_via_draft(file_path: &Path, contents: &[u8]) -> io::Result<()> {
+    let draft_path = draft_path_for(file_path);
+    let write_result = (|| -> io::Result<()> {
+        fs::write(&draft_path, contents)?;
+        let read_back = fs::read(&draft_path)?;
+        if let Some(i) = first_mismatch(&read_back, contents) {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::WrittenBytes,
+                i as u64,
+                u64::from(contents[i]),
+                u64::from(read_back[i]),
+            )
+            .into());
+        }
+        if read_back.len() != contents.len() {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::TotalLength,
+                0,
+                contents.len() as u64,
+                read_back.len() as u64,
+            )
+            .into());
+        }
+        fs::rename(&draft_path, file_path)
+    })();
+
+    if write_result.is_err() {
+        let _ = fs::remove_file(&draft_path);
+    }
+    write_result
+}
+
+pub(crate) fn draft_path_for(file_path: &Path) -> PathBuf {
+    let mut draft_path = file_path.as_os_str().to_owned();
+    draft_path.push(DRAFT_FILE_SUFFIX);
+    PathBuf::from(draft_path)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256::sha256_of_bytes;
+
+    #[test]
+    fn test_split_file_chunks_and_manifest() {
+        let test_dir = std::env::temp_dir().join("test_split_file_dir");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+        let source_path = test_dir.join("image.bin");
+        let source_bytes: Vec<u8> = (0..250u32).map(|value| value as u8).collect();
+        fs::write(&source_path, &source_bytes).expect("create source file");
+        let output_directory = test_dir.join("parts");
+
+        let report = split_file(&source_path, 100, &output_directory).expect("split");
+
+        assert_eq!(report.chunks.len(), 3);
+        assert_eq!(report.chunks[2].byte_length, 50);
+        assert_eq!(
+            report.chunks[1].chunk_path,
+            output_directory.join("image.bin.part0001")
+        );
+        let mut rejoined = Vec::new();
+        for chunk in &report.chunks {
+            let chunk_bytes = fs::read(&chunk.chunk_path).expect("read chunk");
+            assert_eq!(chunk.sha256, sha256_of_bytes(&chunk_bytes));
+            rejoined.extend(chunk_bytes);
+        }
+        assert_eq!(rejoined, source_bytes);
+
+        let manifest_text = fs::read_to_string(&report.manifest_path).expect("read manifest");
+        assert_eq!(manifest_text.lines().count(), 3);
+        assert!(manifest_text.starts_with(&format!(
+            "{}  image.bin.part0000\n",
+            digest_to_hex(&sha256_of_bytes(&source_bytes[..100]))
+        )));
+
+        // A second split into the same directory is refused
+        assert_eq!(
+            split_file(&source_path, 100, &output_directory)
+                .expect_err("existing output")
+                .kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert!(split_file(&source_path, 0, &test_dir.join("other")).is_err());
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_copy_first_and_last_n_bytes() {
+        let test_dir = std::env::temp_dir().join("test_copy_head_tail_dir");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+        let source_path = test_dir.join("source.bin");
+        let source_bytes: Vec<u8> = (0u8..130).collect();
+        fs::write(&source_path, &source_bytes).expect("create source file");
+
+        let head_path = test_dir.join("head.bin");
+        let head_sha256 = copy_first_n_bytes(&source_path, 100, &head_path).expect("head");
+        assert_eq!(
+            fs::read(&head_path).expect("read head"),
+            &source_bytes[..100]
+        );
+        assert_eq!(head_sha256, sha256_of_bytes(&source_bytes[..100]));
+
+        let tail_path = test_dir.join("tail.bin");
+        copy_last_n_bytes(&source_path, 65, &tail_path).expect("tail");
+        assert_eq!(
+            fs::read(&tail_path).expect("read tail"),
+            &source_bytes[65..]
+        );
+
+        copy_last_n_bytes(&source_path, 0, &test_dir.join("empty.bin")).expect("empty tail");
+        assert!(copy_last_n_bytes(&source_path, 131, &test_dir.join("big.bin")).is_err());
+        assert!(copy_first_n_bytes(&source_path, 1, &head_path).is_err());
+        assert_eq!(fs::read(&source_path).expect("read source"), source_bytes);
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_concatenate_files_rejoins_split() {
+        let test_dir = std::env::temp_dir().join("test_concatenate_files_dir");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+        let source_path = test_dir.join("data.bin");
+        let source_bytes: Vec<u8> = (0..200u32).map(|value| (value * 7) as u8).collect();
+        fs::write(&source_path, &source_bytes).expect("create source file");
+        let empty_path = test_dir.join("empty.bin");
+        fs::write(&empty_path, b"").expect("create empty file");
+
+        let report = split_file(&source_path, 70, &test_dir.join("parts")).expect("split");
+        let mut input_paths: Vec<PathBuf> = report
+            .chunks
+            .iter()
+            .map(|chunk| chunk.chunk_path.clone())
+            .collect();
+        input_paths.insert(1, empty_path);
+
+        let output_path = test_dir.join("joined.bin");
+        let concatenated = concatenate_files(&input_paths, &output_path).expect("concatenate");
+        assert_eq!(concatenated.len(), 4);
+        assert_eq!(concatenated[2].output_offset, 70);
+        assert_eq!(concatenated[2].sha256, report.chunks[1].sha256);
+        assert_eq!(fs::read(&output_path).expect("read joined"), source_bytes);
+
+        // Existing output and empty input list are refused
+        assert!(concatenate_files(&input_paths, &output_path).is_err());
+        assert!(concatenate_files(&[], &test_dir.join("none.bin")).is_err());
+        assert!(!draft_path_for(&test_dir.join("none.bin")).exists());
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+}
@@ -0,0 +1,482 @@
+//! Pluggable checksums for verification
+//!
+//! Verification compares the draft against the original byte for byte, and
+//! also keeps a running checksum of each side as a second, independent
+//! check. That checksum used to be hard-wired to `compute_simple_checksum`.
+//! It now goes through the `Digest` trait, and `HashAlgorithm` picks the
+//! implementation (`OperationConfigBuilder::verification_hash`):
+//!
+//! - `XorSum`: the positional XOR checksum, summed over the chunks it is
+//!   fed. The default
+//! - `Crc32`: CRC-32 (IEEE), as used by ZIP and gzip (`crc32`, which
+//!   also fixes up the ZIP member and GPT header checksums). About as fast
+//!   as `XorSum`, and it catches every burst error up to 32 bits
+//! - `Sha256`: SHA-256 (`sha256`), collision-resistant. Close to `Crc32`
+//!   on x86-64 CPUs with the SHA extensions, about 10x slower without
+//! - `Blake3`: BLAKE3, collision-resistant, with the `blake3` Cargo
+//!   feature. Several GB/s on one core with AVX2 or AVX-512, so checking a
+//!   multi-gigabyte file costs little more than reading it
+//!
+//! `hash_file` applies any of them but `XorSum` to a whole file, for
+//! callers that want before-and-after hashes around an edit (CLI:
+//! `bfbo hash`). `XorSum` is chunk-positional, so a whole-file value would
+//! change with the buffer size.
+//!
+//! The algorithm's name appears in the verification report lines, in
+//! `OperationReport::verification_hash` and in backup manifests. A backup
+//! manifest records the backup's digest with the verification hash if it
+//! is collision-resistant (SHA-256, BLAKE3), and with SHA-256 otherwise.
+
+pub mod crc32;
+pub mod sha256;
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use self::crc32::Crc32;
+use self::sha256::{Sha256, digest_to_hex};
+use crate::draft_workflow::{check_chunk_limit, chunk_limit_for};
+use crate::operation_config::active_buffer_size;
+use crate::util::compute_simple_checksum;
+
+/// Checksum algorithm used by verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// `compute_simple_checksum` of each chunk, wrapping-summed
+    XorSum,
+    /// CRC-32 (IEEE 802.3)
+    Crc32,
+    /// SHA-256
+    Sha256,
+    /// BLAKE3 (`blake3` feature)
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Every algorithm this build has, in the order they are listed to
+    /// users.
+    #[cfg(not(feature = "blake3"))]
+    pub const ALL: &'static [HashAlgorithm] = &[
+        HashAlgorithm::XorSum,
+        HashAlgorithm::Crc32,
+        HashAlgorithm::Sha256,
+    ];
+    #[cfg(feature = "blake3")]
+    pub const ALL: &'static [HashAlgorithm] = &[
+        HashAlgorithm::XorSum,
+        HashAlgorithm::Crc32,
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Blake3,
+    ];
+
+    /// Stable name used in reports, manifests and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::XorSum => "xor-sum",
+            HashAlgorithm::Crc32 => "crc32",
+            HashAlgorithm::Sha256 => "sha256",
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Length of a value in bytes.
+    pub fn digest_length(&self) -> usize {
+        match self {
+            HashAlgorithm::XorSum => 8,
+            HashAlgorithm::Crc32 => 4,
+            HashAlgorithm::Sha256 => 32,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => blake3::OUT_LEN,
+        }
+    }
+
+    /// Whether finding two inputs with the same value is infeasible, as a
+    /// backup manifest needs. The others only catch accidental damage.
+    pub fn is_collision_resistant(&self) -> bool {
+        match self {
+            HashAlgorithm::XorSum | HashAlgorithm::Crc32 => false,
+            HashAlgorithm::Sha256 => true,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => true,
+        }
+    }
+
+    /// Whether the value depends only on the bytes, not on how they were
+    /// split into `Digest::update` calls. Only these can hash a whole file.
+    pub fn is_chunking_independent(&self) -> bool {
+        !matches!(self, HashAlgorithm::XorSum)
+    }
+
+    /// Looks an algorithm up by its `name()`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        HashAlgorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name() == name)
+    }
+
+    /// A fresh digest computing this algorithm.
+    pub fn digest(&self) -> Box<dyn Digest> {
+        match self {
+            HashAlgorithm::XorSum => Box::new(XorSumDigest::default()),
+            HashAlgorithm::Crc32 => Box::new(Crc32::new()),
+            HashAlgorithm::Sha256 => Box::new(Sha256::new()),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Box::new(blake3::Hasher::new()),
+        }
+    }
+}
+
+/// Verification checksum used when no config is given
+pub const DEFAULT_VERIFICATION_HASH: HashAlgorithm = HashAlgorithm::XorSum;
+
+/// A checksum fed incrementally, one bucket-brigade chunk at a time.
+pub trait Digest {
+    fn algorithm(&self) -> HashAlgorithm;
+
+    /// Feeds the next chunk.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Checksum of everything fed so far (the digest can keep going).
+    fn value(&self) -> HashValue;
+}
+
+/// A finished checksum, tagged with the algorithm that made it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashValue {
+    algorithm: HashAlgorithm,
+    bytes: Vec<u8>,
+}
+
+impl HashValue {
+    pub fn new(algorithm: HashAlgorithm, bytes: Vec<u8>) -> Self {
+        HashValue { algorithm, bytes }
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Checksum bytes, big-endian for the integer checksums
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Lowercase hex of `as_bytes()`
+    pub fn to_hex(&self) -> String {
+        digest_to_hex(&self.bytes)
+    }
+
+    /// Parses the `to_hex()` form of a value of `algorithm`.
+    pub fn from_hex(algorithm: HashAlgorithm, hex_text: &str) -> Option<HashValue> {
+        if hex_text.len() != algorithm.digest_length() * 2 || !hex_text.is_ascii() {
+            return None;
+        }
+        let bytes = (0..hex_text.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex_text[index..index + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        Some(HashValue::new(algorithm, bytes))
+    }
+
+    /// First 8 bytes as an integer, for the `u64` fields of
+    /// `ByteOpError::VerificationFailed`.
+    pub(crate) fn leading_u64(&self) -> u64 {
+        let mut leading_bytes = [0u8; 8];
+        let copied_length = self.bytes.len().min(8);
+        leading_bytes[..copied_length].copy_from_slice(&self.bytes[..copied_length]);
+        u64::from_be_bytes(leading_bytes)
+    }
+}
+
+impl fmt::Display for HashValue {
+    /// `<algorithm>:<hex>`, e.g. `crc32:cbf43926`
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}:{}", self.algorithm.name(), self.to_hex())
+    }
+}
+
+/// Running sum of `compute_simple_checksum` over the chunks fed.
+///
+/// The checksum is positional within a chunk, so the value depends on how
+/// the bytes were chunked; both sides of a comparison are chunked alike.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XorSumDigest {
+    checksum: u64,
+}
+
+impl Digest for XorSumDigest {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::XorSum
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.checksum = self.checksum.wrapping_add(compute_simple_checksum(bytes));
+    }
+
+    fn value(&self) -> HashValue {
+        HashValue::new(HashAlgorithm::XorSum, self.checksum.to_be_bytes().to_vec())
+    }
+}
+
+impl Digest for Crc32 {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Crc32
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc32::update(self, bytes);
+    }
+
+    fn value(&self) -> HashValue {
+        HashValue::new(HashAlgorithm::Crc32, self.finalize().to_be_bytes().to_vec())
+    }
+}
+
+impl Digest for Sha256 {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Sha256
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        Sha256::update(self, bytes);
+    }
+
+    fn value(&self) -> HashValue {
+        HashValue::new(HashAlgorithm::Sha256, self.finalize().to_vec())
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl Digest for blake3::Hasher {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Blake3
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        blake3::Hasher::update(self, bytes);
+    }
+
+    fn value(&self) -> HashValue {
+        HashValue::new(HashAlgorithm::Blake3, self.finalize().as_bytes().to_vec())
+    }
+}
+
+/// Digest of a whole file with `algorithm`, streamed through a
+/// bucket-brigade buffer (the active config's `buffer_size`).
+///
+/// Lets a caller record a file's hash before and after an edit with the
+/// same code the operations use. For backups and custody records prefer a
+/// collision-resistant algorithm (`HashAlgorithm::is_collision_resistant`).
+/// `XorSum` is refused: its value is chunk-positional, so it would depend
+/// on the buffer size rather than on the file alone.
+///
+/// # Returns
+/// - `Ok(HashValue)` tagged with `algorithm`
+/// - `Err(io::Error)` (`InvalidInput`) for `XorSum`, before the file is
+///   opened
+/// - `Err(io::Error)` if the file cannot be opened or read
+pub fn hash_file(file_path: &Path, algorithm: HashAlgorithm) -> io::Result<HashValue> {
+    refuse_chunk_dependent_algorithm(algorithm)?;
+    let source_file = File::open(file_path)?;
+    let chunk_limit = chunk_limit_for(source_file.metadata()?.len());
+    hash_chunks(source_file, algorithm, Some(chunk_limit))
+}
+
+/// Digest of everything `source` yields until EOF, e.g. standard input.
+///
+/// Like `hash_file`, but the length is not known up front, so there is no
+/// chunk cap: every read consumes at least one byte or ends the stream.
+///
+/// # Returns
+/// - `Ok(HashValue)` tagged with `algorithm`
+/// - `Err(io::Error)` (`InvalidInput`) for `XorSum`, before anything is read
+/// - `Err(io::Error)` if reading fails
+pub fn hash_reader<R: Read>(source: R, algorithm: HashAlgorithm) -> io::Result<HashValue> {
+    refuse_chunk_dependent_algorithm(algorithm)?;
+    hash_chunks(source, algorithm, None)
+}
+
+fn refuse_chunk_dependent_algorithm(algorithm: HashAlgorithm) -> io::Result<()> {
+    if algorithm.is_chunking_independent() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "{} depends on the buffer size and cannot hash a whole file",
+            algorithm.name()
+        ),
+    ))
+}
+
+fn hash_chunks<R: Read>(
+    mut source: R,
+    algorithm: HashAlgorithm,
+    chunk_limit: Option<usize>,
+) -> io::Result<HashValue> {
+    let mut digest = algorithm.digest();
+    let mut read_buffer = vec![0u8; active_buffer_size()];
+    let mut chunk_number: usize = 0;
+
+    loop {
+        if let Some(chunk_limit) = chunk_limit {
+            check_chunk_limit(chunk_number, chunk_limit)?;
+        }
+        chunk_number += 1;
+
+        let bytes_read = source.read(&mut read_buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        digest.update(&read_buffer[..bytes_read]);
+    }
+
+    Ok(digest.value())
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation_config::{OperationConfig, with_operation_config};
+    use crate::replace::replace_single_byte_in_file_with_report;
+    use crate::reporter::{ReportEvent, Reporter, with_reporter};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_digests_stream_and_name_their_algorithm() {
+        let check_bytes = b"123456789";
+        let expected_hex = [
+            (
+                HashAlgorithm::XorSum,
+                format!("{:016x}", compute_simple_checksum(check_bytes)),
+            ),
+            (HashAlgorithm::Crc32, "cbf43926".to_string()),
+            (
+                HashAlgorithm::Sha256,
+                "15e2b0d3c33891ebb0f1ef609ec419420c20e320ce94c65fbc8c3312448eb225".to_string(),
+            ),
+        ];
+        for (algorithm, expected) in expected_hex {
+            assert_eq!(HashAlgorithm::from_name(algorithm.name()), Some(algorithm));
+            let mut digest = algorithm.digest();
+            digest.update(check_bytes);
+            let value = digest.value();
+            assert_eq!(value.algorithm(), algorithm);
+            assert_eq!(value.to_hex(), expected);
+            assert_eq!(
+                value.to_string(),
+                format!("{}:{}", algorithm.name(), expected)
+            );
+        }
+        assert_eq!(HashAlgorithm::from_name("md5"), None);
+
+        // Split feeding gives the same value, except for the chunk-positional XOR sum
+        for algorithm in [HashAlgorithm::Crc32, HashAlgorithm::Sha256] {
+            let mut digest = algorithm.digest();
+            digest.update(&check_bytes[..4]);
+            digest.update(&check_bytes[4..]);
+            let mut whole_digest = algorithm.digest();
+            whole_digest.update(check_bytes);
+            assert_eq!(digest.value(), whole_digest.value());
+        }
+    }
+
+    struct LineCollector(RefCell<Vec<String>>);
+
+    impl Reporter for LineCollector {
+        fn report(&self, event: &ReportEvent<'_>) {
+            if let ReportEvent::VerificationPassed { detail } = event {
+                self.0.borrow_mut().push(detail.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_file_streams_through_the_buffer() {
+        let test_dir = std::env::temp_dir().join("bfbo_test_hash_file");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("data.bin");
+        let file_bytes: Vec<u8> = (0..1000u32).map(|index| (index % 251) as u8).collect();
+        std::fs::write(&file_path, &file_bytes).expect("write test file");
+
+        // 64-byte buffers: many chunks, the last one partial
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("tiny buffer config");
+        for algorithm in [HashAlgorithm::Crc32, HashAlgorithm::Sha256] {
+            let mut whole_digest = algorithm.digest();
+            whole_digest.update(&file_bytes);
+            let streamed = with_operation_config(&tiny_config, || hash_file(&file_path, algorithm))
+                .expect("hash with tiny buffers");
+            assert_eq!(streamed, whole_digest.value());
+            let from_reader =
+                with_operation_config(&tiny_config, || hash_reader(&file_bytes[..], algorithm))
+                    .expect("hash a reader with tiny buffers");
+            assert_eq!(from_reader, whole_digest.value());
+        }
+
+        // The XOR sum would differ between buffer sizes, so it is refused
+        let error = hash_file(&file_path, HashAlgorithm::XorSum).expect_err("xor-sum refused");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error =
+            hash_reader(&file_bytes[..], HashAlgorithm::XorSum).expect_err("xor-sum refused");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let missing = hash_file(&test_dir.join("missing.bin"), HashAlgorithm::Sha256);
+        assert_eq!(
+            missing.expect_err("missing file").kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_verification_uses_configured_hash() {
+        let test_dir = std::env::temp_dir().join("bfbo_test_verification_hash");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("data.bin");
+        std::fs::write(&file_path, b"0123456789").expect("write test file");
+
+        let config = OperationConfig::builder()
+            .verification_hash(HashAlgorithm::Sha256)
+            .build()
+            .expect("sha256 verification config");
+        let collector = Rc::new(LineCollector(RefCell::new(Vec::new())));
+        let report = with_reporter(collector.clone(), || {
+            with_operation_config(&config, || {
+                replace_single_byte_in_file_with_report(file_path.clone(), 4, b'X')
+            })
+        })
+        .expect("replace with sha256 verification");
+
+        assert_eq!(
+            std::fs::read(&file_path).expect("read edited file"),
+            b"0123X56789"
+        );
+        assert_eq!(report.verification_hash, HashAlgorithm::Sha256);
+        let lines = collector.0.borrow();
+        assert!(
+            lines.iter().any(|line| line.contains("sha256:")),
+            "{:?}",
+            lines
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
@@ -0,0 +1,492 @@
+//! SHA-256 (FIPS 180-4)
+//!
+//! A collision-resistant digest for records that must stand up to scrutiny
+//! (forensic sector hashes, custody reports), where CRC-32 is not enough.
+//! Backups, backup manifests, journals and the `expected_original_sha256`
+//! precondition all use it, and verification can (`HashAlgorithm::Sha256`).
+//! Streaming through `Sha256::update`, with a fixed 64-byte block buffer,
+//! so callers can feed bucket-brigade chunks of any size from arbitrarily
+//! large files.
+//!
+//! Whole blocks are compressed straight from the caller's chunk. On x86-64
+//! CPUs with the SHA extensions the compression runs on those instructions
+//! (about 0.9 GB/s on one core, against about 0.1 GB/s for the portable
+//! rounds).
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::draft_workflow::{check_chunk_limit, chunk_limit_for, open_at_offset};
+use crate::operation_config::active_buffer_size;
+use crate::util::chunk_length;
+
+/// Round constants (first 32 bits of the fractional parts of the cube roots
+/// of the first 64 primes)
+const SHA256_ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Initial hash state
+const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Streaming SHA-256 state.
+#[derive(Debug, Clone, Copy)]
+pub struct Sha256 {
+    state: [u32; 8],
+    block_buffer: [u8; 64],
+    block_buffer_length: usize,
+    total_byte_count: u64,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {
+            state: SHA256_INITIAL_STATE,
+            block_buffer: [0u8; 64],
+            block_buffer_length: 0,
+            total_byte_count: 0,
+        }
+    }
+
+    /// Feeds more bytes into the digest.
+    ///
+    /// Whole blocks are compressed straight from `bytes`; only a partial
+    /// block at either end goes through the block buffer.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.total_byte_count = self.total_byte_count.wrapping_add(bytes.len() as u64);
+        let mut remaining_bytes = bytes;
+
+        if self.block_buffer_length > 0 {
+            let fill_length = (64 - self.block_buffer_length).min(remaining_bytes.len());
+            self.block_buffer[self.block_buffer_length..self.block_buffer_length + fill_length]
+                .copy_from_slice(&remaining_bytes[..fill_length]);
+            self.block_buffer_length += fill_length;
+            remaining_bytes = &remaining_bytes[fill_length..];
+            if self.block_buffer_length < 64 {
+                return;
+            }
+            let block = self.block_buffer;
+            self.compress_blocks(&[block]);
+            self.block_buffer_length = 0;
+        }
+
+        let (blocks, tail_bytes) = remaining_bytes.as_chunks::<64>();
+        self.compress_blocks(blocks);
+        self.block_buffer[..tail_bytes.len()].copy_from_slice(tail_bytes);
+        self.block_buffer_length = tail_bytes.len();
+    }
+
+    /// Pads, processes the final block(s), and returns the 32-byte digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let message_bit_count = self.total_byte_count.wrapping_mul(8);
+
+        self.update_padding_byte(0x80);
+        while self.block_buffer_length != 56 {
+            self.update_padding_byte(0x00);
+        }
+        for length_byte in message_bit_count.to_be_bytes() {
+            self.update_padding_byte(length_byte);
+        }
+
+        let mut digest = [0u8; 32];
+        for (word_index, word) in self.state.iter().enumerate() {
+            digest[word_index * 4..word_index * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    /// Like `update` for one byte, but not counted in the message length.
+    fn update_padding_byte(&mut self, byte_value: u8) {
+        self.block_buffer[self.block_buffer_length] = byte_value;
+        self.block_buffer_length += 1;
+        if self.block_buffer_length == 64 {
+            let block = self.block_buffer;
+            self.compress_blocks(&[block]);
+            self.block_buffer_length = 0;
+        }
+    }
+
+    /// Runs the compression function over `blocks`, with the CPU's SHA
+    /// instructions where it has them.
+    fn compress_blocks(&mut self, blocks: &[[u8; 64]]) {
+        #[cfg(target_arch = "x86_64")]
+        if sha_extensions::is_available() {
+            // SAFETY: the CPU supports the features the function enables
+            unsafe { sha_extensions::compress_blocks(&mut self.state, blocks) };
+            return;
+        }
+        for block in blocks {
+            self.compress_block(block);
+        }
+    }
+
+    fn compress_block(&mut self, block: &[u8; 64]) {
+        let mut message_schedule = [0u32; 64];
+        for word_index in 0..16 {
+            message_schedule[word_index] = u32::from_be_bytes([
+                block[word_index * 4],
+                block[word_index * 4 + 1],
+                block[word_index * 4 + 2],
+                block[word_index * 4 + 3],
+            ]);
+        }
+        for word_index in 16..64 {
+            let previous_15 = message_schedule[word_index - 15];
+            let previous_2 = message_schedule[word_index - 2];
+            let sigma_0 =
+                previous_15.rotate_right(7) ^ previous_15.rotate_right(18) ^ (previous_15 >> 3);
+            let sigma_1 =
+                previous_2.rotate_right(17) ^ previous_2.rotate_right(19) ^ (previous_2 >> 10);
+            message_schedule[word_index] = message_schedule[word_index - 16]
+                .wrapping_add(sigma_0)
+                .wrapping_add(message_schedule[word_index - 7])
+                .wrapping_add(sigma_1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for round_index in 0..64 {
+            let big_sigma_1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choose = (e & f) ^ (!e & g);
+            let temporary_1 = h
+                .wrapping_add(big_sigma_1)
+                .wrapping_add(choose)
+                .wrapping_add(SHA256_ROUND_CONSTANTS[round_index])
+                .wrapping_add(message_schedule[round_index]);
+            let big_sigma_0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temporary_2 = big_sigma_0.wrapping_add(majority);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temporary_1);
+            d = c;
+            c = b;
+            b = a;
+            a = temporary_1.wrapping_add(temporary_2);
+        }
+
+        for (state_word, round_word) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state_word = state_word.wrapping_add(round_word);
+        }
+    }
+}
+
+/// x86-64 SHA extensions (`SHA-NI`, Intel since Goldmont and Ice Lake, AMD
+/// since Zen): two rounds per `sha256rnds2`, about 8x the portable code.
+#[cfg(target_arch = "x86_64")]
+mod sha_extensions {
+    use std::arch::x86_64::{
+        __m128i, _mm_add_epi32, _mm_alignr_epi8, _mm_blend_epi16, _mm_loadu_si128, _mm_set_epi64x,
+        _mm_sha256msg1_epu32, _mm_sha256msg2_epu32, _mm_sha256rnds2_epu32, _mm_shuffle_epi8,
+        _mm_shuffle_epi32, _mm_storeu_si128,
+    };
+
+    use super::SHA256_ROUND_CONSTANTS;
+
+    pub(super) fn is_available() -> bool {
+        std::arch::is_x86_feature_detected!("sha")
+            && std::arch::is_x86_feature_detected!("sse4.1")
+            && std::arch::is_x86_feature_detected!("ssse3")
+    }
+
+    /// # Safety
+    /// The CPU must support `sha`, `sse4.1` and `ssse3` (`is_available`).
+    #[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+    pub(super) unsafe fn compress_blocks(state: &mut [u32; 8], blocks: &[[u8; 64]]) {
+        // Byte order reversal of each 32-bit word (message words are big-endian)
+        let byte_swap_mask = _mm_set_epi64x(
+            0x0C0D_0E0F_0809_0A0Bu64 as i64,
+            0x0405_0607_0001_0203u64 as i64,
+        );
+
+        // The instructions keep the state as ABEF and CDGH
+        // SAFETY: `state` is 32 bytes; unaligned loads
+        let (dcba, hgfe) = unsafe {
+            let state_pointer = state.as_ptr() as *const __m128i;
+            (
+                _mm_loadu_si128(state_pointer),
+                _mm_loadu_si128(state_pointer.add(1)),
+            )
+        };
+        let cdab = _mm_shuffle_epi32(dcba, 0xB1);
+        let efgh = _mm_shuffle_epi32(hgfe, 0x1B);
+        let mut abef = _mm_alignr_epi8(cdab, efgh, 8);
+        let mut cdgh = _mm_blend_epi16(efgh, cdab, 0xF0);
+
+        for block in blocks {
+            let (saved_abef, saved_cdgh) = (abef, cdgh);
+
+            // SAFETY: `block` is 64 bytes; unaligned loads
+            let mut message_words: [__m128i; 4] = unsafe {
+                let block_pointer = block.as_ptr() as *const __m128i;
+                [0, 1, 2, 3].map(|word_index| {
+                    _mm_shuffle_epi8(
+                        _mm_loadu_si128(block_pointer.add(word_index)),
+                        byte_swap_mask,
+                    )
+                })
+            };
+
+            for group_index in 0..16 {
+                if group_index >= 4 {
+                    // The next four schedule words replace the oldest four
+                    let oldest = message_words[group_index % 4];
+                    let second = message_words[(group_index + 1) % 4];
+                    let third = message_words[(group_index + 2) % 4];
+                    let newest = message_words[(group_index + 3) % 4];
+                    message_words[group_index % 4] = _mm_sha256msg2_epu32(
+                        _mm_add_epi32(
+                            _mm_sha256msg1_epu32(oldest, second),
+                            _mm_alignr_epi8(newest, third, 4),
+                        ),
+                        newest,
+                    );
+                }
+
+                // SAFETY: 4 of the 64 round constants; unaligned load
+                let round_constants = unsafe {
+                    _mm_loadu_si128(
+                        SHA256_ROUND_CONSTANTS.as_ptr().add(group_index * 4) as *const __m128i
+                    )
+                };
+                let schedule_plus_constants =
+                    _mm_add_epi32(message_words[group_index % 4], round_constants);
+                cdgh = _mm_sha256rnds2_epu32(cdgh, abef, schedule_plus_constants);
+                abef = _mm_sha256rnds2_epu32(
+                    abef,
+                    cdgh,
+                    _mm_shuffle_epi32(schedule_plus_constants, 0x0E),
+                );
+            }
+
+            abef = _mm_add_epi32(abef, saved_abef);
+            cdgh = _mm_add_epi32(cdgh, saved_cdgh);
+        }
+
+        let feba = _mm_shuffle_epi32(abef, 0x1B);
+        let dchg = _mm_shuffle_epi32(cdgh, 0xB1);
+        let dcba = _mm_blend_epi16(feba, dchg, 0xF0);
+        let hgef = _mm_alignr_epi8(dchg, feba, 8);
+        // SAFETY: `state` is 32 bytes; unaligned stores
+        unsafe {
+            let state_pointer = state.as_mut_ptr() as *mut __m128i;
+            _mm_storeu_si128(state_pointer, dcba);
+            _mm_storeu_si128(state_pointer.add(1), hgef);
+        }
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+/// SHA-256 of a byte slice in one call.
+pub fn sha256_of_bytes(bytes: &[u8]) -> [u8; 32] {
+    let mut digest = Sha256::new();
+    digest.update(bytes);
+    digest.finalize()
+}
+
+/// SHA-256 of a whole file, streamed through a bucket-brigade buffer.
+pub fn sha256_of_file(file_path: &Path) -> io::Result<[u8; 32]> {
+    let mut source_file = File::open(file_path)?;
+    let mut digest = Sha256::new();
+    let mut read_buffer = vec![0u8; active_buffer_size()];
+    let chunk_limit = chunk_limit_for(source_file.metadata()?.len());
+    let mut chunk_number: usize = 0;
+
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_read = source_file.read(&mut read_buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        digest.update(&read_buffer[..bytes_read]);
+    }
+
+    Ok(digest.finalize())
+}
+
+/// SHA-256 of `byte_length` bytes at `byte_offset`, streamed.
+pub fn sha256_of_file_range(
+    file_path: &Path,
+    byte_offset: u64,
+    byte_length: u64,
+) -> io::Result<[u8; 32]> {
+    let mut source_file = open_at_offset(file_path, byte_offset)?;
+    let mut digest = Sha256::new();
+    let mut read_buffer = vec![0u8; active_buffer_size()];
+    let mut bytes_hashed: u64 = 0;
+    let chunk_limit = chunk_limit_for(byte_length);
+    let mut chunk_number: usize = 0;
+
+    while bytes_hashed < byte_length {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_read = chunk_length(read_buffer.len(), byte_length - bytes_hashed);
+        source_file.read_exact(&mut read_buffer[..bytes_to_read])?;
+        digest.update(&read_buffer[..bytes_to_read]);
+        bytes_hashed += bytes_to_read as u64;
+    }
+
+    Ok(digest.finalize())
+}
+
+/// HMAC-SHA256 (RFC 2104) of `message` under `key`.
+///
+/// Used where a record must be tamper-evident to anyone holding the key
+/// (custody reports, canaries). It is a shared-secret MAC, not a
+/// public-key signature.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const HMAC_BLOCK_SIZE: usize = 64;
+
+    let mut key_block = [0u8; HMAC_BLOCK_SIZE];
+    if key.len() > HMAC_BLOCK_SIZE {
+        key_block[..32].copy_from_slice(&sha256_of_bytes(key));
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner_pad = [0x36u8; HMAC_BLOCK_SIZE];
+    let mut outer_pad = [0x5Cu8; HMAC_BLOCK_SIZE];
+    for byte_index in 0..HMAC_BLOCK_SIZE {
+        inner_pad[byte_index] ^= key_block[byte_index];
+        outer_pad[byte_index] ^= key_block[byte_index];
+    }
+
+    let mut inner_digest = Sha256::new();
+    inner_digest.update(&inner_pad);
+    inner_digest.update(message);
+    let inner_hash = inner_digest.finalize();
+
+    let mut outer_digest = Sha256::new();
+    outer_digest.update(&outer_pad);
+    outer_digest.update(&inner_hash);
+    outer_digest.finalize()
+}
+
+/// Parses 64 hex digits (either case) back into a digest.
+pub fn hex_to_digest(hex_text: &str) -> Option<[u8; 32]> {
+    if hex_text.len() != 64 || !hex_text.is_ascii() {
+        return None;
+    }
+    let mut digest = [0u8; 32];
+    for (index, byte_value) in digest.iter_mut().enumerate() {
+        *byte_value = u8::from_str_radix(&hex_text[index * 2..index * 2 + 2], 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// Lowercase hex rendering of a digest, as printed by `sha256sum`.
+pub fn digest_to_hex(digest: &[u8]) -> String {
+    let mut hex_text = String::with_capacity(digest.len() * 2);
+    for byte_value in digest {
+        hex_text.push_str(&format!("{:02x}", byte_value));
+    }
+    hex_text
+}
+
+/// First 8 bytes of a digest as an integer, for the `u64` fields of
+/// `ByteOpError::VerificationFailed`.
+pub(crate) fn digest_leading_u64(digest: &[u8; 32]) -> u64 {
+    let mut leading_bytes = [0u8; 8];
+    leading_bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(leading_bytes)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_known_values() {
+        assert_eq!(
+            digest_to_hex(&sha256_of_bytes(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            digest_to_hex(&sha256_of_bytes(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        // Two-block message, streamed in uneven pieces
+        let message = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        let mut digest = Sha256::new();
+        digest.update(&message[..5]);
+        digest.update(&message[5..]);
+        assert_eq!(
+            digest_to_hex(&digest.finalize()),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        // One million 'a', fed in pieces that straddle block boundaries
+        let piece_lengths = [1, 63, 64, 65, 130, 1000];
+        let a_bytes = [b'a'; 1000];
+        let mut digest = Sha256::new();
+        let mut bytes_fed = 0;
+        for piece_length in piece_lengths.iter().cycle() {
+            let piece_length = (*piece_length).min(1_000_000 - bytes_fed);
+            digest.update(&a_bytes[..piece_length]);
+            bytes_fed += piece_length;
+            if bytes_fed == 1_000_000 {
+                break;
+            }
+        }
+        assert_eq!(
+            digest_to_hex(&digest.finalize()),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn test_sha_extensions_match_portable_rounds() {
+        let blocks: Vec<[u8; 64]> = (0..5u8)
+            .map(|block_index| std::array::from_fn(|i| (i as u8).wrapping_mul(37) ^ block_index))
+            .collect();
+        let mut portable = Sha256::new();
+        for block in &blocks {
+            portable.compress_block(block);
+        }
+        let mut dispatched = Sha256::new();
+        dispatched.compress_blocks(&blocks);
+        assert_eq!(dispatched.state, portable.state);
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        assert_eq!(
+            digest_to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Key longer than one block is hashed first (RFC 4231 case 6)
+        assert_eq!(
+            digest_to_hex(&hmac_sha256(
+                &[0xAA; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Host-level safety switch ("no-write freeze")
+//!
+//! Fleet operators can freeze all binary editing on a host, e.g. during an
+//! incident window, without uninstalling the tool. While a freeze is active,
+//! every CLI invocation of a write subcommand is forced into dry-run mode,
+//! regardless of command-line flags, and exits with `EXIT_WRITES_FROZEN`.
+//!
+//! A freeze is active if either:
+//! - the environment variable `BFBO_NO_WRITE` is set to anything other than
+//!   empty, `0`, `false`, or `no`; or
+//! - the marker file `/etc/bfbo/no-write` exists (Unix only).
+
+use std::path::Path;
+
+/// Environment variable that activates the freeze
+pub const NO_WRITE_ENVIRONMENT_VARIABLE: &str = "BFBO_NO_WRITE";
+
+/// Marker file that activates the freeze host-wide (Unix only)
+pub const NO_WRITE_MARKER_FILE_PATH: &str = "/etc/bfbo/no-write";
+
+/// Why writes are frozen on this host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostWriteFreeze {
+    /// `BFBO_NO_WRITE` is set (value recorded for the message)
+    EnvironmentVariable(String),
+    /// The marker file exists
+    MarkerFile(String),
+}
+
+impl HostWriteFreeze {
+    /// Human-readable reason, for the dry-run notice.
+    pub fn describe(&self) -> String {
+        match self {
+            HostWriteFreeze::EnvironmentVariable(value) => format!(
+                "{}={} is set on this host",
+                NO_WRITE_ENVIRONMENT_VARIABLE, value
+            ),
+            HostWriteFreeze::MarkerFile(path) => {
+                format!("marker file {} exists on this host", path)
+            }
+        }
+    }
+}
+
+/// Checks the environment and marker file for an active write freeze.
+pub fn detect_host_write_freeze() -> Option<HostWriteFreeze> {
+    let environment_value = std::env::var(NO_WRITE_ENVIRONMENT_VARIABLE).ok();
+    let marker_file_exists = cfg!(unix) && Path::new(NO_WRITE_MARKER_FILE_PATH).exists();
+    freeze_from_sources(environment_value.as_deref(), marker_file_exists)
+}
+
+/// Decides the freeze state from already-read sources (testable, no globals).
+fn freeze_from_sources(
+    environment_value: Option<&str>,
+    marker_file_exists: bool,
+) -> Option<HostWriteFreeze> {
+    if let Some(value) = environment_value {
+        let normalized_value = value.trim().to_ascii_lowercase();
+        let is_disabled_value = normalized_value.is_empty()
+            || normalized_value == "0"
+            || normalized_value == "false"
+            || normalized_value == "no";
+        if !is_disabled_value {
+            return Some(HostWriteFreeze::EnvironmentVariable(value.to_string()));
+        }
+    }
+
+    if marker_file_exists {
+        return Some(HostWriteFreeze::MarkerFile(
+            NO_WRITE_MARKER_FILE_PATH.to_string(),
+        ));
+    }
+
+    None
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freeze_from_environment_values() {
+        assert_eq!(freeze_from_sources(None, false), None);
+        assert_eq!(freeze_from_sources(Some(""), false), None);
+        assert_eq!(freeze_from_sources(Some("0"), false), None);
+        assert_eq!(freeze_from_sources(Some("False"), false), None);
+        assert_eq!(
+            freeze_from_sources(Some("1"), false),
+            Some(HostWriteFreeze::EnvironmentVariable("1".to_string()))
+        );
+        assert_eq!(
+            freeze_from_sources(Some("incident-4711"), false),
+            Some(HostWriteFreeze::EnvironmentVariable(
+                "incident-4711".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_freeze_from_marker_file() {
+        assert_eq!(
+            freeze_from_sources(Some("0"), true),
+            Some(HostWriteFreeze::MarkerFile(
+                NO_WRITE_MARKER_FILE_PATH.to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,1020 @@
+//! Single-byte insertion (frame-shift +1)
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+use crate::backup_manifest::record_backup_manifest;
+use crate::canary::seal_draft_with_canary;
+use crate::draft_workflow::{
+    DraftArtifactPaths, VerificationLevel, build_artifact_paths, check_artifact_overwrite,
+    check_draft_against_utf8_guard, check_original_preconditions, chunk_limit_for,
+    copy_original_metadata, create_buffered_draft, open_buffered_reader, replace_with_draft,
+    resolve_symlinked_target, rotate_backups,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::journal::{JournalPhase, OperationJournal};
+use crate::lock_file::OperationLock;
+use crate::offset::Offset;
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::operation_report::{OperationReport, run_with_operation_report};
+use crate::page_cache::release_cached_pages;
+use crate::reflink::clone_or_copy;
+use crate::reporter::{
+    OperationPhase, ReportEvent, report, report_backup_kept, report_detail, report_phase,
+    report_problem,
+};
+use crate::verify::verify_byte_addition_operation;
+use crate::writability::check_target_writable;
+
+/*
+Mechanical Steps of Add Byte:
+For building the draft file when adding a byte at position N:
+- Step 2: Append pre-position bytes (0 to N-1) from original to draft
+- Step 3: Append the NEW byte to draft (do NOT advance original read position)
+- Step 4: Append remaining bytes (FROM position N to EOF) from original to draft
+So the original post-target-position-step position at step 4 is still at N,
+causing the byte that WAS(is) at N in the original to now be at N+1 in draft.
+
+Appending at end of file must be allowed.
+*/
+
+/// Performs a byte insertion operation on a file using a safe copy-and-replace strategy.
+///
+/// # Overview
+/// This function inserts a single byte at a specified position in a file, causing all
+/// subsequent bytes to shift forward by one position (frame-shift +1). It uses a defensive
+/// "build-new-file" approach rather than modifying the original file directly.
+///
+/// # Memory Safety
+/// - Uses one bucket-brigade buffer, allocated once per operation (64 KiB
+///   unless the active `OperationConfig` sets another size)
+/// - Never loads entire file into memory
+/// - Processes file chunk-by-chunk using bucket brigade pattern
+/// - No allocation inside the processing loop
+///
+/// # File Safety Strategy
+/// 1. Creates a backup copy of the original file (.backup extension)
+/// 2. Builds a new draft file (.draft extension) with the byte inserted
+/// 3. Verifies the operation succeeded (including frame-shift verification)
+/// 4. Atomically replaces original with draft
+/// 5. Removes backup only after successful completion
+///
+/// # Operation Behavior - Mechanical Steps
+/// The draft file is constructed by appending bytes sequentially:
+///
+/// **Step 1**: Create empty draft file
+///
+/// **Step 2**: Append pre-position bytes
+/// - Read from original: positions 0 to `byte_position - 1`
+/// - Append to draft: all these bytes
+///
+/// **Step 3**: Perform insertion AT position
+/// - Draft file: append the new byte
+/// - Original file: do NOT advance read position (stays at `byte_position`)
+/// - Effect: The new byte is written at `byte_position` in draft
+///
+/// **Step 4**: Append post-position bytes
+/// - Read from original: positions `byte_position` to EOF
+/// - Append to draft: all remaining bytes
+/// - Effect: These bytes naturally occupy positions starting at `byte_position + 1` in draft
+/// - This creates the +1 frame-shift automatically
+///
+/// # Frame-Shift Behavior
+/// After inserting byte at position N:
+/// - Bytes 0 to N-1: unchanged positions
+/// - Byte at N: the newly inserted byte
+/// - Bytes N to EOF in original: all shift forward by 1 position (become N+1 to EOF+1 in draft)
+/// - File length increases by exactly 1
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: Insertion point: a zero-indexed `u64` from the start, or
+///   `Offset::FromEnd(n)` (`FromEnd(0)` appends, `FromEnd(1)` goes before
+///   the last byte)
+/// - `new_byte_value`: The byte value to insert
+///
+/// # Position Semantics
+/// Position represents an insertion point (gap), not an existing byte:
+/// - Position 0: Insert before first byte
+/// - Position N: Insert between byte N-1 and byte N
+/// - Position file_size: Append after last byte (valid operation)
+///
+/// # Returns
+/// - `Ok(())` on successful byte insertion
+/// - `Err(io::Error)` if file operations fail or position is invalid
+///
+/// # Error Conditions
+/// - File does not exist
+/// - Byte position > file length (out of bounds)
+/// - Insufficient permissions
+/// - Disk full
+/// - I/O errors during read/write
+///
+/// # Recovery Behavior
+/// - If operation fails before replacing original, draft is removed, backup remains
+/// - If atomic rename fails, both original and backup are preserved
+/// - Orphaned .draft files indicate incomplete operations
+/// - Orphaned .backup files indicate failed replacements
+///
+/// # Edge Cases
+/// - Empty file at position 0: Results in single-byte file (valid operation)
+/// - Position 0: Inserts before first byte, all bytes shift forward
+/// - Position == file_size: Appends to end, no bytes shift (valid operation)
+/// - Position > file_size: Returns error (cannot insert beyond EOF)
+/// - Very large files: Processes in chunks, no memory issues
+///
+/// # Example
+/// ```no_run
+/// # use std::io;
+/// # use std::path::PathBuf;
+/// # fn add_single_byte_to_file(path: PathBuf, pos: u64, byte: u8) -> io::Result<()> { Ok(()) }
+/// // Original file: [0x41, 0x42, 0x43]
+/// let file_path = PathBuf::from("/absolute/path/to/file.dat");
+/// let position = 1; // Insert between 0x41 and 0x42
+/// let new_byte = 0xFF;
+/// let result = add_single_byte_to_file(file_path, position, new_byte);
+/// // Resulting file: [0x41, 0xFF, 0x42, 0x43]
+/// // Note: 0x42 and 0x43 shifted forward by 1 position
+/// assert!(result.is_ok());
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn add_single_byte_to_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+
+    report(&ReportEvent::OperationStarted {
+        operation_name: "Byte Insertion",
+        target_path: &original_file_path,
+    });
+    report_detail!("Insert position: {}", byte_offset);
+    report_detail!("New byte value: 0x{:02X}", new_byte_value);
+
+    // A symlinked target is resolved or refused before the path is used
+    let config = active_operation_config();
+    let original_file_path = resolve_symlinked_target(&original_file_path, &config)?;
+
+    // Verify file exists before any operations
+    if !original_file_path.exists() {
+        let error = ByteOpError::target_not_found(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Verify file is actually a file, not a directory
+    if !original_file_path.is_file() {
+        let error = ByteOpError::not_a_file(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Get original file metadata for validation
+    let original_metadata = fs::metadata(&original_file_path)?;
+    let original_file_size = original_metadata.len();
+
+    // Resolve an end-relative offset against the size just read
+    let byte_position_from_start = match byte_offset.resolve(original_file_size) {
+        Ok(position) => position,
+        Err(error) => {
+            report_problem!("ERROR: {}", error);
+            return Err(error);
+        }
+    };
+    if let Offset::FromEnd(_) = byte_offset {
+        report_detail!("Resolved position: {}", byte_position_from_start);
+    }
+
+    // Validate byte position is within valid insertion range
+    // Note: position == file_size is valid (append operation)
+    if byte_position_from_start > original_file_size {
+        let error = ByteOpError::PositionOutOfBounds {
+            position: byte_position_from_start,
+            file_size: original_file_size,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // =========================================
+    // Path Construction Phase
+    // =========================================
+
+    // Build backup and draft file paths
+    check_original_preconditions(&original_file_path, &config)?;
+    let artifact_paths = build_artifact_paths(&original_file_path)?;
+    check_target_writable(&original_file_path)?;
+    let _operation_lock = OperationLock::acquire(&original_file_path)?;
+    check_artifact_overwrite(&original_file_path, &artifact_paths, true)?;
+    let mut journal = OperationJournal::begin(
+        &original_file_path,
+        "Byte Insertion",
+        &artifact_paths,
+        true,
+        &config,
+    )?;
+    let DraftArtifactPaths {
+        backup_file_path,
+        draft_file_path,
+        ..
+    } = artifact_paths;
+
+    report_detail!("Backup path: {}", backup_file_path.display());
+    report_detail!("Draft path: {}", draft_file_path.display());
+
+    // =========================================
+    // Backup Creation Phase
+    // =========================================
+
+    report_phase(OperationPhase::Backup);
+
+    clone_or_copy(&original_file_path, &backup_file_path).map_err(|source| {
+        let error = ByteOpError::BackupFailed { source };
+        report_problem!("ERROR: {}", error);
+        io::Error::from(error)
+    })?;
+    record_backup_manifest(
+        &backup_file_path,
+        &original_file_path,
+        "Byte Insertion",
+        &config,
+    )?;
+
+    report_detail!("Backup created successfully");
+    journal.mark(JournalPhase::BackedUp)?;
+
+    // =========================================
+    // Draft File Construction Phase
+    // =========================================
+
+    report_phase(OperationPhase::Draft);
+    report_detail!(
+        "Building modified draft file (inserting byte at position {})...",
+        byte_position_from_start
+    );
+
+    // Open original for reading
+    let mut source_file = open_buffered_reader(&original_file_path)?;
+
+    // Create draft file for writing
+    let mut draft_file = create_buffered_draft(&draft_file_path)?;
+
+    // Buffer for bucket brigade operations, allocated once per operation.
+    // Only the first `bytes_read` bytes of it are ever written out, so it is
+    // not re-zeroed between chunks.
+    let bucket_brigade_buffer_size = config.buffer_size();
+    let mut bucket_brigade_buffer = vec![0u8; bucket_brigade_buffer_size];
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert!(
+        bucket_brigade_buffer_size > 0,
+        "Bucket brigade buffer must have non-zero size"
+    );
+
+    #[cfg(test)]
+    {
+        assert!(
+            bucket_brigade_buffer_size > 0,
+            "Bucket brigade buffer must have non-zero size"
+        );
+    }
+
+    if bucket_brigade_buffer_size == 0 {
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid buffer configuration",
+        ));
+    }
+
+    // Tracking variables
+    let mut total_bytes_read_from_original: u64 = 0;
+    let mut total_bytes_written_to_draft: u64 = 0;
+    let mut chunk_number: usize = 0;
+    let mut byte_was_inserted = false;
+
+    // Loop bound scaled to the file: see `chunk_limit_for`
+    let max_chunks_allowed = chunk_limit_for(original_file_size);
+
+    // =========================================
+    // Main Processing Loop
+    // =========================================
+
+    loop {
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        debug_assert!(
+            chunk_number < max_chunks_allowed,
+            "Exceeded maximum chunk limit"
+        );
+
+        #[cfg(test)]
+        {
+            assert!(
+                chunk_number < max_chunks_allowed,
+                "Exceeded maximum chunk limit"
+            );
+        }
+
+        if chunk_number >= max_chunks_allowed {
+            report_problem!("ERROR: Maximum chunk limit exceeded for safety");
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(ByteOpError::ChunkLimitExceeded {
+                chunk_limit: max_chunks_allowed,
+            }
+            .into());
+        }
+
+        chunk_number += 1;
+
+        // Check if we need to insert the byte before reading next chunk
+        if !byte_was_inserted && total_bytes_read_from_original == byte_position_from_start {
+            // We've reached the insertion position
+            // Insert the new byte BEFORE continuing to copy from original
+
+            report_detail!(
+                "Inserting byte at position {}: 0x{:02X}",
+                byte_position_from_start,
+                new_byte_value
+            );
+
+            let insert_buffer = [new_byte_value];
+            let bytes_written = draft_file.write(&insert_buffer)?;
+
+            // =================================================
+            // Debug-Assert, Test-Assert, Production-Catch-Handle
+            // =================================================
+
+            debug_assert_eq!(bytes_written, 1, "Failed to write inserted byte");
+
+            #[cfg(test)]
+            {
+                assert_eq!(bytes_written, 1, "Failed to write inserted byte");
+            }
+
+            if bytes_written != 1 {
+                report_problem!("ERROR: Failed to write inserted byte");
+                let _ = fs::remove_file(&draft_file_path);
+                return Err(ByteOpError::IncompleteWrite {
+                    requested: 1,
+                    written: bytes_written,
+                }
+                .into());
+            }
+
+            total_bytes_written_to_draft += bytes_written as u64;
+            byte_was_inserted = true;
+
+            // Continue to read and copy remaining bytes from original
+        }
+
+        // Read next chunk from source
+        let bytes_read = source_file.read(&mut bucket_brigade_buffer)?;
+
+        // EOF detection
+        if bytes_read == 0 {
+            report_detail!("Reached end of original file");
+
+            // Handle edge case: inserting at EOF (appending)
+            if !byte_was_inserted {
+                report_detail!(
+                    "Appending byte at EOF (position {}): 0x{:02X}",
+                    byte_position_from_start,
+                    new_byte_value
+                );
+
+                let insert_buffer = [new_byte_value];
+                let bytes_written = draft_file.write(&insert_buffer)?;
+
+                if bytes_written != 1 {
+                    report_problem!("ERROR: Failed to append byte at EOF");
+                    let _ = fs::remove_file(&draft_file_path);
+                    return Err(ByteOpError::IncompleteWrite {
+                        requested: 1,
+                        written: bytes_written,
+                    }
+                    .into());
+                }
+
+                total_bytes_written_to_draft += bytes_written as u64;
+                byte_was_inserted = true;
+            }
+
+            break;
+        }
+        report(&ReportEvent::ChunkProcessed {
+            chunk_number,
+            byte_count: bytes_read,
+        });
+
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        debug_assert!(
+            bytes_read <= bucket_brigade_buffer_size,
+            "Read more bytes than buffer size"
+        );
+
+        #[cfg(test)]
+        {
+            assert!(
+                bytes_read <= bucket_brigade_buffer_size,
+                "Read more bytes than buffer size"
+            );
+        }
+
+        if bytes_read > bucket_brigade_buffer_size {
+            report_problem!("ERROR: Buffer overflow detected");
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(io::Error::other("Buffer overflow in read operation"));
+        }
+
+        // Determine if insertion point is in this chunk
+        let chunk_start_position = total_bytes_read_from_original;
+        let chunk_end_position = chunk_start_position + bytes_read as u64;
+
+        // Check if we need to insert a byte within this chunk
+        if !byte_was_inserted
+            && byte_position_from_start >= chunk_start_position
+            && byte_position_from_start < chunk_end_position
+        {
+            // Calculate position within this chunk (below `bytes_read`, so it fits)
+            let position_in_chunk = (byte_position_from_start - chunk_start_position) as usize;
+
+            report_detail!(
+                "Inserting byte at position {}: 0x{:02X}",
+                byte_position_from_start,
+                new_byte_value
+            );
+
+            // Write bytes BEFORE the insertion position in this chunk
+            if position_in_chunk > 0 {
+                let bytes_before = &bucket_brigade_buffer[..position_in_chunk];
+                let bytes_written_before = draft_file.write(bytes_before)?;
+
+                // =================================================
+                // Debug-Assert, Test-Assert, Production-Catch-Handle
+                // =================================================
+
+                debug_assert_eq!(
+                    bytes_written_before, position_in_chunk,
+                    "Not all pre-insertion bytes were written"
+                );
+
+                #[cfg(test)]
+                {
+                    assert_eq!(
+                        bytes_written_before, position_in_chunk,
+                        "Not all pre-insertion bytes were written"
+                    );
+                }
+
+                if bytes_written_before != position_in_chunk {
+                    report_problem!("ERROR: Incomplete write before insertion position");
+                    let _ = fs::remove_file(&draft_file_path);
+                    return Err(ByteOpError::IncompleteWrite {
+                        requested: position_in_chunk,
+                        written: bytes_written_before,
+                    }
+                    .into());
+                }
+
+                total_bytes_written_to_draft += bytes_written_before as u64;
+            }
+
+            // INSERT the new byte
+            let insert_buffer = [new_byte_value];
+            let bytes_written_insert = draft_file.write(&insert_buffer)?;
+
+            if bytes_written_insert != 1 {
+                report_problem!("ERROR: Failed to write inserted byte");
+                let _ = fs::remove_file(&draft_file_path);
+                return Err(ByteOpError::IncompleteWrite {
+                    requested: 1,
+                    written: bytes_written_insert,
+                }
+                .into());
+            }
+
+            total_bytes_written_to_draft += bytes_written_insert as u64;
+            byte_was_inserted = true;
+
+            // Write bytes FROM the insertion position onward (these shift forward by 1)
+            let bytes_from_position = &bucket_brigade_buffer[position_in_chunk..bytes_read];
+            let bytes_written_after = draft_file.write(bytes_from_position)?;
+
+            let expected_bytes_after = bytes_read - position_in_chunk;
+
+            // =================================================
+            // Debug-Assert, Test-Assert, Production-Catch-Handle
+            // =================================================
+
+            debug_assert_eq!(
+                bytes_written_after, expected_bytes_after,
+                "Not all post-insertion bytes were written"
+            );
+
+            #[cfg(test)]
+            {
+                assert_eq!(
+                    bytes_written_after, expected_bytes_after,
+                    "Not all post-insertion bytes were written"
+                );
+            }
+
+            if bytes_written_after != expected_bytes_after {
+                report_problem!("ERROR: Incomplete write after insertion position");
+                let _ = fs::remove_file(&draft_file_path);
+                return Err(ByteOpError::IncompleteWrite {
+                    requested: expected_bytes_after,
+                    written: bytes_written_after,
+                }
+                .into());
+            }
+
+            total_bytes_written_to_draft += bytes_written_after as u64;
+        } else {
+            // This chunk does not contain the insertion position
+            // Write entire chunk to draft file
+            let bytes_written = draft_file.write(&bucket_brigade_buffer[..bytes_read])?;
+
+            // =================================================
+            // Debug-Assert, Test-Assert, Production-Catch-Handle
+            // =================================================
+
+            debug_assert_eq!(bytes_written, bytes_read, "Not all bytes were written");
+
+            #[cfg(test)]
+            {
+                assert_eq!(bytes_written, bytes_read, "Not all bytes were written");
+            }
+
+            if bytes_written != bytes_read {
+                report_problem!(
+                    "ERROR: Write mismatch - expected {} bytes, wrote {} bytes",
+                    bytes_read,
+                    bytes_written
+                );
+                let _ = fs::remove_file(&draft_file_path);
+                return Err(ByteOpError::IncompleteWrite {
+                    requested: bytes_read,
+                    written: bytes_written,
+                }
+                .into());
+            }
+
+            total_bytes_written_to_draft += bytes_written as u64;
+        }
+
+        total_bytes_read_from_original += bytes_read as u64;
+    }
+
+    // =========================================
+    // Basic Verification Phase
+    // =========================================
+
+    report_phase(OperationPhase::Verify);
+
+    // Verify byte was actually inserted
+    if !byte_was_inserted {
+        report_problem!("ERROR: Byte insertion did not occur");
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(ByteOpError::EditNotApplied {
+            operation_name: "Byte insertion",
+        }
+        .into());
+    }
+
+    // Verify draft file is exactly 1 byte larger
+    config.durability_mode().finish_draft(draft_file)?;
+    drop(source_file);
+
+    let draft_metadata = fs::metadata(&draft_file_path)?;
+    let draft_size = draft_metadata.len();
+    let expected_draft_size = original_file_size + 1;
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert_eq!(draft_size, expected_draft_size, "Draft file size incorrect");
+
+    #[cfg(test)]
+    {
+        assert_eq!(draft_size, expected_draft_size, "Draft file size incorrect");
+    }
+
+    if draft_size != expected_draft_size {
+        report_problem!(
+            "ERROR: File size mismatch - original: {} bytes, draft: {} bytes, expected: {} bytes",
+            original_file_size,
+            draft_size,
+            expected_draft_size
+        );
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            expected_draft_size,
+            draft_size,
+        )
+        .into());
+    }
+
+    report_detail!(
+        "Basic verification passed: original={} bytes, draft={} bytes (+1 byte)",
+        original_file_size,
+        draft_size
+    );
+
+    // =========================================
+    // Comprehensive Verification Phase
+    // =========================================
+
+    // Perform all verification checks before replacing the original
+    if config.verification_level() == VerificationLevel::LengthOnly {
+        report_detail!("Verification level length-only: byte comparison skipped");
+    } else {
+        verify_byte_addition_operation(
+            &original_file_path,
+            &draft_file_path,
+            byte_position_from_start,
+            new_byte_value,
+        )?;
+    }
+
+    check_draft_against_utf8_guard(&original_file_path, &draft_file_path, "Byte Insertion")?;
+    seal_draft_with_canary(&original_file_path, &draft_file_path)?;
+    let draft_size = fs::metadata(&draft_file_path)?.len();
+    journal.mark_draft_verified(&draft_file_path)?;
+
+    // =========================================
+    // Atomic Replacement Phase
+    // =========================================
+
+    report_phase(OperationPhase::Replace);
+    release_cached_pages(&[&original_file_path, &backup_file_path, &draft_file_path]);
+    copy_original_metadata(&original_file_path, &draft_file_path, &config)?;
+
+    // Attempt atomic rename
+    match replace_with_draft(&draft_file_path, &original_file_path) {
+        Ok(()) => {
+            report_detail!("Original file successfully replaced");
+            config
+                .durability_mode()
+                .finish_replace(&original_file_path)?;
+            journal.mark_replaced();
+        }
+        Err(source) => {
+            let error = ByteOpError::AtomicReplaceFailed { source };
+            report_problem!("{}", error);
+            return Err(error.into());
+        }
+    }
+
+    // =========================================
+    // Cleanup Phase
+    // =========================================
+
+    report_phase(OperationPhase::Cleanup);
+
+    if let Some(rotated_backup_count) = config.rotated_backup_count() {
+        rotate_backups(&backup_file_path, rotated_backup_count);
+    } else if config.keep_backup() {
+        report_backup_kept(&backup_file_path);
+    } else {
+        match fs::remove_file(&backup_file_path) {
+            Ok(()) => {
+                report_detail!("Backup file removed");
+            }
+            Err(e) => {
+                report_problem!(
+                    "WARNING: Could not remove backup file: {} ({})",
+                    backup_file_path.display(),
+                    e
+                );
+                report_backup_kept(&backup_file_path);
+            }
+        }
+    }
+
+    // =========================================
+    // Operation Summary
+    // =========================================
+
+    report_detail!("Inserted byte at position: {}", byte_position_from_start);
+    report_detail!("Inserted byte value: 0x{:02X}", new_byte_value);
+    report_detail!("Original size: {} bytes", original_file_size);
+    report_detail!("New size: {} bytes", draft_size);
+    report_detail!(
+        "Bytes read from original: {}",
+        total_bytes_read_from_original
+    );
+    report_detail!("Bytes written to draft: {}", total_bytes_written_to_draft);
+    report_detail!("Total chunks: {}", chunk_number);
+    report(&ReportEvent::OperationFinished {
+        operation_name: "Byte Insertion",
+        target_path: &original_file_path,
+    });
+
+    Ok(())
+}
+
+/// Inserts a single byte at a position in a file (frame-shift +1).
+///
+/// # Overview
+/// This is the "insert" name for the Add-a-byte-operation described in the module
+/// notes. The draft file is built with the same backup/draft/bucket-brigade/verify
+/// workflow as `replace_single_byte_in_file` and `remove_single_byte_from_file`,
+/// by way of `add_single_byte_to_file`:
+/// - Pre-position bytes are appended to the draft unchanged
+/// - The new byte is appended (original read position is NOT advanced)
+/// - Bytes from the position to EOF are appended, effecting the +1 frame-shift
+/// - `verify_byte_addition_operation` checks total length (+1), pre-position
+///   similarity, the inserted byte, and post-position similarity with +1 shift
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: Insertion point (gap before this byte), from the start
+///   or as `Offset::FromEnd(n)`; see `add_single_byte_to_file`
+/// - `new_byte_value`: The byte value to insert
+///
+/// # Position Semantics
+/// - Position 0: Insert before first byte
+/// - Position N: Insert between byte N-1 and byte N
+/// - Position file_size: Append after last byte (valid operation)
+///
+/// Unlike replace/remove (whose target is an existing byte, so
+/// `position >= file_size` is rejected), insertion targets a gap, so the
+/// valid range is `0..=file_size`.
+///
+/// # Edge Cases
+/// - Empty file at position 0: Results in a single-byte file
+/// - Position == file_size: Appends; verification finds no post-position bytes
+///   and checks only length (+1), pre-position bytes, and the appended byte
+/// - Position == file_size where file_size is a multiple of the buffer size:
+///   the byte is written after the final full chunk, before EOF is detected
+/// - Position > file_size: Returns error (cannot insert beyond EOF)
+///
+/// # Returns
+/// - `Ok(())` on successful byte insertion
+/// - `Err(io::Error)` if file operations fail or position is invalid
+///
+/// # Example
+/// ```no_run
+/// # use std::io;
+/// # use std::path::PathBuf;
+/// # fn insert_single_byte_into_file(path: PathBuf, pos: u64, byte: u8) -> io::Result<()> { Ok(()) }
+/// // Original file: [0x41, 0x42, 0x43]
+/// let file_path = PathBuf::from("/absolute/path/to/file.dat");
+/// let result = insert_single_byte_into_file(file_path, 1, 0xFF);
+/// // Resulting file: [0x41, 0xFF, 0x42, 0x43]
+/// assert!(result.is_ok());
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn insert_single_byte_into_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+) -> io::Result<()> {
+    add_single_byte_to_file(original_file_path, byte_offset, new_byte_value)
+}
+
+/// Inserts a byte like `insert_single_byte_into_file`, returning an `OperationReport`.
+///
+/// Adds two SHA-256 passes (before and after) to the operation; see
+/// `operation_report.rs`.
+pub fn insert_single_byte_into_file_with_report(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+) -> io::Result<OperationReport> {
+    let byte_offset: Offset = byte_offset.into();
+    run_with_operation_report(
+        "Byte Insertion",
+        &original_file_path,
+        byte_offset,
+        false,
+        Some(new_byte_value),
+        || insert_single_byte_into_file(original_file_path.clone(), byte_offset, new_byte_value),
+    )
+}
+
+/// Inserts a byte like `insert_single_byte_into_file`, using `config` instead of the
+/// active (or default) `OperationConfig`.
+pub fn insert_single_byte_into_file_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+    with_operation_config(config, || {
+        insert_single_byte_into_file(original_file_path, byte_offset, new_byte_value)
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_single_byte_basic() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_byte_add.bin");
+
+        // Create test file: [0x00, 0x11, 0x22, 0x33]
+        let test_data = vec![0x00, 0x11, 0x22, 0x33];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Insert byte 0xFF at position 2 (between 0x11 and 0x22)
+        let result = add_single_byte_to_file(test_file.clone(), 2, 0xFF);
+
+        assert!(result.is_ok(), "Operation should succeed");
+
+        // Verify result: [0x00, 0x11, 0xFF, 0x22, 0x33]
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0x00, 0x11, 0xFF, 0x22, 0x33]);
+
+        // Cleanup
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_add_byte_at_start() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_add_start.bin");
+
+        let test_data = vec![0xAA, 0xBB, 0xCC];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Insert at position 0 (before first byte)
+        let result = add_single_byte_to_file(test_file.clone(), 0, 0xFF);
+
+        assert!(result.is_ok());
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0xFF, 0xAA, 0xBB, 0xCC]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_add_byte_at_end() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_add_end.bin");
+
+        let test_data = vec![0xAA, 0xBB, 0xCC];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Insert at position 3 (append after last byte)
+        let result = add_single_byte_to_file(test_file.clone(), 3, 0xFF);
+
+        assert!(result.is_ok());
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0xAA, 0xBB, 0xCC, 0xFF]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_add_to_empty_file() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_add_empty.bin");
+
+        // Create empty file
+        std::fs::write(&test_file, Vec::<u8>::new()).expect("Failed to create empty file");
+
+        // Insert at position 0
+        let result = add_single_byte_to_file(test_file.clone(), 0, 0x42);
+
+        assert!(result.is_ok());
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0x42]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_add_byte_out_of_bounds() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_add_bounds.bin");
+
+        std::fs::write(&test_file, vec![0x00, 0x11]).expect("Failed to create test file");
+
+        // Try to insert beyond EOF (position 10 when file has only 2 bytes)
+        let result = add_single_byte_to_file(test_file.clone(), 10, 0xFF);
+
+        assert!(result.is_err(), "Should fail with out of bounds position");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_single_byte_basic() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_byte_insert.bin");
+
+        std::fs::write(&test_file, vec![0x41, 0x42, 0x43]).expect("Failed to create test file");
+
+        let result = insert_single_byte_into_file(test_file.clone(), 1, 0xFF);
+
+        assert!(result.is_ok(), "Operation should succeed");
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0x41, 0xFF, 0x42, 0x43]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_byte_across_chunk_boundary() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_insert_chunk_boundary.bin");
+
+        // 150 bytes spans three 64-byte bucket-brigade chunks
+        let test_data: Vec<u8> = (0..150u8).collect();
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Insert exactly at the start of the second chunk
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("valid config");
+        let result =
+            insert_single_byte_into_file_with_config(test_file.clone(), 64, 0xEE, &tiny_config);
+
+        assert!(result.is_ok(), "Operation should succeed");
+
+        let mut expected_data = test_data.clone();
+        expected_data.insert(64, 0xEE);
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, expected_data);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_byte_at_eof_of_chunk_aligned_file() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_insert_eof_aligned.bin");
+
+        // Exactly two full 64-byte chunks: EOF is found on a separate, empty read
+        let test_data: Vec<u8> = (0..128u8).collect();
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Position == file size means append
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("valid config");
+        let result =
+            insert_single_byte_into_file_with_config(test_file.clone(), 128, 0xAB, &tiny_config);
+
+        assert!(result.is_ok(), "Append at EOF should succeed");
+
+        let mut expected_data = test_data.clone();
+        expected_data.push(0xAB);
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, expected_data);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_byte_one_past_eof_rejected() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_insert_past_eof.bin");
+
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("Failed to create test file");
+
+        // file_size + 1 is the first invalid insertion point
+        let result = insert_single_byte_into_file(test_file.clone(), 4, 0xFF);
+
+        assert!(result.is_err(), "Should fail beyond EOF");
+        let unchanged_data = std::fs::read(&test_file).expect("Failed to read file");
+        assert_eq!(unchanged_data, vec![0x00, 0x11, 0x22]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
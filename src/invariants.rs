@@ -0,0 +1,300 @@
+//! Machine-verifiable invariants of the current build
+//!
+//! Audits need to capture what a given version of the tool promises, not
+//! what the README says it promises. Every value here is read from the
+//! constants and `cfg` branches that the operations themselves use, so the
+//! listing cannot drift from the code paths.
+//!
+//! `bfbo invariants` prints the listing as `key=value` lines, or as a single
+//! JSON object with `--json`.
+
+use crate::backup_manifest::BACKUP_MANIFEST_SUFFIX;
+use crate::draft_workflow::{
+    BACKUP_FILE_SUFFIX, CHUNK_LIMIT_ITERATIONS_PER_CHUNK, CHUNK_LIMIT_SLACK,
+    DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE, DEFAULT_DURABILITY_MODE, DEFAULT_VERIFICATION_LEVEL,
+    DRAFT_FILE_SUFFIX, JOURNAL_FILE_SUFFIX,
+};
+use crate::hash::DEFAULT_VERIFICATION_HASH;
+use crate::host_policy::{NO_WRITE_ENVIRONMENT_VARIABLE, NO_WRITE_MARKER_FILE_PATH};
+use crate::lock_file::LOCK_FILE_SUFFIX;
+use crate::operation_config::{
+    DEFAULT_BACKUP_NAMING, DEFAULT_OVERWRITE_POLICY, DEFAULT_PAGE_CACHE_MODE,
+    DEFAULT_SYMLINK_POLICY, OperationConfig,
+};
+
+/// How the finished draft replaces the original on this platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomicityClass {
+    /// `rename(2)` atomically replaces the target (same filesystem):
+    /// readers see either the old or the new file, never a mix
+    AtomicRename,
+    /// `MoveFileExW(MOVEFILE_REPLACE_EXISTING)`: replacement is not
+    /// documented as atomic; a crash may leave the target missing
+    ReplaceNotGuaranteedAtomic,
+    /// Platform semantics of `fs::rename` are not known to this build
+    Unknown,
+}
+
+impl AtomicityClass {
+    /// Stable name used in the listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AtomicityClass::AtomicRename => "atomic-rename",
+            AtomicityClass::ReplaceNotGuaranteedAtomic => "replace-not-guaranteed-atomic",
+            AtomicityClass::Unknown => "unknown",
+        }
+    }
+}
+
+/// How an operation changes its target; the listing describes each mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Verified draft renamed over the original, with a backup: every
+    /// operation except `write_bytes_directly`
+    DraftAndRename,
+    /// Bytes overwritten where they are, under a journal of the old bytes
+    /// instead of a backup (`write_bytes_directly`)
+    JournaledDirectWrite,
+}
+
+impl WriteMode {
+    /// Stable name used in the listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WriteMode::DraftAndRename => "draft-and-rename",
+            WriteMode::JournaledDirectWrite => "journaled-direct-write",
+        }
+    }
+
+    /// Whether the original's bytes are changed where they are (rather than
+    /// the original being replaced by a finished draft).
+    pub fn modifies_original_in_place(&self) -> bool {
+        match self {
+            WriteMode::DraftAndRename => false,
+            WriteMode::JournaledDirectWrite => true,
+        }
+    }
+
+    /// Whether a backup copy of the original is left on disk when the
+    /// operation fails. A failed direct write restores the old bytes from
+    /// its journal instead.
+    pub fn retains_backup_on_failure(&self) -> bool {
+        match self {
+            WriteMode::DraftAndRename => true,
+            WriteMode::JournaledDirectWrite => false,
+        }
+    }
+}
+
+/// Atomicity class of the rename step for the platform this was built for.
+pub fn replacement_atomicity_class() -> AtomicityClass {
+    if cfg!(unix) {
+        AtomicityClass::AtomicRename
+    } else if cfg!(windows) {
+        AtomicityClass::ReplaceNotGuaranteedAtomic
+    } else {
+        AtomicityClass::Unknown
+    }
+}
+
+/// Ordered `(key, value)` pairs describing the guarantees of this build.
+///
+/// Keys are stable across versions; new keys are only ever appended. The
+/// unprefixed `original_modified_in_place` and `backup_retained_on_failure`
+/// describe `WriteMode::DraftAndRename`; the `direct_write_` keys describe
+/// `WriteMode::JournaledDirectWrite`.
+pub fn build_invariants() -> Vec<(&'static str, String)> {
+    vec![
+        ("tool_version", env!("CARGO_PKG_VERSION").to_string()),
+        ("target_os", std::env::consts::OS.to_string()),
+        (
+            "atomicity_class",
+            replacement_atomicity_class().name().to_string(),
+        ),
+        (
+            "durability_mode_default",
+            DEFAULT_DURABILITY_MODE.name().to_string(),
+        ),
+        (
+            "verification_level_default",
+            DEFAULT_VERIFICATION_LEVEL.name().to_string(),
+        ),
+        ("backup_file_suffix", BACKUP_FILE_SUFFIX.to_string()),
+        ("draft_file_suffix", DRAFT_FILE_SUFFIX.to_string()),
+        (
+            "original_modified_in_place",
+            WriteMode::DraftAndRename
+                .modifies_original_in_place()
+                .to_string(),
+        ),
+        (
+            "backup_retained_on_failure",
+            WriteMode::DraftAndRename
+                .retains_backup_on_failure()
+                .to_string(),
+        ),
+        (
+            "bucket_brigade_buffer_bytes",
+            DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE.to_string(),
+        ),
+        (
+            "max_chunks_per_pass",
+            format!(
+                "{}*ceil(pass_bytes/buffer_bytes)+{}",
+                CHUNK_LIMIT_ITERATIONS_PER_CHUNK, CHUNK_LIMIT_SLACK
+            ),
+        ),
+        (
+            "no_write_environment_variable",
+            NO_WRITE_ENVIRONMENT_VARIABLE.to_string(),
+        ),
+        (
+            "no_write_marker_file",
+            NO_WRITE_MARKER_FILE_PATH.to_string(),
+        ),
+        (
+            "overwrite_policy_default",
+            DEFAULT_OVERWRITE_POLICY.name().to_string(),
+        ),
+        ("journal_file_suffix", JOURNAL_FILE_SUFFIX.to_string()),
+        ("backup_manifest_suffix", BACKUP_MANIFEST_SUFFIX.to_string()),
+        ("lock_file_suffix", LOCK_FILE_SUFFIX.to_string()),
+        (
+            "backup_naming_default",
+            DEFAULT_BACKUP_NAMING.name().to_string(),
+        ),
+        (
+            "symlink_policy_default",
+            DEFAULT_SYMLINK_POLICY.name().to_string(),
+        ),
+        (
+            "page_cache_mode_default",
+            DEFAULT_PAGE_CACHE_MODE.name().to_string(),
+        ),
+        (
+            "verification_hash_default",
+            DEFAULT_VERIFICATION_HASH.name().to_string(),
+        ),
+        (
+            "write_modes",
+            [WriteMode::DraftAndRename, WriteMode::JournaledDirectWrite]
+                .map(|write_mode| write_mode.name())
+                .join(","),
+        ),
+        (
+            "direct_write_original_modified_in_place",
+            WriteMode::JournaledDirectWrite
+                .modifies_original_in_place()
+                .to_string(),
+        ),
+        (
+            "direct_write_backup_retained_on_failure",
+            WriteMode::JournaledDirectWrite
+                .retains_backup_on_failure()
+                .to_string(),
+        ),
+        (
+            "utf8_guard_default",
+            OperationConfig::default().utf8_guard().to_string(),
+        ),
+        (
+            "memory_budget_default",
+            match OperationConfig::default().memory_budget() {
+                Some(memory_budget) => memory_budget.limit_bytes.to_string(),
+                None => "none".to_string(),
+            },
+        ),
+    ]
+}
+
+/// Renders the invariants as `key=value` lines.
+pub fn format_invariants_as_lines(invariants: &[(&'static str, String)]) -> String {
+    let mut output = String::new();
+    for (key, value) in invariants {
+        output.push_str(key);
+        output.push('=');
+        output.push_str(value);
+        output.push('\n');
+    }
+    output
+}
+
+/// Renders the invariants as one JSON object (all values are strings).
+pub fn format_invariants_as_json(invariants: &[(&'static str, String)]) -> String {
+    let mut output = String::from("{");
+    for (index, (key, value)) in invariants.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        output.push_str(&format!(
+            "\"{}\":\"{}\"",
+            escape_json_string(key),
+            escape_json_string(value)
+        ));
+    }
+    output.push('}');
+    output
+}
+
+/// Escapes a string for inclusion in a JSON string literal.
+pub(crate) fn escape_json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if (control as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", control as u32))
+            }
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invariants_reflect_code_constants() {
+        let invariants = build_invariants();
+        let lookup = |key: &str| {
+            invariants
+                .iter()
+                .find(|(candidate, _)| *candidate == key)
+                .map(|(_, value)| value.clone())
+                .expect("key should be present")
+        };
+
+        assert_eq!(lookup("backup_file_suffix"), ".backup");
+        assert_eq!(lookup("draft_file_suffix"), ".draft");
+        assert_eq!(lookup("durability_mode_default"), "flush-only");
+        assert_eq!(lookup("verification_level_default"), "full-comparison");
+        assert_eq!(lookup("original_modified_in_place"), "false");
+        assert_eq!(lookup("backup_retained_on_failure"), "true");
+        assert_eq!(lookup("direct_write_original_modified_in_place"), "true");
+        assert_eq!(lookup("direct_write_backup_retained_on_failure"), "false");
+        assert_eq!(lookup("utf8_guard_default"), "false");
+        assert_eq!(lookup("memory_budget_default"), "none");
+        #[cfg(unix)]
+        assert_eq!(lookup("atomicity_class"), "atomic-rename");
+    }
+
+    #[test]
+    fn test_invariants_json_rendering() {
+        let invariants = vec![("a", "x\"y".to_string()), ("b", "1".to_string())];
+        assert_eq!(
+            format_invariants_as_json(&invariants),
+            "{\"a\":\"x\\\"y\",\"b\":\"1\"}"
+        );
+        assert_eq!(format_invariants_as_lines(&invariants), "a=x\"y\nb=1\n");
+    }
+}
@@ -0,0 +1,838 @@
+//! Crash-recovery journal (write-ahead log) for the draft workflow
+//!
+//! With `OperationConfigBuilder::journal(true)`, every operation writes
+//! `<name>.journal` next to the target before it touches anything. The
+//! journal records the operation, the absolute target/backup/draft paths,
+//! and the original's size and SHA-256, then gets one phase marker per
+//! step:
+//!
+//! ```text
+//! bfbo-journal 1
+//! operation: Byte Removal
+//! target: /data/f.bin
+//! backup: /data/f.bin.backup
+//! draft: /data/f.bin.draft
+//! keep_backup: false
+//! original_size: 4096
+//! original_sha256: 9f86d0...
+//! phase: started
+//! phase: backed-up
+//! draft_sha256: 60303a...
+//! phase: draft-verified
+//! phase: replaced
+//! ```
+//!
+//! The first block appears atomically (written to a temporary file, synced,
+//! renamed into place); each marker is synced before the step it announces
+//! counts as done. An operation that returns, whether it succeeded or
+//! failed, removes its journal, so only a crash leaves one behind. While a
+//! journal is present, every operation on that target is refused with
+//! `ByteOpError::PendingJournal`.
+//!
+//! `recover` reads a leftover journal, hashes what is on disk, and picks
+//! one action from the journal alone (see `RecoveryAction`). It either
+//! completes the operation from its verified draft or returns the target
+//! to its original content. If neither is possible it changes nothing and
+//! keeps the journal. Like any operation, it holds the target's lock while
+//! it works.
+//!
+//! `format_journal_as_json` renders a journal read back with `read_journal`
+//! for tooling; its schema is `bfbo schema journal`.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::backup_manifest::remove_backup_manifest;
+use crate::direct_write::DIRECT_WRITE_JOURNAL_HEADER;
+use crate::draft_workflow::{DraftArtifactPaths, build_artifact_paths, replace_with_draft};
+use crate::hash::sha256::{digest_to_hex, hex_to_digest, sha256_of_file};
+use crate::json_schema::{JsonField, JsonKind, JsonObject, JsonValue, render_json_object};
+use crate::lock_file::OperationLock;
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::reporter::{report_detail, report_problem};
+
+/// First line of every journal; the number is the format version
+const JOURNAL_HEADER: &str = "bfbo-journal 1";
+
+/// Suffix of the temporary file the first journal block is written to
+const JOURNAL_TEMPORARY_SUFFIX: &str = ".tmp";
+
+/// The last step an interrupted operation recorded as done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JournalPhase {
+    /// Journal written; the backup may be missing or incomplete
+    Started,
+    /// Backup complete; the draft may be missing or incomplete
+    BackedUp,
+    /// Draft complete and verified, with its SHA-256 recorded; the rename
+    /// may or may not have happened
+    DraftVerified,
+    /// Draft renamed over the target; only backup cleanup was left
+    Replaced,
+}
+
+impl JournalPhase {
+    /// Every phase, in the order an operation records them.
+    pub const ALL: [JournalPhase; 4] = [
+        JournalPhase::Started,
+        JournalPhase::BackedUp,
+        JournalPhase::DraftVerified,
+        JournalPhase::Replaced,
+    ];
+
+    /// Stable name used in the journal file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            JournalPhase::Started => "started",
+            JournalPhase::BackedUp => "backed-up",
+            JournalPhase::DraftVerified => "draft-verified",
+            JournalPhase::Replaced => "replaced",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        JournalPhase::ALL
+            .into_iter()
+            .find(|phase| phase.name() == name)
+    }
+}
+
+/// A journal as read back from disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub operation_name: String,
+    pub target_path: PathBuf,
+    /// `None` when the operation wrote no backup (inside a `FileSession`)
+    pub backup_file_path: Option<PathBuf>,
+    pub draft_file_path: PathBuf,
+    pub keep_backup: bool,
+    pub original_file_size: u64,
+    pub original_sha256: [u8; 32],
+    /// Recorded with `JournalPhase::DraftVerified`
+    pub draft_sha256: Option<[u8; 32]>,
+    /// Last phase marker that was completely written
+    pub phase: JournalPhase,
+}
+
+impl JsonObject for JournalEntry {
+    const JSON_FIELDS: &'static [JsonField<Self>] = &[
+        JsonField {
+            name: "operation",
+            kind: JsonKind::String,
+            value: |journal| JsonValue::String(journal.operation_name.clone()),
+        },
+        JsonField {
+            name: "target",
+            kind: JsonKind::String,
+            value: |journal| JsonValue::String(journal.target_path.to_string_lossy().into_owned()),
+        },
+        JsonField {
+            name: "backup",
+            kind: JsonKind::Nullable(&JsonKind::String),
+            value: |journal| {
+                JsonValue::optional(journal.backup_file_path.as_ref(), |backup_path| {
+                    JsonValue::String(backup_path.to_string_lossy().into_owned())
+                })
+            },
+        },
+        JsonField {
+            name: "draft",
+            kind: JsonKind::String,
+            value: |journal| {
+                JsonValue::String(journal.draft_file_path.to_string_lossy().into_owned())
+            },
+        },
+        JsonField {
+            name: "keep_backup",
+            kind: JsonKind::Boolean,
+            value: |journal| JsonValue::Boolean(journal.keep_backup),
+        },
+        JsonField {
+            name: "original_size",
+            kind: JsonKind::Integer,
+            value: |journal| JsonValue::Integer(journal.original_file_size),
+        },
+        JsonField {
+            name: "original_sha256",
+            kind: JsonKind::Sha256Hex,
+            value: |journal| JsonValue::String(digest_to_hex(&journal.original_sha256)),
+        },
+        JsonField {
+            name: "draft_sha256",
+            kind: JsonKind::Nullable(&JsonKind::Sha256Hex),
+            value: |journal| {
+                JsonValue::optional(journal.draft_sha256, |digest| {
+                    JsonValue::String(digest_to_hex(&digest))
+                })
+            },
+        },
+        JsonField {
+            name: "phase",
+            kind: JsonKind::Name(|| JournalPhase::ALL.iter().map(|phase| phase.name()).collect()),
+            value: |journal| JsonValue::String(journal.phase.name().to_string()),
+        },
+    ];
+}
+
+/// Renders a journal as one JSON object (schema: `bfbo schema journal`),
+/// with the keys of the journal file and `null` for absent values.
+pub fn format_journal_as_json(journal: &JournalEntry) -> String {
+    render_json_object(journal)
+}
+
+/// What `recover` does for a leftover journal.
+///
+/// The first one that applies is chosen:
+/// 1. `CommitDraft`: the journal reached `DraftVerified`, the draft is
+///    still there with its recorded SHA-256, and the target is unchanged
+/// 2. `FinishCleanup`: the journal reached `DraftVerified` or `Replaced`
+///    and the target already has the draft's SHA-256 (the rename happened)
+/// 3. `RollBack`: the target still has its original SHA-256
+/// 4. `RestoreBackup`: the target does not, but the backup does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Rename the verified draft over the target, then clean up the backup
+    CommitDraft,
+    /// Remove the backup (unless the operation kept it)
+    FinishCleanup,
+    /// Remove the draft and any incomplete backup; a complete backup is
+    /// kept, as after a failed operation
+    RollBack,
+    /// Rename the backup over the target and remove the draft
+    RestoreBackup,
+}
+
+impl RecoveryAction {
+    /// Stable name used in CLI output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecoveryAction::CommitDraft => "commit-draft",
+            RecoveryAction::FinishCleanup => "finish-cleanup",
+            RecoveryAction::RollBack => "roll-back",
+            RecoveryAction::RestoreBackup => "restore-backup",
+        }
+    }
+}
+
+/// A leftover journal and the action chosen for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPlan {
+    pub journal: JournalEntry,
+    pub action: RecoveryAction,
+}
+
+// =========================================
+// Writing
+// =========================================
+
+/// The journal of one running operation.
+///
+/// Inert (writes nothing) unless the config enables journaling. Dropping
+/// it removes the journal file.
+#[derive(Debug)]
+pub(crate) struct OperationJournal {
+    journal_file_path: Option<PathBuf>,
+}
+
+impl OperationJournal {
+    /// Writes the journal's first block with the `started` marker.
+    ///
+    /// Call after the overwrite checks and before the backup is created.
+    /// `backup_written` is false when the operation writes no backup.
+    pub(crate) fn begin(
+        original_file_path: &Path,
+        operation_name: &str,
+        artifact_paths: &DraftArtifactPaths,
+        backup_written: bool,
+        config: &OperationConfig,
+    ) -> io::Result<Self> {
+        if !config.journal() {
+            return Ok(OperationJournal {
+                journal_file_path: None,
+            });
+        }
+
+        let original_file_size = fs::metadata(original_file_path)?.len();
+        let original_sha256 = sha256_of_file(original_file_path)?;
+
+        let mut journal_text = format!("{}\n", JOURNAL_HEADER);
+        push_field(&mut journal_text, "operation", operation_name)?;
+        push_path_field(&mut journal_text, "target", original_file_path)?;
+        if backup_written {
+            push_path_field(
+                &mut journal_text,
+                "backup",
+                &artifact_paths.backup_file_path,
+            )?;
+        }
+        push_path_field(&mut journal_text, "draft", &artifact_paths.draft_file_path)?;
+        push_field(
+            &mut journal_text,
+            "keep_backup",
+            // Kept or rotated; recovery leaves such a backup in place
+            &config.retains_backup().to_string(),
+        )?;
+        push_field(
+            &mut journal_text,
+            "original_size",
+            &original_file_size.to_string(),
+        )?;
+        push_field(
+            &mut journal_text,
+            "original_sha256",
+            &digest_to_hex(&original_sha256),
+        )?;
+        push_field(&mut journal_text, "phase", JournalPhase::Started.name())?;
+
+        // Written aside and renamed, so a journal is never half a header
+        let journal_file_path = artifact_paths.journal_file_path.clone();
+        let mut temporary_path = journal_file_path.clone().into_os_string();
+        temporary_path.push(JOURNAL_TEMPORARY_SUFFIX);
+        let temporary_path = PathBuf::from(temporary_path);
+        {
+            let mut temporary_file = File::create(&temporary_path)?;
+            temporary_file.write_all(journal_text.as_bytes())?;
+            temporary_file.sync_all()?;
+        }
+        fs::rename(&temporary_path, &journal_file_path)?;
+        report_detail!("Journal path: {}", journal_file_path.display());
+
+        Ok(OperationJournal {
+            journal_file_path: Some(journal_file_path),
+        })
+    }
+
+    /// Appends a phase marker once the step it names is done.
+    pub(crate) fn mark(&mut self, phase: JournalPhase) -> io::Result<()> {
+        self.append(&format!("phase: {}\n", phase.name()))
+    }
+
+    /// Records the finished draft's SHA-256 with the `draft-verified`
+    /// marker. Call after every check of the draft, just before the rename.
+    pub(crate) fn mark_draft_verified(&mut self, draft_file_path: &Path) -> io::Result<()> {
+        if self.journal_file_path.is_none() {
+            return Ok(());
+        }
+        let draft_sha256 = sha256_of_file(draft_file_path)?;
+        self.append(&format!(
+            "draft_sha256: {}\nphase: {}\n",
+            digest_to_hex(&draft_sha256),
+            JournalPhase::DraftVerified.name()
+        ))
+    }
+
+    /// Records the rename. Never fails: the operation has already taken
+    /// effect, and a journal stuck at `draft-verified` recovers the same way.
+    pub(crate) fn mark_replaced(&mut self) {
+        if let Err(e) = self.mark(JournalPhase::Replaced) {
+            report_problem!("WARNING: Could not record replacement in journal ({})", e);
+        }
+    }
+
+    fn append(&mut self, text: &str) -> io::Result<()> {
+        let Some(journal_file_path) = &self.journal_file_path else {
+            return Ok(());
+        };
+        let mut journal_file = OpenOptions::new().append(true).open(journal_file_path)?;
+        journal_file.write_all(text.as_bytes())?;
+        journal_file.sync_all()
+    }
+}
+
+impl Drop for OperationJournal {
+    fn drop(&mut self) {
+        if let Some(journal_file_path) = self.journal_file_path.take()
+            && let Err(e) = fs::remove_file(&journal_file_path)
+        {
+            report_problem!(
+                "WARNING: Could not remove journal: {} ({})",
+                journal_file_path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn push_field(journal_text: &mut String, key: &str, value: &str) -> io::Result<()> {
+    if value.contains(['\n', '\r']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot journal a {} containing a line break", key),
+        ));
+    }
+    journal_text.push_str(&format!("{}: {}\n", key, value));
+    Ok(())
+}
+
+/// Records `path` as an absolute path, so recovery works from any directory.
+fn push_path_field(journal_text: &mut String, key: &str, path: &Path) -> io::Result<()> {
+    let absolute_path = std::path::absolute(path)?;
+    let path_text = absolute_path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot journal a non-UTF-8 path: {}", path.display()),
+        )
+    })?;
+    push_field(journal_text, key, path_text)
+}
+
+// =========================================
+// Reading and Recovery
+// =========================================
+
+/// Reads the journal an interrupted operation left for `target_path`.
+///
+/// # Returns
+/// - `Ok(None)` if there is no journal
+/// - `Ok(Some(JournalEntry))`; a final marker cut short by the crash is
+///   ignored
+/// - `Err(io::Error)` with `ErrorKind::InvalidData` if the journal is not
+///   one this version wrote, or belongs to another target
+pub fn read_journal(target_path: &Path) -> io::Result<Option<JournalEntry>> {
+    let journal_file_path = build_artifact_paths(target_path)?.journal_file_path;
+    let journal_text = match fs::read_to_string(&journal_file_path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let journal = parse_journal_text(&journal_text).map_err(|reason| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", journal_file_path.display(), reason),
+        )
+    })?;
+
+    if journal.target_path != std::path::absolute(target_path)? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Journal {} belongs to {}",
+                journal_file_path.display(),
+                journal.target_path.display()
+            ),
+        ));
+    }
+    Ok(Some(journal))
+}
+
+fn parse_journal_text(journal_text: &str) -> Result<JournalEntry, String> {
+    // Only lines with their line break were completely written
+    let complete_length = journal_text.rfind('\n').map_or(0, |index| index + 1);
+    let mut lines = journal_text[..complete_length].lines();
+    let header = lines.next();
+    if header == Some(DIRECT_WRITE_JOURNAL_HEADER) {
+        return Err(
+            "a direct-write journal; recover it with direct_write::recover_direct_write"
+                .to_string(),
+        );
+    }
+    if header != Some(JOURNAL_HEADER) {
+        return Err("not a bfbo journal (or an unsupported version)".to_string());
+    }
+
+    let mut operation_name = None;
+    let mut target_path = None;
+    let mut backup_file_path = None;
+    let mut draft_file_path = None;
+    let mut keep_backup = None;
+    let mut original_file_size = None;
+    let mut original_sha256 = None;
+    let mut draft_sha256 = None;
+    let mut phase = None;
+
+    for line in lines {
+        let (key, value) = line
+            .split_once(": ")
+            .ok_or_else(|| format!("malformed line: {}", line))?;
+        match key {
+            "operation" => operation_name = Some(value.to_string()),
+            "target" => target_path = Some(PathBuf::from(value)),
+            "backup" => backup_file_path = Some(PathBuf::from(value)),
+            "draft" => draft_file_path = Some(PathBuf::from(value)),
+            "keep_backup" => {
+                keep_backup = Some(
+                    value
+                        .parse::<bool>()
+                        .map_err(|_| format!("bad keep_backup: {}", value))?,
+                )
+            }
+            "original_size" => {
+                original_file_size = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("bad original_size: {}", value))?,
+                )
+            }
+            "original_sha256" => original_sha256 = Some(parse_digest_hex(value)?),
+            "draft_sha256" => draft_sha256 = Some(parse_digest_hex(value)?),
+            "phase" => {
+                phase = Some(
+                    JournalPhase::from_name(value)
+                        .ok_or_else(|| format!("unknown phase: {}", value))?,
+                )
+            }
+            _ => return Err(format!("unknown field: {}", key)),
+        }
+    }
+
+    let missing = |field: &str| format!("missing field: {}", field);
+    let phase = phase.ok_or_else(|| missing("phase"))?;
+    if phase >= JournalPhase::DraftVerified && draft_sha256.is_none() {
+        return Err(missing("draft_sha256"));
+    }
+    Ok(JournalEntry {
+        operation_name: operation_name.ok_or_else(|| missing("operation"))?,
+        target_path: target_path.ok_or_else(|| missing("target"))?,
+        backup_file_path,
+        draft_file_path: draft_file_path.ok_or_else(|| missing("draft"))?,
+        keep_backup: keep_backup.ok_or_else(|| missing("keep_backup"))?,
+        original_file_size: original_file_size.ok_or_else(|| missing("original_size"))?,
+        original_sha256: original_sha256.ok_or_else(|| missing("original_sha256"))?,
+        draft_sha256,
+        phase,
+    })
+}
+
+fn parse_digest_hex(hex_text: &str) -> Result<[u8; 32], String> {
+    hex_to_digest(hex_text).ok_or_else(|| format!("bad SHA-256: {}", hex_text))
+}
+
+/// SHA-256 of `file_path`, or `None` if there is no such file.
+fn sha256_if_present(file_path: &Path) -> io::Result<Option<[u8; 32]>> {
+    if file_path.is_file() {
+        sha256_of_file(file_path).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Reads the leftover journal for `target_path` and picks the recovery
+/// action, without changing anything.
+///
+/// # Returns
+/// - `Ok(None)` if there is no journal
+/// - `Ok(Some(RecoveryPlan))`
+/// - `Err(io::Error)` with `ErrorKind::InvalidData` if the journal is
+///   unreadable or no action applies (the files on disk match neither the
+///   original nor the verified draft)
+pub fn plan_recovery(target_path: &Path) -> io::Result<Option<RecoveryPlan>> {
+    let Some(journal) = read_journal(target_path)? else {
+        return Ok(None);
+    };
+
+    let target_sha256 = sha256_if_present(&journal.target_path)?;
+    let target_is_original = target_sha256 == Some(journal.original_sha256);
+    let draft_reached = journal.phase >= JournalPhase::DraftVerified;
+
+    let action = if draft_reached
+        && target_is_original
+        && sha256_if_present(&journal.draft_file_path)? == journal.draft_sha256
+    {
+        RecoveryAction::CommitDraft
+    } else if draft_reached && target_sha256 == journal.draft_sha256 {
+        RecoveryAction::FinishCleanup
+    } else if target_is_original {
+        RecoveryAction::RollBack
+    } else if let Some(backup_file_path) = &journal.backup_file_path
+        && sha256_if_present(backup_file_path)? == Some(journal.original_sha256)
+    {
+        RecoveryAction::RestoreBackup
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Cannot recover {} ({}, interrupted after {}): the target matches \
+                 neither its original nor its verified draft, and no intact backup \
+                 exists; nothing changed",
+                journal.target_path.display(),
+                journal.operation_name,
+                journal.phase.name()
+            ),
+        ));
+    };
+
+    Ok(Some(RecoveryPlan { journal, action }))
+}
+
+/// Completes or rolls back an operation interrupted on `target_path`, then
+/// removes its journal.
+///
+/// # Returns
+/// - `Ok(None)` if there was no journal (nothing to recover)
+/// - `Ok(Some(RecoveryPlan))`: the action that was carried out
+/// - `Err(io::Error)` if the target is locked by another operation, no
+///   action applies (see `plan_recovery`), or a step fails; the journal is
+///   kept, so recovery can be run again
+pub fn recover(target_path: PathBuf) -> io::Result<Option<RecoveryPlan>> {
+    // Held from reading the journal to removing it, so no other operation
+    // can start on the target in between
+    let _operation_lock = OperationLock::acquire(&target_path)?;
+    let Some(plan) = plan_recovery(&target_path)? else {
+        report_detail!(
+            "No journal for {}; nothing to recover",
+            target_path.display()
+        );
+        return Ok(None);
+    };
+    let journal = &plan.journal;
+    report_detail!(
+        "Recovering {} ({}, interrupted after {}): {}",
+        journal.target_path.display(),
+        journal.operation_name,
+        journal.phase.name(),
+        plan.action.name()
+    );
+
+    match plan.action {
+        RecoveryAction::CommitDraft => {
+            replace_with_draft(&journal.draft_file_path, &journal.target_path)?;
+            remove_backup_unless_kept(journal)?;
+        }
+        RecoveryAction::FinishCleanup => remove_backup_unless_kept(journal)?,
+        RecoveryAction::RollBack => {
+            remove_if_present(&journal.draft_file_path)?;
+            if let Some(backup_file_path) = &journal.backup_file_path
+                && backup_file_path.is_file()
+                && sha256_of_file(backup_file_path)? != journal.original_sha256
+            {
+                // Cut short by the crash; not a copy of anything
+                fs::remove_file(backup_file_path)?;
+                remove_backup_manifest(backup_file_path)?;
+            }
+        }
+        RecoveryAction::RestoreBackup => {
+            if let Some(backup_file_path) = &journal.backup_file_path {
+                replace_with_draft(backup_file_path, &journal.target_path)?;
+                remove_backup_manifest(backup_file_path)?;
+            }
+            remove_if_present(&journal.draft_file_path)?;
+        }
+    }
+
+    fs::remove_file(build_artifact_paths(&target_path)?.journal_file_path)?;
+    Ok(Some(plan))
+}
+
+/// Like `recover`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn recover_with_config(
+    target_path: PathBuf,
+    config: &OperationConfig,
+) -> io::Result<Option<RecoveryPlan>> {
+    with_operation_config(config, || recover(target_path))
+}
+
+fn remove_backup_unless_kept(journal: &JournalEntry) -> io::Result<()> {
+    match &journal.backup_file_path {
+        Some(backup_file_path) if !journal.keep_backup => {
+            remove_if_present(backup_file_path)?;
+            remove_backup_manifest(backup_file_path)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn remove_if_present(file_path: &Path) -> io::Result<()> {
+    match fs::remove_file(file_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draft_workflow::build_artifact_paths;
+    use crate::error::ByteOpError;
+    use crate::remove_single_byte_from_file_with_config;
+
+    fn journaling_config() -> OperationConfig {
+        OperationConfig::builder()
+            .journal(true)
+            .build()
+            .expect("valid config")
+    }
+
+    /// Leaves the artifacts of an operation on "0123" -> "123" that crashed
+    /// right after recording `phase`.
+    fn simulate_crash_after(test_file: &Path, phase: JournalPhase) -> DraftArtifactPaths {
+        std::fs::write(test_file, b"0123").expect("create test file");
+        let artifact_paths = build_artifact_paths(test_file).expect("artifact paths");
+        let mut journal = OperationJournal::begin(
+            test_file,
+            "Byte Removal",
+            &artifact_paths,
+            true,
+            &journaling_config(),
+        )
+        .expect("begin journal");
+
+        if phase == JournalPhase::Started {
+            std::fs::write(&artifact_paths.backup_file_path, b"01").expect("partial backup");
+        } else {
+            std::fs::write(&artifact_paths.backup_file_path, b"0123").expect("backup");
+            journal.mark(JournalPhase::BackedUp).expect("mark");
+            std::fs::write(&artifact_paths.draft_file_path, b"12").expect("partial draft");
+        }
+        if phase >= JournalPhase::DraftVerified {
+            std::fs::write(&artifact_paths.draft_file_path, b"123").expect("draft");
+            journal
+                .mark_draft_verified(&artifact_paths.draft_file_path)
+                .expect("mark");
+        }
+        if phase == JournalPhase::Replaced {
+            std::fs::rename(&artifact_paths.draft_file_path, test_file).expect("rename");
+            journal.mark_replaced();
+        }
+
+        // A crash never runs the destructor
+        std::mem::forget(journal);
+        artifact_paths
+    }
+
+    #[test]
+    fn test_journaled_operation_leaves_no_journal() {
+        let test_file = std::env::temp_dir().join("test_journal_clean.bin");
+        std::fs::write(&test_file, b"ABCD").expect("create test file");
+        let journal_file_path = build_artifact_paths(&test_file)
+            .expect("artifact paths")
+            .journal_file_path;
+
+        remove_single_byte_from_file_with_config(test_file.clone(), 1, &journaling_config())
+            .expect("remove");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ACD");
+        assert!(!journal_file_path.exists());
+
+        // A failed operation removes its journal too
+        assert!(
+            remove_single_byte_from_file_with_config(test_file.clone(), 9, &journaling_config())
+                .is_err()
+        );
+        assert!(!journal_file_path.exists());
+        assert_eq!(recover(test_file.clone()).expect("recover"), None);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_recover_each_interrupted_phase() {
+        let cases = [
+            (
+                JournalPhase::Started,
+                RecoveryAction::RollBack,
+                &b"0123"[..],
+            ),
+            (JournalPhase::BackedUp, RecoveryAction::RollBack, b"0123"),
+            (
+                JournalPhase::DraftVerified,
+                RecoveryAction::CommitDraft,
+                b"123",
+            ),
+            (
+                JournalPhase::Replaced,
+                RecoveryAction::FinishCleanup,
+                b"123",
+            ),
+        ];
+        for (phase, expected_action, expected_content) in cases {
+            let test_file = std::env::temp_dir().join("test_journal_recover.bin");
+            let artifact_paths = simulate_crash_after(&test_file, phase);
+
+            // The pending journal blocks new operations
+            let error = remove_single_byte_from_file_with_config(
+                test_file.clone(),
+                0,
+                &OperationConfig::default(),
+            )
+            .expect_err("journal pending");
+            assert!(matches!(
+                ByteOpError::from_io_error(&error),
+                Some(ByteOpError::PendingJournal { .. })
+            ));
+
+            let plan = plan_recovery(&test_file)
+                .expect("plan")
+                .expect("journal present");
+            assert_eq!(plan.journal.phase, phase);
+            assert_eq!(plan.journal.operation_name, "Byte Removal");
+
+            let carried_out = recover(test_file.clone()).expect("recover").expect("plan");
+            assert_eq!(carried_out.action, expected_action, "phase {:?}", phase);
+            assert_eq!(
+                std::fs::read(&test_file).expect("read file"),
+                expected_content
+            );
+            assert!(!artifact_paths.draft_file_path.exists());
+            assert!(!artifact_paths.journal_file_path.exists());
+            // A partial backup is dropped; a complete one is kept on roll-back
+            assert_eq!(
+                artifact_paths.backup_file_path.exists(),
+                phase == JournalPhase::BackedUp
+            );
+
+            let _ = std::fs::remove_file(&artifact_paths.backup_file_path);
+            let _ = std::fs::remove_file(&test_file);
+        }
+    }
+
+    #[test]
+    fn test_recover_restores_backup_or_refuses() {
+        let test_file = std::env::temp_dir().join("test_journal_restore.bin");
+
+        // Target damaged outside the workflow; the backup is intact
+        let artifact_paths = simulate_crash_after(&test_file, JournalPhase::BackedUp);
+        std::fs::write(&test_file, b"junk").expect("damage target");
+
+        // Recovery waits for the lock like any other operation
+        let operation_lock = OperationLock::acquire(&test_file).expect("acquire");
+        let error = recover(test_file.clone()).expect_err("target locked");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::LockHeldByThisProcess { .. })
+        ));
+        assert!(artifact_paths.journal_file_path.exists());
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"junk");
+        drop(operation_lock);
+
+        let plan = recover(test_file.clone()).expect("recover").expect("plan");
+        assert_eq!(plan.action, RecoveryAction::RestoreBackup);
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"0123");
+        assert!(!artifact_paths.backup_file_path.exists());
+
+        // Nothing on disk matches the journal: refuse and keep the journal
+        let artifact_paths = simulate_crash_after(&test_file, JournalPhase::Started);
+        std::fs::write(&test_file, b"junk").expect("damage target");
+        let error = recover(test_file.clone()).expect_err("no action applies");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(artifact_paths.journal_file_path.exists());
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"junk");
+
+        let _ = std::fs::remove_file(&artifact_paths.journal_file_path);
+        let _ = std::fs::remove_file(&artifact_paths.backup_file_path);
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_parse_journal_ignores_torn_marker() {
+        let digest = "ab".repeat(32);
+        let journal_text = format!(
+            "{}\noperation: X\ntarget: /t\ndraft: /t.draft\nkeep_backup: true\n\
+             original_size: 4\noriginal_sha256: {}\nphase: started\nphase: back",
+            JOURNAL_HEADER, digest
+        );
+        let journal = parse_journal_text(&journal_text).expect("parse");
+        assert_eq!(journal.phase, JournalPhase::Started);
+        assert_eq!(journal.backup_file_path, None);
+        assert_eq!(journal.original_sha256, [0xAB; 32]);
+
+        assert!(parse_journal_text("bfbo-journal 2\n").is_err());
+        assert!(parse_journal_text(&journal_text.replace("phase: started\n", "")).is_err());
+    }
+}
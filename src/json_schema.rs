@@ -0,0 +1,437 @@
+//! JSON Schema definitions for the JSON outputs
+//!
+//! Integrators validating or code-generating against `--json` output should
+//! not have to reverse-engineer the format from examples. Each JSON output
+//! has a JSON Schema (draft 2020-12) here, printed by `bfbo schema <NAME>`.
+//!
+//! The crate has no serialization dependency. The operation report, the
+//! patch plan and the journal are rendered from a field table on their
+//! types (`JsonObject`): each field's name, its schema, and how to read it
+//! from the value. `render_json_object` and `json_object_schema` walk the
+//! same table, so a field cannot appear in one and not the other.
+//!
+//! The older outputs predate the tables: the dedup-report schema is written
+//! next to its hand-written renderer, and the invariants schema is
+//! generated from the same key list the renderer uses. The tests render
+//! real output and check it against the schema's property lists, so the two
+//! cannot drift silently.
+
+use crate::invariants::{build_invariants, escape_json_string};
+use crate::journal::JournalEntry;
+use crate::operation_report::OperationReport;
+use crate::patch_plan::PlanEntry;
+
+/// Names accepted by `json_schema_for`, one per JSON output.
+pub const JSON_SCHEMA_NAMES: [&str; 5] =
+    ["report", "plan", "journal", "dedup-report", "invariants"];
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns the JSON Schema document for a JSON output, by name.
+///
+/// # Returns
+/// - `Some(String)`: the schema, as a single JSON object
+/// - `None` if `output_name` is not one of `JSON_SCHEMA_NAMES`
+pub fn json_schema_for(output_name: &str) -> Option<String> {
+    match output_name {
+        "report" => Some(document_schema(
+            output_name,
+            &json_object_schema::<OperationReport>(),
+        )),
+        "plan" => Some(document_schema(
+            output_name,
+            &format!(
+                "{{\"type\":\"array\",\"items\":{}}}",
+                json_object_schema::<PlanEntry>()
+            ),
+        )),
+        "journal" => Some(document_schema(
+            output_name,
+            &json_object_schema::<JournalEntry>(),
+        )),
+        "dedup-report" => Some(dedup_report_schema()),
+        "invariants" => Some(invariants_schema()),
+        _ => None,
+    }
+}
+
+// =========================================
+// Field Tables
+// =========================================
+
+/// A Rust type rendered as one JSON object, described by its field table.
+pub(crate) trait JsonObject: Sized + 'static {
+    /// The object's fields, in output order
+    const JSON_FIELDS: &'static [JsonField<Self>];
+}
+
+/// One field of a `JsonObject`: its name, its schema, and its value.
+pub(crate) struct JsonField<T> {
+    pub(crate) name: &'static str,
+    pub(crate) kind: JsonKind,
+    pub(crate) value: fn(&T) -> JsonValue,
+}
+
+/// The schema of a field value.
+pub(crate) enum JsonKind {
+    /// Non-negative integer
+    Integer,
+    Boolean,
+    String,
+    /// Lower-case hex of a SHA-256 digest
+    Sha256Hex,
+    /// Lower-case hex of any number of bytes
+    BytesHex,
+    /// One of the names the function lists
+    Name(fn() -> Vec<&'static str>),
+    Nullable(&'static JsonKind),
+    Array(&'static JsonKind),
+    /// Another `JsonObject` (pass `json_object_schema::<T>`)
+    Object(fn() -> String),
+}
+
+/// A rendered field value; nested objects are already rendered.
+pub(crate) enum JsonValue {
+    Null,
+    Boolean(bool),
+    Integer(u64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(String),
+}
+
+impl JsonValue {
+    /// `Null` for `None`, otherwise the value `present` makes.
+    pub(crate) fn optional<T>(value: Option<T>, present: impl FnOnce(T) -> JsonValue) -> Self {
+        value.map_or(JsonValue::Null, present)
+    }
+
+    fn render_into(&self, output: &mut String) {
+        match self {
+            JsonValue::Null => output.push_str("null"),
+            JsonValue::Boolean(flag) => output.push_str(if *flag { "true" } else { "false" }),
+            JsonValue::Integer(number) => output.push_str(&number.to_string()),
+            JsonValue::String(text) => {
+                output.push('"');
+                output.push_str(&escape_json_string(text));
+                output.push('"');
+            }
+            JsonValue::Array(items) => {
+                output.push('[');
+                for (item_index, item) in items.iter().enumerate() {
+                    if item_index > 0 {
+                        output.push(',');
+                    }
+                    item.render_into(output);
+                }
+                output.push(']');
+            }
+            JsonValue::Object(rendered_object) => output.push_str(rendered_object),
+        }
+    }
+}
+
+impl JsonKind {
+    fn schema(&self) -> String {
+        match self {
+            JsonKind::Integer => "{\"type\":\"integer\",\"minimum\":0}".to_string(),
+            JsonKind::Boolean => "{\"type\":\"boolean\"}".to_string(),
+            JsonKind::String => "{\"type\":\"string\"}".to_string(),
+            JsonKind::Sha256Hex => {
+                "{\"type\":\"string\",\"pattern\":\"^[0-9a-f]{64}$\"}".to_string()
+            }
+            JsonKind::BytesHex => {
+                "{\"type\":\"string\",\"pattern\":\"^([0-9a-f]{2})*$\"}".to_string()
+            }
+            JsonKind::Name(names) => {
+                let quoted_names: Vec<String> = names()
+                    .iter()
+                    .map(|name| format!("\"{}\"", escape_json_string(name)))
+                    .collect();
+                format!("{{\"enum\":[{}]}}", quoted_names.join(","))
+            }
+            JsonKind::Nullable(inner_kind) => {
+                format!(
+                    "{{\"anyOf\":[{},{{\"type\":\"null\"}}]}}",
+                    inner_kind.schema()
+                )
+            }
+            JsonKind::Array(item_kind) => {
+                format!("{{\"type\":\"array\",\"items\":{}}}", item_kind.schema())
+            }
+            JsonKind::Object(object_schema) => object_schema(),
+        }
+    }
+}
+
+/// Renders `item` as one JSON object, fields in table order.
+pub(crate) fn render_json_object<T: JsonObject>(item: &T) -> String {
+    let mut output = String::from("{");
+    for (field_index, field) in T::JSON_FIELDS.iter().enumerate() {
+        if field_index > 0 {
+            output.push(',');
+        }
+        output.push('"');
+        output.push_str(field.name);
+        output.push_str("\":");
+        (field.value)(item).render_into(&mut output);
+    }
+    output.push('}');
+    output
+}
+
+/// Schema of the object `render_json_object::<T>` renders: every field is
+/// required (absent values are `null`) and no others appear.
+pub(crate) fn json_object_schema<T: JsonObject>() -> String {
+    let quoted_names: Vec<String> = T::JSON_FIELDS
+        .iter()
+        .map(|field| format!("\"{}\"", field.name))
+        .collect();
+    let property_entries: Vec<String> = T::JSON_FIELDS
+        .iter()
+        .map(|field| format!("\"{}\":{}", field.name, field.kind.schema()))
+        .collect();
+    format!(
+        concat!(
+            "{{\"type\":\"object\",",
+            "\"required\":[{}],",
+            "\"additionalProperties\":false,",
+            "\"properties\":{{{}}}}}"
+        ),
+        quoted_names.join(","),
+        property_entries.join(",")
+    )
+}
+
+/// Adds the dialect, id and title to a bare schema object.
+fn document_schema(output_name: &str, bare_schema: &str) -> String {
+    format!(
+        "{{\"$schema\":\"{}\",\"$id\":\"bfbo:{}\",\"title\":\"bfbo {}\",{}",
+        SCHEMA_DIALECT,
+        output_name,
+        output_name,
+        &bare_schema[1..]
+    )
+}
+
+/// Schema for `bfbo dedup-report --json` (`format_dedup_report_as_json`).
+fn dedup_report_schema() -> String {
+    format!(
+        concat!(
+            "{{\"$schema\":\"{}\",",
+            "\"$id\":\"bfbo:dedup-report\",",
+            "\"title\":\"bfbo dedup-report\",",
+            "\"type\":\"object\",",
+            "\"required\":[\"chunk_size\",\"files_scanned\",\"chunks_hashed\",\"duplicate_regions\"],",
+            "\"additionalProperties\":false,",
+            "\"properties\":{{",
+            "\"chunk_size\":{{\"type\":\"integer\",\"minimum\":1}},",
+            "\"files_scanned\":{{\"type\":\"integer\",\"minimum\":0}},",
+            "\"chunks_hashed\":{{\"type\":\"integer\",\"minimum\":0}},",
+            "\"duplicate_regions\":{{\"type\":\"array\",\"items\":{{",
+            "\"type\":\"object\",",
+            "\"required\":[\"length\",\"occurrences\"],",
+            "\"additionalProperties\":false,",
+            "\"properties\":{{",
+            "\"length\":{{\"type\":\"integer\",\"minimum\":1}},",
+            "\"occurrences\":{{\"type\":\"array\",\"minItems\":2,\"items\":{{",
+            "\"type\":\"object\",",
+            "\"required\":[\"file\",\"offset\"],",
+            "\"additionalProperties\":false,",
+            "\"properties\":{{",
+            "\"file\":{{\"type\":\"string\"}},",
+            "\"offset\":{{\"type\":\"integer\",\"minimum\":0}}",
+            "}}}}}}}}}}}}}}}}"
+        ),
+        SCHEMA_DIALECT
+    )
+}
+
+/// Schema for `bfbo invariants --json`, generated from `build_invariants`.
+fn invariants_schema() -> String {
+    let invariant_keys: Vec<String> = build_invariants()
+        .iter()
+        .map(|(key, _)| format!("\"{}\"", escape_json_string(key)))
+        .collect();
+    let property_entries: Vec<String> = invariant_keys
+        .iter()
+        .map(|quoted_key| format!("{}:{{\"type\":\"string\"}}", quoted_key))
+        .collect();
+
+    format!(
+        concat!(
+            "{{\"$schema\":\"{}\",",
+            "\"$id\":\"bfbo:invariants\",",
+            "\"title\":\"bfbo invariants\",",
+            "\"type\":\"object\",",
+            "\"required\":[{}],",
+            "\"additionalProperties\":{{\"type\":\"string\"}},",
+            "\"properties\":{{{}}}}}"
+        ),
+        SCHEMA_DIALECT,
+        invariant_keys.join(","),
+        property_entries.join(",")
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dedup_report::{
+        ChunkLocation, DedupReport, DuplicateRegion, format_dedup_report_as_json,
+    };
+    use crate::hash::HashAlgorithm;
+    use crate::invariants::format_invariants_as_json;
+    use crate::journal::{JournalPhase, format_journal_as_json};
+    use crate::operation_report::format_operation_report_as_json;
+    use crate::patch_plan::{format_plan_as_json, parse_plan_text};
+    use crate::reporter::OperationPhase;
+    use std::time::Duration;
+
+    /// Object keys (`"name":`) in a rendered JSON document, in order.
+    fn object_keys(json_text: &str) -> Vec<String> {
+        json_text
+            .split('"')
+            .collect::<Vec<&str>>()
+            .windows(2)
+            .filter(|pair| pair[1].starts_with(':'))
+            .map(|pair| pair[0].to_string())
+            .collect()
+    }
+
+    /// Structural sanity: balanced braces and brackets outside strings.
+    fn is_balanced(json_text: &str) -> bool {
+        let mut depth: i64 = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        for character in json_text.chars() {
+            match (in_string, escaped, character) {
+                (true, true, _) => escaped = false,
+                (true, false, '\\') => escaped = true,
+                (_, _, '"') => in_string = !in_string,
+                (false, _, '{') | (false, _, '[') => depth += 1,
+                (false, _, '}') | (false, _, ']') => depth -= 1,
+                _ => {}
+            }
+            if depth < 0 {
+                return false;
+            }
+        }
+        depth == 0 && !in_string
+    }
+
+    #[test]
+    fn test_schemas_cover_rendered_output() {
+        for output_name in JSON_SCHEMA_NAMES {
+            let schema = json_schema_for(output_name).expect("schema exists");
+            assert!(is_balanced(&schema), "{} schema is malformed", output_name);
+        }
+        assert!(json_schema_for("recipe").is_none());
+
+        let report = DedupReport {
+            chunk_size: 16,
+            files_scanned: 2,
+            chunks_hashed: 4,
+            duplicate_regions: vec![DuplicateRegion {
+                byte_length: 16,
+                occurrences: vec![
+                    ChunkLocation {
+                        file_path: "a.bin".into(),
+                        byte_offset: 0,
+                    },
+                    ChunkLocation {
+                        file_path: "b.bin".into(),
+                        byte_offset: 32,
+                    },
+                ],
+            }],
+        };
+        let dedup_schema = json_schema_for("dedup-report").expect("schema exists");
+        for key in object_keys(&format_dedup_report_as_json(&report)) {
+            assert!(
+                dedup_schema.contains(&format!("\"{}\":{{\"type\"", key)),
+                "dedup-report key {} missing from schema",
+                key
+            );
+        }
+
+        let invariants_schema = json_schema_for("invariants").expect("schema exists");
+        for key in object_keys(&format_invariants_as_json(&build_invariants())) {
+            assert!(
+                invariants_schema.contains(&format!("\"{}\":{{\"type\":\"string\"}}", key)),
+                "invariants key {} missing from schema",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_field_table_outputs_match_their_schemas() {
+        let report = OperationReport {
+            operation_name: "Byte Removal".to_string(),
+            byte_position: 3,
+            old_byte_value: Some(0x41),
+            new_byte_value: None,
+            original_size: 10,
+            new_size: 9,
+            bytes_processed: 10,
+            chunk_count: 1,
+            original_sha256: [0xAB; 32],
+            result_sha256: [0xCD; 32],
+            verification_hash: HashAlgorithm::Sha256,
+            phase_timings: vec![(OperationPhase::Backup, Duration::from_micros(1500))],
+            total_duration: Duration::from_millis(2),
+            kept_backup_path: None,
+            preserved_modified_time: None,
+        };
+        let report_json = format_operation_report_as_json(&report);
+        assert!(is_balanced(&report_json));
+        assert!(report_json.contains("\"new_byte_value\":null"));
+        assert!(report_json.contains("{\"phase\":\"backup\",\"duration_us\":1500}"));
+        assert!(report_json.contains("\"total_duration_us\":2000"));
+
+        let plan_entries = parse_plan_text(
+            "replace 0x10 02 when byte 0x10 == 01\nremove 0 when contains EFBBBF\ntouch\n",
+        )
+        .expect("valid plan");
+        let plan_json = format_plan_as_json(&plan_entries);
+        assert!(is_balanced(&plan_json));
+        assert!(plan_json.starts_with("[{\"operation\":\"replace\",\"position\":16,"));
+        assert!(plan_json.contains("\"pattern\":\"efbbbf\""));
+
+        let journal = JournalEntry {
+            operation_name: "Byte Insertion".to_string(),
+            target_path: "/data/f.bin".into(),
+            backup_file_path: None,
+            draft_file_path: "/data/f.bin.draft".into(),
+            keep_backup: false,
+            original_file_size: 4096,
+            original_sha256: [0x01; 32],
+            draft_sha256: None,
+            phase: JournalPhase::BackedUp,
+        };
+        let journal_json = format_journal_as_json(&journal);
+        assert!(is_balanced(&journal_json));
+        assert!(journal_json.contains("\"phase\":\"backed-up\""));
+
+        for (output_name, rendered_json) in [
+            ("report", &report_json),
+            ("plan", &plan_json),
+            ("journal", &journal_json),
+        ] {
+            let schema = json_schema_for(output_name).expect("schema exists");
+            for key in object_keys(rendered_json) {
+                assert!(
+                    schema.contains(&format!("\"{}\":{{", key)),
+                    "{} key {} missing from schema",
+                    output_name,
+                    key
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! basic_file_byte_operations
+//!
+//! Byte-level file edits that never modify the original in place: every
+//! operation builds a draft beside the file, verifies it against the
+//! original, and only then renames it over the original.
+//!
+//! The single-byte operations live in `replace`, `remove`, and `insert`
+//! (re-exported here); their checks are in `verify`. Multi-byte, transform,
+//! archive, and reporting operations each have their own module and share
+//! the workflow in `draft_workflow`.
+
+// The Debug-Assert, Test-Assert, Production-Catch-Handle pattern deliberately
+// asserts on constants and indexes pre-allocated buffers by position.
+#![allow(clippy::assertions_on_constants, clippy::needless_range_loop)]
+
+pub mod backup_audit;
+pub mod backup_manifest;
+pub mod backup_restore;
+pub mod batch;
+pub mod bit_operations;
+pub mod byte_order_mark;
+pub mod byte_search;
+pub mod canary;
+pub mod cli;
+pub mod cost_estimate;
+pub mod cstring_field;
+pub mod custody_report;
+pub mod dedup_report;
+pub mod diff_report;
+pub mod direct_write;
+pub mod disk_image;
+pub mod draft_workflow;
+pub mod edit_session;
+pub mod error;
+pub mod file_session;
+pub mod file_splitting;
+pub mod hash;
+pub mod host_policy;
+pub mod insert;
+pub mod invariants;
+pub mod journal;
+pub mod json_schema;
+pub mod lock_file;
+pub mod memory_budget;
+pub mod multi_edit;
+pub mod offset;
+pub mod operation_config;
+pub mod operation_planner;
+pub mod operation_report;
+pub mod orphan_scan;
+pub mod page_cache;
+pub mod patch_plan;
+pub mod range_operations;
+pub mod range_verification;
+pub mod recipes;
+mod reflink;
+pub mod remove;
+pub mod replace;
+pub mod reporter;
+pub mod resize_operations;
+pub mod sector_editing;
+#[cfg(feature = "soak-test")]
+pub mod soak_test;
+pub mod tar_archive;
+pub mod transform_operations;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring_backend;
+pub mod utf8_operations;
+pub mod util;
+pub mod value_parsing;
+pub mod verify;
+#[cfg(windows)]
+mod windows_replace;
+mod writability;
+pub mod zip_archive;
+
+pub use hash::{HashAlgorithm, HashValue, hash_file, hash_reader};
+pub use insert::{
+    add_single_byte_to_file, insert_single_byte_into_file,
+    insert_single_byte_into_file_with_config, insert_single_byte_into_file_with_report,
+};
+pub use operation_config::{OperationConfig, with_operation_config};
+pub use operation_report::OperationReport;
+pub use remove::{
+    remove_single_byte_from_file, remove_single_byte_from_file_with_config,
+    remove_single_byte_from_file_with_report,
+};
+pub use replace::{
+    replace_byte_if, replace_byte_if_with_config, replace_single_byte_in_file,
+    replace_single_byte_in_file_with_config, replace_single_byte_in_file_with_report,
+};
+
+/*
+
+# File Identities & Workflow
+At the granular level of these operations, it may be best to avoid user-abstractions such as 'add' or 'remove' or 'modify' and 'original' or 'copy' when speaking of the actual mechanical steps. We should to look instead at specific well-defined steps and actions. The semantics may seem counter-intuitive, as to effect the same result we never make any changes to either the original file (preserved for safety) or to the new file, which we describe as 'altered' meaning that it is different from the original, not that 'change operations' were ever performed on the file as such. For example reconstructing a file after frameshifting does not ever literally happen (as it would need to if there were only one file without a backup).
+
+It may be possible to effect the desired end-state (retroactively described as 'add' or 'remove') with steps such as these:
+
+1. Create a draft file.
+
+2. Append bytes (from the original file, to the draft-file) up to the 'file byte position of the change operation' in question:
+append byte by byte, or append with a small bucket-brigade buffer.
+
+3. Performing Operation at 'file byte position of the change operation':
+- For Remove-a-byte-operation: no action taken for draft-file, nothing written. This is an effective frame shift/advance in reading the original file one byte.
+- For Add-a-byte-operation: append the 'new' (not in original file) byte to the draft file. Do not shift original file read-location.
+- For Hex-edit: append the 'new' (not in original file) byte to the draft file.
+
+4. Performing Operation ~after 'file byte position of the change operation':
+- For hex-edit: Append bytes (from the original file, to the draft-file) after the 'file byte position of the change operation' in question:
+append byte by byte, or append with a small bucket-brigade buffer.
+- For remove-byte: Append bytes (from the original file, to the draft-file), after the 'file byte position of the change operation' in question: append byte by byte, or append with a small bucket-brigade buffer. This is similar to hex-edit, except that nothing is added AT the target position, effecting a frame-shift.
+- For Add-byte Edit: Append bytes (from the original file, to the draft-file), FROM/INCLUDING the 'file byte position of the change operation' in question: append byte by byte, or append with a small bucket-brigade buffer, effecting a frame-shift.
+
+
+In theory, this process only 'need' apply to Add-a-byte-operation and Remove-a-byte-operation not (hex-edit)change-a-byte-in-place. An in-place byte change can be done simple on a file. However, what is better:
+1. A standard process of building a new file cleanly and not making any internal changes to it and which is a single process always used, or
+2. Having two different workflows in the same tool-kit, whereby in-place edit makes a complete copy of a file and then navigates back to the change-spot and changes it and resaves the file. Is that simpler than writing the file per-design in the first place with a standard workflow, especially when a backup copy would be made for safety in either case? We will assume that a more uniform workflow is more practical.
+
+Using these steps we are not 'altering' any file per-se; we are constructing the 'altered' (relatively speaking) file in one clean workflow.
+
+# Test, Check, And Verify
+There can also be checking steps such as:
+- (double)checking original vs. new file: total byte length
+- (double)checking original vs. new file: pre-position byte length similarity (possible a hash-check)
+- (double)checking original vs. new file value: at-position, must be dissimilarity
+- (double)checking original vs. new file: post-position, must be similarity given frame-shift or not (possible a hash-check)
+ - - hex-edit in place: no frameshift: post-position must be the same
+ - - remove byte: -1 frameshift in new file compared with original: given -1 frameshift post position must be the same
+ - - add byte: +1 frameshift in new file compared with original: given +1 frameshift, post position must be the same
+
+
+Remove-Byte Operation Workflow
+Let me restate the remove-byte operation using your precise mechanical terminology:
+Draft File Construction Process
+Step 1: Create Draft File
+
+Open original file for reading (read position starts at 0)
+Create empty draft file for writing (write position starts at 0)
+
+Step 2: Append Pre-Position Bytes
+
+Read from original: bytes at positions 0 through byte_position_from_start - 1
+Append to draft: all these bytes sequentially
+Original read position after: at byte_position_from_start
+Draft write position after: at byte_position_from_start
+
+Step 3: Perform Remove Operation AT Position
+
+Original file: advance read position by 1 (skip the byte at byte_position_from_start)
+
+Read position moves from byte_position_from_start to byte_position_from_start + 1
+
+
+Draft file: write nothing, take no action
+
+Write position remains at byte_position_from_start
+
+
+Effect: The byte at byte_position_from_start in the original is never appended to draft
+
+Step 4: Append Post-Position Bytes
+
+Read from original: bytes starting at position byte_position_from_start + 1 through EOF
+
+(Original read position is already at byte_position_from_start + 1 from Step 3)
+
+
+Append to draft: all remaining bytes sequentially
+Effect: These bytes are written to draft starting at position byte_position_from_start
+
+This creates the -1 frame-shift automatically
+*/
@@ -0,0 +1,518 @@
+//! Lock files against concurrent edits of one target
+//!
+//! Every operation that replaces a file holds `<name>.lock` next to it from
+//! before the backup until cleanup. The lock names the process that holds
+//! it:
+//!
+//! ```text
+//! bfbo-lock 1
+//! pid: 4242
+//! created: 2024-01-31T12:00:00Z
+//! ```
+//!
+//! The lock is created with `create_new`, so of two operations racing for
+//! one file exactly one gets it; the other fails with
+//! `ByteOpError::TargetLocked` and changes nothing. The lock is removed
+//! when the operation returns, whether it succeeded or failed.
+//!
+//! The holder also keeps an advisory OS lock on the lock file (`flock` on
+//! Unix, `LockFileEx` on Windows), taken before the text is written. The
+//! OS drops it when the holder exits, however it exits, so a lock file
+//! whose OS lock is free is stale: the next operation breaks it
+//! (`break_stale_lock`) and goes ahead, and a crashed run never blocks
+//! later ones. A reused PID does not keep a stale lock alive. Elsewhere
+//! every readable lock counts as live. A lock that cannot be parsed (a
+//! crash between creating and writing it) counts as stale once it is older
+//! than `UNREADABLE_LOCK_GRACE`, unless its OS lock is held.
+//!
+//! A second operation on a file this process is already editing (from a
+//! hook, or another thread) fails with `ByteOpError::LockHeldByThisProcess`
+//! before the lock file is touched.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use crate::custody_report::{current_unix_seconds, format_rfc3339_utc};
+use crate::error::ByteOpError;
+use crate::reporter::{report_detail, report_problem};
+
+/// Suffix appended to the target's file name to name its lock
+pub const LOCK_FILE_SUFFIX: &str = ".lock";
+
+/// First line of every lock file; the number is the format version
+const LOCK_FILE_HEADER: &str = "bfbo-lock 1";
+
+/// Age after which a lock file that cannot be parsed counts as stale
+const UNREADABLE_LOCK_GRACE: Duration = Duration::from_secs(60);
+
+/// Whether lock files carry an OS lock on this platform
+const OS_LOCKS_AVAILABLE: bool = cfg!(any(unix, windows));
+
+/// Lock files held by operations in this process, canonicalized
+static HELD_LOCK_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// The process that holds a lock, as recorded in the lock file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockOwner {
+    pub pid: u32,
+    /// RFC 3339 UTC time the lock was taken
+    pub created: String,
+}
+
+/// Path of the lock file that guards `target_path`.
+pub fn lock_file_path(target_path: &Path) -> io::Result<PathBuf> {
+    let file_name = target_path
+        .file_name()
+        .ok_or_else(|| ByteOpError::invalid_file_name(target_path))?
+        .to_string_lossy()
+        .to_string();
+    Ok(target_path.with_file_name(format!("{}{}", file_name, LOCK_FILE_SUFFIX)))
+}
+
+/// Reads the lock on `target_path`.
+///
+/// # Returns
+/// - `Ok(None)` if the target is not locked
+/// - `Ok(Some(LockOwner))`
+/// - `Err(io::Error)` with `ErrorKind::InvalidData` if the lock file cannot
+///   be parsed
+pub fn read_lock_file(target_path: &Path) -> io::Result<Option<LockOwner>> {
+    let lock_file_path = lock_file_path(target_path)?;
+    match fs::read_to_string(&lock_file_path) {
+        Ok(lock_text) => parse_lock_text(&lock_text, &lock_file_path).map(Some),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Removes the lock on `target_path` if the process that took it is gone
+/// (its OS lock is free).
+///
+/// The lock is moved aside and re-read before it is deleted, so a fresh
+/// lock taken in between by another process is put back, not removed.
+///
+/// # Returns
+/// - `Ok(true)` if a stale lock was removed
+/// - `Ok(false)` if there was no lock
+/// - `Err(io::Error)` carrying `ByteOpError::TargetLocked` if the lock is
+///   live (or unreadable but recent); it is left in place
+pub fn break_stale_lock(target_path: &Path) -> io::Result<bool> {
+    let lock_file_path = lock_file_path(target_path)?;
+    let stale_lock_text = match fs::read_to_string(&lock_file_path) {
+        Ok(lock_text) => lock_text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    let os_lock_held = match os_lock_is_held(&lock_file_path) {
+        Ok(held) => held,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    let stale_owner = match parse_lock_text(&stale_lock_text, &lock_file_path) {
+        // Without OS locks there is no telling a live holder from a dead one
+        Ok(owner) if os_lock_held != Some(false) => {
+            return Err(target_locked(&lock_file_path, Some(owner.pid)));
+        }
+        Ok(owner) => Some(owner),
+        Err(_) if os_lock_held == Some(true) => {
+            return Err(target_locked(&lock_file_path, None));
+        }
+        Err(_) => {
+            let lock_age = fs::metadata(&lock_file_path)?
+                .modified()?
+                .elapsed()
+                .unwrap_or_default();
+            if lock_age < UNREADABLE_LOCK_GRACE {
+                return Err(target_locked(&lock_file_path, None));
+            }
+            None
+        }
+    };
+
+    let mut aside_path = lock_file_path.clone().into_os_string();
+    aside_path.push(format!(".stale-{}", process::id()));
+    let aside_path = PathBuf::from(aside_path);
+    match fs::rename(&lock_file_path, &aside_path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        other => other?,
+    }
+    if fs::read_to_string(&aside_path)? != stale_lock_text {
+        // Another process broke the stale lock and took a fresh one first
+        fs::rename(&aside_path, &lock_file_path)?;
+        return Err(target_locked(&lock_file_path, None));
+    }
+    fs::remove_file(&aside_path)?;
+
+    match stale_owner {
+        Some(owner) => report_detail!(
+            "Removed stale lock {} (process {} is gone)",
+            lock_file_path.display(),
+            owner.pid
+        ),
+        None => report_detail!("Removed unreadable lock {}", lock_file_path.display()),
+    }
+    Ok(true)
+}
+
+/// The lock an operation holds on its target; removed when dropped.
+#[derive(Debug)]
+pub(crate) struct OperationLock {
+    lock_file_path: PathBuf,
+    /// `lock_file_path` as recorded in `HELD_LOCK_FILES`
+    held_lock_path: PathBuf,
+    /// Open for as long as the lock is held; closing it releases the OS
+    /// lock
+    _lock_file: File,
+}
+
+impl OperationLock {
+    /// Takes the lock on `target_path`, breaking a stale one first.
+    ///
+    /// # Returns
+    /// - `Ok(OperationLock)`
+    /// - `Err(io::Error)` carrying `ByteOpError::LockHeldByThisProcess` if
+    ///   an operation in this process holds the lock, or
+    ///   `ByteOpError::TargetLocked` if another live process holds it;
+    ///   nothing was changed
+    pub(crate) fn acquire(target_path: &Path) -> io::Result<Self> {
+        let lock_file_path = lock_file_path(target_path)?;
+        if is_held_by_this_process(&lock_file_path) {
+            let error: io::Error = ByteOpError::LockHeldByThisProcess {
+                path: lock_file_path,
+            }
+            .into();
+            report_problem!("ERROR: {}", error);
+            return Err(error);
+        }
+        let lock_text = format!(
+            "{}\npid: {}\ncreated: {}\n",
+            LOCK_FILE_HEADER,
+            process::id(),
+            format_rfc3339_utc(current_unix_seconds())
+        );
+
+        let mut stale_lock_broken = false;
+        loop {
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_file_path)
+            {
+                Ok(mut lock_file) => {
+                    // Locked before the text is written, so every readable
+                    // lock file was OS-locked by its holder. Waits only for
+                    // another process's brief `os_lock_is_held` probe
+                    let write_result = lock_exclusive(&lock_file, true)
+                        .and_then(|_| lock_file.write_all(lock_text.as_bytes()))
+                        .and_then(|()| lock_file.sync_all());
+                    if let Err(e) = write_result {
+                        let _ = fs::remove_file(&lock_file_path);
+                        return Err(e);
+                    }
+                    let held_lock_path = fs::canonicalize(&lock_file_path)
+                        .unwrap_or_else(|_| lock_file_path.clone());
+                    held_lock_files().push(held_lock_path.clone());
+                    return Ok(OperationLock {
+                        lock_file_path,
+                        held_lock_path,
+                        _lock_file: lock_file,
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists && !stale_lock_broken => {
+                    if let Err(error) = break_stale_lock(target_path) {
+                        report_problem!("ERROR: {}", error);
+                        return Err(error);
+                    }
+                    stale_lock_broken = true;
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    // Taken by another process since the stale one was broken
+                    let owner_pid = read_lock_file(target_path)
+                        .ok()
+                        .flatten()
+                        .map(|owner| owner.pid);
+                    let error = target_locked(&lock_file_path, owner_pid);
+                    report_problem!("ERROR: {}", error);
+                    return Err(error);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for OperationLock {
+    fn drop(&mut self) {
+        // Removed while the OS lock is still held, so no other process can
+        // take the file for stale and break a lock it has just taken
+        let _ = fs::remove_file(&self.lock_file_path);
+        let mut held_lock_paths = held_lock_files();
+        if let Some(index) = held_lock_paths
+            .iter()
+            .position(|path| path == &self.held_lock_path)
+        {
+            held_lock_paths.swap_remove(index);
+        }
+    }
+}
+
+fn held_lock_files() -> MutexGuard<'static, Vec<PathBuf>> {
+    HELD_LOCK_FILES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Whether an `OperationLock` in this process holds `lock_file_path`.
+fn is_held_by_this_process(lock_file_path: &Path) -> bool {
+    // A lock file that does not exist is not held
+    fs::canonicalize(lock_file_path)
+        .is_ok_and(|canonical_path| held_lock_files().contains(&canonical_path))
+}
+
+fn target_locked(lock_file_path: &Path, owner_pid: Option<u32>) -> io::Error {
+    ByteOpError::TargetLocked {
+        path: lock_file_path.to_path_buf(),
+        owner_pid,
+    }
+    .into()
+}
+
+fn parse_lock_text(lock_text: &str, lock_file_path: &Path) -> io::Result<LockOwner> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", lock_file_path.display(), reason),
+        )
+    };
+    let mut lines = lock_text.lines();
+    if lines.next() != Some(LOCK_FILE_HEADER) {
+        return Err(invalid("not a bfbo lock file (or an unsupported version)"));
+    }
+
+    let mut pid = None;
+    let mut created = None;
+    for line in lines {
+        match line.split_once(": ") {
+            Some(("pid", value)) => {
+                pid = Some(value.parse::<u32>().map_err(|_| invalid("bad pid"))?);
+            }
+            Some(("created", value)) => created = Some(value.to_string()),
+            _ => return Err(invalid("malformed line")),
+        }
+    }
+    Ok(LockOwner {
+        pid: pid.ok_or_else(|| invalid("missing field: pid"))?,
+        created: created.ok_or_else(|| invalid("missing field: created"))?,
+    })
+}
+
+/// Whether an open handle, in this process or another, holds the OS lock
+/// on `lock_file_path`; `None` where lock files have no OS lock.
+fn os_lock_is_held(lock_file_path: &Path) -> io::Result<Option<bool>> {
+    if !OS_LOCKS_AVAILABLE {
+        return Ok(None);
+    }
+    let probe_file = File::open(lock_file_path)?;
+    // Whatever lock the probe gets is released when it is closed
+    Ok(Some(!lock_exclusive(&probe_file, false)?))
+}
+
+/// Takes the exclusive `flock` on `lock_file`. Without `wait`, returns
+/// `Ok(false)` at once if another open file description holds it.
+#[cfg(unix)]
+fn lock_exclusive(lock_file: &File, wait: bool) -> io::Result<bool> {
+    use std::os::fd::AsRawFd;
+
+    const LOCK_EX: i32 = 2;
+    const LOCK_NB: i32 = 4;
+    unsafe extern "C" {
+        fn flock(fd: i32, operation: i32) -> i32;
+    }
+
+    let operation = if wait { LOCK_EX } else { LOCK_EX | LOCK_NB };
+    loop {
+        // SAFETY: the descriptor belongs to `lock_file`, which outlives the
+        // call; flock only changes the lock state of the open file
+        if unsafe { flock(lock_file.as_raw_fd(), operation) } == 0 {
+            return Ok(true);
+        }
+        let error = io::Error::last_os_error();
+        match error.kind() {
+            io::ErrorKind::Interrupted => continue,
+            io::ErrorKind::WouldBlock if !wait => return Ok(false),
+            _ => return Err(error),
+        }
+    }
+}
+
+/// Takes an exclusive `LockFileEx` lock on `lock_file`. Without `wait`,
+/// returns `Ok(false)` at once if another handle holds it.
+#[cfg(windows)]
+fn lock_exclusive(lock_file: &File, wait: bool) -> io::Result<bool> {
+    use std::ffi::c_void;
+    use std::os::windows::io::AsRawHandle;
+
+    const LOCKFILE_FAIL_IMMEDIATELY: u32 = 0x1;
+    const LOCKFILE_EXCLUSIVE_LOCK: u32 = 0x2;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    // Windows byte-range locks are mandatory, so the locked byte lies far
+    // past the end of the file, where it blocks no reader of the text
+    const LOCKED_BYTE_OFFSET_HIGH: u32 = 0x7FFF_FFFF;
+
+    #[repr(C)]
+    struct Overlapped {
+        internal: usize,
+        internal_high: usize,
+        offset: u32,
+        offset_high: u32,
+        event: *mut c_void,
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn LockFileEx(
+            file: *mut c_void,
+            flags: u32,
+            reserved: u32,
+            length_low: u32,
+            length_high: u32,
+            overlapped: *mut Overlapped,
+        ) -> i32;
+    }
+
+    let flags = if wait {
+        LOCKFILE_EXCLUSIVE_LOCK
+    } else {
+        LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY
+    };
+    let mut overlapped = Overlapped {
+        internal: 0,
+        internal_high: 0,
+        offset: 0,
+        offset_high: LOCKED_BYTE_OFFSET_HIGH,
+        event: std::ptr::null_mut(),
+    };
+    // SAFETY: the handle belongs to `lock_file`, which outlives the call;
+    // the handle is synchronous, so `overlapped` is only used during it
+    let locked = unsafe { LockFileEx(lock_file.as_raw_handle(), flags, 0, 1, 0, &mut overlapped) };
+    if locked != 0 {
+        return Ok(true);
+    }
+    let error = io::Error::last_os_error();
+    if !wait && error.raw_os_error() == Some(ERROR_LOCK_VIOLATION) {
+        return Ok(false);
+    }
+    Err(error)
+}
+
+/// Without OS locks the lock file alone guards the target.
+#[cfg(not(any(unix, windows)))]
+fn lock_exclusive(_lock_file: &File, _wait: bool) -> io::Result<bool> {
+    Ok(true)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_lock_refused_and_stale_lock_broken() {
+        let directory = std::env::temp_dir().join("test_lock_file");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        fs::write(&target_path, b"ABCD").expect("write target");
+        let lock_path = lock_file_path(&target_path).expect("lock path");
+
+        // Held by this process: a nested operation is refused as such
+        let operation_lock = OperationLock::acquire(&target_path).expect("acquire");
+        assert_eq!(
+            read_lock_file(&target_path)
+                .expect("read lock")
+                .map(|owner| owner.pid),
+            Some(process::id())
+        );
+        let error = crate::replace_single_byte_in_file(target_path.clone(), 0, b'X')
+            .expect_err("target locked");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::LockHeldByThisProcess { .. })
+        ));
+        assert_eq!(error.kind(), io::ErrorKind::ResourceBusy);
+        // The held OS lock (or, without one, the live PID) keeps it live
+        let error = break_stale_lock(&target_path).expect_err("live lock");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::TargetLocked {
+                owner_pid: Some(_),
+                ..
+            })
+        ));
+        assert!(lock_path.exists());
+        drop(operation_lock);
+        assert!(!lock_path.exists());
+        let operation_lock = OperationLock::acquire(&target_path).expect("re-acquire");
+        drop(operation_lock);
+
+        // Left by a process that is gone: broken, and the edit goes ahead
+        fs::write(
+            &lock_path,
+            format!(
+                "{}\npid: {}\ncreated: 2024-01-31T12:00:00Z\n",
+                LOCK_FILE_HEADER,
+                i32::MAX
+            ),
+        )
+        .expect("write stale lock");
+        crate::replace_single_byte_in_file(target_path.clone(), 0, b'X').expect("replace");
+        assert_eq!(fs::read(&target_path).expect("read target"), b"XBCD");
+        assert!(!lock_path.exists());
+        assert!(!break_stale_lock(&target_path).expect("no lock"));
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_lock_without_os_lock_is_stale_despite_live_pid() {
+        let directory = std::env::temp_dir().join("test_lock_file_os_lock");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        fs::write(&target_path, b"ABCD").expect("write target");
+        let lock_path = lock_file_path(&target_path).expect("lock path");
+
+        // A crashed run whose PID now belongs to a live process (this one)
+        fs::write(
+            &lock_path,
+            format!(
+                "{}\npid: {}\ncreated: 2024-01-31T12:00:00Z\n",
+                LOCK_FILE_HEADER,
+                process::id()
+            ),
+        )
+        .expect("write stale lock");
+        assert!(break_stale_lock(&target_path).expect("stale lock"));
+        assert!(!lock_path.exists());
+
+        // An unreadable lock whose OS lock is held counts as live at any age
+        let held_lock_file = File::create(&lock_path).expect("create lock");
+        assert!(lock_exclusive(&held_lock_file, true).expect("lock"));
+        assert_eq!(os_lock_is_held(&lock_path).expect("probe"), Some(true));
+        assert!(break_stale_lock(&target_path).is_err());
+        drop(held_lock_file);
+        assert_eq!(os_lock_is_held(&lock_path).expect("probe"), Some(false));
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
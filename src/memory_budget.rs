@@ -0,0 +1,355 @@
+//! Explicit memory budgets
+//!
+//! Embedded and container-limited deployments need a hard memory ceiling
+//! they can state up front and trust. The engine's own working memory is
+//! fixed-size (bucket-brigade buffers of the active `OperationConfig`'s
+//! buffer size, fixed hash state);
+//! what varies with the input is a handful of features: offset and
+//! replacement lists, search results, diff windows, and the dedup index.
+//!
+//! A `MemoryBudget` is checked once, at configuration time, against the
+//! worst-case footprint of every `MemoryConsumer` an operation will use.
+//! If anything cannot fit, or has no bound at all (e.g. a dedup index over
+//! an unknown directory), admission fails before any file is touched.
+//!
+//! Set on an `OperationConfig` (`OperationConfigBuilder::memory_budget`),
+//! the budget is checked against the draft workflow's own consumers when the
+//! config is built and again when each operation starts. The entry points
+//! that hold input-sized data admit it as well before touching a file:
+//! pattern search (`byte_search`), multi-range replacement and diff reports,
+//! and `multi_edit::apply_operations`. So library callers get the same
+//! refusal as `bfbo --memory-budget`.
+//!
+//! The budget covers the engine's data, not the process baseline (binary
+//! image, stack, runtime), which callers should account for separately.
+
+use std::io;
+
+use crate::diff_report::DiffReportFormat;
+use crate::draft_workflow::STREAM_BUFFER_CAPACITY;
+use crate::error::ByteOpError;
+use crate::hash::sha256::Sha256;
+use crate::operation_config::{
+    OperationConfig, active_buffer_size, active_operation_config, with_operation_config,
+};
+use crate::patch_plan::{ByteOperation, PlanEntry};
+use crate::reporter::report_problem;
+
+/// Allowance per path held by an operation (target, backup, draft)
+const PATH_ALLOWANCE_BYTES: usize = 4096;
+
+/// Rendered diff-report bytes per hexdump line, by format (upper bound)
+const TEXT_DIFF_LINE_BYTES: usize = 96;
+const HTML_DIFF_LINE_BYTES: usize = 640;
+
+/// Something that holds memory while an operation runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryConsumer {
+    /// Backup/draft/verify buffers and artifact paths
+    DraftWorkflow,
+    /// Streaming search window plus collected match offsets
+    PatternSearch {
+        pattern_length: usize,
+        /// `None` (collect all matches) has no bound
+        max_matches: Option<usize>,
+    },
+    /// One streaming SHA-256 state and its read buffer
+    Sha256Hashing,
+    /// Offsets parsed from the command line or stdin
+    OffsetList {
+        /// `None` (read from stdin) has no bound
+        offset_count: Option<usize>,
+    },
+    /// Replacement ranges held (and sorted) in memory
+    ReplacementList {
+        entry_count: usize,
+        total_new_bytes: usize,
+    },
+    /// Rendered diff report
+    DiffReport {
+        region_count: usize,
+        total_region_bytes: usize,
+        context_bytes: usize,
+        format: DiffReportFormat,
+    },
+    /// Plan or recipe text and its parsed entries
+    PlanText { text_bytes: usize },
+    /// Chunk fingerprint index over a directory (grows with input, no bound)
+    DedupIndex,
+    /// Chain-of-custody session and rendered report (one operation)
+    CustodyReport,
+}
+
+impl MemoryConsumer {
+    /// Short name for messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemoryConsumer::DraftWorkflow => "draft workflow buffers",
+            MemoryConsumer::PatternSearch { .. } => "pattern search",
+            MemoryConsumer::Sha256Hashing => "SHA-256 state",
+            MemoryConsumer::OffsetList { .. } => "offset list",
+            MemoryConsumer::ReplacementList { .. } => "replacement list",
+            MemoryConsumer::DiffReport { .. } => "diff report",
+            MemoryConsumer::PlanText { .. } => "plan text",
+            MemoryConsumer::DedupIndex => "dedup index",
+            MemoryConsumer::CustodyReport => "custody report",
+        }
+    }
+
+    /// Worst-case bytes held, or `None` if the consumer has no bound.
+    pub fn worst_case_bytes(&self) -> Option<usize> {
+        match self {
+            MemoryConsumer::DraftWorkflow => Some(
+                4 * active_buffer_size() + 2 * STREAM_BUFFER_CAPACITY + 3 * PATH_ALLOWANCE_BYTES,
+            ),
+            MemoryConsumer::PatternSearch {
+                pattern_length,
+                max_matches,
+            } => {
+                let search_window = 2 * pattern_length + active_buffer_size();
+                max_matches
+                    .map(|max_matches| search_window + max_matches * std::mem::size_of::<usize>())
+            }
+            MemoryConsumer::Sha256Hashing => {
+                Some(std::mem::size_of::<Sha256>() + active_buffer_size())
+            }
+            MemoryConsumer::OffsetList { offset_count } => offset_count.map(|offset_count| {
+                offset_count * (std::mem::size_of::<usize>() + std::mem::size_of::<ByteOperation>())
+            }),
+            MemoryConsumer::ReplacementList {
+                entry_count,
+                total_new_bytes,
+            } => {
+                // Caller's list plus the validated, sorted copy
+                Some(2 * (entry_count * std::mem::size_of::<(usize, Vec<u8>)>() + total_new_bytes))
+            }
+            MemoryConsumer::DiffReport {
+                region_count,
+                total_region_bytes,
+                context_bytes,
+                format,
+            } => {
+                let window_bytes = total_region_bytes + region_count * (2 * context_bytes + 32);
+                let line_count = window_bytes / 16 + region_count;
+                let bytes_per_line = match format {
+                    DiffReportFormat::Text => TEXT_DIFF_LINE_BYTES,
+                    DiffReportFormat::Html => HTML_DIFF_LINE_BYTES,
+                };
+                // Changed lines are shown twice (- and +)
+                Some(2 * line_count * bytes_per_line + PATH_ALLOWANCE_BYTES)
+            }
+            MemoryConsumer::PlanText { text_bytes } => {
+                // Every entry needs at least two bytes of text (`x\n`)
+                let entry_bound = text_bytes / 2 + 1;
+                Some(2 * text_bytes + entry_bound * std::mem::size_of::<PlanEntry>())
+            }
+            MemoryConsumer::DedupIndex => None,
+            MemoryConsumer::CustodyReport => Some(4096 + 2 * PATH_ALLOWANCE_BYTES),
+        }
+    }
+}
+
+/// Worst-case accounting for an admitted configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryPlan {
+    pub line_items: Vec<(&'static str, usize)>,
+    pub total_bytes: usize,
+    pub limit_bytes: usize,
+}
+
+/// A hard ceiling on the engine's memory use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBudget {
+    pub limit_bytes: usize,
+}
+
+impl MemoryBudget {
+    pub fn new(limit_bytes: usize) -> Self {
+        MemoryBudget { limit_bytes }
+    }
+
+    /// Admits a configuration if every consumer is bounded and all fit.
+    ///
+    /// # Returns
+    /// - `Ok(MemoryPlan)` with the per-consumer worst cases
+    /// - `Err(io::Error)` (`InvalidInput`) naming the unbounded consumer, or
+    ///   carrying `ByteOpError::MemoryBudgetExceeded`
+    pub fn admit(&self, consumers: &[MemoryConsumer]) -> io::Result<MemoryPlan> {
+        let mut line_items: Vec<(&'static str, usize)> = Vec::with_capacity(consumers.len());
+        let mut total_bytes: usize = 0;
+
+        for consumer in consumers {
+            let Some(consumer_bytes) = consumer.worst_case_bytes() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Memory budget: {} has no upper bound in this configuration",
+                        consumer.name()
+                    ),
+                ));
+            };
+            total_bytes = total_bytes.saturating_add(consumer_bytes);
+            line_items.push((consumer.name(), consumer_bytes));
+        }
+
+        if total_bytes > self.limit_bytes {
+            return Err(ByteOpError::MemoryBudgetExceeded {
+                worst_case_bytes: total_bytes,
+                limit_bytes: self.limit_bytes,
+            }
+            .into());
+        }
+
+        Ok(MemoryPlan {
+            line_items,
+            total_bytes,
+            limit_bytes: self.limit_bytes,
+        })
+    }
+}
+
+/// What every draft-workflow operation holds under `config`: the workflow
+/// buffers, plus one SHA-256 pass for the journal or an expected original
+/// digest.
+pub(crate) fn draft_workflow_consumers(config: &OperationConfig) -> Vec<MemoryConsumer> {
+    let mut consumers = vec![MemoryConsumer::DraftWorkflow];
+    if config.journal() || config.expected_original_sha256().is_some() {
+        consumers.push(MemoryConsumer::Sha256Hashing);
+    }
+    consumers
+}
+
+/// Admits the draft workflow under `config`'s memory budget, sized at its
+/// buffer size; `Ok` if the config has no budget.
+pub(crate) fn admit_draft_workflow(config: &OperationConfig) -> io::Result<()> {
+    admit_under_budget(config, &draft_workflow_consumers(config))
+}
+
+/// Admits a draft-workflow operation that also holds `input_consumers`
+/// (replacement lists, diff windows) under the active config's budget.
+pub(crate) fn admit_draft_operation(input_consumers: Vec<MemoryConsumer>) -> io::Result<()> {
+    let config = active_operation_config();
+    let mut consumers = draft_workflow_consumers(&config);
+    consumers.extend(input_consumers);
+    admit_under_budget(&config, &consumers).inspect_err(|e| report_problem!("ERROR: {}", e))
+}
+
+/// Admits `consumers` under the active config's budget, for operations
+/// outside the draft workflow (search); `Ok` if there is no budget.
+pub(crate) fn admit_consumers(consumers: &[MemoryConsumer]) -> io::Result<()> {
+    admit_under_budget(&active_operation_config(), consumers)
+        .inspect_err(|e| report_problem!("ERROR: {}", e))
+}
+
+fn admit_under_budget(config: &OperationConfig, consumers: &[MemoryConsumer]) -> io::Result<()> {
+    let Some(memory_budget) = config.memory_budget() else {
+        return Ok(());
+    };
+    // Footprints are sized at `config`'s buffer size
+    with_operation_config(config, || memory_budget.admit(consumers))?;
+    Ok(())
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation_config::{OperationConfig, with_operation_config};
+
+    #[test]
+    fn test_memory_budget_admits_and_rejects() {
+        let consumers = vec![
+            MemoryConsumer::DraftWorkflow,
+            MemoryConsumer::OffsetList {
+                offset_count: Some(10),
+            },
+        ];
+        let plan = MemoryBudget::new(512 * 1024)
+            .admit(&consumers)
+            .expect("should fit");
+        assert_eq!(plan.line_items.len(), 2);
+        assert!(plan.total_bytes <= 512 * 1024);
+
+        // Too small for the default 64 KiB workflow buffers, but enough in
+        // tiny-buffer mode
+        assert!(MemoryBudget::new(64 * 1024).admit(&consumers).is_err());
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("valid config");
+        assert!(
+            with_operation_config(&tiny_config, || {
+                MemoryBudget::new(64 * 1024).admit(&consumers)
+            })
+            .is_ok()
+        );
+        assert!(MemoryBudget::new(1024).admit(&consumers).is_err());
+
+        // Unbounded consumers are rejected at any size
+        assert!(
+            MemoryBudget::new(usize::MAX)
+                .admit(&[MemoryConsumer::DedupIndex])
+                .is_err()
+        );
+        assert!(
+            MemoryBudget::new(usize::MAX)
+                .admit(&[MemoryConsumer::PatternSearch {
+                    pattern_length: 4,
+                    max_matches: None
+                }])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_library_entry_points_admit_their_inputs() {
+        let test_file = std::env::temp_dir().join("test_memory_budget_entry_points.bin");
+        std::fs::write(&test_file, vec![0xAAu8; 20 * 1024]).expect("create test file");
+        let config = OperationConfig::builder()
+            .tiny_buffer()
+            .memory_budget(40 * 1024)
+            .build()
+            .expect("tiny buffers fit");
+
+        // A search without a match cap has no bound; with one it fits
+        let error = with_operation_config(&config, || {
+            crate::byte_search::find_pattern_offsets_in_file(&test_file, &[0xAA], None)
+        })
+        .expect_err("unbounded search");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let offsets = with_operation_config(&config, || {
+            crate::byte_search::find_pattern_offsets_in_file(&test_file, &[0xAA], Some(2))
+        })
+        .expect("bounded search");
+        assert_eq!(offsets, vec![0, 1]);
+
+        // A replacement list larger than the budget is refused before the
+        // file is touched
+        let replacements = vec![(0u64, vec![0x55u8; 16 * 1024])];
+        let error = crate::range_operations::replace_multiple_byte_ranges_in_file_with_config(
+            test_file.clone(),
+            &replacements,
+            &config,
+        )
+        .expect_err("replacement list over budget");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::MemoryBudgetExceeded { .. })
+        ));
+        assert_eq!(
+            std::fs::read(&test_file).expect("read unchanged file"),
+            vec![0xAAu8; 20 * 1024]
+        );
+        crate::range_operations::replace_multiple_byte_ranges_in_file_with_config(
+            test_file.clone(),
+            &[(0, vec![0x55])],
+            &config,
+        )
+        .expect("small list fits");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
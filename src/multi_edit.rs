@@ -0,0 +1,588 @@
+//! Single-pass multi-edit: many byte operations, one draft
+//!
+//! Running N single-byte operations one after another rewrites the whole
+//! file N times, and every position after a frame-shifting edit has to be
+//! adjusted for the edits before it. `apply_operations` takes the whole
+//! list at once instead:
+//! - every position refers to the ORIGINAL file layout, so the list can be
+//!   given in any order
+//! - the operations are sorted by position and coalesced into splices
+//!   (runs of adjacent edits), which are written into one draft
+//! - the draft is verified splice by splice (gaps identical, edited bytes
+//!   as requested) before the usual single atomic rename
+//!
+//! At one original position, inserts come first (in list order), followed
+//! by the replacement or removal of the original byte, so `Insert { 4, .. }`
+//! and `Remove { 4 }` together swap out byte 4 for the inserted byte.
+//!
+//! `dry_run_operations` runs the same validation and returns a
+//! `ChangeReport` (each affected offset with its old and new value, and the
+//! resulting size) without writing anything.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    copy_exact_bytes, copy_remaining_bytes, open_buffered_reader, run_draft_workflow,
+    skip_exact_bytes, validate_target_file, verify_both_at_eof, verify_next_bytes_equal_slice,
+    verify_next_bytes_match, write_all_checked,
+};
+use crate::memory_budget::{MemoryConsumer, admit_draft_operation};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::patch_plan::ByteOperation;
+use crate::range_operations::checked_range_end;
+use crate::reporter::report_detail;
+
+/// A run of adjacent edits: `removed_length` original bytes starting at
+/// `position` are replaced by `new_bytes` (either may be empty).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Splice {
+    position: u64,
+    removed_length: u64,
+    new_bytes: Vec<u8>,
+}
+
+/// Applies replace/insert/remove operations in one draft pass.
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `operations`: Operations with positions in the original file layout,
+///   in any order; `Touch` entries are ignored
+///
+/// # Returns
+/// - `Ok(())` on success, including a list with nothing to write (the file
+///   is checked but not rewritten)
+/// - `Err(io::Error)` if a position is out of bounds (replace/remove past
+///   the last byte, insert past EOF), one original byte is replaced or
+///   removed more than once, or any phase fails; the file is untouched
+///
+/// # Example
+/// ```no_run
+/// use basic_file_byte_operations::multi_edit::apply_operations;
+/// use basic_file_byte_operations::patch_plan::ByteOperation;
+///
+/// // Original file: b"ABCDE"
+/// apply_operations(
+///     "/absolute/path/to/file.dat".into(),
+///     &[
+///         ByteOperation::Remove { position: 3 },
+///         ByteOperation::Replace { position: 0, new_byte_value: b'a' },
+///         ByteOperation::Insert { position: 5, new_byte_value: b'!' },
+///     ],
+/// )?;
+/// // Resulting file: b"aBCE!"
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn apply_operations(
+    original_file_path: PathBuf,
+    operations: &[ByteOperation],
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+    // The operations and their sorted, coalesced copies
+    admit_draft_operation(vec![MemoryConsumer::OffsetList {
+        offset_count: Some(operations.len()),
+    }])?;
+    let splices = coalesce_splices(&sort_edits(operations, original_file_size)?);
+
+    if splices.is_empty() {
+        report_detail!("No byte edits to apply: {}", original_file_path.display());
+        return Ok(());
+    }
+
+    let mut expected_draft_size = original_file_size;
+    for splice in &splices {
+        expected_draft_size = checked_range_end(
+            expected_draft_size - splice.removed_length,
+            splice.new_bytes.len() as u64,
+        )?;
+    }
+    report_detail!(
+        "{} operations coalesced into {} spliced regions",
+        operations.len(),
+        splices.len()
+    );
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Multi-Edit",
+        expected_draft_size,
+        |source_file, draft_file| build_spliced_draft(source_file, draft_file, &splices),
+        |original_path, draft_path| {
+            verify_spliced_draft(original_path, draft_path, &splices, original_file_size)
+        },
+    )
+}
+
+/// Like `apply_operations`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn apply_operations_with_config(
+    original_file_path: PathBuf,
+    operations: &[ByteOperation],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || apply_operations(original_file_path, operations))
+}
+
+/// One operation with its position in the original layout.
+#[derive(Debug, Clone, Copy)]
+struct SortedEdit {
+    position: u64,
+    /// Replace and remove consume the original byte; insert does not
+    consumes_original_byte: bool,
+    /// `None` for a removal
+    new_byte_value: Option<u8>,
+}
+
+/// Checks the operations against the original size and returns them
+/// sorted in file order (`Touch` dropped).
+fn sort_edits(
+    operations: &[ByteOperation],
+    original_file_size: u64,
+) -> io::Result<Vec<SortedEdit>> {
+    let mut edits: Vec<SortedEdit> = Vec::with_capacity(operations.len());
+    for operation in operations {
+        let edit = match *operation {
+            ByteOperation::Replace {
+                position,
+                new_byte_value,
+            } => SortedEdit {
+                position,
+                consumes_original_byte: true,
+                new_byte_value: Some(new_byte_value),
+            },
+            ByteOperation::Insert {
+                position,
+                new_byte_value,
+            } => SortedEdit {
+                position,
+                consumes_original_byte: false,
+                new_byte_value: Some(new_byte_value),
+            },
+            ByteOperation::Remove { position } => SortedEdit {
+                position,
+                consumes_original_byte: true,
+                new_byte_value: None,
+            },
+            ByteOperation::Touch => continue,
+        };
+
+        // Insertion at EOF (position == size) is an append
+        if (edit.consumes_original_byte && edit.position >= original_file_size)
+            || edit.position > original_file_size
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{:?}: position exceeds file size {}",
+                    operation, original_file_size
+                ),
+            ));
+        }
+        edits.push(edit);
+    }
+
+    // Stable: inserts at one position keep their list order, ahead of the
+    // replacement/removal of the original byte there
+    edits.sort_by_key(|edit| (edit.position, edit.consumes_original_byte));
+
+    for pair in edits.windows(2) {
+        if pair[0].consumes_original_byte
+            && pair[1].consumes_original_byte
+            && pair[0].position == pair[1].position
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Byte {} is replaced or removed more than once",
+                    pair[0].position
+                ),
+            ));
+        }
+    }
+
+    Ok(edits)
+}
+
+/// Coalesces sorted edits into non-overlapping splices.
+fn coalesce_splices(edits: &[SortedEdit]) -> Vec<Splice> {
+    let mut splices: Vec<Splice> = Vec::new();
+    for edit in edits {
+        let continues_last_splice = splices
+            .last()
+            .is_some_and(|splice| splice.position + splice.removed_length == edit.position);
+        if !continues_last_splice {
+            splices.push(Splice {
+                position: edit.position,
+                removed_length: 0,
+                new_bytes: Vec::new(),
+            });
+        }
+
+        if let Some(splice) = splices.last_mut() {
+            if edit.consumes_original_byte {
+                splice.removed_length += 1;
+            }
+            splice.new_bytes.extend(edit.new_byte_value);
+        }
+    }
+    splices
+}
+
+/// Draft builder: copy each gap, drop the removed bytes, write the new ones.
+fn build_spliced_draft<R: Read + Seek + 'static, W: Write + 'static>(
+    source_file: &mut R,
+    draft_file: &mut W,
+    splices: &[Splice],
+) -> io::Result<()> {
+    let mut source_position: u64 = 0;
+    for splice in splices {
+        copy_exact_bytes(source_file, draft_file, splice.position - source_position)?;
+        skip_exact_bytes(source_file, splice.removed_length)?;
+        write_all_checked(draft_file, &splice.new_bytes)?;
+        source_position = splice.position + splice.removed_length;
+    }
+    copy_remaining_bytes(source_file, draft_file)?;
+    Ok(())
+}
+
+/// Verifier: every gap identical (at its frame-shifted draft position),
+/// every splice equal to its new bytes.
+fn verify_spliced_draft(
+    original_path: &Path,
+    draft_path: &Path,
+    splices: &[Splice],
+    original_file_size: u64,
+) -> io::Result<()> {
+    let mut original_file = open_buffered_reader(original_path)?;
+    let mut draft_file = open_buffered_reader(draft_path)?;
+
+    let mut original_position: u64 = 0;
+    let mut draft_position: u64 = 0;
+    for splice in splices {
+        let gap_length = splice.position - original_position;
+        verify_next_bytes_match(
+            &mut original_file,
+            &mut draft_file,
+            gap_length,
+            original_position,
+            draft_position,
+            "Unchanged gap",
+        )?;
+        draft_position += gap_length;
+
+        // Frame-shift: removed bytes exist only in the original,
+        // new bytes only in the draft
+        skip_exact_bytes(&mut original_file, splice.removed_length)?;
+        verify_next_bytes_equal_slice(
+            &mut draft_file,
+            &splice.new_bytes,
+            draft_position,
+            "Spliced region",
+        )?;
+        original_position = splice.position + splice.removed_length;
+        draft_position += splice.new_bytes.len() as u64;
+    }
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        original_file_size - original_position,
+        original_position,
+        draft_position,
+        "Unchanged tail",
+    )?;
+
+    verify_both_at_eof(&mut original_file, &mut draft_file)
+}
+
+// ============================
+// Dry Run
+// ============================
+
+/// One byte `apply_operations` would change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteChange {
+    /// Position in the original file; for an insert, the byte it goes before
+    pub original_position: u64,
+    /// Position in the resulting file (`None`: removed)
+    pub resulting_position: Option<u64>,
+    /// `None`: inserted
+    pub old_byte_value: Option<u8>,
+    /// `None`: removed
+    pub new_byte_value: Option<u8>,
+}
+
+/// What `apply_operations` would do, computed without writing anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeReport {
+    pub target_path: PathBuf,
+    pub original_file_size: u64,
+    pub resulting_file_size: u64,
+    /// In file order
+    pub changes: Vec<ByteChange>,
+}
+
+impl fmt::Display for ChangeReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            formatter,
+            "{}: {} byte change(s), size {} -> {}",
+            self.target_path.display(),
+            self.changes.len(),
+            self.original_file_size,
+            self.resulting_file_size
+        )?;
+        for change in &self.changes {
+            match (
+                change.old_byte_value,
+                change.new_byte_value,
+                change.resulting_position,
+            ) {
+                (Some(old_byte_value), Some(new_byte_value), _) => writeln!(
+                    formatter,
+                    "  replace at {}: 0x{:02X} -> 0x{:02X}",
+                    change.original_position, old_byte_value, new_byte_value
+                )?,
+                (None, Some(new_byte_value), Some(resulting_position)) => writeln!(
+                    formatter,
+                    "  insert before {} (at {}): 0x{:02X}",
+                    change.original_position, resulting_position, new_byte_value
+                )?,
+                (Some(old_byte_value), None, _) => writeln!(
+                    formatter,
+                    "  remove at {}: 0x{:02X}",
+                    change.original_position, old_byte_value
+                )?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validates `operations` exactly as `apply_operations` would and reports
+/// the changes, writing nothing (no backup, no draft).
+///
+/// The old byte values are read from the file, so the report shows each
+/// affected offset with its current and new value.
+///
+/// # Returns
+/// - `Ok(ChangeReport)` listing every changed byte in file order
+/// - `Err(io::Error)` where `apply_operations` would fail validation, or
+///   if the file cannot be read
+pub fn dry_run_operations(
+    original_file_path: PathBuf,
+    operations: &[ByteOperation],
+) -> io::Result<ChangeReport> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let edits = sort_edits(operations, original_file_size)?;
+
+    let mut original_file = File::open(&original_file_path)?;
+    let mut changes = Vec::with_capacity(edits.len());
+    let mut inserted_count: u64 = 0;
+    let mut removed_count: u64 = 0;
+    for edit in &edits {
+        // Every removal so far was of an earlier byte, so this cannot underflow
+        let resulting_position = edit.position - removed_count + inserted_count;
+        let old_byte_value = if edit.consumes_original_byte {
+            original_file.seek(SeekFrom::Start(edit.position))?;
+            let mut old_byte = [0u8; 1];
+            original_file.read_exact(&mut old_byte)?;
+            Some(old_byte[0])
+        } else {
+            None
+        };
+
+        match (edit.consumes_original_byte, edit.new_byte_value) {
+            (false, _) => inserted_count += 1,
+            (true, None) => removed_count += 1,
+            (true, Some(_)) => {}
+        }
+        changes.push(ByteChange {
+            original_position: edit.position,
+            resulting_position: edit.new_byte_value.map(|_| resulting_position),
+            old_byte_value,
+            new_byte_value: edit.new_byte_value,
+        });
+    }
+
+    let resulting_file_size =
+        checked_range_end(original_file_size - removed_count, inserted_count)?;
+    Ok(ChangeReport {
+        target_path: original_file_path,
+        original_file_size,
+        resulting_file_size,
+        changes,
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace(position: u64, new_byte_value: u8) -> ByteOperation {
+        ByteOperation::Replace {
+            position,
+            new_byte_value,
+        }
+    }
+
+    fn insert(position: u64, new_byte_value: u8) -> ByteOperation {
+        ByteOperation::Insert {
+            position,
+            new_byte_value,
+        }
+    }
+
+    #[test]
+    fn test_apply_operations_uses_original_layout() {
+        let test_file = std::env::temp_dir().join("test_apply_operations_layout.bin");
+        std::fs::write(&test_file, b"ABCDEFGH").expect("create test file");
+
+        apply_operations(
+            test_file.clone(),
+            &[
+                ByteOperation::Remove { position: 7 },
+                insert(0, b'<'),
+                replace(2, b'c'),
+                ByteOperation::Remove { position: 3 },
+                insert(3, b'x'),
+                insert(3, b'y'),
+                ByteOperation::Touch,
+                insert(8, b'>'),
+            ],
+        )
+        .expect("apply operations");
+
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"<ABcxyEFG>");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_apply_operations_rejects_conflicts_and_bounds() {
+        let test_file = std::env::temp_dir().join("test_apply_operations_rejects.bin");
+        std::fs::write(&test_file, b"ABCD").expect("create test file");
+
+        for operations in [
+            vec![replace(1, b'x'), ByteOperation::Remove { position: 1 }],
+            vec![replace(2, b'x'), replace(2, b'y')],
+            vec![replace(4, b'x')],
+            vec![ByteOperation::Remove { position: 4 }],
+            vec![insert(5, b'x')],
+        ] {
+            let error = apply_operations(test_file.clone(), &operations).expect_err("rejected");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ABCD");
+
+        // Nothing to write: checked, not rewritten
+        apply_operations(test_file.clone(), &[ByteOperation::Touch]).expect("touch only");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ABCD");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_coalesce_splices_merges_adjacent_edits() {
+        let edits = sort_edits(
+            &[
+                replace(1, b'b'),
+                ByteOperation::Remove { position: 2 },
+                insert(3, b'!'),
+                replace(6, b'g'),
+            ],
+            8,
+        )
+        .expect("valid operations");
+        let splices = coalesce_splices(&edits);
+
+        assert_eq!(
+            splices,
+            vec![
+                Splice {
+                    position: 1,
+                    removed_length: 2,
+                    new_bytes: b"b!".to_vec(),
+                },
+                Splice {
+                    position: 6,
+                    removed_length: 1,
+                    new_bytes: b"g".to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dry_run_operations_reports_without_writing() {
+        let test_file = std::env::temp_dir().join("test_dry_run_operations.bin");
+        std::fs::write(&test_file, b"ABCDE").expect("create test file");
+        let operations = [
+            ByteOperation::Remove { position: 1 },
+            replace(3, b'd'),
+            insert(3, b'+'),
+            insert(5, b'!'),
+        ];
+
+        let report = dry_run_operations(test_file.clone(), &operations).expect("dry run");
+        assert_eq!(report.original_file_size, 5);
+        assert_eq!(report.resulting_file_size, 6);
+        assert_eq!(
+            report.changes,
+            vec![
+                ByteChange {
+                    original_position: 1,
+                    resulting_position: None,
+                    old_byte_value: Some(b'B'),
+                    new_byte_value: None,
+                },
+                ByteChange {
+                    original_position: 3,
+                    resulting_position: Some(2),
+                    old_byte_value: None,
+                    new_byte_value: Some(b'+'),
+                },
+                ByteChange {
+                    original_position: 3,
+                    resulting_position: Some(3),
+                    old_byte_value: Some(b'D'),
+                    new_byte_value: Some(b'd'),
+                },
+                ByteChange {
+                    original_position: 5,
+                    resulting_position: Some(5),
+                    old_byte_value: None,
+                    new_byte_value: Some(b'!'),
+                },
+            ]
+        );
+        let report_text = report.to_string();
+        assert!(report_text.contains("4 byte change(s), size 5 -> 6"));
+        assert!(report_text.contains("replace at 3: 0x44 -> 0x64"));
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"ABCDE");
+
+        // Same validation as the real run
+        assert!(dry_run_operations(test_file.clone(), &[replace(5, 0)]).is_err());
+
+        // The report describes what the real run produces
+        apply_operations(test_file.clone(), &operations).expect("apply operations");
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"AC+dE!");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,521 @@
+//! File positions counted from the start or from the end
+//!
+//! The single-byte operations and the basic range operations take
+//! `impl Into<Offset>`, so a plain `u64` still means "from the start" and
+//! callers can name a byte relative to EOF without stat-ing the file first:
+//!
+//! ```no_run
+//! use basic_file_byte_operations::{offset::Offset, replace_single_byte_in_file};
+//!
+//! // The 4th byte from the end
+//! replace_single_byte_in_file("data.bin".into(), Offset::FromEnd(4), 0x00)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! `FromEnd(n)` resolves to `file_size - n`, like `SeekFrom::End(-n)`:
+//! `FromEnd(1)` is the last byte and `FromEnd(0)` is EOF (the append point
+//! for insertion). The file size is the one the operation reads during its
+//! own validation, so the position is resolved exactly once per operation.
+//!
+//! [`parse_offset`] reads an `Offset` from text such as `0x1F4 + 2*sector`,
+//! `4KiB` or `end-512`, so the library and the CLI accept the same
+//! expressions.
+
+use std::{fmt, io};
+
+use crate::error::ByteOpError;
+
+/// A byte position, counted from the start or from the end of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offset {
+    /// Zero-indexed position from the first byte
+    FromStart(u64),
+    /// Distance back from EOF: `FromEnd(1)` is the last byte
+    FromEnd(u64),
+}
+
+impl Offset {
+    /// Absolute position in a file of `file_size` bytes.
+    ///
+    /// # Returns
+    /// - `Ok(position)`; a `FromStart` position is returned as given (the
+    ///   operation checks it against its own valid range)
+    /// - `Err(io::Error)` carrying `ByteOpError::OffsetBeforeStart` if a
+    ///   `FromEnd` distance exceeds the file size
+    pub fn resolve(self, file_size: u64) -> io::Result<u64> {
+        match self {
+            Offset::FromStart(position) => Ok(position),
+            Offset::FromEnd(distance_from_end) => {
+                file_size.checked_sub(distance_from_end).ok_or_else(|| {
+                    ByteOpError::OffsetBeforeStart {
+                        distance_from_end,
+                        file_size,
+                    }
+                    .into()
+                })
+            }
+        }
+    }
+}
+
+impl From<u64> for Offset {
+    fn from(position: u64) -> Self {
+        Offset::FromStart(position)
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Offset::FromStart(position) => write!(formatter, "{}", position),
+            Offset::FromEnd(distance_from_end) => write!(formatter, "EOF-{}", distance_from_end),
+        }
+    }
+}
+
+/// Bytes per `sector` in [`parse_offset`] expressions
+pub const DEFAULT_SECTOR_SIZE: u64 = 512;
+
+/// Parses an offset expression, with `sector` meaning 512 bytes.
+///
+/// See [`parse_offset_with_sector_size`] for the grammar.
+pub fn parse_offset(text: &str) -> io::Result<Offset> {
+    parse_offset_with_sector_size(text, DEFAULT_SECTOR_SIZE)
+}
+
+/// Parses an offset expression with a caller-chosen `sector` size
+/// (e.g. 4096 for Advanced Format disks).
+///
+/// An expression is a sum of terms joined by `+` or `-`. A term is a
+/// product of factors joined by `*`. A factor is a decimal or `0x` hex
+/// number, optionally followed by a unit, or a unit on its own:
+///
+/// | Unit | Bytes |
+/// |------|-------|
+/// | `B` | 1 |
+/// | `KiB` | 1024 |
+/// | `MiB` | 1024² |
+/// | `GiB` | 1024³ |
+/// | `sector`, `sectors` | `sector_size` |
+///
+/// Units are case-insensitive and whitespace is ignored between tokens.
+/// A leading `end` makes the offset relative to EOF: `end` alone is
+/// `FromEnd(0)`, and `end-N` (where `N` may itself be an expression such
+/// as `end - 2*sector + 16`) is `FromEnd(N)`.
+///
+/// # Returns
+/// - `Ok(Offset)` if the whole text parses and every step stays within `u64`
+/// - `Err(io::Error)` with `ErrorKind::InvalidInput` naming the problem
+///   (unknown unit, missing operand, overflow, a negative result, ...)
+pub fn parse_offset_with_sector_size(text: &str, sector_size: u64) -> io::Result<Offset> {
+    let tokens = tokenize_offset_expression(text)?;
+    let relative_to_end = matches!(tokens.first(), Some(OffsetToken::End));
+    let expression_tokens = if relative_to_end {
+        &tokens[1..]
+    } else {
+        &tokens[..]
+    };
+
+    if relative_to_end && expression_tokens.is_empty() {
+        return Ok(Offset::FromEnd(0));
+    }
+    if relative_to_end && expression_tokens.first() != Some(&OffsetToken::Minus) {
+        return Err(invalid_offset(text, "`end` must be followed by `-`"));
+    }
+
+    let total = evaluate_offset_sum(expression_tokens, sector_size, text)?;
+    // Past `end` the sum starts with `-`, so the distance back is its negation
+    let value = if relative_to_end { -total } else { total };
+    if value < 0 {
+        return Err(invalid_offset(
+            text,
+            if relative_to_end {
+                "resolves past EOF"
+            } else {
+                "resolves before the start of the file"
+            },
+        ));
+    }
+    let value =
+        u64::try_from(value).map_err(|_| invalid_offset(text, "does not fit in 64 bits"))?;
+
+    Ok(if relative_to_end {
+        Offset::FromEnd(value)
+    } else {
+        Offset::FromStart(value)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OffsetToken {
+    Number(u64),
+    Unit(u64),
+    End,
+    Plus,
+    Minus,
+    Times,
+}
+
+fn tokenize_offset_expression(text: &str) -> io::Result<Vec<OffsetToken>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+        if byte.is_ascii_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        let token_start = index;
+        let token =
+            match byte {
+                b'+' => {
+                    index += 1;
+                    OffsetToken::Plus
+                }
+                b'-' => {
+                    index += 1;
+                    OffsetToken::Minus
+                }
+                b'*' => {
+                    index += 1;
+                    OffsetToken::Times
+                }
+                b'0'..=b'9' => {
+                    let is_hex = byte == b'0' && matches!(bytes.get(index + 1), Some(b'x' | b'X'));
+                    let (digits_start, radix) = if is_hex { (index + 2, 16) } else { (index, 10) };
+                    index = digits_start;
+                    while index < bytes.len() && (bytes[index] as char).is_digit(radix) {
+                        index += 1;
+                    }
+                    let digits = &text[digits_start..index];
+                    let number = u64::from_str_radix(digits, radix).map_err(|_| {
+                        invalid_offset(
+                            text,
+                            &format!("invalid number `{}`", &text[token_start..index]),
+                        )
+                    })?;
+                    OffsetToken::Number(number)
+                }
+                _ if byte.is_ascii_alphabetic() => {
+                    while index < bytes.len() && bytes[index].is_ascii_alphabetic() {
+                        index += 1;
+                    }
+                    let word = &text[token_start..index];
+                    if word.eq_ignore_ascii_case("end") {
+                        if !tokens.is_empty() {
+                            return Err(invalid_offset(text, "`end` may only appear first"));
+                        }
+                        OffsetToken::End
+                    } else {
+                        OffsetToken::Unit(unit_size(word).ok_or_else(|| {
+                            invalid_offset(text, &format!("unknown unit `{}`", word))
+                        })?)
+                    }
+                }
+                _ => {
+                    let character = text[index..].chars().next().unwrap_or('?');
+                    return Err(invalid_offset(
+                        text,
+                        &format!("unexpected character `{}`", character),
+                    ));
+                }
+            };
+        tokens.push(token);
+    }
+
+    if tokens.is_empty() {
+        return Err(invalid_offset(text, "empty expression"));
+    }
+    Ok(tokens)
+}
+
+/// Unit multipliers; `sector` is 0 here and replaced by the caller's size.
+fn unit_size(word: &str) -> Option<u64> {
+    const UNITS: [(&str, u64); 6] = [
+        ("b", 1),
+        ("kib", 1 << 10),
+        ("mib", 1 << 20),
+        ("gib", 1 << 30),
+        ("sector", 0),
+        ("sectors", 0),
+    ];
+    UNITS
+        .iter()
+        .find(|(name, _)| word.eq_ignore_ascii_case(name))
+        .map(|&(_, size)| size)
+}
+
+/// Sums signed terms; a leading sign is allowed so `end-N` can reuse this.
+fn evaluate_offset_sum(tokens: &[OffsetToken], sector_size: u64, text: &str) -> io::Result<i128> {
+    let mut total: i128 = 0;
+    let mut index = 0;
+    let mut negative = false;
+    if let Some(OffsetToken::Plus | OffsetToken::Minus) = tokens.first() {
+        negative = tokens[0] == OffsetToken::Minus;
+        index = 1;
+    }
+
+    loop {
+        let (term, next_index) = evaluate_offset_term(tokens, index, sector_size, text)?;
+        let term = i128::from(term);
+        total = if negative {
+            total.checked_sub(term)
+        } else {
+            total.checked_add(term)
+        }
+        .ok_or_else(|| invalid_offset(text, "overflows"))?;
+
+        match tokens.get(next_index) {
+            None => return Ok(total),
+            Some(OffsetToken::Plus) => negative = false,
+            Some(OffsetToken::Minus) => negative = true,
+            Some(_) => return Err(invalid_offset(text, "expected `+`, `-` or `*`")),
+        }
+        index = next_index + 1;
+    }
+}
+
+/// Evaluates `factor (* factor)*` starting at `index`; returns the product
+/// and the index of the first token after it.
+fn evaluate_offset_term(
+    tokens: &[OffsetToken],
+    mut index: usize,
+    sector_size: u64,
+    text: &str,
+) -> io::Result<(u64, usize)> {
+    let mut product: u64 = 1;
+    loop {
+        let factor = match tokens.get(index) {
+            Some(OffsetToken::Number(number)) => {
+                index += 1;
+                match tokens.get(index) {
+                    Some(&OffsetToken::Unit(size)) => {
+                        index += 1;
+                        number.checked_mul(resolve_unit(size, sector_size))
+                    }
+                    _ => Some(*number),
+                }
+            }
+            Some(&OffsetToken::Unit(size)) => {
+                index += 1;
+                Some(resolve_unit(size, sector_size))
+            }
+            _ => return Err(invalid_offset(text, "expected a number or unit")),
+        };
+        product = factor
+            .and_then(|factor| product.checked_mul(factor))
+            .ok_or_else(|| invalid_offset(text, "overflows"))?;
+
+        if tokens.get(index) != Some(&OffsetToken::Times) {
+            return Ok((product, index));
+        }
+        index += 1;
+    }
+}
+
+fn resolve_unit(size: u64, sector_size: u64) -> u64 {
+    if size == 0 { sector_size } else { size }
+}
+
+fn invalid_offset(text: &str, problem: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid offset expression `{}`: {}", text, problem),
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_resolves_from_either_end() {
+        assert_eq!(Offset::from(7).resolve(10).expect("from start"), 7);
+        assert_eq!(Offset::FromEnd(1).resolve(10).expect("last byte"), 9);
+        assert_eq!(Offset::FromEnd(0).resolve(10).expect("EOF"), 10);
+        assert_eq!(Offset::FromEnd(10).resolve(10).expect("first byte"), 0);
+
+        let error = Offset::FromEnd(11).resolve(10).expect_err("before start");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::OffsetBeforeStart {
+                distance_from_end: 11,
+                file_size: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_offset_numbers_and_units() {
+        assert_eq!(
+            parse_offset("500").expect("parse 500"),
+            Offset::FromStart(500)
+        );
+        assert_eq!(
+            parse_offset("0x1F4").expect("parse 0x1F4"),
+            Offset::FromStart(500)
+        );
+        assert_eq!(
+            parse_offset("0X1f4").expect("parse 0X1f4"),
+            Offset::FromStart(500)
+        );
+        assert_eq!(
+            parse_offset("4KiB").expect("parse 4KiB"),
+            Offset::FromStart(4096)
+        );
+        assert_eq!(
+            parse_offset("4 kib").expect("parse 4 kib"),
+            Offset::FromStart(4096)
+        );
+        assert_eq!(
+            parse_offset("1MiB").expect("parse 1MiB"),
+            Offset::FromStart(1 << 20)
+        );
+        assert_eq!(
+            parse_offset("2GiB").expect("parse 2GiB"),
+            Offset::FromStart(2 << 30)
+        );
+        assert_eq!(
+            parse_offset("16B").expect("parse 16B"),
+            Offset::FromStart(16)
+        );
+        assert_eq!(
+            parse_offset("0x10KiB").expect("parse 0x10KiB"),
+            Offset::FromStart(16 * 1024)
+        );
+        assert_eq!(
+            parse_offset("sector").expect("parse sector"),
+            Offset::FromStart(512)
+        );
+        assert_eq!(
+            parse_offset("3 sectors").expect("parse 3 sectors"),
+            Offset::FromStart(1536)
+        );
+    }
+
+    #[test]
+    fn test_parse_offset_arithmetic() {
+        assert_eq!(
+            parse_offset("0x1F4 + 2*sector").expect("parse 0x1F4 + 2*sector"),
+            Offset::FromStart(500 + 1024)
+        );
+        assert_eq!(
+            parse_offset("1KiB-1").expect("parse 1KiB-1"),
+            Offset::FromStart(1023)
+        );
+        assert_eq!(
+            parse_offset("2*3+4*5").expect("parse 2*3+4*5"),
+            Offset::FromStart(26)
+        );
+        assert_eq!(
+            parse_offset("10 - 4 - 3").expect("parse 10 - 4 - 3"),
+            Offset::FromStart(3)
+        );
+        assert_eq!(
+            parse_offset("2 * 2 * sector").expect("parse 2 * 2 * sector"),
+            Offset::FromStart(2048)
+        );
+        assert_eq!(parse_offset("+7").expect("parse +7"), Offset::FromStart(7));
+        // Intermediate negatives are fine if the total is not
+        assert_eq!(
+            parse_offset("1 - 2 + 3").expect("parse 1 - 2 + 3"),
+            Offset::FromStart(2)
+        );
+    }
+
+    #[test]
+    fn test_parse_offset_sector_size() {
+        assert_eq!(
+            parse_offset_with_sector_size("2*sector", 4096)
+                .expect("parse 2*sector with 4 KiB sectors"),
+            Offset::FromStart(8192)
+        );
+        assert_eq!(
+            parse_offset_with_sector_size("end-sector", 4096)
+                .expect("parse end-sector with 4 KiB sectors"),
+            Offset::FromEnd(4096)
+        );
+    }
+
+    #[test]
+    fn test_parse_offset_end_relative() {
+        assert_eq!(parse_offset("end").expect("parse end"), Offset::FromEnd(0));
+        assert_eq!(parse_offset("END").expect("parse END"), Offset::FromEnd(0));
+        assert_eq!(
+            parse_offset("end-1").expect("parse end-1"),
+            Offset::FromEnd(1)
+        );
+        assert_eq!(
+            parse_offset("end - 0x10").expect("parse end - 0x10"),
+            Offset::FromEnd(16)
+        );
+        assert_eq!(
+            parse_offset("end-2*sector").expect("parse end-2*sector"),
+            Offset::FromEnd(1024)
+        );
+        // Read left to right: end - 1024 + 16 is 1008 back from EOF
+        assert_eq!(
+            parse_offset("end - 2*sector + 16").expect("parse end - 2*sector + 16"),
+            Offset::FromEnd(1008)
+        );
+        assert_eq!(
+            parse_offset("end-4+4").expect("parse end-4+4"),
+            Offset::FromEnd(0)
+        );
+    }
+
+    #[test]
+    fn test_parse_offset_rejects_bad_expressions() {
+        for text in [
+            "",
+            "   ",
+            "-1",
+            "1-2",
+            "0x",
+            "0xZZ",
+            "12abc",
+            "4 KB",
+            "1 +",
+            "* 2",
+            "2 ** 3",
+            "1 2",
+            "KiB KiB",
+            "(1)",
+            "end+1",
+            "end 1",
+            "end-5+6",
+            "1+end",
+            "18446744073709551616",
+            "0xFFFFFFFFFFFFFFFF * 2",
+            "16GiB * 0x4000000000",
+            "0xFFFFFFFFFFFFFFFF + 1",
+        ] {
+            let error = parse_offset(text).expect_err(text);
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_offset_round_trips_display() {
+        for offset in [
+            Offset::FromStart(0),
+            Offset::FromStart(42),
+            Offset::FromEnd(7),
+        ] {
+            let text = offset.to_string().replace("EOF", "end");
+            assert_eq!(parse_offset(&text).expect(&text), offset);
+        }
+        assert_eq!(
+            parse_offset(&u64::MAX.to_string()).expect("parse u64::MAX"),
+            Offset::FromStart(u64::MAX)
+        );
+    }
+}
@@ -0,0 +1,888 @@
+//! Policy knobs for the backup/draft/verify workflow
+//!
+//! The defaults: a 64 KiB bucket-brigade buffer, a `<name>.backup` sibling that is removed after
+//! success, full verification, `flush()` only, and a refusal to start if a
+//! `.backup`/`.draft` file from an earlier run is in the way (it may be the
+//! only copy of an earlier original). An `OperationConfig` changes any of
+//! these:
+//!
+//! ```no_run
+//! use basic_file_byte_operations::operation_config::{OperationConfig, OverwritePolicy};
+//! use basic_file_byte_operations::replace_single_byte_in_file_with_config;
+//!
+//! let config = OperationConfig::builder()
+//!     .backup_directory("/var/backups/bfbo")
+//!     .keep_backup(true)
+//!     .overwrite_policy(OverwritePolicy::ReplaceStaleArtifacts)
+//!     .build()?;
+//! replace_single_byte_in_file_with_config("data.bin".into(), 10, 0xFF, &config)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Every operation that writes a file has a `*_with_config` entry point
+//! taking the config as its last parameter. The plain entry points pick up
+//! the config of the enclosing `with_operation_config` scope (or the
+//! defaults), the same way reporters are scoped.
+//!
+//! The draft is always written next to the target, so the final rename
+//! stays on one filesystem; only the backup can be moved elsewhere.
+
+use std::{
+    cell::RefCell,
+    io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use crate::canary::CanarySeal;
+use crate::draft_workflow::{
+    BACKUP_FILE_SUFFIX, DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE, DEFAULT_DURABILITY_MODE,
+    DEFAULT_VERIFICATION_LEVEL, DRAFT_FILE_SUFFIX, DurabilityMode, TINY_BUCKET_BRIGADE_BUFFER_SIZE,
+    VerificationLevel,
+};
+use crate::error::ByteOpError;
+use crate::hash::{DEFAULT_VERIFICATION_HASH, HashAlgorithm};
+use crate::memory_budget::{MemoryBudget, admit_draft_workflow};
+
+/// Largest bucket-brigade buffer a config accepts (64 MiB)
+pub const MAX_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// What to do when a `.backup` or `.draft` file is already present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Overwrite it (leftovers of an earlier, interrupted run)
+    ReplaceStaleArtifacts,
+    /// Fail before anything is written; the leftover may be the only copy
+    /// of an earlier original
+    RefuseExistingArtifacts,
+}
+
+impl OverwritePolicy {
+    /// Stable name used in reports and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverwritePolicy::ReplaceStaleArtifacts => "replace-stale-artifacts",
+            OverwritePolicy::RefuseExistingArtifacts => "refuse-existing-artifacts",
+        }
+    }
+}
+
+/// How backup files are named.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupNaming {
+    /// `<name><suffix>`, e.g. `data.bin.backup`; one backup per file
+    Suffixed,
+    /// `<name>.<UTC time><suffix>`, e.g. `data.bin.20240131T120000.backup`;
+    /// every operation gets its own backup, so none is overwritten
+    Timestamped,
+}
+
+impl BackupNaming {
+    /// Stable name used in reports and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackupNaming::Suffixed => "suffixed",
+            BackupNaming::Timestamped => "timestamped",
+        }
+    }
+}
+
+/// What to do when the target path is a symbolic link.
+///
+/// Renaming the finished draft onto a symlink replaces the link itself with
+/// a regular file and leaves the file it pointed to untouched, so a
+/// symlinked target is never edited through the link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Resolve the link and edit the file it points to; the backup and
+    /// draft are made next to that file, and the link is left as it is
+    FollowAndEditTarget,
+    /// Fail before anything is written
+    RefuseSymlinks,
+}
+
+impl SymlinkPolicy {
+    /// Stable name used in reports and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SymlinkPolicy::FollowAndEditTarget => "follow-and-edit-target",
+            SymlinkPolicy::RefuseSymlinks => "refuse-symlinks",
+        }
+    }
+}
+
+/// How the source and draft of a rewrite use the OS page cache.
+///
+/// A bulk rewrite reads and writes the whole file once. Through the cache,
+/// that pushes everything else a production host keeps cached out of it.
+/// See `page_cache.rs` for the platform details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageCacheMode {
+    /// Plain cached I/O
+    Normal,
+    /// Cached I/O with hints: the source is read once-only
+    /// (`posix_fadvise` `SEQUENTIAL`, `NOREUSE`), and the draft is written
+    /// back and dropped from the cache as it grows (`DONTNEED`). Both files
+    /// leave the cache before the rename
+    DropBehind,
+    /// Drop-behind, and the draft is written with `O_DIRECT` from aligned
+    /// buffers, bypassing the cache entirely (Linux; `F_NOCACHE` on macOS)
+    Unbuffered,
+}
+
+impl PageCacheMode {
+    /// Stable name used in reports and the invariants listing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PageCacheMode::Normal => "normal",
+            PageCacheMode::DropBehind => "drop-behind",
+            PageCacheMode::Unbuffered => "unbuffered",
+        }
+    }
+}
+
+/// Page-cache mode used when no config is given
+pub const DEFAULT_PAGE_CACHE_MODE: PageCacheMode = PageCacheMode::Normal;
+
+/// Symlink policy used when no config is given
+pub const DEFAULT_SYMLINK_POLICY: SymlinkPolicy = SymlinkPolicy::FollowAndEditTarget;
+
+/// Backup naming used when no config is given
+pub const DEFAULT_BACKUP_NAMING: BackupNaming = BackupNaming::Suffixed;
+
+/// Overwrite policy used when no config is given: leftovers are never
+/// silently destroyed; `OperationConfigBuilder::force` opts out
+pub const DEFAULT_OVERWRITE_POLICY: OverwritePolicy = OverwritePolicy::RefuseExistingArtifacts;
+
+/// Settings for one or more byte operations. Build with
+/// `OperationConfig::builder()`; `OperationConfig::default()` is the
+/// historical behaviour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationConfig {
+    buffer_size: usize,
+    backup_suffix: String,
+    backup_directory: Option<PathBuf>,
+    keep_backup: bool,
+    verification_level: VerificationLevel,
+    durability_mode: DurabilityMode,
+    overwrite_policy: OverwritePolicy,
+    expected_original_size: Option<u64>,
+    expected_original_sha256: Option<[u8; 32]>,
+    journal: bool,
+    rotated_backup_count: Option<usize>,
+    backup_naming: BackupNaming,
+    preserve_timestamps: bool,
+    symlink_policy: SymlinkPolicy,
+    page_cache_mode: PageCacheMode,
+    verification_hash: HashAlgorithm,
+    utf8_guard: bool,
+    memory_budget: Option<MemoryBudget>,
+    canary_seal: Option<CanarySeal>,
+}
+
+impl Default for OperationConfig {
+    fn default() -> Self {
+        OperationConfig {
+            buffer_size: DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE,
+            backup_suffix: BACKUP_FILE_SUFFIX.to_string(),
+            backup_directory: None,
+            keep_backup: false,
+            verification_level: DEFAULT_VERIFICATION_LEVEL,
+            durability_mode: DEFAULT_DURABILITY_MODE,
+            overwrite_policy: DEFAULT_OVERWRITE_POLICY,
+            expected_original_size: None,
+            expected_original_sha256: None,
+            journal: false,
+            rotated_backup_count: None,
+            backup_naming: DEFAULT_BACKUP_NAMING,
+            preserve_timestamps: false,
+            symlink_policy: DEFAULT_SYMLINK_POLICY,
+            page_cache_mode: DEFAULT_PAGE_CACHE_MODE,
+            verification_hash: DEFAULT_VERIFICATION_HASH,
+            utf8_guard: false,
+            memory_budget: None,
+            canary_seal: None,
+        }
+    }
+}
+
+impl OperationConfig {
+    /// Starts a builder from the default settings.
+    pub fn builder() -> OperationConfigBuilder {
+        OperationConfigBuilder {
+            config: OperationConfig::default(),
+        }
+    }
+
+    /// Bytes read or written per bucket-brigade chunk
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Suffix appended to the file name for the backup copy
+    pub fn backup_suffix(&self) -> &str {
+        &self.backup_suffix
+    }
+
+    /// Directory for the backup copy (`None`: next to the target)
+    pub fn backup_directory(&self) -> Option<&Path> {
+        self.backup_directory.as_deref()
+    }
+
+    /// Whether the backup is kept after a successful operation
+    pub fn keep_backup(&self) -> bool {
+        self.keep_backup
+    }
+
+    pub fn verification_level(&self) -> VerificationLevel {
+        self.verification_level
+    }
+
+    pub fn durability_mode(&self) -> DurabilityMode {
+        self.durability_mode
+    }
+
+    pub fn overwrite_policy(&self) -> OverwritePolicy {
+        self.overwrite_policy
+    }
+
+    /// Size in bytes the original must have before an operation starts
+    pub fn expected_original_size(&self) -> Option<u64> {
+        self.expected_original_size
+    }
+
+    /// SHA-256 the original must have before an operation starts
+    pub fn expected_original_sha256(&self) -> Option<[u8; 32]> {
+        self.expected_original_sha256
+    }
+
+    /// Whether operations write a crash-recovery journal
+    pub fn journal(&self) -> bool {
+        self.journal
+    }
+
+    /// How many numbered backups (`<backup>.1` newest) are kept per file
+    pub fn rotated_backup_count(&self) -> Option<usize> {
+        self.rotated_backup_count
+    }
+
+    pub fn backup_naming(&self) -> BackupNaming {
+        self.backup_naming
+    }
+
+    /// Whether the replacement keeps the original's access and
+    /// modification times
+    pub fn preserve_timestamps(&self) -> bool {
+        self.preserve_timestamps
+    }
+
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        self.symlink_policy
+    }
+
+    pub fn page_cache_mode(&self) -> PageCacheMode {
+        self.page_cache_mode
+    }
+
+    /// Checksum kept alongside the byte comparison during verification
+    pub fn verification_hash(&self) -> HashAlgorithm {
+        self.verification_hash
+    }
+
+    /// Whether every finished draft must be valid UTF-8 before it replaces
+    /// the original
+    pub fn utf8_guard(&self) -> bool {
+        self.utf8_guard
+    }
+
+    /// Memory the operation's buffers and hashers may use at worst
+    pub fn memory_budget(&self) -> Option<MemoryBudget> {
+        self.memory_budget
+    }
+
+    /// How each draft's canary trailer is re-signed before the rename, if
+    /// at all
+    pub fn canary_seal(&self) -> Option<&CanarySeal> {
+        self.canary_seal.as_ref()
+    }
+
+    /// Whether a successful operation leaves its backup on disk (kept or
+    /// rotated), which is when it gets a manifest
+    pub(crate) fn retains_backup(&self) -> bool {
+        self.keep_backup || self.rotated_backup_count.is_some()
+    }
+
+    /// This config without the expected size and SHA-256, for the later
+    /// steps of an operation that checked them once and then changes the
+    /// file several times
+    pub(crate) fn without_original_preconditions(&self) -> OperationConfig {
+        OperationConfig {
+            expected_original_size: None,
+            expected_original_sha256: None,
+            ..self.clone()
+        }
+    }
+
+    /// This config with `canary_seal` set, for wrapping an edit whose
+    /// config was built elsewhere
+    pub(crate) fn with_canary_seal(&self, canary_seal: CanarySeal) -> OperationConfig {
+        OperationConfig {
+            canary_seal: Some(canary_seal),
+            ..self.clone()
+        }
+    }
+
+    /// This config with another bucket-brigade buffer size, which must
+    /// already be within 1 to `MAX_BUFFER_SIZE` bytes
+    #[cfg(feature = "soak-test")]
+    pub(crate) fn with_buffer_size(&self, buffer_size: usize) -> OperationConfig {
+        debug_assert!(buffer_size > 0 && buffer_size <= MAX_BUFFER_SIZE);
+        OperationConfig {
+            buffer_size,
+            ..self.clone()
+        }
+    }
+}
+
+/// Builder for `OperationConfig`; `build()` checks the settings.
+#[derive(Debug, Clone)]
+pub struct OperationConfigBuilder {
+    config: OperationConfig,
+}
+
+impl OperationConfigBuilder {
+    /// Sets the bucket-brigade buffer size (1 to `MAX_BUFFER_SIZE` bytes).
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.config.buffer_size = buffer_size;
+        self
+    }
+
+    /// Tiny-buffer mode: 64-byte buffers, for constrained environments.
+    /// Much slower on large files (one read and write per 64 bytes).
+    pub fn tiny_buffer(self) -> Self {
+        self.buffer_size(TINY_BUCKET_BRIGADE_BUFFER_SIZE)
+    }
+
+    /// Sets the backup suffix (non-empty, no path separators, not the
+    /// draft suffix).
+    pub fn backup_suffix(mut self, backup_suffix: impl Into<String>) -> Self {
+        self.config.backup_suffix = backup_suffix.into();
+        self
+    }
+
+    /// Writes backups into `directory` instead of next to the target.
+    /// The directory must already exist.
+    pub fn backup_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.config.backup_directory = Some(directory.into());
+        self
+    }
+
+    /// Keeps the backup after a successful operation instead of removing it.
+    pub fn keep_backup(mut self, keep_backup: bool) -> Self {
+        self.config.keep_backup = keep_backup;
+        self
+    }
+
+    pub fn verification_level(mut self, verification_level: VerificationLevel) -> Self {
+        self.config.verification_level = verification_level;
+        self
+    }
+
+    pub fn durability_mode(mut self, durability_mode: DurabilityMode) -> Self {
+        self.config.durability_mode = durability_mode;
+        self
+    }
+
+    pub fn overwrite_policy(mut self, overwrite_policy: OverwritePolicy) -> Self {
+        self.config.overwrite_policy = overwrite_policy;
+        self
+    }
+
+    /// With `true`, leftover `.backup`/`.draft` files are overwritten
+    /// (`OverwritePolicy::ReplaceStaleArtifacts`) instead of refused. Check
+    /// them first (`orphan_scan::scan_for_orphans`): a leftover backup may
+    /// be the only copy of an earlier original.
+    pub fn force(self, force: bool) -> Self {
+        self.overwrite_policy(if force {
+            OverwritePolicy::ReplaceStaleArtifacts
+        } else {
+            OverwritePolicy::RefuseExistingArtifacts
+        })
+    }
+
+    /// Fails each operation before its backup unless the original is
+    /// exactly `size` bytes, the size the patch was prepared for. Checked
+    /// before the SHA-256 precondition, which has to read the whole file.
+    pub fn expected_original_size(mut self, size: u64) -> Self {
+        self.config.expected_original_size = Some(size);
+        self
+    }
+
+    /// Fails each operation before its backup unless the original's SHA-256
+    /// is `digest`, so a patch is only applied to the version it was made
+    /// for. The original changes with every edit, so scope such a config to
+    /// one operation (or one `multi_edit::apply_operations` call).
+    pub fn expected_original_sha256(mut self, digest: [u8; 32]) -> Self {
+        self.config.expected_original_sha256 = Some(digest);
+        self
+    }
+
+    /// Writes a crash-recovery journal next to the target for each
+    /// operation (see `journal.rs`). Costs a SHA-256 pass over the original
+    /// and one over the draft, plus a sync per phase marker.
+    pub fn journal(mut self, enabled: bool) -> Self {
+        self.config.journal = enabled;
+        self
+    }
+
+    /// Carries the original's access and modification times over to the
+    /// replacement, for build systems and sync tools that key off
+    /// timestamps. By default the replacement has the time of the edit.
+    pub fn preserve_timestamps(mut self, preserve_timestamps: bool) -> Self {
+        self.config.preserve_timestamps = preserve_timestamps;
+        self
+    }
+
+    /// Keeps the last `count` backups of each file as `<backup>.1` (the
+    /// newest) to `<backup>.<count>`. After each successful operation the
+    /// numbered backups shift up by one, the oldest is pruned, and the new
+    /// backup becomes `.1`, so repeated edits never destroy earlier
+    /// recovery points. Takes precedence over `keep_backup`.
+    pub fn rotate_backups(mut self, count: usize) -> Self {
+        self.config.rotated_backup_count = Some(count);
+        self
+    }
+
+    /// Sets how backups are named. `BackupNaming::Timestamped` gives each
+    /// operation its own backup; combine with `keep_backup(true)` to retain
+    /// them all.
+    pub fn backup_naming(mut self, backup_naming: BackupNaming) -> Self {
+        self.config.backup_naming = backup_naming;
+        self
+    }
+
+    /// Sets what happens when the target is a symlink (default:
+    /// `SymlinkPolicy::FollowAndEditTarget`).
+    pub fn symlink_policy(mut self, symlink_policy: SymlinkPolicy) -> Self {
+        self.config.symlink_policy = symlink_policy;
+        self
+    }
+
+    /// Sets how rewrites use the page cache (default:
+    /// `PageCacheMode::Normal`).
+    pub fn page_cache_mode(mut self, page_cache_mode: PageCacheMode) -> Self {
+        self.config.page_cache_mode = page_cache_mode;
+        self
+    }
+
+    /// Sets the checksum verification keeps of both files (default:
+    /// `HashAlgorithm::XorSum`).
+    pub fn verification_hash(mut self, verification_hash: HashAlgorithm) -> Self {
+        self.config.verification_hash = verification_hash;
+        self
+    }
+
+    /// Requires every finished draft to be valid UTF-8; otherwise the
+    /// operation fails before the rename and the original is left untouched
+    /// (default: off). For byte-wise edits of text files; binary files would
+    /// always fail it.
+    pub fn utf8_guard(mut self, enabled: bool) -> Self {
+        self.config.utf8_guard = enabled;
+        self
+    }
+
+    /// Caps the worst-case memory of each operation at `limit_bytes`
+    /// (default: no cap). Checked against the buffer size, the journal and
+    /// the expected digest when the config is built, and again when an
+    /// operation starts.
+    pub fn memory_budget(mut self, limit_bytes: usize) -> Self {
+        self.config.memory_budget = Some(MemoryBudget::new(limit_bytes));
+        self
+    }
+
+    /// Re-signs the canary trailer (see `canary.rs`) of every draft after
+    /// it is verified, so the edit and the refresh share one rename
+    /// (default: off). A draft whose trailer the edit changed is refused.
+    pub fn canary_seal(mut self, canary_seal: CanarySeal) -> Self {
+        self.config.canary_seal = Some(canary_seal);
+        self
+    }
+
+    /// Checks the settings and returns the config.
+    ///
+    /// # Returns
+    /// - `Ok(OperationConfig)`
+    /// - `Err(io::Error)` (`InvalidInput`, carrying
+    ///   `ByteOpError::InvalidConfiguration`) naming the first bad setting,
+    ///   or `ByteOpError::MemoryBudgetExceeded` if the draft workflow cannot
+    ///   fit the memory budget
+    pub fn build(self) -> io::Result<OperationConfig> {
+        let config = self.config;
+        let problem = if config.buffer_size == 0 || config.buffer_size > MAX_BUFFER_SIZE {
+            Some("buffer size must be between 1 byte and 64 MiB")
+        } else if config.backup_suffix.is_empty() {
+            Some("backup suffix must not be empty")
+        } else if config.backup_suffix.contains(['/', '\\']) {
+            Some("backup suffix must not contain path separators")
+        } else if config.rotated_backup_count == Some(0) {
+            Some("rotated backup count must be at least 1")
+        } else if config.rotated_backup_count.is_some()
+            && config.backup_naming == BackupNaming::Timestamped
+        {
+            Some("rotated backups need suffixed backup naming")
+        } else if config.backup_suffix == DRAFT_FILE_SUFFIX {
+            Some("backup suffix must differ from the draft suffix")
+        } else {
+            None
+        };
+
+        if let Some(reason) = problem {
+            return Err(ByteOpError::InvalidConfiguration { reason }.into());
+        }
+        admit_draft_workflow(&config)?;
+        Ok(config)
+    }
+}
+
+thread_local! {
+    /// Configs of the enclosing `with_operation_config` scopes, innermost last
+    static ACTIVE_CONFIGS: RefCell<Vec<OperationConfig>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Keeps a config active on this thread until dropped, so a panic or an
+/// early return cannot leave it on the stack.
+pub(crate) struct ActiveConfigScope {
+    /// The stack is thread-local: the scope must end on the thread that
+    /// entered it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ActiveConfigScope {
+    /// Makes `config` the innermost active config.
+    pub(crate) fn enter(config: &OperationConfig) -> Self {
+        ACTIVE_CONFIGS.with(|configs| configs.borrow_mut().push(config.clone()));
+        ActiveConfigScope {
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for ActiveConfigScope {
+    fn drop(&mut self) {
+        ACTIVE_CONFIGS.with(|configs| {
+            configs.borrow_mut().pop();
+        });
+    }
+}
+
+/// Runs `operations` with `config` applied to every byte operation started
+/// on this thread. Scopes nest; the innermost config wins.
+pub fn with_operation_config<T>(
+    config: &OperationConfig,
+    operations: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    let _active_config_scope = ActiveConfigScope::enter(config);
+    operations()
+}
+
+/// The config operations on this thread run with right now.
+pub(crate) fn active_operation_config() -> OperationConfig {
+    ACTIVE_CONFIGS.with(|configs| configs.borrow().last().cloned().unwrap_or_default())
+}
+
+/// Buffer size of the active config, for the streaming helpers.
+pub(crate) fn active_buffer_size() -> usize {
+    ACTIVE_CONFIGS.with(|configs| {
+        configs
+            .borrow()
+            .last()
+            .map_or(DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE, |config| {
+                config.buffer_size
+            })
+    })
+}
+
+/// Verification checksum of the active config, for the verification loops.
+pub(crate) fn active_verification_hash() -> HashAlgorithm {
+    ACTIVE_CONFIGS.with(|configs| {
+        configs
+            .borrow()
+            .last()
+            .map_or(DEFAULT_VERIFICATION_HASH, |config| config.verification_hash)
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256::sha256_of_bytes;
+    use crate::range_operations::{
+        replace_byte_range_in_file, replace_byte_range_in_file_with_config,
+    };
+    use crate::{
+        remove_single_byte_from_file, remove_single_byte_from_file_with_config,
+        replace_single_byte_in_file_with_config,
+    };
+
+    #[test]
+    fn test_builder_rejects_bad_settings() {
+        assert!(OperationConfig::builder().buffer_size(0).build().is_err());
+        assert!(
+            OperationConfig::builder()
+                .backup_suffix("")
+                .build()
+                .is_err()
+        );
+        assert!(
+            OperationConfig::builder()
+                .backup_suffix("/x")
+                .build()
+                .is_err()
+        );
+        let error = OperationConfig::builder()
+            .backup_suffix(DRAFT_FILE_SUFFIX)
+            .build()
+            .expect_err("draft suffix");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            OperationConfig::builder().build().expect("defaults"),
+            OperationConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_builder_checks_memory_budget() {
+        let error = OperationConfig::builder()
+            .memory_budget(100)
+            .build()
+            .expect_err("budget too small");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::MemoryBudgetExceeded {
+                limit_bytes: 100,
+                ..
+            })
+        ));
+        assert!(
+            OperationConfig::builder()
+                .memory_budget(0x10000)
+                .build()
+                .is_err()
+        );
+
+        let config = OperationConfig::builder()
+            .tiny_buffer()
+            .memory_budget(0x10000)
+            .build()
+            .expect("tiny buffers fit");
+        assert_eq!(config.memory_budget(), Some(MemoryBudget::new(0x10000)));
+        let test_file = std::env::temp_dir().join("test_memory_budget_config.bin");
+        std::fs::write(&test_file, b"abc").expect("create file");
+        replace_single_byte_in_file_with_config(test_file.clone(), 1, b'B', &config)
+            .expect("admitted");
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"aBc");
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_config_controls_backup_and_overwrite() {
+        let work_dir = std::env::temp_dir().join("test_operation_config");
+        let backup_dir = work_dir.join("backups");
+        let _ = std::fs::remove_dir_all(&work_dir);
+        std::fs::create_dir_all(&backup_dir).expect("create dirs");
+        let test_file = work_dir.join("target.bin");
+        std::fs::write(&test_file, (0..200u8).collect::<Vec<u8>>()).expect("create file");
+
+        let config = OperationConfig::builder()
+            .buffer_size(7)
+            .backup_directory(&backup_dir)
+            .backup_suffix(".orig")
+            .keep_backup(true)
+            .durability_mode(DurabilityMode::SyncDraft)
+            .overwrite_policy(OverwritePolicy::RefuseExistingArtifacts)
+            .build()
+            .expect("valid config");
+
+        replace_single_byte_in_file_with_config(test_file.clone(), 150, 0xFF, &config)
+            .expect("replace");
+        let backup_file = backup_dir.join("target.bin.orig");
+        let backup_bytes = std::fs::read(&backup_file).expect("backup kept");
+        assert_eq!(backup_bytes[150], 150);
+        assert_eq!(std::fs::read(&test_file).expect("read")[150], 0xFF);
+
+        // The kept backup is now an existing artifact
+        let error = remove_single_byte_from_file_with_config(test_file.clone(), 0, &config)
+            .expect_err("refuses to overwrite backup");
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&test_file).expect("read").len(), 200);
+
+        // Multi-byte operations take the config from the enclosing scope
+        std::fs::remove_file(&backup_file).expect("remove backup");
+        with_operation_config(&config, || {
+            replace_byte_range_in_file(test_file.clone(), 10, b"abc")
+        })
+        .expect("range replace");
+        assert_eq!(&std::fs::read(&test_file).expect("read")[10..13], b"abc");
+        assert_eq!(std::fs::read(&backup_file).expect("backup kept")[150], 0xFF);
+
+        // ... or as their last parameter
+        std::fs::remove_file(&backup_file).expect("remove backup");
+        replace_byte_range_in_file_with_config(test_file.clone(), 20, b"xyz", &config)
+            .expect("range replace with config");
+        assert_eq!(&std::fs::read(&test_file).expect("read")[20..23], b"xyz");
+        assert_eq!(
+            &std::fs::read(&backup_file).expect("backup kept")[10..13],
+            b"abc"
+        );
+
+        let _ = std::fs::remove_dir_all(&work_dir);
+    }
+
+    #[test]
+    fn test_config_scope_ends_on_panic_and_error() {
+        let config = OperationConfig::builder()
+            .buffer_size(7)
+            .build()
+            .expect("valid config");
+
+        let outcome = std::panic::catch_unwind(|| {
+            with_operation_config(&config, || -> io::Result<()> {
+                panic!("operation panicked inside the scope")
+            })
+        });
+        assert!(outcome.is_err());
+        assert_eq!(active_buffer_size(), DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE);
+
+        let outcome = with_operation_config(&config, || -> io::Result<()> {
+            assert_eq!(active_buffer_size(), 7);
+            Err(io::Error::other("early return"))
+        });
+        assert!(outcome.is_err());
+        assert_eq!(active_buffer_size(), DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_leftover_artifacts_refused_unless_forced() {
+        let test_file = std::env::temp_dir().join("test_leftover_artifacts.bin");
+        let draft_file = test_file.with_file_name("test_leftover_artifacts.bin.draft");
+        let backup_file = test_file.with_file_name("test_leftover_artifacts.bin.backup");
+        std::fs::write(&test_file, b"current").expect("create file");
+
+        // A leftover draft or a backup that differs from the original is refused
+        for leftover in [&draft_file, &backup_file] {
+            std::fs::write(leftover, b"earlier").expect("create leftover");
+            let error =
+                remove_single_byte_from_file(test_file.clone(), 0).expect_err("leftover refused");
+            assert!(matches!(
+                ByteOpError::from_io_error(&error),
+                Some(ByteOpError::ArtifactExists { path }) if path == leftover
+            ));
+            assert_eq!(std::fs::read(leftover).expect("leftover kept"), b"earlier");
+            std::fs::remove_file(leftover).expect("remove leftover");
+        }
+
+        // A backup identical to the original is redundant and replaced
+        std::fs::write(&backup_file, b"current").expect("create backup");
+        remove_single_byte_from_file(test_file.clone(), 0).expect("redundant backup");
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"urrent");
+
+        std::fs::write(&draft_file, b"earlier").expect("create leftover");
+        let forced = OperationConfig::builder()
+            .force(true)
+            .build()
+            .expect("valid config");
+        remove_single_byte_from_file_with_config(test_file.clone(), 0, &forced).expect("forced");
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"rrent");
+        assert!(!draft_file.exists());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_expected_sha256_checked_before_backup() {
+        let test_file = std::env::temp_dir().join("test_expected_sha256.bin");
+        std::fs::write(&test_file, b"version 1").expect("create file");
+
+        let wrong_version = OperationConfig::builder()
+            .expected_original_sha256(sha256_of_bytes(b"version 2"))
+            .build()
+            .expect("valid config");
+        let error =
+            replace_single_byte_in_file_with_config(test_file.clone(), 0, b'V', &wrong_version)
+                .expect_err("hash mismatch");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::UnexpectedFileHash { .. })
+        ));
+        let error = with_operation_config(&wrong_version, || {
+            replace_byte_range_in_file(test_file.clone(), 0, b"V")
+        })
+        .expect_err("hash mismatch");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"version 1");
+        assert!(
+            !test_file
+                .with_file_name("test_expected_sha256.bin.backup")
+                .exists()
+        );
+
+        let right_version = OperationConfig::builder()
+            .expected_original_sha256(sha256_of_bytes(b"version 1"))
+            .build()
+            .expect("valid config");
+        replace_single_byte_in_file_with_config(test_file.clone(), 0, b'V', &right_version)
+            .expect("hash matches");
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"Version 1");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_expected_size_checked_before_backup() {
+        let test_file = std::env::temp_dir().join("test_expected_size.bin");
+        std::fs::write(&test_file, b"12345").expect("create file");
+
+        let config = OperationConfig::builder()
+            .expected_original_size(4)
+            .build()
+            .expect("valid config");
+        let error = remove_single_byte_from_file_with_config(test_file.clone(), 0, &config)
+            .expect_err("size mismatch");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::UnexpectedFileSize {
+                expected: 4,
+                actual: 5
+            })
+        ));
+        assert!(
+            with_operation_config(&config, || {
+                replace_byte_range_in_file(test_file.clone(), 0, b"x")
+            })
+            .is_err()
+        );
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"12345");
+        assert!(
+            !test_file
+                .with_file_name("test_expected_size.bin.backup")
+                .exists()
+        );
+
+        let config = OperationConfig::builder()
+            .expected_original_size(5)
+            .build()
+            .expect("valid config");
+        remove_single_byte_from_file_with_config(test_file.clone(), 0, &config)
+            .expect("size matches");
+        assert_eq!(std::fs::read(&test_file).expect("read"), b"2345");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,652 @@
+//! Batch planning: check an operation list before anything is written
+//!
+//! A batch (a patch plan, a CLI offset list, ...) runs its operations one
+//! after another, so each position refers to the file as the earlier
+//! operations left it. `plan_operations` replays such a list against a
+//! virtual layout, without touching the file, and either:
+//! - returns an `OperationPlan`: the same edits with positions in the
+//!   ORIGINAL layout, ready for `multi_edit::apply_operations`, or
+//! - returns every `OperationConflict` it found, so a caller can report
+//!   them all at once instead of failing on the first
+//!
+//! Conflicts are:
+//! - a position out of bounds for the file size at that step (the size
+//!   after all earlier inserts and removals)
+//! - one original byte replaced twice with different values (the same
+//!   value twice is accepted; the plan keeps one)
+//! - overlapping edits: removing a byte an earlier operation replaced, or
+//!   replacing/removing a byte an earlier operation inserted
+//!
+//! Each operation walks the edits planned so far, so planning is quadratic
+//! in the number of operations; it is meant for plan-sized lists.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+use crate::patch_plan::ByteOperation;
+
+/// A conflict-free batch, expressed in the original file layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationPlan {
+    /// Sorted by position; at one position inserts come first, in order
+    pub operations: Vec<ByteOperation>,
+    pub original_file_size: u64,
+    pub final_file_size: u64,
+}
+
+/// Why an operation in a batch cannot be planned.
+///
+/// `operation_index` is the index in the input list; positions named
+/// `original_position` are in the original file layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationConflict {
+    /// The position is past the file as earlier operations left it
+    OutOfBounds {
+        operation_index: usize,
+        position: u64,
+        file_size: u64,
+    },
+    /// An original byte was already replaced with a different value
+    ConflictingValues {
+        operation_index: usize,
+        earlier_operation_index: usize,
+        original_position: u64,
+        earlier_byte_value: u8,
+        new_byte_value: u8,
+    },
+    /// The operation edits a byte an earlier operation inserted or replaced
+    Overlap {
+        operation_index: usize,
+        earlier_operation_index: usize,
+        original_position: u64,
+    },
+}
+
+impl fmt::Display for OperationConflict {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationConflict::OutOfBounds {
+                operation_index,
+                position,
+                file_size,
+            } => write!(
+                formatter,
+                "Operation {}: position {} is out of bounds (file size at that step: {})",
+                operation_index, position, file_size
+            ),
+            OperationConflict::ConflictingValues {
+                operation_index,
+                earlier_operation_index,
+                original_position,
+                earlier_byte_value,
+                new_byte_value,
+            } => write!(
+                formatter,
+                "Operation {}: original byte {} set to 0x{:02X}, but operation {} already set it to 0x{:02X}",
+                operation_index,
+                original_position,
+                new_byte_value,
+                earlier_operation_index,
+                earlier_byte_value
+            ),
+            OperationConflict::Overlap {
+                operation_index,
+                earlier_operation_index,
+                original_position,
+            } => write!(
+                formatter,
+                "Operation {}: overlaps operation {} at original byte {}",
+                operation_index, earlier_operation_index, original_position
+            ),
+        }
+    }
+}
+
+/// What happens to one original byte (`None`: kept as is).
+#[derive(Debug, Clone, Copy)]
+enum PlannedFate {
+    Replace {
+        byte_value: u8,
+        operation_index: usize,
+    },
+    Remove {
+        operation_index: usize,
+    },
+}
+
+/// Edits planned at one original position: bytes inserted before it (in
+/// layout order, with the inserting operation), then its fate.
+#[derive(Debug, Clone, Default)]
+struct PlannedSlot {
+    inserted: Vec<(u8, usize)>,
+    fate: Option<PlannedFate>,
+}
+
+/// Where a current-layout position lands in the original layout.
+enum LocatedByte {
+    Original(u64),
+    Inserted { slot_position: u64, index: usize },
+    EndOfFile,
+}
+
+/// Edits replayed so far, keyed by original position, and the layout
+/// (current file size, position mapping) they produce.
+#[derive(Debug, Clone)]
+pub(crate) struct EditOverlay {
+    slots: BTreeMap<u64, PlannedSlot>,
+    original_file_size: u64,
+    current_file_size: u64,
+}
+
+impl EditOverlay {
+    pub(crate) fn new(original_file_size: u64) -> Self {
+        EditOverlay {
+            slots: BTreeMap::new(),
+            original_file_size,
+            current_file_size: original_file_size,
+        }
+    }
+
+    /// File size with every edit so far applied.
+    pub(crate) fn current_file_size(&self) -> u64 {
+        self.current_file_size
+    }
+
+    /// Replays one operation, positioned in the current layout.
+    ///
+    /// A conflicting operation is not recorded, so the overlay is unchanged.
+    pub(crate) fn add(
+        &mut self,
+        operation_index: usize,
+        operation: &ByteOperation,
+    ) -> Result<(), OperationConflict> {
+        let (position, consumes_byte) = match *operation {
+            ByteOperation::Replace { position, .. } | ByteOperation::Remove { position } => {
+                (position, true)
+            }
+            ByteOperation::Insert { position, .. } => (position, false),
+            ByteOperation::Touch => return Ok(()),
+        };
+
+        // Insertion at EOF (position == size) is an append
+        if position > self.current_file_size
+            || (consumes_byte && position == self.current_file_size)
+        {
+            return Err(OperationConflict::OutOfBounds {
+                operation_index,
+                position,
+                file_size: self.current_file_size,
+            });
+        }
+
+        match (operation, self.locate(position)) {
+            (
+                &ByteOperation::Insert { new_byte_value, .. },
+                LocatedByte::Original(slot_position),
+            ) => {
+                self.slots
+                    .entry(slot_position)
+                    .or_default()
+                    .inserted
+                    .push((new_byte_value, operation_index));
+            }
+            (&ByteOperation::Insert { new_byte_value, .. }, LocatedByte::EndOfFile) => {
+                self.slots
+                    .entry(self.original_file_size)
+                    .or_default()
+                    .inserted
+                    .push((new_byte_value, operation_index));
+            }
+            (
+                &ByteOperation::Insert { new_byte_value, .. },
+                LocatedByte::Inserted {
+                    slot_position,
+                    index,
+                },
+            ) => {
+                if let Some(slot) = self.slots.get_mut(&slot_position) {
+                    slot.inserted
+                        .insert(index, (new_byte_value, operation_index));
+                }
+            }
+            (
+                _,
+                LocatedByte::Inserted {
+                    slot_position,
+                    index,
+                },
+            ) => {
+                let earlier_operation_index = self
+                    .slots
+                    .get(&slot_position)
+                    .map_or(operation_index, |slot| slot.inserted[index].1);
+                return Err(OperationConflict::Overlap {
+                    operation_index,
+                    earlier_operation_index,
+                    original_position: slot_position,
+                });
+            }
+            (_, LocatedByte::Original(original_position)) => {
+                let new_fate = match *operation {
+                    ByteOperation::Replace { new_byte_value, .. } => PlannedFate::Replace {
+                        byte_value: new_byte_value,
+                        operation_index,
+                    },
+                    _ => PlannedFate::Remove { operation_index },
+                };
+                let slot = self.slots.entry(original_position).or_default();
+                match (slot.fate, new_fate) {
+                    (None, _) => slot.fate = Some(new_fate),
+                    (
+                        Some(PlannedFate::Replace {
+                            byte_value: earlier_byte_value,
+                            operation_index: earlier_operation_index,
+                        }),
+                        PlannedFate::Replace {
+                            byte_value: new_byte_value,
+                            ..
+                        },
+                    ) => {
+                        // The same value twice is a harmless duplicate
+                        if earlier_byte_value != new_byte_value {
+                            return Err(OperationConflict::ConflictingValues {
+                                operation_index,
+                                earlier_operation_index,
+                                original_position,
+                                earlier_byte_value,
+                                new_byte_value,
+                            });
+                        }
+                    }
+                    (
+                        Some(
+                            PlannedFate::Replace {
+                                operation_index: earlier_operation_index,
+                                ..
+                            }
+                            | PlannedFate::Remove {
+                                operation_index: earlier_operation_index,
+                            },
+                        ),
+                        _,
+                    ) => {
+                        return Err(OperationConflict::Overlap {
+                            operation_index,
+                            earlier_operation_index,
+                            original_position,
+                        });
+                    }
+                }
+            }
+            // Bounds were checked above: only an insert can land at EOF
+            (_, LocatedByte::EndOfFile) => return Ok(()),
+        }
+
+        match operation {
+            ByteOperation::Insert { .. } => self.current_file_size += 1,
+            ByteOperation::Remove { .. } => self.current_file_size -= 1,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The replayed edits in the original layout.
+    pub(crate) fn to_plan(&self) -> OperationPlan {
+        let mut planned_operations = Vec::new();
+        for (&position, slot) in &self.slots {
+            for &(new_byte_value, _) in &slot.inserted {
+                planned_operations.push(ByteOperation::Insert {
+                    position,
+                    new_byte_value,
+                });
+            }
+            match slot.fate {
+                Some(PlannedFate::Replace { byte_value, .. }) => {
+                    planned_operations.push(ByteOperation::Replace {
+                        position,
+                        new_byte_value: byte_value,
+                    })
+                }
+                Some(PlannedFate::Remove { .. }) => {
+                    planned_operations.push(ByteOperation::Remove { position })
+                }
+                None => {}
+            }
+        }
+
+        OperationPlan {
+            operations: planned_operations,
+            original_file_size: self.original_file_size,
+            final_file_size: self.current_file_size,
+        }
+    }
+
+    /// Reads `buffer.len()` bytes at `current_position` of the replayed
+    /// layout: original bytes from `original`, edited bytes from the overlay.
+    ///
+    /// # Returns
+    /// - `Ok(count)` of bytes read; fewer than requested (0 at or past the
+    ///   end) only where the replayed file ends
+    /// - `Err(io::Error)` if `original` cannot be read at a mapped position
+    pub(crate) fn read_at<R: Read + Seek>(
+        &self,
+        original: &mut R,
+        current_position: u64,
+        buffer: &mut [u8],
+    ) -> io::Result<usize> {
+        let window = OverlayWindow {
+            start: current_position,
+            end: current_position
+                .saturating_add(buffer.len() as u64)
+                .min(self.current_file_size),
+        };
+        if window.start >= window.end {
+            return Ok(0);
+        }
+
+        let mut original_cursor: u64 = 0;
+        let mut current_cursor: u64 = 0;
+        for (&slot_position, slot) in &self.slots {
+            if current_cursor >= window.end {
+                break;
+            }
+            let gap_length = slot_position - original_cursor;
+            window.copy_original(
+                original,
+                buffer,
+                original_cursor,
+                current_cursor,
+                gap_length,
+            )?;
+            current_cursor += gap_length;
+
+            for &(byte_value, _) in &slot.inserted {
+                window.put_byte(buffer, current_cursor, byte_value);
+                current_cursor += 1;
+            }
+
+            if slot_position < self.original_file_size {
+                match slot.fate {
+                    None => {
+                        window.copy_original(original, buffer, slot_position, current_cursor, 1)?;
+                        current_cursor += 1;
+                    }
+                    Some(PlannedFate::Replace { byte_value, .. }) => {
+                        window.put_byte(buffer, current_cursor, byte_value);
+                        current_cursor += 1;
+                    }
+                    Some(PlannedFate::Remove { .. }) => {}
+                }
+            }
+            original_cursor = slot_position + 1;
+        }
+
+        let tail_length = self.original_file_size.saturating_sub(original_cursor);
+        window.copy_original(
+            original,
+            buffer,
+            original_cursor,
+            current_cursor,
+            tail_length,
+        )?;
+
+        // The window is within `buffer.len()`, so it fits in usize
+        Ok((window.end - window.start) as usize)
+    }
+
+    /// Maps a position in the current (replayed) layout to the original one.
+    ///
+    /// The caller has bounds-checked `current_position`; `EndOfFile` means it
+    /// equals the current file size.
+    fn locate(&self, current_position: u64) -> LocatedByte {
+        let mut original_cursor: u64 = 0;
+        let mut current_cursor: u64 = 0;
+
+        for (&slot_position, slot) in &self.slots {
+            // Untouched original bytes before this slot
+            let gap_length = slot_position - original_cursor;
+            if current_position < current_cursor + gap_length {
+                return LocatedByte::Original(
+                    original_cursor + (current_position - current_cursor),
+                );
+            }
+            current_cursor += gap_length;
+
+            let inserted_length = slot.inserted.len() as u64;
+            if current_position < current_cursor + inserted_length {
+                return LocatedByte::Inserted {
+                    slot_position,
+                    // Below `slot.inserted.len()`, so it fits in usize
+                    index: (current_position - current_cursor) as usize,
+                };
+            }
+            current_cursor += inserted_length;
+
+            let keeps_original_byte = slot_position < self.original_file_size
+                && !matches!(slot.fate, Some(PlannedFate::Remove { .. }));
+            if keeps_original_byte {
+                if current_position == current_cursor {
+                    return LocatedByte::Original(slot_position);
+                }
+                current_cursor += 1;
+            }
+            original_cursor = slot_position + 1;
+        }
+
+        let tail_length = self.original_file_size.saturating_sub(original_cursor);
+        if current_position < current_cursor + tail_length {
+            LocatedByte::Original(original_cursor + (current_position - current_cursor))
+        } else {
+            LocatedByte::EndOfFile
+        }
+    }
+}
+
+/// The part of the replayed layout being read, `start..end`; byte
+/// `start + i` goes to `buffer[i]`.
+struct OverlayWindow {
+    start: u64,
+    end: u64,
+}
+
+impl OverlayWindow {
+    fn put_byte(&self, buffer: &mut [u8], current_position: u64, byte_value: u8) {
+        if (self.start..self.end).contains(&current_position) {
+            // Within the window, so below `buffer.len()`
+            buffer[(current_position - self.start) as usize] = byte_value;
+        }
+    }
+
+    /// Copies the part of an unedited run (`length` original bytes from
+    /// `original_start`, placed at `current_start`) that falls in the window.
+    fn copy_original<R: Read + Seek>(
+        &self,
+        original: &mut R,
+        buffer: &mut [u8],
+        original_start: u64,
+        current_start: u64,
+        length: u64,
+    ) -> io::Result<()> {
+        let overlap_start = current_start.max(self.start);
+        let overlap_end = (current_start + length).min(self.end);
+        if overlap_start >= overlap_end {
+            return Ok(());
+        }
+
+        original.seek(SeekFrom::Start(
+            original_start + (overlap_start - current_start),
+        ))?;
+        // Within the window, so both ends are at most `buffer.len()`
+        let buffer_range =
+            (overlap_start - self.start) as usize..(overlap_end - self.start) as usize;
+        original.read_exact(&mut buffer[buffer_range])
+    }
+}
+
+/// Replays a sequential batch against a file of `original_file_size` bytes.
+///
+/// # Parameters
+/// - `operations`: In execution order, each position in the layout left by
+///   the operations before it (as `patch_plan::apply_patch_plan` and the
+///   CLI run them); `Touch` entries are ignored
+/// - `original_file_size`: Size of the target before the batch
+///
+/// # Returns
+/// - `Ok(OperationPlan)` if no operation conflicts
+/// - `Err(Vec<OperationConflict>)` with every conflict, in input order; an
+///   operation that conflicts is left out of the replay, so later positions
+///   are checked as if it had not been given
+pub fn plan_operations(
+    operations: &[ByteOperation],
+    original_file_size: u64,
+) -> Result<OperationPlan, Vec<OperationConflict>> {
+    let mut overlay = EditOverlay::new(original_file_size);
+    let conflicts: Vec<OperationConflict> = operations
+        .iter()
+        .enumerate()
+        .filter_map(|(operation_index, operation)| overlay.add(operation_index, operation).err())
+        .collect();
+
+    if conflicts.is_empty() {
+        Ok(overlay.to_plan())
+    } else {
+        Err(conflicts)
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_edit::apply_operations;
+    use crate::patch_plan::apply_byte_operation;
+
+    fn replace(position: u64, new_byte_value: u8) -> ByteOperation {
+        ByteOperation::Replace {
+            position,
+            new_byte_value,
+        }
+    }
+
+    fn insert(position: u64, new_byte_value: u8) -> ByteOperation {
+        ByteOperation::Insert {
+            position,
+            new_byte_value,
+        }
+    }
+
+    #[test]
+    fn test_plan_matches_sequential_application() {
+        let operations = [
+            insert(0, b'<'),
+            ByteOperation::Remove { position: 4 },
+            replace(1, b'a'),
+            insert(4, b'x'),
+            insert(4, b'y'),
+            ByteOperation::Touch,
+            insert(9, b'>'),
+            ByteOperation::Remove { position: 10 },
+            replace(2, b'b'),
+        ];
+
+        let sequential_file = std::env::temp_dir().join("test_plan_sequential.bin");
+        let planned_file = std::env::temp_dir().join("test_plan_planned.bin");
+        std::fs::write(&sequential_file, b"ABCDEFGH").expect("create test file");
+        std::fs::write(&planned_file, b"ABCDEFGH").expect("create test file");
+
+        for operation in &operations {
+            apply_byte_operation(&sequential_file, operation).expect("sequential operation");
+        }
+        let plan = plan_operations(&operations, 8).expect("no conflicts");
+        assert_eq!(plan.final_file_size, 10);
+        apply_operations(planned_file.clone(), &plan.operations).expect("planned operations");
+
+        let sequential_bytes = std::fs::read(&sequential_file).expect("read file");
+        assert_eq!(sequential_bytes, b"<abCyxEFG>");
+        assert_eq!(
+            std::fs::read(&planned_file).expect("read file"),
+            sequential_bytes
+        );
+
+        let _ = std::fs::remove_file(&sequential_file);
+        let _ = std::fs::remove_file(&planned_file);
+    }
+
+    #[test]
+    fn test_plan_bounds_follow_frame_shifts() {
+        // The 4-byte file grows to 5, so position 4 is valid for the replace
+        let plan = plan_operations(&[insert(0, 0), replace(4, 1)], 4).expect("in bounds");
+        assert_eq!(plan.operations, vec![insert(0, 0), replace(3, 1)]);
+
+        // ...and shrinks to 3, so position 3 is only an append point
+        let conflicts = plan_operations(
+            &[
+                ByteOperation::Remove { position: 0 },
+                replace(3, 1),
+                insert(3, 2),
+            ],
+            4,
+        )
+        .expect_err("out of bounds");
+        assert_eq!(
+            conflicts,
+            vec![OperationConflict::OutOfBounds {
+                operation_index: 1,
+                position: 3,
+                file_size: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_reports_every_conflict() {
+        let conflicts = plan_operations(
+            &[
+                replace(1, 0xAA),
+                replace(1, 0xAA),
+                replace(1, 0xBB),
+                insert(3, 0x00),
+                replace(3, 0xCC),
+                ByteOperation::Remove { position: 1 },
+                replace(9, 0x00),
+            ],
+            6,
+        )
+        .expect_err("conflicts");
+
+        assert_eq!(
+            conflicts,
+            vec![
+                OperationConflict::ConflictingValues {
+                    operation_index: 2,
+                    earlier_operation_index: 0,
+                    original_position: 1,
+                    earlier_byte_value: 0xAA,
+                    new_byte_value: 0xBB,
+                },
+                OperationConflict::Overlap {
+                    operation_index: 4,
+                    earlier_operation_index: 3,
+                    original_position: 3,
+                },
+                OperationConflict::Overlap {
+                    operation_index: 5,
+                    earlier_operation_index: 0,
+                    original_position: 1,
+                },
+                OperationConflict::OutOfBounds {
+                    operation_index: 6,
+                    position: 9,
+                    file_size: 7,
+                },
+            ]
+        );
+        assert!(conflicts[0].to_string().contains("0xAA"));
+    }
+}
@@ -0,0 +1,470 @@
+//! Structured results for the single-byte operations
+//!
+//! `replace_single_byte_in_file` and friends return `Ok(())`. Their
+//! `*_with_report` variants run the same operation and return an
+//! `OperationReport` instead, so a caller can log or audit the result
+//! without parsing output.
+//!
+//! The report is assembled around the operation, not inside it: chunk
+//! counts, bytes processed and phase timings come from the operation's own
+//! `ReportEvent`s (collected by a reporter that also forwards every event to
+//! whatever reporter was already active), as does the path of a backup left
+//! on disk as an undo point (`keep_backup`), and the checksums are SHA-256
+//! digests of the file taken before and after. That is two extra read
+//! passes over the file, the price of a checksum an auditor can recompute.
+//!
+//! `format_operation_report_as_json` renders a report for tooling; its
+//! schema (`bfbo schema report`) comes from the same field table.
+
+use std::{
+    cell::RefCell,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::custody_report::format_rfc3339_utc;
+
+use crate::draft_workflow::open_at_offset;
+use crate::hash::HashAlgorithm;
+use crate::hash::sha256::{digest_to_hex, sha256_of_file};
+use crate::json_schema::{
+    JsonField, JsonKind, JsonObject, JsonValue, json_object_schema, render_json_object,
+};
+use crate::offset::Offset;
+use crate::operation_config::active_operation_config;
+use crate::reporter::{
+    OperationPhase, ReportEvent, Reporter, active_reporter, report_to, with_reporter,
+};
+
+/// What a single-byte operation did, for logging and audit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationReport {
+    /// Operation name as used in reporter events (e.g. "Byte Removal")
+    pub operation_name: String,
+    pub byte_position: u64,
+    /// Byte that was at `byte_position` before (`None` for insertions)
+    pub old_byte_value: Option<u8>,
+    /// Byte now at `byte_position` (`None` for removals)
+    pub new_byte_value: Option<u8>,
+    pub original_size: u64,
+    pub new_size: u64,
+    /// Bytes read from the original while building the draft
+    pub bytes_processed: u64,
+    /// Bucket-brigade chunks read while building the draft
+    pub chunk_count: usize,
+    pub original_sha256: [u8; 32],
+    pub result_sha256: [u8; 32],
+    /// Checksum verification kept of original and draft
+    /// (`verification_hash`)
+    pub verification_hash: HashAlgorithm,
+    /// Time spent in each workflow phase, in order
+    pub phase_timings: Vec<(OperationPhase, Duration)>,
+    /// Wall time of the operation itself (excludes the checksum passes)
+    pub total_duration: Duration,
+    /// Backup left on disk as an undo point (`keep_backup`, rotated
+    /// backups), `None` if it was removed
+    pub kept_backup_path: Option<PathBuf>,
+    /// Modification time carried over from the original
+    /// (`preserve_timestamps`), `None` if the file got the time of the edit
+    pub preserved_modified_time: Option<SystemTime>,
+}
+
+impl JsonObject for OperationReport {
+    const JSON_FIELDS: &'static [JsonField<Self>] = &[
+        JsonField {
+            name: "operation_name",
+            kind: JsonKind::String,
+            value: |report| JsonValue::String(report.operation_name.clone()),
+        },
+        JsonField {
+            name: "byte_position",
+            kind: JsonKind::Integer,
+            value: |report| JsonValue::Integer(report.byte_position),
+        },
+        JsonField {
+            name: "old_byte_value",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |report| {
+                JsonValue::optional(report.old_byte_value, |byte_value| {
+                    JsonValue::Integer(byte_value.into())
+                })
+            },
+        },
+        JsonField {
+            name: "new_byte_value",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |report| {
+                JsonValue::optional(report.new_byte_value, |byte_value| {
+                    JsonValue::Integer(byte_value.into())
+                })
+            },
+        },
+        JsonField {
+            name: "original_size",
+            kind: JsonKind::Integer,
+            value: |report| JsonValue::Integer(report.original_size),
+        },
+        JsonField {
+            name: "new_size",
+            kind: JsonKind::Integer,
+            value: |report| JsonValue::Integer(report.new_size),
+        },
+        JsonField {
+            name: "bytes_processed",
+            kind: JsonKind::Integer,
+            value: |report| JsonValue::Integer(report.bytes_processed),
+        },
+        JsonField {
+            name: "chunk_count",
+            kind: JsonKind::Integer,
+            value: |report| JsonValue::Integer(report.chunk_count as u64),
+        },
+        JsonField {
+            name: "original_sha256",
+            kind: JsonKind::Sha256Hex,
+            value: |report| JsonValue::String(digest_to_hex(&report.original_sha256)),
+        },
+        JsonField {
+            name: "result_sha256",
+            kind: JsonKind::Sha256Hex,
+            value: |report| JsonValue::String(digest_to_hex(&report.result_sha256)),
+        },
+        JsonField {
+            name: "verification_hash",
+            kind: JsonKind::Name(|| {
+                HashAlgorithm::ALL
+                    .iter()
+                    .map(|algorithm| algorithm.name())
+                    .collect()
+            }),
+            value: |report| JsonValue::String(report.verification_hash.name().to_string()),
+        },
+        JsonField {
+            name: "phase_timings",
+            kind: JsonKind::Array(&JsonKind::Object(json_object_schema::<PhaseTiming>)),
+            value: |report| {
+                JsonValue::Array(
+                    report
+                        .phase_timings
+                        .iter()
+                        .map(|&(phase, duration)| {
+                            JsonValue::Object(render_json_object(&PhaseTiming { phase, duration }))
+                        })
+                        .collect(),
+                )
+            },
+        },
+        JsonField {
+            name: "total_duration_us",
+            kind: JsonKind::Integer,
+            value: |report| JsonValue::Integer(duration_micros(report.total_duration)),
+        },
+        JsonField {
+            name: "kept_backup_path",
+            kind: JsonKind::Nullable(&JsonKind::String),
+            value: |report| {
+                JsonValue::optional(report.kept_backup_path.as_ref(), |backup_path| {
+                    JsonValue::String(backup_path.to_string_lossy().into_owned())
+                })
+            },
+        },
+        JsonField {
+            name: "preserved_modified_time",
+            kind: JsonKind::Nullable(&JsonKind::String),
+            value: |report| {
+                JsonValue::optional(report.preserved_modified_time, |modified_time| {
+                    let unix_seconds = modified_time
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |since_epoch| since_epoch.as_secs());
+                    JsonValue::String(format_rfc3339_utc(unix_seconds))
+                })
+            },
+        },
+    ];
+}
+
+/// One entry of `OperationReport::phase_timings`, as rendered to JSON
+struct PhaseTiming {
+    phase: OperationPhase,
+    duration: Duration,
+}
+
+impl JsonObject for PhaseTiming {
+    const JSON_FIELDS: &'static [JsonField<Self>] = &[
+        JsonField {
+            name: "phase",
+            kind: JsonKind::Name(|| {
+                OperationPhase::ALL
+                    .iter()
+                    .map(|phase| phase.name())
+                    .collect()
+            }),
+            value: |timing| JsonValue::String(timing.phase.name().to_string()),
+        },
+        JsonField {
+            name: "duration_us",
+            kind: JsonKind::Integer,
+            value: |timing| JsonValue::Integer(duration_micros(timing.duration)),
+        },
+    ];
+}
+
+fn duration_micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Renders a report as one JSON object (schema: `bfbo schema report`).
+///
+/// Digests are lower-case hex, durations whole microseconds, and the
+/// preserved modification time RFC 3339 UTC; absent values are `null`.
+pub fn format_operation_report_as_json(report: &OperationReport) -> String {
+    render_json_object(report)
+}
+
+/// Counts chunks and times phases, forwarding every event onward.
+struct ReportCollector {
+    outer_reporter: Option<Rc<dyn Reporter>>,
+    state: RefCell<CollectedTotals>,
+}
+
+#[derive(Default)]
+struct CollectedTotals {
+    bytes_processed: u64,
+    chunk_count: usize,
+    current_phase: Option<(OperationPhase, Instant)>,
+    phase_timings: Vec<(OperationPhase, Duration)>,
+    kept_backup_path: Option<PathBuf>,
+}
+
+impl CollectedTotals {
+    fn close_current_phase(&mut self) {
+        if let Some((phase, started_at)) = self.current_phase.take() {
+            self.phase_timings.push((phase, started_at.elapsed()));
+        }
+    }
+}
+
+impl Reporter for ReportCollector {
+    fn report(&self, event: &ReportEvent<'_>) {
+        {
+            let mut totals = self.state.borrow_mut();
+            match event {
+                ReportEvent::PhaseStarted { phase } => {
+                    totals.close_current_phase();
+                    totals.current_phase = Some((*phase, Instant::now()));
+                }
+                ReportEvent::ChunkProcessed { byte_count, .. } => {
+                    totals.chunk_count += 1;
+                    totals.bytes_processed += *byte_count as u64;
+                }
+                ReportEvent::BackupKept { backup_file_path } => {
+                    totals.kept_backup_path = Some(backup_file_path.to_path_buf());
+                }
+                ReportEvent::OperationFinished { .. } => totals.close_current_phase(),
+                _ => {}
+            }
+        }
+        report_to(self.outer_reporter.as_ref(), event);
+    }
+}
+
+/// Runs a single-byte `operation` on `file_path` and reports on it.
+///
+/// # Parameters
+/// - `operation_name`: Name for the report
+/// - `byte_position`: Position the operation targets
+/// - `reads_old_byte`: Whether a byte exists at `byte_position` before
+///   the operation (replace, remove) or not (insert)
+/// - `new_byte_value`: Byte written at `byte_position`, if any
+/// - `operation`: The operation itself
+///
+/// # Returns
+/// - `Ok(OperationReport)` if the operation succeeded
+/// - `Err(io::Error)` from the operation, or from the checksum passes
+pub(crate) fn run_with_operation_report(
+    operation_name: &str,
+    file_path: &Path,
+    byte_offset: Offset,
+    reads_old_byte: bool,
+    new_byte_value: Option<u8>,
+    operation: impl FnOnce() -> io::Result<()>,
+) -> io::Result<OperationReport> {
+    // Failures here are left for the operation to report in its own terms
+    let original_size = std::fs::metadata(file_path).map(|metadata| metadata.len());
+    let preserved_modified_time = if active_operation_config().preserve_timestamps() {
+        std::fs::metadata(file_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    } else {
+        None
+    };
+    let original_sha256 = sha256_of_file(file_path);
+    let old_byte_value = match &original_size {
+        Ok(file_size) if reads_old_byte => byte_offset
+            .resolve(*file_size)
+            .and_then(|byte_position| read_byte_at(file_path, byte_position))
+            .ok(),
+        _ => None,
+    };
+
+    let collector = Rc::new(ReportCollector {
+        outer_reporter: active_reporter(),
+        state: RefCell::new(CollectedTotals::default()),
+    });
+    let started_at = Instant::now();
+    with_reporter(collector.clone(), operation)?;
+    let total_duration = started_at.elapsed();
+
+    let mut totals = collector.state.borrow_mut();
+    totals.close_current_phase();
+
+    // The operation succeeded, so the offset resolved against this size
+    let original_size = original_size?;
+    Ok(OperationReport {
+        operation_name: operation_name.to_string(),
+        byte_position: byte_offset.resolve(original_size)?,
+        old_byte_value,
+        new_byte_value,
+        original_size,
+        new_size: std::fs::metadata(file_path)?.len(),
+        bytes_processed: totals.bytes_processed,
+        chunk_count: totals.chunk_count,
+        original_sha256: original_sha256?,
+        result_sha256: sha256_of_file(file_path)?,
+        verification_hash: active_operation_config().verification_hash(),
+        phase_timings: std::mem::take(&mut totals.phase_timings),
+        total_duration,
+        kept_backup_path: totals.kept_backup_path.take(),
+        preserved_modified_time,
+    })
+}
+
+fn read_byte_at(file_path: &Path, byte_position: u64) -> io::Result<u8> {
+    let mut byte_buffer = [0u8; 1];
+    open_at_offset(file_path, byte_position)?.read_exact(&mut byte_buffer)?;
+    Ok(byte_buffer[0])
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::sha256::sha256_of_bytes;
+    use crate::{
+        insert::insert_single_byte_into_file_with_report,
+        remove::remove_single_byte_from_file_with_report,
+        replace::replace_single_byte_in_file_with_report,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_operation_reports_for_single_byte_operations() {
+        let test_file = std::env::temp_dir().join("test_operation_report.bin");
+        let original_bytes: Vec<u8> = (0..100u8).collect();
+        std::fs::write(&test_file, &original_bytes).expect("create test file");
+
+        let report =
+            replace_single_byte_in_file_with_report(test_file.clone(), 70, 0xFF).expect("replace");
+        assert_eq!(report.old_byte_value, Some(70));
+        assert_eq!(report.new_byte_value, Some(0xFF));
+        assert_eq!((report.original_size, report.new_size), (100, 100));
+        assert_eq!(report.bytes_processed, 100);
+        assert_eq!(report.chunk_count, 1);
+        assert_eq!(report.original_sha256, sha256_of_bytes(&original_bytes));
+        let phases: Vec<OperationPhase> = report
+            .phase_timings
+            .iter()
+            .map(|(phase, _)| *phase)
+            .collect();
+        assert_eq!(
+            phases,
+            [
+                OperationPhase::Backup,
+                OperationPhase::Draft,
+                OperationPhase::Verify,
+                OperationPhase::Replace,
+                OperationPhase::Cleanup
+            ]
+        );
+
+        let report =
+            remove_single_byte_from_file_with_report(test_file.clone(), 0).expect("remove");
+        assert_eq!(report.old_byte_value, Some(0));
+        assert_eq!(report.new_byte_value, None);
+        assert_eq!((report.original_size, report.new_size), (100, 99));
+
+        let report = insert_single_byte_into_file_with_report(test_file.clone(), 99, 0x42)
+            .expect("insert at EOF");
+        assert_eq!(report.old_byte_value, None);
+        assert_eq!(report.new_size, 100);
+        let final_bytes = std::fs::read(&test_file).expect("read result");
+        assert_eq!(report.result_sha256, sha256_of_bytes(&final_bytes));
+        assert_eq!(report.kept_backup_path, None);
+
+        // A deliberately kept backup is the undo point the report points to
+        let keep_config = crate::OperationConfig::builder()
+            .keep_backup(true)
+            .build()
+            .expect("valid config");
+        let report = crate::with_operation_config(&keep_config, || {
+            replace_single_byte_in_file_with_report(test_file.clone(), 0, 0x00)
+        })
+        .expect("replace keeping backup");
+        let kept_backup_path = report.kept_backup_path.expect("backup kept");
+        assert_eq!(
+            std::fs::read(&kept_backup_path).expect("read backup"),
+            final_bytes
+        );
+        let _ = std::fs::remove_file(crate::backup_manifest::backup_manifest_path(
+            &kept_backup_path,
+        ));
+        let _ = std::fs::remove_file(&kept_backup_path);
+
+        assert!(remove_single_byte_from_file_with_report(test_file.clone(), 500).is_err());
+
+        // Timestamps carried over on request, otherwise the time of the edit
+        let old_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let set_old_times = || {
+            std::fs::File::options()
+                .write(true)
+                .open(&test_file)
+                .and_then(|file| {
+                    file.set_times(
+                        std::fs::FileTimes::new()
+                            .set_accessed(old_time)
+                            .set_modified(old_time),
+                    )
+                })
+                .expect("set times")
+        };
+        let modified_time = || {
+            std::fs::metadata(&test_file)
+                .and_then(|metadata| metadata.modified())
+                .expect("modified time")
+        };
+        set_old_times();
+        let report =
+            replace_single_byte_in_file_with_report(test_file.clone(), 1, 0x01).expect("replace");
+        assert_eq!(report.preserved_modified_time, None);
+        assert_ne!(modified_time(), old_time);
+
+        set_old_times();
+        let preserve_config = crate::OperationConfig::builder()
+            .preserve_timestamps(true)
+            .build()
+            .expect("valid config");
+        let report = crate::with_operation_config(&preserve_config, || {
+            replace_single_byte_in_file_with_report(test_file.clone(), 1, 0x02)
+        })
+        .expect("replace preserving timestamps");
+        assert_eq!(report.preserved_modified_time, Some(old_time));
+        assert_eq!(modified_time(), old_time);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,474 @@
+//! Orphaned `.backup`/`.draft`/`.journal` files left by interrupted runs
+//!
+//! A crash (or a kill) between the backup and the cleanup leaves artifacts
+//! next to the target. `scan_for_orphans` lists every target in a directory
+//! that has any, works out how far the interrupted operation got, and
+//! offers the actions that are safe from that state:
+//!
+//! - `Complete`: rename the draft over the target, remove the backup
+//! - `RollBack`: rename the backup over the target, remove the draft
+//! - `CleanUp`: remove the leftovers, leave the target as it is
+//!
+//! With a journal (see `journal.rs`) the state is exact and the one action
+//! `journal::recover` would take is offered. Without one, the state is
+//! inferred from which files exist and whether the backup has the target's
+//! content (size first, then SHA-256). A draft is never offered for
+//! completion then, since nothing says it was finished and verified,
+//! unless it is the only copy left. A backup with a manifest (see
+//! `backup_manifest.rs`) must match it, or nothing is offered.
+//!
+//! Backups are looked for under the active `OperationConfig`'s suffix and
+//! directory; a backup in a separate backup directory is only found through
+//! a draft or journal next to its target. The scan only reads;
+//! `resolve_orphan` carries out a chosen action.
+
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::backup_manifest::{backup_manifest_path, verify_backup_manifest};
+use crate::draft_workflow::{
+    DRAFT_FILE_SUFFIX, JOURNAL_FILE_SUFFIX, build_artifact_paths, files_have_same_content,
+    replace_with_draft,
+};
+use crate::journal::{JournalPhase, RecoveryAction, plan_recovery, recover};
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::reporter::report_detail;
+
+/// How far an interrupted operation got, as far as the files show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrphanState {
+    /// A journal records the last phase; `recovery_action` is what
+    /// `journal::recover` would do
+    Journaled {
+        phase: JournalPhase,
+        recovery_action: RecoveryAction,
+    },
+    /// A journal is present but unreadable, or matches nothing on disk;
+    /// nothing is offered (see `reason`)
+    StuckJournal { reason: String },
+    /// The backup does not match its manifest (wrong size or SHA-256, or
+    /// a copy of another file); nothing is offered (see `reason`)
+    CorruptBackup { reason: String },
+    /// A draft next to a target the backup (if any) still matches: the
+    /// operation stopped before its rename
+    UnfinishedDraft,
+    /// The target itself is gone; only the backup and/or draft remain
+    TargetMissing,
+    /// A backup with the target's exact content: nothing to undo
+    RedundantBackup,
+    /// A backup that differs from the target: the operation finished but
+    /// its cleanup did not, or the backup was kept on purpose
+    RetainedBackup,
+}
+
+impl OrphanState {
+    /// Stable name used in CLI output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrphanState::Journaled { .. } => "journaled",
+            OrphanState::StuckJournal { .. } => "stuck-journal",
+            OrphanState::CorruptBackup { .. } => "corrupt-backup",
+            OrphanState::UnfinishedDraft => "unfinished-draft",
+            OrphanState::TargetMissing => "target-missing",
+            OrphanState::RedundantBackup => "redundant-backup",
+            OrphanState::RetainedBackup => "retained-backup",
+        }
+    }
+}
+
+/// A way to resolve an orphan (see the module docs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanAction {
+    Complete,
+    RollBack,
+    CleanUp,
+}
+
+impl OrphanAction {
+    /// Stable name used in CLI output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrphanAction::Complete => "complete",
+            OrphanAction::RollBack => "roll-back",
+            OrphanAction::CleanUp => "clean-up",
+        }
+    }
+}
+
+/// One target with leftover artifacts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanReport {
+    pub target_path: PathBuf,
+    pub backup_file_path: Option<PathBuf>,
+    /// Manifest of the backup, if it has one
+    pub backup_manifest_path: Option<PathBuf>,
+    pub draft_file_path: Option<PathBuf>,
+    pub journal_file_path: Option<PathBuf>,
+    pub state: OrphanState,
+    /// Offered actions, recommended first; empty if only manual
+    /// inspection is safe
+    pub actions: Vec<OrphanAction>,
+}
+
+/// Finds leftover artifacts in `directory` (not recursive) and classifies
+/// each target that has any.
+///
+/// # Returns
+/// - `Ok(Vec<OrphanReport>)`, sorted by target path; empty if nothing is
+///   left over
+/// - `Err(io::Error)` if the directory or an artifact cannot be read
+pub fn scan_for_orphans(directory: &Path) -> io::Result<Vec<OrphanReport>> {
+    let config = active_operation_config();
+    let artifact_suffixes = [
+        config.backup_suffix(),
+        DRAFT_FILE_SUFFIX,
+        JOURNAL_FILE_SUFFIX,
+    ];
+
+    let mut target_paths = BTreeSet::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        for suffix in artifact_suffixes {
+            if let Some(target_name) = file_name.strip_suffix(suffix)
+                && !target_name.is_empty()
+            {
+                target_paths.insert(directory.join(target_name));
+            }
+        }
+    }
+
+    let mut reports = Vec::with_capacity(target_paths.len());
+    for target_path in target_paths {
+        let report = classify_orphan(target_path)?;
+        report_detail!(
+            "   {} {}",
+            report.state.name(),
+            report.target_path.display()
+        );
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+fn classify_orphan(target_path: PathBuf) -> io::Result<OrphanReport> {
+    let artifact_paths = build_artifact_paths(&target_path)?;
+    let existing = |path: PathBuf| Some(path).filter(|path| path.is_file());
+    let backup_file_path = existing(artifact_paths.backup_file_path);
+    let draft_file_path = existing(artifact_paths.draft_file_path);
+    let journal_file_path = existing(artifact_paths.journal_file_path);
+    let backup_manifest_path = backup_file_path
+        .as_deref()
+        .and_then(|backup_path| existing(backup_manifest_path(backup_path)));
+
+    let journaled = match plan_recovery(&target_path) {
+        Ok(plan) => plan.map(|plan| {
+            let action = match plan.action {
+                RecoveryAction::CommitDraft | RecoveryAction::FinishCleanup => {
+                    OrphanAction::Complete
+                }
+                RecoveryAction::RollBack | RecoveryAction::RestoreBackup => OrphanAction::RollBack,
+            };
+            let state = OrphanState::Journaled {
+                phase: plan.journal.phase,
+                recovery_action: plan.action,
+            };
+            (state, vec![action])
+        }),
+        Err(e) => Some((
+            OrphanState::StuckJournal {
+                reason: e.to_string(),
+            },
+            Vec::new(),
+        )),
+    };
+
+    let backup_problem = match (&journaled, &backup_file_path) {
+        (None, Some(backup_path)) => verify_backup_manifest(backup_path, &target_path).err(),
+        _ => None,
+    };
+
+    let (state, actions) = if let Some(journaled) = journaled {
+        journaled
+    } else if let Some(backup_problem) = backup_problem {
+        (
+            OrphanState::CorruptBackup {
+                reason: backup_problem.to_string(),
+            },
+            Vec::new(),
+        )
+    } else if !target_path.is_file() {
+        let action = if backup_file_path.is_some() {
+            OrphanAction::RollBack
+        } else {
+            // The draft is the only copy left
+            OrphanAction::Complete
+        };
+        (OrphanState::TargetMissing, vec![action])
+    } else {
+        match &backup_file_path {
+            Some(backup_path) if !files_have_same_content(backup_path, &target_path)? => (
+                OrphanState::RetainedBackup,
+                vec![OrphanAction::CleanUp, OrphanAction::RollBack],
+            ),
+            _ if draft_file_path.is_some() => {
+                (OrphanState::UnfinishedDraft, vec![OrphanAction::CleanUp])
+            }
+            _ => (OrphanState::RedundantBackup, vec![OrphanAction::CleanUp]),
+        }
+    };
+
+    Ok(OrphanReport {
+        target_path,
+        backup_file_path,
+        backup_manifest_path,
+        draft_file_path,
+        journal_file_path,
+        state,
+        actions,
+    })
+}
+
+/// Carries out one of the actions a report offers.
+///
+/// Journaled orphans are resolved with `journal::recover`, which re-checks
+/// the files first.
+///
+/// # Returns
+/// - `Ok(())` once the artifacts are gone
+/// - `Err(io::Error)` with `ErrorKind::InvalidInput` if `action` is not
+///   offered for this report, or any I/O error from the renames/removals
+pub fn resolve_orphan(report: &OrphanReport, action: OrphanAction) -> io::Result<()> {
+    if !report.actions.contains(&action) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} is not offered for {} ({})",
+                action.name(),
+                report.target_path.display(),
+                report.state.name()
+            ),
+        ));
+    }
+    report_detail!(
+        "Resolving {} ({}): {}",
+        report.target_path.display(),
+        report.state.name(),
+        action.name()
+    );
+
+    if let OrphanState::Journaled { .. } = report.state {
+        return recover(report.target_path.clone()).map(|_| ());
+    }
+    let replacement_path = match action {
+        OrphanAction::Complete => report.draft_file_path.as_ref(),
+        OrphanAction::RollBack => report.backup_file_path.as_ref(),
+        OrphanAction::CleanUp => None,
+    };
+    if let Some(replacement_path) = replacement_path {
+        replace_with_draft(replacement_path, &report.target_path)?;
+    }
+    // Whatever was not renamed over the target is left over
+    for artifact_path in [&report.draft_file_path, &report.backup_file_path]
+        .into_iter()
+        .flatten()
+        .filter(|artifact_path| Some(*artifact_path) != replacement_path)
+    {
+        fs::remove_file(artifact_path)?;
+    }
+    // The backup is gone either way, and its manifest with it
+    if let Some(manifest_path) = &report.backup_manifest_path {
+        fs::remove_file(manifest_path)?;
+    }
+    Ok(())
+}
+
+/// Like `resolve_orphan`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn resolve_orphan_with_config(
+    report: &OrphanReport,
+    action: OrphanAction,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || resolve_orphan(report, action))
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_for_orphans_classifies_leftovers() {
+        let directory = std::env::temp_dir().join("test_orphan_scan");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let write = |name: &str, content: &[u8]| {
+            fs::write(directory.join(name), content).expect("write file")
+        };
+
+        write("clean.bin", b"no artifacts");
+        write("draft.bin", b"ABCD");
+        write("draft.bin.backup", b"ABCD");
+        write("draft.bin.draft", b"AB");
+        write("missing.bin.backup", b"only copy");
+        write("redundant.bin", b"same");
+        write("redundant.bin.backup", b"same");
+        write("retained.bin", b"new!");
+        write("retained.bin.backup", b"old!");
+
+        let reports = scan_for_orphans(&directory).expect("scan");
+        let summary: Vec<(String, &str, Vec<OrphanAction>)> = reports
+            .iter()
+            .map(|report| {
+                let file_name = report.target_path.file_name().expect("file name");
+                (
+                    file_name.to_string_lossy().to_string(),
+                    report.state.name(),
+                    report.actions.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "draft.bin".to_string(),
+                    "unfinished-draft",
+                    vec![OrphanAction::CleanUp]
+                ),
+                (
+                    "missing.bin".to_string(),
+                    "target-missing",
+                    vec![OrphanAction::RollBack]
+                ),
+                (
+                    "redundant.bin".to_string(),
+                    "redundant-backup",
+                    vec![OrphanAction::CleanUp]
+                ),
+                (
+                    "retained.bin".to_string(),
+                    "retained-backup",
+                    vec![OrphanAction::CleanUp, OrphanAction::RollBack]
+                ),
+            ]
+        );
+
+        // Carry out each recommended action; only the targets remain
+        for report in &reports {
+            assert!(resolve_orphan(report, OrphanAction::Complete).is_err());
+            resolve_orphan(report, report.actions[0]).expect("resolve");
+        }
+        assert!(scan_for_orphans(&directory).expect("rescan").is_empty());
+        assert_eq!(
+            fs::read(directory.join("missing.bin")).expect("restored"),
+            b"only copy"
+        );
+        assert_eq!(
+            fs::read(directory.join("draft.bin")).expect("kept"),
+            b"ABCD"
+        );
+        assert_eq!(
+            fs::read(directory.join("retained.bin")).expect("kept"),
+            b"new!"
+        );
+
+        // A kept backup that rotted no longer matches its manifest
+        let keep_config = crate::OperationConfig::builder()
+            .keep_backup(true)
+            .build()
+            .expect("valid config");
+        crate::replace_single_byte_in_file_with_config(
+            directory.join("retained.bin"),
+            0,
+            b'N',
+            &keep_config,
+        )
+        .expect("replace keeping backup");
+        write("retained.bin.backup", b"nEw!");
+        let reports = scan_for_orphans(&directory).expect("scan rotted");
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].state.name(), "corrupt-backup");
+        assert!(reports[0].actions.is_empty());
+        assert!(reports[0].backup_manifest_path.is_some());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_scan_for_orphans_uses_journal() {
+        use crate::operation_config::{OperationConfig, with_operation_config};
+        use crate::remove_single_byte_from_file_with_config;
+
+        let directory = std::env::temp_dir().join("test_orphan_scan_journal");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        fs::write(&target_path, b"0123").expect("write file");
+
+        // A kept backup of a finished operation, then a crash after the
+        // draft was verified: the journal says the draft can be committed
+        let config = OperationConfig::builder()
+            .journal(true)
+            .keep_backup(true)
+            .build()
+            .expect("valid config");
+        remove_single_byte_from_file_with_config(target_path.clone(), 0, &config).expect("remove");
+        with_operation_config(&config, || {
+            let artifact_paths = build_artifact_paths(&target_path)?;
+            let mut journal = crate::journal::OperationJournal::begin(
+                &target_path,
+                "Byte Removal",
+                &artifact_paths,
+                true,
+                &config,
+            )?;
+            fs::copy(&target_path, &artifact_paths.backup_file_path)?;
+            crate::backup_manifest::record_backup_manifest(
+                &artifact_paths.backup_file_path,
+                &target_path,
+                "Byte Removal",
+                &config,
+            )?;
+            journal.mark(JournalPhase::BackedUp)?;
+            fs::write(&artifact_paths.draft_file_path, b"23")?;
+            journal.mark_draft_verified(&artifact_paths.draft_file_path)?;
+            std::mem::forget(journal);
+            Ok(())
+        })
+        .expect("simulate crash");
+
+        let reports = scan_for_orphans(&directory).expect("scan");
+        assert_eq!(reports.len(), 1);
+        assert_eq!(
+            reports[0].state,
+            OrphanState::Journaled {
+                phase: JournalPhase::DraftVerified,
+                recovery_action: RecoveryAction::CommitDraft
+            }
+        );
+        assert_eq!(reports[0].actions, vec![OrphanAction::Complete]);
+
+        resolve_orphan(&reports[0], OrphanAction::Complete).expect("resolve");
+        assert_eq!(fs::read(&target_path).expect("read file"), b"23");
+        // The journal recorded keep_backup, so only the backup remains
+        let reports = scan_for_orphans(&directory).expect("rescan");
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].state, OrphanState::RetainedBackup);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,450 @@
+//! Page-cache hints and unbuffered drafts for rewrites of large files
+//!
+//! A rewrite reads the whole original, copies it to a backup and writes a
+//! whole draft. Through the page cache, rewriting a 50 GB file pushes
+//! everything else a production host keeps cached (database pages, hot
+//! binaries) out of memory. `OperationConfigBuilder::page_cache_mode` opts
+//! into:
+//! - `PageCacheMode::DropBehind`: readers opened for a rewrite are advised
+//!   `SEQUENTIAL` (larger readahead) and `NOREUSE` (read once, so the pages
+//!   do not displace the working set; Linux 6.3 and later). The draft is
+//!   written back every `DROP_BEHIND_INTERVAL` bytes (`sync_file_range`)
+//!   and those pages are dropped (`DONTNEED`). Once the draft is verified,
+//!   whatever the original, the backup and the draft still have cached is
+//!   dropped too.
+//! - `PageCacheMode::Unbuffered`: the same, but the draft is opened with
+//!   `O_DIRECT` and written from an aligned staging buffer in whole
+//!   `DIRECT_IO_ALIGNMENT` blocks; the unaligned tail is written after
+//!   `O_DIRECT` is switched off. A filesystem that refuses `O_DIRECT`
+//!   gets drop-behind instead. The source is still read through the cache,
+//!   since its reads start at arbitrary offsets.
+//!
+//! On macOS both modes set `F_NOCACHE` on the source and the draft instead.
+//! Other platforms ignore the setting. Every mode produces the same file.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::operation_config::{PageCacheMode, active_operation_config};
+use crate::reporter::report_detail;
+
+/// Alignment of `O_DIRECT` buffers, offsets and lengths (4 KiB covers
+/// 512-byte and 4 KiB logical sectors)
+pub const DIRECT_IO_ALIGNMENT: usize = 4096;
+
+/// Size of the aligned staging buffer of an unbuffered draft
+pub const DIRECT_IO_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Draft bytes written back and dropped from the cache at a time in
+/// drop-behind mode
+pub const DROP_BEHIND_INTERVAL: u64 = 8 * 1024 * 1024;
+
+/// Applies the active mode's read-once hints to a reader of a rewrite.
+pub(crate) fn advise_reader(file: &File) {
+    if active_operation_config().page_cache_mode() != PageCacheMode::Normal {
+        platform::advise_streaming(file);
+    }
+}
+
+/// Drops what the given files still have in the page cache; a no-op in
+/// `PageCacheMode::Normal`. Called once the draft is verified, so nothing
+/// reads them again through this operation.
+pub(crate) fn release_cached_pages(file_paths: &[&Path]) {
+    if active_operation_config().page_cache_mode() == PageCacheMode::Normal {
+        return;
+    }
+    for file_path in file_paths {
+        if let Ok(file) = File::open(file_path) {
+            platform::drop_pages(&file);
+        }
+    }
+}
+
+/// The draft file of a rewrite, written in the active page-cache mode.
+///
+/// It sits under the draft's `BufWriter`. An unbuffered draft only writes
+/// whole blocks, so `flush` may leave up to one block staged; `finish`
+/// writes it.
+pub(crate) struct DraftFile {
+    file: File,
+    drop_behind: bool,
+    direct_staging: Option<DirectStaging>,
+    bytes_written: u64,
+    bytes_dropped: u64,
+}
+
+/// Block-aligned staging buffer of an `O_DIRECT` draft
+struct DirectStaging {
+    buffer: Vec<u8>,
+    start: usize,
+    filled: usize,
+}
+
+impl DirectStaging {
+    /// Over-allocates by one alignment unit and starts at the first aligned
+    /// address inside, which needs no custom allocator.
+    fn new() -> Option<DirectStaging> {
+        let buffer = vec![0u8; DIRECT_IO_BUFFER_SIZE + DIRECT_IO_ALIGNMENT];
+        let start = buffer.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+        (start < DIRECT_IO_ALIGNMENT).then_some(DirectStaging {
+            buffer,
+            start,
+            filled: 0,
+        })
+    }
+
+    fn block(&mut self) -> &mut [u8] {
+        &mut self.buffer[self.start..self.start + DIRECT_IO_BUFFER_SIZE]
+    }
+
+    /// Writes the whole aligned blocks staged so far and keeps the rest.
+    fn write_whole_blocks(&mut self, file: &mut File) -> io::Result<u64> {
+        let whole_length = self.filled / DIRECT_IO_ALIGNMENT * DIRECT_IO_ALIGNMENT;
+        file.write_all(&self.block()[..whole_length])?;
+        let filled = self.filled;
+        self.block().copy_within(whole_length..filled, 0);
+        self.filled -= whole_length;
+        Ok(whole_length as u64)
+    }
+}
+
+impl DraftFile {
+    /// Creates (or truncates) the draft at `draft_file_path` for the
+    /// active page-cache mode.
+    pub(crate) fn create(draft_file_path: &Path) -> io::Result<DraftFile> {
+        let page_cache_mode = active_operation_config().page_cache_mode();
+        let mut options = OpenOptions::new();
+        options.create(true).write(true).truncate(true);
+
+        let mut draft_file = DraftFile {
+            file: options.open(draft_file_path)?,
+            drop_behind: page_cache_mode != PageCacheMode::Normal,
+            direct_staging: None,
+            bytes_written: 0,
+            bytes_dropped: 0,
+        };
+        if page_cache_mode == PageCacheMode::Normal {
+            return Ok(draft_file);
+        }
+        platform::advise_streaming(&draft_file.file);
+
+        if page_cache_mode == PageCacheMode::Unbuffered
+            && let Some(direct_staging) = DirectStaging::new()
+        {
+            match platform::open_direct(&options, draft_file_path) {
+                Ok(Some(direct_file)) => {
+                    draft_file.file = direct_file;
+                    draft_file.direct_staging = Some(direct_staging);
+                }
+                Ok(None) => {}
+                // Filesystems without O_DIRECT refuse the open with EINVAL
+                Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                    report_detail!("O_DIRECT not supported here; writing with drop-behind");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(draft_file)
+    }
+
+    /// Whether bytes may be copied straight into `file_mut()` (not while
+    /// `O_DIRECT` needs aligned writes).
+    pub(crate) fn accepts_kernel_copy(&self) -> bool {
+        self.direct_staging.is_none()
+    }
+
+    /// The underlying file, for kernel-side copies; report them with
+    /// `note_written`.
+    pub(crate) fn file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    /// Accounts for `byte_count` bytes appended to the draft; in
+    /// drop-behind mode, every `DROP_BEHIND_INTERVAL` bytes are written
+    /// back and dropped from the cache.
+    pub(crate) fn note_written(&mut self, byte_count: u64) -> io::Result<()> {
+        self.bytes_written += byte_count;
+        let pending_byte_count = self.bytes_written - self.bytes_dropped;
+        if self.drop_behind && pending_byte_count >= DROP_BEHIND_INTERVAL {
+            platform::write_back_and_drop(&self.file, self.bytes_dropped, pending_byte_count)?;
+            self.bytes_dropped = self.bytes_written;
+        }
+        Ok(())
+    }
+
+    /// Writes what is still staged, drops the rest of the draft from the
+    /// cache (drop-behind), and returns the file.
+    pub(crate) fn finish(mut self) -> io::Result<File> {
+        if let Some(mut direct_staging) = self.direct_staging.take() {
+            self.bytes_written += direct_staging.write_whole_blocks(&mut self.file)?;
+            if direct_staging.filled > 0 {
+                platform::end_direct(&self.file)?;
+                let filled = direct_staging.filled;
+                self.file.write_all(&direct_staging.block()[..filled])?;
+                self.bytes_written += filled as u64;
+            }
+        }
+        if self.drop_behind {
+            platform::write_back_and_drop(
+                &self.file,
+                self.bytes_dropped,
+                self.bytes_written - self.bytes_dropped,
+            )?;
+            self.bytes_dropped = self.bytes_written;
+        }
+        Ok(self.file)
+    }
+}
+
+impl Write for DraftFile {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let Some(direct_staging) = &mut self.direct_staging else {
+            let bytes_written = self.file.write(bytes)?;
+            self.note_written(bytes_written as u64)?;
+            return Ok(bytes_written);
+        };
+        let filled = direct_staging.filled;
+        let copied_length = bytes.len().min(DIRECT_IO_BUFFER_SIZE - filled);
+        direct_staging.block()[filled..filled + copied_length]
+            .copy_from_slice(&bytes[..copied_length]);
+        direct_staging.filled += copied_length;
+        if direct_staging.filled == DIRECT_IO_BUFFER_SIZE {
+            self.bytes_written += direct_staging.write_whole_blocks(&mut self.file)?;
+        }
+        Ok(copied_length)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{
+        ffi::c_long,
+        fs::{File, OpenOptions},
+        io,
+        os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+        path::Path,
+    };
+
+    const POSIX_FADV_SEQUENTIAL: i32 = 2;
+    #[cfg(not(target_arch = "s390x"))]
+    const POSIX_FADV_DONTNEED: i32 = 4;
+    #[cfg(not(target_arch = "s390x"))]
+    const POSIX_FADV_NOREUSE: i32 = 5;
+    #[cfg(target_arch = "s390x")]
+    const POSIX_FADV_DONTNEED: i32 = 6;
+    #[cfg(target_arch = "s390x")]
+    const POSIX_FADV_NOREUSE: i32 = 7;
+
+    const SYNC_FILE_RANGE_WAIT_BEFORE: u32 = 1;
+    const SYNC_FILE_RANGE_WRITE: u32 = 2;
+    const SYNC_FILE_RANGE_WAIT_AFTER: u32 = 4;
+
+    const F_GETFL: i32 = 3;
+    const F_SETFL: i32 = 4;
+
+    // O_DIRECT differs between architectures; elsewhere drafts stay cached
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    const O_DIRECT: Option<i32> = Some(0o40000);
+    #[cfg(any(target_arch = "aarch64", target_arch = "arm"))]
+    const O_DIRECT: Option<i32> = Some(0o200000);
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "arm"
+    )))]
+    const O_DIRECT: Option<i32> = None;
+
+    unsafe extern "C" {
+        fn posix_fadvise(fd: i32, offset: c_long, length: c_long, advice: i32) -> i32;
+        fn sync_file_range(fd: i32, offset: i64, byte_count: i64, flags: u32) -> i32;
+        fn fcntl(fd: i32, command: i32, ...) -> i32;
+    }
+
+    /// A hint that fails changes nothing, so its result is ignored.
+    fn advise(file: &File, offset: u64, length: u64, advice: i32) {
+        // SAFETY: integer arguments only; the descriptor is open
+        unsafe { posix_fadvise(file.as_raw_fd(), offset as c_long, length as c_long, advice) };
+    }
+
+    pub(super) fn advise_streaming(file: &File) {
+        advise(file, 0, 0, POSIX_FADV_SEQUENTIAL);
+        advise(file, 0, 0, POSIX_FADV_NOREUSE);
+    }
+
+    pub(super) fn drop_pages(file: &File) {
+        advise(file, 0, 0, POSIX_FADV_DONTNEED);
+    }
+
+    /// `DONTNEED` skips dirty pages, so the range is written back first.
+    pub(super) fn write_back_and_drop(file: &File, offset: u64, length: u64) -> io::Result<()> {
+        let flags =
+            SYNC_FILE_RANGE_WAIT_BEFORE | SYNC_FILE_RANGE_WRITE | SYNC_FILE_RANGE_WAIT_AFTER;
+        // SAFETY: integer arguments only; the descriptor is open
+        if unsafe { sync_file_range(file.as_raw_fd(), offset as i64, length as i64, flags) } != 0 {
+            // Not supported by this filesystem: a data sync does the same
+            file.sync_data()?;
+        }
+        advise(file, offset, length, POSIX_FADV_DONTNEED);
+        Ok(())
+    }
+
+    pub(super) fn open_direct(options: &OpenOptions, file_path: &Path) -> io::Result<Option<File>> {
+        let Some(o_direct) = O_DIRECT else {
+            return Ok(None);
+        };
+        options
+            .clone()
+            .custom_flags(o_direct)
+            .open(file_path)
+            .map(Some)
+    }
+
+    /// Clears `O_DIRECT` so the unaligned tail can be written.
+    pub(super) fn end_direct(file: &File) -> io::Result<()> {
+        let Some(o_direct) = O_DIRECT else {
+            return Ok(());
+        };
+        // SAFETY: F_GETFL takes no argument and F_SETFL an int; the
+        // descriptor is open
+        let status_flags = unsafe { fcntl(file.as_raw_fd(), F_GETFL) };
+        if status_flags < 0
+            || unsafe { fcntl(file.as_raw_fd(), F_SETFL, status_flags & !o_direct) } != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{
+        fs::{File, OpenOptions},
+        io,
+        os::fd::AsRawFd,
+        path::Path,
+    };
+
+    const F_NOCACHE: i32 = 48;
+
+    unsafe extern "C" {
+        fn fcntl(fd: i32, command: i32, ...) -> i32;
+    }
+
+    /// `F_NOCACHE` keeps this descriptor's reads and writes out of the
+    /// unified buffer cache; a failure only loses the hint.
+    pub(super) fn advise_streaming(file: &File) {
+        // SAFETY: F_NOCACHE takes an int; the descriptor is open
+        unsafe { fcntl(file.as_raw_fd(), F_NOCACHE, 1) };
+    }
+
+    pub(super) fn drop_pages(_file: &File) {}
+
+    pub(super) fn write_back_and_drop(_file: &File, _offset: u64, _length: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub(super) fn open_direct(
+        _options: &OpenOptions,
+        _file_path: &Path,
+    ) -> io::Result<Option<File>> {
+        Ok(None)
+    }
+
+    pub(super) fn end_direct(_file: &File) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod platform {
+    use std::{
+        fs::{File, OpenOptions},
+        io,
+        path::Path,
+    };
+
+    pub(super) fn advise_streaming(_file: &File) {}
+
+    pub(super) fn drop_pages(_file: &File) {}
+
+    pub(super) fn write_back_and_drop(_file: &File, _offset: u64, _length: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub(super) fn open_direct(
+        _options: &OpenOptions,
+        _file_path: &Path,
+    ) -> io::Result<Option<File>> {
+        Ok(None)
+    }
+
+    pub(super) fn end_direct(_file: &File) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation_config::{OperationConfig, with_operation_config};
+    use std::{fs, io::BufWriter};
+
+    #[test]
+    fn test_page_cache_modes_write_the_same_file() {
+        let content: Vec<u8> = (0..300_007u32).map(|value| (value % 253) as u8).collect();
+        for page_cache_mode in [
+            PageCacheMode::Normal,
+            PageCacheMode::DropBehind,
+            PageCacheMode::Unbuffered,
+        ] {
+            let config = OperationConfig::builder()
+                .page_cache_mode(page_cache_mode)
+                .build()
+                .expect("config");
+
+            // Odd-sized writes leave an unaligned tail for `finish`
+            let draft_path = std::env::temp_dir()
+                .join(format!("test_page_cache_{}.draft", page_cache_mode.name()));
+            with_operation_config(&config, || {
+                let mut draft_writer = BufWriter::new(DraftFile::create(&draft_path)?);
+                for piece in content.chunks(70_001) {
+                    draft_writer.write_all(piece)?;
+                }
+                draft_writer
+                    .into_inner()
+                    .map_err(io::IntoInnerError::into_error)?
+                    .finish()?;
+                Ok(())
+            })
+            .expect("write draft");
+            assert_eq!(fs::read(&draft_path).expect("read draft"), content);
+            let _ = fs::remove_file(&draft_path);
+
+            // A whole rewrite in this mode
+            let target_path = std::env::temp_dir()
+                .join(format!("test_page_cache_{}.bin", page_cache_mode.name()));
+            fs::write(&target_path, &content).expect("write target");
+            with_operation_config(&config, || {
+                crate::replace_single_byte_in_file(target_path.clone(), 200_000, b'X')
+            })
+            .expect("replace byte");
+            let mut expected = content.clone();
+            expected[200_000] = b'X';
+            assert_eq!(fs::read(&target_path).expect("read target"), expected);
+            let _ = fs::remove_file(&target_path);
+        }
+    }
+}
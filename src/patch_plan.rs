@@ -0,0 +1,656 @@
+//! Patch plans: ordered byte operations with optional guard conditions
+//!
+//! A plan is a list of entries, each holding one byte operation and zero or
+//! more guards. Guards are evaluated at resolution time, i.e. against the
+//! target file as it is immediately before that entry would run (after any
+//! earlier entries have been applied). If every guard holds, the operation is
+//! performed with the usual backup/draft/verify workflow; otherwise the entry
+//! is skipped and recorded as such, rather than failing the whole plan.
+//!
+//! This lets a plan act as a small declarative patch program that adapts to
+//! the current state of the target, e.g.:
+//! - "replace byte 0x10 with 0x02, only if byte 0x10 is currently 0x01"
+//! - "append a trailer byte, only if file size > 4096"
+//! - "remove byte 0, only if pattern `EF BB BF` is present"
+//!
+//! # Plan Text Format
+//! Plans can also be written as text, one entry per line (see
+//! `parse_plan_text`). Blank lines and `#` comments are ignored:
+//! ```text
+//! # op     offset  byte   guards...
+//! replace  0x10    02     when byte 0x10 == 01
+//! insert   4096    00     when size > 4096
+//! remove   0              when contains EFBBBF
+//! touch                   # verify and record only
+//! ```
+//!
+//! `touch` is the explicit no-op: it runs the preflight checks and hashes the
+//! file without modifying it, so a manifest can "verify and record" some
+//! files with the same machinery that edits others. Its outcome is reported
+//! as `Recorded`, never `Applied`.
+//!
+//! `format_plan_as_json` renders a parsed plan for tooling, one object per
+//! entry; its schema is `bfbo schema plan`.
+
+use std::{io, path::Path};
+
+use crate::byte_search::find_pattern_offsets_in_file;
+use crate::draft_workflow::{
+    check_original_preconditions, resolve_symlinked_target, validate_target_file,
+};
+use crate::hash::sha256::{digest_to_hex, sha256_of_file};
+use crate::json_schema::{
+    JsonField, JsonKind, JsonObject, JsonValue, json_object_schema, render_json_object,
+};
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::reporter::report_detail;
+use crate::value_parsing::{parse_hex_byte_text, parse_hex_bytes_text, parse_offset_text};
+use crate::writability::check_target_writable;
+use crate::{
+    insert_single_byte_into_file, remove_single_byte_from_file, replace_single_byte_in_file,
+};
+
+/// A single byte operation that a plan entry can perform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteOperation {
+    /// Hex-edit in place (no frame-shift)
+    Replace { position: u64, new_byte_value: u8 },
+    /// Add-a-byte (frame-shift +1); position == file size appends
+    Insert { position: u64, new_byte_value: u8 },
+    /// Remove-a-byte (frame-shift -1)
+    Remove { position: u64 },
+    /// No-op: preflight and hash the file, modify nothing
+    Touch,
+}
+
+/// A condition evaluated against the target file at resolution time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanGuard {
+    /// Holds if the byte at `position` exists and equals `expected_byte_value`
+    ByteEquals {
+        position: u64,
+        expected_byte_value: u8,
+    },
+    /// Holds if the file size in bytes is strictly greater than the value
+    FileSizeGreaterThan(u64),
+    /// Holds if the byte pattern occurs anywhere in the file
+    PatternPresent(Vec<u8>),
+}
+
+/// One step of a patch plan: an operation plus the guards that must all hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanEntry {
+    pub guards: Vec<PlanGuard>,
+    pub operation: ByteOperation,
+}
+
+impl PlanEntry {
+    /// Creates an entry with no guards (always applied).
+    pub fn unconditional(operation: ByteOperation) -> Self {
+        PlanEntry {
+            guards: Vec::new(),
+            operation,
+        }
+    }
+
+    /// Adds a guard to this entry (builder style).
+    pub fn only_if(mut self, guard: PlanGuard) -> Self {
+        self.guards.push(guard);
+        self
+    }
+}
+
+impl ByteOperation {
+    /// Keyword of the operation in the plan text format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ByteOperation::Replace { .. } => "replace",
+            ByteOperation::Insert { .. } => "insert",
+            ByteOperation::Remove { .. } => "remove",
+            ByteOperation::Touch => "touch",
+        }
+    }
+
+    fn position(&self) -> Option<u64> {
+        match self {
+            ByteOperation::Replace { position, .. }
+            | ByteOperation::Insert { position, .. }
+            | ByteOperation::Remove { position } => Some(*position),
+            ByteOperation::Touch => None,
+        }
+    }
+
+    fn new_byte_value(&self) -> Option<u8> {
+        match self {
+            ByteOperation::Replace { new_byte_value, .. }
+            | ByteOperation::Insert { new_byte_value, .. } => Some(*new_byte_value),
+            ByteOperation::Remove { .. } | ByteOperation::Touch => None,
+        }
+    }
+}
+
+impl PlanGuard {
+    /// Keyword of the guard in the plan text format (after `when`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlanGuard::ByteEquals { .. } => "byte",
+            PlanGuard::FileSizeGreaterThan(_) => "size",
+            PlanGuard::PatternPresent(_) => "contains",
+        }
+    }
+}
+
+impl JsonObject for PlanEntry {
+    const JSON_FIELDS: &'static [JsonField<Self>] = &[
+        JsonField {
+            name: "operation",
+            kind: JsonKind::Name(|| vec!["replace", "insert", "remove", "touch"]),
+            value: |entry| JsonValue::String(entry.operation.name().to_string()),
+        },
+        JsonField {
+            name: "position",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |entry| JsonValue::optional(entry.operation.position(), JsonValue::Integer),
+        },
+        JsonField {
+            name: "new_byte_value",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |entry| {
+                JsonValue::optional(entry.operation.new_byte_value(), |byte_value| {
+                    JsonValue::Integer(byte_value.into())
+                })
+            },
+        },
+        JsonField {
+            name: "guards",
+            kind: JsonKind::Array(&JsonKind::Object(json_object_schema::<PlanGuard>)),
+            value: |entry| {
+                JsonValue::Array(
+                    entry
+                        .guards
+                        .iter()
+                        .map(|guard| JsonValue::Object(render_json_object(guard)))
+                        .collect(),
+                )
+            },
+        },
+    ];
+}
+
+impl JsonObject for PlanGuard {
+    const JSON_FIELDS: &'static [JsonField<Self>] = &[
+        JsonField {
+            name: "guard",
+            kind: JsonKind::Name(|| vec!["byte", "size", "contains"]),
+            value: |guard| JsonValue::String(guard.name().to_string()),
+        },
+        JsonField {
+            name: "position",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |guard| match guard {
+                PlanGuard::ByteEquals { position, .. } => JsonValue::Integer(*position),
+                _ => JsonValue::Null,
+            },
+        },
+        JsonField {
+            name: "expected_byte_value",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |guard| match guard {
+                PlanGuard::ByteEquals {
+                    expected_byte_value,
+                    ..
+                } => JsonValue::Integer((*expected_byte_value).into()),
+                _ => JsonValue::Null,
+            },
+        },
+        JsonField {
+            name: "size_greater_than",
+            kind: JsonKind::Nullable(&JsonKind::Integer),
+            value: |guard| match guard {
+                PlanGuard::FileSizeGreaterThan(minimum_exclusive) => {
+                    JsonValue::Integer(*minimum_exclusive)
+                }
+                _ => JsonValue::Null,
+            },
+        },
+        JsonField {
+            name: "pattern",
+            kind: JsonKind::Nullable(&JsonKind::BytesHex),
+            value: |guard| match guard {
+                PlanGuard::PatternPresent(pattern) => JsonValue::String(digest_to_hex(pattern)),
+                _ => JsonValue::Null,
+            },
+        },
+    ];
+}
+
+/// Renders a plan as a JSON array, one object per entry (schema:
+/// `bfbo schema plan`).
+///
+/// Every entry has the same fields; those that do not apply to its
+/// operation or guard kind are `null`.
+pub fn format_plan_as_json(plan_entries: &[PlanEntry]) -> String {
+    let rendered_entries: Vec<String> = plan_entries.iter().map(render_json_object).collect();
+    format!("[{}]", rendered_entries.join(","))
+}
+
+/// What happened to one plan entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanEntryOutcome {
+    /// All guards held and the operation completed and verified
+    Applied,
+    /// A guard did not hold; the file was not touched for this entry
+    Skipped { failed_guard_index: usize },
+    /// A `Touch` entry: preflight passed and the file was hashed, unmodified
+    Recorded { sha256_hex: String },
+}
+
+/// Evaluates one guard against the current state of a file.
+///
+/// # Returns
+/// - `Ok(true)` if the condition holds
+/// - `Ok(false)` if it does not (including a `ByteEquals` position past EOF)
+/// - `Err(io::Error)` if the file cannot be read or the guard is malformed
+pub fn evaluate_plan_guard(file_path: &Path, guard: &PlanGuard) -> io::Result<bool> {
+    match guard {
+        PlanGuard::ByteEquals {
+            position,
+            expected_byte_value,
+        } => {
+            let file_size = std::fs::metadata(file_path)?.len();
+            if *position >= file_size {
+                return Ok(false);
+            }
+            let actual_byte_value = read_byte_at_position(file_path, *position)?;
+            Ok(actual_byte_value == *expected_byte_value)
+        }
+        PlanGuard::FileSizeGreaterThan(minimum_exclusive) => {
+            let file_size = std::fs::metadata(file_path)?.len();
+            Ok(file_size > *minimum_exclusive)
+        }
+        PlanGuard::PatternPresent(pattern) => {
+            let first_match = find_pattern_offsets_in_file(file_path, pattern, Some(1))?;
+            Ok(!first_match.is_empty())
+        }
+    }
+}
+
+/// Applies a patch plan to a file, entry by entry, in order.
+///
+/// # Behavior
+/// - Each entry's guards are evaluated in order immediately before that entry
+///   runs; the first guard that does not hold causes the entry to be skipped
+/// - Applied entries use the standard single-byte operations, so every change
+///   gets its own backup, draft, verification, and atomic replacement
+/// - An I/O or verification error stops the plan; entries already applied
+///   remain applied (each was individually verified and committed)
+///
+/// # Returns
+/// - `Ok(Vec<PlanEntryOutcome>)`, one outcome per entry
+/// - `Err(io::Error)` on the first failing guard evaluation or operation
+pub fn apply_patch_plan(
+    file_path: &Path,
+    plan_entries: &[PlanEntry],
+) -> io::Result<Vec<PlanEntryOutcome>> {
+    let mut outcomes = Vec::with_capacity(plan_entries.len());
+
+    for (entry_index, entry) in plan_entries.iter().enumerate() {
+        // =========================================
+        // Resolution Phase: evaluate guards now
+        // =========================================
+        let mut failed_guard_index: Option<usize> = None;
+        for (guard_index, guard) in entry.guards.iter().enumerate() {
+            if !evaluate_plan_guard(file_path, guard)? {
+                failed_guard_index = Some(guard_index);
+                break;
+            }
+        }
+
+        if let Some(failed_guard_index) = failed_guard_index {
+            report_detail!(
+                "Plan entry {} skipped: guard {} ({:?}) does not hold",
+                entry_index,
+                failed_guard_index,
+                entry.guards[failed_guard_index]
+            );
+            outcomes.push(PlanEntryOutcome::Skipped { failed_guard_index });
+            continue;
+        }
+
+        // =========================================
+        // Operation Phase
+        // =========================================
+        report_detail!("Plan entry {}: applying {:?}", entry_index, entry.operation);
+
+        apply_byte_operation(file_path, &entry.operation)?;
+        if entry.operation == ByteOperation::Touch {
+            let sha256_hex = digest_to_hex(&sha256_of_file(file_path)?);
+            outcomes.push(PlanEntryOutcome::Recorded { sha256_hex });
+        } else {
+            outcomes.push(PlanEntryOutcome::Applied);
+        }
+    }
+
+    Ok(outcomes)
+}
+
+/// Like `apply_patch_plan`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn apply_patch_plan_with_config(
+    file_path: &Path,
+    plan_entries: &[PlanEntry],
+    config: &OperationConfig,
+) -> io::Result<Vec<PlanEntryOutcome>> {
+    with_operation_config(config, || apply_patch_plan(file_path, plan_entries))
+}
+
+/// Runs one byte operation through its standard backup/draft/verify workflow.
+///
+/// `Touch` only runs the preflight checks the edits run before their
+/// backup: the symlink policy, the file exists and is a regular file, the
+/// file could be replaced, and the config's preconditions (memory budget,
+/// expected size and SHA-256) hold. Nothing is written.
+pub fn apply_byte_operation(file_path: &Path, operation: &ByteOperation) -> io::Result<()> {
+    match operation {
+        ByteOperation::Replace {
+            position,
+            new_byte_value,
+        } => replace_single_byte_in_file(file_path.to_path_buf(), *position, *new_byte_value),
+        ByteOperation::Insert {
+            position,
+            new_byte_value,
+        } => insert_single_byte_into_file(file_path.to_path_buf(), *position, *new_byte_value),
+        ByteOperation::Remove { position } => {
+            remove_single_byte_from_file(file_path.to_path_buf(), *position)
+        }
+        ByteOperation::Touch => run_touch_preflight(file_path),
+    }
+}
+
+fn run_touch_preflight(file_path: &Path) -> io::Result<()> {
+    let config = active_operation_config();
+    let target_path = resolve_symlinked_target(file_path, &config)?;
+    validate_target_file(&target_path)?;
+    check_target_writable(&target_path)?;
+    check_original_preconditions(&target_path, &config)
+}
+
+/// Like `apply_byte_operation`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn apply_byte_operation_with_config(
+    file_path: &Path,
+    operation: &ByteOperation,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || apply_byte_operation(file_path, operation))
+}
+
+/// Parses a plan written in the plan text format.
+///
+/// # Grammar (one entry per line)
+/// ```text
+/// replace <OFFSET> <HEX-BYTE> [when <GUARD>]...
+/// insert  <OFFSET> <HEX-BYTE> [when <GUARD>]...
+/// remove  <OFFSET>            [when <GUARD>]...
+/// touch                       [when <GUARD>]...
+///
+/// GUARD := byte <OFFSET> == <HEX-BYTE>
+///        | size > <N>
+///        | contains <HEX-BYTES>
+/// ```
+/// Offsets are decimal or `0x` hex; bytes are hex.
+///
+/// # Returns
+/// - `Ok(Vec<PlanEntry>)` in file order
+/// - `Err(io::Error)` (`InvalidData`) naming the first bad line
+pub fn parse_plan_text(plan_text: &str) -> io::Result<Vec<PlanEntry>> {
+    let mut plan_entries = Vec::new();
+
+    for (line_index, raw_line) in plan_text.lines().enumerate() {
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let plan_entry = parse_plan_line(line).map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Plan line {}: {}", line_index + 1, error),
+            )
+        })?;
+        plan_entries.push(plan_entry);
+    }
+
+    Ok(plan_entries)
+}
+
+/// Parses one non-empty, comment-stripped plan line.
+fn parse_plan_line(line: &str) -> io::Result<PlanEntry> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+
+    // Operation part: everything before the first `when`
+    let operation_token_count = tokens
+        .iter()
+        .position(|token| *token == "when")
+        .unwrap_or(tokens.len());
+    let operation = match &tokens[..operation_token_count] {
+        ["replace", offset, byte] => ByteOperation::Replace {
+            position: parse_offset_text(offset)?,
+            new_byte_value: parse_hex_byte_text(byte)?,
+        },
+        ["insert", offset, byte] => ByteOperation::Insert {
+            position: parse_offset_text(offset)?,
+            new_byte_value: parse_hex_byte_text(byte)?,
+        },
+        ["remove", offset] => ByteOperation::Remove {
+            position: parse_offset_text(offset)?,
+        },
+        ["touch"] => ByteOperation::Touch,
+        _ => return Err(malformed_plan_line("unrecognized operation", line)),
+    };
+
+    // Guard part: `when <GUARD>` repeated
+    let mut plan_entry = PlanEntry::unconditional(operation);
+    for guard_tokens in tokens[operation_token_count..]
+        .split(|token| *token == "when")
+        .skip(1)
+    {
+        let guard = match guard_tokens {
+            ["byte", offset, "==", byte] => PlanGuard::ByteEquals {
+                position: parse_offset_text(offset)?,
+                expected_byte_value: parse_hex_byte_text(byte)?,
+            },
+            ["size", ">", size] => PlanGuard::FileSizeGreaterThan(parse_offset_text(size)?),
+            ["contains", pattern] => PlanGuard::PatternPresent(parse_hex_bytes_text(pattern)?),
+            _ => return Err(malformed_plan_line("unrecognized guard", line)),
+        };
+        plan_entry = plan_entry.only_if(guard);
+    }
+
+    Ok(plan_entry)
+}
+
+fn malformed_plan_line(reason: &str, line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: `{}`", reason, line),
+    )
+}
+
+/// Reads the single byte at `position` (caller has checked bounds).
+fn read_byte_at_position(file_path: &Path, position: u64) -> io::Result<u8> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut source_file = std::fs::File::open(file_path)?;
+    source_file.seek(SeekFrom::Start(position))?;
+    let mut byte_buffer = [0u8; 1];
+    source_file.read_exact(&mut byte_buffer)?;
+    Ok(byte_buffer[0])
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_applies_entries_whose_guards_hold() {
+        let test_file = std::env::temp_dir().join("test_plan_guards_hold.bin");
+        std::fs::write(&test_file, vec![0x00, 0x01, 0x02, 0x03]).expect("create test file");
+
+        let plan = vec![
+            PlanEntry::unconditional(ByteOperation::Replace {
+                position: 1,
+                new_byte_value: 0xAA,
+            })
+            .only_if(PlanGuard::ByteEquals {
+                position: 1,
+                expected_byte_value: 0x01,
+            }),
+            PlanEntry::unconditional(ByteOperation::Remove { position: 0 })
+                .only_if(PlanGuard::FileSizeGreaterThan(3))
+                .only_if(PlanGuard::PatternPresent(vec![0xAA, 0x02])),
+        ];
+
+        let outcomes = apply_patch_plan(&test_file, &plan).expect("plan should succeed");
+        assert_eq!(
+            outcomes,
+            vec![PlanEntryOutcome::Applied, PlanEntryOutcome::Applied]
+        );
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0xAA, 0x02, 0x03]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_plan_skips_entries_whose_guards_fail() {
+        let test_file = std::env::temp_dir().join("test_plan_guards_fail.bin");
+        std::fs::write(&test_file, vec![0x00, 0x01, 0x02]).expect("create test file");
+
+        let plan = vec![
+            // Guard sees the state left by the previous entry
+            PlanEntry::unconditional(ByteOperation::Insert {
+                position: 3,
+                new_byte_value: 0x03,
+            }),
+            PlanEntry::unconditional(ByteOperation::Remove { position: 0 })
+                .only_if(PlanGuard::FileSizeGreaterThan(3))
+                .only_if(PlanGuard::ByteEquals {
+                    position: 0,
+                    expected_byte_value: 0x7F,
+                }),
+            PlanEntry::unconditional(ByteOperation::Remove { position: 0 }).only_if(
+                PlanGuard::ByteEquals {
+                    position: 99,
+                    expected_byte_value: 0x00,
+                },
+            ),
+        ];
+
+        let outcomes = apply_patch_plan(&test_file, &plan).expect("plan should succeed");
+        assert_eq!(
+            outcomes,
+            vec![
+                PlanEntryOutcome::Applied,
+                PlanEntryOutcome::Skipped {
+                    failed_guard_index: 1
+                },
+                PlanEntryOutcome::Skipped {
+                    failed_guard_index: 0
+                },
+            ]
+        );
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x00, 0x01, 0x02, 0x03]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_parse_plan_text() {
+        let plan_text = "\
+# header comment
+replace 0x10 02 when byte 0x10 == 01
+insert  4096 00 when size > 4096 when contains EFBBBF   # trailing comment
+
+remove  0
+";
+        let plan = parse_plan_text(plan_text).expect("plan should parse");
+        assert_eq!(
+            plan,
+            vec![
+                PlanEntry::unconditional(ByteOperation::Replace {
+                    position: 0x10,
+                    new_byte_value: 0x02,
+                })
+                .only_if(PlanGuard::ByteEquals {
+                    position: 0x10,
+                    expected_byte_value: 0x01,
+                }),
+                PlanEntry::unconditional(ByteOperation::Insert {
+                    position: 4096,
+                    new_byte_value: 0x00,
+                })
+                .only_if(PlanGuard::FileSizeGreaterThan(4096))
+                .only_if(PlanGuard::PatternPresent(vec![0xEF, 0xBB, 0xBF])),
+                PlanEntry::unconditional(ByteOperation::Remove { position: 0 }),
+            ]
+        );
+
+        let error = parse_plan_text("remove 0\nreplace 1\n").expect_err("should fail");
+        assert!(error.to_string().contains("line 2"));
+        assert!(parse_plan_text("remove 0 when size 3").is_err());
+    }
+
+    #[test]
+    fn test_plan_touch_records_without_modifying() {
+        let test_file = std::env::temp_dir().join("test_plan_touch.bin");
+        std::fs::write(&test_file, b"abc").expect("create test file");
+
+        let plan = parse_plan_text(
+            "touch
+touch when size > 10
+",
+        )
+        .expect("plan should parse");
+        let outcomes = apply_patch_plan(&test_file, &plan).expect("plan should succeed");
+        assert_eq!(
+            outcomes,
+            vec![
+                PlanEntryOutcome::Recorded {
+                    sha256_hex: digest_to_hex(&crate::hash::sha256::sha256_of_bytes(b"abc")),
+                },
+                PlanEntryOutcome::Skipped {
+                    failed_guard_index: 0
+                },
+            ]
+        );
+        assert_eq!(std::fs::read(&test_file).expect("read file"), b"abc");
+
+        // Preflight still rejects a missing target
+        let missing_file = std::env::temp_dir().join("test_plan_touch_missing.bin");
+        assert!(apply_patch_plan(&missing_file, &plan[..1]).is_err());
+
+        // ... and the config's preconditions, as an edit would
+        let expecting_other_size = OperationConfig::builder()
+            .expected_original_size(4)
+            .build()
+            .expect("valid config");
+        let error = apply_patch_plan_with_config(&test_file, &plan[..1], &expecting_other_size)
+            .expect_err("size precondition fails");
+        assert!(matches!(
+            crate::error::ByteOpError::from_io_error(&error),
+            Some(crate::error::ByteOpError::UnexpectedFileSize { .. })
+        ));
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,2141 @@
+//! Multi-byte range operations
+//!
+//! These generalize the single-byte operations to contiguous ranges, so that
+//! editing N bytes costs one draft-file pass instead of N full rewrites.
+//! Each operation uses the shared backup/draft/verify workflow in
+//! `draft_workflow.rs` and verifies the resulting frame-shift.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::direct_write::{MAX_DIRECT_WRITE_LENGTH, write_bytes_directly};
+use crate::draft_workflow::{
+    check_chunk_limit, check_original_preconditions, chunk_limit_for_chunk_size, copy_exact_bytes,
+    copy_remaining_bytes, open_at_offset, run_draft_workflow, skip_exact_bytes,
+    validate_target_file, verify_both_at_eof, verify_next_bytes_equal_slice,
+    verify_next_bytes_follow_pattern, verify_next_bytes_match, write_all_checked,
+    write_repeating_pattern,
+};
+use crate::memory_budget::{MemoryConsumer, admit_draft_operation};
+use crate::offset::Offset;
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::reporter::report_problem;
+use crate::util::chunk_length;
+
+// ============================
+// Remove-Byte-Range Operation
+// ============================
+
+/// Removes a contiguous range of bytes from a file (frame-shift -N).
+///
+/// # Overview
+/// Generalizes `remove_single_byte_from_file` to `byte_count` bytes: the
+/// removed bytes are skipped while reading the original, so they are never
+/// appended to the draft. Everything after the range shifts back by
+/// `byte_count` positions.
+///
+/// # Mechanical Steps
+/// 1. Append pre-range bytes (0 to start-1) from original to draft
+/// 2. Skip `byte_count` bytes in the original (nothing written to draft)
+/// 3. Append the remaining bytes (start+byte_count to EOF) to draft
+///
+/// # Verification
+/// - Total length: draft == original - byte_count
+/// - Pre-range similarity: draft[0..start] == original[0..start]
+/// - Post-range similarity with -N frame-shift:
+///   draft[start..] == original[start+byte_count..]
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: First byte to remove, from the start (`u64`) or as
+///   `Offset::FromEnd(n)`
+/// - `byte_count`: Number of bytes to remove (must be > 0)
+///
+/// # Returns
+/// - `Ok(())` on successful removal
+/// - `Err(io::Error)` if the range is empty or out of bounds, or any phase fails
+///
+/// # Edge Cases
+/// - Empty file: Returns error (no bytes to remove)
+/// - Range ends exactly at EOF: Valid, no post-range bytes
+/// - Range covers the whole file: Valid, results in an empty file
+/// - Range extends past EOF: Returns error, file untouched
+///
+/// # Example
+/// ```no_run
+/// # use std::io;
+/// # use std::path::PathBuf;
+/// # fn remove_byte_range_from_file(path: PathBuf, start: u64, len: u64) -> io::Result<()> { Ok(()) }
+/// // Original file: [0x41, 0x42, 0x43, 0x44, 0x45]
+/// let file_path = PathBuf::from("/absolute/path/to/file.dat");
+/// let result = remove_byte_range_from_file(file_path, 1, 3);
+/// // Resulting file: [0x41, 0x45]
+/// assert!(result.is_ok());
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn remove_byte_range_from_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    byte_count: u64,
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let byte_position_from_start = byte_offset.into().resolve(original_file_size)?;
+
+    if byte_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Byte count to remove must be greater than 0",
+        ));
+    }
+
+    if original_file_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot remove bytes from empty file (file size is 0)",
+        ));
+    }
+
+    let range_end_exclusive = checked_range_end(byte_position_from_start, byte_count)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+
+    let expected_draft_size = original_file_size - byte_count;
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Range Removal",
+        expected_draft_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+            // The removal: advance the original read position, write nothing
+            skip_exact_bytes(source_file, byte_count)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            verify_byte_range_removal(
+                original_path,
+                draft_path,
+                byte_position_from_start,
+                byte_count,
+                post_range_byte_count,
+            )
+        },
+    )
+}
+
+/// Like `remove_byte_range_from_file`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn remove_byte_range_from_file_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    byte_count: u64,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        remove_byte_range_from_file(original_file_path, byte_offset, byte_count)
+    })
+}
+
+/// Verifies a range removal: pre-range identical, post-range shifted by -N.
+fn verify_byte_range_removal(
+    original_path: &Path,
+    draft_path: &Path,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    post_range_byte_count: u64,
+) -> io::Result<()> {
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        byte_position_from_start,
+        0,
+        0,
+        "Pre-range",
+    )?;
+
+    // Frame-shift: the removed range exists only in the original
+    skip_exact_bytes(&mut original_file, byte_count)?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        post_range_byte_count,
+        byte_position_from_start + byte_count,
+        byte_position_from_start,
+        "Post-range (-N frame-shift)",
+    )?;
+
+    verify_both_at_eof(&mut original_file, &mut draft_file)
+}
+
+// ============================
+// Replace-Byte-Range Operation
+// ============================
+
+/// Overwrites a contiguous range of bytes with new values (no frame-shift).
+///
+/// # Overview
+/// Generalizes `replace_single_byte_in_file` to `new_bytes.len()` bytes.
+/// File length is unchanged; the original bytes of the range are skipped
+/// while reading and `new_bytes` is written in their place.
+///
+/// # Mechanical Steps
+/// 1. Append pre-range bytes (0 to start-1) from original to draft
+/// 2. Skip N bytes in the original, write `new_bytes` to draft
+/// 3. Append the remaining bytes (start+N to EOF) to draft
+///
+/// # Verification
+/// - Total length: draft == original
+/// - Pre-range similarity: draft[0..start] == original[0..start]
+/// - At-range check: draft[start..start+N] == new_bytes
+/// - Post-range similarity (no shift): draft[start+N..] == original[start+N..]
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: First byte to overwrite, from the start (`u64`) or as
+///   `Offset::FromEnd(n)`
+/// - `new_bytes`: Replacement values (must be non-empty)
+///
+/// # Returns
+/// - `Ok(())` on successful replacement
+/// - `Err(io::Error)` if the payload is empty, the range extends past EOF,
+///   or any phase fails
+///
+/// # Edge Cases
+/// - Range ends exactly at EOF: Valid, no post-range bytes
+/// - Range extends past EOF: Returns error (use insert/append to grow files)
+/// - `new_bytes` equal to the current bytes: Valid, file content unchanged
+pub fn replace_byte_range_in_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_bytes: &[u8],
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let byte_position_from_start = byte_offset.into().resolve(original_file_size)?;
+
+    if new_bytes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Replacement bytes must not be empty",
+        ));
+    }
+
+    let range_end_exclusive = checked_range_end(byte_position_from_start, new_bytes.len() as u64)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Range Replacement",
+        original_file_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+            // The replacement: skip the old bytes, write the new ones
+            skip_exact_bytes(source_file, new_bytes.len() as u64)?;
+            write_all_checked(draft_file, new_bytes)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            verify_byte_range_replacement(
+                original_path,
+                draft_path,
+                byte_position_from_start,
+                new_bytes,
+                post_range_byte_count,
+            )
+        },
+    )
+}
+
+/// Like `replace_byte_range_in_file`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn replace_byte_range_in_file_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_bytes: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        replace_byte_range_in_file(original_file_path, byte_offset, new_bytes)
+    })
+}
+
+/// Verifies a range replacement: pre identical, range == new bytes, post identical.
+fn verify_byte_range_replacement(
+    original_path: &Path,
+    draft_path: &Path,
+    byte_position_from_start: u64,
+    new_bytes: &[u8],
+    post_range_byte_count: u64,
+) -> io::Result<()> {
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        byte_position_from_start,
+        0,
+        0,
+        "Pre-range",
+    )?;
+
+    skip_exact_bytes(&mut original_file, new_bytes.len() as u64)?;
+    verify_next_bytes_equal_slice(
+        &mut draft_file,
+        new_bytes,
+        byte_position_from_start,
+        "Replaced range",
+    )?;
+
+    let range_end_exclusive = byte_position_from_start + new_bytes.len() as u64;
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        post_range_byte_count,
+        range_end_exclusive,
+        range_end_exclusive,
+        "Post-range",
+    )?;
+
+    verify_both_at_eof(&mut original_file, &mut draft_file)
+}
+
+// ============================
+// Multi-Range Replace Operation
+// ============================
+
+/// Overwrites several non-overlapping ranges in one draft pass (no frame-shift).
+///
+/// # Overview
+/// Used where several related fields must change together or not at all,
+/// e.g. member data plus its checksum fields inside an archive. All
+/// replacements land in the same draft and are verified together before the
+/// single atomic rename.
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `replacements`: `(position, new_bytes)` pairs; any order, must not
+///   overlap, each non-empty and within the file
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if the list is empty, a range is empty, out of
+///   bounds, or overlaps another, or any phase fails
+pub fn replace_multiple_byte_ranges_in_file(
+    original_file_path: PathBuf,
+    replacements: &[(u64, Vec<u8>)],
+) -> io::Result<()> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let sorted_replacements = validate_range_replacements(replacements, original_file_size)?;
+    admit_draft_operation(vec![replacement_list_consumer(replacements)])?;
+
+    run_draft_workflow(
+        &original_file_path,
+        "Multi-Range Replacement",
+        original_file_size,
+        |source_file, draft_file| {
+            build_multi_replacement_draft(source_file, draft_file, &sorted_replacements)
+        },
+        |original_path, draft_path| {
+            verify_multi_replacement(
+                original_path,
+                draft_path,
+                &sorted_replacements,
+                original_file_size,
+            )
+        },
+    )
+}
+
+/// Like `replace_multiple_byte_ranges_in_file`, using `config` instead of the
+/// active (or default) `OperationConfig`.
+pub fn replace_multiple_byte_ranges_in_file_with_config(
+    original_file_path: PathBuf,
+    replacements: &[(u64, Vec<u8>)],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        replace_multiple_byte_ranges_in_file(original_file_path, replacements)
+    })
+}
+
+/// What a replacement list holds in memory, for the memory budget.
+pub(crate) fn replacement_list_consumer(replacements: &[(u64, Vec<u8>)]) -> MemoryConsumer {
+    MemoryConsumer::ReplacementList {
+        entry_count: replacements.len(),
+        total_new_bytes: replacements
+            .iter()
+            .map(|(_, new_bytes)| new_bytes.len())
+            .sum(),
+    }
+}
+
+/// Checks replacement ranges and returns them sorted by position.
+pub(crate) fn validate_range_replacements(
+    replacements: &[(u64, Vec<u8>)],
+    original_file_size: u64,
+) -> io::Result<Vec<(u64, Vec<u8>)>> {
+    if replacements.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "At least one replacement range is required",
+        ));
+    }
+
+    let mut sorted_replacements = replacements.to_vec();
+    sorted_replacements.sort_by_key(|(position, _)| *position);
+
+    let mut previous_end_exclusive: u64 = 0;
+    for (position, new_bytes) in &sorted_replacements {
+        if new_bytes.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Replacement at {} is empty", position),
+            ));
+        }
+        if *position < previous_end_exclusive {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Replacement at {} overlaps the previous range", position),
+            ));
+        }
+        let range_end_exclusive = checked_range_end(*position, new_bytes.len() as u64)?;
+        if range_end_exclusive > original_file_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Byte range {}..{} exceeds file size {}",
+                    position, range_end_exclusive, original_file_size
+                ),
+            ));
+        }
+        previous_end_exclusive = range_end_exclusive;
+    }
+
+    Ok(sorted_replacements)
+}
+
+/// Draft builder for sorted, validated replacements.
+pub(crate) fn build_multi_replacement_draft<R: Read + Seek + 'static, W: Write + 'static>(
+    source_file: &mut R,
+    draft_file: &mut W,
+    sorted_replacements: &[(u64, Vec<u8>)],
+) -> io::Result<()> {
+    let mut source_position: u64 = 0;
+    for (position, new_bytes) in sorted_replacements {
+        copy_exact_bytes(source_file, draft_file, position - source_position)?;
+        skip_exact_bytes(source_file, new_bytes.len() as u64)?;
+        write_all_checked(draft_file, new_bytes)?;
+        source_position = position + new_bytes.len() as u64;
+    }
+    copy_remaining_bytes(source_file, draft_file)?;
+    Ok(())
+}
+
+/// Verifier for sorted, validated replacements: every gap identical,
+/// every range equal to its new bytes.
+pub(crate) fn verify_multi_replacement(
+    original_path: &Path,
+    draft_path: &Path,
+    sorted_replacements: &[(u64, Vec<u8>)],
+    original_file_size: u64,
+) -> io::Result<()> {
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    let mut current_position: u64 = 0;
+    for (position, new_bytes) in sorted_replacements {
+        verify_next_bytes_match(
+            &mut original_file,
+            &mut draft_file,
+            position - current_position,
+            current_position,
+            current_position,
+            "Unchanged gap",
+        )?;
+        skip_exact_bytes(&mut original_file, new_bytes.len() as u64)?;
+        verify_next_bytes_equal_slice(&mut draft_file, new_bytes, *position, "Replaced range")?;
+        current_position = position + new_bytes.len() as u64;
+    }
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        original_file_size - current_position,
+        current_position,
+        current_position,
+        "Unchanged tail",
+    )?;
+
+    verify_both_at_eof(&mut original_file, &mut draft_file)
+}
+
+// ============================
+// Insert-Bytes Operation
+// ============================
+
+/// Inserts a buffer of bytes at a position in a file (frame-shift +N).
+///
+/// # Overview
+/// Generalizes `insert_single_byte_into_file` to a multi-byte payload
+/// (headers, magic numbers, padding) in a single draft-file pass. The byte
+/// previously at `byte_position_from_start` ends up at
+/// `byte_position_from_start + new_bytes.len()`.
+///
+/// # Mechanical Steps
+/// 1. Append pre-position bytes (0 to position-1) from original to draft
+/// 2. Write all `new_bytes` to draft (original read position unchanged)
+/// 3. Append the remaining bytes (position to EOF) to draft
+///
+/// # Verification
+/// - Total length: draft == original + N
+/// - Pre-position similarity: draft[0..position] == original[0..position]
+/// - At-position check: draft[position..position+N] == new_bytes
+/// - Post-position similarity with +N frame-shift:
+///   draft[position+N..] == original[position..]
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: Insertion position (0..=file size), from the start
+///   (`u64`) or as `Offset::FromEnd(n)` (`FromEnd(0)` appends)
+/// - `new_bytes`: The payload to insert (must be non-empty)
+///
+/// # Returns
+/// - `Ok(())` on successful insertion
+/// - `Err(io::Error)` if the payload is empty, the position is past EOF,
+///   or any phase fails
+///
+/// # Edge Cases
+/// - Position == file size: Valid, appends the payload
+/// - Empty file with position 0: Valid, file becomes `new_bytes`
+/// - Position > file size: Returns error, file untouched
+///
+/// # Example
+/// ```no_run
+/// # use std::io;
+/// # use std::path::PathBuf;
+/// # fn insert_bytes_into_file(path: PathBuf, pos: u64, bytes: &[u8]) -> io::Result<()> { Ok(()) }
+/// // Original file: [0x41, 0x42]
+/// let file_path = PathBuf::from("/absolute/path/to/file.dat");
+/// let result = insert_bytes_into_file(file_path, 1, &[0xDE, 0xAD]);
+/// // Resulting file: [0x41, 0xDE, 0xAD, 0x42]
+/// assert!(result.is_ok());
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn insert_bytes_into_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_bytes: &[u8],
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let byte_position_from_start = byte_offset.into().resolve(original_file_size)?;
+
+    if new_bytes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Bytes to insert must not be empty",
+        ));
+    }
+
+    // Insertion at EOF (position == size) is an append
+    if byte_position_from_start > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Insert position {} exceeds file size {}",
+                byte_position_from_start, original_file_size
+            ),
+        ));
+    }
+
+    let expected_draft_size = checked_range_end(original_file_size, new_bytes.len() as u64)?;
+    let post_position_byte_count = original_file_size - byte_position_from_start;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Bytes Insertion",
+        expected_draft_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+            // The insertion: write the payload, original read position unchanged
+            write_all_checked(draft_file, new_bytes)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            verify_bytes_insertion(
+                original_path,
+                draft_path,
+                byte_position_from_start,
+                new_bytes,
+                post_position_byte_count,
+            )
+        },
+    )
+}
+
+/// Like `insert_bytes_into_file`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn insert_bytes_into_file_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_bytes: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        insert_bytes_into_file(original_file_path, byte_offset, new_bytes)
+    })
+}
+
+/// Verifies a buffer insertion: pre identical, payload present, post shifted by +N.
+fn verify_bytes_insertion(
+    original_path: &Path,
+    draft_path: &Path,
+    byte_position_from_start: u64,
+    new_bytes: &[u8],
+    post_position_byte_count: u64,
+) -> io::Result<()> {
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        byte_position_from_start,
+        0,
+        0,
+        "Pre-position",
+    )?;
+
+    // Frame-shift: the payload exists only in the draft
+    verify_next_bytes_equal_slice(
+        &mut draft_file,
+        new_bytes,
+        byte_position_from_start,
+        "Inserted bytes",
+    )?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        post_position_byte_count,
+        byte_position_from_start,
+        byte_position_from_start + new_bytes.len() as u64,
+        "Post-position (+N frame-shift)",
+    )?;
+
+    verify_both_at_eof(&mut original_file, &mut draft_file)
+}
+
+// ============================
+// Splice Operation
+// ============================
+
+/// Replaces a range with content of a (possibly) different length.
+///
+/// # Overview
+/// Generalizes replace, insert, and remove into one draft-construction pass:
+/// `remove_len` bytes starting at `start` are dropped and `replacement` is
+/// written in their place. Everything after the range shifts by
+/// `replacement.len() - remove_len` positions (may be negative, zero, or
+/// positive).
+///
+/// - `remove_len == replacement.len()`: same as `replace_byte_range_in_file`
+/// - `remove_len == 0`: same as `insert_bytes_into_file`
+/// - `replacement` empty: same as `remove_byte_range_from_file`
+///
+/// # Mechanical Steps
+/// 1. Append pre-range bytes (0 to start-1) from original to draft
+/// 2. Skip `remove_len` bytes in the original, write `replacement` to draft
+/// 3. Append the remaining bytes (start+remove_len to EOF) to draft
+///
+/// # Verification
+/// - Total length: draft == original - remove_len + replacement.len()
+/// - Pre-range similarity: draft[0..start] == original[0..start]
+/// - At-range check: draft[start..start+R] == replacement
+/// - Post-range similarity with frame-shift (R - remove_len):
+///   draft[start+R..] == original[start+remove_len..]
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_position_from_start`: Zero-indexed start of the range (0..=file size)
+/// - `remove_len`: Number of original bytes to drop
+/// - `replacement`: Bytes to write in their place
+///
+/// # Returns
+/// - `Ok(())` on successful splice
+/// - `Err(io::Error)` if both `remove_len` and `replacement` are empty, the
+///   range extends past EOF, or any phase fails
+pub fn splice_byte_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    remove_len: u64,
+    replacement: &[u8],
+) -> io::Result<()> {
+    splice_byte_range_with_draft_check(
+        original_file_path,
+        byte_position_from_start,
+        remove_len,
+        replacement,
+        "Byte Range Splice",
+        |_draft_path| Ok(()),
+    )
+}
+
+/// Like `splice_byte_range`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn splice_byte_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    remove_len: u64,
+    replacement: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        splice_byte_range(
+            original_file_path,
+            byte_position_from_start,
+            remove_len,
+            replacement,
+        )
+    })
+}
+
+/// `splice_byte_range` plus a caller-supplied check of the finished draft.
+///
+/// The extra check runs after the standard splice verification and before
+/// the rename, so a failing check leaves the original untouched. Used by
+/// content-aware operations (e.g. UTF-8 character replacement) that must
+/// also prove the draft is still well-formed.
+pub(crate) fn splice_byte_range_with_draft_check<DraftCheck>(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    remove_len: u64,
+    replacement: &[u8],
+    operation_name: &str,
+    draft_check: DraftCheck,
+) -> io::Result<()>
+where
+    DraftCheck: FnOnce(&Path) -> io::Result<()>,
+{
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    if remove_len == 0 && replacement.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Splice must remove or insert at least one byte",
+        ));
+    }
+
+    let range_end_exclusive = checked_range_end(byte_position_from_start, remove_len)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+
+    let expected_draft_size =
+        checked_range_end(original_file_size - remove_len, replacement.len() as u64)?;
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        operation_name,
+        expected_draft_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+            // The splice: drop the old range, write the replacement
+            skip_exact_bytes(source_file, remove_len)?;
+            write_all_checked(draft_file, replacement)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            verify_byte_range_splice(
+                original_path,
+                draft_path,
+                byte_position_from_start,
+                remove_len,
+                replacement,
+                post_range_byte_count,
+            )?;
+            draft_check(draft_path)
+        },
+    )
+}
+
+/// Verifies a splice: pre identical, replacement present, post frame-shifted.
+fn verify_byte_range_splice(
+    original_path: &Path,
+    draft_path: &Path,
+    byte_position_from_start: u64,
+    remove_len: u64,
+    replacement: &[u8],
+    post_range_byte_count: u64,
+) -> io::Result<()> {
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        byte_position_from_start,
+        0,
+        0,
+        "Pre-range",
+    )?;
+
+    // Frame-shift: removed bytes exist only in the original,
+    // replacement bytes only in the draft
+    skip_exact_bytes(&mut original_file, remove_len)?;
+    verify_next_bytes_equal_slice(
+        &mut draft_file,
+        replacement,
+        byte_position_from_start,
+        "Spliced range",
+    )?;
+
+    verify_next_bytes_match(
+        &mut original_file,
+        &mut draft_file,
+        post_range_byte_count,
+        byte_position_from_start + remove_len,
+        byte_position_from_start + replacement.len() as u64,
+        "Post-range (frame-shift)",
+    )?;
+
+    verify_both_at_eof(&mut original_file, &mut draft_file)
+}
+
+// ============================
+// Copy-Range-Within-File Operation
+// ============================
+
+/// Inserts a duplicate of an existing region at another offset (frame-shift +N).
+///
+/// # Overview
+/// Replicates headers or records: the `byte_count` bytes at
+/// `source_start` are copied and inserted at `destination_offset`. Both
+/// offsets refer to the original file layout. The region is streamed from a
+/// second read handle on the original, so it is never loaded as a whole.
+///
+/// # Mechanical Steps
+/// 1. Append original bytes 0 to destination-1 to draft
+/// 2. Append `byte_count` bytes read from a second handle at `source_start`
+/// 3. Append the remaining original bytes (destination to EOF) to draft
+///
+/// # Verification
+/// - Total length: draft == original + N
+/// - Pre-destination similarity: draft[0..dest] == original[0..dest]
+/// - At-destination check: draft[dest..dest+N] == original[src..src+N]
+/// - Post-destination similarity with +N frame-shift:
+///   draft[dest+N..] == original[dest..]
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `source_start`: Zero-indexed start of the region to duplicate
+/// - `byte_count`: Length of the region (must be > 0)
+/// - `destination_offset`: Insertion position in the original layout (0..=file size)
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if the region is empty or out of bounds, the
+///   destination is past EOF, or any phase fails
+///
+/// # Edge Cases
+/// - Destination inside the source region: Valid; the copy is of the
+///   original bytes, inserted at that point
+/// - Destination == file size: Valid, appends the duplicate
+pub fn copy_byte_range_within_file(
+    original_file_path: PathBuf,
+    source_start: u64,
+    byte_count: u64,
+    destination_offset: u64,
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    if byte_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Byte count to copy must be greater than 0",
+        ));
+    }
+
+    let source_end_exclusive = checked_range_end(source_start, byte_count)?;
+    if source_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Source range {}..{} exceeds file size {}",
+                source_start, source_end_exclusive, original_file_size
+            ),
+        ));
+    }
+
+    if destination_offset > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Destination offset {} exceeds file size {}",
+                destination_offset, original_file_size
+            ),
+        ));
+    }
+
+    let expected_draft_size = checked_range_end(original_file_size, byte_count)?;
+    let post_destination_byte_count = original_file_size - destination_offset;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Range Copy",
+        expected_draft_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, destination_offset)?;
+            // The duplicate: streamed from a second handle on the original
+            let mut region_source = open_at_offset(&original_file_path, source_start)?;
+            copy_exact_bytes(&mut region_source, draft_file, byte_count)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                destination_offset,
+                0,
+                0,
+                "Pre-destination",
+            )?;
+
+            let mut region_source = open_at_offset(original_path, source_start)?;
+            verify_next_bytes_match(
+                &mut region_source,
+                &mut draft_file,
+                byte_count,
+                source_start,
+                destination_offset,
+                "Copied region",
+            )?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                post_destination_byte_count,
+                destination_offset,
+                destination_offset + byte_count,
+                "Post-destination (+N frame-shift)",
+            )?;
+
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )
+}
+
+/// Like `copy_byte_range_within_file`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn copy_byte_range_within_file_with_config(
+    original_file_path: PathBuf,
+    source_start: u64,
+    byte_count: u64,
+    destination_offset: u64,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        copy_byte_range_within_file(
+            original_file_path,
+            source_start,
+            byte_count,
+            destination_offset,
+        )
+    })
+}
+
+// ============================
+// Swap-Ranges Operation
+// ============================
+
+/// Exchanges two non-overlapping byte ranges in one pass.
+///
+/// # Overview
+/// Reorders sections of container files. The ranges may differ in length;
+/// the bytes between them then shift by the length difference, and total
+/// file length is unchanged. Either range may come first in the file.
+///
+/// # Mechanical Steps (with `low` = earlier range, `high` = later range)
+/// 1. Append bytes before `low` from original to draft
+/// 2. Append the `high` range (second handle on the original)
+/// 3. Skip `low` in the original, append the gap between the ranges
+/// 4. Append the `low` range (second handle), skip `high` in the original
+/// 5. Append the remaining bytes to draft
+///
+/// # Verification
+/// - Total length: draft == original
+/// - Prefix identical; draft's first slot == original `high`
+/// - Gap identical but shifted by `high_len - low_len`
+/// - Draft's second slot == original `low`; tail identical
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `first_start`, `first_len`: One range (length > 0)
+/// - `second_start`, `second_len`: The other range (length > 0)
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if a range is empty, out of bounds, or the ranges
+///   overlap (adjacent ranges are allowed)
+pub fn swap_byte_ranges(
+    original_file_path: PathBuf,
+    first_start: u64,
+    first_len: u64,
+    second_start: u64,
+    second_len: u64,
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    if first_len == 0 || second_len == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Both ranges to swap must be non-empty",
+        ));
+    }
+
+    let first_end = checked_range_end(first_start, first_len)?;
+    let second_end = checked_range_end(second_start, second_len)?;
+    if first_end > original_file_size || second_end > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Ranges {}..{} and {}..{} must lie within file size {}",
+                first_start, first_end, second_start, second_end, original_file_size
+            ),
+        ));
+    }
+
+    if first_start < second_end && second_start < first_end {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Ranges {}..{} and {}..{} overlap",
+                first_start, first_end, second_start, second_end
+            ),
+        ));
+    }
+
+    let (low_start, low_len, high_start, high_len) = if first_start < second_start {
+        (first_start, first_len, second_start, second_len)
+    } else {
+        (second_start, second_len, first_start, first_len)
+    };
+    let gap_len = high_start - (low_start + low_len);
+    let tail_len = original_file_size - (high_start + high_len);
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Range Swap",
+        original_file_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, low_start)?;
+
+            let mut high_source = open_at_offset(&original_file_path, high_start)?;
+            copy_exact_bytes(&mut high_source, draft_file, high_len)?;
+
+            skip_exact_bytes(source_file, low_len)?;
+            copy_exact_bytes(source_file, draft_file, gap_len)?;
+
+            let mut low_source = open_at_offset(&original_file_path, low_start)?;
+            copy_exact_bytes(&mut low_source, draft_file, low_len)?;
+
+            skip_exact_bytes(source_file, high_len)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let mut draft_file = File::open(draft_path)?;
+
+            let mut prefix_source = File::open(original_path)?;
+            verify_next_bytes_match(
+                &mut prefix_source,
+                &mut draft_file,
+                low_start,
+                0,
+                0,
+                "Prefix",
+            )?;
+
+            let mut high_source = open_at_offset(original_path, high_start)?;
+            verify_next_bytes_match(
+                &mut high_source,
+                &mut draft_file,
+                high_len,
+                high_start,
+                low_start,
+                "First slot (original later range)",
+            )?;
+
+            let gap_start = low_start + low_len;
+            let mut gap_source = open_at_offset(original_path, gap_start)?;
+            verify_next_bytes_match(
+                &mut gap_source,
+                &mut draft_file,
+                gap_len,
+                gap_start,
+                low_start + high_len,
+                "Gap (shifted by length difference)",
+            )?;
+
+            let mut low_source = open_at_offset(original_path, low_start)?;
+            verify_next_bytes_match(
+                &mut low_source,
+                &mut draft_file,
+                low_len,
+                low_start,
+                low_start + high_len + gap_len,
+                "Second slot (original earlier range)",
+            )?;
+
+            let tail_start = high_start + high_len;
+            let mut tail_source = open_at_offset(original_path, tail_start)?;
+            verify_next_bytes_match(
+                &mut tail_source,
+                &mut draft_file,
+                tail_len,
+                tail_start,
+                tail_start,
+                "Tail",
+            )?;
+
+            verify_both_at_eof(&mut tail_source, &mut draft_file)
+        },
+    )
+}
+
+/// Like `swap_byte_ranges`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn swap_byte_ranges_with_config(
+    original_file_path: PathBuf,
+    first_start: u64,
+    first_len: u64,
+    second_start: u64,
+    second_len: u64,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        swap_byte_ranges(
+            original_file_path,
+            first_start,
+            first_len,
+            second_start,
+            second_len,
+        )
+    })
+}
+
+// ============================
+// Rotate-Range Operation
+// ============================
+
+/// Direction of a byte rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationDirection {
+    /// Bytes move toward the start; the first k bytes wrap to the end
+    Left,
+    /// Bytes move toward the end; the last k bytes wrap to the start
+    Right,
+}
+
+/// Rotates the bytes of a range by `k` positions (no frame-shift outside it).
+///
+/// # Overview
+/// A left rotation by k turns `original[start..start+len]` into
+/// `original[start+k..start+len] ++ original[start..start+k]`; a right
+/// rotation by k equals a left rotation by `len - k`. `k` is taken modulo
+/// `len`. The rotation happens during draft construction: the two parts are
+/// streamed from two read handles, so large ranges are never loaded.
+///
+/// # Verification
+/// - Total length: draft == original
+/// - Pre-range and post-range bytes identical (untouched)
+/// - Range content equals the two rotated parts of the original range
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_position_from_start`: Start of the range
+/// - `byte_count`: Length of the range (must be > 0)
+/// - `rotate_by`: Number of positions (any value; reduced modulo `byte_count`)
+/// - `direction`: Left or right
+///
+/// # Returns
+/// - `Ok(())` on success (a rotation by a multiple of `byte_count` leaves
+///   the file content unchanged)
+/// - `Err(io::Error)` if the range is empty or out of bounds, or any phase fails
+pub fn rotate_byte_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    rotate_by: u64,
+    direction: RotationDirection,
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    if byte_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Byte count to rotate must be greater than 0",
+        ));
+    }
+
+    let range_end_exclusive = checked_range_end(byte_position_from_start, byte_count)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+
+    // Everything is expressed as a left rotation
+    let left_rotation = match direction {
+        RotationDirection::Left => rotate_by % byte_count,
+        RotationDirection::Right => (byte_count - rotate_by % byte_count) % byte_count,
+    };
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Range Rotation",
+        original_file_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+            // Tail part of the range first...
+            skip_exact_bytes(source_file, left_rotation)?;
+            copy_exact_bytes(source_file, draft_file, byte_count - left_rotation)?;
+            // ...then the head part, from a second handle
+            let mut head_source = open_at_offset(&original_file_path, byte_position_from_start)?;
+            copy_exact_bytes(&mut head_source, draft_file, left_rotation)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                byte_position_from_start,
+                0,
+                0,
+                "Pre-range",
+            )?;
+
+            skip_exact_bytes(&mut original_file, left_rotation)?;
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                byte_count - left_rotation,
+                byte_position_from_start + left_rotation,
+                byte_position_from_start,
+                "Rotated range (tail part)",
+            )?;
+
+            let mut head_source = open_at_offset(original_path, byte_position_from_start)?;
+            verify_next_bytes_match(
+                &mut head_source,
+                &mut draft_file,
+                left_rotation,
+                byte_position_from_start,
+                range_end_exclusive - left_rotation,
+                "Rotated range (head part)",
+            )?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                post_range_byte_count,
+                range_end_exclusive,
+                range_end_exclusive,
+                "Post-range",
+            )?;
+
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )
+}
+
+/// Like `rotate_byte_range`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn rotate_byte_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    rotate_by: u64,
+    direction: RotationDirection,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        rotate_byte_range(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            rotate_by,
+            direction,
+        )
+    })
+}
+
+// ============================
+// Fill-Range Operation
+// ============================
+
+/// Overwrites a range with a repeating multi-byte pattern (no frame-shift).
+///
+/// # Overview
+/// Tiles `pattern` across `byte_count` bytes starting at
+/// `byte_position_from_start`, e.g. writing `DEADBEEF` sentinels into
+/// padding areas. The pattern starts at its first byte at the range start;
+/// the last repetition is cut short if the length is not a multiple.
+/// Tiles are generated during draft construction, so the range is never
+/// materialized in memory.
+///
+/// # Verification
+/// - Total length: draft == original
+/// - Pre-range and post-range bytes identical
+/// - Every range byte == pattern[(i - start) % pattern.len()]
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if the pattern or range is empty, the range extends
+///   past EOF, or any phase fails
+pub fn fill_range_with_pattern(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    pattern: &[u8],
+) -> io::Result<()> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    if pattern.is_empty() || byte_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Fill pattern and range must be non-empty",
+        ));
+    }
+
+    let range_end_exclusive = checked_range_end(byte_position_from_start, byte_count)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Pattern Fill",
+        original_file_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+            skip_exact_bytes(source_file, byte_count)?;
+            write_repeating_pattern(draft_file, pattern, byte_count)?;
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                byte_position_from_start,
+                0,
+                0,
+                "Pre-range",
+            )?;
+
+            skip_exact_bytes(&mut original_file, byte_count)?;
+            verify_next_bytes_follow_pattern(
+                &mut draft_file,
+                pattern,
+                byte_count,
+                byte_position_from_start,
+                "Filled range",
+            )?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                post_range_byte_count,
+                range_end_exclusive,
+                range_end_exclusive,
+                "Post-range",
+            )?;
+
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )
+}
+
+/// Like `fill_range_with_pattern`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn fill_range_with_pattern_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    pattern: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        fill_range_with_pattern(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            pattern,
+        )
+    })
+}
+
+// ============================
+// Secure-Wipe Operation
+// ============================
+
+/// Overwrites a range in place with `random_pass_count` pseudorandom
+/// passes, then zeros.
+///
+/// # Overview
+/// Intended for secrets embedded in binaries (keys, tokens, test
+/// credentials). Unlike the other range operations there is no backup and
+/// no draft: a copy of the file would be another copy of the secret. Each
+/// pass writes over the range's own blocks with `write_bytes_directly`, in
+/// chunks of up to `MAX_DIRECT_WRITE_LENGTH` bytes, and the file is synced
+/// before the next pass starts. The final pass writes zeros, leaving a
+/// recognizable, verified result.
+///
+/// # Verification (every chunk of every pass)
+/// - The chunk is read back and compared with the pass's generated bytes
+///   (or zeros); bytes outside the range are never written
+///
+/// # Limits
+/// - A config that keeps backups (`keep_backup`, rotated backups) is
+///   refused
+/// - The first pass's journal holds each chunk's old bytes until that chunk
+///   is written; it is then unlinked, not overwritten
+/// - Copy-on-write file systems, SSD wear leveling and snapshots can all
+///   retain old data. For storage-level erasure, wipe the device or use
+///   full-disk encryption
+///
+/// # Parameters
+/// - `random_pass_count`: Number of pseudorandom passes before the zero
+///   pass (must be > 0)
+///
+/// # Returns
+/// - `Ok(())` once the final zero pass is synced
+/// - `Err(io::Error)` if the range is empty or past EOF, the config keeps
+///   backups, or any chunk fails; passes already written remain applied
+pub fn secure_wipe_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    random_pass_count: usize,
+) -> io::Result<()> {
+    if random_pass_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Secure wipe needs at least one random pass",
+        ));
+    }
+    let config = active_operation_config();
+    if config.retains_backup() {
+        let error = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Secure wipe refuses configs that keep backups: each would be a copy of the secret",
+        );
+        report_problem!("ERROR: {}", error);
+        return Err(error);
+    }
+
+    let original_file_size = validate_target_file(&original_file_path)?;
+    if byte_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Range must be non-empty",
+        ));
+    }
+    let range_end_exclusive = checked_range_end(byte_position_from_start, byte_count)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+    // The expected size and hash describe the file before the first pass
+    check_original_preconditions(&original_file_path, &config)?;
+
+    let base_seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+        ^ byte_position_from_start;
+
+    with_operation_config(&config.without_original_preconditions(), || {
+        for pass_index in 0..random_pass_count {
+            let pass_seed = base_seed.wrapping_add(pass_index as u64);
+            overwrite_range_in_place(
+                &original_file_path,
+                byte_position_from_start,
+                byte_count,
+                |buffer, stream_offset| fill_pseudorandom_bytes(pass_seed, stream_offset, buffer),
+            )?;
+        }
+        overwrite_range_in_place(
+            &original_file_path,
+            byte_position_from_start,
+            byte_count,
+            |buffer, _stream_offset| buffer.fill(0x00),
+        )
+    })
+}
+
+/// Like `secure_wipe_range`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn secure_wipe_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    random_pass_count: usize,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        secure_wipe_range(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            random_pass_count,
+        )
+    })
+}
+
+/// One secure-wipe pass: the range is overwritten in place with a generated
+/// byte stream, chunk by chunk through `write_bytes_directly`, then the
+/// file is synced.
+///
+/// `generate(buffer, stream_offset)` must fill `buffer` with the stream's
+/// bytes starting at `stream_offset`.
+fn overwrite_range_in_place<Generate>(
+    original_file_path: &Path,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    generate: Generate,
+) -> io::Result<()>
+where
+    Generate: Fn(&mut [u8], u64),
+{
+    let mut pass_buffer = vec![0u8; chunk_length(MAX_DIRECT_WRITE_LENGTH, byte_count)];
+    let mut bytes_written: u64 = 0;
+    let chunk_limit = chunk_limit_for_chunk_size(byte_count, pass_buffer.len());
+    let mut chunk_number: usize = 0;
+    while bytes_written < byte_count {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_to_write = chunk_length(pass_buffer.len(), byte_count - bytes_written);
+        generate(&mut pass_buffer[..bytes_to_write], bytes_written);
+        write_bytes_directly(
+            original_file_path.to_path_buf(),
+            byte_position_from_start + bytes_written,
+            &pass_buffer[..bytes_to_write],
+        )?;
+        bytes_written += bytes_to_write as u64;
+    }
+
+    // Each chunk was synced as it was written; this makes the whole pass
+    // durable before the next one starts
+    OpenOptions::new()
+        .write(true)
+        .open(original_file_path)?
+        .sync_all()
+}
+
+/// Fills `buffer` with the pseudorandom stream for `seed` from `stream_offset`.
+///
+/// Counter-based (SplitMix64 of seed + word index), so any offset can be
+/// regenerated without replaying the stream. Not cryptographic; the wipe
+/// only needs bytes unrelated to the original content.
+fn fill_pseudorandom_bytes(seed: u64, stream_offset: u64, buffer: &mut [u8]) {
+    for (index, byte_slot) in buffer.iter_mut().enumerate() {
+        let absolute_offset = stream_offset + index as u64;
+        let word_index = absolute_offset / 8;
+        let mut word = seed.wrapping_add(word_index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        word = (word ^ (word >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        word = (word ^ (word >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        word ^= word >> 31;
+        *byte_slot = word.to_le_bytes()[(absolute_offset % 8) as usize];
+    }
+}
+
+// =========================================
+// Shared Helpers
+// =========================================
+
+/// Computes `start + count`, erroring on overflow.
+pub(crate) fn checked_range_end(byte_position_from_start: u64, byte_count: u64) -> io::Result<u64> {
+    byte_position_from_start
+        .checked_add(byte_count)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Byte range overflows"))
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_byte_range_middle() {
+        let test_file = std::env::temp_dir().join("test_remove_range_middle.bin");
+        std::fs::write(&test_file, vec![0x41, 0x42, 0x43, 0x44, 0x45]).expect("create test file");
+
+        let result = remove_byte_range_from_file(test_file.clone(), 1, 3);
+
+        assert!(result.is_ok(), "Operation should succeed");
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0x41, 0x45]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_byte_range_spanning_chunks() {
+        let test_file = std::env::temp_dir().join("test_remove_range_chunks.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(5000).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        let result = remove_byte_range_from_file(test_file.clone(), 60, 4096);
+
+        assert!(result.is_ok(), "Operation should succeed");
+        let mut expected_data = test_data.clone();
+        expected_data.drain(60..60 + 4096);
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, expected_data);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_byte_range_whole_file_and_tail() {
+        let test_file = std::env::temp_dir().join("test_remove_range_whole.bin");
+        std::fs::write(&test_file, vec![0x01, 0x02, 0x03]).expect("create test file");
+
+        assert!(remove_byte_range_from_file(test_file.clone(), 2, 1).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x01, 0x02]
+        );
+
+        assert!(remove_byte_range_from_file(test_file.clone(), 0, 2).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            Vec::<u8>::new()
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_byte_range_invalid_ranges() {
+        let test_file = std::env::temp_dir().join("test_remove_range_invalid.bin");
+        std::fs::write(&test_file, vec![0x01, 0x02, 0x03]).expect("create test file");
+
+        assert!(remove_byte_range_from_file(test_file.clone(), 2, 2).is_err());
+        assert!(remove_byte_range_from_file(test_file.clone(), 1, 0).is_err());
+        assert!(remove_byte_range_from_file(test_file.clone(), u64::MAX, 2).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x01, 0x02, 0x03]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_bytes_middle_and_eof() {
+        let test_file = std::env::temp_dir().join("test_insert_bytes_middle.bin");
+        std::fs::write(&test_file, vec![0x41, 0x42]).expect("create test file");
+
+        assert!(insert_bytes_into_file(test_file.clone(), 1, &[0xDE, 0xAD]).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x41, 0xDE, 0xAD, 0x42]
+        );
+
+        assert!(insert_bytes_into_file(test_file.clone(), 4, &[0xFF]).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x41, 0xDE, 0xAD, 0x42, 0xFF]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_bytes_payload_larger_than_buffer() {
+        let test_file = std::env::temp_dir().join("test_insert_bytes_large.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(300).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+        let payload: Vec<u8> = (0..200u8).map(|value| value ^ 0x5A).collect();
+
+        assert!(insert_bytes_into_file(test_file.clone(), 63, &payload).is_ok());
+
+        let mut expected_data = test_data.clone();
+        expected_data.splice(63..63, payload.iter().copied());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_insert_bytes_invalid_input() {
+        let test_file = std::env::temp_dir().join("test_insert_bytes_invalid.bin");
+        std::fs::write(&test_file, vec![0x01, 0x02]).expect("create test file");
+
+        assert!(insert_bytes_into_file(test_file.clone(), 3, &[0xFF]).is_err());
+        assert!(insert_bytes_into_file(test_file.clone(), 0, &[]).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x01, 0x02]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_byte_range_across_chunks() {
+        let test_file = std::env::temp_dir().join("test_replace_range_chunks.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(300).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+        let new_bytes = [0xEEu8; 100];
+
+        assert!(replace_byte_range_in_file(test_file.clone(), 50, &new_bytes).is_ok());
+
+        let mut expected_data = test_data.clone();
+        expected_data[50..150].copy_from_slice(&new_bytes);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_byte_range_bounds() {
+        let test_file = std::env::temp_dir().join("test_replace_range_bounds.bin");
+        std::fs::write(&test_file, vec![0x01, 0x02, 0x03]).expect("create test file");
+
+        // Ending exactly at EOF is valid
+        assert!(replace_byte_range_in_file(test_file.clone(), 1, &[0xAA, 0xBB]).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x01, 0xAA, 0xBB]
+        );
+
+        // Past EOF and empty payloads are rejected, file untouched
+        assert!(replace_byte_range_in_file(test_file.clone(), 2, &[0x00, 0x00]).is_err());
+        assert!(replace_byte_range_in_file(test_file.clone(), 0, &[]).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x01, 0xAA, 0xBB]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_splice_grow_shrink_and_equal() {
+        let test_file = std::env::temp_dir().join("test_splice_variants.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(200).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+        let mut expected_data = test_data.clone();
+
+        // Grow: remove 2, insert 70 (crosses a buffer boundary)
+        let long_replacement = [0x11u8; 70];
+        assert!(splice_byte_range(test_file.clone(), 10, 2, &long_replacement).is_ok());
+        expected_data.splice(10..12, long_replacement.iter().copied());
+
+        // Shrink: remove 100, insert 1
+        assert!(splice_byte_range(test_file.clone(), 60, 100, &[0x22]).is_ok());
+        expected_data.splice(60..160, [0x22u8]);
+
+        // Pure removal at the tail
+        let tail_start = expected_data.len() - 3;
+        assert!(splice_byte_range(test_file.clone(), tail_start as u64, 3, &[]).is_ok());
+        expected_data.truncate(tail_start);
+
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_splice_invalid_input() {
+        let test_file = std::env::temp_dir().join("test_splice_invalid.bin");
+        std::fs::write(&test_file, vec![0x01, 0x02]).expect("create test file");
+
+        assert!(splice_byte_range(test_file.clone(), 0, 0, &[]).is_err());
+        assert!(splice_byte_range(test_file.clone(), 1, 2, &[0xFF]).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read file"),
+            vec![0x01, 0x02]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_copy_byte_range_forward_and_backward() {
+        let test_file = std::env::temp_dir().join("test_copy_range_within.bin");
+        std::fs::write(&test_file, vec![0x48, 0x44, 0x52, 0x00, 0x01, 0x02])
+            .expect("create test file");
+
+        // Duplicate the 3-byte header at the end
+        assert!(copy_byte_range_within_file(test_file.clone(), 0, 3, 6).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![0x48, 0x44, 0x52, 0x00, 0x01, 0x02, 0x48, 0x44, 0x52]
+        );
+
+        // Copy a later record to the front
+        assert!(copy_byte_range_within_file(test_file.clone(), 3, 3, 0).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            vec![
+                0x00, 0x01, 0x02, 0x48, 0x44, 0x52, 0x00, 0x01, 0x02, 0x48, 0x44, 0x52
+            ]
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_copy_byte_range_large_overlapping_destination() {
+        let test_file = std::env::temp_dir().join("test_copy_range_overlap.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(500).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        assert!(copy_byte_range_within_file(test_file.clone(), 10, 200, 100).is_ok());
+
+        let mut expected_data = test_data.clone();
+        expected_data.splice(100..100, test_data[10..210].iter().copied());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        assert!(copy_byte_range_within_file(test_file.clone(), 650, 100, 0).is_err());
+        assert!(copy_byte_range_within_file(test_file.clone(), 0, 0, 0).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_multiple_byte_ranges() {
+        let test_file = std::env::temp_dir().join("test_multi_range_replace.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(200).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        let replacements = vec![
+            (150, vec![0xAA; 10]),
+            (0, vec![0xBB, 0xCC]),
+            (63, vec![0xDD; 3]),
+        ];
+        assert!(replace_multiple_byte_ranges_in_file(test_file.clone(), &replacements).is_ok());
+
+        let mut expected_data = test_data.clone();
+        for (position, new_bytes) in &replacements {
+            let start = *position as usize;
+            expected_data[start..start + new_bytes.len()].copy_from_slice(new_bytes);
+        }
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        // Overlapping ranges are rejected
+        let overlapping = vec![(10, vec![0x00; 5]), (12, vec![0x00])];
+        assert!(replace_multiple_byte_ranges_in_file(test_file.clone(), &overlapping).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_swap_byte_ranges_unequal_lengths() {
+        let test_file = std::env::temp_dir().join("test_swap_ranges_unequal.bin");
+        std::fs::write(&test_file, b"AAxxxBBBBByy").expect("create test file");
+
+        // Given in either order, the result is the same
+        assert!(swap_byte_ranges(test_file.clone(), 5, 5, 0, 2).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            b"BBBBBxxxAAyy".to_vec()
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_swap_byte_ranges_large_and_invalid() {
+        let test_file = std::env::temp_dir().join("test_swap_ranges_large.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(400).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        assert!(swap_byte_ranges(test_file.clone(), 10, 70, 200, 130).is_ok());
+
+        let mut expected_data = Vec::new();
+        expected_data.extend_from_slice(&test_data[..10]);
+        expected_data.extend_from_slice(&test_data[200..330]);
+        expected_data.extend_from_slice(&test_data[80..200]);
+        expected_data.extend_from_slice(&test_data[10..80]);
+        expected_data.extend_from_slice(&test_data[330..]);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        // Overlapping, empty, and out-of-bounds ranges are rejected
+        assert!(swap_byte_ranges(test_file.clone(), 0, 10, 5, 10).is_err());
+        assert!(swap_byte_ranges(test_file.clone(), 0, 0, 5, 10).is_err());
+        assert!(swap_byte_ranges(test_file.clone(), 0, 10, 395, 10).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_rotate_byte_range_left_and_right() {
+        let test_file = std::env::temp_dir().join("test_rotate_range.bin");
+        std::fs::write(&test_file, b"xxABCDEyy").expect("create test file");
+
+        assert!(rotate_byte_range(test_file.clone(), 2, 5, 2, RotationDirection::Left).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            b"xxCDEAByy".to_vec()
+        );
+
+        // Right by 2 undoes it; right by 7 == right by 2
+        assert!(rotate_byte_range(test_file.clone(), 2, 5, 7, RotationDirection::Right).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            b"xxABCDEyy".to_vec()
+        );
+
+        assert!(rotate_byte_range(test_file.clone(), 5, 5, 1, RotationDirection::Left).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_rotate_byte_range_large() {
+        let test_file = std::env::temp_dir().join("test_rotate_range_large.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        assert!(
+            rotate_byte_range(test_file.clone(), 100, 700, 130, RotationDirection::Right).is_ok()
+        );
+
+        let mut expected_data = test_data.clone();
+        expected_data[100..800].rotate_right(130);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_fill_range_with_pattern() {
+        let test_file = std::env::temp_dir().join("test_fill_range_pattern.bin");
+        let test_data = vec![0x11u8; 200];
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        let pattern = [0xDE, 0xAD, 0xBE, 0xEF];
+        assert!(fill_range_with_pattern(test_file.clone(), 5, 130, &pattern).is_ok());
+
+        let mut expected_data = test_data.clone();
+        for i in 0..130 {
+            expected_data[5 + i] = pattern[i % pattern.len()];
+        }
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        assert!(fill_range_with_pattern(test_file.clone(), 0, 10, &[]).is_err());
+        assert!(fill_range_with_pattern(test_file.clone(), 195, 10, &pattern).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_secure_wipe_range() {
+        let test_file = std::env::temp_dir().join("test_secure_wipe_range.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(300).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        assert!(secure_wipe_range(test_file.clone(), 10, 150, 3).is_ok());
+
+        let mut expected_data = test_data.clone();
+        expected_data[10..160].fill(0x00);
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read modified file"),
+            expected_data
+        );
+
+        // In place: no backup or draft is left behind
+        assert!(!test_file.with_extension("bin.backup").exists());
+
+        assert!(secure_wipe_range(test_file.clone(), 10, 150, 0).is_err());
+        assert!(secure_wipe_range(test_file.clone(), 290, 20, 1).is_err());
+
+        // A kept backup would be another copy of the secret
+        std::fs::write(&test_file, &test_data).expect("recreate test file");
+        let keep_backup_config = OperationConfig::builder()
+            .keep_backup(true)
+            .build()
+            .expect("keep-backup config");
+        let error =
+            secure_wipe_range_with_config(test_file.clone(), 10, 150, 1, &keep_backup_config)
+                .expect_err("backups refused");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            std::fs::read(&test_file).expect("read unchanged file"),
+            test_data
+        );
+
+        let mut first_stream = [0u8; 40];
+        let mut second_stream = [0u8; 40];
+        fill_pseudorandom_bytes(7, 0, &mut first_stream);
+        fill_pseudorandom_bytes(7, 13, &mut second_stream[..27]);
+        assert_eq!(first_stream[13..], second_stream[..27]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_range_operations_accept_end_relative_offsets() {
+        let test_file = std::env::temp_dir().join("test_range_end_relative.bin");
+        std::fs::write(&test_file, b"header-body-trailer").expect("create test file");
+
+        assert!(
+            replace_byte_range_in_file(test_file.clone(), Offset::FromEnd(7), b"TRAILER").is_ok()
+        );
+        assert!(insert_bytes_into_file(test_file.clone(), Offset::FromEnd(0), b"!").is_ok());
+        assert!(remove_byte_range_from_file(test_file.clone(), Offset::FromEnd(13), 5).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("read modified file"),
+            b"header-TRAILER!"
+        );
+        assert!(remove_byte_range_from_file(test_file.clone(), Offset::FromEnd(16), 1).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
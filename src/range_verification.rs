@@ -0,0 +1,214 @@
+//! Range-equality checks between two files
+//!
+//! For users validating that an external copy or transform tool preserved
+//! specific regions: `verify_ranges_equal` compares pairs of
+//! `(offset, length)` ranges across two files with the same comparison
+//! engine the draft workflow uses (`verify_next_bytes_match`), and each
+//! mismatch names the first differing position in each file's own
+//! coordinates.
+//!
+//! Each file is opened once. When both range lists are in ascending offset
+//! order, each file is read front to back in a single forward pass.
+
+use std::{
+    fs::File,
+    io::{self, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::draft_workflow::verify_next_bytes_match;
+use crate::error::{ByteOpError, VerificationPhase};
+
+/// Bytes handed to one `verify_next_bytes_match` call (keeps every call far
+/// below the per-call chunk limit, so ranges of any size can be compared)
+const COMPARISON_SLICE_SIZE: u64 = 1 << 20;
+
+/// A range pair whose bytes differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeMismatch {
+    /// Index into the range lists
+    pub pair_index: usize,
+    /// First difference, in both files' coordinates (the engine's wording:
+    /// `original[...]` is `file_a`, `draft[...]` is `file_b`)
+    pub detail: String,
+}
+
+/// Compares `ranges_a[i]` of `file_a` with `ranges_b[i]` of `file_b`.
+///
+/// # Parameters
+/// - `ranges_a`, `ranges_b`: `(offset, length)` lists of equal length;
+///   paired ranges must have equal lengths
+///
+/// # Returns
+/// - `Ok(Vec<RangeMismatch>)`: empty if every pair is byte-identical,
+///   otherwise one entry per differing pair (first difference only)
+/// - `Err(io::Error)` if the lists are malformed, a range extends past its
+///   file's end, or reading fails
+pub fn verify_ranges_equal(
+    file_a: &Path,
+    ranges_a: &[(u64, u64)],
+    file_b: &Path,
+    ranges_b: &[(u64, u64)],
+) -> io::Result<Vec<RangeMismatch>> {
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    if ranges_a.len() != ranges_b.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Range lists differ in length: {} vs {}",
+                ranges_a.len(),
+                ranges_b.len()
+            ),
+        ));
+    }
+
+    let mut reader_a = File::open(file_a)?;
+    let mut reader_b = File::open(file_b)?;
+    let size_a = reader_a.metadata()?.len();
+    let size_b = reader_b.metadata()?.len();
+
+    for (pair_index, (&(offset_a, length_a), &(offset_b, length_b))) in
+        ranges_a.iter().zip(ranges_b).enumerate()
+    {
+        if length_a != length_b {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Range pair {} differs in length: {} vs {}",
+                    pair_index, length_a, length_b
+                ),
+            ));
+        }
+        let fits_a = offset_a
+            .checked_add(length_a)
+            .is_some_and(|end| end <= size_a);
+        let fits_b = offset_b
+            .checked_add(length_b)
+            .is_some_and(|end| end <= size_b);
+        if !fits_a || !fits_b {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Range pair {} extends past end of file", pair_index),
+            ));
+        }
+    }
+
+    // =========================================
+    // Comparison Phase
+    // =========================================
+    let mut mismatches: Vec<RangeMismatch> = Vec::new();
+    let mut position_a: u64 = 0;
+    let mut position_b: u64 = 0;
+
+    for (pair_index, (&(offset_a, byte_length), &(offset_b, _))) in
+        ranges_a.iter().zip(ranges_b).enumerate()
+    {
+        if position_a != offset_a {
+            reader_a.seek(SeekFrom::Start(offset_a))?;
+        }
+        if position_b != offset_b {
+            reader_b.seek(SeekFrom::Start(offset_b))?;
+        }
+
+        let mut bytes_compared: u64 = 0;
+        while bytes_compared < byte_length {
+            let slice_length = std::cmp::min(COMPARISON_SLICE_SIZE, byte_length - bytes_compared);
+            let comparison = verify_next_bytes_match(
+                &mut reader_a,
+                &mut reader_b,
+                slice_length,
+                offset_a + bytes_compared,
+                offset_b + bytes_compared,
+                &format!("Range pair {}", pair_index),
+            );
+            match comparison {
+                Ok(_) => bytes_compared += slice_length,
+                Err(error) => match ByteOpError::from_io_error(&error) {
+                    // The engine reports draft (`file_b`) offsets; the
+                    // `file_a` byte sits at the same distance into its range
+                    Some(ByteOpError::VerificationFailed {
+                        phase: VerificationPhase::CopiedRange,
+                        offset,
+                        expected,
+                        actual,
+                    }) => {
+                        mismatches.push(RangeMismatch {
+                            pair_index,
+                            detail: format!(
+                                "original[{}]=0x{:02X}, draft[{}]=0x{:02X}",
+                                offset_a + (offset - offset_b),
+                                expected,
+                                offset,
+                                actual
+                            ),
+                        });
+                        break;
+                    }
+                    Some(ByteOpError::VerificationFailed { .. }) => {
+                        mismatches.push(RangeMismatch {
+                            pair_index,
+                            detail: error.to_string(),
+                        });
+                        break;
+                    }
+                    _ => return Err(error),
+                },
+            }
+        }
+
+        // After a mismatch the readers stopped mid-range; force a seek
+        position_a = if bytes_compared == byte_length {
+            offset_a + byte_length
+        } else {
+            u64::MAX
+        };
+        position_b = if bytes_compared == byte_length {
+            offset_b + byte_length
+        } else {
+            u64::MAX
+        };
+    }
+
+    Ok(mismatches)
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_ranges_equal_reports_differing_pairs() {
+        let file_a = std::env::temp_dir().join("test_verify_ranges_a.bin");
+        let file_b = std::env::temp_dir().join("test_verify_ranges_b.bin");
+        let data_a: Vec<u8> = (0..=255u8).cycle().take(400).collect();
+        let mut data_b = vec![0xEEu8; 50];
+        data_b.extend_from_slice(&data_a[..300]);
+        data_b[50 + 250] ^= 0xFF; // corrupt one byte of the second region
+        std::fs::write(&file_a, &data_a).expect("write a");
+        std::fs::write(&file_b, &data_b).expect("write b");
+
+        let mismatches = verify_ranges_equal(
+            &file_a,
+            &[(0, 200), (200, 100)],
+            &file_b,
+            &[(50, 200), (250, 100)],
+        )
+        .expect("comparison should run");
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].pair_index, 1);
+        assert!(mismatches[0].detail.contains("original[250]"));
+        assert!(mismatches[0].detail.contains("draft[300]"));
+
+        assert!(verify_ranges_equal(&file_a, &[(0, 10)], &file_b, &[]).is_err());
+        assert!(verify_ranges_equal(&file_a, &[(0, 10)], &file_b, &[(345, 10)]).is_err());
+
+        let _ = std::fs::remove_file(&file_a);
+        let _ = std::fs::remove_file(&file_b);
+    }
+}
@@ -0,0 +1,231 @@
+//! Recipes: named, versioned patch plans shared through a local directory
+//!
+//! A recipe is a plan text file (see `patch_plan::parse_plan_text`) with a
+//! small header, stored as `<anything>.recipe` in the recipes directory:
+//! ```text
+//! name: fix-widget-serial
+//! version: 2
+//! description: Clear the serial-check flag in widget firmware 1.x
+//!
+//! replace 0x1F0 00 when byte 0x1F0 == 01
+//! ```
+//! Several versions of one recipe may live side by side. `NAME` selects the
+//! highest version; `NAME@VERSION` selects an exact one.
+//!
+//! The directory is `$BFBO_RECIPES_DIR` if set, else `./.bfbo/recipes`.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::patch_plan::{PlanEntry, parse_plan_text};
+
+/// Environment variable overriding the recipes directory
+pub const RECIPES_DIRECTORY_VARIABLE: &str = "BFBO_RECIPES_DIR";
+
+/// Recipes directory used when the variable is not set (relative to cwd)
+pub const DEFAULT_RECIPES_DIRECTORY: &str = ".bfbo/recipes";
+
+/// File extension of recipe files
+pub const RECIPE_FILE_EXTENSION: &str = "recipe";
+
+/// Upper bound on recipe files read from one directory
+const MAX_RECIPE_FILES: usize = 65_536;
+
+/// A parsed recipe file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    pub name: String,
+    pub version: u32,
+    pub description: String,
+    pub plan_entries: Vec<PlanEntry>,
+    pub source_path: PathBuf,
+    /// Full file text, for `recipe show`
+    pub source_text: String,
+}
+
+/// Returns the recipes directory for this process.
+pub fn recipes_directory() -> PathBuf {
+    match std::env::var(RECIPES_DIRECTORY_VARIABLE) {
+        Ok(directory) if !directory.is_empty() => PathBuf::from(directory),
+        _ => PathBuf::from(DEFAULT_RECIPES_DIRECTORY),
+    }
+}
+
+/// Parses a recipe file's text (header lines, then plan lines).
+///
+/// # Returns
+/// - `Ok(Recipe)` if `name` and `version` are present and the plan parses
+/// - `Err(io::Error)` (`InvalidData`) otherwise
+pub fn parse_recipe_text(source_text: &str, source_path: &Path) -> io::Result<Recipe> {
+    let mut name: Option<String> = None;
+    let mut version: Option<u32> = None;
+    let mut description = String::new();
+    let mut plan_text = String::new();
+
+    for line in source_text.lines() {
+        let is_comment = line.trim_start().starts_with('#');
+        let header = line
+            .split_once(':')
+            .filter(|(key, _)| !is_comment && !key.trim().contains(char::is_whitespace));
+        match header {
+            Some((key, value)) if key.trim() == "name" => name = Some(value.trim().to_string()),
+            Some((key, value)) if key.trim() == "version" => {
+                version = Some(value.trim().parse::<u32>().map_err(|_| {
+                    invalid_recipe(source_path, &format!("invalid version `{}`", value.trim()))
+                })?)
+            }
+            Some((key, value)) if key.trim() == "description" => {
+                description = value.trim().to_string()
+            }
+            Some((key, _)) => {
+                return Err(invalid_recipe(
+                    source_path,
+                    &format!("unknown header `{}`", key.trim()),
+                ));
+            }
+            None => {}
+        }
+        // Header lines become blank plan lines, keeping line numbers aligned
+        if header.is_none() {
+            plan_text.push_str(line);
+        }
+        plan_text.push('\n');
+    }
+
+    let name = name.ok_or_else(|| invalid_recipe(source_path, "missing `name:` header"))?;
+    let version =
+        version.ok_or_else(|| invalid_recipe(source_path, "missing `version:` header"))?;
+    let plan_entries = parse_plan_text(&plan_text)
+        .map_err(|error| invalid_recipe(source_path, &error.to_string()))?;
+
+    Ok(Recipe {
+        name,
+        version,
+        description,
+        plan_entries,
+        source_path: source_path.to_path_buf(),
+        source_text: source_text.to_string(),
+    })
+}
+
+/// Loads every `*.recipe` file in a directory, sorted by name then version.
+///
+/// A missing directory yields an empty list; a malformed recipe is an error
+/// (a shared registry should not silently hide a broken patch).
+pub fn load_recipes(directory: &Path) -> io::Result<Vec<Recipe>> {
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut recipes = Vec::new();
+    for (entry_count, directory_entry) in fs::read_dir(directory)?.enumerate() {
+        if entry_count >= MAX_RECIPE_FILES {
+            return Err(io::Error::other("Too many files in recipes directory"));
+        }
+        let entry_path = directory_entry?.path();
+        let is_recipe_file = entry_path.is_file()
+            && entry_path.extension().and_then(|e| e.to_str()) == Some(RECIPE_FILE_EXTENSION);
+        if !is_recipe_file {
+            continue;
+        }
+        let source_text = fs::read_to_string(&entry_path)?;
+        recipes.push(parse_recipe_text(&source_text, &entry_path)?);
+    }
+
+    recipes.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    Ok(recipes)
+}
+
+/// Finds a recipe by `NAME` (highest version) or `NAME@VERSION`.
+pub fn find_recipe<'a>(recipes: &'a [Recipe], selector: &str) -> io::Result<&'a Recipe> {
+    let (name, wanted_version) = match selector.split_once('@') {
+        Some((name, version_text)) => {
+            let version = version_text.parse::<u32>().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid recipe version in `{}`", selector),
+                )
+            })?;
+            (name, Some(version))
+        }
+        None => (selector, None),
+    };
+
+    recipes
+        .iter()
+        .filter(|recipe| recipe.name == name)
+        .filter(|recipe| wanted_version.is_none_or(|version| recipe.version == version))
+        .max_by_key(|recipe| recipe.version)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No recipe matches `{}`", selector),
+            )
+        })
+}
+
+fn invalid_recipe(source_path: &Path, reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Recipe {}: {}", source_path.display(), reason),
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patch_plan::ByteOperation;
+
+    #[test]
+    fn test_recipe_parse_and_version_selection() {
+        let recipes_dir = std::env::temp_dir().join("test_recipes_registry");
+        let _ = fs::remove_dir_all(&recipes_dir);
+        fs::create_dir_all(&recipes_dir).expect("create recipes dir");
+
+        fs::write(
+            recipes_dir.join("fix-v1.recipe"),
+            "name: fix-widget\nversion: 1\ndescription: first\nremove 0\n",
+        )
+        .expect("write recipe");
+        fs::write(
+            recipes_dir.join("fix-v2.recipe"),
+            "name: fix-widget\nversion: 2\n\n# newer\nreplace 0x01 FF when size > 1\n",
+        )
+        .expect("write recipe");
+        fs::write(recipes_dir.join("notes.txt"), "not a recipe").expect("write notes");
+
+        let recipes = load_recipes(&recipes_dir).expect("recipes should load");
+        assert_eq!(recipes.len(), 2);
+
+        let latest = find_recipe(&recipes, "fix-widget").expect("found");
+        assert_eq!(latest.version, 2);
+        assert_eq!(
+            latest.plan_entries[0].operation,
+            ByteOperation::Replace {
+                position: 1,
+                new_byte_value: 0xFF
+            }
+        );
+
+        let pinned = find_recipe(&recipes, "fix-widget@1").expect("found");
+        assert_eq!(pinned.description, "first");
+        assert!(find_recipe(&recipes, "fix-widget@3").is_err());
+        assert!(find_recipe(&recipes, "other").is_err());
+
+        let _ = fs::remove_dir_all(&recipes_dir);
+    }
+
+    #[test]
+    fn test_recipe_missing_header_rejected() {
+        let error = parse_recipe_text("version: 1\nremove 0\n", Path::new("x.recipe"))
+            .expect_err("missing name");
+        assert!(error.to_string().contains("name"));
+        assert!(parse_recipe_text("name: a\nversion: one\n", Path::new("x.recipe")).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Copy-on-write clones for backups
+//!
+//! A backup is a full copy of the original, so the backup phase of a
+//! multi-gigabyte file takes as long as reading and writing all of it. On
+//! filesystems with reflinks (Btrfs, XFS, bcachefs on Linux via `FICLONE`;
+//! APFS on macOS via `clonefile`) the backup is made as a clone instead:
+//! it shares the original's blocks until one of them is written, so it is
+//! created in constant time and takes no space up front. Later writes to
+//! either file do not affect the other.
+//!
+//! Where cloning is not supported (another filesystem, another platform,
+//! source and backup on different filesystems) `fs::copy` is used, with
+//! the same result.
+
+use std::{fs, io, path::Path};
+
+use crate::reporter::report_detail;
+
+/// Copies `source_file_path` to `destination_file_path` (created or
+/// replaced), as a copy-on-write clone where the filesystem supports it.
+///
+/// # Returns
+/// - `Ok(bytes)`: the size of the copy, like `fs::copy`
+/// - `Err(io::Error)` from the fallback `fs::copy`
+pub(crate) fn clone_or_copy(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+) -> io::Result<u64> {
+    if try_clone(source_file_path, destination_file_path) {
+        report_detail!("Cloned {} (copy-on-write)", destination_file_path.display());
+        return fs::metadata(destination_file_path).map(|metadata| metadata.len());
+    }
+    fs::copy(source_file_path, destination_file_path)
+}
+
+/// `FICLONE`: the destination shares the source's extents. Any failure
+/// (unsupported filesystem, different filesystems, ...) means "copy".
+#[cfg(target_os = "linux")]
+fn try_clone(source_file_path: &Path, destination_file_path: &Path) -> bool {
+    use std::{ffi::c_ulong, fs::File, os::fd::AsRawFd};
+
+    // _IOW(0x94, 9, int)
+    const FICLONE: c_ulong = 0x4004_9409;
+    unsafe extern "C" {
+        fn ioctl(fd: i32, request: c_ulong, ...) -> i32;
+    }
+
+    let Ok(source_file) = File::open(source_file_path) else {
+        return false;
+    };
+    let Ok(destination_file) = File::create(destination_file_path) else {
+        return false;
+    };
+    // SAFETY: both descriptors are open for the duration of the call
+    let cloned = unsafe {
+        ioctl(
+            destination_file.as_raw_fd(),
+            FICLONE,
+            source_file.as_raw_fd(),
+        )
+    } == 0;
+    // `fs::copy` carries the permissions over; so does a clone
+    cloned
+        && source_file
+            .metadata()
+            .and_then(|metadata| destination_file.set_permissions(metadata.permissions()))
+            .is_ok()
+}
+
+/// `clonefile(2)`: creates the destination as a clone, with the source's
+/// permissions. It refuses an existing destination, so a stale one is
+/// removed first.
+#[cfg(target_os = "macos")]
+fn try_clone(source_file_path: &Path, destination_file_path: &Path) -> bool {
+    use std::{
+        ffi::{CString, c_char},
+        os::unix::ffi::OsStrExt,
+    };
+
+    unsafe extern "C" {
+        fn clonefile(source: *const c_char, destination: *const c_char, flags: u32) -> i32;
+    }
+
+    let (Ok(source_c_path), Ok(destination_c_path)) = (
+        CString::new(source_file_path.as_os_str().as_bytes()),
+        CString::new(destination_file_path.as_os_str().as_bytes()),
+    ) else {
+        return false;
+    };
+    match fs::remove_file(destination_file_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return false,
+        _ => {}
+    }
+    // SAFETY: both paths are NUL-terminated and outlive the call
+    unsafe { clonefile(source_c_path.as_ptr(), destination_c_path.as_ptr(), 0) == 0 }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn try_clone(_source_file_path: &Path, _destination_file_path: &Path) -> bool {
+    false
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_or_copy_matches_source() {
+        let source_path = std::env::temp_dir().join("test_reflink_source.bin");
+        let destination_path = std::env::temp_dir().join("test_reflink_source.bin.backup");
+        let content: Vec<u8> = (0..100_000u32).map(|value| value as u8).collect();
+        fs::write(&source_path, &content).expect("write source");
+        // A stale destination is replaced, cloned or copied
+        fs::write(&destination_path, b"stale backup that is longer").expect("write stale");
+
+        let copied = clone_or_copy(&source_path, &destination_path).expect("clone or copy");
+        assert_eq!(copied, content.len() as u64);
+        assert_eq!(fs::read(&destination_path).expect("read copy"), content);
+
+        // The copy is independent of later changes to the source
+        fs::write(&source_path, b"changed").expect("rewrite source");
+        assert_eq!(fs::read(&destination_path).expect("read copy"), content);
+
+        let _ = fs::remove_file(&source_path);
+        let _ = fs::remove_file(&destination_path);
+    }
+}
@@ -0,0 +1,777 @@
+//! Single-byte removal (frame-shift -1)
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+use crate::backup_manifest::record_backup_manifest;
+use crate::canary::seal_draft_with_canary;
+use crate::draft_workflow::{
+    DraftArtifactPaths, VerificationLevel, build_artifact_paths, check_artifact_overwrite,
+    check_draft_against_utf8_guard, check_original_preconditions, chunk_limit_for,
+    copy_original_metadata, create_buffered_draft, open_buffered_reader, replace_with_draft,
+    resolve_symlinked_target, rotate_backups,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::journal::{JournalPhase, OperationJournal};
+use crate::lock_file::OperationLock;
+use crate::offset::Offset;
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::operation_report::{OperationReport, run_with_operation_report};
+use crate::page_cache::release_cached_pages;
+use crate::reflink::clone_or_copy;
+use crate::reporter::{
+    OperationPhase, ReportEvent, report, report_backup_kept, report_detail, report_phase,
+    report_problem,
+};
+use crate::verify::verify_byte_removal_operation;
+use crate::writability::check_target_writable;
+
+/// Performs a byte removal operation on a file using a safe copy-and-replace strategy.
+///
+/// # Overview
+/// This function removes a single byte at a specified position in a file, causing all
+/// subsequent bytes to shift backward by one position (frame-shift -1). It uses a defensive
+/// "build-new-file" approach rather than modifying the original file directly.
+///
+/// # Memory Safety
+/// - Uses one bucket-brigade buffer, allocated once per operation (64 KiB
+///   unless the active `OperationConfig` sets another size)
+/// - Never loads entire file into memory
+/// - Processes file chunk-by-chunk using bucket brigade pattern
+/// - No allocation inside the processing loop
+///
+/// # File Safety Strategy
+/// 1. Creates a backup copy of the original file (.backup extension)
+/// 2. Builds a new draft file (.draft extension) with the byte removed
+/// 3. Verifies the operation succeeded (including frame-shift verification)
+/// 4. Atomically replaces original with draft
+/// 5. Removes backup only after successful completion
+///
+/// # Operation Behavior - Mechanical Steps
+/// The draft file is constructed by appending bytes sequentially:
+///
+/// **Step 1**: Create empty draft file
+///
+/// **Step 2**: Append pre-position bytes
+/// - Read from original: positions 0 to `byte_position - 1`
+/// - Append to draft: all these bytes
+///
+/// **Step 3**: Perform removal AT position
+/// - Original file: advance read position by 1 (skip target byte)
+/// - Draft file: write nothing (no append action)
+/// - Effect: The byte at target position is never written to draft
+///
+/// **Step 4**: Append post-position bytes
+/// - Read from original: positions `byte_position + 1` to EOF
+/// - Append to draft: all remaining bytes
+/// - Effect: These bytes naturally occupy positions starting at `byte_position` in draft
+/// - This creates the -1 frame-shift automatically
+///
+/// # Frame-Shift Behavior
+/// After removing byte at position N:
+/// - Bytes 0 to N-1: unchanged positions
+/// - Byte at N: removed (does not exist in new file)
+/// - Bytes N+1 to EOF: all shift backward by 1 position
+/// - File length decreases by exactly 1
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: Position of the byte to remove: a zero-indexed `u64`
+///   from the start, or `Offset::FromEnd(n)` (`FromEnd(1)` is the last byte)
+///
+/// # Returns
+/// - `Ok(())` on successful byte removal
+/// - `Err(io::Error)` if file operations fail or position is invalid
+///
+/// # Error Conditions
+/// - File does not exist
+/// - File is empty
+/// - Byte position >= file length (out of bounds)
+/// - Insufficient permissions
+/// - Disk full
+/// - I/O errors during read/write
+///
+/// # Recovery Behavior
+/// - If operation fails before replacing original, draft is removed, backup remains
+/// - If atomic rename fails, both original and backup are preserved
+/// - Orphaned .draft files indicate incomplete operations
+/// - Orphaned .backup files indicate failed replacements
+///
+/// # Edge Cases
+/// - Empty file: Returns error (no bytes to remove)
+/// - Position >= file length: Returns error (position out of bounds)
+/// - Single byte file at position 0: Results in empty file (valid operation)
+/// - Remove last byte: File becomes 1 byte shorter, no post-position bytes
+/// - Remove first byte: No pre-position bytes, all bytes shift backward
+/// - Very large files: Processes in chunks, no memory issues
+///
+/// # Example
+/// ```no_run
+/// # use std::io;
+/// # use std::path::PathBuf;
+/// # fn remove_single_byte_from_file(path: PathBuf, pos: u64) -> io::Result<()> { Ok(()) }
+/// // Original file: [0x41, 0x42, 0x43, 0x44, 0x45]
+/// let file_path = PathBuf::from("/absolute/path/to/file.dat");
+/// let position = 2; // Remove byte at position 2 (0x43)
+/// let result = remove_single_byte_from_file(file_path, position);
+/// // Resulting file: [0x41, 0x42, 0x44, 0x45]
+/// // Note: 0x44 and 0x45 shifted backward by 1 position
+/// assert!(result.is_ok());
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn remove_single_byte_from_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    report(&ReportEvent::OperationStarted {
+        operation_name: "Byte Removal",
+        target_path: &original_file_path,
+    });
+    report_detail!("Byte position to remove: {}", byte_offset);
+
+    // A symlinked target is resolved or refused before the path is used
+    let config = active_operation_config();
+    let original_file_path = resolve_symlinked_target(&original_file_path, &config)?;
+
+    // Verify file exists before any operations
+    if !original_file_path.exists() {
+        let error = ByteOpError::target_not_found(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Verify file is actually a file, not a directory
+    if !original_file_path.is_file() {
+        let error = ByteOpError::not_a_file(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Get original file metadata for validation
+    let original_metadata = fs::metadata(&original_file_path)?;
+    let original_file_size = original_metadata.len();
+
+    // Handle empty file case
+    if original_file_size == 0 {
+        let error = ByteOpError::EmptyFile;
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Resolve an end-relative offset against the size just read
+    let byte_position_from_start = match byte_offset.resolve(original_file_size) {
+        Ok(position) => position,
+        Err(error) => {
+            report_problem!("ERROR: {}", error);
+            return Err(error);
+        }
+    };
+    if let Offset::FromEnd(_) = byte_offset {
+        report_detail!("Resolved position: {}", byte_position_from_start);
+    }
+
+    // Validate byte position is within file bounds
+    if byte_position_from_start >= original_file_size {
+        let error = ByteOpError::PositionOutOfBounds {
+            position: byte_position_from_start,
+            file_size: original_file_size,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // =========================================
+    // Path Construction Phase
+    // =========================================
+
+    // Build backup and draft file paths
+    check_original_preconditions(&original_file_path, &config)?;
+    let artifact_paths = build_artifact_paths(&original_file_path)?;
+    check_target_writable(&original_file_path)?;
+    let _operation_lock = OperationLock::acquire(&original_file_path)?;
+    check_artifact_overwrite(&original_file_path, &artifact_paths, true)?;
+    let mut journal = OperationJournal::begin(
+        &original_file_path,
+        "Byte Removal",
+        &artifact_paths,
+        true,
+        &config,
+    )?;
+    let DraftArtifactPaths {
+        backup_file_path,
+        draft_file_path,
+        ..
+    } = artifact_paths;
+    report_detail!("Backup path: {}", backup_file_path.display());
+    report_detail!("Draft path: {}", draft_file_path.display());
+
+    // =========================================
+    // Backup Creation Phase
+    // =========================================
+    report_phase(OperationPhase::Backup);
+    clone_or_copy(&original_file_path, &backup_file_path).map_err(|source| {
+        let error = ByteOpError::BackupFailed { source };
+        report_problem!("ERROR: {}", error);
+        io::Error::from(error)
+    })?;
+    record_backup_manifest(
+        &backup_file_path,
+        &original_file_path,
+        "Byte Removal",
+        &config,
+    )?;
+    report_detail!("Backup created successfully");
+    journal.mark(JournalPhase::BackedUp)?;
+
+    // =========================================
+    // Draft File Construction Phase
+    // =========================================
+    report_phase(OperationPhase::Draft);
+    report_detail!(
+        "Building modified draft file (removing byte at position {})...",
+        byte_position_from_start
+    );
+
+    // Open original for reading
+    let mut source_file = open_buffered_reader(&original_file_path)?;
+
+    // Create draft file for writing
+    let mut draft_file = create_buffered_draft(&draft_file_path)?;
+
+    // Buffer for bucket brigade operations, allocated once per operation.
+    // Only the first `bytes_read` bytes of it are ever written out, so it is
+    // not re-zeroed between chunks.
+    let bucket_brigade_buffer_size = config.buffer_size();
+    let mut bucket_brigade_buffer = vec![0u8; bucket_brigade_buffer_size];
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert!(
+        bucket_brigade_buffer_size > 0,
+        "Bucket brigade buffer must have non-zero size"
+    );
+
+    #[cfg(test)]
+    {
+        assert!(
+            bucket_brigade_buffer_size > 0,
+            "Bucket brigade buffer must have non-zero size"
+        );
+    }
+
+    if bucket_brigade_buffer_size == 0 {
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid buffer configuration",
+        ));
+    }
+
+    // Tracking variables
+    let mut total_bytes_read_from_original: u64 = 0;
+    let mut total_bytes_written_to_draft: u64 = 0;
+    let mut chunk_number: usize = 0;
+    let mut byte_was_removed = false;
+    let mut removed_byte_value: u8 = 0;
+
+    // Loop bound scaled to the file: see `chunk_limit_for`
+    let max_chunks_allowed = chunk_limit_for(original_file_size);
+
+    // =========================================
+    // Main Processing Loop
+    // =========================================
+
+    loop {
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        debug_assert!(
+            chunk_number < max_chunks_allowed,
+            "Exceeded maximum chunk limit"
+        );
+
+        #[cfg(test)]
+        {
+            assert!(
+                chunk_number < max_chunks_allowed,
+                "Exceeded maximum chunk limit"
+            );
+        }
+
+        if chunk_number >= max_chunks_allowed {
+            report_problem!("ERROR: Maximum chunk limit exceeded for safety");
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(ByteOpError::ChunkLimitExceeded {
+                chunk_limit: max_chunks_allowed,
+            }
+            .into());
+        }
+
+        chunk_number += 1;
+
+        // Read next chunk from source
+        let bytes_read = source_file.read(&mut bucket_brigade_buffer)?;
+
+        // EOF detection
+        if bytes_read == 0 {
+            report_detail!("Reached end of original file");
+            break;
+        }
+        report(&ReportEvent::ChunkProcessed {
+            chunk_number,
+            byte_count: bytes_read,
+        });
+
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        debug_assert!(
+            bytes_read <= bucket_brigade_buffer_size,
+            "Read more bytes than buffer size"
+        );
+
+        #[cfg(test)]
+        {
+            assert!(
+                bytes_read <= bucket_brigade_buffer_size,
+                "Read more bytes than buffer size"
+            );
+        }
+
+        if bytes_read > bucket_brigade_buffer_size {
+            report_problem!("ERROR: Buffer overflow detected");
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(io::Error::other("Buffer overflow in read operation"));
+        }
+
+        // Determine if target byte is in this chunk
+        let chunk_start_position = total_bytes_read_from_original;
+        let chunk_end_position = chunk_start_position + bytes_read as u64;
+
+        // Check if we need to skip a byte in this chunk (the removal operation)
+        if byte_position_from_start >= chunk_start_position
+            && byte_position_from_start < chunk_end_position
+        {
+            // Calculate position within this chunk (below `bytes_read`, so it fits)
+            let position_in_chunk = (byte_position_from_start - chunk_start_position) as usize;
+
+            // Store the byte being removed for verification
+            removed_byte_value = bucket_brigade_buffer[position_in_chunk];
+            byte_was_removed = true;
+            report_detail!(
+                "Removing byte at position {}: 0x{:02X}",
+                byte_position_from_start,
+                removed_byte_value
+            );
+
+            // Write bytes BEFORE the removal position in this chunk
+            if position_in_chunk > 0 {
+                let bytes_before = &bucket_brigade_buffer[..position_in_chunk];
+                let bytes_written_before = draft_file.write(bytes_before)?;
+
+                // =================================================
+                // Debug-Assert, Test-Assert, Production-Catch-Handle
+                // =================================================
+
+                debug_assert_eq!(
+                    bytes_written_before, position_in_chunk,
+                    "Not all pre-removal bytes were written"
+                );
+
+                #[cfg(test)]
+                {
+                    assert_eq!(
+                        bytes_written_before, position_in_chunk,
+                        "Not all pre-removal bytes were written"
+                    );
+                }
+
+                if bytes_written_before != position_in_chunk {
+                    report_problem!("ERROR: Incomplete write before removal position");
+                    let _ = fs::remove_file(&draft_file_path);
+                    return Err(ByteOpError::IncompleteWrite {
+                        requested: position_in_chunk,
+                        written: bytes_written_before,
+                    }
+                    .into());
+                }
+
+                total_bytes_written_to_draft += bytes_written_before as u64;
+            }
+
+            // SKIP the byte at position_in_chunk (this is the removal operation)
+            // Do not write bucket_brigade_buffer[position_in_chunk] to draft
+
+            // Write bytes AFTER the removal position in this chunk
+            let position_after_removal = position_in_chunk + 1;
+            if position_after_removal < bytes_read {
+                let bytes_after = &bucket_brigade_buffer[position_after_removal..bytes_read];
+                let bytes_written_after = draft_file.write(bytes_after)?;
+
+                let expected_bytes_after = bytes_read - position_after_removal;
+
+                // =================================================
+                // Debug-Assert, Test-Assert, Production-Catch-Handle
+                // =================================================
+
+                debug_assert_eq!(
+                    bytes_written_after, expected_bytes_after,
+                    "Not all post-removal bytes were written"
+                );
+
+                #[cfg(test)]
+                {
+                    assert_eq!(
+                        bytes_written_after, expected_bytes_after,
+                        "Not all post-removal bytes were written"
+                    );
+                }
+
+                if bytes_written_after != expected_bytes_after {
+                    report_problem!("ERROR: Incomplete write after removal position");
+                    let _ = fs::remove_file(&draft_file_path);
+                    return Err(ByteOpError::IncompleteWrite {
+                        requested: expected_bytes_after,
+                        written: bytes_written_after,
+                    }
+                    .into());
+                }
+
+                total_bytes_written_to_draft += bytes_written_after as u64;
+            }
+        } else {
+            // This chunk does not contain the removal position
+            // Write entire chunk to draft file
+            let bytes_written = draft_file.write(&bucket_brigade_buffer[..bytes_read])?;
+
+            // =================================================
+            // Debug-Assert, Test-Assert, Production-Catch-Handle
+            // =================================================
+
+            debug_assert_eq!(bytes_written, bytes_read, "Not all bytes were written");
+
+            #[cfg(test)]
+            {
+                assert_eq!(bytes_written, bytes_read, "Not all bytes were written");
+            }
+
+            if bytes_written != bytes_read {
+                report_problem!(
+                    "ERROR: Write mismatch - expected {} bytes, wrote {} bytes",
+                    bytes_read,
+                    bytes_written
+                );
+                let _ = fs::remove_file(&draft_file_path);
+                return Err(ByteOpError::IncompleteWrite {
+                    requested: bytes_read,
+                    written: bytes_written,
+                }
+                .into());
+            }
+
+            total_bytes_written_to_draft += bytes_written as u64;
+        }
+
+        total_bytes_read_from_original += bytes_read as u64;
+    }
+
+    // =========================================
+    // Basic Verification Phase
+    // =========================================
+    report_phase(OperationPhase::Verify);
+
+    // Verify byte was actually removed
+    if !byte_was_removed {
+        report_problem!("ERROR: Target byte position was never reached");
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(ByteOpError::EditNotApplied {
+            operation_name: "Byte removal",
+        }
+        .into());
+    }
+
+    // Verify draft file is exactly 1 byte smaller
+    config.durability_mode().finish_draft(draft_file)?;
+    drop(source_file);
+
+    let draft_metadata = fs::metadata(&draft_file_path)?;
+    let draft_size = draft_metadata.len();
+    let expected_draft_size = original_file_size - 1;
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert_eq!(draft_size, expected_draft_size, "Draft file size incorrect");
+
+    #[cfg(test)]
+    {
+        assert_eq!(draft_size, expected_draft_size, "Draft file size incorrect");
+    }
+
+    if draft_size != expected_draft_size {
+        report_problem!(
+            "ERROR: File size mismatch - original: {} bytes, draft: {} bytes, expected: {} bytes",
+            original_file_size,
+            draft_size,
+            expected_draft_size
+        );
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            expected_draft_size,
+            draft_size,
+        )
+        .into());
+    }
+    report_detail!(
+        "Basic verification passed: original={} bytes, draft={} bytes (-1 byte)",
+        original_file_size,
+        draft_size
+    );
+
+    // =========================================
+    // Comprehensive Verification Phase
+    // =========================================
+
+    // Perform all verification checks before replacing the original
+    if config.verification_level() == VerificationLevel::LengthOnly {
+        report_detail!("Verification level length-only: byte comparison skipped");
+    } else {
+        verify_byte_removal_operation(
+            &original_file_path,
+            &draft_file_path,
+            byte_position_from_start,
+            removed_byte_value,
+        )?;
+    }
+
+    check_draft_against_utf8_guard(&original_file_path, &draft_file_path, "Byte Removal")?;
+    seal_draft_with_canary(&original_file_path, &draft_file_path)?;
+    journal.mark_draft_verified(&draft_file_path)?;
+
+    // =========================================
+    // Atomic Replacement Phase
+    // =========================================
+    report_phase(OperationPhase::Replace);
+    release_cached_pages(&[&original_file_path, &backup_file_path, &draft_file_path]);
+    copy_original_metadata(&original_file_path, &draft_file_path, &config)?;
+
+    // Attempt atomic rename
+    match replace_with_draft(&draft_file_path, &original_file_path) {
+        Ok(()) => {
+            report_detail!("Original file successfully replaced");
+            config
+                .durability_mode()
+                .finish_replace(&original_file_path)?;
+            journal.mark_replaced();
+        }
+        Err(source) => {
+            let error = ByteOpError::AtomicReplaceFailed { source };
+            report_problem!("{}", error);
+            return Err(error.into());
+        }
+    }
+
+    // =========================================
+    // Cleanup Phase
+    // =========================================
+    report_phase(OperationPhase::Cleanup);
+
+    if let Some(rotated_backup_count) = config.rotated_backup_count() {
+        rotate_backups(&backup_file_path, rotated_backup_count);
+    } else if config.keep_backup() {
+        report_backup_kept(&backup_file_path);
+    } else {
+        match fs::remove_file(&backup_file_path) {
+            Ok(()) => report_detail!("Backup file removed"),
+            Err(e) => {
+                report_problem!(
+                    "WARNING: Could not remove backup file: {} ({})",
+                    backup_file_path.display(),
+                    e
+                );
+                report_backup_kept(&backup_file_path);
+            }
+        }
+    }
+
+    // =========================================
+    // Operation Summary
+    // =========================================
+    report_detail!("Removed byte at position: {}", byte_position_from_start);
+    report_detail!("Removed byte value: 0x{:02X}", removed_byte_value);
+    report_detail!("Original size: {} bytes", original_file_size);
+    report_detail!("New size: {} bytes", draft_size);
+    report_detail!(
+        "Bytes read from original: {}",
+        total_bytes_read_from_original
+    );
+    report_detail!("Bytes written to draft: {}", total_bytes_written_to_draft);
+    report_detail!("Total chunks: {}", chunk_number);
+    report(&ReportEvent::OperationFinished {
+        operation_name: "Byte Removal",
+        target_path: &original_file_path,
+    });
+
+    Ok(())
+}
+
+/// Removes a byte like `remove_single_byte_from_file`, returning an `OperationReport`.
+///
+/// Adds two SHA-256 passes (before and after) to the operation; see
+/// `operation_report.rs`.
+pub fn remove_single_byte_from_file_with_report(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+) -> io::Result<OperationReport> {
+    let byte_offset: Offset = byte_offset.into();
+    run_with_operation_report(
+        "Byte Removal",
+        &original_file_path,
+        byte_offset,
+        true,
+        None,
+        || remove_single_byte_from_file(original_file_path.clone(), byte_offset),
+    )
+}
+
+/// Removes a byte like `remove_single_byte_from_file`, using `config` instead of the
+/// active (or default) `OperationConfig`.
+pub fn remove_single_byte_from_file_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+    with_operation_config(config, || {
+        remove_single_byte_from_file(original_file_path, byte_offset)
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_single_byte_basic() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_byte_remove.bin");
+
+        // Create test file: [0x00, 0x11, 0x22, 0x33, 0x44]
+        let test_data = vec![0x00, 0x11, 0x22, 0x33, 0x44];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Remove byte at position 2 (0x22)
+        let result = remove_single_byte_from_file(test_file.clone(), 2);
+
+        assert!(result.is_ok(), "Operation should succeed");
+
+        // Verify result: [0x00, 0x11, 0x33, 0x44]
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0x00, 0x11, 0x33, 0x44]);
+
+        // Cleanup
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_first_byte() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_remove_first.bin");
+
+        let test_data = vec![0xAA, 0xBB, 0xCC];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Remove first byte
+        let result = remove_single_byte_from_file(test_file.clone(), 0);
+
+        assert!(result.is_ok());
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0xBB, 0xCC]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_last_byte() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_remove_last.bin");
+
+        let test_data = vec![0xAA, 0xBB, 0xCC];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Remove last byte
+        let result = remove_single_byte_from_file(test_file.clone(), 2);
+
+        assert!(result.is_ok());
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0xAA, 0xBB]);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_from_single_byte_file() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_remove_single.bin");
+
+        std::fs::write(&test_file, vec![0x42]).expect("Failed to create test file");
+
+        let result = remove_single_byte_from_file(test_file.clone(), 0);
+
+        assert!(result.is_ok());
+
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, Vec::<u8>::new()); // Empty file
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_byte_out_of_bounds() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_remove_bounds.bin");
+
+        std::fs::write(&test_file, vec![0x00, 0x11]).expect("Failed to create test file");
+
+        let result = remove_single_byte_from_file(test_file.clone(), 10);
+
+        assert!(result.is_err(), "Should fail with out of bounds position");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_remove_from_empty_file() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_remove_empty.bin");
+
+        fs::File::create(&test_file).expect("Failed to create empty file");
+
+        let result = remove_single_byte_from_file(test_file.clone(), 0);
+
+        assert!(result.is_err(), "Should fail with empty file");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
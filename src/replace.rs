@@ -0,0 +1,830 @@
+//! In-place single-byte replacement (hex-edit, no frame-shift)
+
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use crate::backup_manifest::record_backup_manifest;
+use crate::canary::seal_draft_with_canary;
+use crate::draft_workflow::{
+    DraftArtifactPaths, VerificationLevel, build_artifact_paths, check_artifact_overwrite,
+    check_draft_against_utf8_guard, check_original_preconditions, chunk_limit_for,
+    copy_original_metadata, create_buffered_draft, open_buffered_reader, replace_with_draft,
+    resolve_symlinked_target, rotate_backups, validate_target_file,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::journal::{JournalPhase, OperationJournal};
+use crate::lock_file::OperationLock;
+use crate::offset::Offset;
+use crate::operation_config::{OperationConfig, active_operation_config, with_operation_config};
+use crate::operation_report::{OperationReport, run_with_operation_report};
+use crate::page_cache::release_cached_pages;
+use crate::reflink::clone_or_copy;
+use crate::reporter::{
+    OperationPhase, ReportEvent, report, report_backup_kept, report_detail, report_phase,
+    report_problem,
+};
+use crate::verify::verify_byte_replacement_operation;
+use crate::writability::check_target_writable;
+
+/// Performs an in-place byte replacement operation on a file using a safe copy-and-replace strategy.
+///
+/// # Overview
+/// This function (effectively) "replaces" a single byte at a specified position
+/// "in" a file without changing file length. The method is a defensive "build-new-file"
+/// approach rather than modifying/changing the original file directly in any way,
+/// allowing for a completely unaltered original file in the case of any errors or exceptions.
+///
+/// # Memory Safety
+/// - Uses one bucket-brigade buffer, allocated once per operation (64 KiB
+///   unless the active `OperationConfig` sets another size)
+/// - Never loads entire file into memory
+/// - Processes file chunk-by-chunk using a "bucket brigade" pattern
+/// - No allocation inside the processing loop
+///
+/// # File Safety Strategy
+/// 1. Creates a backup copy of the original file (.backup extension)
+/// 2. Builds a new draft file (.draft extension) with the modified byte
+/// 3. Verifies that the operation succeeded
+/// 4. Atomically replaces original with draft
+/// 5. Removes backup only after verification tests pass and successful completion
+///
+/// # Operation Behavior
+/// - Copies all bytes before target position unchanged
+/// - Replaces the byte at target position with new_byte_value
+/// - Copies all bytes after target position unchanged
+/// - File length remains exactly the same
+/// - No frame-shifting occurs
+///
+/// # Parameters
+/// - `original_file_path`: Absolute path to the file to modify
+/// - `byte_offset`: Position of the byte to replace: a zero-indexed `u64`
+///   from the start, or `Offset::FromEnd(n)` (`FromEnd(1)` is the last byte)
+/// - `new_byte_value`: The new byte value to write at the specified position
+///
+/// # Returns
+/// - `Ok(())` on successful byte replacement
+/// - `Err(io::Error)` if file operations fail or position is invalid
+///
+/// # Error Conditions
+/// - File does not exist
+/// - Byte position exceeds file length
+/// - Insufficient permissions
+/// - Disk full
+/// - I/O errors during read/write
+///
+/// # Recovery Behavior
+/// - If operation fails before replacing original, draft is removed, backup remains
+/// - If operation fails during replacement, backup file is preserved for manual recovery
+/// - Orphaned .draft files indicate incomplete operations
+/// - Orphaned .backup files indicate failed replacements
+///
+/// # Edge Cases
+/// - Empty file: Returns error (no bytes to edit)
+/// - Position equals file length: Returns error (position out of bounds)
+/// - Position > file length: Returns error (position out of bounds)
+/// - Single byte file: Replaces that byte if position is 0
+/// - Same byte value: Completes operation (idempotent)
+/// - Very large files: Processes in chunks, no memory issues
+///
+/// # Example
+/// ```no_run
+/// # use std::io;
+/// # use std::path::PathBuf;
+/// # fn replace_single_byte_in_file(path: PathBuf, pos: u64, byte: u8) -> io::Result<()> { Ok(()) }
+/// let file_path = PathBuf::from("/absolute/path/to/file.dat");
+/// let position = 1024; // Replace byte at position 1024
+/// let new_byte = 0xFF; // Replace with 0xFF
+/// let result = replace_single_byte_in_file(file_path, position, new_byte);
+/// assert!(result.is_ok());
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn replace_single_byte_in_file(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+
+    // =========================================
+    // Input Validation Phase
+    // =========================================
+    report(&ReportEvent::OperationStarted {
+        operation_name: "In-Place Byte Replacement",
+        target_path: &original_file_path,
+    });
+    report_detail!("Byte position: {}", byte_offset);
+    report_detail!("New byte value: 0x{:02X}", new_byte_value);
+
+    // A symlinked target is resolved or refused before the path is used
+    let config = active_operation_config();
+    let original_file_path = resolve_symlinked_target(&original_file_path, &config)?;
+
+    // Verify file exists before any operations
+    if !original_file_path.exists() {
+        let error = ByteOpError::target_not_found(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Verify file is actually a file, not a directory
+    if !original_file_path.is_file() {
+        let error = ByteOpError::not_a_file(&original_file_path);
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Get original file metadata for validation
+    let original_metadata = fs::metadata(&original_file_path)?;
+    let original_file_size = original_metadata.len();
+
+    // Resolve an end-relative offset against the size just read
+    let byte_position_from_start = match byte_offset.resolve(original_file_size) {
+        Ok(position) => position,
+        Err(error) => {
+            report_problem!("ERROR: {}", error);
+            return Err(error);
+        }
+    };
+    if let Offset::FromEnd(_) = byte_offset {
+        report_detail!("Resolved position: {}", byte_position_from_start);
+    }
+
+    // Validate byte position is within file bounds
+    if byte_position_from_start >= original_file_size {
+        let error = ByteOpError::PositionOutOfBounds {
+            position: byte_position_from_start,
+            file_size: original_file_size,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // Handle empty file case
+    if original_file_size == 0 {
+        let error = ByteOpError::EmptyFile;
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    // =========================================
+    // Path Construction Phase
+    // =========================================
+
+    // Build backup and draft file paths
+    check_original_preconditions(&original_file_path, &config)?;
+    let artifact_paths = build_artifact_paths(&original_file_path)?;
+    check_target_writable(&original_file_path)?;
+    let _operation_lock = OperationLock::acquire(&original_file_path)?;
+    check_artifact_overwrite(&original_file_path, &artifact_paths, true)?;
+    let mut journal = OperationJournal::begin(
+        &original_file_path,
+        "In-Place Byte Replacement",
+        &artifact_paths,
+        true,
+        &config,
+    )?;
+    let DraftArtifactPaths {
+        backup_file_path,
+        draft_file_path,
+        ..
+    } = artifact_paths;
+    report_detail!("Backup path: {}", backup_file_path.display());
+    report_detail!("Draft path: {}", draft_file_path.display());
+
+    // =========================================
+    // Backup Creation Phase
+    // =========================================
+    report_phase(OperationPhase::Backup);
+    clone_or_copy(&original_file_path, &backup_file_path).map_err(|source| {
+        let error = ByteOpError::BackupFailed { source };
+        report_problem!("ERROR: {}", error);
+        io::Error::from(error)
+    })?;
+    record_backup_manifest(
+        &backup_file_path,
+        &original_file_path,
+        "In-Place Byte Replacement",
+        &config,
+    )?;
+    report_detail!("Backup created successfully");
+    journal.mark(JournalPhase::BackedUp)?;
+
+    // =========================================
+    // Draft File Construction Phase
+    // =========================================
+    report_phase(OperationPhase::Draft);
+    report_detail!("Building modified draft file...");
+
+    // Open original for reading
+    let mut source_file = open_buffered_reader(&original_file_path)?;
+
+    // Create draft file for writing
+    let mut draft_file = create_buffered_draft(&draft_file_path)?;
+
+    // Buffer for bucket brigade operations, allocated once per operation.
+    // Only the first `bytes_read` bytes of it are ever written out, so it is
+    // not re-zeroed between chunks.
+    let bucket_brigade_buffer_size = config.buffer_size();
+    let mut bucket_brigade_buffer = vec![0u8; bucket_brigade_buffer_size];
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    // Debug build assertion
+    debug_assert!(
+        bucket_brigade_buffer_size > 0,
+        "Bucket brigade buffer must have non-zero size"
+    );
+
+    // Test build assertion
+    #[cfg(test)]
+    {
+        assert!(
+            bucket_brigade_buffer_size > 0,
+            "Bucket brigade buffer must have non-zero size"
+        );
+    }
+
+    // Production safety check and handle
+    if bucket_brigade_buffer_size == 0 {
+        // Clean up draft file on error
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid buffer configuration",
+        ));
+    }
+
+    // Tracking variables
+    let mut total_bytes_processed: u64 = 0;
+    let mut chunk_number: usize = 0;
+    let mut byte_was_replaced = false;
+
+    // Loop bound scaled to the file: see `chunk_limit_for`
+    let max_chunks_allowed = chunk_limit_for(original_file_size);
+
+    // =========================================
+    // Main Processing Loop
+    // =========================================
+
+    loop {
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        // Debug build assertion
+        debug_assert!(
+            chunk_number < max_chunks_allowed,
+            "Exceeded maximum chunk limit"
+        );
+
+        // Test build assertion
+        #[cfg(test)]
+        {
+            assert!(
+                chunk_number < max_chunks_allowed,
+                "Exceeded maximum chunk limit"
+            );
+        }
+
+        // Production safety check and handle
+        if chunk_number >= max_chunks_allowed {
+            report_problem!("ERROR: Maximum chunk limit exceeded for safety");
+            // Clean up files
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(ByteOpError::ChunkLimitExceeded {
+                chunk_limit: max_chunks_allowed,
+            }
+            .into());
+        }
+
+        chunk_number += 1;
+
+        // Read next chunk from source
+        let bytes_read = source_file.read(&mut bucket_brigade_buffer)?;
+
+        // EOF detection
+        if bytes_read == 0 {
+            report_detail!("Reached end of file");
+            break;
+        }
+        report(&ReportEvent::ChunkProcessed {
+            chunk_number,
+            byte_count: bytes_read,
+        });
+
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        // Debug build assertion
+        debug_assert!(
+            bytes_read <= bucket_brigade_buffer_size,
+            "Read more bytes than buffer size"
+        );
+
+        // Test build assertion
+        #[cfg(test)]
+        {
+            assert!(
+                bytes_read <= bucket_brigade_buffer_size,
+                "Read more bytes than buffer size"
+            );
+        }
+
+        // Production safety check and handle
+        if bytes_read > bucket_brigade_buffer_size {
+            report_problem!("ERROR: Buffer overflow detected");
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(io::Error::other("Buffer overflow in read operation"));
+        }
+
+        // Determine if target byte is in this chunk
+        let chunk_start_position = total_bytes_processed;
+        let chunk_end_position = chunk_start_position + bytes_read as u64;
+
+        // Check if we need to modify a byte in this chunk
+        if byte_position_from_start >= chunk_start_position
+            && byte_position_from_start < chunk_end_position
+        {
+            // Calculate position within this chunk (below `bytes_read`, so it fits)
+            let position_in_chunk = (byte_position_from_start - chunk_start_position) as usize;
+
+            // Store original byte for logging
+            let original_byte_value = bucket_brigade_buffer[position_in_chunk];
+
+            // Perform the byte replacement
+            bucket_brigade_buffer[position_in_chunk] = new_byte_value;
+            byte_was_replaced = true;
+            report_detail!(
+                "Replaced byte at position {}: 0x{:02X} -> 0x{:02X}",
+                byte_position_from_start,
+                original_byte_value,
+                new_byte_value
+            );
+        }
+
+        // Write chunk to draft file
+        let bytes_written = draft_file.write(&bucket_brigade_buffer[..bytes_read])?;
+
+        // =================================================
+        // Debug-Assert, Test-Assert, Production-Catch-Handle
+        // =================================================
+
+        // Debug build assertion
+        debug_assert_eq!(bytes_written, bytes_read, "Not all bytes were written");
+
+        // Test build assertion
+        #[cfg(test)]
+        {
+            assert_eq!(bytes_written, bytes_read, "Not all bytes were written");
+        }
+
+        // Production safety check and handle
+        if bytes_written != bytes_read {
+            report_problem!(
+                "ERROR: Write mismatch - expected {} bytes, wrote {} bytes",
+                bytes_read,
+                bytes_written
+            );
+            let _ = fs::remove_file(&draft_file_path);
+            return Err(ByteOpError::IncompleteWrite {
+                requested: bytes_read,
+                written: bytes_written,
+            }
+            .into());
+        }
+
+        total_bytes_processed += bytes_written as u64;
+    }
+
+    // =========================================
+    // Verification Phase
+    // =========================================
+    report_phase(OperationPhase::Verify);
+
+    // Verify byte was actually replaced
+    if !byte_was_replaced {
+        report_problem!("ERROR: Target byte position was never reached");
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(ByteOpError::EditNotApplied {
+            operation_name: "Byte replacement",
+        }
+        .into());
+    }
+
+    // Verify file sizes match
+    config.durability_mode().finish_draft(draft_file)?;
+    drop(source_file); // Ensure file is closed
+
+    let draft_metadata = fs::metadata(&draft_file_path)?;
+    let draft_size = draft_metadata.len();
+
+    // =========================================
+    // Comprehensive Verification Phase
+    // =========================================
+
+    // let mut original_check_file = File::open(&original_file_path)?; // THE ACTUAL ORIGINAL!
+    // original_check_file.seek(SeekFrom::Start(byte_position_from_start as u64))?;
+    // let mut byte_buffer = [0u8; 1];
+    // original_check_file.read_exact(&mut byte_buffer)?;
+    // let original_byte_at_position = byte_buffer[0];
+
+    // Read original byte for verification
+    /*
+    This ensures the file handle is closed before you try to rename.
+    The curly braces { } create a new scope. When that scope ends,
+    original_check_file is immediately dropped and the file handle is closed.
+    */
+    let original_byte_at_position = {
+        let mut original_check_file = File::open(&original_file_path)?;
+        original_check_file.seek(SeekFrom::Start(byte_position_from_start))?;
+        let mut byte_buffer = [0u8; 1];
+        original_check_file.read_exact(&mut byte_buffer)?;
+        byte_buffer[0]
+        // original_check_file automatically dropped here
+    };
+
+    // Perform all verification checks before replacing the original
+    if config.verification_level() == VerificationLevel::LengthOnly {
+        report_detail!("Verification level length-only: byte comparison skipped");
+    } else {
+        verify_byte_replacement_operation(
+            &original_file_path, // The actual original (still unmodified)
+            &draft_file_path,    // Modified (draft) file
+            byte_position_from_start,
+            original_byte_at_position,
+            new_byte_value,
+        )?;
+    }
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    // Debug build assertion
+    debug_assert_eq!(
+        draft_size, original_file_size,
+        "Draft file size doesn't match original"
+    );
+
+    // Test build assertion
+    #[cfg(test)]
+    {
+        assert_eq!(
+            draft_size, original_file_size,
+            "Draft file size doesn't match original"
+        );
+    }
+
+    // Production safety check and handle
+    if draft_size != original_file_size {
+        report_problem!(
+            "ERROR: File size mismatch - original: {} bytes, draft: {} bytes",
+            original_file_size,
+            draft_size
+        );
+        let _ = fs::remove_file(&draft_file_path);
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            original_file_size,
+            draft_size,
+        )
+        .into());
+    }
+    report_detail!("File size verified: {} bytes", draft_size);
+
+    check_draft_against_utf8_guard(
+        &original_file_path,
+        &draft_file_path,
+        "In-Place Byte Replacement",
+    )?;
+    seal_draft_with_canary(&original_file_path, &draft_file_path)?;
+    journal.mark_draft_verified(&draft_file_path)?;
+
+    // =========================================
+    // Atomic Replacement Phase
+    // =========================================
+    report_phase(OperationPhase::Replace);
+    release_cached_pages(&[&original_file_path, &backup_file_path, &draft_file_path]);
+    copy_original_metadata(&original_file_path, &draft_file_path, &config)?;
+
+    // Attempt atomic rename (most filesystems support this)
+    match replace_with_draft(&draft_file_path, &original_file_path) {
+        Ok(()) => {
+            report_detail!("Original file successfully replaced");
+            config
+                .durability_mode()
+                .finish_replace(&original_file_path)?;
+            journal.mark_replaced();
+        }
+        Err(source) => {
+            // DO NOT try to copy over the original!
+            // Leave all files as-is for safety
+            let error = ByteOpError::AtomicReplaceFailed { source };
+            report_problem!("{}", error);
+            return Err(error.into());
+        }
+    }
+
+    // =========================================
+    // Cleanup Phase
+    // =========================================
+    report_phase(OperationPhase::Cleanup);
+
+    // Only remove backup after successful replacement
+    if let Some(rotated_backup_count) = config.rotated_backup_count() {
+        rotate_backups(&backup_file_path, rotated_backup_count);
+    } else if config.keep_backup() {
+        report_backup_kept(&backup_file_path);
+    } else {
+        match fs::remove_file(&backup_file_path) {
+            Ok(()) => {
+                report_detail!("Backup file removed")
+            }
+            Err(e) => {
+                // Non-fatal: backup removal failure is not critical
+                report_problem!(
+                    "WARNING: Could not remove backup file: {} ({})",
+                    backup_file_path.display(),
+                    e
+                );
+                report_backup_kept(&backup_file_path);
+            }
+        }
+    }
+
+    // =========================================
+    // Operation Summary
+    // =========================================
+    report_detail!("Modified position: {}", byte_position_from_start);
+    report_detail!("New byte value: 0x{:02X}", new_byte_value);
+    report_detail!("Total bytes processed: {}", total_bytes_processed);
+    report_detail!("Total chunks: {}", chunk_number);
+    report(&ReportEvent::OperationFinished {
+        operation_name: "In-Place Byte Replacement",
+        target_path: &original_file_path,
+    });
+
+    Ok(())
+}
+
+/// Replaces a byte like `replace_single_byte_in_file`, returning an `OperationReport`.
+///
+/// Adds two SHA-256 passes (before and after) to the operation; see
+/// `operation_report.rs`.
+pub fn replace_single_byte_in_file_with_report(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+) -> io::Result<OperationReport> {
+    let byte_offset: Offset = byte_offset.into();
+    run_with_operation_report(
+        "In-Place Byte Replacement",
+        &original_file_path,
+        byte_offset,
+        true,
+        Some(new_byte_value),
+        || replace_single_byte_in_file(original_file_path.clone(), byte_offset, new_byte_value),
+    )
+}
+
+/// Replaces a byte like `replace_single_byte_in_file`, using `config` instead of the
+/// active (or default) `OperationConfig`.
+pub fn replace_single_byte_in_file_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    new_byte_value: u8,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    let byte_offset: Offset = byte_offset.into();
+    with_operation_config(config, || {
+        replace_single_byte_in_file(original_file_path, byte_offset, new_byte_value)
+    })
+}
+
+/// Compare-and-swap: replaces the byte at `byte_offset` only if it
+/// currently equals `expected_old_byte_value`.
+///
+/// The current byte is read before any backup or draft is created, so
+/// editing the wrong file (or the wrong version of it) fails fast and
+/// leaves nothing behind. The replacement itself is
+/// `replace_single_byte_in_file`, which verifies the draft as usual.
+///
+/// # Returns
+/// - `Ok(())` if the byte matched and was replaced
+/// - `Err(io::Error)` carrying `ByteOpError::UnexpectedByteValue`
+///   (`InvalidData`) if it did not; the file is untouched
+/// - `Err(io::Error)` for anything `replace_single_byte_in_file` rejects
+pub fn replace_byte_if(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    expected_old_byte_value: u8,
+    new_byte_value: u8,
+) -> io::Result<()> {
+    // =========================================
+    // Precondition Phase
+    // =========================================
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let byte_position_from_start = match byte_offset.into().resolve(original_file_size) {
+        Ok(position) => position,
+        Err(error) => {
+            report_problem!("ERROR: {}", error);
+            return Err(error);
+        }
+    };
+    if byte_position_from_start >= original_file_size {
+        let error = ByteOpError::PositionOutOfBounds {
+            position: byte_position_from_start,
+            file_size: original_file_size,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+
+    let current_byte_value = {
+        let mut original_check_file = File::open(&original_file_path)?;
+        original_check_file.seek(SeekFrom::Start(byte_position_from_start))?;
+        let mut byte_buffer = [0u8; 1];
+        original_check_file.read_exact(&mut byte_buffer)?;
+        byte_buffer[0]
+    };
+    if current_byte_value != expected_old_byte_value {
+        let error = ByteOpError::UnexpectedByteValue {
+            position: byte_position_from_start,
+            expected: expected_old_byte_value,
+            actual: current_byte_value,
+        };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+    report_detail!(
+        "Precondition passed: byte {} is 0x{:02X}",
+        byte_position_from_start,
+        current_byte_value
+    );
+
+    replace_single_byte_in_file(original_file_path, byte_position_from_start, new_byte_value)
+}
+
+/// Like `replace_byte_if`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn replace_byte_if_with_config(
+    original_file_path: PathBuf,
+    byte_offset: impl Into<Offset>,
+    expected_old_byte_value: u8,
+    new_byte_value: u8,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        replace_byte_if(
+            original_file_path,
+            byte_offset,
+            expected_old_byte_value,
+            new_byte_value,
+        )
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    // use std::io::Write;
+
+    #[test]
+    fn test_replace_single_byte_basic() {
+        // Create test file
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_byte_replace.bin");
+
+        // Write test data
+        let test_data = vec![0x00, 0x11, 0x22, 0x33, 0x44];
+        std::fs::write(&test_file, &test_data).expect("Failed to create test file");
+
+        // Replace byte at position 2 (0x22) with 0xFF
+        let result = replace_single_byte_in_file(test_file.clone(), 2, 0xFF);
+
+        assert!(result.is_ok(), "Operation should succeed");
+
+        // Verify result
+        let modified_data = std::fs::read(&test_file).expect("Failed to read modified file");
+        assert_eq!(modified_data, vec![0x00, 0x11, 0xFF, 0x33, 0x44]);
+
+        // Cleanup
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_byte_from_end() {
+        let test_file = std::env::temp_dir().join("test_replace_byte_from_end.bin");
+        std::fs::write(&test_file, [0x00, 0x11, 0x22, 0x33, 0x44]).expect("create test file");
+
+        let report =
+            replace_single_byte_in_file_with_report(test_file.clone(), Offset::FromEnd(4), 0xFF)
+                .expect("4th byte from the end should be replaced");
+        assert_eq!(report.byte_position, 1);
+        assert_eq!(report.old_byte_value, Some(0x11));
+        assert_eq!(
+            std::fs::read(&test_file).expect("read modified file"),
+            [0x00, 0xFF, 0x22, 0x33, 0x44]
+        );
+
+        // FromEnd(0) is EOF, which has no byte to replace
+        assert!(replace_single_byte_in_file(test_file.clone(), Offset::FromEnd(0), 0xFF).is_err());
+        let error = replace_single_byte_in_file(test_file.clone(), Offset::FromEnd(6), 0xFF)
+            .expect_err("offset before the first byte");
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::OffsetBeforeStart { .. })
+        ));
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_byte_position_out_of_bounds() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_byte_bounds.bin");
+
+        // Create small file
+        std::fs::write(&test_file, vec![0x00, 0x11]).expect("Failed to create test file");
+
+        // Try to replace byte at invalid position
+        let result = replace_single_byte_in_file(
+            test_file.clone(),
+            10, // Position beyond file size
+            0xFF,
+        );
+
+        assert!(result.is_err(), "Should fail with out of bounds position");
+
+        // Cleanup
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_byte_empty_file() {
+        let test_dir = std::env::temp_dir();
+        let test_file = test_dir.join("test_empty.bin");
+
+        // Create empty file
+        File::create(&test_file).expect("Failed to create empty file");
+
+        // Try to replace byte in empty file
+        let result = replace_single_byte_in_file(test_file.clone(), 0, 0xFF);
+
+        assert!(result.is_err(), "Should fail with empty file");
+
+        // Cleanup
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_byte_if_checks_current_value() {
+        let test_file = std::env::temp_dir().join("test_replace_byte_if.bin");
+        std::fs::write(&test_file, [0x00, 0x11, 0x22]).expect("create test file");
+
+        let error = replace_byte_if(test_file.clone(), 1, 0x10, 0xFF).expect_err("wrong old byte");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::UnexpectedByteValue {
+                position: 1,
+                expected: 0x10,
+                actual: 0x11
+            })
+        ));
+        assert_eq!(
+            std::fs::read(&test_file).expect("read file"),
+            [0x00, 0x11, 0x22]
+        );
+        assert!(
+            !test_file
+                .with_file_name("test_replace_byte_if.bin.backup")
+                .exists()
+        );
+        assert!(
+            !test_file
+                .with_file_name("test_replace_byte_if.bin.draft")
+                .exists()
+        );
+
+        replace_byte_if(test_file.clone(), Offset::FromEnd(2), 0x11, 0xFF).expect("matching byte");
+        assert_eq!(
+            std::fs::read(&test_file).expect("read file"),
+            [0x00, 0xFF, 0x22]
+        );
+        assert!(replace_byte_if(test_file.clone(), 3, 0x00, 0xFF).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,453 @@
+//! Progress and diagnostic events from the library operations
+//!
+//! Operations do not print. They send `ReportEvent`s to the reporter that is
+//! active on the current thread, and the reporter decides what (if anything)
+//! to show. A host program installs its own with `with_reporter`:
+//!
+//! - `StdoutReporter`: every event, details to stdout, problems to stderr
+//! - `SilentReporter`: nothing
+//! - any type implementing `Reporter` (collect events, forward to a logger)
+//!
+//! With no reporter installed, debug builds behave like `StdoutReporter` and
+//! release builds show only `Problem` events (on stderr).
+//!
+//! Optional adapters forward events to a host's logging stack:
+//! - `LogReporter` (feature `log`): one `log` record per event
+//! - `TracingReporter` (feature `tracing`): a span per operation with a child
+//!   span per phase, and `tracing` events inside them
+//!
+//! Messages are passed as `fmt::Arguments`, so nothing is formatted unless
+//! the reporter actually renders it.
+
+use std::{cell::RefCell, fmt, path::Path, rc::Rc};
+
+/// Workflow phase an operation is entering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationPhase {
+    /// Copying the original to `<name>.backup`
+    Backup,
+    /// Building `<name>.draft` from the original
+    Draft,
+    /// Comparing the draft against the original
+    Verify,
+    /// Renaming the draft over the original
+    Replace,
+    /// Removing the backup
+    Cleanup,
+}
+
+impl OperationPhase {
+    /// Every phase, in workflow order.
+    pub const ALL: [OperationPhase; 5] = [
+        OperationPhase::Backup,
+        OperationPhase::Draft,
+        OperationPhase::Verify,
+        OperationPhase::Replace,
+        OperationPhase::Cleanup,
+    ];
+
+    /// Stable lower-case name, for log fields and span names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OperationPhase::Backup => "backup",
+            OperationPhase::Draft => "draft",
+            OperationPhase::Verify => "verify",
+            OperationPhase::Replace => "replace",
+            OperationPhase::Cleanup => "cleanup",
+        }
+    }
+}
+
+/// One event reported by an operation.
+#[derive(Debug, Clone, Copy)]
+pub enum ReportEvent<'a> {
+    /// An operation begins on `target_path`
+    OperationStarted {
+        operation_name: &'a str,
+        target_path: &'a Path,
+    },
+    /// The current operation enters `phase`
+    PhaseStarted { phase: OperationPhase },
+    /// One bucket-brigade chunk of the draft was written
+    ChunkProcessed {
+        chunk_number: usize,
+        byte_count: usize,
+    },
+    /// A verification step passed; `detail` says what was compared
+    VerificationPassed { detail: fmt::Arguments<'a> },
+    /// The current operation completed successfully
+    OperationFinished {
+        operation_name: &'a str,
+        target_path: &'a Path,
+    },
+    /// The operation's backup stays on disk at `backup_file_path`, as an
+    /// undo point (kept, rotated, or left behind by a failed removal)
+    BackupKept { backup_file_path: &'a Path },
+    /// Free-form progress detail
+    Detail { message: fmt::Arguments<'a> },
+    /// Something failed or looks wrong; the error (if any) is also returned
+    Problem { message: fmt::Arguments<'a> },
+}
+
+/// Receives the events of operations run on this thread.
+pub trait Reporter {
+    fn report(&self, event: &ReportEvent<'_>);
+}
+
+/// Prints every event: problems to stderr, everything else to stdout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutReporter;
+
+impl Reporter for StdoutReporter {
+    fn report(&self, event: &ReportEvent<'_>) {
+        match event {
+            ReportEvent::OperationStarted {
+                operation_name,
+                target_path,
+            } => {
+                println!("=== {} Operation ===", operation_name);
+                println!("Target file: {}", target_path.display());
+            }
+            ReportEvent::PhaseStarted { phase } => println!("-- {} phase --", phase.name()),
+            ReportEvent::ChunkProcessed {
+                chunk_number,
+                byte_count,
+            } => println!("Chunk {}: {} bytes", chunk_number, byte_count),
+            ReportEvent::VerificationPassed { detail } => println!("{}", detail),
+            ReportEvent::OperationFinished {
+                operation_name,
+                target_path,
+            } => println!(
+                "=== {} Complete: {} ===",
+                operation_name,
+                target_path.display()
+            ),
+            ReportEvent::BackupKept { backup_file_path } => {
+                println!("Backup kept at: {}", backup_file_path.display())
+            }
+            ReportEvent::Detail { message } => println!("{}", message),
+            ReportEvent::Problem { message } => eprintln!("{}", message),
+        }
+    }
+}
+
+/// Discards every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {
+    fn report(&self, _event: &ReportEvent<'_>) {}
+}
+
+/// Forwards events to the `log` facade (target: this module's path).
+///
+/// Started/finished operations and kept backups are `info`, problems are `warn`, phases,
+/// verification passes and details are `debug`, chunks are `trace`.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogReporter;
+
+#[cfg(feature = "log")]
+impl Reporter for LogReporter {
+    fn report(&self, event: &ReportEvent<'_>) {
+        match event {
+            ReportEvent::OperationStarted {
+                operation_name,
+                target_path,
+            } => log::info!("{} started: {}", operation_name, target_path.display()),
+            ReportEvent::PhaseStarted { phase } => log::debug!("phase: {}", phase.name()),
+            ReportEvent::ChunkProcessed {
+                chunk_number,
+                byte_count,
+            } => log::trace!("chunk {}: {} bytes", chunk_number, byte_count),
+            ReportEvent::VerificationPassed { detail } => log::debug!("{}", detail),
+            ReportEvent::OperationFinished {
+                operation_name,
+                target_path,
+            } => log::info!("{} finished: {}", operation_name, target_path.display()),
+            ReportEvent::BackupKept { backup_file_path } => {
+                log::info!("backup kept: {}", backup_file_path.display())
+            }
+            ReportEvent::Detail { message } => log::debug!("{}", message),
+            ReportEvent::Problem { message } => log::warn!("{}", message),
+        }
+    }
+}
+
+/// Forwards events to `tracing`, with spans for operations and phases.
+///
+/// `OperationStarted` opens an `operation` span (fields `name`, `target`);
+/// each `PhaseStarted` closes the previous `phase` span and opens a new one
+/// inside it (field `phase`: backup, draft, verify, replace, cleanup).
+/// `OperationFinished` closes both. An operation that fails has no finish
+/// event, so its spans stay open until the next operation starts or the
+/// reporter is dropped.
+///
+/// Spans are entered on the calling thread, like the reporter itself.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+pub struct TracingReporter {
+    operation_span: RefCell<Option<tracing::span::EnteredSpan>>,
+    phase_span: RefCell<Option<tracing::span::EnteredSpan>>,
+}
+
+#[cfg(feature = "tracing")]
+impl TracingReporter {
+    /// Exits the phase span first, then the operation span.
+    fn close_spans(&self) {
+        self.phase_span.borrow_mut().take();
+        self.operation_span.borrow_mut().take();
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for TracingReporter {
+    fn drop(&mut self) {
+        self.close_spans();
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Reporter for TracingReporter {
+    fn report(&self, event: &ReportEvent<'_>) {
+        match event {
+            ReportEvent::OperationStarted {
+                operation_name,
+                target_path,
+            } => {
+                self.close_spans();
+                let span = tracing::info_span!(
+                    "operation",
+                    name = *operation_name,
+                    target = %target_path.display()
+                );
+                *self.operation_span.borrow_mut() = Some(span.entered());
+            }
+            ReportEvent::PhaseStarted { phase } => {
+                self.phase_span.borrow_mut().take();
+                let span = tracing::info_span!("phase", phase = phase.name());
+                *self.phase_span.borrow_mut() = Some(span.entered());
+            }
+            ReportEvent::ChunkProcessed {
+                chunk_number,
+                byte_count,
+            } => tracing::trace!(chunk_number, byte_count, "chunk processed"),
+            ReportEvent::VerificationPassed { detail } => {
+                tracing::debug!(check = %detail, "verification passed")
+            }
+            ReportEvent::OperationFinished { .. } => {
+                tracing::info!("operation finished");
+                self.close_spans();
+            }
+            ReportEvent::BackupKept { backup_file_path } => {
+                tracing::info!(backup = %backup_file_path.display(), "backup kept")
+            }
+            ReportEvent::Detail { message } => tracing::debug!("{}", message),
+            ReportEvent::Problem { message } => tracing::warn!("{}", message),
+        }
+    }
+}
+
+thread_local! {
+    /// Reporters installed by `with_reporter` on this thread (innermost last)
+    static ACTIVE_REPORTERS: RefCell<Vec<Rc<dyn Reporter>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `operations` with `reporter` receiving all events on this thread.
+///
+/// Scopes nest; the innermost reporter wins. The reporter is removed when
+/// `operations` returns. Keep a clone of the `Rc` to read back anything
+/// the reporter collected.
+pub fn with_reporter<T>(reporter: Rc<dyn Reporter>, operations: impl FnOnce() -> T) -> T {
+    ACTIVE_REPORTERS.with(|reporters| reporters.borrow_mut().push(reporter));
+    let result = operations();
+    ACTIVE_REPORTERS.with(|reporters| {
+        reporters.borrow_mut().pop();
+    });
+    result
+}
+
+/// Sends `event` to the active reporter, or to the default behavior.
+pub fn report(event: &ReportEvent<'_>) {
+    report_to(active_reporter().as_ref(), event);
+}
+
+/// The innermost reporter installed on this thread, if any.
+///
+/// Cloned out of the cell so a reporter may itself run operations.
+pub(crate) fn active_reporter() -> Option<Rc<dyn Reporter>> {
+    ACTIVE_REPORTERS.with(|reporters| reporters.borrow().last().cloned())
+}
+
+/// Sends `event` to `reporter`, or to the default behavior if `None`.
+pub(crate) fn report_to(reporter: Option<&Rc<dyn Reporter>>, event: &ReportEvent<'_>) {
+    match reporter {
+        Some(reporter) => reporter.report(event),
+        None => {
+            if cfg!(debug_assertions) || matches!(event, ReportEvent::Problem { .. }) {
+                StdoutReporter.report(event);
+            }
+        }
+    }
+}
+
+/// Reports a `ReportEvent::Detail` built from `format!`-style arguments.
+macro_rules! report_detail {
+    ($($argument:tt)*) => {
+        $crate::reporter::report(&$crate::reporter::ReportEvent::Detail {
+            message: format_args!($($argument)*),
+        })
+    };
+}
+
+/// Reports a `ReportEvent::VerificationPassed` built from `format!`-style arguments.
+macro_rules! report_check_passed {
+    ($($argument:tt)*) => {
+        $crate::reporter::report(&$crate::reporter::ReportEvent::VerificationPassed {
+            detail: format_args!($($argument)*),
+        })
+    };
+}
+
+/// Reports a `ReportEvent::Problem` built from `format!`-style arguments.
+macro_rules! report_problem {
+    ($($argument:tt)*) => {
+        $crate::reporter::report(&$crate::reporter::ReportEvent::Problem {
+            message: format_args!($($argument)*),
+        })
+    };
+}
+
+pub(crate) use {report_check_passed, report_detail, report_problem};
+
+/// Reports that the current operation enters `phase`.
+pub(crate) fn report_phase(phase: OperationPhase) {
+    report(&ReportEvent::PhaseStarted { phase });
+}
+
+/// Reports that the current operation's backup stays on disk.
+pub(crate) fn report_backup_kept(backup_file_path: &Path) {
+    report(&ReportEvent::BackupKept { backup_file_path });
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keeps one line per event, for assertions
+    #[derive(Default)]
+    struct CollectingReporter {
+        lines: RefCell<Vec<String>>,
+    }
+
+    impl Reporter for CollectingReporter {
+        fn report(&self, event: &ReportEvent<'_>) {
+            let line = match event {
+                ReportEvent::PhaseStarted { phase } => format!("phase {}", phase.name()),
+                ReportEvent::VerificationPassed { .. } => "check".to_string(),
+                ReportEvent::Problem { message } => format!("problem {}", message),
+                _ => return,
+            };
+            self.lines.borrow_mut().push(line);
+        }
+    }
+
+    #[test]
+    fn test_reporter_receives_phases_and_silences_output() {
+        let test_file = std::env::temp_dir().join("test_reporter_events.bin");
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("create test file");
+
+        let collector = Rc::new(CollectingReporter::default());
+        let result = with_reporter(collector.clone(), || {
+            crate::replace_single_byte_in_file(test_file.clone(), 1, 0xFF)
+        });
+        assert!(result.is_ok());
+
+        let lines = collector.lines.borrow();
+        let phases: Vec<&str> = lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("phase "))
+            .collect();
+        assert_eq!(phases, ["backup", "draft", "verify", "replace", "cleanup"]);
+        assert!(lines.iter().any(|line| line == "check"));
+        drop(lines);
+
+        // Failures are reported as problems, and nested scopes win
+        let silent_result = with_reporter(collector.clone(), || {
+            with_reporter(Rc::new(SilentReporter), || {
+                crate::replace_single_byte_in_file(test_file.clone(), 99, 0xFF)
+            })
+        });
+        assert!(silent_result.is_err());
+        assert!(
+            !collector
+                .lines
+                .borrow()
+                .iter()
+                .any(|line| line.starts_with("problem"))
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_reporter_forwards_records() {
+        use std::sync::Mutex;
+
+        /// Keeps the message of every record from this crate
+        struct CapturingLogger {
+            messages: Mutex<Vec<String>>,
+        }
+
+        impl log::Log for CapturingLogger {
+            fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record<'_>) {
+                if record.target().starts_with("basic_file_byte_operations")
+                    && let Ok(mut messages) = self.messages.lock()
+                {
+                    messages.push(format!("{} {}", record.level(), record.args()));
+                }
+            }
+            fn flush(&self) {}
+        }
+
+        static LOGGER: CapturingLogger = CapturingLogger {
+            messages: Mutex::new(Vec::new()),
+        };
+        log::set_logger(&LOGGER).expect("install test logger");
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let test_file = std::env::temp_dir().join("test_log_reporter.bin");
+        std::fs::write(&test_file, vec![0x00, 0x11, 0x22]).expect("create test file");
+        let result = with_reporter(Rc::new(LogReporter), || {
+            crate::remove_single_byte_from_file(test_file.clone(), 0)
+        });
+        assert!(result.is_ok());
+
+        let messages = LOGGER.messages.lock().expect("logger lock");
+        assert!(
+            messages
+                .iter()
+                .any(|message| message.starts_with("INFO Byte Removal started"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|message| message == "DEBUG phase: verify")
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|message| message.starts_with("INFO Byte Removal finished"))
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
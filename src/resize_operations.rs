@@ -0,0 +1,380 @@
+//! End-of-file length changes
+//!
+//! Truncating or growing a file at its end is usually done in place
+//! (`File::set_len`, `OpenOptions::append`), which leaves no backup and
+//! nothing to verify against. These operations instead build the new file
+//! as a draft through the standard backup/draft/verify workflow, so a crash
+//! or failed check never leaves a half-resized original.
+
+use std::{fs::File, io, path::PathBuf};
+
+use crate::draft_workflow::{
+    copy_exact_bytes, copy_remaining_bytes, run_draft_workflow, validate_target_file,
+    verify_both_at_eof, verify_next_bytes_equal_slice, verify_next_bytes_follow_pattern,
+    verify_next_bytes_match, write_all_checked, write_repeating_pattern,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::reporter::report_check_passed;
+
+// ============================
+// Truncation
+// ============================
+
+/// Shortens a file to its first `new_len` bytes.
+///
+/// # Overview
+/// Only the first `new_len` bytes of the original are copied into the
+/// draft; the draft then replaces the original. Unlike `set_len`, the
+/// original stays intact until the shortened copy has been verified.
+///
+/// # Verification
+/// - Total length: draft == `new_len`
+/// - Prefix: draft[0..new_len] == original[0..new_len]
+/// - Draft is at EOF after the prefix
+///
+/// # Parameters
+/// - `original_file_path`: Path to the file to truncate
+/// - `new_len`: Length to keep (0..=file size)
+///
+/// # Returns
+/// - `Ok(())` on success (`new_len` == file size leaves the content unchanged)
+/// - `Err(io::Error)` (`InvalidInput`) if `new_len` exceeds the file size
+///   (use `pad_file_to` to grow); any workflow error otherwise
+pub fn truncate_file_at(original_file_path: PathBuf, new_len: u64) -> io::Result<()> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+    if new_len > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Truncation length {} exceeds file size {}",
+                new_len, original_file_size
+            ),
+        ));
+    }
+
+    run_draft_workflow(
+        &original_file_path,
+        "Truncation",
+        new_len,
+        |source_file, draft_file| copy_exact_bytes(source_file, draft_file, new_len),
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                new_len,
+                0,
+                0,
+                "Kept prefix",
+            )?;
+
+            let draft_trailing_count = io::copy(&mut draft_file, &mut io::sink())?;
+            if draft_trailing_count != 0 {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::TrailingBytes,
+                    0,
+                    0,
+                    draft_trailing_count,
+                )
+                .into());
+            }
+
+            report_check_passed!(
+                "   ✓ Draft is the first {} of {} original bytes",
+                new_len,
+                original_file_size
+            );
+            Ok(())
+        },
+    )
+}
+
+/// Like `truncate_file_at`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn truncate_file_at_with_config(
+    original_file_path: PathBuf,
+    new_len: u64,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || truncate_file_at(original_file_path, new_len))
+}
+
+// ============================
+// Padding
+// ============================
+
+/// How far `pad_file_to` grows a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadTarget {
+    /// Grow to exactly this many bytes
+    Length(u64),
+    /// Grow to the next multiple of this many bytes (e.g. 512 for sectors)
+    Alignment(u64),
+}
+
+/// Appends `fill_byte` until the file reaches the target length or alignment.
+///
+/// # Overview
+/// Used for sector-aligned images and fixed-size firmware slots. The
+/// original is copied into the draft unchanged, followed by the padding.
+///
+/// # Verification
+/// - Total length: draft == target length
+/// - Prefix: draft[0..original size] == original (exact)
+/// - Padding: every byte after the original content equals `fill_byte`
+///
+/// # Parameters
+/// - `original_file_path`: Path to the file to pad
+/// - `pad_target`: `Length(n)` (n >= file size) or `Alignment(n)` (n > 0)
+/// - `fill_byte`: Padding value
+///
+/// # Returns
+/// - `Ok(u64)`: the new file length (unchanged if already at the target)
+/// - `Err(io::Error)` (`InvalidInput`) if the target length is below the
+///   file size, the alignment is zero, or the target overflows; any
+///   workflow error otherwise
+pub fn pad_file_to(
+    original_file_path: PathBuf,
+    pad_target: PadTarget,
+    fill_byte: u8,
+) -> io::Result<u64> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    let target_len = match pad_target {
+        PadTarget::Length(target_len) => {
+            if target_len < original_file_size {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Target length {} is below file size {} (use truncate_file_at)",
+                        target_len, original_file_size
+                    ),
+                ));
+            }
+            target_len
+        }
+        PadTarget::Alignment(0) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Alignment must be greater than zero",
+            ));
+        }
+        PadTarget::Alignment(alignment) => original_file_size
+            .checked_next_multiple_of(alignment)
+            .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Aligned length overflows u64")
+        })?,
+    };
+    let padding_byte_count = target_len - original_file_size;
+
+    run_draft_workflow(
+        &original_file_path,
+        "Padding",
+        target_len,
+        |source_file, draft_file| {
+            copy_remaining_bytes(source_file, draft_file)?;
+            write_repeating_pattern(draft_file, &[fill_byte], padding_byte_count)
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                original_file_size,
+                0,
+                0,
+                "Original content",
+            )?;
+            verify_next_bytes_follow_pattern(
+                &mut draft_file,
+                &[fill_byte],
+                padding_byte_count,
+                original_file_size,
+                "Padding",
+            )?;
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )?;
+
+    Ok(target_len)
+}
+
+/// Like `pad_file_to`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn pad_file_to_with_config(
+    original_file_path: PathBuf,
+    pad_target: PadTarget,
+    fill_byte: u8,
+    config: &OperationConfig,
+) -> io::Result<u64> {
+    with_operation_config(config, || {
+        pad_file_to(original_file_path, pad_target, fill_byte)
+    })
+}
+
+// ============================
+// Appending
+// ============================
+
+/// Appends `new_bytes` to the end of a file.
+///
+/// # Overview
+/// The verified alternative to `OpenOptions::append`: the original is
+/// copied into the draft, followed by `new_bytes`, and the draft replaces
+/// the original only after verification. An interrupted append therefore
+/// never leaves a partially written tail on the original.
+///
+/// # Verification
+/// - Total length: draft == original + `new_bytes.len()`
+/// - Prefix: draft[0..original size] == original (exact)
+/// - Tail: draft[original size..] == `new_bytes`
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if `new_bytes` is empty or any phase fails
+pub fn append_bytes_to_file(original_file_path: PathBuf, new_bytes: &[u8]) -> io::Result<()> {
+    if new_bytes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Append payload must be non-empty",
+        ));
+    }
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let new_file_size = original_file_size
+        .checked_add(new_bytes.len() as u64)
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Appended length overflows u64")
+        })?;
+
+    run_draft_workflow(
+        &original_file_path,
+        "Append",
+        new_file_size,
+        |source_file, draft_file| {
+            copy_remaining_bytes(source_file, draft_file)?;
+            write_all_checked(draft_file, new_bytes)
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                original_file_size,
+                0,
+                0,
+                "Original content",
+            )?;
+            verify_next_bytes_equal_slice(
+                &mut draft_file,
+                new_bytes,
+                original_file_size,
+                "Appended bytes",
+            )?;
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )
+}
+
+/// Like `append_bytes_to_file`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn append_bytes_to_file_with_config(
+    original_file_path: PathBuf,
+    new_bytes: &[u8],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        append_bytes_to_file(original_file_path, new_bytes)
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_file_at() {
+        let test_file = std::env::temp_dir().join("test_truncate_file_at.bin");
+        let original_bytes: Vec<u8> = (0u8..150).collect();
+        std::fs::write(&test_file, &original_bytes).expect("create test file");
+
+        truncate_file_at(test_file.clone(), 150).expect("same length");
+        truncate_file_at(test_file.clone(), 70).expect("truncate across a chunk");
+        assert_eq!(
+            std::fs::read(&test_file).expect("read truncated file"),
+            original_bytes[..70].to_vec()
+        );
+
+        assert!(truncate_file_at(test_file.clone(), 71).is_err());
+
+        truncate_file_at(test_file.clone(), 0).expect("truncate to empty");
+        assert!(
+            std::fs::read(&test_file)
+                .expect("read empty file")
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_append_bytes_to_file() {
+        let test_file = std::env::temp_dir().join("test_append_bytes_to_file.bin");
+        std::fs::write(&test_file, b"").expect("create empty test file");
+
+        append_bytes_to_file(test_file.clone(), b"head").expect("append to empty file");
+        let long_tail: Vec<u8> = (0u8..100).collect();
+        append_bytes_to_file(test_file.clone(), &long_tail).expect("append across chunks");
+
+        let mut expected_bytes = b"head".to_vec();
+        expected_bytes.extend_from_slice(&long_tail);
+        assert_eq!(
+            std::fs::read(&test_file).expect("read appended file"),
+            expected_bytes
+        );
+        assert!(append_bytes_to_file(test_file.clone(), &[]).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_pad_file_to_length_and_alignment() {
+        let test_file = std::env::temp_dir().join("test_pad_file_to.bin");
+        std::fs::write(&test_file, [1, 2, 3]).expect("create test file");
+
+        assert_eq!(
+            pad_file_to(test_file.clone(), PadTarget::Alignment(4), 0xFF).expect("align"),
+            4
+        );
+        assert_eq!(
+            pad_file_to(test_file.clone(), PadTarget::Alignment(4), 0xFF).expect("aligned"),
+            4
+        );
+        assert_eq!(
+            pad_file_to(test_file.clone(), PadTarget::Length(100), 0x00).expect("length"),
+            100
+        );
+
+        let mut expected_bytes = vec![1, 2, 3, 0xFF];
+        expected_bytes.resize(100, 0x00);
+        assert_eq!(
+            std::fs::read(&test_file).expect("read padded file"),
+            expected_bytes
+        );
+
+        assert!(pad_file_to(test_file.clone(), PadTarget::Length(99), 0).is_err());
+        assert!(pad_file_to(test_file.clone(), PadTarget::Alignment(0), 0).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
@@ -0,0 +1,271 @@
+//! Sector-aligned editing for forensic images
+//!
+//! Forensic workflows require that any change to an evidence image be
+//! documented sector by sector. `replace_ranges_sector_aligned` applies
+//! same-length replacements (one draft, like
+//! `replace_multiple_byte_ranges_in_file`) while treating the image as a
+//! sequence of fixed-size sectors:
+//!
+//! - Every edit is mapped to the sectors it touches. An edit crossing a
+//!   sector boundary is rejected, or, with `SectorStraddlePolicy::Expand`,
+//!   its footprint is widened to all sectors it touches.
+//! - For every touched sector, SHA-256 of the sector is recorded before
+//!   (from the original) and after (from the verified draft), inside the
+//!   verification phase, so both hashes describe exactly the committed files.
+//!
+//! A trailing partial sector (image size not a multiple of the sector size)
+//! is hashed over the bytes it has.
+
+use std::{collections::BTreeSet, io, path::PathBuf};
+
+use crate::draft_workflow::{run_draft_workflow, validate_target_file};
+use crate::hash::sha256::{digest_to_hex, sha256_of_file_range};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::range_operations::{
+    build_multi_replacement_draft, validate_range_replacements, verify_multi_replacement,
+};
+use crate::util::chunk_length;
+
+/// What to do with an edit that crosses a sector boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectorStraddlePolicy {
+    /// Refuse the whole edit set; nothing is written
+    Reject,
+    /// Allow it; every sector it touches is hashed and reported
+    Expand,
+}
+
+/// Hashes of one touched sector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectorHashRecord {
+    pub sector_index: u64,
+    pub byte_offset: u64,
+    /// Sector size, or less for a trailing partial sector
+    pub byte_length: usize,
+    pub pre_edit_sha256: [u8; 32],
+    pub post_edit_sha256: [u8; 32],
+}
+
+impl SectorHashRecord {
+    /// `sector<TAB>offset<TAB>length<TAB>pre<TAB>post`, hashes as hex.
+    pub fn format_as_line(&self) -> String {
+        format!(
+            "{}\t0x{:X}\t{}\t{}\t{}",
+            self.sector_index,
+            self.byte_offset,
+            self.byte_length,
+            digest_to_hex(&self.pre_edit_sha256),
+            digest_to_hex(&self.post_edit_sha256)
+        )
+    }
+}
+
+/// Result of a sector-aligned edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectorEditReport {
+    pub sector_size: usize,
+    /// One record per touched sector, ascending
+    pub sector_records: Vec<SectorHashRecord>,
+}
+
+/// Validates edits against the sector grid and returns the touched sectors.
+///
+/// Usable on its own for dry runs.
+///
+/// # Returns
+/// - `Ok(Vec<u64>)`: touched sector indices, ascending, deduplicated
+/// - `Err(io::Error)` if the sector size is 0, the edits are invalid (see
+///   `validate_range_replacements`), or an edit straddles a sector boundary
+///   under `SectorStraddlePolicy::Reject`
+pub fn plan_sector_edits(
+    original_file_size: u64,
+    sector_size: usize,
+    replacements: &[(u64, Vec<u8>)],
+    straddle_policy: SectorStraddlePolicy,
+) -> io::Result<Vec<u64>> {
+    if sector_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Sector size must be greater than 0",
+        ));
+    }
+
+    let sorted_replacements = validate_range_replacements(replacements, original_file_size)?;
+
+    let sector_length = sector_size as u64;
+    let mut touched_sectors: BTreeSet<u64> = BTreeSet::new();
+    for (position, new_bytes) in &sorted_replacements {
+        let first_sector = position / sector_length;
+        let last_sector = (position + new_bytes.len() as u64 - 1) / sector_length;
+        if first_sector != last_sector && straddle_policy == SectorStraddlePolicy::Reject {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Edit at {} ({} bytes) straddles sectors {}..={} (sector size {})",
+                    position,
+                    new_bytes.len(),
+                    first_sector,
+                    last_sector,
+                    sector_size
+                ),
+            ));
+        }
+        touched_sectors.extend(first_sector..=last_sector);
+    }
+
+    Ok(touched_sectors.into_iter().collect())
+}
+
+/// Applies same-length replacements in one draft, with per-sector hashes.
+///
+/// # Parameters
+/// - `original_file_path`: Image to modify
+/// - `sector_size`: Sector size in bytes (e.g. 512 or 4096)
+/// - `replacements`: `(position, new_bytes)` pairs, non-overlapping
+/// - `straddle_policy`: Reject or expand edits crossing sector boundaries
+///
+/// # Returns
+/// - `Ok(SectorEditReport)` after the edit is committed
+/// - `Err(io::Error)` if planning or any workflow phase fails; the image is
+///   untouched in that case
+pub fn replace_ranges_sector_aligned(
+    original_file_path: PathBuf,
+    sector_size: usize,
+    replacements: &[(u64, Vec<u8>)],
+    straddle_policy: SectorStraddlePolicy,
+) -> io::Result<SectorEditReport> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let touched_sectors = plan_sector_edits(
+        original_file_size,
+        sector_size,
+        replacements,
+        straddle_policy,
+    )?;
+    let sorted_replacements = validate_range_replacements(replacements, original_file_size)?;
+
+    let mut sector_records: Vec<SectorHashRecord> = Vec::with_capacity(touched_sectors.len());
+
+    run_draft_workflow(
+        &original_file_path,
+        "Sector-Aligned Replacement",
+        original_file_size,
+        |source_file, draft_file| {
+            build_multi_replacement_draft(source_file, draft_file, &sorted_replacements)
+        },
+        |original_path, draft_path| {
+            verify_multi_replacement(
+                original_path,
+                draft_path,
+                &sorted_replacements,
+                original_file_size,
+            )?;
+
+            for &sector_index in &touched_sectors {
+                let byte_offset = sector_index * sector_size as u64;
+                let byte_length = chunk_length(sector_size, original_file_size - byte_offset);
+                let hashed_length = byte_length as u64;
+                sector_records.push(SectorHashRecord {
+                    sector_index,
+                    byte_offset,
+                    byte_length,
+                    pre_edit_sha256: sha256_of_file_range(
+                        original_path,
+                        byte_offset,
+                        hashed_length,
+                    )?,
+                    post_edit_sha256: sha256_of_file_range(draft_path, byte_offset, hashed_length)?,
+                });
+            }
+            Ok(())
+        },
+    )?;
+
+    Ok(SectorEditReport {
+        sector_size,
+        sector_records,
+    })
+}
+
+/// Like `replace_ranges_sector_aligned`, using `config` instead of the active
+/// (or default) `OperationConfig`.
+pub fn replace_ranges_sector_aligned_with_config(
+    original_file_path: PathBuf,
+    sector_size: usize,
+    replacements: &[(u64, Vec<u8>)],
+    straddle_policy: SectorStraddlePolicy,
+    config: &OperationConfig,
+) -> io::Result<SectorEditReport> {
+    with_operation_config(config, || {
+        replace_ranges_sector_aligned(
+            original_file_path,
+            sector_size,
+            replacements,
+            straddle_policy,
+        )
+    })
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256::sha256_of_bytes;
+
+    #[test]
+    fn test_sector_aligned_edit_records_hashes() {
+        let test_file = std::env::temp_dir().join("test_sector_aligned_edit.img");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(1100).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        let edits = vec![(10, vec![0xAA; 4]), (1030, vec![0xBB; 2])];
+        let report = replace_ranges_sector_aligned(
+            test_file.clone(),
+            512,
+            &edits,
+            SectorStraddlePolicy::Reject,
+        )
+        .expect("edit should succeed");
+
+        let modified_data = std::fs::read(&test_file).expect("read modified file");
+        assert_eq!(modified_data[10..14], [0xAA; 4]);
+
+        let touched: Vec<u64> = report
+            .sector_records
+            .iter()
+            .map(|record| record.sector_index)
+            .collect();
+        assert_eq!(touched, vec![0, 2]);
+
+        let last_record = &report.sector_records[1];
+        assert_eq!(last_record.byte_length, 1100 - 1024);
+        assert_eq!(
+            last_record.pre_edit_sha256,
+            sha256_of_bytes(&test_data[1024..])
+        );
+        assert_eq!(
+            last_record.post_edit_sha256,
+            sha256_of_bytes(&modified_data[1024..])
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_straddling_edit_rejected_or_expanded() {
+        let straddling_edit = vec![(510, vec![0x00; 4])];
+        assert!(
+            plan_sector_edits(1024, 512, &straddling_edit, SectorStraddlePolicy::Reject).is_err()
+        );
+        assert_eq!(
+            plan_sector_edits(1024, 512, &straddling_edit, SectorStraddlePolicy::Expand)
+                .expect("expand should succeed"),
+            vec![0, 1]
+        );
+        assert!(
+            plan_sector_edits(1024, 0, &straddling_edit, SectorStraddlePolicy::Expand).is_err()
+        );
+    }
+}
@@ -0,0 +1,666 @@
+//! Soak testing (feature `soak-test`)
+//!
+//! A one-command confidence test for a new platform or filesystem: run
+//! randomized operations against scratch files for a while and compare the
+//! file after every step with an in-memory reference model (a `Vec<u8>` to
+//! which the same edit is applied with plain slice operations).
+//!
+//! Everything is derived from one seed: operation kinds, positions, sizes,
+//! payloads, and the strategy used for edits that several functions can
+//! perform (e.g. a one-byte replace via `replace_single_byte_in_file`,
+//! `replace_byte_range_in_file`, or `splice_byte_range`). On the first
+//! divergence the seed and iteration are written to `soak-failure.txt` in
+//! the scratch directory and returned in the error, and the scratch files
+//! are kept; `--seed S --iterations N` replays the run exactly.
+//!
+//! The bucket-brigade buffer size is drawn per operation as well, from
+//! sizes that put chunk boundaries everywhere (1 and 7 bytes) to the usual
+//! ones, so random range sizes and positions meet every alignment.
+//!
+//! ```text
+//! cargo run --features soak-test -- soak-test --seconds 600 [--seed N] [--dir DIR]
+//! ```
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::draft_workflow::DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE;
+use crate::operation_config::{active_operation_config, with_operation_config};
+use crate::range_operations::{
+    RotationDirection, copy_byte_range_within_file, fill_range_with_pattern,
+    insert_bytes_into_file, remove_byte_range_from_file, replace_byte_range_in_file,
+    rotate_byte_range, splice_byte_range, swap_byte_ranges,
+};
+use crate::reporter::report_problem;
+use crate::transform_operations::{ByteRangeTransform, byteswap_range, transform_byte_range};
+use crate::{add_single_byte_to_file, remove_single_byte_from_file, replace_single_byte_in_file};
+
+/// Scratch files operated on in turn
+const SOAK_SCRATCH_FILE_COUNT: usize = 3;
+/// Hard cap on iterations, even for long durations
+const SOAK_ITERATION_LIMIT: usize = 100_000_000;
+/// Largest payload or range generated for one operation
+const SOAK_MAX_OPERATION_BYTES: usize = 300;
+/// Divergence record written to the scratch directory
+pub const SOAK_FAILURE_FILE_NAME: &str = "soak-failure.txt";
+/// Bucket-brigade buffer sizes an operation may run with
+const SOAK_BUFFER_SIZES: [usize; 6] = [1, 7, 64, 512, 4096, DEFAULT_BUCKET_BRIGADE_BUFFER_SIZE];
+
+/// What to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoakConfig {
+    pub seed: u64,
+    pub duration: Duration,
+    /// Stop after this many operations even if time remains
+    pub max_iterations: Option<usize>,
+    pub scratch_directory: PathBuf,
+    /// Scratch files are steered to stay below this size
+    pub max_file_size: usize,
+}
+
+/// Outcome of a run without divergence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoakSummary {
+    pub seed: u64,
+    pub iterations: usize,
+    pub elapsed: Duration,
+}
+
+/// One randomized edit, applicable to a file and to the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SoakOperation {
+    /// `strategy` picks single-byte, range, or splice implementation
+    ReplaceByte {
+        position: usize,
+        value: u8,
+        strategy: u8,
+    },
+    InsertByte {
+        position: usize,
+        value: u8,
+        strategy: u8,
+    },
+    RemoveByte {
+        position: usize,
+        strategy: u8,
+    },
+    ReplaceRange {
+        start: usize,
+        bytes: Vec<u8>,
+    },
+    InsertRange {
+        start: usize,
+        bytes: Vec<u8>,
+    },
+    RemoveRange {
+        start: usize,
+        length: usize,
+    },
+    Splice {
+        start: usize,
+        remove_length: usize,
+        bytes: Vec<u8>,
+    },
+    CopyWithin {
+        source: usize,
+        length: usize,
+        destination: usize,
+    },
+    Swap {
+        first: usize,
+        first_length: usize,
+        second: usize,
+        second_length: usize,
+    },
+    Rotate {
+        start: usize,
+        length: usize,
+        rotate_by: usize,
+        rotate_left: bool,
+    },
+    Fill {
+        start: usize,
+        length: usize,
+        pattern: Vec<u8>,
+    },
+    Xor {
+        start: usize,
+        length: usize,
+        key: u8,
+    },
+    ByteSwap {
+        start: usize,
+        length: usize,
+        word_size: usize,
+    },
+}
+
+/// SplitMix64: small, seedable, and reproducible across platforms.
+struct SoakRandom {
+    state: u64,
+}
+
+impl SoakRandom {
+    fn new(seed: u64) -> Self {
+        SoakRandom { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut word = self.state;
+        word = (word ^ (word >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        word = (word ^ (word >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        word ^ (word >> 31)
+    }
+
+    /// Uniform-enough value in `0..bound` (`bound` > 0).
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Value in `1..=maximum` (`maximum` > 0).
+    fn length_up_to(&mut self, maximum: usize) -> usize {
+        1 + self.below(maximum)
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next_u64() as u8
+    }
+
+    fn bytes(&mut self, count: usize) -> Vec<u8> {
+        (0..count).map(|_| self.byte()).collect()
+    }
+}
+
+/// Runs randomized operations until the duration or iteration cap is reached.
+///
+/// # Returns
+/// - `Ok(SoakSummary)` if file and model never diverged
+/// - `Err(io::Error)` on the first divergence (message includes seed and
+///   iteration; `soak-failure.txt` is written and scratch files are kept),
+///   or if the scratch directory cannot be prepared
+pub fn run_soak_test(config: &SoakConfig) -> io::Result<SoakSummary> {
+    std::fs::create_dir_all(&config.scratch_directory)?;
+    let mut random = SoakRandom::new(config.seed);
+
+    // Scratch files start at different sizes, including empty
+    let mut scratch_paths = Vec::with_capacity(SOAK_SCRATCH_FILE_COUNT);
+    let mut models = Vec::with_capacity(SOAK_SCRATCH_FILE_COUNT);
+    for file_index in 0..SOAK_SCRATCH_FILE_COUNT {
+        let scratch_path = config
+            .scratch_directory
+            .join(format!("soak-scratch-{}.bin", file_index));
+        let initial_size = config.max_file_size * file_index / SOAK_SCRATCH_FILE_COUNT;
+        let initial_bytes = random.bytes(initial_size);
+        std::fs::write(&scratch_path, &initial_bytes)?;
+        scratch_paths.push(scratch_path);
+        models.push(initial_bytes);
+    }
+
+    let iteration_limit = config
+        .max_iterations
+        .unwrap_or(SOAK_ITERATION_LIMIT)
+        .min(SOAK_ITERATION_LIMIT);
+    let start_time = Instant::now();
+    let mut iterations: usize = 0;
+
+    while iterations < iteration_limit && start_time.elapsed() < config.duration {
+        let file_index = random.below(SOAK_SCRATCH_FILE_COUNT);
+        let model = &mut models[file_index];
+        let operation = generate_operation(&mut random, model.len(), config.max_file_size);
+        let buffer_size = SOAK_BUFFER_SIZES[random.below(SOAK_BUFFER_SIZES.len())];
+
+        let file_result = with_operation_config(
+            &active_operation_config().with_buffer_size(buffer_size),
+            || apply_operation_to_file(&scratch_paths[file_index], &operation),
+        );
+        apply_operation_to_model(model, &operation);
+        let divergence = match file_result {
+            Err(error) => Some(format!("operation failed: {}", error)),
+            Ok(()) => {
+                let file_bytes = std::fs::read(&scratch_paths[file_index])?;
+                describe_difference(&file_bytes, model)
+            }
+        };
+
+        if let Some(detail) = divergence {
+            return Err(record_divergence(
+                config,
+                iterations,
+                &scratch_paths[file_index],
+                &operation,
+                buffer_size,
+                &detail,
+            ));
+        }
+        iterations += 1;
+    }
+
+    for scratch_path in &scratch_paths {
+        let _ = std::fs::remove_file(scratch_path);
+    }
+
+    Ok(SoakSummary {
+        seed: config.seed,
+        iterations,
+        elapsed: start_time.elapsed(),
+    })
+}
+
+/// Picks an operation that is valid for a file of `file_size` bytes.
+fn generate_operation(
+    random: &mut SoakRandom,
+    file_size: usize,
+    max_file_size: usize,
+) -> SoakOperation {
+    let growth_room = max_file_size.saturating_sub(file_size);
+    let max_growth = growth_room.min(SOAK_MAX_OPERATION_BYTES);
+
+    // Empty file: only insertions are valid
+    if file_size == 0 {
+        return SoakOperation::InsertRange {
+            start: 0,
+            bytes: {
+                let length = random.length_up_to(max_growth.max(1));
+                random.bytes(length)
+            },
+        };
+    }
+
+    // Kinds 0-2 grow the file; skip them when there is no room
+    let first_kind = if max_growth == 0 { 3 } else { 0 };
+    let strategy = random.below(3) as u8;
+    let max_range = file_size.min(SOAK_MAX_OPERATION_BYTES);
+
+    match first_kind + random.below(14 - first_kind) {
+        0 => SoakOperation::InsertByte {
+            position: random.below(file_size + 1),
+            value: random.byte(),
+            strategy,
+        },
+        1 => {
+            let length = random.length_up_to(max_growth);
+            SoakOperation::InsertRange {
+                start: random.below(file_size + 1),
+                bytes: random.bytes(length),
+            }
+        }
+        2 => {
+            let length = random.length_up_to(max_range.min(max_growth));
+            SoakOperation::CopyWithin {
+                source: random.below(file_size - length + 1),
+                length,
+                destination: random.below(file_size + 1),
+            }
+        }
+        3 | 4 => SoakOperation::ReplaceByte {
+            position: random.below(file_size),
+            value: random.byte(),
+            strategy,
+        },
+        5 => SoakOperation::RemoveByte {
+            position: random.below(file_size),
+            strategy,
+        },
+        6 => {
+            let length = random.length_up_to(max_range);
+            SoakOperation::ReplaceRange {
+                start: random.below(file_size - length + 1),
+                bytes: random.bytes(length),
+            }
+        }
+        7 => {
+            let length = random.length_up_to(max_range);
+            SoakOperation::RemoveRange {
+                start: random.below(file_size - length + 1),
+                length,
+            }
+        }
+        8 => {
+            let remove_length = random.below(max_range + 1);
+            // Growth by the splice may not exceed the room left
+            let new_length = random.below(remove_length + max_growth + 1);
+            let new_length = if remove_length == 0 {
+                new_length.max(1)
+            } else {
+                new_length
+            };
+            SoakOperation::Splice {
+                start: random.below(file_size - remove_length + 1),
+                remove_length,
+                bytes: random.bytes(new_length),
+            }
+        }
+        9 if file_size >= 2 => {
+            // Split the file at a pivot so the ranges cannot overlap
+            let pivot = 1 + random.below(file_size - 1);
+            let first_length = random.length_up_to(pivot.min(SOAK_MAX_OPERATION_BYTES));
+            let second_length =
+                random.length_up_to((file_size - pivot).min(SOAK_MAX_OPERATION_BYTES));
+            SoakOperation::Swap {
+                first: random.below(pivot - first_length + 1),
+                first_length,
+                second: pivot + random.below(file_size - pivot - second_length + 1),
+                second_length,
+            }
+        }
+        10 => {
+            let length = random.length_up_to(max_range);
+            SoakOperation::Rotate {
+                start: random.below(file_size - length + 1),
+                length,
+                rotate_by: random.below(2 * length + 1),
+                rotate_left: random.below(2) == 0,
+            }
+        }
+        11 => {
+            let length = random.length_up_to(max_range);
+            let pattern_length = random.length_up_to(8);
+            SoakOperation::Fill {
+                start: random.below(file_size - length + 1),
+                length,
+                pattern: random.bytes(pattern_length),
+            }
+        }
+        12 => {
+            let length = random.length_up_to(max_range);
+            SoakOperation::Xor {
+                start: random.below(file_size - length + 1),
+                length,
+                key: random.byte(),
+            }
+        }
+        _ => {
+            let word_size = [2, 4, 8][random.below(3)];
+            if file_size < word_size {
+                return SoakOperation::ReplaceByte {
+                    position: random.below(file_size),
+                    value: random.byte(),
+                    strategy,
+                };
+            }
+            let length = random.length_up_to(max_range / word_size) * word_size;
+            SoakOperation::ByteSwap {
+                start: random.below(file_size - length + 1),
+                length,
+                word_size,
+            }
+        }
+    }
+}
+
+/// Performs the operation with the engine.
+fn apply_operation_to_file(scratch_path: &Path, operation: &SoakOperation) -> io::Result<()> {
+    let path = scratch_path.to_path_buf();
+    match operation {
+        SoakOperation::ReplaceByte {
+            position,
+            value,
+            strategy,
+        } => match strategy {
+            0 => replace_single_byte_in_file(path, *position as u64, *value),
+            1 => replace_byte_range_in_file(path, *position as u64, &[*value]),
+            _ => splice_byte_range(path, *position as u64, 1, &[*value]),
+        },
+        SoakOperation::InsertByte {
+            position,
+            value,
+            strategy,
+        } => match strategy {
+            0 => add_single_byte_to_file(path, *position as u64, *value),
+            1 => insert_bytes_into_file(path, *position as u64, &[*value]),
+            _ => splice_byte_range(path, *position as u64, 0, &[*value]),
+        },
+        SoakOperation::RemoveByte { position, strategy } => match strategy {
+            0 => remove_single_byte_from_file(path, *position as u64),
+            1 => remove_byte_range_from_file(path, *position as u64, 1),
+            _ => splice_byte_range(path, *position as u64, 1, &[]),
+        },
+        SoakOperation::ReplaceRange { start, bytes } => {
+            replace_byte_range_in_file(path, *start as u64, bytes)
+        }
+        SoakOperation::InsertRange { start, bytes } => {
+            insert_bytes_into_file(path, *start as u64, bytes)
+        }
+        SoakOperation::RemoveRange { start, length } => {
+            remove_byte_range_from_file(path, *start as u64, *length as u64)
+        }
+        SoakOperation::Splice {
+            start,
+            remove_length,
+            bytes,
+        } => splice_byte_range(path, *start as u64, *remove_length as u64, bytes),
+        SoakOperation::CopyWithin {
+            source,
+            length,
+            destination,
+        } => copy_byte_range_within_file(path, *source as u64, *length as u64, *destination as u64),
+        SoakOperation::Swap {
+            first,
+            first_length,
+            second,
+            second_length,
+        } => swap_byte_ranges(
+            path,
+            *first as u64,
+            *first_length as u64,
+            *second as u64,
+            *second_length as u64,
+        ),
+        SoakOperation::Rotate {
+            start,
+            length,
+            rotate_by,
+            rotate_left,
+        } => {
+            let direction = if *rotate_left {
+                RotationDirection::Left
+            } else {
+                RotationDirection::Right
+            };
+            rotate_byte_range(
+                path,
+                *start as u64,
+                *length as u64,
+                *rotate_by as u64,
+                direction,
+            )
+        }
+        SoakOperation::Fill {
+            start,
+            length,
+            pattern,
+        } => fill_range_with_pattern(path, *start as u64, *length as u64, pattern),
+        SoakOperation::Xor { start, length, key } => transform_byte_range(
+            path,
+            *start as u64,
+            *length as u64,
+            ByteRangeTransform::XorWith(*key),
+        ),
+        SoakOperation::ByteSwap {
+            start,
+            length,
+            word_size,
+        } => byteswap_range(path, *start as u64, *length as u64, *word_size),
+    }
+}
+
+/// Performs the operation on the reference model with slice operations.
+fn apply_operation_to_model(model: &mut Vec<u8>, operation: &SoakOperation) {
+    match operation {
+        SoakOperation::ReplaceByte {
+            position, value, ..
+        } => model[*position] = *value,
+        SoakOperation::InsertByte {
+            position, value, ..
+        } => model.insert(*position, *value),
+        SoakOperation::RemoveByte { position, .. } => {
+            model.remove(*position);
+        }
+        SoakOperation::ReplaceRange { start, bytes } => {
+            model[*start..*start + bytes.len()].copy_from_slice(bytes)
+        }
+        SoakOperation::InsertRange { start, bytes } => {
+            model.splice(*start..*start, bytes.iter().copied());
+        }
+        SoakOperation::RemoveRange { start, length } => {
+            model.drain(*start..*start + *length);
+        }
+        SoakOperation::Splice {
+            start,
+            remove_length,
+            bytes,
+        } => {
+            model.splice(*start..*start + *remove_length, bytes.iter().copied());
+        }
+        SoakOperation::CopyWithin {
+            source,
+            length,
+            destination,
+        } => {
+            let copied: Vec<u8> = model[*source..*source + *length].to_vec();
+            model.splice(*destination..*destination, copied);
+        }
+        SoakOperation::Swap {
+            first,
+            first_length,
+            second,
+            second_length,
+        } => {
+            let first_bytes = model[*first..*first + *first_length].to_vec();
+            let second_bytes = model[*second..*second + *second_length].to_vec();
+            // Later range first, so the earlier range's indexes stay valid
+            model.splice(*second..*second + *second_length, first_bytes);
+            model.splice(*first..*first + *first_length, second_bytes);
+        }
+        SoakOperation::Rotate {
+            start,
+            length,
+            rotate_by,
+            rotate_left,
+        } => {
+            let range = &mut model[*start..*start + *length];
+            if *rotate_left {
+                range.rotate_left(rotate_by % length);
+            } else {
+                range.rotate_right(rotate_by % length);
+            }
+        }
+        SoakOperation::Fill {
+            start,
+            length,
+            pattern,
+        } => {
+            for (index, byte_slot) in model[*start..*start + *length].iter_mut().enumerate() {
+                *byte_slot = pattern[index % pattern.len()];
+            }
+        }
+        SoakOperation::Xor { start, length, key } => {
+            for byte_slot in &mut model[*start..*start + *length] {
+                *byte_slot ^= key;
+            }
+        }
+        SoakOperation::ByteSwap {
+            start,
+            length,
+            word_size,
+        } => {
+            for word in model[*start..*start + *length].chunks_exact_mut(*word_size) {
+                word.reverse();
+            }
+        }
+    }
+}
+
+/// Describes the first difference between file and model, if any.
+fn describe_difference(file_bytes: &[u8], model: &[u8]) -> Option<String> {
+    if file_bytes.len() != model.len() {
+        return Some(format!(
+            "length differs: file={}, model={}",
+            file_bytes.len(),
+            model.len()
+        ));
+    }
+    file_bytes
+        .iter()
+        .zip(model)
+        .position(|(file_byte, model_byte)| file_byte != model_byte)
+        .map(|position| {
+            format!(
+                "byte {} differs: file=0x{:02X}, model=0x{:02X}",
+                position, file_bytes[position], model[position]
+            )
+        })
+}
+
+/// Writes the failure record and builds the error returned to the caller.
+fn record_divergence(
+    config: &SoakConfig,
+    iteration: usize,
+    scratch_path: &Path,
+    operation: &SoakOperation,
+    buffer_size: usize,
+    detail: &str,
+) -> io::Error {
+    let failure_text = format!(
+        "seed={}\niteration={}\nfile={}\noperation={:?}\nbuffer_size={}\ndetail={}\nreplay=soak-test --seed {} --iterations {}\n",
+        config.seed,
+        iteration,
+        scratch_path.display(),
+        operation,
+        buffer_size,
+        detail,
+        config.seed,
+        iteration + 1
+    );
+    let failure_path = config.scratch_directory.join(SOAK_FAILURE_FILE_NAME);
+    if let Err(write_error) = std::fs::write(&failure_path, &failure_text) {
+        report_problem!(
+            "WARNING: could not write {}: {}",
+            failure_path.display(),
+            write_error
+        );
+    }
+
+    io::Error::other(format!(
+        "Soak test diverged from the reference model (seed={}, iteration={}): {}",
+        config.seed, iteration, detail
+    ))
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soak_test_short_run_matches_model() {
+        let scratch_directory = std::env::temp_dir().join("test_soak_test_short_run");
+        let _ = std::fs::remove_dir_all(&scratch_directory);
+
+        let config = SoakConfig {
+            seed: 0x5EED,
+            duration: Duration::from_secs(60),
+            max_iterations: Some(200),
+            scratch_directory: scratch_directory.clone(),
+            max_file_size: 1024,
+        };
+        let summary = run_soak_test(&config).expect("no divergence");
+        assert_eq!(summary.iterations, 200);
+
+        let _ = std::fs::remove_dir_all(&scratch_directory);
+    }
+
+    #[test]
+    fn test_soak_model_detects_difference() {
+        assert_eq!(describe_difference(b"abc", b"abc"), None);
+        assert!(describe_difference(b"abc", b"abd").is_some());
+        assert!(describe_difference(b"abc", b"ab").is_some());
+    }
+}
@@ -0,0 +1,518 @@
+//! Editing bytes of a member inside an uncompressed tar archive, in place
+//!
+//! Like `zip_archive.rs`, this treats the archive as a plain file: the
+//! member's data offset is resolved by walking the 512-byte headers, the
+//! edits become range replacements, and the member's header checksum is
+//! recomputed — all within ONE verified draft of the archive.
+//!
+//! Tar header checksums cover the header block only, so same-length data
+//! edits leave it unchanged unless the header itself is touched. Callers may
+//! set a new modification time for the member (recommended, so extraction
+//! tools see the change); the checksum is then recomputed and written in
+//! the same draft. The header checksum is validated before any edit, and
+//! re-validated in the draft before the rename.
+//!
+//! # Current Scope
+//! - Uncompressed tar (ustar, GNU, v7 headers); regular-file members only
+//! - Member names from the header name field, the ustar prefix field, and
+//!   GNU long-name (`L`) records; pax extended names are not resolved
+//! - Same-length edits only (member size and later offsets stay unchanged)
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{run_draft_workflow, validate_target_file};
+use crate::operation_config::{OperationConfig, with_operation_config};
+use crate::range_operations::{
+    build_multi_replacement_draft, validate_range_replacements, verify_multi_replacement,
+};
+use crate::util::offset_to_usize;
+
+/// Size of a tar header and of the data padding unit
+const TAR_BLOCK_SIZE: usize = 512;
+
+const NAME_FIELD: (usize, usize) = (0, 100);
+const SIZE_FIELD: (usize, usize) = (124, 12);
+const MTIME_FIELD: (usize, usize) = (136, 12);
+const CHECKSUM_FIELD: (usize, usize) = (148, 8);
+const TYPEFLAG_OFFSET: usize = 156;
+const MAGIC_FIELD: (usize, usize) = (257, 6);
+const PREFIX_FIELD: (usize, usize) = (345, 155);
+
+/// Type flag of a GNU long-name record (data holds the next member's name)
+const TYPEFLAG_GNU_LONG_NAME: u8 = b'L';
+
+/// Upper bound on a GNU long-name record; real names are at most a few KiB,
+/// so anything larger is a malformed or hostile size field
+const MAX_GNU_LONG_NAME_LENGTH: u64 = 64 * 1024;
+
+/// Upper bound on headers walked, to prevent runaway scans
+const MAX_TAR_HEADERS: usize = 16_777_216;
+
+/// Where a member lives inside the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarMemberLocation {
+    pub member_name: String,
+    pub header_offset: u64,
+    pub data_offset: u64,
+    pub member_size: u64,
+    pub typeflag: u8,
+}
+
+/// Locates a member by exact name by walking the archive's headers.
+///
+/// # Returns
+/// - `Ok(TarMemberLocation)` for the LAST member with that name (tar
+///   semantics: later entries supersede earlier ones)
+/// - `Err(io::Error)`: `NotFound` if absent, `InvalidData` if a header
+///   checksum or size field is malformed
+pub fn locate_tar_member(archive_path: &Path, member_name: &str) -> io::Result<TarMemberLocation> {
+    let archive_size = validate_target_file(archive_path)?;
+    let mut archive_file = File::open(archive_path)?;
+
+    let mut header_offset: u64 = 0;
+    let mut pending_long_name: Option<String> = None;
+    let mut found_member: Option<TarMemberLocation> = None;
+
+    for _header_index in 0..MAX_TAR_HEADERS {
+        if header_offset + TAR_BLOCK_SIZE as u64 > archive_size {
+            break;
+        }
+        let header = read_tar_block(&mut archive_file, header_offset)?;
+        if header.iter().all(|&byte_value| byte_value == 0) {
+            break; // End-of-archive marker
+        }
+        validate_header_checksum(&header, header_offset)?;
+
+        let member_size = parse_octal_field(&header, SIZE_FIELD)?;
+        let data_offset = header_offset + TAR_BLOCK_SIZE as u64;
+        let typeflag = header[TYPEFLAG_OFFSET];
+
+        if typeflag == TYPEFLAG_GNU_LONG_NAME {
+            // Bounded before allocating: the size comes from the archive
+            if member_size > MAX_GNU_LONG_NAME_LENGTH {
+                return Err(invalid_tar(&format!(
+                    "long-name record at offset {} is {} bytes (limit {})",
+                    header_offset, member_size, MAX_GNU_LONG_NAME_LENGTH
+                )));
+            }
+            if member_size > archive_size - data_offset {
+                return Err(invalid_tar(&format!(
+                    "long-name record at offset {} runs past the end of the archive",
+                    header_offset
+                )));
+            }
+            let long_name_length = offset_to_usize(member_size)?;
+            let long_name_bytes = read_exact_at(&mut archive_file, data_offset, long_name_length)?;
+            pending_long_name = Some(field_text(&long_name_bytes));
+        } else {
+            let header_name = pending_long_name
+                .take()
+                .unwrap_or_else(|| header_member_name(&header));
+            if header_name == member_name {
+                found_member = Some(TarMemberLocation {
+                    member_name: header_name,
+                    header_offset,
+                    data_offset,
+                    member_size,
+                    typeflag,
+                });
+            }
+        }
+
+        let padded_size = member_size.div_ceil(TAR_BLOCK_SIZE as u64) * TAR_BLOCK_SIZE as u64;
+        header_offset = data_offset.checked_add(padded_size).ok_or_else(|| {
+            invalid_tar(&format!(
+                "member size at offset {} overflows the archive offset",
+                header_offset
+            ))
+        })?;
+    }
+
+    found_member.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No member named `{}` in archive", member_name),
+        )
+    })
+}
+
+/// Applies same-length edits to a tar member and fixes up its header.
+///
+/// # Parameters
+/// - `archive_path`: The tar archive
+/// - `member_name`: Exact member path as stored in the archive
+/// - `member_edits`: `(offset_within_member, new_bytes)` pairs
+/// - `new_modification_time`: If set and different from the stored one,
+///   written to the member's header (seconds since the epoch) and the
+///   header checksum is recomputed; the header is untouched otherwise
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if the member is missing or not a regular file, an
+///   edit is out of the member's bounds, or any phase fails
+pub fn edit_tar_member(
+    archive_path: PathBuf,
+    member_name: &str,
+    member_edits: &[(u64, Vec<u8>)],
+    new_modification_time: Option<u64>,
+) -> io::Result<()> {
+    // =========================================
+    // Member Resolution Phase
+    // =========================================
+    let member = locate_tar_member(&archive_path, member_name)?;
+    if member.typeflag != b'0' && member.typeflag != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Member `{}` is not a regular file (type `{}`)",
+                member_name, member.typeflag as char
+            ),
+        ));
+    }
+
+    let member_relative_edits = validate_range_replacements(member_edits, member.member_size)?;
+
+    let mut archive_replacements: Vec<(u64, Vec<u8>)> = member_relative_edits
+        .iter()
+        .map(|(offset, new_bytes)| (member.data_offset + offset, new_bytes.clone()))
+        .collect();
+
+    // =========================================
+    // Header Fix-up Phase
+    // =========================================
+    let mut archive_file = File::open(&archive_path)?;
+    let original_header = read_tar_block(&mut archive_file, member.header_offset)?;
+    drop(archive_file);
+
+    // Fields are only rewritten when their value changes, so a header that
+    // formats them differently (e.g. a 7-digit checksum) is kept byte-exact
+    let mut new_header = original_header;
+    if let Some(modification_time) = new_modification_time
+        && parse_octal_field(&original_header, MTIME_FIELD)? != modification_time
+    {
+        write_octal_field(&mut new_header, MTIME_FIELD, modification_time)?;
+    }
+    if parse_octal_field(&new_header, CHECKSUM_FIELD)? != compute_header_checksum(&new_header) {
+        write_header_checksum(&mut new_header);
+    }
+
+    if new_header != original_header {
+        let header_fields_start = MTIME_FIELD.0;
+        let header_fields_end = CHECKSUM_FIELD.0 + CHECKSUM_FIELD.1;
+        archive_replacements.push((
+            member.header_offset + header_fields_start as u64,
+            new_header[header_fields_start..header_fields_end].to_vec(),
+        ));
+    }
+
+    let archive_size = validate_target_file(&archive_path)?;
+    let sorted_replacements = validate_range_replacements(&archive_replacements, archive_size)?;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &archive_path,
+        "Tar Member Edit",
+        archive_size,
+        |source_file, draft_file| {
+            build_multi_replacement_draft(source_file, draft_file, &sorted_replacements)
+        },
+        |original_path, draft_path| {
+            verify_multi_replacement(
+                original_path,
+                draft_path,
+                &sorted_replacements,
+                archive_size,
+            )?;
+
+            // The draft's header must carry a valid checksum
+            let mut draft_file = File::open(draft_path)?;
+            let draft_header = read_tar_block(&mut draft_file, member.header_offset)?;
+            validate_header_checksum(&draft_header, member.header_offset)
+        },
+    )
+}
+
+/// Like `edit_tar_member`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn edit_tar_member_with_config(
+    archive_path: PathBuf,
+    member_name: &str,
+    member_edits: &[(u64, Vec<u8>)],
+    new_modification_time: Option<u64>,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        edit_tar_member(
+            archive_path,
+            member_name,
+            member_edits,
+            new_modification_time,
+        )
+    })
+}
+
+/// Member name from the header name field, joined with the ustar prefix.
+fn header_member_name(header: &[u8; TAR_BLOCK_SIZE]) -> String {
+    let name = field_text(&header[NAME_FIELD.0..NAME_FIELD.0 + NAME_FIELD.1]);
+    let is_ustar = &header[MAGIC_FIELD.0..MAGIC_FIELD.0 + 5] == b"ustar";
+    let prefix = field_text(&header[PREFIX_FIELD.0..PREFIX_FIELD.0 + PREFIX_FIELD.1]);
+    if is_ustar && !prefix.is_empty() {
+        format!("{}/{}", prefix, name)
+    } else {
+        name
+    }
+}
+
+/// Sum of header bytes with the checksum field counted as spaces.
+fn compute_header_checksum(header: &[u8; TAR_BLOCK_SIZE]) -> u64 {
+    header
+        .iter()
+        .enumerate()
+        .map(|(index, &byte_value)| {
+            let in_checksum_field =
+                (CHECKSUM_FIELD.0..CHECKSUM_FIELD.0 + CHECKSUM_FIELD.1).contains(&index);
+            if in_checksum_field {
+                b' ' as u64
+            } else {
+                byte_value as u64
+            }
+        })
+        .sum()
+}
+
+fn validate_header_checksum(header: &[u8; TAR_BLOCK_SIZE], header_offset: u64) -> io::Result<()> {
+    let stored_checksum = parse_octal_field(header, CHECKSUM_FIELD)?;
+    let computed_checksum = compute_header_checksum(header);
+    if stored_checksum != computed_checksum {
+        return Err(invalid_tar(&format!(
+            "header checksum mismatch at offset {} (stored {}, computed {})",
+            header_offset, stored_checksum, computed_checksum
+        )));
+    }
+    Ok(())
+}
+
+/// Writes the checksum in the conventional form: 6 octal digits, NUL, space.
+fn write_header_checksum(header: &mut [u8; TAR_BLOCK_SIZE]) {
+    let checksum = compute_header_checksum(header);
+    let checksum_text = format!("{:06o}\0 ", checksum);
+    header[CHECKSUM_FIELD.0..CHECKSUM_FIELD.0 + CHECKSUM_FIELD.1]
+        .copy_from_slice(checksum_text.as_bytes());
+}
+
+/// Parses a NUL/space-terminated octal numeric field.
+fn parse_octal_field(header: &[u8], (start, length): (usize, usize)) -> io::Result<u64> {
+    let field = &header[start..start + length];
+    if field[0] & 0x80 != 0 {
+        return Err(invalid_tar("base-256 numeric fields are not supported"));
+    }
+    let digits: String = field
+        .iter()
+        .map(|&byte_value| byte_value as char)
+        .take_while(|character| *character != '\0')
+        .filter(|character| !character.is_whitespace())
+        .collect();
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(&digits, 8)
+        .map_err(|_| invalid_tar(&format!("invalid octal field `{}`", digits)))
+}
+
+/// Writes an octal numeric field, zero-padded and NUL-terminated.
+fn write_octal_field(
+    header: &mut [u8; TAR_BLOCK_SIZE],
+    (start, length): (usize, usize),
+    value: u64,
+) -> io::Result<()> {
+    let field_text = format!("{:0width$o}\0", value, width = length - 1);
+    if field_text.len() != length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Value {} does not fit a {}-byte octal field", value, length),
+        ));
+    }
+    header[start..start + length].copy_from_slice(field_text.as_bytes());
+    Ok(())
+}
+
+/// Text of a NUL-terminated field.
+fn field_text(field: &[u8]) -> String {
+    let text_length = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..text_length]).to_string()
+}
+
+fn read_tar_block(file: &mut File, offset: u64) -> io::Result<[u8; TAR_BLOCK_SIZE]> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut block = [0u8; TAR_BLOCK_SIZE];
+    file.read_exact(&mut block)?;
+    Ok(block)
+}
+
+fn read_exact_at(file: &mut File, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0u8; length];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn invalid_tar(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("tar archive: {}", reason),
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a ustar header block for a regular file.
+    fn build_tar_header(member_name: &str, member_size: usize) -> [u8; TAR_BLOCK_SIZE] {
+        let mut header = [0u8; TAR_BLOCK_SIZE];
+        header[..member_name.len()].copy_from_slice(member_name.as_bytes());
+        header[100..108].copy_from_slice(b"0000644\0");
+        write_octal_field(&mut header, SIZE_FIELD, member_size as u64).expect("size fits");
+        write_octal_field(&mut header, MTIME_FIELD, 1_000_000).expect("mtime fits");
+        header[TYPEFLAG_OFFSET] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        write_header_checksum(&mut header);
+        header
+    }
+
+    fn build_tar(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        for (member_name, member_data) in members {
+            archive.extend_from_slice(&build_tar_header(member_name, member_data.len()));
+            archive.extend_from_slice(member_data);
+            let padding =
+                member_data.len().div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE - member_data.len();
+            archive.extend(std::iter::repeat_n(0u8, padding));
+        }
+        archive.extend_from_slice(&[0u8; TAR_BLOCK_SIZE * 2]);
+        archive
+    }
+
+    #[test]
+    fn test_edit_tar_member_data_and_mtime() {
+        let test_file = std::env::temp_dir().join("test_tar_member_edit.tar");
+        let second_data: Vec<u8> = (0..=255u8).cycle().take(700).collect();
+        std::fs::write(
+            &test_file,
+            build_tar(&[("a.txt", b"hello"), ("etc/b.bin", &second_data)]),
+        )
+        .expect("create test archive");
+
+        let member = locate_tar_member(&test_file, "etc/b.bin").expect("member found");
+        assert_eq!(member.data_offset, 1024 + 512);
+        assert_eq!(member.member_size, 700);
+
+        assert!(
+            edit_tar_member(
+                test_file.clone(),
+                "etc/b.bin",
+                &[(600, vec![0xAB, 0xCD])],
+                Some(2_000_000)
+            )
+            .is_ok()
+        );
+
+        let archive_bytes = std::fs::read(&test_file).expect("Failed to read archive");
+        assert_eq!(
+            &archive_bytes[member.data_offset as usize + 600..member.data_offset as usize + 602],
+            &[0xAB, 0xCD]
+        );
+
+        // Header carries the new mtime and a valid checksum
+        let header_start = member.header_offset as usize;
+        let header: [u8; TAR_BLOCK_SIZE] = archive_bytes
+            [header_start..header_start + TAR_BLOCK_SIZE]
+            .try_into()
+            .expect("full block");
+        assert_eq!(
+            parse_octal_field(&header, MTIME_FIELD).expect("mtime"),
+            2_000_000
+        );
+        assert!(validate_header_checksum(&header, member.header_offset).is_ok());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_edit_tar_member_keeps_unchanged_header_fields() {
+        let test_file = std::env::temp_dir().join("test_tar_member_header_kept.tar");
+        let mut archive_bytes = build_tar(&[("a.txt", b"hello")]);
+        // A 7-digit checksum, as some writers produce; same value
+        let checksum = parse_octal_field(&archive_bytes, CHECKSUM_FIELD).expect("checksum");
+        archive_bytes[CHECKSUM_FIELD.0..CHECKSUM_FIELD.0 + CHECKSUM_FIELD.1]
+            .copy_from_slice(format!("{:07o}\0", checksum).as_bytes());
+        std::fs::write(&test_file, &archive_bytes).expect("create test archive");
+
+        // Same mtime as before: only the data changes
+        edit_tar_member(
+            test_file.clone(),
+            "a.txt",
+            &[(0, b"J".to_vec())],
+            Some(1_000_000),
+        )
+        .expect("edit");
+        let edited_bytes = std::fs::read(&test_file).expect("read archive");
+        assert_eq!(
+            &edited_bytes[..TAR_BLOCK_SIZE],
+            &archive_bytes[..TAR_BLOCK_SIZE]
+        );
+        assert_eq!(&edited_bytes[TAR_BLOCK_SIZE..TAR_BLOCK_SIZE + 5], b"Jello");
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_edit_tar_member_rejects_bad_requests() {
+        let test_file = std::env::temp_dir().join("test_tar_member_reject.tar");
+        let archive_bytes = build_tar(&[("a.txt", b"hello")]);
+        std::fs::write(&test_file, &archive_bytes).expect("create test archive");
+
+        assert!(edit_tar_member(test_file.clone(), "nope", &[(0, vec![0x41])], None).is_err());
+        // Past the member's end, even though the padding block would fit
+        assert!(edit_tar_member(test_file.clone(), "a.txt", &[(5, vec![0x41])], None).is_err());
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read archive"),
+            archive_bytes
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_locate_tar_member_rejects_oversized_long_name() {
+        let test_file = std::env::temp_dir().join("test_tar_long_name_size.tar");
+        for long_name_size in [0o77_777_777_777u64, 2048] {
+            let mut long_name_header = build_tar_header("././@LongLink", 0);
+            write_octal_field(&mut long_name_header, SIZE_FIELD, long_name_size)
+                .expect("size fits");
+            long_name_header[TYPEFLAG_OFFSET] = TYPEFLAG_GNU_LONG_NAME;
+            write_header_checksum(&mut long_name_header);
+            let mut archive_bytes = long_name_header.to_vec();
+            archive_bytes.extend_from_slice(&[b'x'; TAR_BLOCK_SIZE]);
+            std::fs::write(&test_file, &archive_bytes).expect("create test archive");
+
+            // Refused as malformed, without allocating the claimed size
+            let error = locate_tar_member(&test_file, "x").expect_err("bad long name");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", error);
+            assert!(error.to_string().contains("long-name"), "{}", error);
+        }
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
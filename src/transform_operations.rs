@@ -0,0 +1,1374 @@
+//! Streaming byte transforms
+//!
+//! Some edits are byte-level but touch a whole file or a long range (line
+//! endings, byte stripping, per-byte arithmetic, byte translation,
+//! endianness, ...). Users reach for hex tools or `sed` to do these, with no
+//! backup and no verification. Here each transform streams the original
+//! through the standard draft workflow and is verified by an independent
+//! invariant (not by re-running the same transform).
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{
+    check_chunk_limit, chunk_limit_for, chunk_limit_for_chunk_size, copy_exact_bytes,
+    copy_remaining_bytes, remaining_byte_count, run_draft_workflow, validate_target_file,
+    verify_both_at_eof, verify_next_bytes_match, write_all_checked,
+};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::hash::sha256::{digest_leading_u64, sha256_of_file};
+use crate::operation_config::{OperationConfig, active_buffer_size, with_operation_config};
+use crate::range_operations::checked_range_end;
+use crate::reporter::{report_check_passed, report_detail};
+use crate::util::chunk_length;
+
+const CARRIAGE_RETURN: u8 = b'\r';
+const LINE_FEED: u8 = b'\n';
+
+// ============================
+// Line-Ending Conversion
+// ============================
+
+/// Target line-ending convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndingTarget {
+    /// `\r\n` → `\n` (lone `\r` is left alone)
+    ToLf,
+    /// lone `\n` → `\r\n` (existing `\r\n` is left alone)
+    ToCrlf,
+}
+
+/// Line-ending conversion named by direction.
+///
+/// Equivalent to `LineEndingTarget` (`CrlfToLf` = `ToLf`, `LfToCrlf` =
+/// `ToCrlf`); either can be passed to `convert_line_endings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    LfToCrlf,
+    CrlfToLf,
+}
+
+impl From<LineEnding> for LineEndingTarget {
+    fn from(line_ending: LineEnding) -> Self {
+        match line_ending {
+            LineEnding::LfToCrlf => LineEndingTarget::ToCrlf,
+            LineEnding::CrlfToLf => LineEndingTarget::ToLf,
+        }
+    }
+}
+
+/// What a line-ending conversion did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineEndingConversionReport {
+    pub target: LineEndingTarget,
+    pub line_endings_converted: u64,
+    pub original_size: u64,
+    pub new_size: u64,
+}
+
+/// Converts all line endings in a file to LF or CRLF.
+///
+/// # Overview
+/// 1. Counting pass: stream the original to count conversions (this fixes
+///    the exact expected draft size)
+/// 2. Draft pass: stream the original again, writing converted bytes
+/// 3. Verification: an independent invariant — with every `\r` removed,
+///    original and draft must be byte-identical, and the draft must hold
+///    exactly `converted` fewer (ToLf) or more (ToCrlf) `\r` bytes
+///
+/// A file that already uses the target convention is left untouched
+/// (no backup, no rename) and reported with zero conversions.
+///
+/// # Returns
+/// - `Ok(LineEndingConversionReport)` with the conversion count
+/// - `Err(io::Error)` if any phase fails; the original is untouched
+pub fn convert_line_endings(
+    original_file_path: PathBuf,
+    target: impl Into<LineEndingTarget>,
+) -> io::Result<LineEndingConversionReport> {
+    let target: LineEndingTarget = target.into();
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    // =========================================
+    // Counting Phase
+    // =========================================
+    let line_endings_converted = {
+        let mut source_file = File::open(&original_file_path)?;
+        stream_line_ending_conversion(&mut source_file, None::<&mut File>, target)?
+    };
+
+    let new_size = match target {
+        LineEndingTarget::ToLf => original_file_size - line_endings_converted,
+        LineEndingTarget::ToCrlf => original_file_size + line_endings_converted,
+    };
+    let report = LineEndingConversionReport {
+        target,
+        line_endings_converted,
+        original_size: original_file_size,
+        new_size,
+    };
+
+    if line_endings_converted == 0 {
+        report_detail!("No line endings to convert; file left untouched");
+        return Ok(report);
+    }
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Line-Ending Conversion",
+        new_size,
+        |source_file, draft_file| {
+            let converted_in_draft =
+                stream_line_ending_conversion(source_file, Some(draft_file), target)?;
+            if converted_in_draft != line_endings_converted {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::OriginalChanged,
+                    0,
+                    line_endings_converted,
+                    converted_in_draft,
+                )
+                .into());
+            }
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let (original_cr_count, draft_cr_count) =
+                verify_equal_ignoring_byte(original_path, draft_path, CARRIAGE_RETURN)?;
+            let expected_draft_cr_count = match target {
+                LineEndingTarget::ToLf => original_cr_count.checked_sub(line_endings_converted),
+                LineEndingTarget::ToCrlf => original_cr_count.checked_add(line_endings_converted),
+            };
+            // Fewer CRs than conversions: the original lost CRs since counting
+            let Some(expected_draft_cr_count) = expected_draft_cr_count else {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::OriginalChanged,
+                    0,
+                    line_endings_converted,
+                    original_cr_count,
+                )
+                .into());
+            };
+            if draft_cr_count != expected_draft_cr_count {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::ContentCount,
+                    0,
+                    expected_draft_cr_count,
+                    draft_cr_count,
+                )
+                .into());
+            }
+            report_check_passed!(
+                "   ✓ Only line endings changed ({} converted)",
+                line_endings_converted
+            );
+            Ok(())
+        },
+    )?;
+
+    Ok(report)
+}
+
+/// Like `convert_line_endings`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn convert_line_endings_with_config(
+    original_file_path: PathBuf,
+    target: impl Into<LineEndingTarget>,
+    config: &OperationConfig,
+) -> io::Result<LineEndingConversionReport> {
+    with_operation_config(config, || convert_line_endings(original_file_path, target))
+}
+
+/// Streams `source` through the conversion; writes to `sink` if given.
+///
+/// # Returns
+/// Number of line endings converted.
+fn stream_line_ending_conversion<R: Read + Seek, W: Write>(
+    source: &mut R,
+    mut sink: Option<&mut W>,
+    target: LineEndingTarget,
+) -> io::Result<u64> {
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+    // Worst case every byte is a lone LF that becomes CRLF
+    let mut output_buffer: Vec<u8> = Vec::with_capacity(bucket_brigade_buffer.len() * 2 + 1);
+    let mut conversions: u64 = 0;
+    // Previous byte was a CR (not yet emitted for ToLf)
+    let mut previous_was_cr = false;
+    let chunk_limit = chunk_limit_for(remaining_byte_count(source)?);
+    let mut chunk_number: usize = 0;
+
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_read = source.read(&mut bucket_brigade_buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        output_buffer.clear();
+        for &byte_value in &bucket_brigade_buffer[..bytes_read] {
+            match target {
+                LineEndingTarget::ToLf => {
+                    if previous_was_cr && byte_value != LINE_FEED {
+                        // Lone CR: keep it
+                        output_buffer.push(CARRIAGE_RETURN);
+                    }
+                    if previous_was_cr && byte_value == LINE_FEED {
+                        conversions += 1;
+                    }
+                    if byte_value != CARRIAGE_RETURN {
+                        output_buffer.push(byte_value);
+                    }
+                }
+                LineEndingTarget::ToCrlf => {
+                    if byte_value == LINE_FEED && !previous_was_cr {
+                        output_buffer.push(CARRIAGE_RETURN);
+                        conversions += 1;
+                    }
+                    output_buffer.push(byte_value);
+                }
+            }
+            previous_was_cr = byte_value == CARRIAGE_RETURN;
+        }
+
+        if let Some(draft) = sink.as_deref_mut() {
+            write_all_checked(draft, &output_buffer)?;
+        }
+    }
+
+    // A CR at EOF is a lone CR
+    if target == LineEndingTarget::ToLf
+        && previous_was_cr
+        && let Some(draft) = sink
+    {
+        write_all_checked(draft, &[CARRIAGE_RETURN])?;
+    }
+
+    Ok(conversions)
+}
+
+// ============================
+// Byte-Range Transforms
+// ============================
+
+/// Per-byte arithmetic/bitwise transform applied across a range.
+///
+/// Every variant is invertible, and verification uses the inverse: each
+/// draft byte, mapped back, must equal the original byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRangeTransform {
+    /// `byte ^ key`
+    XorWith(u8),
+    /// `byte + constant` (mod 256)
+    AddConstant(u8),
+    /// `byte - constant` (mod 256)
+    SubtractConstant(u8),
+}
+
+impl ByteRangeTransform {
+    /// Name used in workflow output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ByteRangeTransform::XorWith(_) => "XOR Range",
+            ByteRangeTransform::AddConstant(_) => "Add-Constant Range",
+            ByteRangeTransform::SubtractConstant(_) => "Subtract-Constant Range",
+        }
+    }
+
+    fn apply(&self, byte_value: u8) -> u8 {
+        match *self {
+            ByteRangeTransform::XorWith(key) => byte_value ^ key,
+            ByteRangeTransform::AddConstant(constant) => byte_value.wrapping_add(constant),
+            ByteRangeTransform::SubtractConstant(constant) => byte_value.wrapping_sub(constant),
+        }
+    }
+
+    fn invert(&self, byte_value: u8) -> u8 {
+        match *self {
+            ByteRangeTransform::XorWith(key) => byte_value ^ key,
+            ByteRangeTransform::AddConstant(constant) => byte_value.wrapping_sub(constant),
+            ByteRangeTransform::SubtractConstant(constant) => byte_value.wrapping_add(constant),
+        }
+    }
+}
+
+/// Applies a per-byte transform to `byte_count` bytes (no frame-shift).
+///
+/// # Verification
+/// - Total length: draft == original
+/// - Pre-range and post-range bytes identical
+/// - Every range byte: `invert(draft[i]) == original[i]`
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if the range is empty or extends past EOF, or any
+///   phase fails
+pub fn transform_byte_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    transform: ByteRangeTransform,
+) -> io::Result<()> {
+    map_byte_range(
+        &original_file_path,
+        byte_position_from_start,
+        byte_count,
+        transform.name(),
+        |byte_value| transform.apply(byte_value),
+        |original_byte, draft_byte| transform.invert(draft_byte) == original_byte,
+    )
+}
+
+/// Like `transform_byte_range`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn transform_byte_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    transform: ByteRangeTransform,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        transform_byte_range(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            transform,
+        )
+    })
+}
+
+/// Maps every byte of a range through a 256-entry table (tr-style).
+///
+/// # Overview
+/// `table[b]` replaces each byte `b` in `start..start+byte_count`, in one
+/// streaming pass: case folding, charset conversion, or any fixed
+/// substitution. Identity entries leave bytes unchanged.
+///
+/// # Verification
+/// - Total length: draft == original
+/// - Pre-range and post-range bytes identical
+/// - Every range byte: `draft[i] == table[original[i]]`, read back from
+///   both files
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` if the range is empty or extends past EOF, or any
+///   phase fails
+pub fn translate_bytes(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    table: &[u8; 256],
+) -> io::Result<()> {
+    map_byte_range(
+        &original_file_path,
+        byte_position_from_start,
+        byte_count,
+        "Byte Translation",
+        |byte_value| table[byte_value as usize],
+        |original_byte, draft_byte| table[original_byte as usize] == draft_byte,
+    )
+}
+
+/// Like `translate_bytes`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn translate_bytes_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    table: &[u8; 256],
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        translate_bytes(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            table,
+        )
+    })
+}
+
+/// Identity translation table, to be edited for `translate_bytes`.
+pub fn identity_translation_table() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (index, entry) in table.iter_mut().enumerate() {
+        *entry = index as u8;
+    }
+    table
+}
+
+/// Shared draft/verify cycle for per-byte range mappings (no frame-shift).
+///
+/// `is_expected_pair(original_byte, draft_byte)` is the verification
+/// invariant for every range byte.
+fn map_byte_range<MapByte, IsExpectedPair>(
+    original_file_path: &Path,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    operation_name: &str,
+    map_byte: MapByte,
+    is_expected_pair: IsExpectedPair,
+) -> io::Result<()>
+where
+    MapByte: Fn(u8) -> u8,
+    IsExpectedPair: Fn(u8, u8) -> bool,
+{
+    let original_file_size = validate_target_file(original_file_path)?;
+
+    if byte_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Transform range must be non-empty",
+        ));
+    }
+    let range_end_exclusive = checked_range_end(byte_position_from_start, byte_count)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+
+    run_draft_workflow(
+        original_file_path,
+        operation_name,
+        original_file_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+
+            let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+            let mut bytes_transformed: u64 = 0;
+            let chunk_limit = chunk_limit_for(byte_count);
+            let mut chunk_number: usize = 0;
+            while bytes_transformed < byte_count {
+                check_chunk_limit(chunk_number, chunk_limit)?;
+                chunk_number += 1;
+
+                let bytes_to_read =
+                    chunk_length(bucket_brigade_buffer.len(), byte_count - bytes_transformed);
+                source_file.read_exact(&mut bucket_brigade_buffer[..bytes_to_read])?;
+                for byte_slot in &mut bucket_brigade_buffer[..bytes_to_read] {
+                    *byte_slot = map_byte(*byte_slot);
+                }
+                write_all_checked(draft_file, &bucket_brigade_buffer[..bytes_to_read])?;
+                bytes_transformed += bytes_to_read as u64;
+            }
+
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                byte_position_from_start,
+                0,
+                0,
+                "Pre-range",
+            )?;
+
+            let mut original_buffer = vec![0u8; active_buffer_size()];
+            let mut draft_buffer = vec![0u8; active_buffer_size()];
+            let mut bytes_verified: u64 = 0;
+            let chunk_limit = chunk_limit_for(byte_count);
+            let mut chunk_number: usize = 0;
+            while bytes_verified < byte_count {
+                check_chunk_limit(chunk_number, chunk_limit)?;
+                chunk_number += 1;
+
+                let bytes_to_read = chunk_length(draft_buffer.len(), byte_count - bytes_verified);
+                original_file.read_exact(&mut original_buffer[..bytes_to_read])?;
+                draft_file.read_exact(&mut draft_buffer[..bytes_to_read])?;
+                for i in 0..bytes_to_read {
+                    if !is_expected_pair(original_buffer[i], draft_buffer[i]) {
+                        return Err(ByteOpError::verification_failed(
+                            VerificationPhase::TransformedBytes,
+                            byte_position_from_start + bytes_verified + i as u64,
+                            u64::from(original_buffer[i]),
+                            u64::from(draft_buffer[i]),
+                        )
+                        .into());
+                    }
+                }
+                bytes_verified += bytes_to_read as u64;
+            }
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                post_range_byte_count,
+                range_end_exclusive,
+                range_end_exclusive,
+                "Post-range",
+            )?;
+
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )
+}
+
+/// Adds `constant` (mod 256) to every byte of a range.
+pub fn add_constant_to_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    constant: u8,
+) -> io::Result<()> {
+    transform_byte_range(
+        original_file_path,
+        byte_position_from_start,
+        byte_count,
+        ByteRangeTransform::AddConstant(constant),
+    )
+}
+
+/// Like `add_constant_to_range`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn add_constant_to_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    constant: u8,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        add_constant_to_range(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            constant,
+        )
+    })
+}
+
+/// Subtracts `constant` (mod 256) from every byte of a range.
+pub fn subtract_constant_from_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    constant: u8,
+) -> io::Result<()> {
+    transform_byte_range(
+        original_file_path,
+        byte_position_from_start,
+        byte_count,
+        ByteRangeTransform::SubtractConstant(constant),
+    )
+}
+
+/// Like `subtract_constant_from_range`, using `config` instead of the active
+/// (or default) `OperationConfig`.
+pub fn subtract_constant_from_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    constant: u8,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        subtract_constant_from_range(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            constant,
+        )
+    })
+}
+
+// ============================
+// Byte Value Removal
+// ============================
+
+/// What `remove_all_bytes_with_value` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteValueRemovalReport {
+    pub byte_value: u8,
+    pub removed_count: u64,
+    pub original_size: u64,
+    pub new_size: u64,
+}
+
+/// Removes every occurrence of `byte_value` from a file.
+///
+/// # Overview
+/// For dropping `0x00` padding or stray `0x0D` bytes.
+/// 1. Counting pass: stream the original to count occurrences (this fixes
+///    the exact expected draft size)
+/// 2. Draft pass: stream the original again, skipping matching bytes
+/// 3. Verification: with every `byte_value` removed, original and draft
+///    must be byte-identical; the draft must contain no `byte_value` and
+///    its size must equal the original size minus the count
+///
+/// A file without the value is left untouched (no backup, no rename) and
+/// reported with a zero count.
+///
+/// # Returns
+/// - `Ok(ByteValueRemovalReport)` with the count
+/// - `Err(io::Error)` if any phase fails; the original is untouched
+pub fn remove_all_bytes_with_value(
+    original_file_path: PathBuf,
+    byte_value: u8,
+) -> io::Result<ByteValueRemovalReport> {
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    // =========================================
+    // Counting Phase
+    // =========================================
+    let removed_count = {
+        let mut source_file = File::open(&original_file_path)?;
+        stream_without_byte_value(&mut source_file, None::<&mut File>, byte_value)?
+    };
+    let report = ByteValueRemovalReport {
+        byte_value,
+        removed_count,
+        original_size: original_file_size,
+        new_size: original_file_size - removed_count,
+    };
+
+    if removed_count == 0 {
+        report_detail!("No 0x{:02X} bytes present; file left untouched", byte_value);
+        return Ok(report);
+    }
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Value Removal",
+        report.new_size,
+        |source_file, draft_file| {
+            let removed_in_draft =
+                stream_without_byte_value(source_file, Some(draft_file), byte_value)?;
+            if removed_in_draft != removed_count {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::OriginalChanged,
+                    0,
+                    removed_count,
+                    removed_in_draft,
+                )
+                .into());
+            }
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let (original_value_count, draft_value_count) =
+                verify_equal_ignoring_byte(original_path, draft_path, byte_value)?;
+            if original_value_count != removed_count {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::OriginalChanged,
+                    0,
+                    removed_count,
+                    original_value_count,
+                )
+                .into());
+            }
+            if draft_value_count != 0 {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::ContentCount,
+                    0,
+                    0,
+                    draft_value_count,
+                )
+                .into());
+            }
+            report_check_passed!(
+                "   ✓ Only 0x{:02X} bytes removed ({})",
+                byte_value,
+                removed_count
+            );
+            Ok(())
+        },
+    )?;
+
+    Ok(report)
+}
+
+/// Like `remove_all_bytes_with_value`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn remove_all_bytes_with_value_with_config(
+    original_file_path: PathBuf,
+    byte_value: u8,
+    config: &OperationConfig,
+) -> io::Result<ByteValueRemovalReport> {
+    with_operation_config(config, || {
+        remove_all_bytes_with_value(original_file_path, byte_value)
+    })
+}
+
+/// Streams `source` without `byte_value`; writes to `sink` if given.
+///
+/// # Returns
+/// Number of bytes skipped.
+fn stream_without_byte_value<R: Read + Seek, W: Write>(
+    source: &mut R,
+    mut sink: Option<&mut W>,
+    byte_value: u8,
+) -> io::Result<u64> {
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+    let mut skipped_count: u64 = 0;
+    let chunk_limit = chunk_limit_for(remaining_byte_count(source)?);
+    let mut chunk_number: usize = 0;
+
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_read = source.read(&mut bucket_brigade_buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        let mut kept_length: usize = 0;
+        for read_index in 0..bytes_read {
+            let current_byte = bucket_brigade_buffer[read_index];
+            if current_byte == byte_value {
+                skipped_count += 1;
+            } else {
+                // Compact in place; kept_length never passes read_index
+                bucket_brigade_buffer[kept_length] = current_byte;
+                kept_length += 1;
+            }
+        }
+        if let Some(draft) = sink.as_deref_mut() {
+            write_all_checked(draft, &bucket_brigade_buffer[..kept_length])?;
+        }
+    }
+
+    Ok(skipped_count)
+}
+
+// ============================
+// Endianness Swap
+// ============================
+
+/// Reverses the byte order of every `word_size`-byte word in a range.
+///
+/// # Overview
+/// Converts big-endian dumps to little-endian (or back) in place: with
+/// `word_size == 4`, `11 22 33 44` becomes `44 33 22 11`. Words are counted
+/// from `byte_position_from_start`. Chunks are the bucket-brigade buffer
+/// size rounded down to whole words (at least one), so no word straddles
+/// two chunks whatever buffer size is configured.
+///
+/// # Verification
+/// Independently of the swap, every draft byte at word offset `i` is
+/// compared to the original byte at word offset `word_size - 1 - i`; bytes
+/// outside the range must be identical and the length unchanged.
+///
+/// # Parameters
+/// - `byte_count`: Range length; must be a non-zero multiple of `word_size`
+/// - `word_size`: 2, 4, or 8
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` (`InvalidInput`) for an unsupported word size, a
+///   range that is empty, not word-aligned, or past EOF; any workflow error
+///   otherwise
+pub fn byteswap_range(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    word_size: usize,
+) -> io::Result<()> {
+    if !matches!(word_size, 2 | 4 | 8) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Word size {} not supported (use 2, 4, or 8)", word_size),
+        ));
+    }
+    if byte_count == 0 || !byte_count.is_multiple_of(word_size as u64) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Range length {} is not a non-zero multiple of the word size {}",
+                byte_count, word_size
+            ),
+        ));
+    }
+
+    let original_file_size = validate_target_file(&original_file_path)?;
+    let range_end_exclusive = checked_range_end(byte_position_from_start, byte_count)?;
+    if range_end_exclusive > original_file_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Byte range {}..{} exceeds file size {}",
+                byte_position_from_start, range_end_exclusive, original_file_size
+            ),
+        ));
+    }
+    let post_range_byte_count = original_file_size - range_end_exclusive;
+    let swap_chunk_size = (active_buffer_size() / word_size).max(1) * word_size;
+
+    run_draft_workflow(
+        &original_file_path,
+        "Byte Swap",
+        original_file_size,
+        |source_file, draft_file| {
+            copy_exact_bytes(source_file, draft_file, byte_position_from_start)?;
+
+            let mut bucket_brigade_buffer = vec![0u8; swap_chunk_size];
+            let mut bytes_swapped: u64 = 0;
+            let chunk_limit = chunk_limit_for_chunk_size(byte_count, swap_chunk_size);
+            let mut chunk_number: usize = 0;
+            while bytes_swapped < byte_count {
+                check_chunk_limit(chunk_number, chunk_limit)?;
+                chunk_number += 1;
+
+                let bytes_to_read =
+                    chunk_length(bucket_brigade_buffer.len(), byte_count - bytes_swapped);
+                source_file.read_exact(&mut bucket_brigade_buffer[..bytes_to_read])?;
+                for word in bucket_brigade_buffer[..bytes_to_read].chunks_exact_mut(word_size) {
+                    word.reverse();
+                }
+                write_all_checked(draft_file, &bucket_brigade_buffer[..bytes_to_read])?;
+                bytes_swapped += bytes_to_read as u64;
+            }
+
+            copy_remaining_bytes(source_file, draft_file)?;
+            Ok(())
+        },
+        |original_path, draft_path| {
+            let mut original_file = File::open(original_path)?;
+            let mut draft_file = File::open(draft_path)?;
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                byte_position_from_start,
+                0,
+                0,
+                "Pre-range",
+            )?;
+
+            let mut original_buffer = vec![0u8; swap_chunk_size];
+            let mut draft_buffer = vec![0u8; swap_chunk_size];
+            let mut bytes_verified: u64 = 0;
+            let chunk_limit = chunk_limit_for_chunk_size(byte_count, swap_chunk_size);
+            let mut chunk_number: usize = 0;
+            while bytes_verified < byte_count {
+                check_chunk_limit(chunk_number, chunk_limit)?;
+                chunk_number += 1;
+
+                let bytes_to_read = chunk_length(draft_buffer.len(), byte_count - bytes_verified);
+                original_file.read_exact(&mut original_buffer[..bytes_to_read])?;
+                draft_file.read_exact(&mut draft_buffer[..bytes_to_read])?;
+                for i in 0..bytes_to_read {
+                    let word_start = i - i % word_size;
+                    let mirrored_index = word_start + word_size - 1 - i % word_size;
+                    if draft_buffer[i] != original_buffer[mirrored_index] {
+                        return Err(ByteOpError::verification_failed(
+                            VerificationPhase::WrittenBytes,
+                            byte_position_from_start + bytes_verified + i as u64,
+                            u64::from(original_buffer[mirrored_index]),
+                            u64::from(draft_buffer[i]),
+                        )
+                        .into());
+                    }
+                }
+                bytes_verified += bytes_to_read as u64;
+            }
+
+            verify_next_bytes_match(
+                &mut original_file,
+                &mut draft_file,
+                post_range_byte_count,
+                range_end_exclusive,
+                range_end_exclusive,
+                "Post-range",
+            )?;
+
+            verify_both_at_eof(&mut original_file, &mut draft_file)
+        },
+    )
+}
+
+/// Like `byteswap_range`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn byteswap_range_with_config(
+    original_file_path: PathBuf,
+    byte_position_from_start: u64,
+    byte_count: u64,
+    word_size: usize,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        byteswap_range(
+            original_file_path,
+            byte_position_from_start,
+            byte_count,
+            word_size,
+        )
+    })
+}
+
+// ============================
+// User-Supplied Chunk Transform
+// ============================
+
+/// Streams the whole file through a caller-supplied chunk transform.
+///
+/// # Overview
+/// For custom length-preserving transforms (masking, tokenizing, ...)
+/// without forking the engine. During draft construction every
+/// bucket-brigade chunk is passed to `transform_chunk(chunk, chunk_offset)`,
+/// where `chunk_offset` is the chunk's position in the file; the transform
+/// edits the chunk in place.
+///
+/// # Verification
+/// The engine cannot know what the transform should produce, so the caller
+/// states it: the finished draft must have the original length and its
+/// SHA-256 must equal `expected_draft_sha256` (computed independently, e.g.
+/// from a reference implementation or a previous run). On mismatch the
+/// original is left untouched.
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)` on digest mismatch or if any phase fails
+pub fn apply_chunk_transform<TransformChunk>(
+    original_file_path: PathBuf,
+    mut transform_chunk: TransformChunk,
+    expected_draft_sha256: [u8; 32],
+) -> io::Result<()>
+where
+    TransformChunk: FnMut(&mut [u8], u64),
+{
+    let original_file_size = validate_target_file(&original_file_path)?;
+
+    run_draft_workflow(
+        &original_file_path,
+        "Chunk Transform",
+        original_file_size,
+        |source_file, draft_file| {
+            let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+            let mut chunk_offset: u64 = 0;
+            let chunk_limit = chunk_limit_for(original_file_size);
+            let mut chunk_number: usize = 0;
+            loop {
+                check_chunk_limit(chunk_number, chunk_limit)?;
+                chunk_number += 1;
+
+                let bytes_read = source_file.read(&mut bucket_brigade_buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                transform_chunk(&mut bucket_brigade_buffer[..bytes_read], chunk_offset);
+                write_all_checked(draft_file, &bucket_brigade_buffer[..bytes_read])?;
+                chunk_offset += bytes_read as u64;
+            }
+            Ok(())
+        },
+        |_original_path, draft_path| {
+            let draft_sha256 = sha256_of_file(draft_path)?;
+            if draft_sha256 != expected_draft_sha256 {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::ContentDigest,
+                    0,
+                    digest_leading_u64(&expected_draft_sha256),
+                    digest_leading_u64(&draft_sha256),
+                )
+                .into());
+            }
+            report_check_passed!("   ✓ Draft SHA-256 matches expected digest");
+            Ok(())
+        },
+    )
+}
+
+/// Like `apply_chunk_transform`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn apply_chunk_transform_with_config<TransformChunk>(
+    original_file_path: PathBuf,
+    transform_chunk: TransformChunk,
+    expected_draft_sha256: [u8; 32],
+    config: &OperationConfig,
+) -> io::Result<()>
+where
+    TransformChunk: FnMut(&mut [u8], u64),
+{
+    with_operation_config(config, || {
+        apply_chunk_transform(original_file_path, transform_chunk, expected_draft_sha256)
+    })
+}
+
+// =========================================
+// Shared Verification Helpers
+// =========================================
+
+/// Compares two files with every `ignored_byte` removed from both.
+///
+/// # Returns
+/// - `Ok((count_in_original, count_in_draft))` of the ignored byte if the
+///   remaining byte sequences are identical
+/// - `Err(io::Error)` carrying `ByteOpError::VerificationFailed` otherwise:
+///   `CopiedRange` at the first differing draft byte, or `TotalLength` with
+///   both files' lengths without the ignored byte
+fn verify_equal_ignoring_byte(
+    original_path: &Path,
+    draft_path: &Path,
+    ignored_byte: u8,
+) -> io::Result<(u64, u64)> {
+    let mut original_stream = FilteredByteStream::open(original_path, ignored_byte)?;
+    let mut draft_stream = FilteredByteStream::open(draft_path, ignored_byte)?;
+    let mut compared_bytes: u64 = 0;
+    let buffer_length = original_stream.buffer.len() as u64;
+
+    loop {
+        let chunk_number = usize::try_from(compared_bytes / buffer_length).unwrap_or(usize::MAX);
+        check_chunk_limit(chunk_number, original_stream.chunk_limit)?;
+        let original_next = original_stream.next_byte()?;
+        let draft_next = draft_stream.next_byte()?;
+        match (original_next, draft_next) {
+            (None, None) => break,
+            (original_byte, draft_byte) if original_byte == draft_byte => compared_bytes += 1,
+            (Some(original_byte), Some(draft_byte)) => {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::CopiedRange,
+                    draft_stream.position - 1,
+                    u64::from(original_byte),
+                    u64::from(draft_byte),
+                )
+                .into());
+            }
+            (original_byte, draft_byte) => {
+                let original_length = compared_bytes
+                    + u64::from(original_byte.is_some())
+                    + original_stream.remaining_count()?;
+                let draft_length = compared_bytes
+                    + u64::from(draft_byte.is_some())
+                    + draft_stream.remaining_count()?;
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::TotalLength,
+                    0,
+                    original_length,
+                    draft_length,
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok((original_stream.ignored_count, draft_stream.ignored_count))
+}
+
+/// Buffered byte reader that skips (and counts) one byte value.
+struct FilteredByteStream {
+    source_file: File,
+    buffer: Vec<u8>,
+    buffer_length: usize,
+    buffer_index: usize,
+    /// Raw position of the last byte returned
+    position: u64,
+    ignored_byte: u8,
+    ignored_count: u64,
+    /// Refill bound, from the file size at open
+    chunk_limit: usize,
+}
+
+impl FilteredByteStream {
+    fn open(file_path: &Path, ignored_byte: u8) -> io::Result<Self> {
+        let source_file = File::open(file_path)?;
+        let chunk_limit = chunk_limit_for(source_file.metadata()?.len());
+        Ok(FilteredByteStream {
+            source_file,
+            buffer: vec![0u8; active_buffer_size()],
+            buffer_length: 0,
+            buffer_index: 0,
+            position: 0,
+            ignored_byte,
+            ignored_count: 0,
+            chunk_limit,
+        })
+    }
+
+    /// Next byte that is not the ignored value, or `None` at EOF.
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut refill_count: usize = 0;
+        loop {
+            if self.buffer_index == self.buffer_length {
+                check_chunk_limit(refill_count, self.chunk_limit)?;
+                refill_count += 1;
+                self.buffer_length = self.source_file.read(&mut self.buffer)?;
+                self.buffer_index = 0;
+                if self.buffer_length == 0 {
+                    return Ok(None);
+                }
+            }
+            let byte_value = self.buffer[self.buffer_index];
+            self.buffer_index += 1;
+            self.position += 1;
+            if byte_value == self.ignored_byte {
+                self.ignored_count += 1;
+            } else {
+                return Ok(Some(byte_value));
+            }
+        }
+    }
+
+    /// Number of bytes left that are not the ignored value.
+    fn remaining_count(&mut self) -> io::Result<u64> {
+        let mut remaining_count: u64 = 0;
+        while self.next_byte()?.is_some() {
+            remaining_count += 1;
+        }
+        Ok(remaining_count)
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation_config::{OperationConfig, with_operation_config};
+
+    #[test]
+    fn test_remove_all_bytes_with_value() {
+        let test_file = std::env::temp_dir().join("test_remove_all_bytes_with_value.bin");
+        let mut original_bytes: Vec<u8> = (0u8..100).collect();
+        original_bytes.extend([0x0D; 40]);
+        original_bytes.push(0x41);
+        std::fs::write(&test_file, &original_bytes).expect("create test file");
+
+        let report = remove_all_bytes_with_value(test_file.clone(), 0x0D).expect("strip CR");
+        assert_eq!(report.removed_count, 41);
+        assert_eq!(report.new_size, original_bytes.len() as u64 - 41);
+        let expected_bytes: Vec<u8> = original_bytes
+            .iter()
+            .copied()
+            .filter(|&byte| byte != 0x0D)
+            .collect();
+        assert_eq!(
+            std::fs::read(&test_file).expect("read stripped file"),
+            expected_bytes
+        );
+
+        // Nothing left to remove: untouched, zero count
+        let report = remove_all_bytes_with_value(test_file.clone(), 0x0D).expect("no-op");
+        assert_eq!(report.removed_count, 0);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_byteswap_range_words() {
+        let test_file = std::env::temp_dir().join("test_byteswap_range.bin");
+        // 2 header bytes, 72 bytes of 4-byte words (crosses a chunk boundary), 1 tail byte
+        let mut original_bytes = vec![0xAA, 0xBB];
+        original_bytes.extend((0u8..72).collect::<Vec<u8>>());
+        original_bytes.push(0xCC);
+        std::fs::write(&test_file, &original_bytes).expect("create test file");
+
+        byteswap_range(test_file.clone(), 2, 72, 4).expect("swap 32-bit words");
+        let swapped = std::fs::read(&test_file).expect("read swapped file");
+        assert_eq!(&swapped[..6], &[0xAA, 0xBB, 3, 2, 1, 0]);
+        assert_eq!(&swapped[70..], &[71, 70, 69, 68, 0xCC]);
+
+        // Swapping twice restores the original
+        byteswap_range(test_file.clone(), 2, 72, 4).expect("swap back");
+        assert_eq!(
+            std::fs::read(&test_file).expect("read file"),
+            original_bytes
+        );
+
+        // A buffer size that is not a multiple of the word size still keeps
+        // words whole
+        let odd_buffer_config = OperationConfig::builder()
+            .buffer_size(7)
+            .build()
+            .expect("valid config");
+        with_operation_config(&odd_buffer_config, || {
+            byteswap_range(test_file.clone(), 2, 72, 8)
+        })
+        .expect("swap 64-bit words with a 7-byte buffer");
+        let swapped = std::fs::read(&test_file).expect("read swapped file");
+        assert_eq!(&swapped[2..10], &[7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(&swapped[66..], &[71, 70, 69, 68, 67, 66, 65, 64, 0xCC]);
+
+        assert!(byteswap_range(test_file.clone(), 2, 6, 4).is_err());
+        assert!(byteswap_range(test_file.clone(), 2, 6, 3).is_err());
+        assert!(byteswap_range(test_file.clone(), 70, 8, 8).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_convert_line_endings_round_trip() {
+        let test_file = std::env::temp_dir().join("test_line_endings_round_trip.txt");
+        // CRLF split across the 64-byte chunk boundary, a lone CR, and a trailing CR
+        let mut original_text = "a".repeat(63);
+        original_text.push_str("\r\nline2\nlone\rcr\r\n\r\nend\r");
+        std::fs::write(&test_file, &original_text).expect("create test file");
+
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("valid config");
+        let to_lf = with_operation_config(&tiny_config, || {
+            convert_line_endings(test_file.clone(), LineEndingTarget::ToLf)
+        })
+        .expect("conversion should succeed");
+        assert_eq!(to_lf.line_endings_converted, 3);
+        let expected_lf = original_text.replace("\r\n", "\n");
+        assert_eq!(
+            std::fs::read_to_string(&test_file).expect("read"),
+            expected_lf
+        );
+        assert_eq!(to_lf.new_size, expected_lf.len() as u64);
+
+        let to_crlf = convert_line_endings(test_file.clone(), LineEndingTarget::ToCrlf)
+            .expect("conversion should succeed");
+        assert_eq!(to_crlf.line_endings_converted, 4);
+        assert_eq!(
+            std::fs::read_to_string(&test_file).expect("read"),
+            expected_lf.replace('\n', "\r\n")
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_convert_line_endings_noop_leaves_file_untouched() {
+        let test_file = std::env::temp_dir().join("test_line_endings_noop.txt");
+        std::fs::write(&test_file, "already\nlf\n").expect("create test file");
+
+        let report = convert_line_endings(test_file.clone(), LineEndingTarget::ToLf)
+            .expect("conversion should succeed");
+        assert_eq!(report.line_endings_converted, 0);
+        assert_eq!(report.new_size, report.original_size);
+
+        // Direction-named form of the same request
+        let report = convert_line_endings(test_file.clone(), LineEnding::CrlfToLf)
+            .expect("conversion should succeed");
+        assert_eq!(report.target, LineEndingTarget::ToLf);
+        assert_eq!(report.line_endings_converted, 0);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_add_and_subtract_constant_over_range() {
+        let test_file = std::env::temp_dir().join("test_add_constant_range.bin");
+        let test_data: Vec<u8> = (0..=255u8).cycle().take(300).collect();
+        std::fs::write(&test_file, &test_data).expect("create test file");
+
+        assert!(add_constant_to_range(test_file.clone(), 100, 150, 0x10).is_ok());
+        let modified_data = std::fs::read(&test_file).expect("read modified file");
+        assert_eq!(modified_data[..100], test_data[..100]);
+        assert_eq!(modified_data[250..], test_data[250..]);
+        for i in 100..250 {
+            assert_eq!(modified_data[i], test_data[i].wrapping_add(0x10));
+        }
+
+        // Subtracting the same constant restores the original (wraps at 0xF0..)
+        assert!(subtract_constant_from_range(test_file.clone(), 100, 150, 0x10).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("read restored file"),
+            test_data
+        );
+
+        assert!(
+            transform_byte_range(test_file.clone(), 290, 20, ByteRangeTransform::XorWith(1))
+                .is_err()
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_translate_bytes_upper_cases_range() {
+        let test_file = std::env::temp_dir().join("test_translate_bytes.txt");
+        std::fs::write(&test_file, b"keep this; shout this part; keep").expect("create test file");
+
+        let mut table = identity_translation_table();
+        for lower_case in b'a'..=b'z' {
+            table[lower_case as usize] = lower_case.to_ascii_uppercase();
+        }
+        assert!(translate_bytes(test_file.clone(), 11, 15, &table).is_ok());
+        assert_eq!(
+            std::fs::read(&test_file).expect("read modified file"),
+            b"keep this; SHOUT THIS PART; keep"
+        );
+
+        assert!(translate_bytes(test_file.clone(), 30, 10, &table).is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_apply_chunk_transform_checks_expected_digest() {
+        let test_file = std::env::temp_dir().join("test_apply_chunk_transform.txt");
+        let original_text: Vec<u8> = b"card 4111111111111111 exp 12/30 "
+            .iter()
+            .copied()
+            .cycle()
+            .take(200)
+            .collect();
+        std::fs::write(&test_file, &original_text).expect("create test file");
+
+        // Mask every ASCII digit
+        let mask_digits = |chunk: &mut [u8], _chunk_offset: u64| {
+            for byte_value in chunk.iter_mut() {
+                if byte_value.is_ascii_digit() {
+                    *byte_value = b'#';
+                }
+            }
+        };
+        let mut expected_text = original_text.clone();
+        mask_digits(&mut expected_text, 0);
+        let expected_sha256 = crate::hash::sha256::sha256_of_bytes(&expected_text);
+
+        // Wrong digest: rejected, original untouched
+        assert!(apply_chunk_transform(test_file.clone(), mask_digits, [0u8; 32]).is_err());
+        assert_eq!(std::fs::read(&test_file).expect("read file"), original_text);
+
+        // Offsets are file positions: only the first 100 bytes masked
+        let mut offsets_seen = Vec::new();
+        let mut expected_half = original_text.clone();
+        mask_digits(&mut expected_half[..100], 0);
+        let partial_mask = |chunk: &mut [u8], chunk_offset: u64| {
+            offsets_seen.push(chunk_offset);
+            for (index, byte_value) in chunk.iter_mut().enumerate() {
+                if chunk_offset + (index as u64) < 100 && byte_value.is_ascii_digit() {
+                    *byte_value = b'#';
+                }
+            }
+        };
+        let tiny_config = OperationConfig::builder()
+            .tiny_buffer()
+            .build()
+            .expect("valid config");
+        assert!(
+            with_operation_config(&tiny_config, || apply_chunk_transform(
+                test_file.clone(),
+                partial_mask,
+                crate::hash::sha256::sha256_of_bytes(&expected_half)
+            ))
+            .is_ok()
+        );
+        assert_eq!(offsets_seen, vec![0, 64, 128, 192]);
+
+        std::fs::write(&test_file, &original_text).expect("reset test file");
+        assert!(apply_chunk_transform(test_file.clone(), mask_digits, expected_sha256).is_ok());
+        assert_eq!(std::fs::read(&test_file).expect("read file"), expected_text);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
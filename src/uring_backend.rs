@@ -0,0 +1,598 @@
+//! `io_uring` backend for draft copies and verification (Linux, feature
+//! `io-uring`)
+//!
+//! The synchronous path moves one bucket-brigade chunk at a time: each read
+//! waits for the previous one, and the disk idles while a chunk is written
+//! or compared. With the `io-uring` feature, the two loops that touch every
+//! byte keep `URING_QUEUE_DEPTH` chunks in flight instead:
+//! - copying the unmodified stretches of a draft (`copy_exact_bytes`,
+//!   `copy_remaining_bytes`): positioned reads of the source, each chunk
+//!   written to the draft as soon as it arrives
+//! - comparing original and draft (`verify_next_bytes_match`): reads of
+//!   both files are queued ahead and compared in order, with the same
+//!   chunk size, checksums and messages as the synchronous loop
+//!
+//! It applies to stretches of at least `URING_MIN_BYTE_COUNT` bytes whose
+//! handles are plain files (the handles of a draft build,
+//! `verify_ranges_equal`). Anything else, an `O_DIRECT` draft, and kernels
+//! or seccomp policies without `io_uring` use the synchronous path, as does
+//! everything inside `with_io_uring_disabled`; `cargo bench --features
+//! io-uring` compares the two.
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom, Write},
+    os::fd::AsRawFd,
+};
+
+use io_uring::{IoUring, opcode, squeue, types};
+
+use crate::reporter::report_detail;
+use crate::util::chunk_length;
+
+/// Chunks in flight per pipeline (per file when comparing two)
+pub const URING_QUEUE_DEPTH: usize = 8;
+
+/// Smallest stretch handed to the ring; shorter ones are not worth
+/// giving up the reader's buffer for
+pub const URING_MIN_BYTE_COUNT: u64 = 1024 * 1024;
+
+thread_local! {
+    /// This thread's ring, set up on first use
+    static RING: RefCell<RingState> = const { RefCell::new(RingState::Untried) };
+    /// Set inside `with_io_uring_disabled`
+    static IO_URING_DISABLED: Cell<bool> = const { Cell::new(false) };
+    /// Set when a pipeline leaked its buffers because its operations could
+    /// not be waited for; the ring may still complete them
+    static RING_ABANDONED: Cell<bool> = const { Cell::new(false) };
+}
+
+enum RingState {
+    Untried,
+    /// Setup failed, or an operation could not be drained; never retried
+    Unavailable,
+    Ready(Box<IoUring>),
+}
+
+/// Runs `operations` with the synchronous read/write path, as if the
+/// `io-uring` feature were off.
+pub fn with_io_uring_disabled<T>(operations: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    let was_disabled = IO_URING_DISABLED.with(|disabled| disabled.replace(true));
+    let result = operations();
+    IO_URING_DISABLED.with(|disabled| disabled.set(was_disabled));
+    result
+}
+
+/// Runs `run` on this thread's ring; `None` if there is none.
+fn with_ring<T>(run: impl FnOnce(&mut IoUring) -> io::Result<T>) -> Option<io::Result<T>> {
+    if IO_URING_DISABLED.with(Cell::get) {
+        return None;
+    }
+    RING.with(|ring_state| {
+        let mut ring_state = ring_state.borrow_mut();
+        if let RingState::Untried = *ring_state {
+            *ring_state = match IoUring::new((URING_QUEUE_DEPTH * 2) as u32) {
+                Ok(ring) => RingState::Ready(Box::new(ring)),
+                Err(e) => {
+                    report_detail!("io_uring unavailable ({}); using synchronous I/O", e);
+                    RingState::Unavailable
+                }
+            };
+        }
+        let RingState::Ready(ring) = &mut *ring_state else {
+            return None;
+        };
+        let result = run(ring);
+        if RING_ABANDONED.with(|abandoned| abandoned.replace(false)) {
+            *ring_state = RingState::Unavailable;
+        }
+        Some(result)
+    })
+}
+
+/// Buffers with operations in flight on a ring.
+///
+/// The kernel writes into the buffers until each operation completes, so
+/// dropping a pipeline first waits for everything still in flight (an
+/// error return included). If even that fails, the buffers are leaked
+/// rather than freed under the kernel, and the ring is retired.
+struct Pipeline<'ring> {
+    ring: &'ring mut IoUring,
+    buffers: Vec<Vec<u8>>,
+    in_flight: usize,
+}
+
+impl<'ring> Pipeline<'ring> {
+    fn new(ring: &'ring mut IoUring, buffer_count: usize, buffer_size: usize) -> Self {
+        Pipeline {
+            ring,
+            buffers: vec![vec![0u8; buffer_size]; buffer_count],
+            in_flight: 0,
+        }
+    }
+
+    /// Queues a read of `file` at `file_offset` into
+    /// `buffers[buffer_index][start..end]`.
+    fn read(
+        &mut self,
+        buffer_index: usize,
+        file: &File,
+        file_offset: u64,
+        start: usize,
+        end: usize,
+    ) -> io::Result<()> {
+        let target = &mut self.buffers[buffer_index][start..end];
+        let entry = opcode::Read::new(
+            types::Fd(file.as_raw_fd()),
+            target.as_mut_ptr(),
+            target.len() as u32,
+        )
+        .offset(file_offset)
+        .build()
+        .user_data(buffer_index as u64);
+        self.push(entry)
+    }
+
+    /// Queues a write of `buffers[buffer_index][start..end]` to `file` at
+    /// `file_offset`.
+    fn write(
+        &mut self,
+        buffer_index: usize,
+        file: &File,
+        file_offset: u64,
+        start: usize,
+        end: usize,
+    ) -> io::Result<()> {
+        let source = &self.buffers[buffer_index][start..end];
+        let entry = opcode::Write::new(
+            types::Fd(file.as_raw_fd()),
+            source.as_ptr(),
+            source.len() as u32,
+        )
+        .offset(file_offset)
+        .build()
+        .user_data(buffer_index as u64);
+        self.push(entry)
+    }
+
+    fn push(&mut self, entry: squeue::Entry) -> io::Result<()> {
+        // SAFETY: the buffer and the descriptor outlive the operation:
+        // `Drop` waits for every operation before the buffers go, and the
+        // callers' files outlive the pipeline
+        if unsafe { self.ring.submission().push(&entry) }.is_err() {
+            self.ring.submit()?;
+            // SAFETY: as above
+            unsafe { self.ring.submission().push(&entry) }
+                .map_err(|_| io::Error::other("io_uring submission queue full"))?;
+        }
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    /// Waits for the next completion: which buffer, and the byte count.
+    fn complete(&mut self) -> io::Result<(usize, io::Result<usize>)> {
+        loop {
+            if let Some(completion) = self.ring.completion().next() {
+                self.in_flight -= 1;
+                let result = completion.result();
+                let byte_count = if result < 0 {
+                    Err(io::Error::from_raw_os_error(-result))
+                } else {
+                    Ok(result as usize)
+                };
+                return Ok((completion.user_data() as usize, byte_count));
+            }
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for Pipeline<'_> {
+    fn drop(&mut self) {
+        while self.in_flight > 0 {
+            if self.complete().is_err() {
+                std::mem::forget(std::mem::take(&mut self.buffers));
+                RING_ABANDONED.with(|abandoned| abandoned.set(true));
+                return;
+            }
+        }
+    }
+}
+
+/// Per-chunk state of a copy: the chunk's place in the stretch, and how
+/// much of it has been read and written so far
+#[derive(Clone, Copy)]
+struct CopySlot {
+    stretch_offset: u64,
+    length: usize,
+    bytes_read: usize,
+    bytes_written: usize,
+}
+
+/// Copies `byte_count` bytes of `source` at `source_offset` to `draft` at
+/// `draft_offset`.
+fn copy_positioned(
+    ring: &mut IoUring,
+    source: &File,
+    source_offset: u64,
+    draft: &File,
+    draft_offset: u64,
+    byte_count: u64,
+    chunk_size: usize,
+) -> io::Result<()> {
+    let mut pipeline = Pipeline::new(ring, URING_QUEUE_DEPTH, chunk_size);
+    let mut slots: Vec<Option<CopySlot>> = vec![None; URING_QUEUE_DEPTH];
+    let mut next_stretch_offset: u64 = 0;
+
+    loop {
+        for (slot_index, slot) in slots.iter_mut().enumerate() {
+            if slot.is_none() && next_stretch_offset < byte_count {
+                let length = chunk_length(chunk_size, byte_count - next_stretch_offset);
+                pipeline.read(
+                    slot_index,
+                    source,
+                    source_offset + next_stretch_offset,
+                    0,
+                    length,
+                )?;
+                *slot = Some(CopySlot {
+                    stretch_offset: next_stretch_offset,
+                    length,
+                    bytes_read: 0,
+                    bytes_written: 0,
+                });
+                next_stretch_offset += length as u64;
+            }
+        }
+        if pipeline.in_flight == 0 {
+            return Ok(());
+        }
+
+        let (slot_index, completed) = pipeline.complete()?;
+        let byte_count_done = completed?;
+        let Some(slot) = &mut slots[slot_index] else {
+            return Err(io::Error::other("io_uring completion for an idle slot"));
+        };
+        // Each slot has one operation in flight: a write while read bytes
+        // are unwritten, otherwise a read
+        if slot.bytes_written < slot.bytes_read {
+            if byte_count_done == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            slot.bytes_written += byte_count_done;
+        } else {
+            if byte_count_done == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Source ended early during io_uring copy",
+                ));
+            }
+            slot.bytes_read += byte_count_done;
+        }
+
+        let slot_state = *slot;
+        if slot_state.bytes_written < slot_state.bytes_read {
+            pipeline.write(
+                slot_index,
+                draft,
+                draft_offset + slot_state.stretch_offset + slot_state.bytes_written as u64,
+                slot_state.bytes_written,
+                slot_state.bytes_read,
+            )?;
+        } else if slot_state.bytes_read < slot_state.length {
+            pipeline.read(
+                slot_index,
+                source,
+                source_offset + slot_state.stretch_offset + slot_state.bytes_read as u64,
+                slot_state.bytes_read,
+                slot_state.length,
+            )?;
+        } else {
+            slots[slot_index] = None;
+        }
+    }
+}
+
+/// Appends up to `byte_limit` bytes from `source` (at its position) to
+/// `draft` (at its position); fewer means the source ended.
+///
+/// # Returns
+/// - `None`: no ring, or too few bytes to be worth it
+/// - `Some(Ok(bytes))` with both positions advanced past the copy
+pub(crate) fn copy_to_draft(
+    source: &mut BufReader<File>,
+    draft: &mut File,
+    byte_limit: u64,
+    chunk_size: usize,
+) -> Option<io::Result<u64>> {
+    if byte_limit < URING_MIN_BYTE_COUNT {
+        return None;
+    }
+    with_ring(|ring| {
+        // What the reader already buffered goes first
+        let buffered_length = chunk_length(source.buffer().len(), byte_limit);
+        draft.write_all(&source.buffer()[..buffered_length])?;
+        source.consume(buffered_length);
+        let mut bytes_copied = buffered_length as u64;
+        if bytes_copied == byte_limit {
+            return Ok(bytes_copied);
+        }
+
+        // The reader's buffer is empty now: its file position is its own
+        let source_offset = source.get_mut().stream_position()?;
+        let source_length = source.get_ref().metadata()?.len();
+        let byte_count =
+            (byte_limit - bytes_copied).min(source_length.saturating_sub(source_offset));
+        let draft_offset = draft.stream_position()?;
+        copy_positioned(
+            ring,
+            source.get_ref(),
+            source_offset,
+            draft,
+            draft_offset,
+            byte_count,
+            chunk_size,
+        )?;
+        source.seek(SeekFrom::Start(source_offset + byte_count))?;
+        draft.seek(SeekFrom::Start(draft_offset + byte_count))?;
+        bytes_copied += byte_count;
+        Ok(bytes_copied)
+    })
+}
+
+/// Receives each pair of chunks (original, draft) in order
+type CompareChunk<'a> = dyn FnMut(&[u8], &[u8]) -> io::Result<()> + 'a;
+
+/// A reader the comparison can address by offset
+enum FileReader<'a> {
+    Plain(&'a mut File),
+    Buffered(&'a mut BufReader<File>),
+}
+
+impl<'a> FileReader<'a> {
+    fn downcast(reader: &'a mut dyn Any) -> Option<FileReader<'a>> {
+        if reader.is::<File>() {
+            return reader.downcast_mut::<File>().map(FileReader::Plain);
+        }
+        reader
+            .downcast_mut::<BufReader<File>>()
+            .map(FileReader::Buffered)
+    }
+
+    fn file(&self) -> &File {
+        match self {
+            FileReader::Plain(file) => file,
+            FileReader::Buffered(reader) => reader.get_ref(),
+        }
+    }
+
+    /// Logical position (a `BufReader` accounts for its buffer).
+    fn position(&mut self) -> io::Result<u64> {
+        match self {
+            FileReader::Plain(file) => file.stream_position(),
+            FileReader::Buffered(reader) => reader.stream_position(),
+        }
+    }
+
+    fn set_position(&mut self, position: u64) -> io::Result<()> {
+        match self {
+            FileReader::Plain(file) => file.seek(SeekFrom::Start(position)),
+            FileReader::Buffered(reader) => reader.seek(SeekFrom::Start(position)),
+        }
+        .map(|_| ())
+    }
+}
+
+/// Per-chunk state of a comparison: where the chunk is, and how much of
+/// it each file has delivered (`[original, draft]`)
+#[derive(Clone, Copy)]
+struct CompareSlot {
+    stretch_offset: u64,
+    length: usize,
+    bytes_read: [usize; 2],
+}
+
+/// Hands `compare_chunk` the next `byte_count` bytes of `original` and
+/// `draft`, in chunks of `chunk_size`, in order, and advances both readers
+/// past them.
+///
+/// # Returns
+/// - `None`: not two plain files, no ring, or too few bytes
+/// - `Some(result)`: `UnexpectedEof` if either file ends early, or the
+///   first error of `compare_chunk`
+pub(crate) fn compare_readers(
+    original: &mut dyn Any,
+    draft: &mut dyn Any,
+    byte_count: u64,
+    chunk_size: usize,
+    compare_chunk: &mut CompareChunk<'_>,
+) -> Option<io::Result<()>> {
+    if byte_count < URING_MIN_BYTE_COUNT {
+        return None;
+    }
+    let mut original = FileReader::downcast(original)?;
+    let mut draft = FileReader::downcast(draft)?;
+    with_ring(|ring| {
+        let original_offset = original.position()?;
+        let draft_offset = draft.position()?;
+        compare_positioned(
+            ring,
+            [original.file(), draft.file()],
+            [original_offset, draft_offset],
+            byte_count,
+            chunk_size,
+            compare_chunk,
+        )?;
+        original.set_position(original_offset + byte_count)?;
+        draft.set_position(draft_offset + byte_count)
+    })
+}
+
+/// Compares `byte_count` bytes of `files[0]` and `files[1]` from
+/// `offsets`. Slot `i` owns buffers `2 * i` (original) and `2 * i + 1`
+/// (draft); chunk `k` uses slot `k % URING_QUEUE_DEPTH`.
+fn compare_positioned(
+    ring: &mut IoUring,
+    files: [&File; 2],
+    offsets: [u64; 2],
+    byte_count: u64,
+    chunk_size: usize,
+    compare_chunk: &mut CompareChunk<'_>,
+) -> io::Result<()> {
+    let mut pipeline = Pipeline::new(ring, URING_QUEUE_DEPTH * 2, chunk_size);
+    let mut slots: Vec<Option<CompareSlot>> = vec![None; URING_QUEUE_DEPTH];
+    let mut next_stretch_offset: u64 = 0;
+    let mut next_chunk_to_start: usize = 0;
+    let mut next_chunk_to_compare: usize = 0;
+
+    loop {
+        // Keep every idle slot reading its next chunk
+        while next_stretch_offset < byte_count
+            && slots[next_chunk_to_start % URING_QUEUE_DEPTH].is_none()
+        {
+            let slot_index = next_chunk_to_start % URING_QUEUE_DEPTH;
+            let length = chunk_length(chunk_size, byte_count - next_stretch_offset);
+            for side in 0..2 {
+                pipeline.read(
+                    slot_index * 2 + side,
+                    files[side],
+                    offsets[side] + next_stretch_offset,
+                    0,
+                    length,
+                )?;
+            }
+            slots[slot_index] = Some(CompareSlot {
+                stretch_offset: next_stretch_offset,
+                length,
+                bytes_read: [0, 0],
+            });
+            next_stretch_offset += length as u64;
+            next_chunk_to_start += 1;
+        }
+
+        // Compare the oldest chunk once both halves are in
+        let head_index = next_chunk_to_compare % URING_QUEUE_DEPTH;
+        match slots[head_index] {
+            None => return Ok(()),
+            Some(slot) if slot.bytes_read == [slot.length, slot.length] => {
+                let (original_buffers, draft_buffers) =
+                    pipeline.buffers.split_at(head_index * 2 + 1);
+                compare_chunk(
+                    &original_buffers[head_index * 2][..slot.length],
+                    &draft_buffers[0][..slot.length],
+                )?;
+                slots[head_index] = None;
+                next_chunk_to_compare += 1;
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let (buffer_index, completed) = pipeline.complete()?;
+        let byte_count_read = completed?;
+        let (slot_index, side) = (buffer_index / 2, buffer_index % 2);
+        let Some(slot) = &mut slots[slot_index] else {
+            return Err(io::Error::other("io_uring completion for an idle slot"));
+        };
+        if byte_count_read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        slot.bytes_read[side] += byte_count_read;
+        let slot_state = *slot;
+        if slot_state.bytes_read[side] < slot_state.length {
+            pipeline.read(
+                buffer_index,
+                files[side],
+                offsets[side] + slot_state.stretch_offset + slot_state.bytes_read[side] as u64,
+                slot_state.bytes_read[side],
+                slot_state.length,
+            )?;
+        }
+    }
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, io::Read};
+
+    #[test]
+    fn test_uring_copy_and_compare_match_synchronous_path() {
+        let source_path = std::env::temp_dir().join("test_uring_source.bin");
+        let draft_path = std::env::temp_dir().join("test_uring_source.bin.draft");
+        let content: Vec<u8> = (0..3_000_017u32).map(|value| (value % 241) as u8).collect();
+        fs::write(&source_path, &content).expect("write source");
+
+        // Copy from a partly consumed reader, after bytes already written
+        let mut source = BufReader::new(File::open(&source_path).expect("open source"));
+        let mut head = [0u8; 5];
+        source.read_exact(&mut head).expect("read head");
+        let mut draft = File::create(&draft_path).expect("create draft");
+        draft.write_all(b"HEAD").expect("write head");
+        let Some(copied) = copy_to_draft(&mut source, &mut draft, 10_000_000, 64 * 1024) else {
+            // No io_uring on this kernel: nothing to compare
+            let _ = fs::remove_file(&source_path);
+            let _ = fs::remove_file(&draft_path);
+            return;
+        };
+        assert_eq!(copied.expect("uring copy"), (content.len() - 5) as u64);
+        assert_eq!(source.read(&mut head).expect("read at end"), 0);
+        draft.write_all(b"TAIL").expect("write tail");
+        drop(draft);
+        let mut expected = b"HEAD".to_vec();
+        expected.extend_from_slice(&content[5..]);
+        expected.extend_from_slice(b"TAIL");
+        assert_eq!(fs::read(&draft_path).expect("read draft"), expected);
+
+        // Chunks arrive in order and both readers end up past them
+        let mut original = BufReader::new(File::open(&source_path).expect("open"));
+        let mut draft = File::open(&draft_path).expect("open draft");
+        original.read_exact(&mut head).expect("skip head");
+        draft.seek(SeekFrom::Start(4)).expect("skip draft head");
+        let mut compared = Vec::new();
+        compare_readers(
+            &mut original,
+            &mut draft,
+            2_000_000,
+            64 * 1024,
+            &mut |original_chunk, draft_chunk| {
+                assert_eq!(original_chunk, draft_chunk);
+                compared.extend_from_slice(original_chunk);
+                Ok(())
+            },
+        )
+        .expect("ring available")
+        .expect("compare");
+        assert_eq!(compared, content[5..2_000_005]);
+        assert_eq!(original.stream_position().expect("position"), 2_000_005);
+        assert_eq!(draft.stream_position().expect("position"), 2_000_004);
+
+        // Running past the end of a file is an early EOF
+        let error = compare_readers(
+            &mut original,
+            &mut draft,
+            2_000_000,
+            64 * 1024,
+            &mut |_, _| Ok(()),
+        )
+        .expect("ring available")
+        .expect_err("past the end");
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let _ = fs::remove_file(&source_path);
+        let _ = fs::remove_file(&draft_path);
+    }
+}
@@ -0,0 +1,363 @@
+//! UTF-8 aware character operations
+//!
+//! Byte offsets are the wrong unit for editing text: replacing "byte 10" of
+//! a UTF-8 file can split a multi-byte character and leave the file
+//! undecodable. These operations address characters by index, decode only as
+//! much of the stream as needed to find them, and route the change through
+//! the verified splice pipeline (same-length replace, or grow/shrink splice).
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{check_chunk_limit, chunk_limit_for, open_at_offset};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::operation_config::{OperationConfig, active_buffer_size, with_operation_config};
+use crate::range_operations::splice_byte_range_with_draft_check;
+use crate::reporter::report_check_passed;
+
+/// Byte location of one character in a UTF-8 file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8CharLocation {
+    pub byte_offset: u64,
+    /// Encoded length in bytes (1-4)
+    pub encoded_length: usize,
+}
+
+/// Replaces the character at `char_index` with `new_char`.
+///
+/// # Overview
+/// 1. Stream-decode the file up to the target character, validating every
+///    sequence on the way (an invalid file is rejected, not "repaired")
+/// 2. Splice: remove the old character's bytes, insert `new_char`'s encoding.
+///    Encoded lengths may differ (e.g. `e` → `é` grows by one byte); the
+///    splice verifies the resulting frame-shift
+/// 3. Before the rename, check that the draft decodes cleanly in the
+///    affected window: the new bytes decode to exactly `new_char`, and the
+///    following byte (if any) starts a new character
+///
+/// # Parameters
+/// - `original_file_path`: Path to a UTF-8 text file
+/// - `char_index`: Zero-indexed character (not byte) position
+/// - `new_char`: Replacement character
+///
+/// # Returns
+/// - `Ok(())` on success
+/// - `Err(io::Error)`: `InvalidData` if the file is not valid UTF-8 up to
+///   and including the target character, `InvalidInput` if `char_index` is
+///   past the last character
+pub fn replace_char_at(
+    original_file_path: PathBuf,
+    char_index: u64,
+    new_char: char,
+) -> io::Result<()> {
+    let location = locate_utf8_char(&original_file_path, char_index)?;
+
+    let mut encode_buffer = [0u8; 4];
+    let new_char_bytes = new_char.encode_utf8(&mut encode_buffer).as_bytes().to_vec();
+    let replacement_start = location.byte_offset;
+    let replacement_length = new_char_bytes.len();
+
+    splice_byte_range_with_draft_check(
+        original_file_path,
+        location.byte_offset,
+        location.encoded_length as u64,
+        &new_char_bytes,
+        "UTF-8 Character Replacement",
+        |draft_path| {
+            verify_utf8_window(draft_path, replacement_start, replacement_length, new_char)
+        },
+    )
+}
+
+/// Like `replace_char_at`, using `config` instead of the active (or default)
+/// `OperationConfig`.
+pub fn replace_char_at_with_config(
+    original_file_path: PathBuf,
+    char_index: u64,
+    new_char: char,
+    config: &OperationConfig,
+) -> io::Result<()> {
+    with_operation_config(config, || {
+        replace_char_at(original_file_path, char_index, new_char)
+    })
+}
+
+/// Finds the byte offset and length of the `char_index`-th character.
+pub fn locate_utf8_char(file_path: &Path, char_index: u64) -> io::Result<Utf8CharLocation> {
+    let mut source_file = File::open(file_path)?;
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+
+    let mut byte_offset: u64 = 0;
+    let mut current_char_index: u64 = 0;
+    // Continuation bytes still expected for the current character
+    let mut continuation_bytes_remaining: usize = 0;
+    let mut current_char_start: u64 = 0;
+    let mut current_char_length: usize = 0;
+    let chunk_limit = chunk_limit_for(source_file.metadata()?.len());
+    let mut chunk_number: usize = 0;
+
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_read = source_file.read(&mut bucket_brigade_buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        for &byte_value in &bucket_brigade_buffer[..bytes_read] {
+            if continuation_bytes_remaining > 0 {
+                if byte_value & 0xC0 != 0x80 {
+                    return Err(invalid_utf8(byte_offset));
+                }
+                continuation_bytes_remaining -= 1;
+            } else {
+                let sequence_length =
+                    utf8_sequence_length(byte_value).ok_or_else(|| invalid_utf8(byte_offset))?;
+                current_char_start = byte_offset;
+                current_char_length = sequence_length;
+                continuation_bytes_remaining = sequence_length - 1;
+            }
+            byte_offset += 1;
+
+            if continuation_bytes_remaining == 0 {
+                if current_char_index == char_index {
+                    // Final check: overlong/surrogate forms are rejected by std
+                    let mut char_bytes = vec![0u8; current_char_length];
+                    open_at_offset(file_path, current_char_start)?.read_exact(&mut char_bytes)?;
+                    if std::str::from_utf8(&char_bytes).is_err() {
+                        return Err(invalid_utf8(current_char_start));
+                    }
+                    return Ok(Utf8CharLocation {
+                        byte_offset: current_char_start,
+                        encoded_length: current_char_length,
+                    });
+                }
+                current_char_index += 1;
+            }
+        }
+    }
+
+    if continuation_bytes_remaining > 0 {
+        return Err(invalid_utf8(current_char_start));
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "Character index {} is past the end ({} characters)",
+            char_index, current_char_index
+        ),
+    ))
+}
+
+/// Checks that a whole file is valid UTF-8, streaming it in small chunks.
+///
+/// Uses the standard library decoder on each chunk, so overlong forms and
+/// surrogates are rejected too. A sequence split across a chunk boundary is
+/// carried into the next chunk.
+///
+/// # Returns
+/// - `Ok(())` if every byte belongs to a complete, valid sequence
+/// - `Err(io::Error)` with `InvalidData` naming the first bad byte offset
+pub fn validate_utf8_file(file_path: &Path) -> io::Result<()> {
+    let mut source_file = File::open(file_path)?;
+    // Room for one chunk plus up to 3 bytes of an unfinished sequence
+    let chunk_size = active_buffer_size();
+    let mut decode_buffer = vec![0u8; chunk_size + 3];
+    let mut carried_length: usize = 0;
+    let mut buffer_start_offset: u64 = 0;
+    let chunk_limit = chunk_limit_for(source_file.metadata()?.len());
+    let mut chunk_number: usize = 0;
+
+    loop {
+        check_chunk_limit(chunk_number, chunk_limit)?;
+        chunk_number += 1;
+
+        let bytes_read =
+            source_file.read(&mut decode_buffer[carried_length..carried_length + chunk_size])?;
+        if bytes_read == 0 {
+            break;
+        }
+        let filled_length = carried_length + bytes_read;
+
+        carried_length = match std::str::from_utf8(&decode_buffer[..filled_length]) {
+            Ok(_) => 0,
+            // Truncated sequence at the end of the chunk: carry it over
+            Err(error) if error.error_len().is_none() => filled_length - error.valid_up_to(),
+            Err(error) => {
+                return Err(invalid_utf8(
+                    buffer_start_offset + error.valid_up_to() as u64,
+                ));
+            }
+        };
+        let consumed_length = filled_length - carried_length;
+        decode_buffer.copy_within(consumed_length..filled_length, 0);
+        buffer_start_offset += consumed_length as u64;
+    }
+
+    if carried_length > 0 {
+        return Err(invalid_utf8(buffer_start_offset));
+    }
+    Ok(())
+}
+
+/// Checks the draft around a replaced character decodes cleanly.
+fn verify_utf8_window(
+    draft_path: &Path,
+    replacement_start: u64,
+    replacement_length: usize,
+    expected_char: char,
+) -> io::Result<()> {
+    let mut window_source = open_at_offset(draft_path, replacement_start)?;
+    let mut window_bytes = [0u8; 5];
+    let window_length = read_up_to(
+        &mut window_source,
+        &mut window_bytes[..replacement_length + 1],
+    )?;
+
+    let decoded_text = std::str::from_utf8(&window_bytes[..replacement_length])
+        .map_err(|_| invalid_utf8(replacement_start))?;
+    if decoded_text.chars().collect::<Vec<char>>() != [expected_char] {
+        // Compared as code points
+        let decoded_char = decoded_text.chars().next().unwrap_or('\0');
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::WrittenBytes,
+            replacement_start,
+            u64::from(u32::from(expected_char)),
+            u64::from(u32::from(decoded_char)),
+        )
+        .into());
+    }
+
+    // The next byte, if any, must begin a character
+    if window_length > replacement_length && window_bytes[replacement_length] & 0xC0 == 0x80 {
+        return Err(invalid_utf8(replacement_start + replacement_length as u64));
+    }
+
+    report_check_passed!(
+        "   ✓ UTF-8 window decodes cleanly at byte {}",
+        replacement_start
+    );
+
+    Ok(())
+}
+
+/// Reads until the buffer is full or EOF; returns bytes read.
+fn read_up_to<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut total_bytes_read: usize = 0;
+    while total_bytes_read < buffer.len() {
+        let bytes_read = reader.read(&mut buffer[total_bytes_read..])?;
+        if bytes_read == 0 {
+            break;
+        }
+        total_bytes_read += bytes_read;
+    }
+    Ok(total_bytes_read)
+}
+
+/// Length of a UTF-8 sequence from its lead byte, or `None` if not a lead byte.
+fn utf8_sequence_length(lead_byte: u8) -> Option<usize> {
+    match lead_byte {
+        0x00..=0x7F => Some(1),
+        0xC2..=0xDF => Some(2),
+        0xE0..=0xEF => Some(3),
+        0xF0..=0xF4 => Some(4),
+        _ => None,
+    }
+}
+
+fn invalid_utf8(byte_offset: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid UTF-8 at byte {}", byte_offset),
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_char_at_changes_encoded_length() {
+        let test_file = std::env::temp_dir().join("test_utf8_replace_char.txt");
+        std::fs::write(&test_file, "naïve café ✓").expect("create test file");
+
+        // 1-byte → 2-byte, 2-byte → 1-byte, 3-byte → 4-byte
+        assert!(replace_char_at(test_file.clone(), 0, 'ñ').is_ok());
+        assert!(replace_char_at(test_file.clone(), 2, 'i').is_ok());
+        assert!(replace_char_at(test_file.clone(), 11, '🦀').is_ok());
+
+        assert_eq!(
+            std::fs::read_to_string(&test_file).expect("still valid UTF-8"),
+            "ñaive café 🦀"
+        );
+
+        assert!(replace_char_at(test_file.clone(), 12, 'x').is_err());
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_replace_char_rejects_invalid_utf8() {
+        let test_file = std::env::temp_dir().join("test_utf8_replace_invalid.txt");
+        std::fs::write(&test_file, [b'a', 0xC3, b'b', b'c']).expect("create test file");
+
+        assert!(replace_char_at(test_file.clone(), 0, 'z').is_ok());
+        assert_eq!(
+            replace_char_at(test_file.clone(), 2, 'z')
+                .expect_err("invalid sequence")
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_utf8_guard_blocks_split_sequence() {
+        use crate::operation_config::OperationConfig;
+        use crate::range_operations::{
+            replace_byte_range_in_file, replace_byte_range_in_file_with_config,
+        };
+
+        let test_file = std::env::temp_dir().join("test_utf8_guard.txt");
+        std::fs::write(&test_file, "café ✓").expect("create test file");
+
+        // Overwriting the first byte of 'é' would leave a stray continuation byte
+        let guarded_config = OperationConfig::builder()
+            .utf8_guard(true)
+            .build()
+            .expect("valid config");
+        let error =
+            replace_byte_range_in_file_with_config(test_file.clone(), 3, b"e", &guarded_config)
+                .expect_err("guard rejects split sequence");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            std::fs::read_to_string(&test_file).expect("original untouched"),
+            "café ✓"
+        );
+
+        // A valid edit passes the guard; without the guard the bad edit commits
+        assert!(
+            replace_byte_range_in_file_with_config(test_file.clone(), 0, b"C", &guarded_config)
+                .is_ok()
+        );
+        assert!(validate_utf8_file(&test_file).is_ok());
+        assert!(replace_byte_range_in_file(test_file.clone(), 3, b"e").is_ok());
+        assert_eq!(
+            validate_utf8_file(&test_file)
+                .expect_err("stray byte")
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}
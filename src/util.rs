@@ -0,0 +1,111 @@
+//! Small helpers shared by the single-byte operations and the draft workflow
+
+use std::io;
+
+/// Converts a file offset or length to `usize`, for indexing a buffer.
+///
+/// File positions are `u64` everywhere; this is the one checked crossing
+/// into memory. Fails (instead of truncating) when the value does not fit,
+/// e.g. an offset past 4 GiB on a 32-bit target.
+pub(crate) fn offset_to_usize(value: u64) -> io::Result<usize> {
+    usize::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Offset {} does not fit in memory on this platform", value),
+        )
+    })
+}
+
+/// Bytes to move in the next bucket-brigade chunk: the whole buffer, or
+/// fewer when less than a buffer's worth remains.
+pub(crate) fn chunk_length(buffer_length: usize, bytes_remaining: u64) -> usize {
+    usize::try_from(bytes_remaining).map_or(buffer_length, |remaining| remaining.min(buffer_length))
+}
+
+/// Bytes compared per slice-equality step of `first_mismatch`
+const MISMATCH_SCAN_BLOCK_SIZE: usize = 4096;
+
+/// Index of the first byte where `first` and `second` differ, comparing
+/// up to the shorter length; `None` if that much is equal.
+///
+/// Verification compares every byte of a file, so this runs slice equality
+/// (`memcmp`, vectorized by the platform) over blocks of
+/// `MISMATCH_SCAN_BLOCK_SIZE` and only walks the first unequal block byte
+/// by byte.
+pub(crate) fn first_mismatch(first: &[u8], second: &[u8]) -> Option<usize> {
+    let compared_length = first.len().min(second.len());
+    let (first, second) = (&first[..compared_length], &second[..compared_length]);
+    if first == second {
+        return None;
+    }
+    first
+        .chunks(MISMATCH_SCAN_BLOCK_SIZE)
+        .zip(second.chunks(MISMATCH_SCAN_BLOCK_SIZE))
+        .enumerate()
+        .find(|(_, (first_block, second_block))| first_block != second_block)
+        .and_then(|(block_index, (first_block, second_block))| {
+            first_block
+                .iter()
+                .zip(second_block)
+                .position(|(first_byte, second_byte)| first_byte != second_byte)
+                .map(|index| block_index * MISMATCH_SCAN_BLOCK_SIZE + index)
+        })
+}
+
+/// Computes a simple checksum for a byte slice (for verification purposes)
+///
+/// Uses a basic XOR-based checksum for speed and simplicity.
+/// This is sufficient for integrity checking, not cryptographic security.
+pub fn compute_simple_checksum(bytes: &[u8]) -> u64 {
+    let mut checksum: u64 = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        // Mix position and value to detect transpositions
+        checksum ^= (byte as u64).rotate_left((i % 64) as u32);
+        checksum = checksum.wrapping_add(byte as u64);
+    }
+    checksum
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_length_caps_at_buffer_and_remaining() {
+        assert_eq!(chunk_length(64, 10), 10);
+        assert_eq!(chunk_length(64, 64), 64);
+        assert_eq!(chunk_length(64, 5 * 1024 * 1024 * 1024), 64);
+        assert_eq!(chunk_length(64, u64::MAX), 64);
+    }
+
+    #[test]
+    fn test_first_mismatch_finds_earliest_difference() {
+        let original: Vec<u8> = (0..20_000u32).map(|value| value as u8).collect();
+        assert_eq!(first_mismatch(&original, &original), None);
+        assert_eq!(first_mismatch(&[], &[]), None);
+
+        for position in [0, 1, 4095, 4096, 12_345, 19_999] {
+            let mut changed = original.clone();
+            changed[position] ^= 0x80;
+            // A later difference does not hide the first one
+            changed[19_999] ^= 0x01;
+            assert_eq!(first_mismatch(&original, &changed), Some(position));
+        }
+
+        // Only the common prefix is compared
+        assert_eq!(first_mismatch(&original[..10], &original[..5]), None);
+    }
+
+    #[test]
+    fn test_offset_to_usize_checks_range() {
+        assert_eq!(offset_to_usize(4096).expect("fits"), 4096);
+        if usize::BITS < 64 {
+            let error = offset_to_usize(u64::MAX).expect_err("should not fit");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Parsing of offsets and hex byte values from text
+//!
+//! Shared by the CLI and by text formats (plan files, recipes) so that an
+//! offset or byte means the same thing everywhere it can be written.
+
+use std::io;
+
+/// Parses a decimal or `0x`-prefixed hexadecimal offset.
+pub(crate) fn parse_offset_text(token: &str) -> io::Result<u64> {
+    let parsed = match token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+    {
+        Some(hex_digits) => u64::from_str_radix(hex_digits, 16),
+        None => token.parse::<u64>(),
+    };
+    parsed.map_err(|_| invalid_input(format!("Invalid offset: {}", token)))
+}
+
+/// Parses a single byte given as hex (`FF`, `0xFF`).
+pub(crate) fn parse_hex_byte_text(token: &str) -> io::Result<u8> {
+    let bytes = parse_hex_bytes_text(token)?;
+    if bytes.len() != 1 {
+        return Err(invalid_input(format!(
+            "Expected exactly one hex byte, got: {}",
+            token
+        )));
+    }
+    Ok(bytes[0])
+}
+
+/// Parses a hex byte string (`DEADBEEF`, `0xDEADBEEF`, `de ad be ef` as one token).
+pub(crate) fn parse_hex_bytes_text(token: &str) -> io::Result<Vec<u8>> {
+    let hex_digits: String = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+        .unwrap_or(token)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    if hex_digits.is_empty() || !hex_digits.len().is_multiple_of(2) {
+        return Err(invalid_input(format!(
+            "Hex bytes must be a non-empty, even number of digits: {}",
+            token
+        )));
+    }
+
+    (0..hex_digits.len())
+        .step_by(2)
+        .map(|start| {
+            u8::from_str_radix(&hex_digits[start..start + 2], 16)
+                .map_err(|_| invalid_input(format!("Invalid hex bytes: {}", token)))
+        })
+        .collect()
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
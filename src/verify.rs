@@ -0,0 +1,938 @@
+//! Draft-vs-original verification for the single-byte operations
+//!
+//! Each check compares a finished draft against the untouched original
+//! before the draft is allowed to replace it: total length, pre-position
+//! similarity, the at-position change, and post-position similarity with
+//! the operation's frame-shift (0 for replace, -1 for remove, +1 for insert).
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::operation_config::{active_buffer_size, active_verification_hash};
+use crate::reporter::{report_check_passed, report_detail};
+use crate::util::{chunk_length, first_mismatch};
+
+// =================
+// Replace-Byte Check
+// =================
+
+/// Performs comprehensive verification of a byte replacement operation.
+///
+/// # Verification Steps
+/// 1. **Total byte length check**: Ensures file sizes match exactly
+/// 2. **Pre-position similarity**: Verifies all bytes before edit position are identical
+/// 3. **At-position verification**: Two-part check:
+///    - Check if new value equals old value (edge case warning)
+///    - Verify draft has the correct new byte value
+/// 4. **Post-position similarity**: Verifies all bytes after edit position are identical
+///
+/// # Parameters
+/// - `original_path`: Path to the original file
+/// - `modified_path`: Path to the modified file (draft)
+/// - `byte_position`: Position where byte was replaced
+/// - `expected_old_byte`: The original byte value that should have been replaced
+/// - `expected_new_byte`: The new byte value that should be at the position
+///
+/// # Returns
+/// - `Ok(())` if all verifications pass
+/// - `Err(io::Error)` if any verification fails
+pub fn verify_byte_replacement_operation(
+    original_path: &Path,
+    modified_path: &Path,
+    byte_position: u64,
+    expected_old_byte: u8,
+    expected_new_byte: u8,
+) -> io::Result<()> {
+    report_detail!("\n=== Comprehensive Verification Phase ===");
+
+    // =========================================
+    // Step 1: Total Byte Length Check
+    // =========================================
+    report_detail!("1. Verifying total byte length...");
+
+    let original_metadata = fs::metadata(original_path)?;
+    let modified_metadata = fs::metadata(modified_path)?;
+    let original_size = original_metadata.len();
+    let modified_size = modified_metadata.len();
+
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    debug_assert_eq!(
+        original_size, modified_size,
+        "File sizes must match for in-place edit"
+    );
+
+    #[cfg(test)]
+    {
+        assert_eq!(
+            original_size, modified_size,
+            "File sizes must match for in-place edit"
+        );
+    }
+
+    if original_size != modified_size {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            original_size,
+            modified_size,
+        )
+        .into());
+    }
+
+    report_check_passed!("   ✓ File sizes match: {} bytes", original_size);
+
+    // Open both files for reading
+    let mut original_file = File::open(original_path)?;
+    let mut modified_file = File::open(modified_path)?;
+
+    // =========================================
+    // Step 2: Pre-Position Similarity Check
+    // =========================================
+    if byte_position > 0 {
+        report_detail!(
+            "2. Verifying pre-position bytes (0 to {})...",
+            byte_position.saturating_sub(1)
+        );
+    } else {
+        report_detail!("2. Verifying pre-position bytes (none - position is 0)...");
+    }
+
+    if byte_position > 0 {
+        // Read and compare bytes before the edit position
+        let verification_buffer_size = active_buffer_size();
+        let mut original_buffer = vec![0u8; verification_buffer_size];
+        let mut modified_buffer = vec![0u8; verification_buffer_size];
+
+        let mut pre_position_original_digest = active_verification_hash().digest();
+        let mut pre_position_modified_digest = active_verification_hash().digest();
+        let mut bytes_verified: u64 = 0;
+
+        while bytes_verified < byte_position {
+            let bytes_to_read =
+                chunk_length(verification_buffer_size, byte_position - bytes_verified);
+
+            let original_bytes_read = original_file.read(&mut original_buffer[..bytes_to_read])?;
+            let modified_bytes_read = modified_file.read(&mut modified_buffer[..bytes_to_read])?;
+
+            // Verify same number of bytes read
+            if original_bytes_read != modified_bytes_read {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::PrePosition,
+                    bytes_verified,
+                    original_bytes_read as u64,
+                    modified_bytes_read as u64,
+                )
+                .into());
+            }
+
+            // Update digests
+            pre_position_original_digest.update(&original_buffer[..original_bytes_read]);
+            pre_position_modified_digest.update(&modified_buffer[..modified_bytes_read]);
+
+            // First differing byte of the pre-position bytes
+            if let Some(i) = first_mismatch(
+                &original_buffer[..original_bytes_read],
+                &modified_buffer[..original_bytes_read],
+            ) {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::PrePosition,
+                    bytes_verified + i as u64,
+                    u64::from(original_buffer[i]),
+                    u64::from(modified_buffer[i]),
+                )
+                .into());
+            }
+
+            bytes_verified += original_bytes_read as u64;
+        }
+
+        // Verify checksums match
+        let pre_position_original_checksum = pre_position_original_digest.value();
+        let pre_position_modified_checksum = pre_position_modified_digest.value();
+        if pre_position_original_checksum != pre_position_modified_checksum {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PrePositionChecksum,
+                0,
+                pre_position_original_checksum.leading_u64(),
+                pre_position_modified_checksum.leading_u64(),
+            )
+            .into());
+        }
+
+        report_check_passed!(
+            "   ✓ Pre-position bytes match ({})",
+            pre_position_original_checksum
+        );
+    } else {
+        report_check_passed!("   ✓ No pre-position bytes to verify (position is 0)");
+    }
+
+    // =========================================
+    // Step 3: At-Position Verification (Two-Part Check)
+    // =========================================
+    report_detail!("3. Verifying at-position byte change...");
+
+    let mut original_byte = [0u8; 1];
+    let mut modified_byte = [0u8; 1];
+
+    original_file.read_exact(&mut original_byte)?;
+    modified_file.read_exact(&mut modified_byte)?;
+
+    // Part 1: Verify original byte is what we expected
+    if original_byte[0] != expected_old_byte {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::AtPosition,
+            byte_position,
+            u64::from(expected_old_byte),
+            u64::from(original_byte[0]),
+        )
+        .into());
+    }
+
+    // Part 2: Verify modified byte is what we set
+    if modified_byte[0] != expected_new_byte {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::AtPosition,
+            byte_position,
+            u64::from(expected_new_byte),
+            u64::from(modified_byte[0]),
+        )
+        .into());
+    }
+
+    // // Edge case check: warn if old and new values are the same
+    // if original_byte[0] == modified_byte[0] {
+    //     #[cfg(debug_assertions)]
+    //     println!(
+    //         "   ⚠ Warning: New byte value (0x{:02X}) equals old byte value (operation is idempotent)",
+    //         original_byte[0]
+    //     );
+    // }
+
+    report_check_passed!(
+        "   ✓ At-position byte correctly changed: 0x{:02X} -> 0x{:02X}",
+        original_byte[0],
+        modified_byte[0]
+    );
+
+    // =========================================
+    // Step 4: Post-Position Similarity Check
+    // =========================================
+    if byte_position + 1 < original_size {
+        report_detail!(
+            "4. Verifying post-position bytes ({} to EOF)...",
+            byte_position + 1
+        );
+    } else {
+        report_detail!("4. Verifying post-position bytes (none - edit was at last byte)...");
+    }
+
+    let post_verification_buffer_size = active_buffer_size();
+    let mut original_post_buffer = vec![0u8; post_verification_buffer_size];
+    let mut modified_post_buffer = vec![0u8; post_verification_buffer_size];
+
+    let mut post_position_original_digest = active_verification_hash().digest();
+    let mut post_position_modified_digest = active_verification_hash().digest();
+    let mut post_bytes_verified: u64 = 0;
+
+    loop {
+        let original_bytes_read = original_file.read(&mut original_post_buffer)?;
+        let modified_bytes_read = modified_file.read(&mut modified_post_buffer)?;
+
+        // Both files should reach EOF at the same time
+        if original_bytes_read != modified_bytes_read {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PostPosition,
+                byte_position + 1 + post_bytes_verified,
+                original_bytes_read as u64,
+                modified_bytes_read as u64,
+            )
+            .into());
+        }
+
+        // Check if we've reached EOF
+        if original_bytes_read == 0 {
+            break;
+        }
+
+        // Update digests
+        post_position_original_digest.update(&original_post_buffer[..original_bytes_read]);
+        post_position_modified_digest.update(&modified_post_buffer[..modified_bytes_read]);
+
+        // First differing byte of the post-position bytes
+        if let Some(i) = first_mismatch(
+            &original_post_buffer[..original_bytes_read],
+            &modified_post_buffer[..original_bytes_read],
+        ) {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PostPosition,
+                byte_position + 1 + post_bytes_verified + i as u64,
+                u64::from(original_post_buffer[i]),
+                u64::from(modified_post_buffer[i]),
+            )
+            .into());
+        }
+
+        post_bytes_verified += original_bytes_read as u64;
+    }
+
+    // Verify post-position checksums match
+    let post_position_original_checksum = post_position_original_digest.value();
+    let post_position_modified_checksum = post_position_modified_digest.value();
+    if post_position_original_checksum != post_position_modified_checksum {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::PostPositionChecksum,
+            0,
+            post_position_original_checksum.leading_u64(),
+            post_position_modified_checksum.leading_u64(),
+        )
+        .into());
+    }
+
+    if post_bytes_verified > 0 {
+        report_check_passed!(
+            "   ✓ Post-position bytes match ({} bytes, {})",
+            post_bytes_verified,
+            post_position_original_checksum
+        );
+    } else {
+        report_check_passed!("   ✓ No post-position bytes (edit was at last byte)");
+    }
+
+    // =========================================
+    // Final Verification Summary
+    // =========================================
+    report_detail!("\n=== Verification Summary ===");
+    report_check_passed!("✓ Total byte length: VERIFIED ({} bytes)", original_size);
+    report_check_passed!("✓ Pre-position similarity: VERIFIED");
+    report_check_passed!("✓ At-position change: VERIFIED");
+    report_check_passed!("✓ Post-position similarity: VERIFIED (no frame-shift)");
+    report_detail!("All verification checks PASSED\n");
+
+    Ok(())
+}
+
+// ================
+// Remove-Byte Check
+// ================
+
+/// Performs comprehensive verification of a byte removal operation.
+///
+/// # Verification Steps
+/// 1. **Total byte length check**: Ensures draft is exactly 1 byte smaller than original
+/// 2. **Pre-position similarity**: Verifies all bytes before removal position are identical
+/// 3. **At-position dissimilarity**: Confirms byte at position has changed (is the next byte)
+/// 4. **Post-position similarity with -1 frame-shift**: Verifies remaining bytes match with shift
+///
+/// # Frame-Shift Verification
+/// After removing a byte at position N:
+/// - `draft[N] == original[N+1]` (the byte after removed byte shifts into its place)
+/// - `draft[N+1] == original[N+2]` (and so on...)
+/// - All bytes after position N in draft correspond to position N+1 in original
+///
+/// # Parameters
+/// - `original_path`: Path to the original file
+/// - `draft_path`: Path to the draft file with byte removed
+/// - `byte_position`: Position where byte was removed
+/// - `removed_byte_value`: The byte value that was removed (for logging)
+///
+/// # Returns
+/// - `Ok(())` if all verifications pass
+/// - `Err(io::Error)` if any verification fails
+pub fn verify_byte_removal_operation(
+    original_path: &Path,
+    draft_path: &Path,
+    byte_position: u64,
+    removed_byte_value: u8,
+) -> io::Result<()> {
+    report_detail!("\n=== Comprehensive Verification Phase ===");
+
+    // =========================================
+    // Step 1: Total Byte Length Check
+    // =========================================
+    report_detail!("1. Verifying total byte length...");
+
+    let original_metadata = fs::metadata(original_path)?;
+    let draft_metadata = fs::metadata(draft_path)?;
+    let original_size = original_metadata.len();
+    let draft_size = draft_metadata.len();
+
+    let expected_draft_size = original_size.saturating_sub(1);
+
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    debug_assert_eq!(
+        draft_size, expected_draft_size,
+        "Draft file must be exactly 1 byte smaller than original"
+    );
+
+    #[cfg(test)]
+    {
+        assert_eq!(
+            draft_size, expected_draft_size,
+            "Draft file must be exactly 1 byte smaller than original"
+        );
+    }
+
+    if draft_size != expected_draft_size {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            expected_draft_size,
+            draft_size,
+        )
+        .into());
+    }
+
+    report_check_passed!(
+        "   ✓ File sizes correct: original={} bytes, draft={} bytes (removed 1 byte)",
+        original_size,
+        draft_size
+    );
+
+    // Open both files for reading
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    // =========================================
+    // Step 2: Pre-Position Similarity Check
+    // =========================================
+    report_detail!(
+        "2. Verifying pre-position bytes (0 to {})...",
+        byte_position.saturating_sub(1)
+    );
+
+    if byte_position > 0 {
+        let verification_buffer_size = active_buffer_size();
+        let mut original_buffer = vec![0u8; verification_buffer_size];
+        let mut draft_buffer = vec![0u8; verification_buffer_size];
+
+        let mut pre_position_original_digest = active_verification_hash().digest();
+        let mut pre_position_draft_digest = active_verification_hash().digest();
+        let mut bytes_verified: u64 = 0;
+
+        while bytes_verified < byte_position {
+            let bytes_to_read =
+                chunk_length(verification_buffer_size, byte_position - bytes_verified);
+
+            let original_bytes_read = original_file.read(&mut original_buffer[..bytes_to_read])?;
+            let draft_bytes_read = draft_file.read(&mut draft_buffer[..bytes_to_read])?;
+
+            // Verify same number of bytes read
+            if original_bytes_read != draft_bytes_read {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::PrePosition,
+                    bytes_verified,
+                    original_bytes_read as u64,
+                    draft_bytes_read as u64,
+                )
+                .into());
+            }
+
+            // Update digests
+            pre_position_original_digest.update(&original_buffer[..original_bytes_read]);
+            pre_position_draft_digest.update(&draft_buffer[..draft_bytes_read]);
+
+            // First differing byte of the pre-position bytes
+            if let Some(i) = first_mismatch(
+                &original_buffer[..original_bytes_read],
+                &draft_buffer[..original_bytes_read],
+            ) {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::PrePosition,
+                    bytes_verified + i as u64,
+                    u64::from(original_buffer[i]),
+                    u64::from(draft_buffer[i]),
+                )
+                .into());
+            }
+
+            bytes_verified += original_bytes_read as u64;
+        }
+
+        // Verify checksums match
+        let pre_position_original_checksum = pre_position_original_digest.value();
+        let pre_position_draft_checksum = pre_position_draft_digest.value();
+        if pre_position_original_checksum != pre_position_draft_checksum {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PrePositionChecksum,
+                0,
+                pre_position_original_checksum.leading_u64(),
+                pre_position_draft_checksum.leading_u64(),
+            )
+            .into());
+        }
+
+        report_check_passed!(
+            "   ✓ Pre-position bytes match ({})",
+            pre_position_original_checksum
+        );
+    } else {
+        report_check_passed!("   ✓ No pre-position bytes to verify (position is 0)");
+    }
+
+    // =========================================
+    // Step 3: At-Position Verification (Frame-Shift Check)
+    // =========================================
+    report_detail!(
+        "3. Verifying byte removal and frame-shift at position {}...",
+        byte_position
+    );
+
+    // Read the byte that was removed from original
+    let mut original_removed_byte = [0u8; 1];
+    original_file.read_exact(&mut original_removed_byte)?;
+
+    // Part 1: Verify it matches what we expected to remove
+    if original_removed_byte[0] != removed_byte_value {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::AtPosition,
+            byte_position,
+            u64::from(removed_byte_value),
+            u64::from(original_removed_byte[0]),
+        )
+        .into());
+    }
+
+    // Part 2: Verify the frame-shift occurred correctly
+    // The byte now at position N in draft should be the byte that was at position N+1 in original
+    let mut draft_current_byte = [0u8; 1];
+
+    // Handle edge case: if we removed the last byte, draft has no more bytes
+    let draft_has_more_bytes = draft_file.read(&mut draft_current_byte)? == 1;
+
+    if draft_has_more_bytes {
+        // Read the next byte from original (the byte after the removed one)
+        let mut original_next_byte = [0u8; 1];
+        let original_has_next = original_file.read(&mut original_next_byte)? == 1;
+
+        if !original_has_next {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::TotalLength,
+                byte_position,
+                byte_position,
+                byte_position + 1,
+            )
+            .into());
+        }
+
+        // Verify: draft[N] == original[N+1]
+        if draft_current_byte[0] != original_next_byte[0] {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::FrameShift,
+                byte_position,
+                u64::from(original_next_byte[0]),
+                u64::from(draft_current_byte[0]),
+            )
+            .into());
+        }
+
+        report_check_passed!(
+            "   ✓ Byte removed: 0x{:02X} | Frame-shift verified: draft[{}]=0x{:02X} == original[{}]=0x{:02X}",
+            original_removed_byte[0],
+            byte_position,
+            draft_current_byte[0],
+            byte_position + 1,
+            original_next_byte[0]
+        );
+    } else {
+        report_check_passed!(
+            "   ✓ Byte removed: 0x{:02X} (was last byte in file)",
+            original_removed_byte[0]
+        );
+    }
+    // =========================================
+    // Step 4: Post-Position Similarity Check with -1 Frame-Shift
+    // =========================================
+    report_detail!("4. Verifying post-position bytes with -1 frame-shift...");
+
+    let post_verification_buffer_size = active_buffer_size();
+    let mut original_post_buffer = vec![0u8; post_verification_buffer_size];
+    let mut draft_post_buffer = vec![0u8; post_verification_buffer_size];
+
+    let mut post_position_original_digest = active_verification_hash().digest();
+    let mut post_position_draft_digest = active_verification_hash().digest();
+    let mut post_bytes_verified: u64 = 0;
+
+    // Note: We already read one byte from each file in Step 3
+    // Original file read position: byte_position + 2
+    // Draft file read position: byte_position + 1
+    // These are already correctly offset by the frame-shift
+
+    loop {
+        let original_bytes_read = original_file.read(&mut original_post_buffer)?;
+        let draft_bytes_read = draft_file.read(&mut draft_post_buffer)?;
+
+        // Both files should reach EOF at the same time (accounting for the removed byte)
+        if original_bytes_read != draft_bytes_read {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PostPosition,
+                byte_position + 1 + post_bytes_verified,
+                original_bytes_read as u64,
+                draft_bytes_read as u64,
+            )
+            .into());
+        }
+
+        // Check if we've reached EOF
+        if original_bytes_read == 0 {
+            break;
+        }
+
+        // Update digests
+        post_position_original_digest.update(&original_post_buffer[..original_bytes_read]);
+        post_position_draft_digest.update(&draft_post_buffer[..draft_bytes_read]);
+
+        // First differing byte of the post-position bytes (with frame-shift already in effect)
+        if let Some(i) = first_mismatch(
+            &original_post_buffer[..original_bytes_read],
+            &draft_post_buffer[..original_bytes_read],
+        ) {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PostPosition,
+                byte_position + 1 + post_bytes_verified + i as u64,
+                u64::from(original_post_buffer[i]),
+                u64::from(draft_post_buffer[i]),
+            )
+            .into());
+        }
+
+        post_bytes_verified += original_bytes_read as u64;
+    }
+
+    // Verify post-position checksums match
+    let post_position_original_checksum = post_position_original_digest.value();
+    let post_position_draft_checksum = post_position_draft_digest.value();
+    if post_position_original_checksum != post_position_draft_checksum {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::PostPositionChecksum,
+            0,
+            post_position_original_checksum.leading_u64(),
+            post_position_draft_checksum.leading_u64(),
+        )
+        .into());
+    }
+
+    if post_bytes_verified > 0 {
+        report_check_passed!(
+            "   ✓ Post-position bytes match with -1 frame-shift ({} bytes, {})",
+            post_bytes_verified,
+            post_position_original_checksum
+        );
+    } else {
+        report_check_passed!("   ✓ No post-position bytes (removal was at last byte)");
+    }
+
+    // =========================================
+    // Final Verification Summary
+    // =========================================
+    report_detail!("\n=== Verification Summary ===");
+    report_check_passed!(
+        "✓ Total byte length: VERIFIED (original={}, draft={}, -1 byte)",
+        original_size,
+        draft_size
+    );
+    report_check_passed!("✓ Pre-position similarity: VERIFIED");
+    report_check_passed!("✓ At-position dissimilarity: VERIFIED (byte removed)");
+    report_check_passed!("✓ Post-position similarity: VERIFIED (with -1 frame-shift)");
+    report_detail!("All verification checks PASSED\n");
+
+    Ok(())
+}
+
+// =============
+// Add-Byte Check
+// =============
+
+/// Performs comprehensive verification of a byte addition operation.
+///
+/// # Verification Steps
+/// 1. **Total byte length check**: Ensures draft is exactly 1 byte larger than original
+/// 2. **Pre-position similarity**: Verifies all bytes before insertion position are identical
+/// 3. **At-position verification**: Confirms the new byte was inserted correctly
+/// 4. **Post-position similarity with +1 frame-shift**: Verifies remaining bytes match with shift
+///
+/// # Frame-Shift Verification
+/// After adding a byte at position N:
+/// - `draft[N] == new_byte_value` (the inserted byte)
+/// - `draft[N+1] == original[N]` (first byte after insertion, shifted forward)
+/// - `draft[N+2] == original[N+1]` (second byte after insertion, shifted forward)
+/// - All bytes from position N onward in original are shifted +1 in draft
+///
+/// # Parameters
+/// - `original_path`: Path to the original file
+/// - `draft_path`: Path to the draft file with byte added
+/// - `byte_position`: Position where byte was inserted
+/// - `new_byte_value`: The byte value that was inserted
+///
+/// # Returns
+/// - `Ok(())` if all verifications pass
+/// - `Err(io::Error)` if any verification fails
+pub fn verify_byte_addition_operation(
+    original_path: &Path,
+    draft_path: &Path,
+    byte_position: u64,
+    new_byte_value: u8,
+) -> io::Result<()> {
+    report_detail!("\n=== Comprehensive Verification Phase ===");
+
+    // =========================================
+    // Step 1: Total Byte Length Check
+    // =========================================
+    report_detail!("1. Verifying total byte length...");
+
+    let original_metadata = fs::metadata(original_path)?;
+    let draft_metadata = fs::metadata(draft_path)?;
+    let original_size = original_metadata.len();
+    let draft_size = draft_metadata.len();
+
+    let expected_draft_size = original_size + 1;
+
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    debug_assert_eq!(
+        draft_size, expected_draft_size,
+        "Draft file must be exactly 1 byte larger than original"
+    );
+
+    #[cfg(test)]
+    {
+        assert_eq!(
+            draft_size, expected_draft_size,
+            "Draft file must be exactly 1 byte larger than original"
+        );
+    }
+
+    if draft_size != expected_draft_size {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::TotalLength,
+            0,
+            expected_draft_size,
+            draft_size,
+        )
+        .into());
+    }
+
+    report_check_passed!(
+        "   ✓ File sizes correct: original={} bytes, draft={} bytes (+1 byte)",
+        original_size,
+        draft_size
+    );
+
+    // Open both files for reading
+    let mut original_file = File::open(original_path)?;
+    let mut draft_file = File::open(draft_path)?;
+
+    // =========================================
+    // Step 2: Pre-Position Similarity Check
+    // =========================================
+    if byte_position > 0 {
+        report_detail!(
+            "2. Verifying pre-position bytes (0 to {})...",
+            byte_position.saturating_sub(1)
+        );
+    } else {
+        report_detail!("2. Verifying pre-position bytes (none - inserting at position 0)...");
+    }
+
+    if byte_position > 0 {
+        let verification_buffer_size = active_buffer_size();
+        let mut original_buffer = vec![0u8; verification_buffer_size];
+        let mut draft_buffer = vec![0u8; verification_buffer_size];
+
+        let mut pre_position_original_digest = active_verification_hash().digest();
+        let mut pre_position_draft_digest = active_verification_hash().digest();
+        let mut bytes_verified: u64 = 0;
+
+        while bytes_verified < byte_position {
+            let bytes_to_read =
+                chunk_length(verification_buffer_size, byte_position - bytes_verified);
+
+            let original_bytes_read = original_file.read(&mut original_buffer[..bytes_to_read])?;
+            let draft_bytes_read = draft_file.read(&mut draft_buffer[..bytes_to_read])?;
+
+            // Verify same number of bytes read
+            if original_bytes_read != draft_bytes_read {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::PrePosition,
+                    bytes_verified,
+                    original_bytes_read as u64,
+                    draft_bytes_read as u64,
+                )
+                .into());
+            }
+
+            // Update digests
+            pre_position_original_digest.update(&original_buffer[..original_bytes_read]);
+            pre_position_draft_digest.update(&draft_buffer[..draft_bytes_read]);
+
+            // First differing byte of the pre-position bytes
+            if let Some(i) = first_mismatch(
+                &original_buffer[..original_bytes_read],
+                &draft_buffer[..original_bytes_read],
+            ) {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::PrePosition,
+                    bytes_verified + i as u64,
+                    u64::from(original_buffer[i]),
+                    u64::from(draft_buffer[i]),
+                )
+                .into());
+            }
+
+            bytes_verified += original_bytes_read as u64;
+        }
+
+        // Verify checksums match
+        let pre_position_original_checksum = pre_position_original_digest.value();
+        let pre_position_draft_checksum = pre_position_draft_digest.value();
+        if pre_position_original_checksum != pre_position_draft_checksum {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PrePositionChecksum,
+                0,
+                pre_position_original_checksum.leading_u64(),
+                pre_position_draft_checksum.leading_u64(),
+            )
+            .into());
+        }
+
+        report_check_passed!(
+            "   ✓ Pre-position bytes match ({})",
+            pre_position_original_checksum
+        );
+    } else {
+        report_check_passed!("   ✓ No pre-position bytes to verify (inserting at position 0)");
+    }
+
+    // =========================================
+    // Step 3: At-Position Verification
+    // =========================================
+    report_detail!(
+        "3. Verifying byte insertion at position {}...",
+        byte_position
+    );
+
+    // Read the byte that should be the newly inserted byte in draft
+    let mut draft_inserted_byte = [0u8; 1];
+    draft_file.read_exact(&mut draft_inserted_byte)?;
+
+    // Verify it matches the byte we inserted
+    if draft_inserted_byte[0] != new_byte_value {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::AtPosition,
+            byte_position,
+            u64::from(new_byte_value),
+            u64::from(draft_inserted_byte[0]),
+        )
+        .into());
+    }
+
+    report_check_passed!(
+        "   ✓ Byte inserted correctly: draft[{}]=0x{:02X}",
+        byte_position,
+        draft_inserted_byte[0]
+    );
+
+    // =========================================
+    // Step 4: Post-Position Similarity Check with +1 Frame-Shift
+    // =========================================
+    if byte_position < original_size {
+        report_detail!("4. Verifying post-position bytes with +1 frame-shift...");
+    } else {
+        report_detail!("4. Verifying post-position bytes (none - inserted at EOF)...");
+    }
+
+    let post_verification_buffer_size = active_buffer_size();
+    let mut original_post_buffer = vec![0u8; post_verification_buffer_size];
+    let mut draft_post_buffer = vec![0u8; post_verification_buffer_size];
+
+    let mut post_position_original_digest = active_verification_hash().digest();
+    let mut post_position_draft_digest = active_verification_hash().digest();
+    let mut post_bytes_verified: u64 = 0;
+
+    // Note: After reading the inserted byte, draft file read position is at byte_position + 1
+    // Original file read position is at byte_position
+    // These are correctly offset for the +1 frame-shift
+
+    loop {
+        let original_bytes_read = original_file.read(&mut original_post_buffer)?;
+        let draft_bytes_read = draft_file.read(&mut draft_post_buffer)?;
+
+        // Both files should reach EOF at the same time (accounting for the inserted byte)
+        if original_bytes_read != draft_bytes_read {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PostPosition,
+                byte_position + 1 + post_bytes_verified,
+                original_bytes_read as u64,
+                draft_bytes_read as u64,
+            )
+            .into());
+        }
+
+        // Check if we've reached EOF
+        if original_bytes_read == 0 {
+            break;
+        }
+
+        // Update digests
+        post_position_original_digest.update(&original_post_buffer[..original_bytes_read]);
+        post_position_draft_digest.update(&draft_post_buffer[..draft_bytes_read]);
+
+        // First differing byte of the post-position bytes (with +1 frame-shift in effect)
+        if let Some(i) = first_mismatch(
+            &original_post_buffer[..original_bytes_read],
+            &draft_post_buffer[..original_bytes_read],
+        ) {
+            return Err(ByteOpError::verification_failed(
+                VerificationPhase::PostPosition,
+                byte_position + 1 + post_bytes_verified + i as u64,
+                u64::from(original_post_buffer[i]),
+                u64::from(draft_post_buffer[i]),
+            )
+            .into());
+        }
+
+        post_bytes_verified += original_bytes_read as u64;
+    }
+
+    // Verify post-position checksums match
+    let post_position_original_checksum = post_position_original_digest.value();
+    let post_position_draft_checksum = post_position_draft_digest.value();
+    if post_position_original_checksum != post_position_draft_checksum {
+        return Err(ByteOpError::verification_failed(
+            VerificationPhase::PostPositionChecksum,
+            0,
+            post_position_original_checksum.leading_u64(),
+            post_position_draft_checksum.leading_u64(),
+        )
+        .into());
+    }
+
+    if post_bytes_verified > 0 {
+        report_check_passed!(
+            "   ✓ Post-position bytes match with +1 frame-shift ({} bytes, {})",
+            post_bytes_verified,
+            post_position_original_checksum
+        );
+    } else {
+        report_check_passed!("   ✓ No post-position bytes (insertion was at EOF)");
+    }
+
+    // =========================================
+    // Final Verification Summary
+    // =========================================
+    report_detail!("\n=== Verification Summary ===");
+    report_check_passed!(
+        "✓ Total byte length: VERIFIED (original={}, draft={}, +1 byte)",
+        original_size,
+        draft_size
+    );
+    report_check_passed!("✓ Pre-position similarity: VERIFIED");
+    report_check_passed!("✓ At-position insertion: VERIFIED");
+    report_check_passed!("✓ Post-position similarity: VERIFIED (with +1 frame-shift)");
+    report_detail!("All verification checks PASSED\n");
+
+    Ok(())
+}
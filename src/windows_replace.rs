@@ -0,0 +1,142 @@
+//! Windows replacement of an existing file by its draft
+//!
+//! `fs::rename` over an existing file on Windows gives the result the
+//! draft's identity: the original's attributes (hidden, system, read-only),
+//! alternate data streams, ACL and creation time are lost, and a read-only
+//! original cannot be replaced at all. `ReplaceFileW` keeps them from the
+//! replaced file, so it is used whenever the original exists:
+//! 1. Read the original's attributes; clear a read-only flag on the
+//!    original and the draft (`ReplaceFileW` needs write access to one and
+//!    delete access to the other)
+//! 2. `ReplaceFileW(original, draft)`, retrying transient failures
+//! 3. Put the original's attributes back on the file that now has its name,
+//!    whether or not the replacement succeeded
+//!
+//! Antivirus scanners, indexers and sync clients briefly open files without
+//! sharing delete access, so a replacement can fail with a sharing or lock
+//! violation that clears within milliseconds. Those failures (and access
+//! denied, which a pending scan also produces) are retried a few times with
+//! a doubling pause before the error is returned.
+
+use std::{
+    ffi::c_void,
+    io, iter,
+    os::windows::ffi::OsStrExt,
+    path::Path,
+    ptr::{null, null_mut},
+    thread,
+    time::Duration,
+};
+
+const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+const INVALID_FILE_ATTRIBUTES: u32 = u32::MAX;
+const REPLACEFILE_IGNORE_MERGE_ERRORS: u32 = 0x2;
+
+const ERROR_ACCESS_DENIED: i32 = 5;
+const ERROR_SHARING_VIOLATION: i32 = 32;
+const ERROR_LOCK_VIOLATION: i32 = 33;
+
+/// Attempts at `ReplaceFileW` before a transient failure is returned
+const REPLACE_ATTEMPTS: u32 = 5;
+
+/// Pause after the first failed attempt; doubled after each further one
+const FIRST_RETRY_PAUSE: Duration = Duration::from_millis(10);
+
+#[link(name = "kernel32")]
+unsafe extern "system" {
+    fn ReplaceFileW(
+        replaced_file_name: *const u16,
+        replacement_file_name: *const u16,
+        backup_file_name: *const u16,
+        replace_flags: u32,
+        exclude: *mut c_void,
+        reserved: *mut c_void,
+    ) -> i32;
+    fn GetFileAttributesW(file_name: *const u16) -> u32;
+    fn SetFileAttributesW(file_name: *const u16, file_attributes: u32) -> i32;
+}
+
+/// Replaces the existing `original_file_path` with `draft_file_path`,
+/// keeping the original's attributes and alternate data streams.
+///
+/// # Returns
+/// - `Ok(())` once the draft has the original's name
+/// - `Err(io::Error)` from `ReplaceFileW` (after retries) or the attribute
+///   calls; the original keeps its attributes either way
+pub(crate) fn replace_file(original_file_path: &Path, draft_file_path: &Path) -> io::Result<()> {
+    let original_wide = to_wide_path(original_file_path);
+    let draft_wide = to_wide_path(draft_file_path);
+
+    let original_attributes = file_attributes(&original_wide)?;
+    let draft_attributes = file_attributes(&draft_wide)?;
+    if original_attributes & FILE_ATTRIBUTE_READONLY != 0 {
+        set_file_attributes(
+            &original_wide,
+            original_attributes & !FILE_ATTRIBUTE_READONLY,
+        )?;
+    }
+    if draft_attributes & FILE_ATTRIBUTE_READONLY != 0 {
+        set_file_attributes(&draft_wide, draft_attributes & !FILE_ATTRIBUTE_READONLY)?;
+    }
+
+    let mut retry_pause = FIRST_RETRY_PAUSE;
+    let mut attempt = 1;
+    let replace_result = loop {
+        // SAFETY: both paths are NUL-terminated UTF-16 buffers that outlive
+        // the call; the optional arguments are null as documented
+        let replaced = unsafe {
+            ReplaceFileW(
+                original_wide.as_ptr(),
+                draft_wide.as_ptr(),
+                null(),
+                REPLACEFILE_IGNORE_MERGE_ERRORS,
+                null_mut(),
+                null_mut(),
+            )
+        };
+        if replaced != 0 {
+            break Ok(());
+        }
+        let error = io::Error::last_os_error();
+        let transient = matches!(
+            error.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION | ERROR_ACCESS_DENIED)
+        );
+        if !transient || attempt == REPLACE_ATTEMPTS {
+            break Err(error);
+        }
+        thread::sleep(retry_pause);
+        retry_pause *= 2;
+        attempt += 1;
+    };
+
+    // The name now belongs to the draft on success, to the original
+    // otherwise; it gets the original's attributes in both cases
+    let restore_result = set_file_attributes(&original_wide, original_attributes);
+    replace_result.and(restore_result)
+}
+
+/// `path` as a NUL-terminated UTF-16 string for the wide Win32 calls.
+fn to_wide_path(path: &Path) -> Vec<u16> {
+    path.as_os_str()
+        .encode_wide()
+        .chain(iter::once(0))
+        .collect()
+}
+
+fn file_attributes(wide_path: &[u16]) -> io::Result<u32> {
+    // SAFETY: `wide_path` is NUL-terminated (see `to_wide_path`)
+    let attributes = unsafe { GetFileAttributesW(wide_path.as_ptr()) };
+    if attributes == INVALID_FILE_ATTRIBUTES {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(attributes)
+}
+
+fn set_file_attributes(wide_path: &[u16], attributes: u32) -> io::Result<()> {
+    // SAFETY: `wide_path` is NUL-terminated (see `to_wide_path`)
+    if unsafe { SetFileAttributesW(wide_path.as_ptr(), attributes) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
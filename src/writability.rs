@@ -0,0 +1,175 @@
+//! Up-front writability check of a target and its directory
+//!
+//! Replacing a file needs a writable file (a read-only one is refused, as
+//! the user marked it so) and a writable directory (the lock, backup, draft
+//! and the rename all happen there). Without this check an unwritable
+//! target fails only after the backup copy, halfway through the workflow.
+//! The check runs before anything is created:
+//! 1. The file's read-only permission (Unix: no write bit at all; Windows:
+//!    the read-only attribute)
+//! 2. Linux only: the immutable or append-only attribute (`chattr +i`,
+//!    `chattr +a`) on the file or its directory, which even root cannot
+//!    write through
+//! 3. Unix only: `access(W_OK)` on the file and its directory, which
+//!    catches permissions of other users and read-only mounts
+//!
+//! It is a preflight, not a guarantee: permissions can change between the
+//! check and the rename, which still fails cleanly then.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{ByteOpError, NotWritableReason};
+use crate::reporter::report_problem;
+
+/// Fails with `ByteOpError::TargetNotWritable` if `original_file_path`
+/// cannot be replaced, before any backup, draft or lock is created.
+pub(crate) fn check_target_writable(original_file_path: &Path) -> io::Result<()> {
+    if let Some((path, reason)) = find_unwritable(original_file_path)? {
+        let error = ByteOpError::TargetNotWritable { path, reason };
+        report_problem!("ERROR: {}", error);
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// The first path (file or directory) that blocks the replacement, and why.
+fn find_unwritable(original_file_path: &Path) -> io::Result<Option<(PathBuf, NotWritableReason)>> {
+    let directory_path = match original_file_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    if fs::metadata(original_file_path)?.permissions().readonly() {
+        return Ok(Some((
+            original_file_path.to_path_buf(),
+            NotWritableReason::ReadOnlyFile,
+        )));
+    }
+
+    #[cfg(target_os = "linux")]
+    for path in [original_file_path, directory_path] {
+        if has_immutable_attribute(path) {
+            return Ok(Some((path.to_path_buf(), NotWritableReason::Immutable)));
+        }
+    }
+
+    #[cfg(unix)]
+    for (path, denied_reason) in [
+        (original_file_path, NotWritableReason::ReadOnlyFile),
+        (directory_path, NotWritableReason::DirectoryNotWritable),
+    ] {
+        const EROFS: i32 = 30;
+        if let Err(error) = check_write_access(path) {
+            let reason = match error.raw_os_error() {
+                Some(EROFS) => NotWritableReason::ReadOnlyFilesystem,
+                _ if error.kind() == io::ErrorKind::PermissionDenied => denied_reason,
+                _ => return Err(error),
+            };
+            return Ok(Some((path.to_path_buf(), reason)));
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = directory_path;
+    Ok(None)
+}
+
+/// `access(path, W_OK)`: whether this process may write to `path`.
+#[cfg(unix)]
+fn check_write_access(path: &Path) -> io::Result<()> {
+    use std::{
+        ffi::{CString, c_char},
+        os::unix::ffi::OsStrExt,
+    };
+
+    const W_OK: i32 = 2;
+    unsafe extern "C" {
+        fn access(path: *const c_char, mode: i32) -> i32;
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Path contains a NUL byte"))?;
+    // SAFETY: `c_path` is NUL-terminated and outlives the call
+    if unsafe { access(c_path.as_ptr(), W_OK) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Whether `path` has the immutable or append-only inode attribute.
+/// Filesystems without inode attributes report neither.
+#[cfg(target_os = "linux")]
+fn has_immutable_attribute(path: &Path) -> bool {
+    use std::{
+        ffi::{c_long, c_ulong},
+        fs::File,
+        os::fd::AsRawFd,
+    };
+
+    // _IOR('f', 1, long)
+    const FS_IOC_GETFLAGS: c_ulong =
+        0x8000_6601 | ((std::mem::size_of::<c_long>() as c_ulong) << 16);
+    const FS_IMMUTABLE_FL: i32 = 0x10;
+    const FS_APPEND_FL: i32 = 0x20;
+    unsafe extern "C" {
+        fn ioctl(fd: i32, request: c_ulong, ...) -> i32;
+    }
+
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let mut flags: i32 = 0;
+    // SAFETY: the kernel writes one int of flags through the pointer; the
+    // descriptor stays open for the call
+    if unsafe { ioctl(file.as_raw_fd(), FS_IOC_GETFLAGS, &mut flags as *mut i32) } != 0 {
+        return false;
+    }
+    flags & (FS_IMMUTABLE_FL | FS_APPEND_FL) != 0
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_target_refused_before_backup() {
+        let directory = std::env::temp_dir().join("test_read_only_target");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("create directory");
+        let target_path = directory.join("data.bin");
+        fs::write(&target_path, b"ABCD").expect("write target");
+        let mut permissions = fs::metadata(&target_path).expect("metadata").permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&target_path, permissions).expect("make read-only");
+
+        let error = crate::replace_single_byte_in_file(target_path.clone(), 0, b'X')
+            .expect_err("read-only target");
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            ByteOpError::from_io_error(&error),
+            Some(ByteOpError::TargetNotWritable {
+                reason: NotWritableReason::ReadOnlyFile,
+                ..
+            })
+        ));
+        // Refused up front: no backup, draft or lock was created
+        let entries: Vec<_> = fs::read_dir(&directory).expect("list").collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(fs::read(&target_path).expect("read target"), b"ABCD");
+
+        let mut permissions = fs::metadata(&target_path).expect("metadata").permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&target_path, permissions).expect("make writable");
+        check_target_writable(&target_path).expect("writable again");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,531 @@
+//! Editing bytes of a member inside a ZIP archive, in place
+//!
+//! Extract/edit/re-zip cycles rewrite every header (timestamps, ordering,
+//! compression choices) and break reproducible builds. This module instead
+//! treats the archive as a plain file: it locates the member's data, applies
+//! the edits to that data, and fixes up the CRC-32 in both the local file
+//! header and the central-directory entry — all as replacements in ONE draft
+//! of the archive, verified and renamed atomically like any other operation.
+//!
+//! # Current Scope
+//! - Stored (method 0, uncompressed) members only
+//! - Same-length edits: member sizes and all later offsets stay unchanged
+//! - No ZIP64, no multi-disk archives, no data descriptors (flag bit 3)
+//! - No encrypted members (flag bits 0 and 6): their data is ciphertext
+//!
+//! Anything outside this scope returns an error rather than guessing.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::draft_workflow::{run_draft_workflow, validate_target_file};
+use crate::error::{ByteOpError, VerificationPhase};
+use crate::hash::crc32::Crc32;
+use crate::operation_config::{OperationConfig, active_buffer_size, with_operation_config};
+use crate::range_operations::{
+    build_multi_replacement_draft, validate_range_replacements, verify_multi_replacement,
+};
+use crate::util::chunk_length;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x0403_4B50;
+const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0201_4B50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4B50;
+
+/// Fixed part of the end-of-central-directory record
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+/// EOCD may be followed by a comment of up to 65535 bytes
+const MAX_END_OF_CENTRAL_DIRECTORY_SEARCH: usize = END_OF_CENTRAL_DIRECTORY_SIZE + 65_535;
+/// Fixed part of a central-directory entry
+const CENTRAL_DIRECTORY_ENTRY_SIZE: usize = 46;
+/// Fixed part of a local file header
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+
+/// Offset of the CRC-32 field within a local file header
+const LOCAL_HEADER_CRC_FIELD_OFFSET: usize = 14;
+/// Offset of the CRC-32 field within a central-directory entry
+const CENTRAL_DIRECTORY_CRC_FIELD_OFFSET: usize = 16;
+
+/// Compression method "stored"
+const COMPRESSION_METHOD_STORED: u16 = 0;
+/// General-purpose flag: sizes/CRC follow the data in a data descriptor
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+/// General-purpose flag: member data is encrypted
+const FLAG_ENCRYPTED: u16 = 0x0001;
+/// General-purpose flag: member data uses strong encryption
+const FLAG_STRONG_ENCRYPTION: u16 = 0x0040;
+
+/// Where a member lives inside the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipMemberLocation {
+    pub member_name: String,
+    pub compression_method: u16,
+    /// Central-directory flags OR-ed with the local header's, so a flag set
+    /// in either header is seen
+    pub general_purpose_flags: u16,
+    pub stored_crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub local_header_offset: u64,
+    pub central_directory_entry_offset: u64,
+    /// Absolute offset of the member's first data byte
+    pub data_offset: u64,
+}
+
+/// Locates a member by exact name via the central directory.
+///
+/// # Returns
+/// - `Ok(ZipMemberLocation)` if found
+/// - `Err(io::Error)`: `NotFound` if no such member, `InvalidData` if the
+///   archive structure is malformed or unsupported (ZIP64, multi-disk)
+pub fn locate_zip_member(archive_path: &Path, member_name: &str) -> io::Result<ZipMemberLocation> {
+    let archive_size = validate_target_file(archive_path)?;
+    let mut archive_file = File::open(archive_path)?;
+
+    // =========================================
+    // End of Central Directory
+    // =========================================
+    let search_length = chunk_length(MAX_END_OF_CENTRAL_DIRECTORY_SEARCH, archive_size);
+    if search_length < END_OF_CENTRAL_DIRECTORY_SIZE {
+        return Err(invalid_zip("file too small to be a ZIP archive"));
+    }
+    let search_start = archive_size - search_length as u64;
+    let tail_bytes = read_exact_at(&mut archive_file, search_start, search_length)?;
+
+    let eocd_relative_offset = (0..=search_length - END_OF_CENTRAL_DIRECTORY_SIZE)
+        .rev()
+        .find(|&index| read_u32_le(&tail_bytes, index) == END_OF_CENTRAL_DIRECTORY_SIGNATURE)
+        .ok_or_else(|| invalid_zip("end-of-central-directory record not found"))?;
+    let eocd = &tail_bytes[eocd_relative_offset..];
+
+    let disk_number = read_u16_le(eocd, 4);
+    let central_directory_disk = read_u16_le(eocd, 6);
+    let total_entries = read_u16_le(eocd, 10) as usize;
+    let central_directory_size = read_u32_le(eocd, 12) as usize;
+    let central_directory_offset = read_u32_le(eocd, 16) as u64;
+
+    if disk_number != 0 || central_directory_disk != 0 {
+        return Err(invalid_zip("multi-disk archives are not supported"));
+    }
+    if total_entries == 0xFFFF
+        || central_directory_size == 0xFFFF_FFFF
+        || central_directory_offset == 0xFFFF_FFFF
+    {
+        return Err(invalid_zip("ZIP64 archives are not supported"));
+    }
+    if central_directory_offset + central_directory_size as u64 > archive_size {
+        return Err(invalid_zip("central directory extends past end of file"));
+    }
+
+    // =========================================
+    // Central Directory Scan
+    // =========================================
+    let central_directory = read_exact_at(
+        &mut archive_file,
+        central_directory_offset,
+        central_directory_size,
+    )?;
+
+    let mut entry_offset: usize = 0;
+    for _entry_index in 0..total_entries {
+        if entry_offset + CENTRAL_DIRECTORY_ENTRY_SIZE > central_directory.len()
+            || read_u32_le(&central_directory, entry_offset) != CENTRAL_DIRECTORY_SIGNATURE
+        {
+            return Err(invalid_zip("malformed central directory entry"));
+        }
+        let entry = &central_directory[entry_offset..];
+        let name_length = read_u16_le(entry, 28) as usize;
+        let extra_length = read_u16_le(entry, 30) as usize;
+        let comment_length = read_u16_le(entry, 32) as usize;
+        let entry_total_length =
+            CENTRAL_DIRECTORY_ENTRY_SIZE + name_length + extra_length + comment_length;
+        if entry_offset + entry_total_length > central_directory.len() {
+            return Err(invalid_zip(
+                "central directory entry extends past directory",
+            ));
+        }
+
+        let entry_name =
+            &entry[CENTRAL_DIRECTORY_ENTRY_SIZE..CENTRAL_DIRECTORY_ENTRY_SIZE + name_length];
+        if entry_name == member_name.as_bytes() {
+            let local_header_offset = read_u32_le(entry, 42) as u64;
+            let compressed_size = read_u32_le(entry, 20) as u64;
+            let local_header = read_exact_at(
+                &mut archive_file,
+                local_header_offset,
+                LOCAL_FILE_HEADER_SIZE,
+            )?;
+            if read_u32_le(&local_header, 0) != LOCAL_FILE_HEADER_SIGNATURE {
+                return Err(invalid_zip("local file header signature mismatch"));
+            }
+            let data_offset = local_header_offset
+                + LOCAL_FILE_HEADER_SIZE as u64
+                + read_u16_le(&local_header, 26) as u64
+                + read_u16_le(&local_header, 28) as u64;
+            if data_offset + compressed_size > archive_size {
+                return Err(invalid_zip("member data extends past end of file"));
+            }
+
+            return Ok(ZipMemberLocation {
+                member_name: member_name.to_string(),
+                compression_method: read_u16_le(entry, 10),
+                general_purpose_flags: read_u16_le(entry, 8) | read_u16_le(&local_header, 6),
+                stored_crc32: read_u32_le(entry, 16),
+                compressed_size,
+                uncompressed_size: read_u32_le(entry, 24) as u64,
+                local_header_offset,
+                central_directory_entry_offset: central_directory_offset + entry_offset as u64,
+                data_offset,
+            });
+        }
+
+        entry_offset += entry_total_length;
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No member named `{}` in archive", member_name),
+    ))
+}
+
+/// Applies same-length edits to a stored member and fixes up its CRCs.
+///
+/// # Overview
+/// 1. Locate the member and check it is stored, unencrypted, and without a
+///    data descriptor
+/// 2. Compute the member's new CRC-32 by streaming its data with the edits
+///    overlaid (no extraction, no full load)
+/// 3. Build ONE draft of the archive containing: the edited data ranges,
+///    the new CRC in the local header, and the new CRC in the central
+///    directory entry
+/// 4. Verify every untouched byte, every replaced range, and re-check the
+///    member's CRC in the draft against the value written
+/// 5. Atomic rename, as for all operations
+///
+/// # Parameters
+/// - `archive_path`: The ZIP archive
+/// - `member_name`: Exact member path as stored in the archive
+/// - `member_edits`: `(offset_within_member, new_bytes)` pairs
+///
+/// # Returns
+/// - `Ok(new_crc32)` on success
+/// - `Err(io::Error)` if the member is missing, compressed, encrypted, uses a
+///   data descriptor, or an edit is out of the member's bounds or overlaps another
+pub fn edit_stored_zip_member(
+    archive_path: PathBuf,
+    member_name: &str,
+    member_edits: &[(u64, Vec<u8>)],
+) -> io::Result<u32> {
+    // =========================================
+    // Member Resolution Phase
+    // =========================================
+    let member = locate_zip_member(&archive_path, member_name)?;
+
+    if member.compression_method != COMPRESSION_METHOD_STORED {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Member `{}` is compressed (method {}); only stored members can be edited",
+                member_name, member.compression_method
+            ),
+        ));
+    }
+    if member.general_purpose_flags & (FLAG_ENCRYPTED | FLAG_STRONG_ENCRYPTION) != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Member `{}` is encrypted; only unencrypted members can be edited",
+                member_name
+            ),
+        ));
+    }
+    if member.general_purpose_flags & FLAG_DATA_DESCRIPTOR != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Member `{}` uses a data descriptor", member_name),
+        ));
+    }
+
+    // Member-relative edits must stay inside the member's data
+    let member_relative_edits = validate_range_replacements(member_edits, member.compressed_size)?;
+
+    // =========================================
+    // CRC Fix-up Phase
+    // =========================================
+    let new_crc32 =
+        compute_member_crc32_with_edits(&archive_path, &member, &member_relative_edits)?;
+
+    let mut archive_replacements: Vec<(u64, Vec<u8>)> = member_relative_edits
+        .iter()
+        .map(|(offset, new_bytes)| (member.data_offset + offset, new_bytes.clone()))
+        .collect();
+    archive_replacements.push((
+        member.local_header_offset + LOCAL_HEADER_CRC_FIELD_OFFSET as u64,
+        new_crc32.to_le_bytes().to_vec(),
+    ));
+    archive_replacements.push((
+        member.central_directory_entry_offset + CENTRAL_DIRECTORY_CRC_FIELD_OFFSET as u64,
+        new_crc32.to_le_bytes().to_vec(),
+    ));
+
+    let archive_size = validate_target_file(&archive_path)?;
+    let sorted_replacements = validate_range_replacements(&archive_replacements, archive_size)?;
+
+    // =========================================
+    // Draft Build + Verification Phases
+    // =========================================
+    run_draft_workflow(
+        &archive_path,
+        "ZIP Member Edit",
+        archive_size,
+        |source_file, draft_file| {
+            build_multi_replacement_draft(source_file, draft_file, &sorted_replacements)
+        },
+        |original_path, draft_path| {
+            verify_multi_replacement(
+                original_path,
+                draft_path,
+                &sorted_replacements,
+                archive_size,
+            )?;
+
+            // The draft's member data must hash to the CRC now recorded
+            let draft_crc32 = compute_member_crc32_with_edits(draft_path, &member, &[])?;
+            if draft_crc32 != new_crc32 {
+                return Err(ByteOpError::verification_failed(
+                    VerificationPhase::ContentDigest,
+                    member.data_offset,
+                    u64::from(new_crc32),
+                    u64::from(draft_crc32),
+                )
+                .into());
+            }
+            Ok(())
+        },
+    )?;
+
+    Ok(new_crc32)
+}
+
+/// Like `edit_stored_zip_member`, using `config` instead of the active (or
+/// default) `OperationConfig`.
+pub fn edit_stored_zip_member_with_config(
+    archive_path: PathBuf,
+    member_name: &str,
+    member_edits: &[(u64, Vec<u8>)],
+    config: &OperationConfig,
+) -> io::Result<u32> {
+    with_operation_config(config, || {
+        edit_stored_zip_member(archive_path, member_name, member_edits)
+    })
+}
+
+/// Streams a member's data, overlays sorted member-relative edits, and
+/// returns the CRC-32 of the result.
+fn compute_member_crc32_with_edits(
+    archive_path: &Path,
+    member: &ZipMemberLocation,
+    sorted_member_edits: &[(u64, Vec<u8>)],
+) -> io::Result<u32> {
+    let mut archive_file = File::open(archive_path)?;
+    archive_file.seek(SeekFrom::Start(member.data_offset))?;
+
+    let mut checksum = Crc32::new();
+    let mut bucket_brigade_buffer = vec![0u8; active_buffer_size()];
+    let mut member_position: u64 = 0;
+
+    while member_position < member.compressed_size {
+        let bytes_this_chunk = chunk_length(
+            bucket_brigade_buffer.len(),
+            member.compressed_size - member_position,
+        );
+        let chunk = &mut bucket_brigade_buffer[..bytes_this_chunk];
+        archive_file.read_exact(chunk)?;
+
+        // Overlay any edit bytes that fall inside this chunk; every
+        // difference below is bounded by the chunk or edit length
+        let chunk_end = member_position + bytes_this_chunk as u64;
+        for (edit_offset, new_bytes) in sorted_member_edits {
+            let edit_end = edit_offset + new_bytes.len() as u64;
+            let overlap_start = (*edit_offset).max(member_position);
+            let overlap_end = edit_end.min(chunk_end);
+            if overlap_start < overlap_end {
+                let chunk_range = (overlap_start - member_position) as usize
+                    ..(overlap_end - member_position) as usize;
+                let edit_range =
+                    (overlap_start - edit_offset) as usize..(overlap_end - edit_offset) as usize;
+                chunk[chunk_range].copy_from_slice(&new_bytes[edit_range]);
+            }
+        }
+
+        checksum.update(chunk);
+        member_position = chunk_end;
+    }
+
+    Ok(checksum.finalize())
+}
+
+/// Reads exactly `length` bytes at `offset`.
+fn read_exact_at(file: &mut File, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0u8; length];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn invalid_zip(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("ZIP archive: {}", reason),
+    )
+}
+
+// =========================================
+// Test Module
+// =========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::crc32::crc32_of_bytes;
+
+    /// Builds a minimal single-member stored ZIP archive.
+    fn build_stored_zip(member_name: &str, member_data: &[u8]) -> Vec<u8> {
+        let crc_value = crc32_of_bytes(member_data);
+        let data_length = member_data.len() as u32;
+        let name_length = member_name.len() as u16;
+        let mut archive = Vec::new();
+
+        // Local file header
+        archive.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        archive.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        archive.extend_from_slice(&crc_value.to_le_bytes());
+        archive.extend_from_slice(&data_length.to_le_bytes());
+        archive.extend_from_slice(&data_length.to_le_bytes());
+        archive.extend_from_slice(&name_length.to_le_bytes());
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive.extend_from_slice(member_name.as_bytes());
+        archive.extend_from_slice(member_data);
+
+        // Central directory
+        let central_directory_offset = archive.len() as u32;
+        archive.extend_from_slice(&CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        archive.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        archive.extend_from_slice(&crc_value.to_le_bytes());
+        archive.extend_from_slice(&data_length.to_le_bytes());
+        archive.extend_from_slice(&data_length.to_le_bytes());
+        archive.extend_from_slice(&name_length.to_le_bytes());
+        archive.extend_from_slice(&[0; 12]);
+        archive.extend_from_slice(&0u32.to_le_bytes());
+        archive.extend_from_slice(member_name.as_bytes());
+        let central_directory_size = archive.len() as u32 - central_directory_offset;
+
+        // End of central directory
+        archive.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        archive.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+        archive.extend_from_slice(&central_directory_size.to_le_bytes());
+        archive.extend_from_slice(&central_directory_offset.to_le_bytes());
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive
+    }
+
+    #[test]
+    fn test_edit_stored_zip_member_fixes_crcs() {
+        let test_file = std::env::temp_dir().join("test_zip_member_edit.zip");
+        let member_data: Vec<u8> = (0..=255u8).cycle().take(150).collect();
+        std::fs::write(
+            &test_file,
+            build_stored_zip("config/serial.bin", &member_data),
+        )
+        .expect("create test archive");
+
+        let new_crc32 = edit_stored_zip_member(
+            test_file.clone(),
+            "config/serial.bin",
+            &[(100, vec![0xEE, 0xEE]), (3, vec![0x00])],
+        )
+        .expect("edit should succeed");
+
+        let mut expected_member = member_data.clone();
+        expected_member[3] = 0x00;
+        expected_member[100] = 0xEE;
+        expected_member[101] = 0xEE;
+        assert_eq!(new_crc32, crc32_of_bytes(&expected_member));
+
+        // Byte-identical to a freshly built archive of the edited member
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read archive"),
+            build_stored_zip("config/serial.bin", &expected_member)
+        );
+
+        let member = locate_zip_member(&test_file, "config/serial.bin").expect("member found");
+        assert_eq!(member.stored_crc32, new_crc32);
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_edit_zip_member_rejects_bad_requests() {
+        let test_file = std::env::temp_dir().join("test_zip_member_reject.zip");
+        let archive_bytes = build_stored_zip("a.txt", b"hello");
+        std::fs::write(&test_file, &archive_bytes).expect("create test archive");
+
+        assert_eq!(
+            edit_stored_zip_member(test_file.clone(), "missing.txt", &[(0, vec![0x41])])
+                .expect_err("missing member")
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+        // Edit past the member's end
+        assert!(
+            edit_stored_zip_member(test_file.clone(), "a.txt", &[(4, vec![0x41, 0x41])]).is_err()
+        );
+        assert_eq!(
+            std::fs::read(&test_file).expect("Failed to read archive"),
+            archive_bytes
+        );
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[test]
+    fn test_edit_zip_member_rejects_encrypted_members() {
+        let test_file = std::env::temp_dir().join("test_zip_member_encrypted.zip");
+
+        for encryption_flag in [FLAG_ENCRYPTED, FLAG_STRONG_ENCRYPTION] {
+            let mut archive_bytes = build_stored_zip("a.txt", b"hello");
+            let central_directory_offset = LOCAL_FILE_HEADER_SIZE + "a.txt".len() + b"hello".len();
+            // Set the flag in both headers, as a real encrypting writer would
+            archive_bytes[6..8].copy_from_slice(&encryption_flag.to_le_bytes());
+            archive_bytes[central_directory_offset + 8..central_directory_offset + 10]
+                .copy_from_slice(&encryption_flag.to_le_bytes());
+            std::fs::write(&test_file, &archive_bytes).expect("create test archive");
+
+            let edit_error = edit_stored_zip_member(test_file.clone(), "a.txt", &[(0, vec![0x41])])
+                .expect_err("encrypted member must be refused");
+            assert_eq!(edit_error.kind(), io::ErrorKind::Unsupported);
+            assert!(edit_error.to_string().contains("encrypted"));
+            assert_eq!(
+                std::fs::read(&test_file).expect("Failed to read archive"),
+                archive_bytes
+            );
+        }
+
+        let _ = std::fs::remove_file(&test_file);
+    }
+}